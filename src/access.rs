@@ -0,0 +1,79 @@
+use std::{env, path::PathBuf};
+
+use serde::Deserialize;
+
+/// Access to the HTTP server of the status, the web dashboard, the REST API and the metrics,
+/// once exposed beyond localhost
+///
+/// ```toml
+/// [http]
+/// token_env = "CFD_STATUS_TOKEN"
+/// control_token_env = "CFD_STATUS_CONTROL_TOKEN"
+/// tls = { certificate = "/etc/cfd-status/cert.pem", private_key = "/etc/cfd-status/key.pem" }
+/// ```
+///
+/// The clients send the token as `Authorization: Bearer <token>`, e.g. the `bearer_token`
+/// of a Prometheus scrape configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HttpConfig {
+    /// Bearer token of every request but `GET /healthz`, all the requests being served without
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Environment variable the bearer token is read from, instead of the configuration file
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Bearer token of the requests changing the cases, `POST /cases`, `DELETE /cases/{name}`,
+    /// `POST /refresh` and `POST /ctl/*`, instead of `token`, e.g. so a metrics scraper
+    /// cannot change them
+    #[serde(default)]
+    pub control_token: Option<String>,
+    /// Environment variable the control token is read from, instead of the configuration file
+    #[serde(default)]
+    pub control_token_env: Option<String>,
+    /// Certificate and private key the server terminates TLS with, requires the `tls` feature
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+/// PEM files of the TLS certificate chain and of its private key
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub certificate: PathBuf,
+    pub private_key: PathBuf,
+}
+
+impl HttpConfig {
+    /// Returns the bearer token, from the environment variable if set,
+    /// `None` if the requests are not authenticated
+    pub fn token(&self) -> Option<String> {
+        secret(&self.token_env, &self.token)
+    }
+    /// Returns the bearer token of the requests changing the cases, the token if none
+    pub fn control_token(&self) -> Option<String> {
+        secret(&self.control_token_env, &self.control_token).or_else(|| self.token())
+    }
+}
+
+/// Returns the value of the environment variable `env` if set, `value` otherwise,
+/// an empty value being none
+fn secret(env: &Option<String>, value: &Option<String>) -> Option<String> {
+    env.as_ref()
+        .and_then(|name| env::var(name).ok())
+        .or_else(|| value.clone())
+        .filter(|secret| !secret.is_empty())
+}
+
+/// Returns true if the value of an `Authorization` header is the bearer token,
+/// compared in constant time so the token cannot be guessed from the response times
+pub fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let Some(given) = authorization.and_then(|value| value.trim().strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let (given, token) = (given.trim().as_bytes(), token.as_bytes());
+    given.len() == token.len()
+        && given
+            .iter()
+            .zip(token)
+            .fold(0, |differ, (a, b)| differ | (a ^ b))
+            == 0
+}
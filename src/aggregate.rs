@@ -0,0 +1,291 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "monitor")]
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{Redactor, StatusSnapshot};
+
+/// Timeout of the snapshot pushes
+#[cfg(feature = "monitor")]
+const PUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum AggregateError {
+    #[error("missing cluster name")]
+    NoCluster,
+    #[error("not a status snapshot, expected the snapshot pushed by a monitor")]
+    NotSnapshot,
+}
+
+/// Publication of the snapshots of a monitor to a central instance merging
+/// the monitors of several clusters
+///
+/// ```toml
+/// [aggregation]
+/// cluster = "hpc-east"
+/// push = "http://central:8080/push"
+/// dir = "/shared/cfd-status/clusters"
+/// ```
+///
+/// Each snapshot, redacted as configured, is posted to the `POST /push` endpoint of the central
+/// [StatusServer](crate::StatusServer) and written to `{cluster}.json` in the shared directory,
+/// both merged by `cfd-status aggregate`.
+/// The central server authenticates the pushes with its own `[http] token`, which is then
+/// the `token` of the monitors, a push without it being refused with `401 Unauthorized`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AggregationConfig {
+    /// Name of the cluster the cases of the monitor are tagged with
+    pub cluster: String,
+    /// URL of the central server the snapshots are pushed to
+    pub push: Option<String>,
+    /// Bearer token of the central server, see [HttpConfig](crate::HttpConfig)
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Shared directory the snapshots are written to
+    pub dir: Option<PathBuf>,
+}
+
+/// Publisher of the snapshots of a monitor
+///
+/// The pushes are sent from a separate thread, a push being skipped while the previous one is pending
+#[derive(Debug)]
+pub struct Publisher {
+    config: AggregationConfig,
+    #[cfg(feature = "monitor")]
+    pending: Arc<AtomicBool>,
+}
+
+impl From<AggregationConfig> for Publisher {
+    fn from(config: AggregationConfig) -> Self {
+        Self {
+            config,
+            #[cfg(feature = "monitor")]
+            pending: Default::default(),
+        }
+    }
+}
+
+impl Publisher {
+    /// Publishes the snapshot, redacted for sharing, tagged with the cluster
+    pub fn publish(&self, snapshot: &StatusSnapshot, redactor: &Redactor) {
+        let Some(status) = serde_json::from_str::<Value>(&snapshot.to_json(redactor)).ok() else {
+            return;
+        };
+        let body = json!({ "cluster": self.config.cluster, "status": status }).to_string();
+        if let Some(dir) = &self.config.dir {
+            let path = dir.join(format!("{}.json", self.config.cluster));
+            let tmp = path.with_extension("tmp");
+            if let Err(e) = fs::write(&tmp, &body).and_then(|_| fs::rename(tmp, &path)) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to write the snapshot");
+            }
+        }
+        if let Some(url) = &self.config.push {
+            self.push(url.clone(), body);
+        }
+    }
+    #[cfg(feature = "monitor")]
+    fn push(&self, url: String, body: String) {
+        if self.pending.swap(true, Ordering::Relaxed) {
+            tracing::debug!(url, "previous snapshot push pending, skipped");
+            return;
+        }
+        let pending = self.pending.clone();
+        let token = self.config.token.clone();
+        thread::spawn(move || {
+            let mut request = ureq::post(&url)
+                .timeout(PUSH_TIMEOUT)
+                .set("Content-Type", "application/json");
+            if let Some(token) = token {
+                request = request.set("Authorization", &format!("Bearer {token}"));
+            }
+            if let Err(e) = request.send_string(&body) {
+                tracing::warn!(url, error = %e, "snapshot push failed");
+            }
+            pending.store(false, Ordering::Relaxed);
+        });
+    }
+    #[cfg(not(feature = "monitor"))]
+    fn push(&self, url: String, _body: String) {
+        tracing::warn!(url, "snapshot pushes require the `monitor` feature");
+    }
+}
+
+/// Latest snapshot of a cluster
+#[derive(Debug, Clone)]
+struct ClusterStatus {
+    taken: DateTime<Local>,
+    cases: Vec<Value>,
+}
+
+/// Merged status of the monitors of several clusters, from the snapshots they published
+#[derive(Debug, Clone, Default)]
+pub struct Aggregate {
+    clusters: BTreeMap<String, ClusterStatus>,
+    stale_after: Option<Duration>,
+}
+
+impl Aggregate {
+    /// Creates an empty aggregate
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Flags the clusters without a snapshot for the given duration
+    pub fn stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = Some(stale_after);
+        self
+    }
+    /// Adds a published snapshot, `{"cluster": ..., "status": ...}`, returning its cluster
+    ///
+    /// A snapshot older than the one of its cluster already there is ignored
+    pub fn insert(&mut self, published: &Value) -> Result<String, AggregateError> {
+        let cluster = published["cluster"]
+            .as_str()
+            .filter(|cluster| !cluster.is_empty())
+            .ok_or(AggregateError::NoCluster)?;
+        let status = &published["status"];
+        let taken: DateTime<Local> = status
+            .get("taken")
+            .and_then(|taken| serde_json::from_value(taken.clone()).ok())
+            .ok_or(AggregateError::NotSnapshot)?;
+        let cases = status["cases"]
+            .as_array()
+            .ok_or(AggregateError::NotSnapshot)?
+            .clone();
+        if self
+            .clusters
+            .get(cluster)
+            .is_none_or(|latest| latest.taken <= taken)
+        {
+            self.clusters
+                .insert(cluster.to_string(), ClusterStatus { taken, cases });
+        }
+        Ok(cluster.to_string())
+    }
+    /// Adds the snapshots written to a shared directory, skipping the invalid ones
+    pub fn read_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let published = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    serde_json::from_str::<Value>(&content).map_err(|e| e.to_string())
+                })
+                .and_then(|published| self.insert(&published).map_err(|e| e.to_string()));
+            if let Err(e) = published {
+                tracing::warn!(path = %path.display(), error = e, "invalid cluster snapshot");
+            }
+        }
+        Ok(())
+    }
+    /// Returns the names of the clusters, sorted
+    pub fn clusters(&self) -> impl Iterator<Item = &str> {
+        self.clusters.keys().map(String::as_str)
+    }
+    /// Returns true if there is no snapshot yet
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+    /// Returns true if the snapshot of the cluster is older than the stale duration
+    pub fn is_stale(&self, cluster: &str) -> bool {
+        self.clusters.get(cluster).is_some_and(|status| {
+            self.stale_after
+                .is_some_and(|stale_after| Local::now() - status.taken > stale_after)
+        })
+    }
+    /// Returns the merged status as JSON, the clusters and their cases,
+    /// each case tagged with its cluster
+    pub fn to_json(&self) -> String {
+        let clusters: Vec<_> = self
+            .clusters
+            .iter()
+            .map(|(cluster, status)| {
+                json!({
+                    "cluster": cluster,
+                    "taken": status.taken,
+                    "cases": status.cases.len(),
+                    "stale": self.is_stale(cluster),
+                })
+            })
+            .collect();
+        let cases: Vec<_> = self
+            .clusters
+            .iter()
+            .flat_map(|(cluster, status)| {
+                status.cases.iter().cloned().map(move |mut case| {
+                    if let Some(case) = case.as_object_mut() {
+                        case.insert("cluster".to_string(), json!(cluster));
+                    }
+                    case
+                })
+            })
+            .collect();
+        json!({ "clusters": clusters, "cases": cases }).to_string()
+    }
+}
+
+impl Display for Aggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (cluster, status) in &self.clusters {
+            writeln!(
+                f,
+                "{cluster}: {} cases, taken {}{}",
+                status.cases.len(),
+                status.taken.format("%Y-%m-%d %H:%M"),
+                if self.is_stale(cluster) {
+                    " (stale)"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<14}{:<20}{:<12}{:>6}{:>18}",
+            "Cluster", "Case", "State", "%", "ETA"
+        )?;
+        let time = |case: &Value, key: &str| {
+            serde_json::from_value::<Option<DateTime<Local>>>(case.get(key)?.clone())
+                .ok()
+                .flatten()
+        };
+        for (cluster, status) in &self.clusters {
+            for case in &status.cases {
+                let eta = time(case, "completed")
+                    .or_else(|| time(case, "eta"))
+                    .map_or_else(
+                        || "-".to_string(),
+                        |eta| eta.format("%Y-%m-%d %H:%M").to_string(),
+                    );
+                writeln!(
+                    f,
+                    "{:<14}{:<20}{:<12}{:>6}{:>18}",
+                    cluster,
+                    case["name"].as_str().unwrap_or("-"),
+                    case["state"].as_str().unwrap_or("-"),
+                    case["percent"]
+                        .as_i64()
+                        .map_or_else(|| "-".to_string(), |percent| percent.to_string()),
+                    eta
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
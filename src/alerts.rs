@@ -0,0 +1,258 @@
+use std::{collections::HashMap, fmt::Display};
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+
+use crate::{active_window, Case, MaintenanceWindow};
+
+/// Alert severity, from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    /// Returns the weight of an alert of this severity in the campaign health index
+    pub fn weight(&self) -> f64 {
+        match self {
+            Severity::Info => 0.25,
+            Severity::Warning => 0.5,
+            Severity::Critical => 1.,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "INFO"),
+            Severity::Warning => write!(f, "WARNING"),
+            Severity::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+/// Conditions raising an alert, named in snake case in the configuration
+///
+/// ```toml
+/// [severity]
+/// stalled = "warning"
+/// coarse_sampling = "info"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// No new time step for longer than the stall threshold
+    Stalled,
+    /// Stalled case whose solver process is not running anymore
+    SolverDead,
+    /// Latest time step duration well above the mean
+    Slowing,
+    /// Case directory too large or filesystem almost full
+    DiskSpace,
+    /// Solver version different from other cases of the group
+    VersionMismatch,
+    /// Time steps longer than the update interval, the interval should be raised
+    CoarseSampling,
+    /// Merged logs of a case at time steps too far apart
+    LogSpread,
+    /// Mean time step duration drifting from the expected one
+    StepTimeBudget,
+    /// Fatal solver error found in the log
+    LogError,
+    /// Inner iterations per time step climbing well above the ones of the first time steps
+    IterationsClimbing,
+}
+
+impl AlertKind {
+    /// Returns true if the alert is expected during a maintenance window
+    pub fn is_suppressible(&self) -> bool {
+        matches!(self, AlertKind::Stalled | AlertKind::SolverDead)
+    }
+    /// Returns the severity of the alert unless configured otherwise
+    pub fn severity(&self) -> Severity {
+        match self {
+            AlertKind::Stalled
+            | AlertKind::SolverDead
+            | AlertKind::DiskSpace
+            | AlertKind::LogError => Severity::Critical,
+            AlertKind::Slowing
+            | AlertKind::VersionMismatch
+            | AlertKind::CoarseSampling
+            | AlertKind::LogSpread
+            | AlertKind::StepTimeBudget
+            | AlertKind::IterationsClimbing => Severity::Warning,
+        }
+    }
+}
+
+impl Display for AlertKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertKind::Stalled => write!(f, "stalled"),
+            AlertKind::SolverDead => write!(f, "solver dead"),
+            AlertKind::Slowing => write!(f, "slowing down"),
+            AlertKind::DiskSpace => write!(f, "disk space"),
+            AlertKind::VersionMismatch => write!(f, "solver version"),
+            AlertKind::CoarseSampling => write!(f, "step > interval"),
+            AlertKind::LogSpread => write!(f, "logs disagree"),
+            AlertKind::StepTimeBudget => write!(f, "off step budget"),
+            AlertKind::LogError => write!(f, "solver error"),
+            AlertKind::IterationsClimbing => write!(f, "iterations up"),
+        }
+    }
+}
+
+/// Active alert of a case
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub case: String,
+    pub kind: AlertKind,
+    pub severity: Severity,
+    pub since: DateTime<Local>,
+    /// Name of the maintenance window the alert is expected in
+    pub expected: Option<String>,
+    /// Set once an operator acknowledged the alerts of the case, until its state changes
+    pub acknowledged: bool,
+}
+
+impl Alert {
+    /// Returns the alert severity, as configured for the case
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+}
+
+impl Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let age = (Local::now() - self.since).num_seconds().max(0);
+        let severity = match &self.expected {
+            Some(_) => "EXPECTED".to_string(),
+            None if self.acknowledged => "ACKED".to_string(),
+            None => self.severity().to_string(),
+        };
+        write!(
+            f,
+            "{:<10}{:<20}{:<16}{:>10}",
+            severity,
+            self.case,
+            self.kind,
+            crate::format_hours_minutes(age)
+        )?;
+        if let Some(window) = &self.expected {
+            write!(f, "  {window}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Tracker of the active alerts of all the cases
+#[derive(Debug, Default)]
+pub struct Alerts {
+    active: HashMap<(String, AlertKind), (DateTime<Local>, Severity)>,
+    /// State of the cases at the latest update
+    states: HashMap<String, &'static str>,
+    /// Cases acknowledged with their state at the time
+    acknowledged: HashMap<String, &'static str>,
+    maintenance: Vec<MaintenanceWindow>,
+    expected: Option<String>,
+}
+
+impl Alerts {
+    /// Creates a new alert tracker
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Sets the maintenance windows during which suppressible alerts are expected
+    pub fn maintenance(mut self, windows: Vec<MaintenanceWindow>) -> Self {
+        self.maintenance = windows;
+        self
+    }
+    /// Updates the active alerts from the state of the cases
+    ///
+    /// Alerts that are still active keep the time they were first raised
+    pub fn update(&mut self, cases: &[Case]) -> &mut Self {
+        self.update_at(cases, Local::now())
+    }
+    /// Updates the active alerts from the state of the cases at `now`, as [Alerts::update] does
+    pub fn update_at(&mut self, cases: &[Case], now: DateTime<Local>) -> &mut Self {
+        self.expected = active_window(&self.maintenance, now).map(|window| window.to_string());
+        let mut active = HashMap::new();
+        for case in cases {
+            let kinds = [
+                (
+                    AlertKind::Stalled,
+                    case.is_stalled() && !case.is_solver_dead(),
+                ),
+                (AlertKind::SolverDead, case.is_solver_dead()),
+                (AlertKind::Slowing, case.is_slowing()),
+                (AlertKind::DiskSpace, case.is_disk_at_risk()),
+                (AlertKind::VersionMismatch, case.has_version_mismatch()),
+                (AlertKind::CoarseSampling, case.is_sampled_coarsely()),
+                (AlertKind::LogSpread, case.log_spread().is_some()),
+                (AlertKind::StepTimeBudget, case.is_off_step_budget()),
+                (AlertKind::LogError, case.log_failure().is_some()),
+                (AlertKind::IterationsClimbing, case.is_iterating_more()),
+            ];
+            for (kind, raised) in kinds {
+                if raised {
+                    let key = (case.name().to_string(), kind);
+                    let since = self.active.get(&key).map_or(now, |(since, _)| *since);
+                    active.insert(key, (since, case.alert_severity(kind)));
+                }
+            }
+        }
+        self.active = active;
+        self.states = cases
+            .iter()
+            .map(|case| (case.name().to_string(), case.state()))
+            .collect();
+        // a case whose alerts all cleared or whose state changed is a new incident,
+        // to be acknowledged again
+        self.acknowledged.retain(|name, state| {
+            self.states.get(name) == Some(state) && self.active.keys().any(|(case, _)| case == name)
+        });
+        self
+    }
+    /// Acknowledges the alerts of a case until its state changes or they all clear,
+    /// the alerts raised meanwhile included, returns false if it has no active alert
+    pub fn acknowledge(&mut self, case: &str) -> bool {
+        let Some(state) = self
+            .states
+            .get(case)
+            .filter(|_| self.active.keys().any(|(name, _)| name == case))
+        else {
+            return false;
+        };
+        self.acknowledged.insert(case.to_string(), state);
+        true
+    }
+    /// Returns the active alerts, the most severe and oldest first
+    pub fn sorted(&self) -> Vec<Alert> {
+        let mut alerts: Vec<_> = self
+            .active
+            .iter()
+            .map(|((case, kind), (since, severity))| Alert {
+                case: case.clone(),
+                kind: *kind,
+                severity: *severity,
+                since: *since,
+                expected: self.expected.clone().filter(|_| kind.is_suppressible()),
+                acknowledged: self.acknowledged.contains_key(case),
+            })
+            .collect();
+        alerts.sort_by(|a, b| {
+            a.expected
+                .is_some()
+                .cmp(&b.expected.is_some())
+                .then(a.acknowledged.cmp(&b.acknowledged))
+                .then(b.severity().cmp(&a.severity()))
+                .then(a.since.cmp(&b.since))
+                .then(a.case.cmp(&b.case))
+        });
+        alerts
+    }
+}
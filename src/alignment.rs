@@ -0,0 +1,183 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{format_hours_minutes, format_signed_hours_minutes, Case};
+
+/// Number of points kept in the simulated time line of a case, every other one being dropped
+/// once full
+const TIMELINE_POINTS: usize = 128;
+/// Share of the median wall-clock time to the common simulated time a case is ahead
+/// or behind by before being reported so
+const ALIGNMENT_TOLERANCE: f64 = 0.05;
+
+/// Simulated time of a case against the wall-clock time since its start, from the simulated
+/// time 0 at the start, a simulation restarted at a later time starting over
+#[derive(Debug, Clone, Default)]
+pub struct SimTimeline {
+    /// Simulated time with the wall-clock seconds since the start it was reached at
+    points: Vec<(f64, f64)>,
+}
+
+impl SimTimeline {
+    /// Creates an empty time line
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Adds the simulated time reached `elapsed` seconds after the start
+    pub fn push(&mut self, time: f64, elapsed: f64) -> &mut Self {
+        if self.points.last().is_some_and(|(latest, _)| time < *latest) {
+            self.points.clear();
+        }
+        if self.points.len() == TIMELINE_POINTS {
+            let latest = self.points.pop();
+            let mut i = 0;
+            self.points.retain(|_| {
+                i += 1;
+                i % 2 == 1
+            });
+            self.points.extend(latest);
+        }
+        self.points.push((time, elapsed));
+        self
+    }
+    /// Returns the wall-clock seconds since the start the simulated time was reached at,
+    /// interpolated between the points, `None` if not reached yet
+    pub fn elapsed_at(&self, time: f64) -> Option<f64> {
+        let (mut before_time, mut before_elapsed) = (0., 0.);
+        for &(at, elapsed) in &self.points {
+            if at >= time {
+                return Some(if at > before_time {
+                    before_elapsed
+                        + (elapsed - before_elapsed) * (time - before_time) / (at - before_time)
+                } else {
+                    elapsed
+                });
+            }
+            (before_time, before_elapsed) = (at, elapsed);
+        }
+        None
+    }
+}
+
+/// Case aligned on the common simulated time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlignedCase {
+    pub name: String,
+    pub group: Option<String>,
+    /// Latest simulated time in seconds
+    pub time: f64,
+    /// Wall-clock seconds since the start the common simulated time was reached at
+    pub elapsed: Option<f64>,
+    /// Wall-clock seconds the case reached the common simulated time after the median case at,
+    /// ahead if negative
+    pub lag: Option<f64>,
+}
+
+impl AlignedCase {
+    /// Returns `ahead`, `behind` or `on par` with the median case, `-` if unknown
+    pub fn standing(&self, median: Option<f64>) -> &'static str {
+        match self.lag.zip(median) {
+            Some((lag, median)) if lag < -ALIGNMENT_TOLERANCE * median => "ahead",
+            Some((lag, median)) if lag > ALIGNMENT_TOLERANCE * median => "behind",
+            Some(_) => "on par",
+            None => "-",
+        }
+    }
+}
+
+/// Cases aligned on the latest simulated time they all reached, with the wall-clock time
+/// each took to reach it, e.g. to check that the zenith and azimuth configurations of the same
+/// flow progress consistently
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SimTimeAlignment {
+    /// Simulated time in seconds the cases are aligned on
+    pub time: Option<f64>,
+    /// Median wall-clock seconds to reach the simulated time
+    pub median: Option<f64>,
+    /// Cases, the earliest to reach the simulated time first
+    pub cases: Vec<AlignedCase>,
+}
+
+impl SimTimeAlignment {
+    /// Aligns the cases with a time step on the latest simulated time they all reached
+    pub fn new<'a>(cases: impl IntoIterator<Item = &'a Case>) -> Self {
+        let cases: Vec<_> = cases
+            .into_iter()
+            .filter(|case| case.step().is_some())
+            .collect();
+        let Some(time) = cases.iter().map(|case| case.time()).reduce(f64::min) else {
+            return Default::default();
+        };
+        let mut cases: Vec<_> = cases
+            .into_iter()
+            .map(|case| AlignedCase {
+                name: case.name().to_string(),
+                group: case.group_name().map(str::to_string),
+                time: case.time(),
+                // at the mean throughput since the start until the time line covers the time
+                elapsed: case.sim_timeline().elapsed_at(time).or_else(|| {
+                    let elapsed = case.elapsed_secs()? as f64;
+                    (case.time() > 0. && elapsed > 0.).then(|| elapsed * time / case.time())
+                }),
+                lag: None,
+            })
+            .collect();
+        cases.sort_by(|a, b| {
+            a.elapsed
+                .unwrap_or(f64::INFINITY)
+                .total_cmp(&b.elapsed.unwrap_or(f64::INFINITY))
+                .then(a.name.cmp(&b.name))
+        });
+        let elapsed: Vec<_> = cases.iter().filter_map(|case| case.elapsed).collect();
+        let median = match elapsed.len() {
+            0 => None,
+            n if n % 2 == 0 => Some((elapsed[n / 2 - 1] + elapsed[n / 2]) / 2.),
+            n => Some(elapsed[n / 2]),
+        };
+        for case in &mut cases {
+            case.lag = case
+                .elapsed
+                .zip(median)
+                .map(|(elapsed, median)| elapsed - median);
+        }
+        Self {
+            time: Some(time),
+            median,
+            cases,
+        }
+    }
+    /// Returns true if there is no case to align
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+}
+
+impl Display for SimTimeAlignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(time) = self.time else {
+            return writeln!(f, "no case with a time step to align");
+        };
+        writeln!(f, "Aligned at {time:.2} simulated s")?;
+        writeln!(
+            f,
+            "{:<20}{:>10}{:>12}{:>12}{:>10}",
+            "Case", "P.[s]", "Reached", "vs median", ""
+        )?;
+        let or_dash = |secs: Option<f64>, format: fn(i64) -> String| {
+            secs.map_or_else(|| "-".to_string(), |secs| format(secs.round() as i64))
+        };
+        for case in &self.cases {
+            writeln!(
+                f,
+                "{:<20}{:>10.2}{:>12}{:>12}{:>10}",
+                case.name,
+                case.time,
+                or_dash(case.elapsed, format_hours_minutes),
+                or_dash(case.lag, format_signed_hours_minutes),
+                case.standing(self.median)
+            )?;
+        }
+        Ok(())
+    }
+}
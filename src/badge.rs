@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use crate::{Case, StatusSnapshot};
+
+/// Approximate width of a character of the 11px Verdana font of the badges in pixels
+const CHAR_WIDTH: usize = 7;
+/// Horizontal padding of each side of a badge in pixels
+const PADDING: usize = 10;
+
+/// State of a case or of the campaign shown by the color of a badge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BadgeState {
+    Completed,
+    Pending,
+    Waiting,
+    Paused,
+    Running,
+    Late,
+    Stalled,
+    Failed,
+}
+
+impl BadgeState {
+    /// Returns the state of a case, the most severe one first
+    pub fn of(case: &Case) -> Self {
+        if case.error().is_some() || case.log_failure().is_some() {
+            Self::Failed
+        } else if case.is_solver_dead() || case.is_stalled() {
+            Self::Stalled
+        } else if case.is_late() && !case.is_complete() {
+            Self::Late
+        } else {
+            match case.state() {
+                "completed" => Self::Completed,
+                "paused" => Self::Paused,
+                "pending" => Self::Pending,
+                "waiting" => Self::Waiting,
+                _ => Self::Running,
+            }
+        }
+    }
+    /// Returns the color of the badge
+    pub fn color(&self) -> &'static str {
+        match self {
+            Self::Completed => "#4c1",
+            Self::Pending | Self::Waiting | Self::Paused => "#9f9f9f",
+            Self::Running => "#007ec6",
+            Self::Late => "#dfb317",
+            Self::Stalled => "#fe7d37",
+            Self::Failed => "#e05d44",
+        }
+    }
+}
+
+impl Display for BadgeState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            Self::Completed => "completed",
+            Self::Pending => "pending",
+            Self::Waiting => "waiting",
+            Self::Paused => "paused",
+            Self::Running => "running",
+            Self::Late => "late",
+            Self::Stalled => "stalled",
+            Self::Failed => "failed",
+        };
+        write!(f, "{state}")
+    }
+}
+
+/// Status badge of a case or of the campaign, rendered as a shields-style SVG image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Badge {
+    pub label: String,
+    pub percent: i64,
+    pub state: BadgeState,
+}
+
+impl Badge {
+    /// Creates the badge of a case
+    pub fn case(case: &Case) -> Self {
+        Self {
+            label: case.name().to_string(),
+            percent: case.percent_complete(),
+            state: BadgeState::of(case),
+        }
+    }
+    /// Creates the badge of the campaign, with the mean progress of the cases
+    /// and the most severe state of the running cases
+    pub fn campaign(snapshot: &StatusSnapshot) -> Self {
+        let n_case = snapshot.cases().count();
+        let percent = match n_case {
+            0 => 0,
+            n => snapshot.cases().map(Case::percent_complete).sum::<i64>() / n as i64,
+        };
+        let state = snapshot.running().map(BadgeState::of).max().unwrap_or(
+            if snapshot.pending().next().is_some() {
+                BadgeState::Pending
+            } else {
+                BadgeState::Completed
+            },
+        );
+        Self {
+            label: "campaign".to_string(),
+            percent,
+            state,
+        }
+    }
+    /// Sets the label of the badge, e.g. redacted
+    pub fn with_label(mut self, label: String) -> Self {
+        self.label = label;
+        self
+    }
+    /// Returns the text of the right-hand side of the badge
+    pub fn message(&self) -> String {
+        match self.state {
+            BadgeState::Running => format!("{}%", self.percent),
+            BadgeState::Completed => self.state.to_string(),
+            state => format!("{}% {state}", self.percent),
+        }
+    }
+    /// Returns the badge as an SVG image
+    pub fn to_svg(&self) -> String {
+        let (label, message) = (escape(&self.label), escape(&self.message()));
+        let width = |text: &str| text.chars().count() * CHAR_WIDTH + 2 * PADDING;
+        let (left, right) = (width(&self.label), width(&self.message()));
+        let total = left + right;
+        let color = self.state.color();
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{total}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{total}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{left}" height="20" fill="#555"/><rect x="{left}" width="{right}" height="20" fill="{color}"/><rect width="{total}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{}" y="14">{label}</text><text x="{}" y="14">{message}</text></g></svg>"##,
+            left / 2,
+            left + right / 2,
+        )
+    }
+}
+
+/// Escapes the XML special characters of a text
+fn escape(text: &str) -> String {
+    text.chars().fold(String::new(), |mut escaped, c| {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+        escaped
+    })
+}
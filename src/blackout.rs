@@ -0,0 +1,257 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde::Deserialize;
+
+/// Number of days searched for the next blackout before giving up, more than 4 years
+/// so a window on February 29 is found
+const SEARCH_DAYS: i64 = 1500;
+/// Maximum number of blackouts an ETA is pushed back by
+const MAX_BLACKOUTS: usize = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlackoutError {
+    #[error("invalid cron schedule {0:?}, expected `minute hour day-of-month month day-of-week`")]
+    Fields(String),
+    #[error("invalid {field} {value:?} in the cron schedule")]
+    Value { field: &'static str, value: String },
+    #[error("the blackout duration must be positive")]
+    Duration,
+}
+
+/// Values of a field of a cron schedule, as a bit mask
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CronField {
+    mask: u64,
+    /// Whether the field is `*`, matching any value
+    any: bool,
+}
+
+impl CronField {
+    /// Parses a field of `min..=max` values: `*`, `5`, `1-5`, `*/15`, `0-30/10` or a comma-separated list
+    fn parse(field: &'static str, value: &str, min: u32, max: u32) -> Result<Self, BlackoutError> {
+        let invalid = || BlackoutError::Value {
+            field,
+            value: value.to_string(),
+        };
+        let mut mask = 0u64;
+        for part in value.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse().map_err(|_| invalid())?),
+                None => (part, 1),
+            };
+            let (first, last) = match range {
+                "*" => (min, max),
+                range => match range.split_once('-') {
+                    Some((first, last)) => (
+                        first.parse().map_err(|_| invalid())?,
+                        last.parse().map_err(|_| invalid())?,
+                    ),
+                    None => {
+                        let value = range.parse().map_err(|_| invalid())?;
+                        // `5/15` stands for `5-max/15`
+                        (value, if step > 1 { max } else { value })
+                    }
+                },
+            };
+            if step == 0 || first < min || last > max || first > last {
+                return Err(invalid());
+            }
+            for value in (first..=last).step_by(step as usize) {
+                mask |= 1 << value;
+            }
+        }
+        Ok(Self {
+            mask,
+            any: value == "*",
+        })
+    }
+    fn contains(&self, value: u32) -> bool {
+        self.mask & (1 << value) != 0
+    }
+}
+
+/// Cron schedule of the start of a window, `minute hour day-of-month month day-of-week`
+///
+/// As with cron, a day matches if either of the day-of-month and day-of-week fields does
+/// when both are restricted, and Sunday is either 0 or 7
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CronSchedule {
+    minute: CronField,
+    hour: CronField,
+    day: CronField,
+    month: CronField,
+    weekday: CronField,
+}
+
+impl FromStr for CronSchedule {
+    type Err = BlackoutError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [minute, hour, day, month, weekday] = s.split_whitespace().collect::<Vec<_>>()[..]
+        else {
+            return Err(BlackoutError::Fields(s.to_string()));
+        };
+        let mut weekday = CronField::parse("day of the week", weekday, 0, 7)?;
+        if weekday.contains(7) {
+            weekday.mask |= 1;
+        }
+        Ok(Self {
+            minute: CronField::parse("minute", minute, 0, 59)?,
+            hour: CronField::parse("hour", hour, 0, 23)?,
+            day: CronField::parse("day of the month", day, 1, 31)?,
+            month: CronField::parse("month", month, 1, 12)?,
+            weekday,
+        })
+    }
+}
+
+impl CronSchedule {
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        let day = self.day.contains(date.day());
+        let weekday = self.weekday.contains(date.weekday().num_days_from_sunday());
+        let day = match (self.day.any, self.weekday.any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+        day && self.month.contains(date.month())
+    }
+    /// Returns the first start of the schedule at or after `time`
+    fn next<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let timezone = time.timezone();
+        let first = time.date_naive();
+        (0..SEARCH_DAYS)
+            .map(|days| first + Duration::days(days))
+            .filter(|date| self.matches_day(*date))
+            .find_map(|date| {
+                (0..24)
+                    .filter(|hour| self.hour.contains(*hour))
+                    .flat_map(|hour| {
+                        (0..60)
+                            .filter(|minute| self.minute.contains(*minute))
+                            .filter_map(move |minute| NaiveTime::from_hms_opt(hour, minute, 0))
+                    })
+                    // the starts in a daylight saving gap are skipped
+                    .filter_map(|clock| {
+                        timezone
+                            .from_local_datetime(&date.and_time(clock))
+                            .earliest()
+                    })
+                    .find(|start| start >= time)
+            })
+    }
+}
+
+/// Recurring window the cluster pauses the jobs in, e.g. during a nightly backup,
+/// the ETAs being pushed back by the windows before the cases complete
+///
+/// ```toml
+/// [[blackouts]]
+/// name = "nightly backup"
+/// cron = "30 1 * * *"
+/// duration = 3600
+/// ```
+///
+/// `cron` is the schedule of the start of the window, `minute hour day-of-month month day-of-week`
+/// in local time, or in UTC if `utc` is set, and `duration` its length in seconds
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "BlackoutConfig")]
+pub struct BlackoutWindow {
+    name: String,
+    cron: String,
+    schedule: CronSchedule,
+    duration: Duration,
+    utc: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlackoutConfig {
+    name: String,
+    cron: String,
+    duration: i64,
+    #[serde(default)]
+    utc: bool,
+}
+
+impl TryFrom<BlackoutConfig> for BlackoutWindow {
+    type Error = BlackoutError;
+
+    fn try_from(config: BlackoutConfig) -> Result<Self, Self::Error> {
+        let window = Self::new(config.name, &config.cron, config.duration)?;
+        Ok(if config.utc { window.utc() } else { window })
+    }
+}
+
+impl BlackoutWindow {
+    /// Creates a window starting on the `cron` schedule, in local time, and lasting `secs` seconds
+    pub fn new<S: ToString>(name: S, cron: &str, secs: i64) -> Result<Self, BlackoutError> {
+        if secs <= 0 {
+            return Err(BlackoutError::Duration);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            cron: cron.to_string(),
+            schedule: cron.parse()?,
+            duration: Duration::seconds(secs),
+            utc: false,
+        })
+    }
+    /// Interprets the schedule in UTC
+    pub fn utc(mut self) -> Self {
+        self.utc = true;
+        self
+    }
+    /// Returns the window name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Returns the first window not over at `time`, as its start and end,
+    /// the window `time` falls within if any
+    pub fn window_at(&self, time: DateTime<Local>) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        // the latest window that may not be over yet starts after `time - duration`
+        let after = time - self.duration + Duration::seconds(1);
+        let start = if self.utc {
+            self.schedule
+                .next(&after.with_timezone(&Utc))?
+                .with_timezone(&Local)
+        } else {
+            self.schedule.next(&after)?
+        };
+        Some((start, start + self.duration))
+    }
+}
+
+impl Display for BlackoutWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}{})",
+            self.name,
+            self.cron,
+            crate::format_hours_minutes(self.duration.num_seconds()),
+            if self.utc { " UTC" } else { "" }
+        )
+    }
+}
+
+/// Returns the time a job needing `secs` more seconds of run time from `now` completes at,
+/// the job being paused during the blackouts
+pub fn run_until(blackouts: &[BlackoutWindow], now: DateTime<Local>, secs: i64) -> DateTime<Local> {
+    let (mut time, mut left) = (now, Duration::seconds(secs.max(0)));
+    for _ in 0..MAX_BLACKOUTS {
+        let next = blackouts
+            .iter()
+            .filter_map(|blackout| blackout.window_at(time))
+            .min();
+        match next {
+            None => break,
+            // within a window, the job resumes at its end
+            Some((start, end)) if start <= time => time = end,
+            Some((start, _)) if time + left <= start => break,
+            Some((start, end)) => {
+                left -= start - time;
+                time = end;
+            }
+        }
+    }
+    time + left
+}
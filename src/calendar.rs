@@ -0,0 +1,85 @@
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+use serde::Deserialize;
+
+/// Default time of the day work resumes on a business day
+const RESUME: &str = "08:00";
+
+/// Calendar of the business days
+///
+/// ```toml
+/// [calendar]
+/// weekends = true
+/// holidays = ["2026-12-25", "2027-01-01"]
+/// resume = "08:00"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "CalendarConfig")]
+pub struct BusinessCalendar {
+    weekends: bool,
+    holidays: Vec<NaiveDate>,
+    resume: NaiveTime,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarConfig {
+    #[serde(default = "default_weekends")]
+    weekends: bool,
+    #[serde(default)]
+    holidays: Vec<String>,
+    resume: Option<String>,
+}
+
+fn default_weekends() -> bool {
+    true
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error("invalid holiday {0:?}, expected YYYY-MM-DD")]
+    Date(String),
+    #[error("invalid resume time {0:?}, expected HH:MM")]
+    Time(String),
+}
+
+impl TryFrom<CalendarConfig> for BusinessCalendar {
+    type Error = CalendarError;
+
+    fn try_from(config: CalendarConfig) -> Result<Self, Self::Error> {
+        let resume = config.resume.unwrap_or_else(|| RESUME.to_string());
+        Ok(Self {
+            weekends: config.weekends,
+            holidays: config
+                .holidays
+                .into_iter()
+                .map(|day| {
+                    NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                        .map_err(|_| CalendarError::Date(day))
+                })
+                .collect::<Result<_, _>>()?,
+            resume: NaiveTime::parse_from_str(&resume, "%H:%M")
+                .map_err(|_| CalendarError::Time(resume))?,
+        })
+    }
+}
+
+impl BusinessCalendar {
+    /// Returns true if `date` is neither a weekend day, if weekends are off, nor a holiday
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        let off = (self.weekends && weekend) || self.holidays.contains(&date);
+        !off
+    }
+    /// Returns the time work resumes on the next business day if `time` falls on a non-business day
+    pub fn adjust(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut date = time.date_naive();
+        if self.is_business_day(date) {
+            return None;
+        }
+        while !self.is_business_day(date) {
+            date = date.checked_add_days(Days::new(1))?;
+        }
+        Local
+            .from_local_datetime(&date.and_time(self.resume))
+            .earliest()
+    }
+}
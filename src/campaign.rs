@@ -0,0 +1,122 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use regex::{Captures, Regex};
+use serde::Deserialize;
+
+use crate::{CaseConfig, SimSeconds};
+
+/// Placeholder of a campaign template, e.g. `{zenith:02}` for the zenith angle padded to 2 digits
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{(\w+)(?::0(\d+))?\}").unwrap());
+
+/// Campaign of cases generated from a template, a case for each zenith angle,
+/// azimuth and wind case
+///
+/// ```toml
+/// [[campaigns]]
+/// group = "baseline"
+/// zenith = [0, 30, 60]
+/// azimuth = [0, 45, 90, 135, 180]
+/// wind = ["OS2", "OS7", "CD12", "CD17"]
+/// log = "solve.out"
+/// duration = 400
+/// cores = 672
+///
+/// [campaigns.durations]
+/// CD = 340
+/// ```
+///
+/// expands into the 60 cases `zen00az000_OS2` to `zen60az180_CD17`.
+/// The name and the log are templates with the `{zenith}`, `{azimuth}`, `{wind}`
+/// and `{category}` placeholders, zero-padded as `{azimuth:03}`.
+/// The category of a wind case is its leading letters, e.g. `CD` for `CD12`.
+/// A case also in `[[cases]]` keeps its own configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct CampaignConfig {
+    /// Template of the case names
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Telescope zenith angles in degrees
+    pub zenith: Vec<u32>,
+    /// Telescope azimuths in degrees
+    pub azimuth: Vec<u32>,
+    /// Wind cases, e.g. `OS2` or `CD12`
+    pub wind: Vec<String>,
+    /// Template of the solver log file names
+    pub log: String,
+    /// Simulation duration in simulated seconds, a number or a string with a `s`, `min` or `h` unit
+    pub duration: SimSeconds,
+    /// Simulation durations by wind case or category, over `duration`, with the same units
+    #[serde(default)]
+    pub durations: HashMap<String, SimSeconds>,
+    /// Campaign the cases belong to
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Number of cores the solver runs on
+    #[serde(default)]
+    pub cores: Option<usize>,
+    /// Expected wall-clock duration of a time step in seconds
+    #[serde(default)]
+    pub step_time: Option<f64>,
+    /// Time in seconds without a new time step before a case is deemed stalled
+    #[serde(default)]
+    pub stall_after: Option<u64>,
+}
+
+fn default_name() -> String {
+    "zen{zenith:02}az{azimuth:03}_{wind}".to_string()
+}
+
+impl CampaignConfig {
+    /// Returns the configurations of the cases of the campaign,
+    /// by zenith angle, then azimuth, then wind case
+    pub fn cases(&self) -> Vec<CaseConfig> {
+        let mut cases = vec![];
+        for zenith in &self.zenith {
+            for azimuth in &self.azimuth {
+                for wind in &self.wind {
+                    let category = category(wind);
+                    let expand = |template: &str| {
+                        PLACEHOLDER
+                            .replace_all(template, |captures: &Captures| {
+                                let value = match &captures[1] {
+                                    "zenith" => zenith.to_string(),
+                                    "azimuth" => azimuth.to_string(),
+                                    "wind" => wind.clone(),
+                                    "category" => category.to_string(),
+                                    _ => return captures[0].to_string(),
+                                };
+                                let width = captures
+                                    .get(2)
+                                    .map_or(0, |w| w.as_str().parse().unwrap_or_default());
+                                format!("{value:0>width$}")
+                            })
+                            .to_string()
+                    };
+                    let duration = self
+                        .durations
+                        .get(wind)
+                        .or_else(|| self.durations.get(category))
+                        .copied()
+                        .unwrap_or(self.duration);
+                    let mut case = CaseConfig::new(expand(&self.name), 0, expand(&self.log));
+                    case.duration = duration;
+                    case.group = self.group.clone();
+                    case.cores = self.cores;
+                    case.step_time = self.step_time;
+                    case.stall_after = self.stall_after;
+                    cases.push(case);
+                }
+            }
+        }
+        cases
+    }
+}
+
+/// Returns the category of a wind case, its leading letters
+fn category(wind: &str) -> &str {
+    let end = wind
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(wind.len());
+    &wind[..end]
+}
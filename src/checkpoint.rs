@@ -0,0 +1,134 @@
+use std::{fmt::Display, sync::LazyLock};
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::StepParser;
+
+/// Default pattern of the log line of a checkpoint save
+const CHECKPOINT_PATTERN: &str = r#"(?i)\b(?:sav(?:e|ed|ing)|writ(?:e|ing|ten)|wrote)\b.*?(?:\b(?:checkpoint|restart|autosave)\b|(?P<file>[^\s"']+\.(?:sim|dat|cas|h5|chk|rst)(?:\.h5|\.gz)?))"#;
+/// Checkpoint file in a checkpoint save line, if the pattern does not capture it
+static CHECKPOINT_FILE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"[^\s"']+\.(?:sim|dat|cas|h5|chk|rst)(?:\.h5|\.gz)?\b"#).unwrap()
+});
+
+/// Pattern of the log line of a checkpoint save, the checkpoint file and time step being
+/// the `file` and `step` named capture groups if any
+///
+/// Without a `step` group, the checkpoint is of the latest time step before the line
+///
+/// ```toml
+/// checkpoint_pattern = 'Autosave (?P<file>\S+) at iteration (?P<step>\d+)'
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct CheckpointPattern(Regex);
+
+impl TryFrom<String> for CheckpointPattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::new(&pattern)
+    }
+}
+
+impl Default for CheckpointPattern {
+    fn default() -> Self {
+        Self::new(CHECKPOINT_PATTERN).unwrap()
+    }
+}
+
+impl CheckpointPattern {
+    /// Creates a new checkpoint pattern
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self(Regex::new(pattern)?))
+    }
+    /// Returns the latest checkpoint saved in the log lines, given the latest time step
+    /// and simulated time before them and the parser of the time step lines
+    pub fn find_last(
+        &self,
+        lines: &[String],
+        latest: Option<(usize, f64)>,
+        steps: &dyn StepParser,
+    ) -> Option<Checkpoint> {
+        let mut latest = latest;
+        let mut checkpoint = None;
+        for line in lines {
+            if let Some(step) = steps.parse_line(line) {
+                latest = Some(step);
+                continue;
+            }
+            let Some(captures) = self.0.captures(line) else {
+                continue;
+            };
+            let step = captures
+                .name("step")
+                .and_then(|step| step.as_str().parse().ok());
+            let (step, time) = match (step, latest) {
+                // the simulated time of another time step is extrapolated at the latest time step size
+                (Some(step), Some((latest, time))) if latest > 0 => {
+                    (step, time * step as f64 / latest as f64)
+                }
+                (Some(step), _) => (step, 0.),
+                (None, Some(latest)) => latest,
+                (None, None) => continue,
+            };
+            let file = captures
+                .name("file")
+                .or_else(|| CHECKPOINT_FILE.find(line))
+                .map(|file| file.as_str().to_string());
+            checkpoint = Some(Checkpoint {
+                step,
+                time,
+                file,
+                at: None,
+            });
+        }
+        checkpoint
+    }
+}
+
+/// Checkpoint saved by the solver, a restart resuming from it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Checkpoint {
+    pub step: usize,
+    /// Simulated time at the checkpoint in seconds
+    pub time: f64,
+    pub file: Option<String>,
+    /// Time the checkpoint was observed, `None` if it was already in the log when monitoring started
+    pub at: Option<DateTime<Local>>,
+}
+
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{file} (step {})", self.step),
+            None => write!(f, "step {}", self.step),
+        }
+    }
+}
+
+/// Loss of a restart of a failed case from its last checkpoint, or from the start without any
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RestartAdvice {
+    pub checkpoint: Option<Checkpoint>,
+    /// Time steps to run again
+    pub lost_steps: usize,
+    /// Simulated time to run again in seconds
+    pub lost_time: f64,
+}
+
+impl Display for RestartAdvice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.checkpoint {
+            Some(checkpoint) => write!(f, "restart from {checkpoint}")?,
+            None => write!(f, "no checkpoint, restart from the start")?,
+        }
+        write!(
+            f,
+            " losing {:.2}s simulated ({} steps)",
+            self.lost_time, self.lost_steps
+        )
+    }
+}
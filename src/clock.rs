@@ -0,0 +1,76 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+/// Source of the current time and of the waits of the cases and of the monitor
+pub trait Clock: Send + Sync + Debug {
+    /// Returns the current time
+    fn now(&self) -> DateTime<Local>;
+    /// Returns the current instant of a monotonic clock
+    fn instant(&self) -> Instant;
+    /// Waits for `duration`
+    fn sleep(&self, duration: Duration);
+}
+
+/// Clock of the system, the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+/// Clock only moving forward when advanced or slept on, for deterministic tests and simulations
+///
+/// The clones share the same time, so a test keeps advancing the clock given to a monitor
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: DateTime<Local>,
+    origin: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    /// Creates a clock starting at `start`
+    pub fn new(start: DateTime<Local>) -> Self {
+        Self {
+            start,
+            origin: Instant::now(),
+            elapsed: Default::default(),
+        }
+    }
+    /// Moves the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+    /// Returns the time elapsed since the start of the clock
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Local> {
+        self.start + chrono::Duration::from_std(self.elapsed()).unwrap_or_default()
+    }
+    fn instant(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+    /// Advances the clock instead of waiting
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
@@ -0,0 +1,647 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+
+use crate::{
+    deltas::DELTA_WIDTH, format_core_hours, format_hours_minutes, format_signed_hours_minutes,
+    Case, CaseDelta, EtaStyle, Theme,
+};
+
+/// Width of the case name column by default
+pub const NAME_WIDTH: usize = 20;
+/// Narrowest case name column a table is fitted with, the names being truncated
+const MIN_NAME_WIDTH: usize = 12;
+
+/// Columns of the status table by default
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Name,
+    Column::Percent,
+    Column::Time,
+    Column::StepTime,
+    Column::Trend,
+    Column::Elapsed,
+    Column::StepsPerHour,
+    Column::SimPerDay,
+    Column::UsedCoreHours,
+    Column::LeftCoreHours,
+    Column::Eta,
+    Column::ConservativeEta,
+    Column::Slack,
+];
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown column {0:?}, expected one of {}", Column::NAMES.join(", "))]
+pub struct ColumnError(String);
+
+/// Field of a case rendered as a column of the status table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Column {
+    Name,
+    /// Percent complete
+    Percent,
+    /// Simulated time in seconds
+    Time,
+    /// Mean time step duration in seconds
+    StepTime,
+    /// Sparkline of the latest time step durations
+    Trend,
+    Elapsed,
+    StepsPerHour,
+    /// Simulated seconds per wall-clock day
+    SimPerDay,
+    /// Simulated seconds left
+    SimRemaining,
+    /// Simulated seconds left with the wall-clock time left
+    Remaining,
+    UsedCoreHours,
+    LeftCoreHours,
+    /// ETA at the current speed, from the latest time step durations
+    Eta,
+    /// ETA at the mean time step duration of the simulation, with the downtime of similar cases
+    ConservativeEta,
+    /// Schedule slack to the deadline
+    Slack,
+    /// Latest time step
+    Step,
+    State,
+    Group,
+    Priority,
+    /// State of the case in its pipeline, solving then the post-processing stages
+    Pipeline,
+    /// Reason the case needs attention, sorting the failed, stalled, late
+    /// and highest priority cases first
+    Attention,
+    /// Note of the operators, truncated to the width of the column
+    Note,
+    /// CPU and GPU utilization of the solver job, from the resource collector of the case
+    Utilization,
+    /// Inner iterations of the latest time step, followed by `↑` if they are climbing
+    Iterations,
+}
+
+impl Column {
+    /// Names of the columns in the configuration and on the command line
+    const NAMES: [&'static str; 24] = [
+        "name",
+        "percent",
+        "time",
+        "step_time",
+        "trend",
+        "elapsed",
+        "steps_per_hour",
+        "sim_per_day",
+        "sim_remaining",
+        "remaining",
+        "used_core_hours",
+        "left_core_hours",
+        "eta",
+        "conservative_eta",
+        "slack",
+        "step",
+        "state",
+        "group",
+        "priority",
+        "pipeline",
+        "attention",
+        "note",
+        "utilization",
+        "iterations",
+    ];
+    const ALL: [Self; 24] = [
+        Self::Name,
+        Self::Percent,
+        Self::Time,
+        Self::StepTime,
+        Self::Trend,
+        Self::Elapsed,
+        Self::StepsPerHour,
+        Self::SimPerDay,
+        Self::SimRemaining,
+        Self::Remaining,
+        Self::UsedCoreHours,
+        Self::LeftCoreHours,
+        Self::Eta,
+        Self::ConservativeEta,
+        Self::Slack,
+        Self::Step,
+        Self::State,
+        Self::Group,
+        Self::Priority,
+        Self::Pipeline,
+        Self::Attention,
+        Self::Note,
+        Self::Utilization,
+        Self::Iterations,
+    ];
+    /// Returns the header of the column
+    pub fn header(&self) -> &'static str {
+        match self {
+            Self::Name => "Case",
+            Self::Percent => "%",
+            Self::Time => "P.[s]",
+            Self::StepTime => "I.[s]",
+            Self::Trend => "Trend",
+            Self::Elapsed => "Elapsed",
+            Self::StepsPerHour => "Step/h",
+            Self::SimPerDay => "Sim s/d",
+            Self::SimRemaining => "Left[s]",
+            Self::Remaining => "Left sim/wall",
+            Self::UsedCoreHours => "Used[ch]",
+            Self::LeftCoreHours => "Left[ch]",
+            Self::Eta => "ETA",
+            Self::ConservativeEta => "Cons. ETA",
+            Self::Slack => "Slack",
+            Self::Step => "Step",
+            Self::State => "State",
+            Self::Group => "Group",
+            Self::Priority => "Prio.",
+            Self::Pipeline => "Pipeline",
+            Self::Attention => "Attention",
+            Self::Note => "Note",
+            Self::Utilization => "CPU/GPU",
+            Self::Iterations => "Iter.",
+        }
+    }
+    /// Returns the width of the column, the ETA column depending on the format of the ETAs
+    pub fn width(&self, eta: EtaStyle) -> usize {
+        match self {
+            Self::Name => NAME_WIDTH,
+            Self::Percent
+            | Self::StepTime
+            | Self::StepsPerHour
+            | Self::Step
+            | Self::Priority
+            | Self::Iterations => 8,
+            Self::Trend | Self::State | Self::Pipeline => 14,
+            Self::Group | Self::Remaining => 16,
+            Self::Note => 32,
+            Self::Eta | Self::ConservativeEta => eta.width(),
+            _ => 10,
+        }
+    }
+    /// Returns true if the changes of the column between refreshes are annotated
+    pub fn has_delta(&self) -> bool {
+        matches!(
+            self,
+            Self::Percent
+                | Self::Time
+                | Self::Step
+                | Self::Eta
+                | Self::ConservativeEta
+                | Self::Slack
+        )
+    }
+    /// Returns the content of the cell of a case, the remaining time of the ETA counted from `now`
+    pub fn cell(&self, case: &Case, eta: EtaStyle, now: DateTime<Local>) -> String {
+        let rate = |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| format!("{r:.0}"));
+        match self {
+            Self::Name => case.name().to_string(),
+            Self::Percent => case.percent_complete().to_string(),
+            Self::Time => format!("{:.2}", case.time()),
+            Self::StepTime => format!("{:.2}", case.elapsed_per_step().mean()),
+            Self::Trend => case.step_times().sparkline(),
+            Self::Elapsed => case
+                .elapsed_secs()
+                .map_or_else(|| "-".to_string(), format_hours_minutes),
+            Self::StepsPerHour => rate(case.steps_per_hour()),
+            Self::SimPerDay => rate(case.sim_secs_per_day()),
+            Self::SimRemaining => case
+                .remaining_sim_secs()
+                .map_or_else(|| "-".to_string(), |secs| format!("{secs:.2}")),
+            Self::Remaining => case.remaining_sim_secs().zip(case.remaining()).map_or_else(
+                || "-".to_string(),
+                |(sim, wall)| format!("{sim:.1}s/{}", format_hours_minutes(wall.num_seconds())),
+            ),
+            Self::UsedCoreHours => format_core_hours(case.consumed_core_hours()),
+            Self::LeftCoreHours => format_core_hours(case.remaining_core_hours()),
+            Self::Eta => eta.format(now + Duration::seconds(case.eta_secs()), now),
+            Self::ConservativeEta => {
+                eta.format(now + Duration::seconds(case.conservative_eta_secs()), now)
+            }
+            Self::Slack => case
+                .slack_secs()
+                .map_or_else(|| "-".to_string(), format_signed_hours_minutes),
+            Self::Step => case
+                .step()
+                .map_or_else(|| "-".to_string(), |step| step.to_string()),
+            Self::State => case.state().to_string(),
+            Self::Group => case.group_name().unwrap_or("-").to_string(),
+            Self::Priority => case.priority_level().to_string(),
+            Self::Pipeline => case.pipeline_state().to_string(),
+            Self::Attention => case.attention().unwrap_or("-").to_string(),
+            Self::Note => case.note().map_or_else(
+                || "-".to_string(),
+                |note| {
+                    // the leading space separates the note from the previous column
+                    let width = self.width(eta) - 1;
+                    if note.text.chars().count() > width {
+                        let text: String = note.text.chars().take(width - 1).collect();
+                        format!("{text}…")
+                    } else {
+                        note.text.clone()
+                    }
+                },
+            ),
+            Self::Utilization => case
+                .utilization()
+                .map_or_else(|| "-".to_string(), |utilization| utilization.to_string()),
+            Self::Iterations => case.inner_iterations().latest().map_or_else(
+                || "-".to_string(),
+                |iterations| {
+                    if case.is_iterating_more() {
+                        format!("{iterations}↑")
+                    } else {
+                        iterations.to_string()
+                    }
+                },
+            ),
+        }
+    }
+    /// Returns the width of the column in a table whose case name column is `name_width` wide
+    fn fitted_width(&self, eta: EtaStyle, name_width: usize) -> usize {
+        match self {
+            Self::Name => name_width,
+            _ => self.width(eta),
+        }
+    }
+    /// Writes the cell of a case, padded to the width of the column,
+    /// the case name column being `name_width` wide
+    pub(crate) fn write(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        case: &Case,
+        eta: EtaStyle,
+        now: DateTime<Local>,
+        name_width: usize,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.padded(case, eta, now, name_width))
+    }
+    /// Returns the cell of a case, padded to the width of the column,
+    /// the case names too long being truncated with an ellipsis
+    fn padded(
+        &self,
+        case: &Case,
+        eta: EtaStyle,
+        now: DateTime<Local>,
+        name_width: usize,
+    ) -> String {
+        let (cell, width) = (
+            self.cell(case, eta, now),
+            self.fitted_width(eta, name_width),
+        );
+        match self {
+            Self::Name => format!("{:<width$}", truncate(&cell, width)),
+            Self::Note => format!(" {cell:<0$}", width - 1),
+            _ => format!("{cell:>width$}"),
+        }
+    }
+    /// Returns the value the cases are sorted by, `None` if unknown
+    fn key(&self, case: &Case) -> Option<SortValue> {
+        let number = |value: Option<f64>| value.map(SortValue::Number);
+        let text = |value: &str| Some(SortValue::Text(value.to_string()));
+        match self {
+            Self::Name => text(case.name()),
+            Self::State => text(case.state()),
+            Self::Group => case.group_name().and_then(text),
+            Self::Percent => number(Some(case.percent_complete() as f64)),
+            Self::Time => number(Some(case.time())),
+            Self::StepTime => number(Some(case.elapsed_per_step().mean())),
+            Self::Trend => number(case.step_times().last()),
+            Self::Elapsed => number(case.elapsed_secs().map(|secs| secs as f64)),
+            Self::StepsPerHour => number(case.steps_per_hour()),
+            Self::SimPerDay => number(case.sim_secs_per_day()),
+            Self::SimRemaining => number(case.remaining_sim_secs()),
+            Self::Remaining => number(case.remaining().map(|wall| wall.num_seconds() as f64)),
+            Self::UsedCoreHours => number(case.consumed_core_hours()),
+            Self::LeftCoreHours => number(case.remaining_core_hours()),
+            Self::Eta => number(case.step().map(|_| case.eta_secs() as f64)),
+            Self::ConservativeEta => {
+                number(case.step().map(|_| case.conservative_eta_secs() as f64))
+            }
+            Self::Slack => number(case.slack_secs().map(|secs| secs as f64)),
+            Self::Step => number(case.step().map(|step| step as f64)),
+            Self::Priority => number(Some(case.priority_level() as f64)),
+            Self::Pipeline => text(case.pipeline_state()),
+            Self::Note => case.note().and_then(|note| text(&note.text)),
+            // the most starved cases first
+            Self::Utilization => number(case.utilization().and_then(|u| u.lowest())),
+            Self::Iterations => number(case.inner_iterations().recent()),
+            Self::Attention => {
+                let rank = ["failed", "stalled", "late"]
+                    .iter()
+                    .position(|reason| Some(*reason) == case.attention())
+                    .unwrap_or(3);
+                Some(SortValue::Ranks(vec![
+                    rank as f64,
+                    -case.priority_level() as f64,
+                    case.slack_secs().map_or(f64::INFINITY, |secs| secs as f64),
+                ]))
+            }
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = ColumnError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .position(|name| *name == s.trim())
+            .map(|i| Self::ALL[i])
+            .ok_or_else(|| ColumnError(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for Column {
+    type Error = ColumnError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let i = Self::ALL.iter().position(|column| column == self).unwrap();
+        write!(f, "{}", Self::NAMES[i])
+    }
+}
+
+/// Returns the text truncated to `width` characters, the last one being an ellipsis
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        let text: String = text.chars().take(width.saturating_sub(1)).collect();
+        format!("{text}…")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Value of a column the cases are sorted by
+#[derive(Debug, PartialEq)]
+enum SortValue {
+    Text(String),
+    Number(f64),
+    /// Numbers compared in sequence, the next one breaking the ties
+    Ranks(Vec<f64>),
+}
+
+impl SortValue {
+    fn compare(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Text(a), Self::Text(b)) => a.cmp(b),
+            (Self::Number(a), Self::Number(b)) => a.total_cmp(b),
+            (Self::Ranks(a), Self::Ranks(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+/// Column the cases are sorted by, written `-column` for a decreasing order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct SortKey {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl Default for SortKey {
+    /// Increasing schedule slack, the cases most at risk of missing their deadline first
+    fn default() -> Self {
+        Self {
+            column: Column::Slack,
+            descending: false,
+        }
+    }
+}
+
+impl SortKey {
+    /// Compares 2 cases, the cases without a value last
+    pub fn compare(&self, a: &Case, b: &Case) -> Ordering {
+        match (self.column.key(a), self.column.key(b)) {
+            (Some(a), Some(b)) if self.descending => b.compare(&a),
+            (Some(a), Some(b)) => a.compare(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = ColumnError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(match s.strip_prefix('-') {
+            Some(column) => Self {
+                column: column.parse()?,
+                descending: true,
+            },
+            None => Self {
+                column: s.parse()?,
+                descending: false,
+            },
+        })
+    }
+}
+
+impl TryFrom<String> for SortKey {
+    type Error = ColumnError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Columns of the status table and order of its rows, fitted to the width of the table if any
+///
+/// ```toml
+/// columns = ["name", "percent", "steps_per_hour", "eta"]
+/// sort_by = "-percent"
+/// table_width = 100
+/// wrap_columns = true
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSpec {
+    pub columns: Vec<Column>,
+    pub sort_by: SortKey,
+    /// Width of the table in characters, as wide as its columns if none
+    pub width: Option<usize>,
+    /// Wraps the columns not fitting the width to a second line per case instead of dropping them
+    pub wrap: bool,
+    /// Width of the case name column
+    pub name_width: usize,
+    /// Columns of the second line of the cases, below the first column after the case name
+    pub wrapped: Vec<Column>,
+}
+
+impl Default for ColumnSpec {
+    fn default() -> Self {
+        Self::new(DEFAULT_COLUMNS.to_vec(), SortKey::default())
+    }
+}
+
+impl ColumnSpec {
+    /// Creates the columns of the status table, the name of the cases always coming first
+    pub fn new(columns: Vec<Column>, sort_by: SortKey) -> Self {
+        let mut columns: Vec<_> = columns
+            .into_iter()
+            .filter(|column| *column != Column::Name)
+            .collect();
+        columns.insert(0, Column::Name);
+        Self {
+            columns,
+            sort_by,
+            width: None,
+            wrap: false,
+            name_width: NAME_WIDTH,
+            wrapped: vec![],
+        }
+    }
+    /// Sets the width of the table, the columns not fitting it being wrapped to a second line
+    /// if `wrap` is set, dropped otherwise
+    pub fn with_width(mut self, width: Option<usize>, wrap: bool) -> Self {
+        self.width = width;
+        self.wrap = wrap;
+        self
+    }
+    /// Returns the columns fitted to the width of the table with the cases of the given names,
+    /// with room for the changes between refreshes next to the columns if `deltas` is set
+    ///
+    /// The case name column is as wide as the longest name, narrowed down to
+    /// [MIN_NAME_WIDTH] if the other columns do not fit, then the last columns are wrapped
+    /// or dropped
+    pub fn fit<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+        eta: EtaStyle,
+        deltas: bool,
+    ) -> Self {
+        let Some(width) = self.width else {
+            return self.clone();
+        };
+        let cell = |column: &Column| {
+            column.width(eta)
+                + if deltas && column.has_delta() {
+                    DELTA_WIDTH
+                } else {
+                    0
+                }
+        };
+        let others: Vec<_> = self
+            .columns
+            .iter()
+            .chain(&self.wrapped)
+            .filter(|column| **column != Column::Name)
+            .copied()
+            .collect();
+        let longest = names
+            .into_iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let natural = (longest + 1).max(Column::Name.header().len() + 1);
+        let room = width.saturating_sub(others.iter().map(cell).sum());
+        let name_width = natural.min(room.max(MIN_NAME_WIDTH));
+        let mut fitted = Self {
+            columns: vec![Column::Name],
+            wrapped: vec![],
+            name_width,
+            ..self.clone()
+        };
+        let (mut first, mut second, mut full) = (name_width, name_width, false);
+        for column in others {
+            full |= first + cell(&column) > width;
+            if !full {
+                first += cell(&column);
+                fitted.columns.push(column);
+            } else if self.wrap && second + cell(&column) <= width {
+                second += cell(&column);
+                fitted.wrapped.push(column);
+            }
+        }
+        fitted
+    }
+    /// Returns the header of the table, the ETA column depending on the format of the ETAs
+    pub fn header(&self, eta: EtaStyle) -> String {
+        self.header_with_deltas(eta, false)
+    }
+    /// Returns the header of the table, with room for the changes between
+    /// refreshes next to the columns if `deltas` is set
+    pub fn header_with_deltas(&self, eta: EtaStyle, deltas: bool) -> String {
+        let headers = |columns: &[Column]| -> String {
+            columns
+                .iter()
+                .map(|column| {
+                    let width = column.fitted_width(eta, self.name_width);
+                    let header = match column {
+                        Column::Name => format!("{:<width$}", column.header()),
+                        _ => format!("{:>width$}", column.header()),
+                    };
+                    if deltas && column.has_delta() {
+                        format!("{header:<0$}", width + DELTA_WIDTH)
+                    } else {
+                        header
+                    }
+                })
+                .collect()
+        };
+        let mut header = headers(&self.columns);
+        if !self.wrapped.is_empty() {
+            let indent = self.name_width;
+            header += &format!("\n{:indent$}{}", "", headers(&self.wrapped));
+        }
+        header
+    }
+    /// Returns the row of a case, with the ETA in the given format
+    pub fn row<'a>(&'a self, case: &'a Case, eta: EtaStyle) -> impl Display + 'a {
+        case.row_fitted(&self.columns, &self.wrapped, eta, self.name_width)
+    }
+    /// Returns the row of a case with its changes since the previous refresh next to the columns,
+    /// the cells in the style of the case and the changes in the improved or regressed style
+    /// if there is a theme
+    pub fn row_with_delta(
+        &self,
+        case: &Case,
+        delta: Option<&CaseDelta>,
+        eta: EtaStyle,
+        theme: Option<&Theme>,
+    ) -> String {
+        let now = case.now();
+        let mut row = String::new();
+        for column in self.columns.iter().chain(&self.wrapped) {
+            if self.wrapped.first() == Some(column) {
+                row += &format!("\n{:1$}", "", self.name_width);
+            }
+            let cell = column.padded(case, eta, now, self.name_width);
+            row += &match theme {
+                Some(theme) => theme.paint(case, cell),
+                None => cell,
+            };
+            if !column.has_delta() {
+                continue;
+            }
+            let (annotation, change) = match delta.and_then(|delta| delta.annotation(*column)) {
+                Some((annotation, change)) => (annotation, Some(change)),
+                None => (String::new(), None),
+            };
+            let annotation = format!(" {annotation:<0$}", DELTA_WIDTH - 1);
+            row += &match (theme, change) {
+                (Some(theme), Some(change)) => theme.paint_change(change, annotation),
+                _ => annotation,
+            };
+        }
+        row
+    }
+    /// Returns the cases in the order of the table
+    pub fn sort<'a>(&self, cases: impl Iterator<Item = &'a Case>) -> Vec<&'a Case> {
+        let mut cases: Vec<_> = cases.collect();
+        cases.sort_by(|a, b| self.sort_by.compare(a, b));
+        cases
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    fs,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{cores_from_log, Case};
+
+/// Run with timed steps, as far as the throughput comparison is concerned
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedRun {
+    pub name: String,
+    pub cores: usize,
+    /// Number of time steps timed
+    pub steps: usize,
+    /// Mean wall-clock duration of a time step in seconds
+    pub secs_per_step: f64,
+}
+
+/// Completed run of the completion records, as far as the throughput is concerned
+#[derive(Debug, Deserialize)]
+struct RecordedRun {
+    name: String,
+    log: String,
+    cores: Option<usize>,
+    steps: Option<usize>,
+    step_time: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Records {
+    #[serde(default)]
+    completed: Vec<RecordedRun>,
+}
+
+impl TimedRun {
+    /// Returns the timed steps of a case, `None` without core count or timed step
+    pub fn from_case(case: &Case) -> Option<Self> {
+        let elapsed = case.elapsed_per_step();
+        Some(Self {
+            name: case.name().to_string(),
+            cores: case.cores()?,
+            steps: Some(elapsed.sample()).filter(|steps| *steps > 0)?,
+            secs_per_step: elapsed.mean(),
+        })
+    }
+    /// Reads the completed runs with a mean time step duration from the completion records,
+    /// none if there are none
+    ///
+    /// The core count of the records written by older versions is parsed from the log file name
+    pub fn read_records<P: AsRef<Path>>(path: P) -> Vec<Self> {
+        let Ok(records) = fs::read_to_string(path) else {
+            return vec![];
+        };
+        let records: Records = match toml::from_str(&records) {
+            Ok(records) => records,
+            Err(e) => {
+                tracing::warn!(error = %e, "invalid completion records file");
+                return vec![];
+            }
+        };
+        records
+            .completed
+            .into_iter()
+            .filter_map(|run| {
+                Some(Self {
+                    cores: run.cores.or_else(|| cores_from_log(&run.log))?,
+                    steps: run.steps.filter(|steps| *steps > 0)?,
+                    secs_per_step: run.step_time.filter(|secs| *secs > 0.)?,
+                    name: run.name,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Time step throughput of the runs on the same number of cores
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CoreGroup {
+    pub cores: usize,
+    /// Number of runs with timed steps
+    pub cases: usize,
+    /// Number of time steps timed
+    pub steps: usize,
+    /// Mean wall-clock duration of a time step in seconds, weighted by the steps timed
+    pub secs_per_step: f64,
+    /// Core-seconds spent per time step
+    pub core_secs_per_step: f64,
+    /// Speedup over the group of the fewest cores
+    pub speedup: f64,
+    /// Parallel efficiency over the group of the fewest cores, the speedup
+    /// divided by the ratio of the core counts
+    pub efficiency: f64,
+}
+
+/// Time step throughput of the runs grouped by core count, e.g. to weigh
+/// 672-core runs against 336-core ones
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ThroughputComparison {
+    pub groups: Vec<CoreGroup>,
+}
+
+impl ThroughputComparison {
+    /// Groups the runs by increasing core count, a run replacing the earlier run of the same name
+    pub fn new(runs: impl IntoIterator<Item = TimedRun>) -> Self {
+        let runs: HashMap<String, TimedRun> = runs
+            .into_iter()
+            .map(|run| (run.name.clone(), run))
+            .collect();
+        let mut groups: BTreeMap<usize, (usize, usize, f64)> = BTreeMap::new();
+        for run in runs.values() {
+            let (cases, steps, secs) = groups.entry(run.cores).or_default();
+            *cases += 1;
+            *steps += run.steps;
+            *secs += run.secs_per_step * run.steps as f64;
+        }
+        let mut base = None;
+        let groups = groups
+            .into_iter()
+            .map(|(cores, (cases, steps, secs))| {
+                let secs_per_step = secs / steps as f64;
+                let (base_cores, base_secs) = *base.get_or_insert((cores, secs_per_step));
+                let speedup = base_secs / secs_per_step;
+                CoreGroup {
+                    cores,
+                    cases,
+                    steps,
+                    secs_per_step,
+                    core_secs_per_step: secs_per_step * cores as f64,
+                    speedup,
+                    efficiency: speedup * base_cores as f64 / cores as f64,
+                }
+            })
+            .collect();
+        Self { groups }
+    }
+    /// Returns true if there is no run to compare
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+}
+
+impl Display for ThroughputComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>8}{:>8}{:>10}{:>12}{:>14}{:>10}{:>12}",
+            "Cores", "Cases", "Steps", "s/step", "core-s/step", "Speedup", "Efficiency"
+        )?;
+        for group in &self.groups {
+            writeln!(
+                f,
+                "{:>8}{:>8}{:>10}{:>12.2}{:>14.0}{:>10.2}{:>11.0}%",
+                group.cores,
+                group.cases,
+                group.steps,
+                group.secs_per_step,
+                group.core_secs_per_step,
+                group.speedup,
+                group.efficiency * 100.
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,996 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+
+use crate::{
+    hooks::HooksConfig, queue::deserialize_start, AggregationConfig, AlertKind, BlackoutWindow,
+    BusinessCalendar, CampaignConfig, Case, CheckpointPattern, Column, ColumnSpec, CommandSource,
+    DiskConfig, DisplayProfile, DtPattern, ErrorPattern, EtaConfig, ExcerptConfig, ExportConfig,
+    FilesystemConfig, HealthConfig, HeartbeatConfig, HttpConfig, InitPhase, Liveness,
+    MaintenanceWindow, MilestonePattern, Notes, NotifyConfig, PluginsConfig, PrecursorConfig,
+    RedactionConfig, RemoteSource, ResourceCollector, Severity, SimSeconds, SortKey, StageConfig,
+    Steps, TabConfig, Theme, TimeStepPattern, TimeStepping, TriageRule, VersionPattern,
+    DEFAULT_COLUMNS, MAX_RATE_RATIO, PROFILES, RATE, ROOT, SSH_COMMAND, UPDATE_TIME,
+};
+
+/// Default time in seconds without a new time step before a case is stalled
+const STALL_AFTER: u64 = 900;
+/// Default number of consecutive update failures of a case before the monitor aborts
+const MAX_FAILURES: usize = 5;
+/// Default time in seconds log modifications are batched over in watch mode
+const DEBOUNCE: u64 = 10;
+/// Default file the records of the completed cases are appended to
+const RECORDS: &str = "cfd-status-records.toml";
+/// Default file of the notes of the operators
+const NOTES: &str = "cfd-status-notes.toml";
+/// Default file the state transitions of the cases are appended to
+const EVENTS: &str = "cfd-status-events.jsonl";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read configuration file")]
+    Read(#[from] io::Error),
+    #[error("failed to parse configuration file")]
+    Parse(#[from] toml::de::Error),
+    #[error("invalid environment variable {0}: {1}")]
+    Env(&'static str, String),
+    #[error("a case has no name")]
+    NoName,
+    #[error("case {0} is defined more than once")]
+    Duplicate(String),
+    #[error("case {0} has no simulation duration")]
+    NoDuration(String),
+    #[error("case {0} has no time steps")]
+    NoSteps(String),
+    #[error("case {0} has both a log command and a log host")]
+    ConflictingSources(String),
+    #[error("case {case} runs after an unknown case {after}")]
+    UnknownPredecessor { case: String, after: String },
+    #[error("case {0} runs after itself through the cases it runs after")]
+    DependencyCycle(String),
+    #[error("unknown display profile {0}")]
+    UnknownProfile(String),
+    #[error(
+        "case {case}: {steps} over {duration} is inconsistent with the {} Hz time stepping, \
+        is the duration a time step count instead of simulated seconds?",
+        RATE
+    )]
+    InconsistentSteps {
+        case: String,
+        duration: SimSeconds,
+        steps: Steps,
+    },
+    #[error(
+        "case {case}: {duration} is over {} times the median {median} of the cases, \
+        is the duration a time step count instead of simulated seconds?",
+        MAX_RATE_RATIO
+    )]
+    OutlierDuration {
+        case: String,
+        duration: SimSeconds,
+        median: SimSeconds,
+    },
+}
+
+/// CFD case configuration
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct CaseConfig {
+    /// Case name, also the name of the case directory
+    pub name: String,
+    /// Simulation duration in simulated seconds, a number or a string with a `s`, `min` or `h` unit
+    pub duration: SimSeconds,
+    /// Solver log file name within the case directory, or a glob of log files
+    /// merged into one, e.g. `solve-672_14.rank*.out` for a log per MPI rank
+    pub log: String,
+    /// With a glob `log`, follows the most recently modified matching log instead of merging them,
+    /// e.g. `solve-672_*.out` whose number increments at each restart, the switches being announced
+    #[serde(default)]
+    pub follow_latest: bool,
+    /// Shell command printing the log, read instead of the log file, e.g. for a remote log
+    #[serde(default)]
+    pub log_command: Option<String>,
+    /// SSH host the log is read from, e.g. `login01` or `user@login01`, the log path being
+    /// the one on the host, only the bytes appended since the previous update being fetched
+    #[serde(default)]
+    pub log_host: Option<String>,
+    /// S3 URL of the log object, `s3://bucket/key`, read instead of the log file,
+    /// requires the `s3` feature
+    #[serde(default)]
+    pub log_object: Option<String>,
+    /// Campaign the case belongs to
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Number of cores the solver runs on, parsed from the log file name if omitted
+    #[serde(default)]
+    pub cores: Option<usize>,
+    /// Planned start of a case not submitted yet, as `YYYY-MM-DD HH:MM`
+    #[serde(default, deserialize_with = "deserialize_start")]
+    pub start: Option<DateTime<Local>>,
+    /// Scheduler job id of a queued case, its expected start is queried with `squeue`
+    #[serde(default)]
+    pub job: Option<String>,
+    /// Expected completion of the case, as `YYYY-MM-DD HH:MM`
+    #[serde(default, deserialize_with = "deserialize_start")]
+    pub deadline: Option<DateTime<Local>>,
+    /// Priority of the case, the cases of higher priority coming first when sorted by attention
+    #[serde(default)]
+    pub priority: Option<i32>,
+    /// Total number of time steps, overriding the count derived from the duration
+    #[serde(default)]
+    pub steps: Option<Steps>,
+    /// Time stepping of the solver, `fixed` or `adaptive`, fixed by default
+    ///
+    /// With adaptive time stepping, progress is computed from the simulated time
+    /// instead of the time step count
+    #[serde(default)]
+    pub time_stepping: TimeStepping,
+    /// Same as `time_stepping = "adaptive"`, for older configurations
+    #[serde(default)]
+    pub physical_time: bool,
+    /// Number of standard deviations above the moving mean of the time step duration
+    /// for a time step to be logged as slow, 3 by default
+    #[serde(default)]
+    pub outlier_sigma: Option<f64>,
+    /// Check of the solver process once the case is stalled
+    #[serde(default)]
+    pub liveness: Option<Liveness>,
+    /// Collector of the CPU and GPU utilization of the solver job, shown in the `utilization` column
+    #[serde(default)]
+    pub resources: Option<ResourceCollector>,
+    /// Number of time steps the logs of a glob may disagree by before a warning
+    #[serde(default)]
+    pub max_log_spread: Option<usize>,
+    /// Severity of the alerts of the case by kind, over the global ones
+    #[serde(default)]
+    pub severity: HashMap<AlertKind, Severity>,
+    /// Expected wall-clock duration of a time step in seconds, e.g. from sizing runs
+    #[serde(default)]
+    pub step_time: Option<f64>,
+    /// Drift in percent of the mean time step duration from `step_time` before a warning,
+    /// over the global one
+    #[serde(default)]
+    pub step_time_tolerance: Option<f64>,
+    /// Number of the latest time step duration samples the ETA is computed from, over the global one
+    #[serde(default)]
+    pub eta_window: Option<usize>,
+    /// Time in seconds without a new time step before the case is deemed stalled, over the global one,
+    /// e.g. shorter for a precursor
+    #[serde(default)]
+    pub stall_after: Option<u64>,
+    /// Post-processing stages of the case, over the global ones
+    #[serde(default)]
+    pub stages: Option<Vec<StageConfig>>,
+    /// Production case seeded by the case, a precursor
+    #[serde(default)]
+    pub precursor: Option<PrecursorConfig>,
+    /// Cases the case can only start after, e.g. the previous run of a restart chain
+    /// or the OS2 case whose fields initialize an OS7 case, the projected start and finish
+    /// of the case following theirs
+    #[serde(default)]
+    pub after: Vec<String>,
+    /// Pattern of the time step lines, for the solver versions logging them differently,
+    /// replacing the default pattern and the parser plugin
+    #[serde(default)]
+    pub time_step: Option<TimeStepPattern>,
+}
+
+impl CaseConfig {
+    /// Creates a new case configuration
+    pub fn new<S: ToString>(name: S, duration: usize, log: S) -> Self {
+        Self {
+            name: name.to_string(),
+            duration: SimSeconds(duration as f64),
+            log: log.to_string(),
+            ..Default::default()
+        }
+    }
+    /// Returns the simulation duration
+    pub fn sim_seconds(&self) -> SimSeconds {
+        self.duration
+    }
+    /// Returns the total number of time steps, `steps` or the count of the duration at the nominal rate
+    pub fn total_steps(&self) -> Steps {
+        self.steps.unwrap_or_else(|| self.sim_seconds().to_steps())
+    }
+    /// Returns the fields of the case that run commands on the monitor host,
+    /// refused for the cases added over HTTP
+    pub fn command_fields(&self) -> Vec<&'static str> {
+        let precursor = self.precursor.as_ref();
+        [
+            ("log_command", self.log_command.is_some()),
+            ("log_host", self.log_host.is_some()),
+            (
+                "resources",
+                matches!(self.resources, Some(ResourceCollector::Command(_))),
+            ),
+            (
+                "precursor.validate",
+                precursor.is_some_and(|precursor| precursor.validate.is_some()),
+            ),
+            (
+                "precursor.start",
+                precursor.is_some_and(|precursor| precursor.start.is_some()),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect()
+    }
+    /// Returns true if progress is computed from the simulated time
+    pub(crate) fn is_adaptive(&self) -> bool {
+        self.physical_time || self.time_stepping == TimeStepping::Adaptive
+    }
+    /// Checks that the case has a simulation duration, a single remote log source and,
+    /// with fixed time stepping, time steps
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.duration.0 <= 0. {
+            return Err(ConfigError::NoDuration(self.name.clone()));
+        }
+        if self.log_command.is_some() && self.log_host.is_some() {
+            return Err(ConfigError::ConflictingSources(self.name.clone()));
+        }
+        match self.steps {
+            Some(Steps(0)) if !self.is_adaptive() => Err(ConfigError::NoSteps(self.name.clone())),
+            _ => Ok(()),
+        }
+    }
+    /// Returns the time steps of the case if, with fixed time stepping, their size over the duration
+    /// is far from the one of the nominal rate, a warning only as some solvers are run at other rates
+    pub fn rate_warning(&self) -> Option<ConfigError> {
+        let steps = self.steps.filter(|_| !self.is_adaptive())?;
+        (!steps.is_consistent_with(self.sim_seconds())).then(|| ConfigError::InconsistentSteps {
+            case: self.name.clone(),
+            duration: self.sim_seconds(),
+            steps,
+        })
+    }
+}
+
+impl From<&CaseConfig> for Case {
+    fn from(config: &CaseConfig) -> Self {
+        let mut case = Case::new(&config.name, 0, &config.log);
+        case = if config.is_adaptive() {
+            case.duration_spec(config.sim_seconds().into())
+        } else {
+            case.duration_spec(config.total_steps().into())
+        };
+        if config.follow_latest {
+            case = case.follow_latest();
+        }
+        if let Some(command) = &config.log_command {
+            case = case.remote_log_source(CommandSource::new(command), command);
+        }
+        if let Some(url) = &config.log_object {
+            #[cfg(feature = "s3")]
+            match crate::S3Source::new(url) {
+                Ok(source) => case = case.remote_log_source(source, url),
+                Err(e) => tracing::error!(case = config.name, error = %e, "invalid log object"),
+            }
+            #[cfg(not(feature = "s3"))]
+            tracing::warn!(
+                case = config.name,
+                url,
+                "log objects require the `s3` feature"
+            );
+        }
+        if let Some(sigma) = config.outlier_sigma {
+            case = case.outlier_sigma(sigma);
+        }
+        if let Some(steps) = config.max_log_spread {
+            case = case.max_log_spread(steps);
+        }
+        if let Some(liveness) = &config.liveness {
+            case = case.liveness(liveness.clone());
+        }
+        if let Some(collector) = &config.resources {
+            case = case.resources(collector.clone());
+        }
+        if let Some(secs) = config.step_time {
+            case = case.expected_step_time(secs);
+        }
+        if let Some(percent) = config.step_time_tolerance {
+            case = case.step_time_tolerance(percent);
+        }
+        if let Some(samples) = config.eta_window {
+            case = case.eta_window(samples);
+        }
+        if let Some(precursor) = &config.precursor {
+            case = case.precursor(precursor.clone());
+        }
+        if !config.after.is_empty() {
+            case = case.after(&config.after);
+        }
+        if let Some(pattern) = &config.time_step {
+            case = case.parser(Arc::new(pattern.clone()));
+        }
+        if !config.severity.is_empty() {
+            case = case.severities(config.severity.clone());
+        }
+        if let Some(group) = &config.group {
+            case = case.group(group);
+        }
+        if let Some(cores) = config.cores {
+            case = case.n_core(cores);
+        }
+        if config.start.is_some() || config.job.is_some() {
+            case = case.queued(config.start, config.job.clone());
+        }
+        if let Some(deadline) = config.deadline {
+            case = case.deadline(deadline);
+        }
+        if let Some(priority) = config.priority {
+            case = case.priority(priority);
+        }
+        case
+    }
+}
+
+/// Monitor configuration
+///
+/// ```toml
+/// root = "/shared"
+/// stall_after = 900
+///
+/// [[cases]]
+/// name = "zen30az045_OS2"
+/// duration = 1200
+/// log = "solve-672_14.out"
+/// group = "zen30"
+/// cores = 672
+/// deadline = "2024-06-14 18:00"
+/// priority = 10
+/// time_stepping = "adaptive"
+/// step_time = 42.5
+///
+/// [cases.severity]
+/// slowing = "info"
+///
+/// [[cases]]
+/// name = "zen00az000_OS1"
+/// duration = 600
+/// log = "solve-288_02.out"
+/// time_step = { pattern = 'Time Step\s+(\d+)\s+Time\s*=\s*(\S+)' }
+///
+/// [hooks]
+/// completed = "extract_dome_seeing.sh $CFD_CASE"
+///
+/// [[planned]]
+/// name = "zen30az180_OS2"
+/// duration = 1200
+/// log = "solve-672_20.out"
+/// group = "zen30"
+/// after = ["zen30az045_OS2"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    /// CFD cases to monitor, including the ones of the campaigns once loaded
+    #[serde(default)]
+    pub cases: Vec<CaseConfig>,
+    /// Templates of the cases of whole campaigns
+    #[serde(default)]
+    pub campaigns: Vec<CampaignConfig>,
+    /// Cases not submitted yet, started in order as the slots free up
+    #[serde(default)]
+    pub planned: Vec<CaseConfig>,
+    /// Number of cases run at once, the number of running cases by default
+    pub slots: Option<usize>,
+    /// Directory the case directories are in
+    #[serde(default = "default_root")]
+    pub root: PathBuf,
+    /// Time in seconds between 2 status updates
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    /// Time in seconds without a new time step before a case is deemed stalled
+    #[serde(default = "default_stall_after")]
+    pub stall_after: u64,
+    /// Number of consecutive update failures of a case before the monitor aborts
+    #[serde(default = "default_max_failures")]
+    pub max_failures: usize,
+    /// Time in seconds log modifications are batched over in watch mode
+    #[serde(default = "default_debounce")]
+    pub debounce: u64,
+    /// Shell commands executed on case events
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Planned outages during which stall and I/O alerts are expected
+    #[serde(default)]
+    pub maintenance: Vec<MaintenanceWindow>,
+    /// Recurring windows the cluster pauses the jobs in, skipped over by the ETAs
+    #[serde(default)]
+    pub blackouts: Vec<BlackoutWindow>,
+    /// Severity of the alerts of all the cases by kind
+    #[serde(default)]
+    pub severity: HashMap<AlertKind, Severity>,
+    /// Drift in percent of the mean time step duration from the expected one before a warning,
+    /// 20 by default
+    #[serde(default)]
+    pub step_time_tolerance: Option<f64>,
+    /// Number of the latest time step duration samples the ETA is computed from, 50 by default,
+    /// the mean over the whole simulation being used if 0
+    #[serde(default)]
+    pub eta_window: Option<usize>,
+    /// Business days calendar ETAs are annotated with
+    pub calendar: Option<BusinessCalendar>,
+    /// Disk usage tracking of the case directories
+    pub disk: Option<DiskConfig>,
+    /// Post-processing stages run once the simulation of each case completes
+    #[serde(default)]
+    pub stages: Vec<StageConfig>,
+    /// Capture of the log lines around the anomalies of the cases
+    pub excerpts: Option<ExcerptConfig>,
+    /// Heartbeat of the monitor for an external watchdog
+    pub heartbeat: Option<HeartbeatConfig>,
+    /// Publication of the snapshots to the central instance of several clusters
+    pub aggregation: Option<AggregationConfig>,
+    /// Limits of the log reads for parallel and networked filesystems
+    #[serde(default)]
+    pub filesystem: FilesystemConfig,
+    /// Command the logs of the cases with a `log_host` are read through,
+    /// called with `--`, the host and the remote script
+    #[serde(default = "default_ssh")]
+    pub ssh: String,
+    /// Log line patterns marking the milestones of the simulations
+    #[serde(default = "MilestonePattern::defaults")]
+    pub milestones: Vec<MilestonePattern>,
+    /// Log line patterns announcing the phases of the solver initialization
+    #[serde(default = "InitPhase::defaults")]
+    pub init_phases: Vec<InitPhase>,
+    /// Log line patterns of the fatal solver errors, failing the cases
+    #[serde(default = "ErrorPattern::defaults")]
+    pub error_patterns: Vec<ErrorPattern>,
+    /// Log line failure signatures mapped to the triage hints shown next to failed cases
+    #[serde(default = "TriageRule::defaults")]
+    pub triage: Vec<TriageRule>,
+    /// Log line pattern giving the solver version, checked for consistency within each group
+    #[serde(default)]
+    pub version_pattern: VersionPattern,
+    /// Log line pattern giving the time step size, the time steps left being rescaled when it changes
+    #[serde(default)]
+    pub dt_pattern: DtPattern,
+    /// Log line pattern of the checkpoint saves, the latest one being the one to restart a failed case from
+    #[serde(default)]
+    pub checkpoint_pattern: CheckpointPattern,
+    /// File the records of the completed cases are appended to, none if empty
+    #[serde(default = "default_records")]
+    pub records: String,
+    /// File every state transition of the cases and of the monitor is appended to
+    /// as a JSON line, none if empty
+    #[serde(default = "default_events")]
+    pub events: String,
+    /// File the notes of the operators on the cases are kept in, none if empty
+    #[serde(default = "default_notes")]
+    pub notes: String,
+    /// SQLite database every update and event is stored in, requires the `db` feature
+    #[serde(default)]
+    pub database: Option<PathBuf>,
+    /// Redaction of the status shared outside of the project network
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// Progress and ETA notifications to the operator
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    /// Export of the status snapshots
+    #[serde(default)]
+    pub export: ExportConfig,
+    /// Authentication and TLS of the HTTP server of the status
+    #[serde(default)]
+    pub http: HttpConfig,
+    /// Custom log parser and snapshot sinks
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    /// Presentation of the ETAs, globally and per view
+    #[serde(default)]
+    pub eta: EtaConfig,
+    /// Columns of the status table, the case name always coming first
+    #[serde(default)]
+    pub columns: Option<Vec<Column>>,
+    /// Column the rows of the status table are sorted by, `-column` for a decreasing order,
+    /// increasing slack to the deadline by default, `attention` for the failed, stalled,
+    /// late and highest priority cases first
+    #[serde(default)]
+    pub sort_by: Option<SortKey>,
+    /// Width of the status table in characters, the width of the terminal by default
+    /// on a terminal, the names being truncated and the columns not fitting dropped
+    #[serde(default)]
+    pub table_width: Option<usize>,
+    /// Wraps the columns not fitting the table width to a second line per case
+    /// instead of dropping them
+    #[serde(default)]
+    pub wrap_columns: bool,
+    /// Tabs of the status board, all the cases and a tab per campaign by default
+    #[serde(default)]
+    pub tabs: Vec<TabConfig>,
+    /// Colors of the status table rows
+    #[serde(default)]
+    pub theme: Theme,
+    /// Display profiles by name, added to or replacing the built-in `operator`, `summary`
+    /// and `pi` profiles
+    #[serde(default)]
+    pub profiles: BTreeMap<String, DisplayProfile>,
+    /// Display profile of the status board at the start, the configured columns without it
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Weights of the campaign health index
+    #[serde(default)]
+    pub health: HealthConfig,
+}
+
+fn default_ssh() -> String {
+    SSH_COMMAND.to_string()
+}
+
+fn default_root() -> PathBuf {
+    PathBuf::from(ROOT)
+}
+
+fn default_interval() -> u64 {
+    UPDATE_TIME as u64
+}
+
+fn default_stall_after() -> u64 {
+    STALL_AFTER
+}
+
+fn default_max_failures() -> usize {
+    MAX_FAILURES
+}
+
+fn default_debounce() -> u64 {
+    DEBOUNCE
+}
+
+fn default_records() -> String {
+    RECORDS.to_string()
+}
+
+fn default_events() -> String {
+    EVENTS.to_string()
+}
+
+fn default_notes() -> String {
+    NOTES.to_string()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cases: vec![],
+            campaigns: vec![],
+            planned: vec![],
+            slots: None,
+            root: default_root(),
+            interval: default_interval(),
+            stall_after: STALL_AFTER,
+            max_failures: MAX_FAILURES,
+            debounce: DEBOUNCE,
+            hooks: Default::default(),
+            maintenance: vec![],
+            blackouts: vec![],
+            severity: HashMap::new(),
+            step_time_tolerance: None,
+            eta_window: None,
+            calendar: None,
+            disk: None,
+            stages: vec![],
+            excerpts: None,
+            heartbeat: None,
+            aggregation: None,
+            filesystem: Default::default(),
+            ssh: default_ssh(),
+            milestones: MilestonePattern::defaults(),
+            init_phases: InitPhase::defaults(),
+            error_patterns: ErrorPattern::defaults(),
+            triage: TriageRule::defaults(),
+            version_pattern: Default::default(),
+            dt_pattern: Default::default(),
+            checkpoint_pattern: Default::default(),
+            records: default_records(),
+            events: default_events(),
+            notes: default_notes(),
+            database: None,
+            redaction: Default::default(),
+            notify: Default::default(),
+            export: Default::default(),
+            http: Default::default(),
+            plugins: Default::default(),
+            eta: Default::default(),
+            columns: None,
+            sort_by: None,
+            table_width: None,
+            wrap_columns: false,
+            tabs: vec![],
+            theme: Default::default(),
+            profiles: BTreeMap::new(),
+            profile: None,
+            health: Default::default(),
+        }
+    }
+}
+
+/// Cases added, removed or changed between two configurations, by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl ConfigDiff {
+    /// Returns true if the cases are the same
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl std::fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no case changed");
+        }
+        let parts: Vec<_> = [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("changed", &self.changed),
+        ]
+        .into_iter()
+        .filter(|(_, cases)| !cases.is_empty())
+        .map(|(kind, cases)| format!("{kind} {}", cases.join(", ")))
+        .collect();
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+impl Config {
+    /// Loads the configuration from a TOML file
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+    /// Loads the configuration from a TOML file without validating the cases
+    pub fn from_path_unchecked<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::from_toml_unchecked(&fs::read_to_string(path)?)
+    }
+    /// Loads the configuration from TOML, the cases of the campaigns being added to the others
+    pub fn from_toml(content: &str) -> Result<Self, ConfigError> {
+        let config = Self::from_toml_unchecked(content)?;
+        config.validate()?;
+        config.log_warnings();
+        Ok(config)
+    }
+    /// Loads the configuration from TOML without validating the cases,
+    /// e.g. for [Preflight](crate::Preflight) to report all their problems at once
+    pub fn from_toml_unchecked(content: &str) -> Result<Self, ConfigError> {
+        let mut config: Self = toml::from_str(content)?;
+        let campaigns: Vec<_> = config
+            .campaigns
+            .iter()
+            .flat_map(CampaignConfig::cases)
+            .collect();
+        for case in campaigns {
+            if config.cases.iter().all(|other| other.name != case.name) {
+                config.cases.push(case);
+            }
+        }
+        Ok(config)
+    }
+    /// Loads the configuration from the `CFD_STATUS_*` environment variables
+    ///
+    /// `CFD_STATUS_CONFIG` is the path to a mounted configuration file, otherwise the cases
+    /// are given by `CFD_STATUS_CASES` as comma-separated `name:duration:log[:group]`.
+    /// `CFD_STATUS_ROOT`, `CFD_STATUS_INTERVAL`, `CFD_STATUS_STALL_AFTER`,
+    /// `CFD_STATUS_MAX_FAILURES`, `CFD_STATUS_RECORDS`, `CFD_STATUS_EVENTS`,
+    /// `CFD_STATUS_NOTES` and `CFD_STATUS_HTTP_TOKEN`, the `[http] token`,
+    /// override the corresponding settings.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let config = Self::from_env_unchecked()?;
+        config.validate()?;
+        config.log_warnings();
+        Ok(config)
+    }
+    /// Loads the configuration from the `CFD_STATUS_*` environment variables as [Config::from_env]
+    /// without validating the cases, e.g. for [Preflight](crate::Preflight)
+    pub fn from_env_unchecked() -> Result<Self, ConfigError> {
+        let mut config = match env::var("CFD_STATUS_CONFIG") {
+            Ok(path) => Self::from_path_unchecked(path)?,
+            Err(_) => Self::default(),
+        };
+        if let Ok(cases) = env::var("CFD_STATUS_CASES") {
+            config.cases = cases
+                .split(',')
+                .map(str::trim)
+                .filter(|case| !case.is_empty())
+                .map(|case| {
+                    let invalid = || ConfigError::Env("CFD_STATUS_CASES", case.to_string());
+                    let fields: Vec<_> = case.split(':').collect();
+                    let [name, duration, log, group @ ..] = fields.as_slice() else {
+                        return Err(invalid());
+                    };
+                    let mut config = CaseConfig::new(*name, 0, *log);
+                    config.duration = duration.parse().map_err(|_| invalid())?;
+                    config.group = group.first().map(|group| group.to_string());
+                    Ok(config)
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        fn var<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+            env::var(name)
+                .ok()
+                .map(|value| value.parse().map_err(|_| ConfigError::Env(name, value)))
+                .transpose()
+        }
+        if let Some(root) = var("CFD_STATUS_ROOT")? {
+            config.root = root;
+        }
+        if let Some(interval) = var("CFD_STATUS_INTERVAL")? {
+            config.interval = interval;
+        }
+        if let Some(stall_after) = var("CFD_STATUS_STALL_AFTER")? {
+            config.stall_after = stall_after;
+        }
+        if let Some(max_failures) = var("CFD_STATUS_MAX_FAILURES")? {
+            config.max_failures = max_failures;
+        }
+        if let Some(records) = var("CFD_STATUS_RECORDS")? {
+            config.records = records;
+        }
+        if let Some(events) = var("CFD_STATUS_EVENTS")? {
+            config.events = events;
+        }
+        if let Some(notes) = var("CFD_STATUS_NOTES")? {
+            config.notes = notes;
+        }
+        if let Some(token) = var("CFD_STATUS_HTTP_TOKEN")? {
+            config.http.token = Some(token);
+        }
+        Ok(config)
+    }
+    /// Checks that the cases have a unique name, a simulation duration
+    /// and a time step count consistent with it, and run after known cases without cycles,
+    /// and that the display profile is known, returning the first of the [problems](Config::problems)
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self.problems().into_iter().next() {
+            Some((_, e)) => Err(e),
+            None => Ok(()),
+        }
+    }
+    /// Returns all the problems of the configuration, with the index of the case
+    /// of each among the cases, if of one of them
+    pub(crate) fn problems(&self) -> Vec<(Option<usize>, ConfigError)> {
+        let mut problems = vec![];
+        let mut names = HashSet::new();
+        for (i, case) in self.cases.iter().enumerate() {
+            let problem = if case.name.is_empty() {
+                Err(ConfigError::NoName)
+            } else if !names.insert(&case.name) {
+                Err(ConfigError::Duplicate(case.name.clone()))
+            } else {
+                case.validate()
+            };
+            if let Err(e) = problem {
+                problems.push((Some(i), e));
+            }
+        }
+        for (name, e) in self.dependency_problems() {
+            let i = self.cases.iter().position(|case| case.name == name);
+            problems.push((i, e));
+        }
+        if let Some(name) = self.profile.as_deref() {
+            if self.display_profile(name).is_none() {
+                problems.push((None, ConfigError::UnknownProfile(name.to_string())));
+            }
+        }
+        problems
+    }
+    /// Returns the suspicious settings the configuration is loaded with anyway: time step counts
+    /// far from the nominal rate over the duration, and durations far above the median one,
+    /// e.g. typed as a time step count
+    pub fn warnings(&self) -> Vec<ConfigError> {
+        let mut warnings: Vec<_> = self
+            .cases
+            .iter()
+            .filter_map(CaseConfig::rate_warning)
+            .collect();
+        let mut durations: Vec<_> = self.cases.iter().map(|case| case.duration.0).collect();
+        durations.sort_by(f64::total_cmp);
+        if let Some(&median) = durations.get(durations.len() / 2) {
+            let median = SimSeconds(median);
+            warnings.extend(
+                self.cases
+                    .iter()
+                    .filter(|case| case.duration.0 > median.0 * MAX_RATE_RATIO)
+                    .map(|case| ConfigError::OutlierDuration {
+                        case: case.name.clone(),
+                        duration: case.duration,
+                        median,
+                    }),
+            );
+        }
+        warnings
+    }
+    /// Logs the [warnings](Config::warnings) of the configuration
+    fn log_warnings(&self) {
+        for warning in self.warnings() {
+            tracing::warn!(%warning, "suspicious configuration");
+        }
+    }
+    /// Returns the cases and the planned cases running after unknown cases or after themselves,
+    /// with their problems
+    fn dependency_problems(&self) -> Vec<(&str, ConfigError)> {
+        let cases: HashMap<&str, &CaseConfig> = self
+            .cases
+            .iter()
+            .chain(&self.planned)
+            .map(|case| (case.name.as_str(), case))
+            .collect();
+        let mut problems = vec![];
+        for case in self.cases.iter().chain(&self.planned) {
+            if let Some(after) = case
+                .after
+                .iter()
+                .find(|after| !cases.contains_key(after.as_str()))
+            {
+                problems.push((
+                    case.name.as_str(),
+                    ConfigError::UnknownPredecessor {
+                        case: case.name.clone(),
+                        after: after.clone(),
+                    },
+                ));
+            }
+        }
+        // the cases are removed once their predecessors all are, the cases left depend on a cycle
+        let mut left: Vec<&CaseConfig> = cases.values().copied().collect();
+        left.sort_by(|a, b| a.name.cmp(&b.name));
+        loop {
+            let before = left.len();
+            let names: HashSet<&str> = left.iter().map(|case| case.name.as_str()).collect();
+            left.retain(|case| {
+                case.after
+                    .iter()
+                    .any(|after| names.contains(after.as_str()))
+            });
+            if left.len() == before {
+                break;
+            }
+        }
+        let Some(mut case) = left.first().copied() else {
+            return problems;
+        };
+        // following the predecessors left for as many cases leads into the cycle
+        for _ in 0..left.len() {
+            case = left
+                .iter()
+                .copied()
+                .find(|other| case.after.contains(&other.name))
+                .expect("a case left runs after another case left");
+        }
+        problems.push((
+            case.name.as_str(),
+            ConfigError::DependencyCycle(case.name.clone()),
+        ));
+        problems
+    }
+    /// Returns the display profile of the given name, configured or built-in
+    pub fn display_profile(&self, name: &str) -> Option<DisplayProfile> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .or_else(|| DisplayProfile::builtin(name))
+    }
+    /// Returns the display profiles, the built-in ones first, in the order they are
+    /// switched through
+    pub fn display_profiles(&self) -> Vec<(String, DisplayProfile)> {
+        let configured = self
+            .profiles
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !PROFILES.contains(name));
+        PROFILES
+            .into_iter()
+            .chain(configured)
+            .filter_map(|name| Some((name.to_string(), self.display_profile(name)?)))
+            .collect()
+    }
+    /// Returns the cases added, removed or changed by the `newer` configuration
+    ///
+    /// A case is changed if any of its settings differs, its statistics being reset on reload
+    /// only if its duration or log differs, as [Case::same_definition] tells
+    pub fn diff(&self, newer: &Config) -> ConfigDiff {
+        let mut diff = ConfigDiff::default();
+        for case in &newer.cases {
+            match self.cases.iter().find(|old| old.name == case.name) {
+                None => diff.added.push(case.name.clone()),
+                Some(old) if old != case => diff.changed.push(case.name.clone()),
+                Some(_) => (),
+            }
+        }
+        diff.removed = self
+            .cases
+            .iter()
+            .filter(|old| newer.cases.iter().all(|case| case.name != old.name))
+            .map(|old| old.name.clone())
+            .collect();
+        diff
+    }
+    /// Returns the configured cases
+    pub fn cases(&self) -> Vec<Case> {
+        let notes = self.notes();
+        self.cases
+            .iter()
+            .map(|config| {
+                let mut case = self.case(config);
+                case.set_note(notes.get(&config.name).cloned());
+                case
+            })
+            .collect()
+    }
+    /// Returns the notes of the operators on the cases, none without notes file
+    pub fn notes(&self) -> Notes {
+        if self.notes.is_empty() {
+            return Notes::default();
+        }
+        Notes::read(&self.notes)
+    }
+    /// Returns the columns of the status table and the order of its rows
+    pub fn column_spec(&self) -> ColumnSpec {
+        ColumnSpec::new(
+            self.columns
+                .clone()
+                .unwrap_or_else(|| DEFAULT_COLUMNS.to_vec()),
+            self.sort_by.unwrap_or_default(),
+        )
+        .with_width(self.table_width, self.wrap_columns)
+    }
+    /// Returns the planned cases not monitored yet, in order
+    pub fn planned(&self) -> Vec<Case> {
+        self.planned
+            .iter()
+            .filter(|planned| self.cases.iter().all(|case| case.name != planned.name))
+            .map(|config| self.case(config))
+            .collect()
+    }
+    /// Returns a case with the monitor-wide settings of the configuration applied
+    pub fn case(&self, config: &CaseConfig) -> Case {
+        let mut severities = self.severity.clone();
+        severities.extend(&config.severity);
+        let mut case = Case::from(config);
+        if let Some(percent) = self
+            .step_time_tolerance
+            .filter(|_| config.step_time_tolerance.is_none())
+        {
+            case = case.step_time_tolerance(percent);
+        }
+        if let Some(samples) = self.eta_window.filter(|_| config.eta_window.is_none()) {
+            case = case.eta_window(samples);
+        }
+        if let Some(excerpts) = &self.excerpts {
+            case = case.excerpt_lines(excerpts.lines);
+        }
+        case = case
+            .severities(severities)
+            .read_options(self.filesystem.read_options())
+            .stages(config.stages.clone().unwrap_or_else(|| self.stages.clone()))
+            .root_dir(&self.root)
+            .stall_after(config.stall_after.unwrap_or(self.stall_after))
+            .milestones(self.milestones.clone())
+            .init_phases(self.init_phases.clone())
+            .error_patterns(self.error_patterns.clone())
+            .blackouts(self.blackouts.clone())
+            .triage_rules(self.triage.clone())
+            .version_pattern(self.version_pattern.clone())
+            .dt_pattern(self.dt_pattern.clone())
+            .checkpoint_pattern(self.checkpoint_pattern.clone());
+        if let Some(host) = &config.log_host {
+            let log = case.log_file();
+            let origin = format!("{host}:{log}");
+            case = case.remote_log_source(RemoteSource::new(host, &log).ssh(&self.ssh), origin);
+        }
+        case
+    }
+}
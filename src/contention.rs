@@ -0,0 +1,150 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+
+/// Default number of standard deviations above the mean for a time step to be an outlier
+pub const OUTLIER_SIGMA: f64 = 3.;
+/// Weight of the latest time step duration in the moving mean and variance
+const EWMA_ALPHA: f64 = 0.1;
+/// Number of updates before outliers are looked for
+const WARMUP: usize = 5;
+/// Smallest standard deviation as a fraction of the mean, so steady time steps do not flag noise
+const MIN_RELATIVE_SIGMA: f64 = 0.05;
+/// Number of slow intervals kept per case
+const SLOW_INTERVAL_LEN: usize = 100;
+
+/// Interval of consecutive time steps much slower than usual,
+/// e.g. while the solver waits for a license or for the filesystem
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlowInterval {
+    pub from_step: usize,
+    pub to_step: usize,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    /// Mean time step duration in seconds before the interval
+    pub expected: f64,
+}
+
+impl SlowInterval {
+    /// Returns the mean time step duration over the interval in seconds
+    pub fn step_time(&self) -> f64 {
+        (self.end - self.start).num_milliseconds() as f64
+            / 1e3
+            / self.to_step.saturating_sub(self.from_step).max(1) as f64
+    }
+}
+
+impl Display for SlowInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "steps {}-{} from {}: {:.2}s per step, expected {:.2}s",
+            self.from_step,
+            self.to_step,
+            self.start.format("%H:%M:%S"),
+            self.step_time(),
+            self.expected
+        )
+    }
+}
+
+/// Detector of the time step durations far above their exponentially weighted moving average
+#[derive(Debug, Clone)]
+pub struct ContentionDetector {
+    sigma: f64,
+    mean: f64,
+    variance: f64,
+    sample: usize,
+    intervals: VecDeque<SlowInterval>,
+    count: usize,
+}
+
+impl Default for ContentionDetector {
+    fn default() -> Self {
+        Self::new(OUTLIER_SIGMA)
+    }
+}
+
+impl ContentionDetector {
+    /// Creates a detector of the time steps more than `sigma` standard deviations above the mean
+    pub fn new(sigma: f64) -> Self {
+        Self {
+            sigma,
+            mean: 0.,
+            variance: 0.,
+            sample: 0,
+            intervals: VecDeque::new(),
+            count: 0,
+        }
+    }
+    /// Returns the number of standard deviations above the mean of the outliers
+    pub(crate) fn sigma(&self) -> f64 {
+        self.sigma
+    }
+    /// Sets the number of standard deviations above the mean, keeping the statistics
+    pub(crate) fn set_sigma(&mut self, sigma: f64) {
+        self.sigma = sigma;
+    }
+    /// Adds the time steps from `from_step` to `to_step` that lasted `step_time` seconds each,
+    /// returns true if they are outliers
+    ///
+    /// Outliers right after a slow interval extend it
+    pub fn update(&mut self, from_step: usize, to_step: usize, step_time: f64) -> bool {
+        let sigma = self.variance.sqrt().max(MIN_RELATIVE_SIGMA * self.mean);
+        let outlier = self.sample >= WARMUP && step_time > self.mean + self.sigma * sigma;
+        if outlier {
+            let end = Local::now();
+            let start = end
+                - Duration::milliseconds((step_time * (to_step - from_step) as f64 * 1e3) as i64);
+            match self.intervals.back_mut() {
+                Some(interval) if interval.to_step == from_step => {
+                    interval.to_step = to_step;
+                    interval.end = end;
+                }
+                _ => {
+                    if self.intervals.len() == SLOW_INTERVAL_LEN {
+                        self.intervals.pop_front();
+                    }
+                    self.intervals.push_back(SlowInterval {
+                        from_step,
+                        to_step,
+                        start,
+                        end,
+                        expected: self.mean,
+                    });
+                    self.count += 1;
+                }
+            }
+        }
+        // outliers only move the mean, so a sustained slowdown is not hidden
+        // by the variance of its first time steps but ends up being the norm
+        if self.sample == 0 {
+            self.mean = step_time;
+        } else {
+            let diff = step_time - self.mean;
+            let increment = EWMA_ALPHA * diff;
+            self.mean += increment;
+            if !outlier {
+                self.variance = (1. - EWMA_ALPHA) * (self.variance + diff * increment);
+            }
+        }
+        self.sample += 1;
+        outlier
+    }
+    /// Restarts the moving mean and variance, keeping the slow intervals found so far
+    pub fn rebase(&mut self) -> &mut Self {
+        self.mean = 0.;
+        self.variance = 0.;
+        self.sample = 0;
+        self
+    }
+    /// Returns the latest slow intervals, from oldest to latest
+    pub fn intervals(&self) -> impl Iterator<Item = &SlowInterval> {
+        self.intervals.iter()
+    }
+    /// Returns the number of slow intervals found so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
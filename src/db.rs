@@ -0,0 +1,448 @@
+use std::{fmt::Display, path::Path};
+
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection};
+
+use crate::{
+    cores_from_log, format_hours_minutes, ArchivedCase, Case, MonitorEvent, StallIncident,
+    ThroughputCalendar,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cases (
+    name TEXT PRIMARY KEY,
+    campaign TEXT,
+    log TEXT NOT NULL,
+    total_step INTEGER NOT NULL,
+    first_seen TEXT NOT NULL,
+    completed TEXT
+);
+CREATE TABLE IF NOT EXISTS samples (
+    name TEXT NOT NULL REFERENCES cases(name),
+    at TEXT NOT NULL,
+    step INTEGER NOT NULL,
+    time REAL NOT NULL,
+    step_time REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS events (
+    name TEXT NOT NULL,
+    at TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    detail TEXT
+);
+CREATE TABLE IF NOT EXISTS archive (
+    name TEXT PRIMARY KEY,
+    campaign TEXT,
+    log TEXT NOT NULL,
+    cores INTEGER,
+    total_step INTEGER NOT NULL,
+    first_seen TEXT NOT NULL,
+    completed TEXT NOT NULL,
+    steps INTEGER NOT NULL,
+    stalls INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS archived_samples (
+    name TEXT NOT NULL,
+    at TEXT NOT NULL,
+    step INTEGER NOT NULL,
+    time REAL NOT NULL,
+    step_time REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS samples_name ON samples(name);
+CREATE INDEX IF NOT EXISTS events_name ON events(name);
+";
+
+/// Copies the summary of the completed cases, all of them or only `?1`, with `?2` cores, to the archive
+const ARCHIVE: &str = "
+INSERT OR REPLACE INTO archive
+    (name, campaign, log, cores, total_step, first_seen, completed, steps, stalls)
+SELECT c.name, c.campaign, c.log, ?2, c.total_step, c.first_seen, c.completed,
+       COALESCE(MAX(s.step) - MIN(s.step), 0),
+       (SELECT COUNT(*) FROM events e WHERE e.name = c.name AND e.kind = 'stalled')
+FROM cases c LEFT JOIN samples s ON s.name = c.name
+WHERE c.completed IS NOT NULL AND (?1 IS NULL OR c.name = ?1)
+GROUP BY c.name
+";
+/// Moves the samples of the completed cases, all of them or only `?1`, to the archive
+const ARCHIVE_SAMPLES: &str = "
+INSERT INTO archived_samples (name, at, step, time, step_time)
+SELECT s.name, s.at, s.step, s.time, s.step_time
+FROM samples s JOIN cases c ON c.name = s.name
+WHERE c.completed IS NOT NULL AND (?1 IS NULL OR c.name = ?1)
+ORDER BY s.rowid
+";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DbError {
+    #[error("SQLite database error")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("invalid timestamp in the database")]
+    Timestamp(#[from] chrono::ParseError),
+}
+
+/// SQLite database of the case updates and events of all the monitoring sessions
+///
+/// The `cases` table has one row per case, `samples` one row per update with a time step
+/// and `events` one row per stall, slow interval, time step change, completion, disk alert or update failure
+///
+/// The completed cases are moved from `cases` to `archive` with their summary and their samples
+/// to `archived_samples`, for the campaign reports of `cfd-status summary`
+pub struct RunDatabase {
+    conn: Connection,
+}
+
+impl RunDatabase {
+    /// Opens the database at `path`, creating it and its tables if needed,
+    /// and archives the cases completed before the archive existed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DbError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        let db = Self { conn };
+        db.archive(None, None)?;
+        Ok(db)
+    }
+    /// Stores a monitor event, snapshots are not stored
+    pub fn record(&self, event: &MonitorEvent) -> Result<(), DbError> {
+        let now = Local::now().to_rfc3339();
+        let (kind, case, detail) = match event {
+            MonitorEvent::Updated(case) => {
+                let Some(step) = case.step() else {
+                    return Ok(());
+                };
+                if case.is_complete() && self.is_archived(case.name())? {
+                    return Ok(());
+                }
+                self.upsert(case)?;
+                self.conn.execute(
+                    "INSERT INTO samples (name, at, step, time, step_time) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![case.name(), now, step as i64, case.time(), case.elapsed_per_step().mean()],
+                )?;
+                return Ok(());
+            }
+            MonitorEvent::Stalled(case) => ("stalled", case, None),
+            MonitorEvent::SolverDead(case) => ("solver_dead", case, None),
+            MonitorEvent::Completed(case) => {
+                self.upsert(case)?;
+                self.archive(Some(case.name()), case.cores())?;
+                ("completed", case, None)
+            }
+            MonitorEvent::SlowInterval(case) => (
+                "slow_interval",
+                case,
+                case.slow_intervals()
+                    .last()
+                    .map(|interval| interval.to_string()),
+            ),
+            MonitorEvent::DtChanged(case) => (
+                "dt_changed",
+                case,
+                case.dt_changes().last().map(|change| change.to_string()),
+            ),
+            MonitorEvent::LogMoved(case) => (
+                "log_moved",
+                case,
+                case.log_relocations()
+                    .last()
+                    .map(|relocation| relocation.to_string()),
+            ),
+            MonitorEvent::DiskAtRisk(case) => ("disk_at_risk", case, None),
+            MonitorEvent::Error {
+                case,
+                error,
+                failures,
+            } => ("error", case, Some(format!("{error} ({failures}x)"))),
+            MonitorEvent::Snapshot(_) => return Ok(()),
+        };
+        self.conn.execute(
+            "INSERT INTO events (name, at, kind, detail) VALUES (?1, ?2, ?3, ?4)",
+            params![case.name(), now, kind, detail],
+        )?;
+        Ok(())
+    }
+    /// Inserts the case or updates its definition and completion
+    fn upsert(&self, case: &Case) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO cases (name, campaign, log, total_step, first_seen, completed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(name) DO UPDATE SET
+                campaign = excluded.campaign,
+                log = excluded.log,
+                total_step = excluded.total_step,
+                completed = excluded.completed",
+            params![
+                case.name(),
+                case.group_name(),
+                case.log_file(),
+                case.total_step() as i64,
+                Local::now().to_rfc3339(),
+                case.completion().map(|completed| completed.to_rfc3339()),
+            ],
+        )?;
+        Ok(())
+    }
+    /// Moves the completed cases, all of them or only `name`, to the archive
+    fn archive(&self, name: Option<&str>, cores: Option<usize>) -> Result<(), DbError> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(ARCHIVE, params![name, cores.map(|cores| cores as i64)])?;
+        transaction.execute(ARCHIVE_SAMPLES, params![name])?;
+        transaction.execute(
+            "DELETE FROM samples WHERE name IN
+                (SELECT name FROM cases WHERE completed IS NOT NULL AND (?1 IS NULL OR name = ?1))",
+            params![name],
+        )?;
+        transaction.execute(
+            "DELETE FROM cases WHERE completed IS NOT NULL AND (?1 IS NULL OR name = ?1)",
+            params![name],
+        )?;
+        Ok(transaction.commit()?)
+    }
+    /// Returns true if the case is in the archive
+    fn is_archived(&self, name: &str) -> Result<bool, DbError> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM archive WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+    /// Returns the names of the cases in the database, archived or not
+    pub fn case_names(&self) -> Result<Vec<String>, DbError> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT name FROM cases UNION SELECT name FROM archive ORDER BY name")?;
+        let names = statement.query_map([], |row| row.get(0))?;
+        Ok(names.collect::<Result<_, _>>()?)
+    }
+    /// Removes a case with its samples and events
+    pub fn remove_case(&self, name: &str) -> Result<(), DbError> {
+        for table in ["samples", "archived_samples", "events", "cases", "archive"] {
+            self.conn
+                .execute(&format!("DELETE FROM {table} WHERE name = ?1"), [name])?;
+        }
+        Ok(())
+    }
+    /// Returns the calendar of the simulated seconds completed per day by all the cases, archived or not,
+    /// each sample counting the progress since the previous sample of its case
+    pub fn throughput_calendar(&self) -> Result<ThroughputCalendar, DbError> {
+        // the samples of an archived case precede those of a later run of the case
+        let mut statement = self.conn.prepare(
+            "SELECT name, at, time FROM (
+                SELECT name, at, time, 0 AS live, rowid AS id FROM archived_samples
+                UNION ALL SELECT name, at, time, 1, rowid FROM samples
+             ) ORDER BY name, live, id",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, f64>(2)?,
+            ))
+        })?;
+        let mut days: Vec<(NaiveDate, f64)> = vec![];
+        let mut previous: Option<(String, f64)> = None;
+        for row in rows {
+            let (name, at, time) = row?;
+            let day = DateTime::parse_from_rfc3339(&at)?
+                .with_timezone(&Local)
+                .date_naive();
+            // a restart sets the simulated time back
+            let secs = match &previous {
+                Some((case, before)) if *case == name => (time - before).max(0.),
+                _ => 0.,
+            };
+            days.push((day, secs));
+            previous = Some((name, time));
+        }
+        Ok(ThroughputCalendar::new(days))
+    }
+    /// Returns the archived cases, by first sighting
+    pub fn archived(&self) -> Result<Vec<ArchivedCase>, DbError> {
+        let mut statement = self.conn.prepare(
+            "SELECT name, campaign, log, cores, total_step, first_seen, completed, steps, stalls
+             FROM archive ORDER BY first_seen",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, i64>(7)?,
+                row.get::<_, i64>(8)?,
+            ))
+        })?;
+        rows.map(|row| {
+            let (name, campaign, log, cores, total_step, first_seen, completed, steps, stalls) =
+                row?;
+            Ok(ArchivedCase {
+                name,
+                campaign,
+                // the cases archived on opening have no core count
+                cores: cores
+                    .map(|cores| cores as usize)
+                    .or_else(|| cores_from_log(&log)),
+                total_step: total_step as usize,
+                first_seen: timestamp(&first_seen)?,
+                completed: timestamp(&completed)?,
+                steps: steps as usize,
+                stalls: stalls as usize,
+            })
+        })
+        .collect()
+    }
+    /// Returns the stalls of the archived cases, oldest first
+    pub fn stall_incidents(&self) -> Result<Vec<StallIncident>, DbError> {
+        let mut statement = self.conn.prepare(
+            "SELECT e.name, e.at FROM events e JOIN archive a ON a.name = e.name
+             WHERE e.kind = 'stalled' ORDER BY e.at",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.map(|row| {
+            let (case, at) = row?;
+            Ok(StallIncident {
+                case,
+                at: timestamp(&at)?,
+            })
+        })
+        .collect()
+    }
+    /// Returns the summary of every case in the database, by campaign and first sighting,
+    /// the archived cases included
+    pub fn report(&self) -> Result<Vec<CaseReport>, DbError> {
+        let mut statement = self.conn.prepare(
+            "SELECT c.name, c.campaign, c.total_step, c.first_seen, c.completed,
+                    MIN(s.step), MAX(s.step), MAX(s.at),
+                    (SELECT COUNT(*) FROM events e WHERE e.name = c.name AND e.kind = 'stalled')
+             FROM cases c LEFT JOIN samples s ON s.name = c.name
+             GROUP BY c.name
+             ORDER BY c.campaign, c.first_seen",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+                row.get::<_, Option<i64>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, i64>(8)?,
+            ))
+        })?;
+        let mut reports = rows
+            .map(|row| {
+                let (
+                    name,
+                    campaign,
+                    total_step,
+                    first_seen,
+                    completed,
+                    first,
+                    last,
+                    sampled,
+                    stalls,
+                ) = row?;
+                let first_seen = timestamp(&first_seen)?;
+                let completed = completed.as_deref().map(timestamp).transpose()?;
+                let sampled = sampled.as_deref().map(timestamp).transpose()?;
+                Ok(CaseReport {
+                    name,
+                    campaign,
+                    total_step: total_step as usize,
+                    first_seen,
+                    last_seen: completed.or(sampled).unwrap_or(first_seen),
+                    completed: completed.is_some(),
+                    steps: (last.unwrap_or_default() - first.unwrap_or_default()).max(0) as usize,
+                    stalls: stalls as usize,
+                })
+            })
+            .collect::<Result<Vec<_>, DbError>>()?;
+        reports.extend(self.archived()?.into_iter().map(CaseReport::from));
+        reports.sort_by(|a, b| (&a.campaign, a.first_seen).cmp(&(&b.campaign, b.first_seen)));
+        Ok(reports)
+    }
+}
+
+/// Parses a timestamp of the database
+fn timestamp(at: &str) -> Result<DateTime<Local>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(at).map(|at| at.with_timezone(&Local))
+}
+
+/// Runtime summary of a case over all the monitoring sessions
+#[derive(Debug, Clone)]
+pub struct CaseReport {
+    pub name: String,
+    pub campaign: Option<String>,
+    pub total_step: usize,
+    /// First time the case was seen running
+    pub first_seen: DateTime<Local>,
+    /// Completion or latest update of the case
+    pub last_seen: DateTime<Local>,
+    pub completed: bool,
+    /// Time steps advanced while monitored
+    pub steps: usize,
+    /// Number of stall incidents
+    pub stalls: usize,
+}
+
+impl From<ArchivedCase> for CaseReport {
+    fn from(case: ArchivedCase) -> Self {
+        Self {
+            name: case.name,
+            campaign: case.campaign,
+            total_step: case.total_step,
+            first_seen: case.first_seen,
+            last_seen: case.completed,
+            completed: true,
+            steps: case.steps,
+            stalls: case.stalls,
+        }
+    }
+}
+
+impl CaseReport {
+    /// Returns the wall-clock time the case was monitored for in seconds
+    pub fn runtime_secs(&self) -> i64 {
+        (self.last_seen - self.first_seen).num_seconds().max(0)
+    }
+    /// Returns the average throughput in time steps per hour
+    pub fn steps_per_hour(&self) -> Option<f64> {
+        let secs = self.runtime_secs();
+        (secs > 0).then(|| self.steps as f64 * 3600. / secs as f64)
+    }
+    /// Returns the header matching the rows of the reports
+    pub fn header() -> String {
+        format!(
+            "{:20}{:>16}{:>18}{:>10}{:>8}{:>10}{:>8}{:>11}",
+            "Case", "Campaign", "First seen", "Runtime", "Steps", "Step/h", "Stalls", "State"
+        )
+    }
+}
+
+impl Display for CaseReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:20}{:>16}{:>18}{:>10}{:>8}{:>10}{:>8}{:>11}",
+            self.name,
+            self.campaign.as_deref().unwrap_or("-"),
+            self.first_seen.format("%Y-%m-%d %H:%M"),
+            format_hours_minutes(self.runtime_secs()),
+            self.steps,
+            self.steps_per_hour()
+                .map_or_else(|| "-".to_string(), |rate| format!("{rate:.0}")),
+            self.stalls,
+            if self.completed {
+                "completed"
+            } else {
+                "unfinished"
+            }
+        )
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
+
+use crate::{format_signed_hours_minutes, Case, Column, StatusSnapshot};
+
+/// Width of the annotation next to a column, its leading space included
+pub(crate) const DELTA_WIDTH: usize = 7;
+/// ETA and slack moves under this duration in seconds are not shown, being noise
+const MIN_MOVE: i64 = 60;
+
+/// Change of a column between two refreshes, better or worse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Improved,
+    Regressed,
+}
+
+/// Changes of a case between two refreshes of the status board
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CaseDelta {
+    /// Time steps gained
+    pub steps: Option<i64>,
+    /// Percent complete gained
+    pub percent: Option<i64>,
+    /// Simulated seconds gained
+    pub sim_secs: Option<f64>,
+    /// Seconds the ETA moved by, negative if earlier
+    pub eta_secs: Option<i64>,
+    /// Seconds the conservative ETA moved by, negative if earlier
+    pub conservative_eta_secs: Option<i64>,
+    /// Seconds the schedule slack changed by
+    pub slack_secs: Option<i64>,
+}
+
+impl CaseDelta {
+    /// Compares a case with its state in the previous snapshot, taken `secs` seconds before
+    pub fn new(previous: &Case, case: &Case, secs: i64) -> Self {
+        let has_eta = |case: &Case| case.step().is_some() && !case.is_complete();
+        let moved = |previous: i64, current: i64| {
+            Some(current + secs - previous).filter(|secs| secs.abs() >= MIN_MOVE)
+        };
+        let etas = has_eta(previous) && has_eta(case);
+        Self {
+            steps: previous
+                .step()
+                .zip(case.step())
+                .map(|(previous, step)| step as i64 - previous as i64)
+                .filter(|steps| *steps != 0),
+            percent: Some(case.percent_complete() - previous.percent_complete())
+                .filter(|percent| *percent != 0),
+            sim_secs: Some(case.time() - previous.time()).filter(|secs| *secs != 0.),
+            eta_secs: etas
+                .then(|| moved(previous.eta_secs(), case.eta_secs()))
+                .flatten(),
+            conservative_eta_secs: etas
+                .then(|| {
+                    moved(
+                        previous.conservative_eta_secs(),
+                        case.conservative_eta_secs(),
+                    )
+                })
+                .flatten(),
+            slack_secs: previous
+                .slack_secs()
+                .zip(case.slack_secs())
+                .map(|(previous, slack)| slack - previous)
+                .filter(|secs| secs.abs() >= MIN_MOVE),
+        }
+    }
+    /// Returns the annotation of a column and whether it improved, `None` if it did not change
+    /// or the column has no annotation
+    pub fn annotation(&self, column: Column) -> Option<(String, Change)> {
+        let gained = |gained: bool| {
+            if gained {
+                Change::Improved
+            } else {
+                Change::Regressed
+            }
+        };
+        let earlier = |secs: i64| (format_signed_hours_minutes(secs), gained(secs < 0));
+        match column {
+            Column::Percent => self
+                .percent
+                .map(|percent| (format!("{percent:+}"), gained(percent > 0))),
+            Column::Time => self
+                .sim_secs
+                .map(|secs| (format!("{secs:+.2}"), gained(secs > 0.))),
+            Column::Step => self
+                .steps
+                .map(|steps| (format!("{steps:+}"), gained(steps > 0))),
+            Column::Eta => self.eta_secs.map(earlier),
+            Column::ConservativeEta => self.conservative_eta_secs.map(earlier),
+            Column::Slack => self
+                .slack_secs
+                .map(|secs| (format_signed_hours_minutes(secs), gained(secs > 0))),
+            _ => None,
+        }
+    }
+}
+
+/// Changes of the cases between two refreshes of the status board
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RefreshDeltas {
+    cases: HashMap<String, CaseDelta>,
+}
+
+impl RefreshDeltas {
+    /// Compares the cases of a snapshot with those of the previous one,
+    /// the cases missing from the previous snapshot having no changes
+    pub fn new(previous: &StatusSnapshot, current: &StatusSnapshot) -> Self {
+        let secs = (current.taken() - previous.taken())
+            .max(Duration::zero())
+            .num_seconds();
+        let cases = current
+            .cases()
+            .filter_map(|case| {
+                let before = previous
+                    .cases()
+                    .find(|before| before.name() == case.name())?;
+                Some((case.name().to_string(), CaseDelta::new(before, case, secs)))
+            })
+            .collect();
+        Self { cases }
+    }
+    /// Returns the changes of a case
+    pub fn get(&self, name: &str) -> Option<&CaseDelta> {
+        self.cases.get(name)
+    }
+}
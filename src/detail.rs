@@ -0,0 +1,295 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
+
+use crate::{Case, ColumnSpec, History, SessionEntry};
+
+/// Default number of the last log lines of the detail view
+pub const DETAIL_LINES: usize = 20;
+/// Number of the latest events of a case kept for its detail view
+const DETAIL_EVENTS: usize = 20;
+/// Number of the latest solver iterations of the residual trends
+const RESIDUAL_ITERATIONS: usize = 40;
+/// Number of bins of the time step duration histogram
+const HISTOGRAM_BINS: usize = 8;
+/// Width of the longest bar of the histogram
+const HISTOGRAM_WIDTH: usize = 30;
+
+/// Trend of the residuals of the latest solver iterations, from the residual table of the log
+///
+/// ```text
+///   Iteration  Continuity  X-momentum  Y-momentum  Z-momentum      Energy
+///           1  1.0000e-03  2.0000e-04  2.0000e-04  3.0000e-04  1.0000e-05
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ResidualTrend {
+    /// Residual names with their values, oldest first
+    residuals: Vec<(String, History)>,
+}
+
+impl ResidualTrend {
+    /// Parses the residuals of the latest iterations of the last residual table in `lines`
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut residuals: Vec<(String, History)> = vec![];
+        for line in lines {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match fields.split_first() {
+                Some((&"Iteration", names)) if !names.is_empty() => {
+                    let same = residuals.len() == names.len()
+                        && residuals.iter().zip(names).all(|((a, _), b)| a == b);
+                    if !same {
+                        residuals = names
+                            .iter()
+                            .map(|name| (name.to_string(), History::new(RESIDUAL_ITERATIONS)))
+                            .collect();
+                    }
+                }
+                Some((iteration, values))
+                    if !residuals.is_empty()
+                        && values.len() == residuals.len()
+                        && iteration.parse::<usize>().is_ok() =>
+                {
+                    let Ok(values) = values
+                        .iter()
+                        .map(|value| value.parse::<f64>())
+                        .collect::<Result<Vec<_>, _>>()
+                    else {
+                        continue;
+                    };
+                    for ((_, history), value) in residuals.iter_mut().zip(values) {
+                        history.push(value);
+                    }
+                }
+                _ => (),
+            }
+        }
+        residuals.retain(|(_, history)| !history.is_empty());
+        Self { residuals }
+    }
+    /// Returns true if no residual was found
+    pub fn is_empty(&self) -> bool {
+        self.residuals.is_empty()
+    }
+    /// Returns the names of the residuals with their latest value
+    pub fn latest(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.residuals
+            .iter()
+            .filter_map(|(name, history)| Some((name.as_str(), history.last()?)))
+    }
+}
+
+impl Display for ResidualTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, history) in &self.residuals {
+            // the decades of the residuals are compared
+            let mut decades = History::new(history.len());
+            for value in history.iter() {
+                decades.push(value.max(f64::MIN_POSITIVE).log10());
+            }
+            writeln!(
+                f,
+                "  {name:<14}{:<2$}{:>12.4e}",
+                decades.sparkline(),
+                history.last().unwrap_or_default(),
+                RESIDUAL_ITERATIONS + 2
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Histogram of the latest time step durations
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepTimeHistogram {
+    /// Lower and upper bounds of the bins in seconds with their count of time steps
+    pub bins: Vec<(f64, f64, usize)>,
+}
+
+impl StepTimeHistogram {
+    /// Bins the durations in seconds between their minimum and maximum,
+    /// a single bin if they are all the same
+    pub fn new(secs: impl IntoIterator<Item = f64>) -> Self {
+        let secs: Vec<_> = secs.into_iter().filter(|secs| secs.is_finite()).collect();
+        let (min, max) = secs
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &secs| {
+                (min.min(secs), max.max(secs))
+            });
+        if secs.is_empty() {
+            return Self { bins: vec![] };
+        }
+        let n = if max > min { HISTOGRAM_BINS } else { 1 };
+        let width = (max - min) / n as f64;
+        let mut bins: Vec<_> = (0..n)
+            .map(|i| (min + i as f64 * width, min + (i + 1) as f64 * width, 0))
+            .collect();
+        for secs in secs {
+            let i = if width > 0. {
+                (((secs - min) / width) as usize).min(n - 1)
+            } else {
+                0
+            };
+            bins[i].2 += 1;
+        }
+        Self { bins }
+    }
+}
+
+impl Display for StepTimeHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let most = self
+            .bins
+            .iter()
+            .map(|(_, _, count)| *count)
+            .max()
+            .unwrap_or(0);
+        for (low, high, count) in &self.bins {
+            let bar = (count * HISTOGRAM_WIDTH).div_ceil(most.max(1));
+            writeln!(
+                f,
+                "  {:>16} {:<2$} {count}",
+                format!("{low:.2}-{high:.2}s"),
+                "█".repeat(bar),
+                HISTOGRAM_WIDTH
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Latest monitor events of the cases, for their detail view
+#[derive(Debug, Clone, Default)]
+pub struct EventHistory {
+    cases: HashMap<String, VecDeque<SessionEntry>>,
+}
+
+impl EventHistory {
+    /// Creates an empty history
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Adds an event, the oldest event of its case being dropped past the capacity,
+    /// snapshots being ignored
+    pub fn push(&mut self, entry: SessionEntry) {
+        let SessionEntry::Event { case, .. } = &entry else {
+            return;
+        };
+        let events = self.cases.entry(case.clone()).or_default();
+        if events.len() == DETAIL_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(entry);
+    }
+    /// Returns the latest events of a case, oldest first
+    pub fn case(&self, name: &str) -> impl Iterator<Item = &SessionEntry> {
+        self.cases.get(name).into_iter().flatten()
+    }
+}
+
+/// Detail view of a case: its status, the last log lines, the residual trend,
+/// the time step duration histogram, the intervals the monitor was offline in
+/// and the latest events
+pub struct CaseDetail<'a> {
+    case: &'a Case,
+    events: Vec<&'a SessionEntry>,
+    lines: usize,
+}
+
+impl<'a> CaseDetail<'a> {
+    /// Creates the detail view of a case with its latest events
+    pub fn new(case: &'a Case, events: impl IntoIterator<Item = &'a SessionEntry>) -> Self {
+        Self {
+            case,
+            events: events.into_iter().collect(),
+            lines: DETAIL_LINES,
+        }
+    }
+    /// Sets the number of the last log lines shown
+    pub fn lines(mut self, lines: usize) -> Self {
+        self.lines = lines;
+        self
+    }
+}
+
+impl Display for CaseDetail<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let columns = ColumnSpec::default();
+        let eta = Default::default();
+        writeln!(f, "{}", columns.header(eta))?;
+        writeln!(f, "{}", columns.row(self.case, eta))?;
+        writeln!(
+            f,
+            "State: {}, log: {}",
+            self.case.state(),
+            self.case.log_file()
+        )?;
+        if let Some(note) = self.case.note() {
+            writeln!(f, "Note: {note}")?;
+        }
+        let residuals = ResidualTrend::from_lines(self.case.recent_lines());
+        if !residuals.is_empty() {
+            writeln!(
+                f,
+                "\nResiduals, last {RESIDUAL_ITERATIONS} iterations (log scale)"
+            )?;
+            write!(f, "{residuals}")?;
+        }
+        let histogram = StepTimeHistogram::new(self.case.step_times().iter().copied());
+        if !histogram.bins.is_empty() {
+            writeln!(f, "\nTime step durations")?;
+            write!(f, "{histogram}")?;
+        }
+        if let Some(percentiles) = self.case.step_time_percentiles() {
+            writeln!(
+                f,
+                "  {percentiles}, max {:.2}s over {} time steps",
+                self.case.step_time_distribution().max(),
+                self.case.step_time_distribution().steps()
+            )?;
+        }
+        let iterations = self.case.inner_iterations();
+        if let Some(latest) = iterations.latest() {
+            write!(
+                f,
+                "\nInner iterations per time step\n  {:<1$}{latest:>12}",
+                iterations.history().sparkline(),
+                RESIDUAL_ITERATIONS + 16
+            )?;
+            match iterations.baseline() {
+                Some(baseline) if self.case.is_iterating_more() => {
+                    writeln!(f, "  climbing from {baseline:.1}")?
+                }
+                _ => writeln!(f)?,
+            }
+        }
+        let mut offline = self.case.offline_intervals().peekable();
+        if offline.peek().is_some() {
+            writeln!(f, "\nMonitor offline")?;
+            for interval in offline {
+                writeln!(f, "  {interval}")?;
+            }
+        }
+        if !self.events.is_empty() {
+            writeln!(f, "\nEvents")?;
+            for event in &self.events {
+                if let SessionEntry::Event {
+                    at, event, detail, ..
+                } = event
+                {
+                    write!(f, "  {} {event}", at.format("%Y-%m-%d %H:%M:%S"))?;
+                    match detail {
+                        Some(detail) => writeln!(f, " {detail}")?,
+                        None => writeln!(f)?,
+                    }
+                }
+            }
+        }
+        let lines: Vec<_> = self.case.recent_lines().collect();
+        writeln!(f, "\nLog, last {} lines", self.lines.min(lines.len()))?;
+        for line in &lines[lines.len().saturating_sub(self.lines)..] {
+            writeln!(f, "  {line}")?;
+        }
+        Ok(())
+    }
+}
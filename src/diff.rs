@@ -0,0 +1,177 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{format_hours_minutes, format_signed_hours_minutes};
+
+/// Time format of the snapshot diff
+const DIFF_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiffError {
+    #[error("not a status snapshot, expected the output of `cfd-status snapshot --json`")]
+    NotSnapshot,
+    #[error("the first snapshot, taken {}, is not older than the second one", .0.format(DIFF_FORMAT))]
+    Order(DateTime<Local>),
+}
+
+/// Status of a case in a JSON snapshot
+#[derive(Debug, Clone)]
+struct CaseState {
+    name: String,
+    state: Option<String>,
+    step: Option<usize>,
+    time: Option<f64>,
+    /// ETA of a running case or completion time of a completed one
+    eta: Option<DateTime<Local>>,
+}
+
+impl CaseState {
+    fn new(case: &Value) -> Option<Self> {
+        let time = |key: &str| {
+            serde_json::from_value::<Option<DateTime<Local>>>(case.get(key)?.clone())
+                .ok()
+                .flatten()
+        };
+        Some(Self {
+            name: case["name"].as_str()?.to_string(),
+            state: case["state"].as_str().map(str::to_string),
+            step: case["step"].as_u64().map(|step| step as usize),
+            time: case["time"].as_f64(),
+            eta: time("completed").or_else(|| time("eta")),
+        })
+    }
+}
+
+/// Progress of a case from a snapshot to a later one
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaseDiff {
+    pub case: String,
+    /// State in each snapshot, `None` if the case is not in it
+    pub from_state: Option<String>,
+    pub to_state: Option<String>,
+    /// Time steps advanced, negative if the case restarted from an earlier time step
+    pub steps: Option<i64>,
+    /// Time steps advanced per hour over the interval
+    pub steps_per_hour: Option<f64>,
+    /// Simulated seconds advanced
+    pub sim_secs: Option<f64>,
+    /// Change of the ETA in seconds, positive if it slipped
+    pub eta_drift_secs: Option<i64>,
+}
+
+/// Progress of the cases between two JSON snapshots, e.g. over a weekend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub from: DateTime<Local>,
+    pub to: DateTime<Local>,
+    pub cases: Vec<CaseDiff>,
+}
+
+impl SnapshotDiff {
+    /// Compares a snapshot, as printed by `cfd-status snapshot --json`, with a later one
+    ///
+    /// A snapshot recorded in a session, with the status under `status`, is also accepted
+    pub fn new(from: &Value, to: &Value) -> Result<Self, DiffError> {
+        let (from, from_cases) = parse(from)?;
+        let (to, to_cases) = parse(to)?;
+        if from >= to {
+            return Err(DiffError::Order(from));
+        }
+        let hours = (to - from).num_seconds() as f64 / 3600.;
+        let mut names: Vec<&str> = to_cases.iter().map(|case| case.name.as_str()).collect();
+        for case in &from_cases {
+            if !names.contains(&case.name.as_str()) {
+                names.push(&case.name);
+            }
+        }
+        let state =
+            |cases: &[CaseState], name: &str| cases.iter().find(|case| case.name == name).cloned();
+        let cases = names
+            .into_iter()
+            .map(|name| {
+                let (a, b) = (state(&from_cases, name), state(&to_cases, name));
+                let (a_step, a_time) = a.as_ref().map_or((None, None), |a| (a.step, a.time));
+                // a case missing from the first snapshot starts from scratch
+                let steps = b
+                    .as_ref()
+                    .and_then(|b| b.step)
+                    .map(|step| step as i64 - a_step.unwrap_or_default() as i64);
+                CaseDiff {
+                    case: name.to_string(),
+                    from_state: a.as_ref().and_then(|a| a.state.clone()),
+                    to_state: b.as_ref().and_then(|b| b.state.clone()),
+                    steps,
+                    steps_per_hour: steps
+                        .filter(|steps| *steps >= 0)
+                        .map(|steps| steps as f64 / hours),
+                    sim_secs: b
+                        .as_ref()
+                        .and_then(|b| b.time)
+                        .map(|time| time - a_time.unwrap_or_default()),
+                    eta_drift_secs: a
+                        .and_then(|a| a.eta)
+                        .zip(b.and_then(|b| b.eta))
+                        .map(|(a, b)| (b - a).num_seconds()),
+                }
+            })
+            .collect();
+        Ok(Self { from, to, cases })
+    }
+}
+
+/// Returns the time a JSON snapshot was taken and the status of its cases
+fn parse(snapshot: &Value) -> Result<(DateTime<Local>, Vec<CaseState>), DiffError> {
+    let snapshot = snapshot.get("status").unwrap_or(snapshot);
+    let taken: DateTime<Local> = snapshot
+        .get("taken")
+        .and_then(|taken| serde_json::from_value(taken.clone()).ok())
+        .ok_or(DiffError::NotSnapshot)?;
+    let cases = snapshot["cases"]
+        .as_array()
+        .ok_or(DiffError::NotSnapshot)?
+        .iter()
+        .filter_map(CaseState::new)
+        .collect();
+    Ok((taken, cases))
+}
+
+impl Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "From {} to {} ({})",
+            self.from.format(DIFF_FORMAT),
+            self.to.format(DIFF_FORMAT),
+            format_hours_minutes((self.to - self.from).num_seconds())
+        )?;
+        writeln!(
+            f,
+            "{:<20}{:<26}{:>10}{:>10}{:>12}{:>11}",
+            "Case", "State", "Steps", "Steps/h", "Sim time", "ETA drift"
+        )?;
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        for case in &self.cases {
+            let state = match (&case.from_state, &case.to_state) {
+                (Some(a), Some(b)) if a == b => a.clone(),
+                (Some(a), Some(b)) => format!("{a} -> {b}"),
+                (None, Some(b)) => format!("new, {b}"),
+                (Some(_), None) => "removed".to_string(),
+                (None, None) => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:<20}{:<26}{:>10}{:>10}{:>12}{:>11}",
+                case.case,
+                state,
+                or_dash(case.steps.map(|steps| format!("{steps:+}"))),
+                or_dash(case.steps_per_hour.map(|rate| format!("{rate:.1}"))),
+                or_dash(case.sim_secs.map(|secs| format!("{secs:+.2}s"))),
+                or_dash(case.eta_drift_secs.map(format_signed_hours_minutes)),
+            )?;
+        }
+        Ok(())
+    }
+}
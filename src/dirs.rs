@@ -0,0 +1,130 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::Config;
+
+/// Subdirectory of the configuration and state directories of the user
+const APP: &str = "cfd-status";
+/// Name of the configuration file in the configuration directory
+const CONFIG_FILE: &str = "config.toml";
+
+/// Configuration scaffolded by `cfd-status init`
+pub const CONFIG_TEMPLATE: &str = r#"# Configuration of cfd-status, see `Config` for all the settings
+
+# Directory the case directories are in
+root = "/shared"
+# Time in seconds between 2 status updates
+interval = 180
+# Time in seconds without a new time step before a case is stalled
+stall_after = 900
+
+# The completion records, the event log, the operator notes and the run database,
+# if relative, are kept in the state directory of the user
+records = "records.toml"
+events = "events.jsonl"
+notes = "notes.toml"
+# database = "runs.db"
+
+# [[cases]]
+# name = "zen30az045_OS2"
+# duration = 1200
+# log = "solve-672_14.out"
+# group = "zen30"
+# cores = 672
+"#;
+
+/// Configuration and state directories of the user, following the XDG base directories:
+/// `~/.config/cfd-status` with the `config.toml` configuration file
+/// and `~/.local/state/cfd-status` with the persisted state of the monitor
+///
+/// `XDG_CONFIG_HOME` and `XDG_STATE_HOME` override the base directories, `APPDATA`
+/// and `LOCALAPPDATA` being the ones on Windows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dirs {
+    /// Configuration directory
+    pub config: PathBuf,
+    /// State directory
+    pub state: PathBuf,
+}
+
+impl Dirs {
+    /// Creates the directories of the application in the configuration and state base directories
+    pub fn new<C: AsRef<Path>, S: AsRef<Path>>(config_home: C, state_home: S) -> Self {
+        Self {
+            config: config_home.as_ref().join(APP),
+            state: state_home.as_ref().join(APP),
+        }
+    }
+    /// Returns the directories of the user from the environment, `None` without a home directory
+    pub fn from_env() -> Option<Self> {
+        let var = |name| env::var_os(name).filter(|value| !value.is_empty());
+        let home = var("HOME")
+            .or_else(|| var("USERPROFILE"))
+            .map(PathBuf::from);
+        let (config_home, state_home) = if cfg!(windows) {
+            (var("APPDATA"), var("LOCALAPPDATA"))
+        } else {
+            (var("XDG_CONFIG_HOME"), var("XDG_STATE_HOME"))
+        };
+        let config_home = config_home
+            .map(PathBuf::from)
+            .or_else(|| Some(home.as_ref()?.join(".config")))?;
+        let state_home = state_home
+            .map(PathBuf::from)
+            .or_else(|| Some(home.as_ref()?.join(".local").join("state")))?;
+        Some(Self::new(config_home, state_home))
+    }
+    /// Returns the path to the configuration file
+    pub fn config_file(&self) -> PathBuf {
+        self.config.join(CONFIG_FILE)
+    }
+    /// Creates the configuration and state directories if missing
+    pub fn create(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.config)?;
+        fs::create_dir_all(&self.state)
+    }
+    /// Creates the directories and writes the [CONFIG_TEMPLATE] configuration file,
+    /// an existing one being kept unless `force` is set, and returns its path
+    pub fn init(&self, force: bool) -> io::Result<PathBuf> {
+        self.create()?;
+        let path = self.config_file();
+        if path.exists() && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ));
+        }
+        fs::write(&path, CONFIG_TEMPLATE)?;
+        Ok(path)
+    }
+}
+
+impl Config {
+    /// Moves the relative paths of the persisted state, the completion records, the event log,
+    /// the notes, the run database and the log excerpts, into the state directory `dir`
+    pub fn relocate_state<P: AsRef<Path>>(&mut self, dir: P) {
+        let dir = dir.as_ref();
+        let relocate = |path: &Path| {
+            if path.is_relative() {
+                dir.join(path)
+            } else {
+                path.to_path_buf()
+            }
+        };
+        for file in [&mut self.records, &mut self.events, &mut self.notes] {
+            if !file.is_empty() {
+                *file = relocate(Path::new(file.as_str()))
+                    .to_string_lossy()
+                    .into_owned();
+            }
+        }
+        if let Some(database) = &mut self.database {
+            *database = relocate(database);
+        }
+        if let Some(excerpts) = &mut self.excerpts {
+            excerpts.dir = relocate(&excerpts.dir);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(feature = "monitor")]
+use std::{fs, io, path::Path};
+
+use serde::Deserialize;
+
+/// Bytes per GB
+const GB: f64 = 1e9;
+
+/// Disk usage tracking configuration
+///
+/// ```toml
+/// [disk]
+/// min_free_gb = 500
+/// max_case_gb = 4000
+/// every = 10
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiskConfig {
+    /// Free space on the case filesystem below which the case is at risk
+    pub min_free_gb: Option<f64>,
+    /// Size of the case directory above which the case is at risk
+    pub max_case_gb: Option<f64>,
+    /// Number of updates between 2 measurements, walking large case directories is expensive
+    #[serde(default = "default_every")]
+    pub every: usize,
+}
+
+fn default_every() -> usize {
+    1
+}
+
+impl DiskConfig {
+    /// Returns true if the disk usage crosses any of the thresholds
+    pub fn is_at_risk(&self, usage: &DiskUsage) -> bool {
+        self.min_free_gb.is_some_and(|min| usage.free_gb() < min)
+            || self.max_case_gb.is_some_and(|max| usage.case_gb() > max)
+    }
+}
+
+/// Disk usage of a case
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskUsage {
+    /// Size of the case directory in bytes
+    pub case_bytes: u64,
+    /// Free space on the case filesystem in bytes
+    pub free_bytes: u64,
+}
+
+impl DiskUsage {
+    #[cfg(feature = "monitor")]
+    /// Measures the size of a case directory and the free space on its filesystem
+    pub fn measure<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        Ok(Self {
+            case_bytes: dir_size(dir.as_ref())?,
+            free_bytes: fs2::available_space(dir)?,
+        })
+    }
+    /// Returns the size of the case directory in GB
+    pub fn case_gb(&self) -> f64 {
+        self.case_bytes as f64 / GB
+    }
+    /// Returns the free space on the case filesystem in GB
+    pub fn free_gb(&self) -> f64 {
+        self.free_bytes as f64 / GB
+    }
+}
+
+#[cfg(feature = "monitor")]
+/// Returns the total size of the files in a directory and its sub-directories
+fn dir_size(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        size += if meta.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            meta.len()
+        };
+    }
+    Ok(size)
+}
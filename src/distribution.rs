@@ -0,0 +1,105 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+/// Bins per decade of the distribution of the time step durations, each bin spanning 12%
+const BINS_PER_DECADE: usize = 20;
+/// Shortest time step duration binned in seconds, shorter ones falling in the first bin
+const MIN_SECS: f64 = 1e-3;
+/// Number of bins, from 1 ms to 1 day
+const BINS: usize = 8 * BINS_PER_DECADE;
+
+/// Percentiles of the time step durations in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl Display for Percentiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "p50 {:.2}s, p90 {:.2}s, p99 {:.2}s",
+            self.p50, self.p90, self.p99
+        )
+    }
+}
+
+/// Distribution of the time step durations of a case over the whole simulation,
+/// for the tail latencies of the checkpoint writes and of the I/O stalls the mean hides
+///
+/// The durations are binned logarithmically, so the memory is bounded
+/// and the percentiles are within 6% of the exact ones
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepTimeDistribution {
+    counts: Vec<u64>,
+    steps: u64,
+    sum: f64,
+    max: f64,
+}
+
+impl StepTimeDistribution {
+    /// Creates an empty distribution
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns the bin of a duration
+    fn bin(secs: f64) -> usize {
+        let bin = ((secs / MIN_SECS).log10() * BINS_PER_DECADE as f64).floor();
+        (bin.max(0.) as usize).min(BINS - 1)
+    }
+    /// Returns the duration at the geometric center of a bin
+    fn center(bin: usize) -> f64 {
+        MIN_SECS * 10f64.powf((bin as f64 + 0.5) / BINS_PER_DECADE as f64)
+    }
+    /// Adds `steps` time steps of `secs` seconds each
+    pub fn add(&mut self, secs: f64, steps: usize) {
+        if !secs.is_finite() || secs < 0. || steps == 0 {
+            return;
+        }
+        if self.counts.is_empty() {
+            self.counts = vec![0; BINS];
+        }
+        self.counts[Self::bin(secs)] += steps as u64;
+        self.steps += steps as u64;
+        self.sum += secs * steps as f64;
+        self.max = self.max.max(secs);
+    }
+    /// Returns the number of time steps
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+    /// Returns the cumulated duration of the time steps in seconds
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+    /// Returns the longest time step duration in seconds
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+    /// Returns the duration under which `percent` of the time steps are, `None` without time steps
+    pub fn percentile(&self, percent: f64) -> Option<f64> {
+        if self.steps == 0 {
+            return None;
+        }
+        let rank = (percent / 1e2 * self.steps as f64).ceil().max(1.) as u64;
+        let mut below = 0;
+        for (bin, count) in self.counts.iter().enumerate() {
+            below += count;
+            if below >= rank {
+                return Some(Self::center(bin).min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+    /// Returns the median, the 90th and the 99th percentiles, `None` without time steps
+    pub fn percentiles(&self) -> Option<Percentiles> {
+        Some(Percentiles {
+            p50: self.percentile(50.)?,
+            p90: self.percentile(90.)?,
+            p99: self.percentile(99.)?,
+        })
+    }
+}
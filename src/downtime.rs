@@ -0,0 +1,114 @@
+use std::{fmt::Display, fs, path::Path};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::format_hours_minutes;
+
+/// Completed run of the completion records, as far as the downtime is concerned
+#[derive(Debug, Deserialize)]
+struct RecordedRun {
+    group: Option<String>,
+    steps: Option<usize>,
+    runtime_secs: Option<i64>,
+    step_time: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Records {
+    #[serde(default)]
+    completed: Vec<RecordedRun>,
+}
+
+/// Downtime of the completed cases, the ratio of their wall-clock runtime to the time
+/// spent time stepping, inflated by the restarts, stalls and outages of the runs
+///
+/// The conservative ETA of a case scales its remaining time stepping by the mean ratio
+/// of the completed cases of its group, or of all the completed cases if none of its group
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DowntimeHistory {
+    runs: Vec<(Option<String>, f64)>,
+}
+
+impl DowntimeHistory {
+    /// Creates the history from the group and downtime factor of each completed run
+    pub fn new(runs: impl IntoIterator<Item = (Option<String>, f64)>) -> Self {
+        Self {
+            runs: runs.into_iter().collect(),
+        }
+    }
+    /// Reads the history from the completion records, empty if there are none
+    ///
+    /// The records without the mean time step duration, written by older versions, are skipped
+    pub fn read<P: AsRef<Path>>(path: P) -> Self {
+        let Ok(records) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let records: Records = match toml::from_str(&records) {
+            Ok(records) => records,
+            Err(e) => {
+                tracing::warn!(error = %e, "invalid completion records file");
+                return Self::default();
+            }
+        };
+        Self::new(records.completed.into_iter().filter_map(|run| {
+            let stepping = run.steps? as f64 * run.step_time.filter(|secs| *secs > 0.)?;
+            // the time stepping cannot take longer than the whole run
+            Some((run.group, (run.runtime_secs? as f64 / stepping).max(1.)))
+        }))
+    }
+    /// Returns the number of completed runs in the history
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+    /// Returns true if there is no completed run in the history
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+    /// Returns the mean downtime factor of the completed cases of a group,
+    /// or of all of them if none of the group has completed
+    pub fn factor(&self, group: Option<&str>) -> Option<f64> {
+        let mean = |factors: Vec<f64>| {
+            (!factors.is_empty()).then(|| factors.iter().sum::<f64>() / factors.len() as f64)
+        };
+        let factors = |same_group: bool| {
+            self.runs
+                .iter()
+                .filter(|(run_group, _)| !same_group || run_group.as_deref() == group)
+                .map(|(_, factor)| *factor)
+                .collect::<Vec<_>>()
+        };
+        mean(factors(true)).or_else(|| mean(factors(false)))
+    }
+}
+
+/// Interval the monitor was offline in, e.g. stopped or suspended, found once the wall-clock
+/// time between two updates of a case is far longer than the sampling window
+///
+/// The time steps done in the interval are spread evenly over it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OfflineInterval {
+    /// Latest update before the interval
+    pub from: DateTime<Local>,
+    /// First update after the interval
+    pub to: DateTime<Local>,
+}
+
+impl OfflineInterval {
+    /// Returns the duration of the interval in seconds
+    pub fn secs(&self) -> i64 {
+        (self.to - self.from).num_seconds()
+    }
+}
+
+impl Display for OfflineInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} to {} ({})",
+            self.from.format("%Y-%m-%d %H:%M:%S"),
+            self.to.format("%Y-%m-%d %H:%M:%S"),
+            format_hours_minutes(self.secs())
+        )
+    }
+}
@@ -0,0 +1,153 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{DurationSpec, RATE};
+
+/// Factor the time step size implied by the duration and the step count of a case
+/// may differ from the nominal one by, and the duration of a case from the median one
+/// of the configured cases, before a warning that the duration may be a time step count
+pub const MAX_RATE_RATIO: f64 = 10.;
+
+/// Simulated time in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct SimSeconds(pub f64);
+
+/// Number of time steps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct Steps(pub usize);
+
+impl SimSeconds {
+    /// Returns the number of time steps of the simulated time at the nominal rate
+    pub fn to_steps(self) -> Steps {
+        self.to_steps_at(RATE as f64)
+    }
+    /// Returns the number of time steps of the simulated time at `rate` time steps per second
+    pub fn to_steps_at(self, rate: f64) -> Steps {
+        Steps((self.0 * rate).round().max(0.) as usize)
+    }
+}
+
+impl Steps {
+    /// Returns the simulated time of the time steps at the nominal rate
+    pub fn to_sim_seconds(self) -> SimSeconds {
+        self.to_sim_seconds_at(RATE as f64)
+    }
+    /// Returns the simulated time of the time steps at `rate` time steps per second
+    pub fn to_sim_seconds_at(self, rate: f64) -> SimSeconds {
+        SimSeconds(self.0 as f64 / rate)
+    }
+    /// Returns the time step size in seconds of the time steps over `duration`
+    pub fn step_size(self, duration: SimSeconds) -> Option<f64> {
+        Some(duration.0 / self.0 as f64).filter(|_| self.0 > 0)
+    }
+    /// Returns true if the step size of the time steps over `duration` is within
+    /// [MAX_RATE_RATIO] of the nominal one
+    pub fn is_consistent_with(self, duration: SimSeconds) -> bool {
+        let nominal = 1. / RATE as f64;
+        self.step_size(duration)
+            .is_some_and(|dt| dt <= nominal * MAX_RATE_RATIO && dt >= nominal / MAX_RATE_RATIO)
+    }
+}
+
+impl Display for SimSeconds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}s", self.0)
+    }
+}
+
+impl Display for Steps {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} steps", self.0)
+    }
+}
+
+impl From<SimSeconds> for DurationSpec {
+    fn from(duration: SimSeconds) -> Self {
+        Self::PhysicalTime(duration.0)
+    }
+}
+
+impl From<Steps> for DurationSpec {
+    fn from(steps: Steps) -> Self {
+        Self::Steps(steps.0)
+    }
+}
+
+impl DurationSpec {
+    /// Returns the simulated time of the simulation, at the nominal rate for a step count
+    pub fn sim_seconds(&self) -> SimSeconds {
+        match *self {
+            Self::Steps(n_step) => Steps(n_step).to_sim_seconds(),
+            Self::PhysicalTime(end) => SimSeconds(end),
+        }
+    }
+    /// Returns the time step count of the simulation, at the nominal rate for a simulated time
+    pub fn steps(&self) -> Steps {
+        match *self {
+            Self::Steps(n_step) => Steps(n_step),
+            Self::PhysicalTime(end) => SimSeconds(end).to_steps(),
+        }
+    }
+}
+
+/// Error parsing a simulation duration
+#[derive(Debug, thiserror::Error)]
+pub enum DurationError {
+    #[error(
+        "invalid duration {0:?}, expected simulated seconds, e.g. \"1200\", \"1200s\" or \"20min\""
+    )]
+    Invalid(String),
+    #[error("duration {0:?} is a time step count, set `steps` instead, the duration being in simulated seconds")]
+    Steps(String),
+}
+
+impl FromStr for SimSeconds {
+    type Err = DurationError;
+    /// Parses simulated seconds, with an optional `s`, `min` or `h` unit
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (value, unit) = s.split_at(split);
+        let value: f64 = value
+            .parse()
+            .map_err(|_| DurationError::Invalid(s.to_string()))?;
+        let scale = match unit.trim() {
+            "" | "s" | "sec" => 1.,
+            "min" => 60.,
+            "h" => 3600.,
+            "step" | "steps" => return Err(DurationError::Steps(s.to_string())),
+            _ => return Err(DurationError::Invalid(s.to_string())),
+        };
+        Ok(Self(value * scale))
+    }
+}
+
+/// Simulation duration in simulated seconds, a number or a string with a `s`, `min` or `h` unit
+impl<'de> Deserialize<'de> for SimSeconds {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Duration {
+            Secs(f64),
+            Text(String),
+        }
+        let secs = match Duration::deserialize(deserializer)? {
+            Duration::Secs(secs) => SimSeconds(secs),
+            Duration::Text(text) => text.parse().map_err(serde::de::Error::custom)?,
+        };
+        if secs.0.is_finite() && secs.0 >= 0. {
+            Ok(secs)
+        } else {
+            Err(serde::de::Error::custom(DurationError::Invalid(
+                secs.to_string(),
+            )))
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::{fs, path::Path, process::Command, sync::LazyLock};
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::Serialize;
+
+static HOST: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bhost(?:name)?s?\b\s*[:=]?\s*([A-Za-z][\w.-]*)").unwrap());
+static INSTANCE_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\binstance[ _-]?type\b\s*[:=]?\s*([\w.-]+)").unwrap());
+static MESH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:loading|reading|importing|opening)\b.*?([\w./-]+\.(?:sim|cas|msh|ccm|cgns|h5))\b")
+        .unwrap()
+});
+
+/// Facts about the environment a case runs in, gathered from its log and from the scheduler
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Environment {
+    pub hosts: Vec<String>,
+    pub instance_type: Option<String>,
+    pub cores: Option<usize>,
+    pub solver_version: Option<String>,
+    pub mesh: Option<String>,
+    /// Last modification of the mesh file
+    pub mesh_modified: Option<DateTime<Local>>,
+}
+
+impl Environment {
+    /// Updates the environment from log lines, the mesh file being looked for relative to `dir`
+    pub fn scan<P: AsRef<Path>>(&mut self, lines: &[String], dir: P) {
+        let capture = |regex: &Regex, line: &str| {
+            regex
+                .captures(line)
+                .and_then(|captures| captures.get(1))
+                .map(|m| m.as_str().to_string())
+        };
+        for line in lines {
+            if let Some(host) = capture(&HOST, line) {
+                if !self.hosts.contains(&host) {
+                    self.hosts.push(host);
+                }
+            }
+            if self.instance_type.is_none() {
+                self.instance_type = capture(&INSTANCE_TYPE, line);
+            }
+            if self.mesh.is_none() {
+                if let Some(mesh) = capture(&MESH, line) {
+                    self.mesh_modified = fs::metadata(dir.as_ref().join(&mesh))
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .map(DateTime::from);
+                    self.mesh = Some(mesh);
+                }
+            }
+        }
+    }
+    /// Updates the hosts and instance type from the SLURM job node list and features
+    ///
+    /// Parses the output of `squeue -h -j <job> -o %N|%f`
+    pub fn query_scheduler(&mut self, job: &str) {
+        let Ok(output) = Command::new("squeue")
+            .args(["-h", "-j", job, "-o", "%N|%f"])
+            .output()
+            .inspect_err(|e| tracing::debug!(job, error = %e, "failed to run squeue"))
+        else {
+            return;
+        };
+        if !output.status.success() {
+            tracing::debug!(job, status = %output.status, "squeue failed");
+            return;
+        }
+        let output = String::from_utf8_lossy(&output.stdout);
+        let Some((nodes, features)) = output.trim().split_once('|') else {
+            return;
+        };
+        if !nodes.is_empty() && !self.hosts.iter().any(|host| host == nodes) {
+            self.hosts.push(nodes.to_string());
+        }
+        if self.instance_type.is_none() && !features.is_empty() && features != "(null)" {
+            self.instance_type = Some(features.to_string());
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use crate::format_hours_minutes;
+
+/// Presentation of an ETA
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EtaFormat {
+    /// Timestamp, e.g. `2024-06-14 18:00`
+    #[default]
+    Absolute,
+    /// Remaining time, e.g. `in 3:25`
+    Relative,
+    /// Timestamp followed by the remaining time, e.g. `2024-06-14 18:00 (in 3:25)`
+    Both,
+}
+
+impl EtaFormat {
+    /// Formats an ETA, the remaining time being counted from `now`
+    pub fn format(&self, eta: DateTime<Local>, now: DateTime<Local>) -> String {
+        let absolute = || eta.format("%Y-%m-%d %H:%M").to_string();
+        let relative = || {
+            format!(
+                "in {}",
+                format_hours_minutes((eta - now).num_seconds().max(0))
+            )
+        };
+        match self {
+            Self::Absolute => absolute(),
+            Self::Relative => relative(),
+            Self::Both => format!("{} ({})", absolute(), relative()),
+        }
+    }
+    /// Returns the width of a table column of ETAs in this format
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Absolute => 20,
+            Self::Relative => 12,
+            Self::Both => 32,
+        }
+    }
+}
+
+/// Presentation of an ETA with the time zone its timestamp is shown in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EtaStyle {
+    pub format: EtaFormat,
+    /// Time zone of the timestamps, the time zone of the machine if none
+    pub timezone: Option<Tz>,
+    /// Follows the timestamps with their UTC time
+    pub utc: bool,
+}
+
+impl From<EtaFormat> for EtaStyle {
+    fn from(format: EtaFormat) -> Self {
+        Self {
+            format,
+            ..Default::default()
+        }
+    }
+}
+
+impl EtaStyle {
+    /// Formats an ETA, the remaining time being counted from `now`
+    ///
+    /// e.g. `2024-06-14 18:00 -04 / 06-14 22:00 UTC (in 3:25)` in the `both` format
+    /// with the `America/Santiago` time zone and the UTC time
+    pub fn format(&self, eta: DateTime<Local>, now: DateTime<Local>) -> String {
+        let absolute = || {
+            let mut absolute = self.zoned(eta, "%Y-%m-%d %H:%M");
+            if self.utc {
+                absolute.push_str(
+                    &eta.with_timezone(&Utc)
+                        .format(" / %m-%d %H:%M UTC")
+                        .to_string(),
+                );
+            }
+            absolute
+        };
+        match self.format {
+            EtaFormat::Relative => self.format.format(eta, now),
+            EtaFormat::Absolute => absolute(),
+            EtaFormat::Both => format!("{} ({})", absolute(), EtaFormat::Relative.format(eta, now)),
+        }
+    }
+    /// Formats a timestamp in the time zone, followed by the zone abbreviation if one is set
+    pub fn zoned<T: TimeZone>(&self, time: DateTime<T>, format: &str) -> String {
+        match self.timezone {
+            Some(timezone) => time
+                .with_timezone(&timezone)
+                .format(&format!("{format} %Z"))
+                .to_string(),
+            None => time.with_timezone(&Local).format(format).to_string(),
+        }
+    }
+    /// Returns the width of a table column of ETAs in this style
+    pub fn width(&self) -> usize {
+        if self.format == EtaFormat::Relative {
+            return self.format.width();
+        }
+        self.format.width()
+            + if self.timezone.is_some() { 6 } else { 0 }
+            + if self.utc { 18 } else { 0 }
+    }
+}
+
+/// ETA presentation of every view, the global format applying to the views without their own
+///
+/// ```toml
+/// [eta]
+/// format = "both"
+/// compact = "relative"
+/// timezone = "America/Santiago"
+/// utc = true
+/// ```
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct EtaConfig {
+    /// Format of all the views
+    #[serde(default)]
+    pub format: EtaFormat,
+    /// Time zone of the timestamps of all the views, the time zone of the machine by default
+    pub timezone: Option<Tz>,
+    /// Follows the timestamps with their UTC time
+    #[serde(default)]
+    pub utc: bool,
+    /// Format of the status table
+    pub table: Option<EtaFormat>,
+    /// Format of the compact blocks of `--scroll`
+    pub compact: Option<EtaFormat>,
+    /// Format of the notifications
+    pub notifications: Option<EtaFormat>,
+}
+
+impl EtaConfig {
+    /// Returns the presentation of the status table
+    pub fn table(&self) -> EtaStyle {
+        self.style(self.table)
+    }
+    /// Returns the presentation of the compact blocks
+    pub fn compact(&self) -> EtaStyle {
+        self.style(self.compact)
+    }
+    /// Returns the presentation of the notifications
+    pub fn notifications(&self) -> EtaStyle {
+        self.style(self.notifications)
+    }
+    fn style(&self, format: Option<EtaFormat>) -> EtaStyle {
+        EtaStyle {
+            format: format.unwrap_or(self.format),
+            timezone: self.timezone,
+            utc: self.utc,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+
+use crate::Case;
+
+/// Default number of the latest log lines captured around an anomaly
+pub const EXCERPT_LINES: usize = 300;
+/// Default number of the last lines of an excerpt included in the notifications
+pub const SNIPPET_LINES: usize = 5;
+
+/// Capture of the log lines around the anomalies of the cases
+///
+/// ```toml
+/// [excerpts]
+/// dir = "/shared/cfd-status/excerpts"
+/// lines = 300
+/// snippet = 5
+/// ```
+///
+/// An excerpt is saved to `{dir}/{case}/{time}-{event}.log` when a case stalls,
+/// its solver dies, its update fails or its time steps slow down
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExcerptConfig {
+    /// Directory the excerpts are saved to
+    pub dir: PathBuf,
+    /// Number of the latest log lines of an excerpt
+    #[serde(default = "default_lines")]
+    pub lines: usize,
+    /// Number of the last lines of an excerpt included in the notifications
+    #[serde(default = "default_snippet")]
+    pub snippet: usize,
+}
+
+fn default_lines() -> usize {
+    EXCERPT_LINES
+}
+
+fn default_snippet() -> usize {
+    SNIPPET_LINES
+}
+
+/// Latest log lines of a case when an anomaly was detected
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogExcerpt {
+    pub case: String,
+    /// Anomaly the excerpt was captured at, e.g. `stalled`
+    pub event: String,
+    pub at: DateTime<Local>,
+    pub step: Option<usize>,
+    pub lines: Vec<String>,
+}
+
+impl LogExcerpt {
+    /// Captures the latest log lines of a case at an anomaly
+    pub fn new<S: ToString>(case: &Case, event: S) -> Self {
+        Self {
+            case: case.name().to_string(),
+            event: event.to_string(),
+            at: Local::now(),
+            step: case.step(),
+            lines: case.recent_lines().map(str::to_string).collect(),
+        }
+    }
+    /// Returns the last `n` lines of the excerpt
+    pub fn snippet(&self, n: usize) -> String {
+        self.lines[self.lines.len().saturating_sub(n)..].join("\n")
+    }
+    /// Saves the excerpt to `{dir}/{case}/{time}-{event}.log`, returning the path of the file
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> io::Result<PathBuf> {
+        let dir = dir.as_ref().join(&self.case);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "{}-{}.log",
+            self.at.format("%Y%m%dT%H%M%S"),
+            self.event
+        ));
+        fs::write(&path, self.to_string())?;
+        Ok(path)
+    }
+}
+
+impl Display for LogExcerpt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "# {} {} at {}",
+            self.case,
+            self.event,
+            self.at.format("%Y-%m-%d %H:%M:%S")
+        )?;
+        if let Some(step) = self.step {
+            write!(f, ", step {step}")?;
+        }
+        writeln!(f, ", last {} log lines", self.lines.len())?;
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
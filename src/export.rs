@@ -0,0 +1,167 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{PlotFormat, StatusSnapshot};
+
+/// Export of the status snapshots for the integrated-modeling pipeline
+///
+/// ```toml
+/// [export]
+/// parquet = "status"
+/// ics = "/shared/www/cfd-completions.ics"
+/// plots = "/shared/www/cfd-plots"
+/// plot_format = "png"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportConfig {
+    /// Directory a timestamped parquet table is written to at each update,
+    /// requires the `parquet` feature
+    pub parquet: Option<PathBuf>,
+    /// iCalendar file of the projected completions rewritten at each update,
+    /// e.g. served for the calendar clients to subscribe to
+    pub ics: Option<PathBuf>,
+    /// Directory the progress charts of the cases and of the campaign are rendered to
+    /// at each update, requires the `plot` feature
+    pub plots: Option<PathBuf>,
+    /// Image format of the progress charts, `svg` or `png`
+    #[serde(default)]
+    pub plot_format: PlotFormat,
+}
+
+impl ExportConfig {
+    /// Writes the snapshot to the configured exports
+    pub fn export(&self, snapshot: &StatusSnapshot) {
+        if let Some(path) = &self.ics {
+            if let Err(e) = write_atomically(path, &snapshot.to_ics()) {
+                tracing::error!(?path, error = %e, "failed to export the completion calendar");
+            }
+        }
+        let Some(dir) = &self.parquet else {
+            return;
+        };
+        #[cfg(feature = "parquet")]
+        {
+            let path = dir.join(format!(
+                "status-{}.parquet",
+                snapshot.taken().format("%Y%m%dT%H%M%S")
+            ));
+            if let Err(e) = std::fs::create_dir_all(dir)
+                .map_err(parquet::errors::ParquetError::from)
+                .and_then(|_| write_parquet(snapshot, &path))
+            {
+                tracing::error!(?path, error = %e, "failed to export snapshot to parquet");
+            }
+        }
+        #[cfg(not(feature = "parquet"))]
+        {
+            let _ = snapshot;
+            tracing::warn!(?dir, "parquet export requires the `parquet` feature");
+        }
+    }
+}
+
+/// Writes a file at once, through a temporary file renamed over it,
+/// so the file is never read partially written
+pub(crate) fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+/// Writes the snapshot as a parquet table with one row per case
+///
+/// The columns are `taken`, `case`, `group`, `state`, `step`, `total_step`, `time`,
+/// `step_time`, `step_time_p50`, `step_time_p90`, `step_time_p99`, `rate` (steps per hour),
+/// `eta` and `note`, timestamps being UTC milliseconds
+#[cfg(feature = "parquet")]
+pub fn write_parquet<P: AsRef<std::path::Path>>(
+    snapshot: &StatusSnapshot,
+    path: P,
+) -> parquet::errors::Result<()> {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt64Array,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use chrono::{Duration, Local};
+    use parquet::arrow::ArrowWriter;
+
+    use crate::Percentiles;
+
+    let cases: Vec<_> = snapshot.cases().collect();
+    let timestamp = || DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("taken", timestamp(), false),
+        Field::new("case", DataType::Utf8, false),
+        Field::new("group", DataType::Utf8, true),
+        Field::new("state", DataType::Utf8, false),
+        Field::new("step", DataType::UInt64, true),
+        Field::new("total_step", DataType::UInt64, false),
+        Field::new("time", DataType::Float64, false),
+        Field::new("step_time", DataType::Float64, false),
+        Field::new("step_time_p50", DataType::Float64, true),
+        Field::new("step_time_p90", DataType::Float64, true),
+        Field::new("step_time_p99", DataType::Float64, true),
+        Field::new("rate", DataType::Float64, true),
+        Field::new("eta", timestamp(), true),
+        Field::new("note", DataType::Utf8, true),
+    ]));
+    let taken = snapshot.taken().timestamp_millis();
+    let percentile = |p: fn(&Percentiles) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter(
+            cases
+                .iter()
+                .map(|c| c.step_time_percentiles().as_ref().map(p)),
+        ))
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(TimestampMillisecondArray::from(vec![taken; cases.len()]).with_timezone("UTC")),
+        Arc::new(StringArray::from_iter_values(
+            cases.iter().map(|c| c.name()),
+        )),
+        Arc::new(StringArray::from_iter(cases.iter().map(|c| c.group_name()))),
+        Arc::new(StringArray::from_iter_values(
+            cases.iter().map(|c| c.state()),
+        )),
+        Arc::new(UInt64Array::from_iter(
+            cases.iter().map(|c| c.step().map(|step| step as u64)),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            cases.iter().map(|c| c.total_step() as u64),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            cases.iter().map(|c| c.time()),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            cases.iter().map(|c| c.elapsed_per_step().mean()),
+        )),
+        percentile(|percentiles| percentiles.p50),
+        percentile(|percentiles| percentiles.p90),
+        percentile(|percentiles| percentiles.p99),
+        Arc::new(Float64Array::from_iter(
+            cases.iter().map(|c| c.steps_per_hour()),
+        )),
+        Arc::new(
+            TimestampMillisecondArray::from_iter(cases.iter().map(|c| {
+                (c.state() == "running")
+                    .then(|| (Local::now() + Duration::seconds(c.eta_secs())).timestamp_millis())
+            }))
+            .with_timezone("UTC"),
+        ),
+        Arc::new(StringArray::from_iter(
+            cases
+                .iter()
+                .map(|c| c.note().map(|note| note.text.as_str())),
+        )),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let mut writer = ArrowWriter::try_new(std::fs::File::create(path)?, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
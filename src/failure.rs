@@ -0,0 +1,103 @@
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::StepParser;
+
+/// Log line pattern of a fatal solver error, the case failing once it is found
+///
+/// ```toml
+/// [[error_patterns]]
+/// name = "diverged"
+/// pattern = "(?i)solution diverged"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "ErrorPatternConfig")]
+pub struct ErrorPattern {
+    name: String,
+    pattern: Regex,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorPatternConfig {
+    name: String,
+    pattern: String,
+}
+
+impl TryFrom<ErrorPatternConfig> for ErrorPattern {
+    type Error = regex::Error;
+
+    fn try_from(config: ErrorPatternConfig) -> Result<Self, Self::Error> {
+        Self::new(config.name, &config.pattern)
+    }
+}
+
+impl ErrorPattern {
+    /// Creates a new error pattern
+    pub fn new<S: ToString>(name: S, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern)?,
+        })
+    }
+    /// Returns the default patterns: floating point exception, divergence,
+    /// license checkout failure and segmentation fault
+    pub fn defaults() -> Vec<Self> {
+        [
+            (
+                "floating point exception",
+                r"(?i)floating point exception|\bSIGFPE\b",
+            ),
+            (
+                "diverged",
+                r"(?i)\b(solution|solver|residuals?)\b.*\bdiverged\b",
+            ),
+            ("license checkout failed", r"(?i)licen[cs]e checkout failed"),
+            ("segmentation fault", r"(?i)segmentation fault|\bSIGSEGV\b"),
+        ]
+        .into_iter()
+        .map(|(name, pattern)| Self::new(name, pattern).unwrap())
+        .collect()
+    }
+    /// Returns the name of the error
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Fatal solver error found in a log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogFailure {
+    /// Name of the error pattern
+    pub name: String,
+    /// Offending log line
+    pub line: String,
+    /// Time the error was found at
+    pub at: DateTime<Local>,
+}
+
+/// Returns the latest fatal error in `lines`, given the one found before
+/// and the parser of the time step lines
+///
+/// A time step after an error clears it, the solver having been restarted
+pub fn find_failure(
+    patterns: &[ErrorPattern],
+    found: Option<LogFailure>,
+    lines: &[String],
+    steps: &dyn StepParser,
+) -> Option<LogFailure> {
+    lines.iter().fold(found, |found, line| {
+        if steps.parse_line(line).is_some() {
+            return None;
+        }
+        patterns
+            .iter()
+            .find(|pattern| pattern.pattern.is_match(line))
+            .map(|pattern| LogFailure {
+                name: pattern.name.clone(),
+                line: line.trim().to_string(),
+                at: Local::now(),
+            })
+            .or(found)
+    })
+}
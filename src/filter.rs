@@ -0,0 +1,104 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Case;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FilterError {
+    #[error("invalid filter {0:?}, expected key=value")]
+    Syntax(String),
+    #[error("unknown filter key {0:?}, expected name, tag, group or state")]
+    Key(String),
+}
+
+/// Condition on a case, written `key=value`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum FilterTerm {
+    /// Case name containing the value
+    Name(String),
+    /// `_`-separated part of the case name, e.g. `OS7` for `zen30az045_OS7`
+    Tag(String),
+    /// Campaign of the case
+    Group(String),
+    /// State of the case, e.g. `running` or `paused`
+    State(String),
+}
+
+impl FilterTerm {
+    /// Returns true if the case satisfies the condition
+    pub fn matches(&self, case: &Case) -> bool {
+        match self {
+            Self::Name(name) => case.name().contains(name.as_str()),
+            Self::Tag(tag) => case.name().split('_').any(|part| part == tag),
+            Self::Group(group) => case.group_name() == Some(group.as_str()),
+            Self::State(state) => case.state() == state,
+        }
+    }
+}
+
+impl FromStr for FilterTerm {
+    type Err = FilterError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .filter(|(_, value)| !value.is_empty())
+            .ok_or_else(|| FilterError::Syntax(s.to_string()))?;
+        let value = value.to_string();
+        match key.trim() {
+            "name" => Ok(Self::Name(value)),
+            "tag" => Ok(Self::Tag(value)),
+            "group" => Ok(Self::Group(value)),
+            "state" => Ok(Self::State(value)),
+            key => Err(FilterError::Key(key.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for FilterTerm {
+    type Error = FilterError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<FilterTerm> for String {
+    fn from(term: FilterTerm) -> Self {
+        term.to_string()
+    }
+}
+
+impl Display for FilterTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name(value) => write!(f, "name={value}"),
+            Self::Tag(value) => write!(f, "tag={value}"),
+            Self::Group(value) => write!(f, "group={value}"),
+            Self::State(value) => write!(f, "state={value}"),
+        }
+    }
+}
+
+/// Selection of the cases of a bulk operation, all the cases without any condition
+///
+/// ```json
+/// {"terms": ["tag=OS7", "state=running"], "stalled": true}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseFilter {
+    /// Conditions all satisfied by the selected cases
+    #[serde(default)]
+    pub terms: Vec<FilterTerm>,
+    /// Selects only the stalled cases, dead solvers included
+    #[serde(default)]
+    pub stalled: bool,
+}
+
+impl CaseFilter {
+    /// Returns true if the case is selected
+    pub fn matches(&self, case: &Case) -> bool {
+        (!self.stalled || case.is_stalled() || case.is_solver_dead())
+            && self.terms.iter().all(|term| term.matches(case))
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+
+use crate::{Case, StatusSnapshot};
+
+/// Standard normal quantile of the 90th percentile
+const Z90: f64 = 1.2816;
+
+/// Projected completion of a case
+///
+/// The remaining run time is the remaining time steps at the mean time step duration,
+/// its uncertainty is the standard deviation of the latest time step durations
+/// applied to all the remaining time steps, as the step time drifts rather than jitters
+#[derive(Debug, Clone, Serialize)]
+pub struct Forecast {
+    pub case: String,
+    pub group: Option<String>,
+    pub state: &'static str,
+    /// Point estimate of the completion
+    pub eta: Option<DateTime<Local>>,
+    /// Standard deviation of the completion in seconds
+    pub sigma_secs: Option<f64>,
+    /// Completion with a 10% chance of being earlier
+    pub p10: Option<DateTime<Local>>,
+    /// Completion with a 90% chance of being earlier
+    pub p90: Option<DateTime<Local>>,
+}
+
+impl Forecast {
+    /// Returns the completion forecast of a case of the snapshot
+    pub fn new(snapshot: &StatusSnapshot, case: &Case) -> Self {
+        let (eta, sigma_secs) = if case.is_complete() {
+            (case.completion(), Some(0.))
+        } else if case.is_pending() {
+            // a projected run has no step time history yet
+            (snapshot.projection(case).map(|(_, finish)| finish), None)
+        } else if case.step().is_some() && case.elapsed_per_step().mean() > 0. {
+            let eta = snapshot.taken() + Duration::seconds(case.eta_secs());
+            let sigma = case
+                .step_times()
+                .std_dev()
+                .map(|sd| sd * case.remaining_steps() as f64);
+            (Some(eta), sigma)
+        } else {
+            (None, None)
+        };
+        let quantile = |z: f64| {
+            eta.map(|eta| {
+                eta + Duration::seconds((z * sigma_secs.unwrap_or_default()).round() as i64)
+            })
+        };
+        Self {
+            case: case.name().to_string(),
+            group: case.group_name().map(str::to_string),
+            state: case.state(),
+            eta,
+            sigma_secs,
+            // a running case cannot complete before the snapshot
+            p10: quantile(-Z90).map(|p10| {
+                if case.is_complete() {
+                    p10
+                } else {
+                    p10.max(snapshot.taken())
+                }
+            }),
+            p90: quantile(Z90),
+        }
+    }
+}
+
+/// Projected completion of every case and of the whole batch
+///
+/// The batch completes with its last case: its point estimate and percentiles
+/// are the latest of the cases, and it is unknown while any case is
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchForecast {
+    pub taken: DateTime<Local>,
+    pub cases: Vec<Forecast>,
+    pub eta: Option<DateTime<Local>>,
+    pub p10: Option<DateTime<Local>>,
+    pub p90: Option<DateTime<Local>>,
+}
+
+impl From<&StatusSnapshot> for BatchForecast {
+    fn from(snapshot: &StatusSnapshot) -> Self {
+        let cases: Vec<_> = snapshot
+            .cases()
+            .map(|case| Forecast::new(snapshot, case))
+            .collect();
+        let latest = |at: fn(&Forecast) -> Option<DateTime<Local>>| {
+            cases
+                .iter()
+                .map(at)
+                .collect::<Option<Vec<_>>>()
+                .and_then(|at| at.into_iter().max())
+        };
+        Self {
+            taken: snapshot.taken(),
+            eta: latest(|forecast| forecast.eta),
+            p10: latest(|forecast| forecast.p10),
+            p90: latest(|forecast| forecast.p90),
+            cases,
+        }
+    }
+}
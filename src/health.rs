@@ -0,0 +1,156 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alert, Case};
+
+/// Default weights of the health factors
+const STALLED_WEIGHT: f64 = 40.;
+const ETA_SLIP_WEIGHT: f64 = 25.;
+const ALERTS_WEIGHT: f64 = 20.;
+const IDLE_WEIGHT: f64 = 15.;
+/// Default mean ETA slip in hours scored as fully unhealthy
+const SLIP_HOURS: f64 = 24.;
+
+/// Weights of the factors of the campaign health index
+///
+/// ```toml
+/// [health]
+/// stalled = 40
+/// eta_slip = 25
+/// alerts = 20
+/// idle = 15
+/// slip_hours = 24
+/// ```
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Weight of the fraction of running cases stalled
+    pub stalled: f64,
+    /// Weight of the mean ETA slip of the running cases
+    pub eta_slip: f64,
+    /// Weight of the active alerts, critical alerts counting twice as much as warnings
+    /// and warnings twice as much as informational alerts
+    pub alerts: f64,
+    /// Weight of the fraction of cores held by stalled or failing cases
+    pub idle: f64,
+    /// Mean ETA slip in hours scored as fully unhealthy
+    pub slip_hours: f64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            stalled: STALLED_WEIGHT,
+            eta_slip: ETA_SLIP_WEIGHT,
+            alerts: ALERTS_WEIGHT,
+            idle: IDLE_WEIGHT,
+            slip_hours: SLIP_HOURS,
+        }
+    }
+}
+
+/// Campaign health index, from 0 (every factor at its worst) to 100 (healthy)
+///
+/// Each factor is scored from 0 (healthy) to 1 and the index is 100 less
+/// the weighted mean of the factors
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct HealthIndex {
+    pub score: f64,
+    /// Fraction of the running cases stalled
+    pub stalled: f64,
+    /// Mean ETA slip of the running cases relative to the slip scored as fully unhealthy
+    pub eta_slip: f64,
+    /// Severity-weighted alerts per running case
+    pub alerts: f64,
+    /// Fraction of the cores of the running cases held by stalled or failing cases
+    pub idle: f64,
+}
+
+impl HealthIndex {
+    /// Computes the health index of the running cases and the active alerts
+    pub fn new<'a>(
+        running: impl Iterator<Item = &'a Case>,
+        alerts: &[Alert],
+        config: &HealthConfig,
+    ) -> Self {
+        let running: Vec<_> = running.collect();
+        if running.is_empty() {
+            return Self {
+                score: 100.,
+                ..Default::default()
+            };
+        }
+        let n = running.len() as f64;
+        let stalled = running.iter().filter(|case| case.is_stalled()).count() as f64 / n;
+        // ETA slip over the ETA history of each case, ETAs moving earlier are no credit
+        let slips: Vec<f64> = running
+            .iter()
+            .filter_map(|case| {
+                let etas = case.etas();
+                let first = *etas.iter().next()?;
+                let last = etas.last().filter(|_| etas.len() > 1)?;
+                Some((last - first).max(0.) / 3600.)
+            })
+            .collect();
+        let eta_slip = if slips.is_empty() || config.slip_hours <= 0. {
+            0.
+        } else {
+            (slips.iter().sum::<f64>() / slips.len() as f64 / config.slip_hours).min(1.)
+        };
+        let alerts = (alerts
+            .iter()
+            .filter(|alert| alert.expected.is_none())
+            .map(|alert| alert.severity().weight())
+            .fold(0., |sum, score| sum + score)
+            / n)
+            .min(1.);
+        // cases with an unknown number of cores count as a single core
+        let cores = |case: &Case| case.cores().unwrap_or(1) as f64;
+        let total = running.iter().map(|case| cores(case)).sum::<f64>();
+        // no idle cores if the cases run on none, e.g. `solve-0_14.out`
+        let idle = if total > 0. {
+            running
+                .iter()
+                .filter(|case| {
+                    case.is_stalled() || case.error().is_some() || case.log_failure().is_some()
+                })
+                .map(|case| cores(case))
+                .fold(0., |sum, cores| sum + cores)
+                / total
+        } else {
+            0.
+        };
+        let weights = config.stalled + config.eta_slip + config.alerts + config.idle;
+        let penalty = if weights > 0. {
+            (config.stalled * stalled
+                + config.eta_slip * eta_slip
+                + config.alerts * alerts
+                + config.idle * idle)
+                / weights
+        } else {
+            0.
+        };
+        Self {
+            score: (100. * (1. - penalty)).clamp(0., 100.),
+            stalled,
+            eta_slip,
+            alerts,
+            idle,
+        }
+    }
+}
+
+impl Display for HealthIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "health {:.0}/100 (stalled {:.0}%, ETA slip {:.0}%, alerts {:.0}%, idle {:.0}%)",
+            self.score,
+            100. * self.stalled,
+            100. * self.eta_slip,
+            100. * self.alerts,
+            100. * self.idle
+        )
+    }
+}
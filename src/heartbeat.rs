@@ -0,0 +1,111 @@
+use std::{fs, io, path::PathBuf};
+#[cfg(feature = "monitor")]
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::StatusSnapshot;
+
+/// Timeout of the heartbeat pings
+#[cfg(feature = "monitor")]
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Heartbeat of the monitor itself, updated at each poll so an external watchdog
+/// can alert when the monitor stops updating
+///
+/// ```toml
+/// [heartbeat]
+/// file = "/shared/cfd-status/heartbeat.json"
+/// url = "https://nosnch.in/c2345d8a1b"
+/// ```
+///
+/// The file is rewritten with the time of the latest poll, its modification time
+/// going stale if the monitor hangs or dies, and the url is pinged, e.g. a
+/// dead man's snitch or a healthchecks.io check
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HeartbeatConfig {
+    /// Status file rewritten at each poll
+    pub file: Option<PathBuf>,
+    /// URL requested at each poll
+    pub url: Option<String>,
+}
+
+/// Heartbeat updated with each snapshot of the monitor
+///
+/// The pings are sent from a separate thread, a ping being skipped while the previous one is pending
+#[derive(Debug, Default)]
+pub struct Heartbeat {
+    config: HeartbeatConfig,
+    beats: usize,
+    #[cfg(feature = "monitor")]
+    pending: Arc<AtomicBool>,
+}
+
+impl From<HeartbeatConfig> for Heartbeat {
+    fn from(config: HeartbeatConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+}
+
+impl Heartbeat {
+    /// Writes the status file and pings the url, given the latest snapshot and the number
+    /// of times the polling loop was restarted
+    pub fn beat(&mut self, snapshot: &StatusSnapshot, restarts: usize) {
+        self.beats += 1;
+        if let Some(path) = &self.config.file {
+            if let Err(e) = self.write(snapshot, restarts) {
+                tracing::warn!(path = %path.display(), error = %e, "failed to write the heartbeat");
+            }
+        }
+        if let Some(url) = &self.config.url {
+            self.ping(url.clone());
+        }
+    }
+    #[cfg(feature = "monitor")]
+    fn ping(&self, url: String) {
+        if self.pending.swap(true, Ordering::Relaxed) {
+            tracing::debug!(url, "previous heartbeat ping pending, skipped");
+            return;
+        }
+        let pending = self.pending.clone();
+        thread::spawn(move || {
+            if let Err(e) = ureq::get(&url).timeout(PING_TIMEOUT).call() {
+                tracing::warn!(url, error = %e, "heartbeat ping failed");
+            }
+            pending.store(false, Ordering::Relaxed);
+        });
+    }
+    #[cfg(not(feature = "monitor"))]
+    fn ping(&self, url: String) {
+        tracing::warn!(url, "heartbeat pings require the `monitor` feature");
+    }
+    /// Writes the status file, replacing the previous one at once
+    /// so the watchdog never reads a partial file
+    fn write(&self, snapshot: &StatusSnapshot, restarts: usize) -> io::Result<()> {
+        let Some(path) = &self.config.file else {
+            return Ok(());
+        };
+        let status = json!({
+            "at": Local::now().to_rfc3339(),
+            "beats": self.beats,
+            "running": snapshot.running().count(),
+            "completed": snapshot.completed().len(),
+            "restarts": restarts,
+        });
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, status.to_string())?;
+        fs::rename(tmp, path)
+    }
+}
@@ -0,0 +1,74 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::{Days, NaiveDate, Weekday};
+
+/// Cells of the calendar, from no progress to the busiest day
+const SHADES: [&str; 5] = ["··", "░░", "▒▒", "▓▓", "██"];
+
+/// Calendar heatmap of the simulated seconds completed per day, a row per week from Monday
+///
+/// ```text
+///             Mo Tu We Th Fr Sa Su
+/// 2024-06-10  ██ ▓▓ ▓▓ ██ ▒▒ ░░ ··   4520.00s
+/// 2024-06-17  ▓▓ ██ ▓▓                2890.00s
+/// ```
+///
+/// The days without data, the monitor not running, are left blank
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThroughputCalendar {
+    days: BTreeMap<NaiveDate, f64>,
+}
+
+impl ThroughputCalendar {
+    /// Creates the calendar of the simulated seconds completed on each day
+    pub fn new(days: impl IntoIterator<Item = (NaiveDate, f64)>) -> Self {
+        let mut calendar = Self::default();
+        for (day, secs) in days {
+            *calendar.days.entry(day).or_default() += secs;
+        }
+        calendar
+    }
+    /// Returns the simulated seconds completed on the busiest day
+    pub fn max(&self) -> f64 {
+        self.days.values().copied().fold(0., f64::max)
+    }
+    /// Returns the cell of a day, shaded by quarter of the busiest day
+    fn cell(&self, secs: f64) -> &'static str {
+        let max = self.max();
+        if secs <= 0. || max <= 0. {
+            return SHADES[0];
+        }
+        SHADES[((4. * secs / max).ceil() as usize).clamp(1, 4)]
+    }
+}
+
+impl Display for ThroughputCalendar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Some(first), Some(last)) = (self.days.keys().next(), self.days.keys().last()) else {
+            return writeln!(f, "no history");
+        };
+        writeln!(f, "{:12}Mo Tu We Th Fr Sa Su", "")?;
+        let mut monday = first.week(Weekday::Mon).first_day();
+        while monday <= *last {
+            write!(f, "{monday}  ")?;
+            let mut total = 0.;
+            for day in (0..7).filter_map(|i| monday.checked_add_days(Days::new(i))) {
+                match self.days.get(&day) {
+                    Some(secs) => {
+                        total += secs;
+                        write!(f, "{} ", self.cell(*secs))?;
+                    }
+                    None => write!(f, "   ")?,
+                }
+            }
+            writeln!(f, "{total:>10.2}s")?;
+            monday = monday + Days::new(7);
+        }
+        write!(f, "{} none", SHADES[0])?;
+        for (i, shade) in SHADES[1..].iter().enumerate() {
+            write!(f, "  {shade} <={}%", 25 * (i + 1))?;
+        }
+        writeln!(f, " of {:.2}s", self.max())?;
+        Ok(())
+    }
+}
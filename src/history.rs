@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+/// Default number of samples kept in a history
+pub const HISTORY_LEN: usize = 12;
+/// Sparkline levels from lowest to highest
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Sparkline mark of the samples spanning an offline interval of the monitor
+const OFFLINE: char = '┆';
+
+/// Ring buffer of the latest samples of a value
+///
+/// The samples spanning an interval the monitor was offline in are marked,
+/// being spread evenly over it instead of measured
+#[derive(Debug, Clone)]
+pub struct History {
+    values: VecDeque<f64>,
+    offline: VecDeque<bool>,
+    capacity: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(HISTORY_LEN)
+    }
+}
+
+impl History {
+    /// Creates a new history holding up to `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: VecDeque::with_capacity(capacity),
+            offline: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+    /// Adds a sample, discarding the oldest one if the history is full
+    pub fn push(&mut self, value: f64) -> &mut Self {
+        self.push_over(value, false)
+    }
+    /// Adds a sample spanning an interval the monitor was offline in
+    pub fn push_offline(&mut self, value: f64) -> &mut Self {
+        self.push_over(value, true)
+    }
+    fn push_over(&mut self, value: f64, offline: bool) -> &mut Self {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+            self.offline.pop_front();
+        }
+        self.values.push_back(value);
+        self.offline.push_back(offline);
+        self
+    }
+    /// Returns an iterator over the flags of the samples spanning an offline interval,
+    /// from oldest to latest
+    pub fn offline(&self) -> impl Iterator<Item = bool> + '_ {
+        self.offline.iter().copied()
+    }
+    /// Returns an iterator over the samples, from oldest to latest
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.values.iter()
+    }
+    /// Returns the number of samples
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    /// Returns true if there is no sample
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    /// Returns the latest sample
+    pub fn last(&self) -> Option<f64> {
+        self.values.back().copied()
+    }
+    /// Returns the mean of the samples
+    pub fn mean(&self) -> Option<f64> {
+        (!self.is_empty()).then(|| self.values.iter().sum::<f64>() / self.len() as f64)
+    }
+    /// Returns the sample standard deviation, if there are at least 2 samples
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean().filter(|_| self.len() > 1)?;
+        let var =
+            self.values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (self.len() - 1) as f64;
+        Some(var.sqrt())
+    }
+    /// Renders the samples as a unicode sparkline scaled between the minimum and maximum samples,
+    /// the samples spanning an offline interval being marked with `┆`
+    pub fn sparkline(&self) -> String {
+        let (min, max) = self
+            .values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &v| {
+                (min.min(v), max.max(v))
+            });
+        let range = max - min;
+        self.values
+            .iter()
+            .zip(&self.offline)
+            .map(|(&v, &offline)| {
+                if offline {
+                    OFFLINE
+                } else if range > 0. {
+                    let level = ((v - min) / range * (LEVELS.len() - 1) as f64).round() as usize;
+                    LEVELS[level]
+                } else {
+                    LEVELS[LEVELS.len() / 2]
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::PathBuf,
+    thread::{self, JoinHandle},
+};
+
+use chrono::Local;
+use serde::Deserialize;
+
+use crate::{shell, Case, Redactor};
+
+/// Default file the hook execution results are appended to
+const HOOKS_LOG: &str = "cfd-status-hooks.log";
+
+/// Case events that can trigger a hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseEvent {
+    Completed,
+    Stalled,
+    SolverDead,
+    Failed,
+    DiskAtRisk,
+}
+
+impl Display for CaseEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaseEvent::Completed => write!(f, "completed"),
+            CaseEvent::Stalled => write!(f, "stalled"),
+            CaseEvent::SolverDead => write!(f, "dead"),
+            CaseEvent::Failed => write!(f, "failed"),
+            CaseEvent::DiskAtRisk => write!(f, "disk"),
+        }
+    }
+}
+
+/// Shell commands executed on case events
+#[derive(Debug, Clone, Deserialize)]
+pub struct HooksConfig {
+    /// Command executed when a case completes
+    pub completed: Option<String>,
+    /// Command executed when a case stalls
+    pub stalled: Option<String>,
+    /// Command executed when the solver of a stalled case is found dead
+    pub dead: Option<String>,
+    /// Command executed when a case update fails
+    pub failed: Option<String>,
+    /// Command executed when a case is at risk of running out of disk space
+    pub disk: Option<String>,
+    /// File the hook execution results are appended to
+    #[serde(default = "default_log")]
+    pub log: PathBuf,
+}
+
+fn default_log() -> PathBuf {
+    PathBuf::from(HOOKS_LOG)
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            completed: None,
+            stalled: None,
+            dead: None,
+            failed: None,
+            disk: None,
+            log: default_log(),
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Returns the command associated to an event
+    pub fn command(&self, event: CaseEvent) -> Option<&str> {
+        match event {
+            CaseEvent::Completed => self.completed.as_deref(),
+            CaseEvent::Stalled => self.stalled.as_deref(),
+            CaseEvent::SolverDead => self.dead.as_deref(),
+            CaseEvent::Failed => self.failed.as_deref(),
+            CaseEvent::DiskAtRisk => self.disk.as_deref(),
+        }
+    }
+}
+
+/// Hooks runner
+///
+/// Each command is executed with `sh -c`, `cmd /C` on Windows, in a separate thread with the case
+/// metadata exposed as `CFD_*` environment variables
+#[derive(Debug, Default)]
+pub struct Hooks {
+    config: HooksConfig,
+    pending: Vec<JoinHandle<()>>,
+    redactor: Redactor,
+}
+
+impl Hooks {
+    /// Creates a new hooks runner
+    pub fn new(config: HooksConfig) -> Self {
+        Self {
+            config,
+            pending: vec![],
+            redactor: Redactor::default(),
+        }
+    }
+    /// Redacts the `CFD_*` environment variables passed to the commands
+    pub fn redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+    /// Executes the command associated to an event, if any
+    ///
+    /// `detail` is exposed as `CFD_DETAIL` (e.g. the error message of a failed update)
+    pub fn trigger(&mut self, event: CaseEvent, case: &Case, detail: Option<&str>) {
+        let Some(command) = self.config.command(event) else {
+            return;
+        };
+        let mut cmd = shell(command);
+        let redact = |value: &str| self.redactor.redact(value);
+        cmd.env("CFD_EVENT", event.to_string())
+            .env("CFD_CASE", redact(case.name()))
+            .env("CFD_LOG", redact(&case.log_file()))
+            .env("CFD_DURATION", case.duration().to_string())
+            .env(
+                "CFD_STEP",
+                case.step().map_or(String::new(), |s| s.to_string()),
+            )
+            .env("CFD_TIME", case.time().to_string())
+            .env("CFD_DETAIL", redact(detail.unwrap_or_default()));
+        tracing::info!(%event, case = case.name(), command, "triggering hook");
+        let log = self.config.log.clone();
+        let header = format!(
+            "{} {event} {}: `{command}`",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            case.name()
+        );
+        self.pending.retain(|handle| !handle.is_finished());
+        self.pending.push(thread::spawn(move || {
+            let record = match cmd.output() {
+                Ok(output) => format!(
+                    "{header} exited with {}\n{}{}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+                Err(e) => format!("{header} failed to execute: {e}\n"),
+            };
+            if let Err(e) = append(&log, &record) {
+                tracing::error!(?log, error = %e, "failed to write hook log");
+            }
+        }));
+    }
+    /// Waits for all the running hooks to finish
+    pub fn wait(&mut self) {
+        for handle in self.pending.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn append(path: &PathBuf, record: &str) -> io::Result<()> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(record.as_bytes())
+}
@@ -0,0 +1,691 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Cursor, Read},
+    net::{SocketAddr, ToSocketAddrs},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    is_authorized, Aggregate, Badge, BatchForecast, CaseConfig, CaseFilter, CaseUpdateStats,
+    HttpConfig, Redactor, StatusSnapshot,
+};
+
+/// Number of the latest snapshots kept for `GET /snapshots/{seq}`
+pub const SNAPSHOT_HISTORY: usize = 64;
+/// Largest body of the POST requests, larger bodies being answered with `413 Payload Too Large`
+pub const MAX_BODY: u64 = 16 << 20;
+
+/// Change to the monitor requested over HTTP
+#[derive(Debug, Clone)]
+pub enum Control {
+    /// Adds a case, replacing the case of the same name
+    Add(Box<CaseConfig>),
+    /// Removes the case of the given name
+    Remove(String),
+    /// Polls all the cases without waiting for the next update
+    Refresh,
+    /// Pauses the updates of the case of the given name
+    Pause(String),
+    /// Resumes the updates of the case of the given name
+    Resume(String),
+    /// Acknowledges the active alerts of the case of the given name
+    Acknowledge(String),
+}
+
+/// Operation applied by `POST /ctl/{operation}` to all the cases matching a [CaseFilter]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkOperation {
+    Pause,
+    Resume,
+    Ack,
+    Remove,
+}
+
+impl BulkOperation {
+    /// Returns the name of the operation in the URL
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Ack => "ack",
+            Self::Remove => "remove",
+        }
+    }
+    /// Returns the names of the cases of the snapshot matching `filter`
+    /// that the operation changes
+    fn select(&self, snapshot: &StatusSnapshot, filter: &CaseFilter) -> Vec<String> {
+        snapshot
+            .cases()
+            .filter(|case| filter.matches(case))
+            .filter(|case| match self {
+                Self::Pause => !case.is_complete() && !case.is_paused(),
+                Self::Resume => case.is_paused(),
+                Self::Ack => snapshot
+                    .alerts()
+                    .iter()
+                    .any(|alert| alert.case == case.name() && !alert.acknowledged),
+                Self::Remove => true,
+            })
+            .map(|case| case.name().to_string())
+            .collect()
+    }
+    fn control(&self, name: String) -> Control {
+        match self {
+            Self::Pause => Control::Pause(name),
+            Self::Resume => Control::Resume(name),
+            Self::Ack => Control::Acknowledge(name),
+            Self::Remove => Control::Remove(name),
+        }
+    }
+}
+
+impl FromStr for BulkOperation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Pause, Self::Resume, Self::Ack, Self::Remove]
+            .into_iter()
+            .find(|operation| operation.name() == s)
+            .ok_or_else(|| format!("unknown operation {s:?}"))
+    }
+}
+
+/// HTTP server of the latest status snapshot
+///
+/// Serves `GET /status` as redacted JSON, `GET /healthz`, the campaign health index
+/// as Prometheus metrics on `GET /metrics`, the completion forecast of the cases
+/// and of the batch on `GET /forecast` and a REST API of the cases:
+///  - `GET /cases` and `GET /cases/{name}`: status of all the cases or of a single case
+///  - `POST /cases`: adds the case of the JSON [CaseConfig] in the request body,
+///    `403 Forbidden` if it sets [CaseConfig::command_fields]
+///  - `DELETE /cases/{name}`: removes a case
+///  - `POST /refresh`: polls all the cases without waiting
+///  - `GET /badge/campaign` and `GET /badge/{name}`: SVG status badge of the campaign or of a case,
+///    showing the progress and colored by state
+///  - `POST /ctl/{pause,resume,ack,remove}`: applies a [BulkOperation] to the cases
+///    of the JSON [CaseFilter] in the request body, answering the names of the cases changed
+///  - `GET /snapshots/{seq}`: one of the latest [SNAPSHOT_HISTORY] snapshots by sequence number,
+///    `410 Gone` once dropped, for the consumers that missed updates to catch up
+///  - `POST /push` and `GET /aggregate`: the snapshots pushed by the monitors of other clusters,
+///    see [AggregationConfig](crate::AggregationConfig), and their merged status
+///
+/// Changes are queued as [Control]s, answered with `202 Accepted`,
+/// and applied by the monitor loop after the wake-up flag is raised.
+/// Request bodies larger than [MAX_BODY] are answered with `413 Payload Too Large`
+///
+/// With a bearer token, the requests but `GET /healthz` without it are answered
+/// with `401 Unauthorized`, the changes requiring the control token if set,
+/// and with a certificate the server terminates TLS, see [HttpConfig].
+/// Without a token, the server only listens on the loopback interface
+pub struct StatusServer {
+    latest: Arc<Mutex<Option<StatusSnapshot>>>,
+    history: Arc<Mutex<VecDeque<StatusSnapshot>>>,
+    controls: Arc<Mutex<Vec<Control>>>,
+    aggregate: Arc<Mutex<Aggregate>>,
+    addr: Option<SocketAddr>,
+}
+
+impl StatusServer {
+    /// Starts the server on `addr` (e.g. `0.0.0.0:8080`) in a separate thread,
+    /// authenticated and over TLS as `access` sets
+    ///
+    /// `wake` is raised whenever a [Control] is queued,
+    /// an address beyond the loopback interface requiring a token
+    pub fn start(
+        addr: &str,
+        redactor: Redactor,
+        wake: Arc<AtomicBool>,
+        access: &HttpConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (token, control_token) = (access.token(), access.control_token());
+        if token.is_none() && !is_loopback(addr) {
+            return Err(format!(
+                "{addr} is not a loopback address, serving it requires an [http] token"
+            )
+            .into());
+        }
+        let server = match &access.tls {
+            None => Server::http(addr)?,
+            #[cfg(feature = "tls")]
+            Some(tls) => {
+                let read = |path: &std::path::Path| {
+                    std::fs::read(path).map_err(|e| format!("reading {}: {e}", path.display()))
+                };
+                Server::https(
+                    addr,
+                    tiny_http::SslConfig {
+                        certificate: read(&tls.certificate)?,
+                        private_key: read(&tls.private_key)?,
+                    },
+                )?
+            }
+            #[cfg(not(feature = "tls"))]
+            Some(_) => return Err("TLS requires the `tls` feature".into()),
+        };
+        let listening = server.server_addr().to_ip();
+        tracing::info!(
+            addr,
+            tls = access.tls.is_some(),
+            authenticated = token.is_some(),
+            "HTTP server started"
+        );
+        let latest: Arc<Mutex<Option<StatusSnapshot>>> = Default::default();
+        let controls: Arc<Mutex<Vec<Control>>> = Default::default();
+        let history: Arc<Mutex<VecDeque<StatusSnapshot>>> = Default::default();
+        let aggregate: Arc<Mutex<Aggregate>> = Default::default();
+        let snapshot = latest.clone();
+        let pushed = aggregate.clone();
+        let snapshots = history.clone();
+        let queue = controls.clone();
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let enqueue = |control: Control| {
+                    tracing::info!(?control, "control requested");
+                    queue.lock().unwrap().push(control);
+                    wake.store(true, Ordering::Relaxed);
+                };
+                let control = |control: Control| {
+                    enqueue(control);
+                    Response::from_string("accepted").with_status_code(202)
+                };
+                let authorization = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Authorization"))
+                    .map(|header| header.value.to_string());
+                let authorized = |token: &Option<String>| {
+                    token
+                        .as_deref()
+                        .is_none_or(|token| is_authorized(authorization.as_deref(), token))
+                };
+                // the control token is also good for reading
+                let reader =
+                    authorized(&token) || control_token.is_some() && authorized(&control_token);
+                let response = match route(&request) {
+                    Route::Health => Response::from_string("ok"),
+                    Route::Add | Route::Remove(_) | Route::Refresh | Route::Bulk(_)
+                        if !authorized(&control_token) =>
+                    {
+                        unauthorized()
+                    }
+                    _ if !reader => unauthorized(),
+                    Route::Status => {
+                        served(&snapshot, |snapshot| json(snapshot.to_json(&redactor)))
+                    }
+                    Route::Metrics => served(&snapshot, |snapshot| {
+                        Response::from_string(metrics(snapshot, &redactor)).with_header(
+                            Header::from_bytes("Content-Type", "text/plain; version=0.0.4")
+                                .unwrap(),
+                        )
+                    }),
+                    Route::Forecast => served(&snapshot, |snapshot| {
+                        let forecast = serde_json::to_value(BatchForecast::from(snapshot))
+                            .expect("forecast serialization failed");
+                        json(redactor.redact_value(forecast).to_string())
+                    }),
+                    Route::Snapshot(sequence) => {
+                        let snapshots = snapshots.lock().unwrap();
+                        match snapshots
+                            .iter()
+                            .find(|snapshot| snapshot.sequence() == Some(sequence))
+                        {
+                            Some(snapshot) => json(snapshot.to_json(&redactor)),
+                            None if snapshots
+                                .front()
+                                .and_then(StatusSnapshot::sequence)
+                                .is_some_and(|oldest| sequence < oldest) =>
+                            {
+                                Response::from_string(
+                                    "snapshot no longer kept, resync from /status",
+                                )
+                                .with_status_code(410)
+                            }
+                            None => not_found(),
+                        }
+                    }
+                    Route::Cases => {
+                        served(&snapshot, |snapshot| json(snapshot.cases_json(&redactor)))
+                    }
+                    Route::Case(name) => served(&snapshot, |snapshot| {
+                        snapshot
+                            .case_json(&name, &redactor)
+                            .map_or_else(not_found, json)
+                    }),
+                    Route::Badge(name) => served(&snapshot, |snapshot| {
+                        let badge = match name.as_deref() {
+                            None => Some(Badge::campaign(snapshot)),
+                            Some(name) => snapshot
+                                .cases()
+                                .find(|case| case.name() == name)
+                                .map(Badge::case),
+                        };
+                        badge.map_or_else(not_found, |badge| {
+                            let label = redactor.redact(&badge.label);
+                            svg(badge.with_label(label).to_svg())
+                        })
+                    }),
+                    Route::Add => {
+                        let config = read_body(&mut request).map(|body| {
+                            serde_json::from_str::<CaseConfig>(&body).map_err(|e| e.to_string())
+                        });
+                        match config {
+                            Ok(Ok(config)) if !config.command_fields().is_empty() => {
+                                Response::from_string(format!(
+                                    "case fields running commands cannot be set over HTTP: {}",
+                                    config.command_fields().join(", ")
+                                ))
+                                .with_status_code(403)
+                            }
+                            Ok(Ok(config)) => control(Control::Add(Box::new(config))),
+                            Ok(Err(e)) => Response::from_string(format!("invalid case: {e}"))
+                                .with_status_code(400),
+                            Err(response) => response,
+                        }
+                    }
+                    Route::Remove(name) => {
+                        let exists = snapshot
+                            .lock()
+                            .unwrap()
+                            .as_ref()
+                            .is_some_and(|snapshot| snapshot.cases().any(|c| c.name() == name));
+                        if exists {
+                            control(Control::Remove(name))
+                        } else {
+                            not_found()
+                        }
+                    }
+                    Route::Refresh => control(Control::Refresh),
+                    Route::Bulk(operation) => {
+                        let filter = read_body(&mut request).map(|body| {
+                            serde_json::from_str::<CaseFilter>(&body).map_err(|e| e.to_string())
+                        });
+                        match filter {
+                            Ok(Ok(filter)) => served(&snapshot, |snapshot| {
+                                let names = operation.select(snapshot, &filter);
+                                if names.is_empty() {
+                                    return Response::from_string("no matching case")
+                                        .with_status_code(404);
+                                }
+                                names
+                                    .iter()
+                                    .for_each(|name| enqueue(operation.control(name.clone())));
+                                json(serde_json::to_string(&names).unwrap()).with_status_code(202)
+                            }),
+                            Ok(Err(e)) => Response::from_string(format!("invalid filter: {e}"))
+                                .with_status_code(400),
+                            Err(response) => response,
+                        }
+                    }
+                    Route::Push => {
+                        let cluster = read_body(&mut request).map(|body| {
+                            serde_json::from_str::<serde_json::Value>(&body)
+                                .map_err(|e| e.to_string())
+                                .and_then(|published| {
+                                    pushed
+                                        .lock()
+                                        .unwrap()
+                                        .insert(&published)
+                                        .map_err(|e| e.to_string())
+                                })
+                        });
+                        match cluster {
+                            Ok(Ok(cluster)) => {
+                                tracing::debug!(cluster, "snapshot pushed");
+                                Response::from_string("accepted").with_status_code(202)
+                            }
+                            Ok(Err(e)) => Response::from_string(format!("invalid snapshot: {e}"))
+                                .with_status_code(400),
+                            Err(response) => response,
+                        }
+                    }
+                    Route::Aggregate => json(pushed.lock().unwrap().to_json()),
+                    Route::NotFound => not_found(),
+                };
+                tracing::debug!(
+                    method = %request.method(),
+                    url = request.url(),
+                    status = ?response.status_code(),
+                    "HTTP request"
+                );
+                if let Err(e) = request.respond(response) {
+                    tracing::warn!(error = %e, "failed to send HTTP response");
+                }
+            }
+        });
+        Ok(Self {
+            latest,
+            history,
+            controls,
+            aggregate,
+            addr: listening,
+        })
+    }
+    /// Returns the address the server listens on, e.g. the port the system picked for port 0
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+    /// Sets the snapshot served, kept by sequence number if numbered
+    pub fn update(&self, snapshot: &StatusSnapshot) {
+        *self.latest.lock().unwrap() = Some(snapshot.clone());
+        if snapshot.sequence().is_some() {
+            let mut history = self.history.lock().unwrap();
+            if history.len() == SNAPSHOT_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(snapshot.clone());
+        }
+    }
+    /// Returns the changes requested since the previous call, in order
+    pub fn controls(&self) -> Vec<Control> {
+        self.controls.lock().unwrap().drain(..).collect()
+    }
+    /// Returns the merged status of the snapshots pushed so far
+    pub fn aggregate(&self) -> Aggregate {
+        self.aggregate.lock().unwrap().clone()
+    }
+}
+
+/// Endpoints of the server
+enum Route {
+    Health,
+    Status,
+    Metrics,
+    Forecast,
+    Cases,
+    Case(String),
+    Add,
+    Remove(String),
+    Refresh,
+    Bulk(BulkOperation),
+    /// Snapshot of the given sequence number
+    Snapshot(u64),
+    /// Badge of the case of the given name, of the campaign if none
+    Badge(Option<String>),
+    Push,
+    Aggregate,
+    NotFound,
+}
+
+fn route(request: &Request) -> Route {
+    let path = request.url().split('?').next().unwrap_or_default();
+    let name = path
+        .strip_prefix("/cases/")
+        .filter(|name| !name.is_empty())
+        .map(str::to_string);
+    if let Some(badge) = path.strip_prefix("/badge/") {
+        return match (request.method(), badge) {
+            (Method::Get, "campaign") => Route::Badge(None),
+            (Method::Get, name) if !name.is_empty() => Route::Badge(Some(name.to_string())),
+            _ => Route::NotFound,
+        };
+    }
+    if let Some(sequence) = path.strip_prefix("/snapshots/") {
+        return match (request.method(), sequence.parse()) {
+            (Method::Get, Ok(sequence)) => Route::Snapshot(sequence),
+            _ => Route::NotFound,
+        };
+    }
+    if let Some(operation) = path.strip_prefix("/ctl/") {
+        return match (request.method(), operation.parse()) {
+            (Method::Post, Ok(operation)) => Route::Bulk(operation),
+            _ => Route::NotFound,
+        };
+    }
+    match (request.method(), path, name) {
+        (Method::Get, "/healthz", _) => Route::Health,
+        (Method::Get, "/status", _) => Route::Status,
+        (Method::Get, "/metrics", _) => Route::Metrics,
+        (Method::Get, "/forecast", _) => Route::Forecast,
+        (Method::Get, "/cases", _) => Route::Cases,
+        (Method::Get, _, Some(name)) => Route::Case(name),
+        (Method::Post, "/cases", _) => Route::Add,
+        (Method::Delete, _, Some(name)) => Route::Remove(name),
+        (Method::Post, "/refresh", _) => Route::Refresh,
+        (Method::Post, "/push", _) => Route::Push,
+        (Method::Get, "/aggregate", _) => Route::Aggregate,
+        _ => Route::NotFound,
+    }
+}
+
+type Body = Response<Cursor<Vec<u8>>>;
+
+/// Answers from the latest snapshot, if any
+fn served(
+    latest: &Mutex<Option<StatusSnapshot>>,
+    respond: impl FnOnce(&StatusSnapshot) -> Body,
+) -> Body {
+    match &*latest.lock().unwrap() {
+        Some(snapshot) => respond(snapshot),
+        None => Response::from_string("no status yet").with_status_code(503),
+    }
+}
+
+/// Returns the campaign health index and the cost of the latest poll in the Prometheus text format,
+/// the names of the cases redacted
+fn metrics(snapshot: &StatusSnapshot, redactor: &Redactor) -> String {
+    let mut metrics: String = snapshot
+        .health()
+        .into_iter()
+        .flat_map(|health| {
+            [
+                ("score", "Campaign health index from 0 to 100", health.score),
+                ("stalled", "Fraction of the running cases stalled", health.stalled),
+                ("eta_slip", "Relative mean ETA slip", health.eta_slip),
+                ("alerts", "Severity-weighted alerts per running case", health.alerts),
+                ("idle", "Fraction of the cores held by stalled or failing cases", health.idle),
+            ]
+        })
+        .map(|(name, help, value)| {
+            format!(
+                "# HELP cfd_status_health_{name} {help}\n# TYPE cfd_status_health_{name} gauge\ncfd_status_health_{name} {value}\n"
+            )
+        })
+        .collect();
+    let name = "cfd_status_case_step_time_seconds";
+    metrics += &format!(
+        "# HELP {name} Time step duration of the case over the simulation\n# TYPE {name} summary\n"
+    );
+    for case in snapshot.cases() {
+        let Some(percentiles) = case.step_time_percentiles() else {
+            continue;
+        };
+        for (quantile, secs) in [
+            ("0.5", percentiles.p50),
+            ("0.9", percentiles.p90),
+            ("0.99", percentiles.p99),
+        ] {
+            metrics += &format!(
+                "{name}{{case=\"{}\",quantile=\"{quantile}\"}} {secs}\n",
+                label(redactor, case.name())
+            );
+        }
+        let distribution = case.step_time_distribution();
+        metrics += &format!(
+            "{name}_sum{{case=\"{0}\"}} {1}\n{name}_count{{case=\"{0}\"}} {2}\n",
+            label(redactor, case.name()),
+            distribution.sum(),
+            distribution.steps()
+        );
+    }
+    let Some(stats) = snapshot.poll_stats() else {
+        return metrics;
+    };
+    for (name, help, value) in [
+        ("seconds", "Duration of the latest poll", stats.secs),
+        (
+            "bytes_read",
+            "Bytes of the logs read by the latest poll",
+            stats.bytes() as f64,
+        ),
+        (
+            "parse_failures",
+            "Updates of the latest poll failing to parse the log",
+            stats.parse_failures() as f64,
+        ),
+    ] {
+        metrics += &format!(
+            "# HELP cfd_status_poll_{name} {help}\n# TYPE cfd_status_poll_{name} gauge\ncfd_status_poll_{name} {value}\n"
+        );
+    }
+    let mut by_case = |name: &str, help: &str, value: fn(&CaseUpdateStats) -> f64| {
+        metrics +=
+            &format!("# HELP cfd_status_case_{name} {help}\n# TYPE cfd_status_case_{name} gauge\n");
+        for case in &stats.cases {
+            metrics += &format!(
+                "cfd_status_case_{name}{{case=\"{}\"}} {}\n",
+                label(redactor, &case.case),
+                value(case)
+            );
+        }
+    };
+    by_case(
+        "update_seconds",
+        "Duration of the update of the case by the latest poll",
+        |case| case.secs,
+    );
+    by_case(
+        "bytes_read",
+        "Bytes of the log of the case read by the latest poll",
+        |case| case.bytes as f64,
+    );
+    metrics
+}
+
+/// Returns a redacted label value escaped for the Prometheus text format
+fn label(redactor: &Redactor, value: &str) -> String {
+    redactor
+        .redact(value)
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+/// Reads the body of a request, at most [MAX_BODY] bytes,
+/// the response to send instead if it is larger or unreadable
+fn read_body(request: &mut Request) -> Result<String, Body> {
+    let too_large = || {
+        Response::from_string(format!("body larger than {MAX_BODY} bytes")).with_status_code(413)
+    };
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > MAX_BODY)
+    {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| {
+            Response::from_string(format!("unreadable body: {e}")).with_status_code(400)
+        })?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+fn json(body: String) -> Body {
+    Response::from_string(body)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+fn svg(body: String) -> Body {
+    Response::from_string(body)
+        .with_header(Header::from_bytes("Content-Type", "image/svg+xml").unwrap())
+        .with_header(Header::from_bytes("Cache-Control", "no-cache").unwrap())
+}
+
+/// Returns true if all the addresses `addr` resolves to are loopback ones
+fn is_loopback(addr: &str) -> bool {
+    addr.to_socket_addrs().is_ok_and(|addrs| {
+        let addrs: Vec<_> = addrs.collect();
+        !addrs.is_empty() && addrs.iter().all(|addr| addr.ip().is_loopback())
+    })
+}
+
+fn unauthorized() -> Body {
+    Response::from_string("unauthorized")
+        .with_status_code(401)
+        .with_header(Header::from_bytes("WWW-Authenticate", "Bearer").unwrap())
+}
+
+fn not_found() -> Body {
+    Response::from_string("not found").with_status_code(404)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ControlError {
+    #[error("failed to reach the monitor")]
+    Io(#[from] io::Error),
+    #[error("malformed response of the monitor")]
+    Malformed,
+    #[error("the monitor answered {0}: {1}")]
+    Rejected(u16, String),
+    #[error("invalid list of cases in the response")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Client of the bulk operations of a running monitor, see [StatusServer]
+#[derive(Debug, Clone)]
+pub struct ControlClient {
+    addr: String,
+    token: Option<String>,
+}
+
+impl ControlClient {
+    /// Creates a client of the monitor HTTP server at `addr`, e.g. `127.0.0.1:8080`,
+    /// or `https://cfd.example.org:8443` over TLS
+    pub fn new<S: ToString>(addr: S) -> Self {
+        Self {
+            addr: addr.to_string(),
+            token: None,
+        }
+    }
+    /// Sets the bearer token of the monitor HTTP server
+    pub fn token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+    /// Applies the operation to the cases matching `filter` and returns the names of the cases changed
+    pub fn apply(
+        &self,
+        operation: BulkOperation,
+        filter: &CaseFilter,
+    ) -> Result<Vec<String>, ControlError> {
+        let body = serde_json::to_string(filter)?;
+        let base = if self.addr.contains("://") {
+            self.addr.trim_end_matches('/').to_string()
+        } else {
+            format!("http://{}", self.addr)
+        };
+        let mut request = ureq::post(&format!("{base}/ctl/{}", operation.name()))
+            .set("Content-Type", "application/json");
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+        let response = match request.send_string(&body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                return Err(ControlError::Rejected(status, body.trim().to_string()));
+            }
+            Err(ureq::Error::Transport(e)) => return Err(io::Error::other(e).into()),
+        };
+        if response.status() != 202 {
+            let status = response.status();
+            let body = response
+                .into_string()
+                .map_err(|_| ControlError::Malformed)?;
+            return Err(ControlError::Rejected(status, body.trim().to_string()));
+        }
+        Ok(serde_json::from_reader(response.into_reader())?)
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::Deserialize;
+
+/// Pattern of a log line announcing a phase of the solver initialization
+///
+/// The first capture group, if any, gives the completion percentage of the phase
+///
+/// ```toml
+/// [[init_phases]]
+/// name = "interpolation"
+/// pattern = 'Interpolating.*?(\d+(?:\.\d+)?)\s*%'
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "InitPhaseConfig")]
+pub struct InitPhase {
+    name: String,
+    pattern: Regex,
+}
+
+#[derive(Debug, Deserialize)]
+struct InitPhaseConfig {
+    name: String,
+    pattern: String,
+}
+
+impl TryFrom<InitPhaseConfig> for InitPhase {
+    type Error = regex::Error;
+
+    fn try_from(config: InitPhaseConfig) -> Result<Self, Self::Error> {
+        Self::new(config.name, &config.pattern)
+    }
+}
+
+impl InitPhase {
+    /// Creates a new initialization phase pattern
+    pub fn new<S: ToString>(name: S, pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern)?,
+        })
+    }
+    /// Returns the default initialization phases: mesh partitioning, AMG setup and interpolation
+    pub fn defaults() -> Vec<Self> {
+        [
+            (
+                "partitioning",
+                r"(?i)\bpartition\w*(?:.*?(\d+(?:\.\d+)?)\s*%)?",
+            ),
+            ("AMG setup", r"(?i)\bAMG\b(?:.*?(\d+(?:\.\d+)?)\s*%)?"),
+            (
+                "interpolation",
+                r"(?i)\binterpolat\w*(?:.*?(\d+(?:\.\d+)?)\s*%)?",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, pattern)| Self::new(name, pattern).unwrap())
+        .collect()
+    }
+    /// Returns the phase name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Progress of a case through the initialization phases
+#[derive(Debug, Clone)]
+pub struct InitProgress {
+    pub phase: String,
+    /// Index of the phase in the initialization phases
+    pub index: usize,
+    pub n_phase: usize,
+    /// Completion percentage of the phase, if printed by the solver
+    pub percent: Option<f64>,
+    /// Time the phase was first observed
+    pub since: DateTime<Local>,
+}
+
+impl Display for InitProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}/{})", self.phase, self.index + 1, self.n_phase)?;
+        if let Some(percent) = self.percent {
+            write!(f, " {percent:.0}%")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the initialization progress after the given log lines, starting from `current`
+pub fn init_progress(
+    phases: &[InitPhase],
+    current: Option<InitProgress>,
+    lines: &[String],
+) -> Option<InitProgress> {
+    lines.iter().fold(current, |current, line| {
+        let Some((index, captures)) = phases
+            .iter()
+            .enumerate()
+            .find_map(|(i, phase)| phase.pattern.captures(line).map(|c| (i, c)))
+        else {
+            return current;
+        };
+        let percent = captures
+            .get(1)
+            .and_then(|percent| percent.as_str().parse().ok());
+        match current {
+            Some(progress) if progress.index == index => Some(InitProgress {
+                percent: percent.or(progress.percent),
+                ..progress
+            }),
+            _ => Some(InitProgress {
+                phase: phases[index].name.clone(),
+                index,
+                n_phase: phases.len(),
+                percent,
+                since: Local::now(),
+            }),
+        }
+    })
+}
@@ -0,0 +1,94 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::{Case, DurationSpec, Redactor, StatusSnapshot, RATE};
+
+/// Progress of a solver log inspected from its content rather than from the filesystem
+///
+/// The same parsing and ETA logic as the monitor is applied to the appended content
+/// of the log at each update, e.g. for a log file dropped on a browser page
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct LogInspector {
+    case: Case,
+    duration: DurationSpec,
+    read: usize,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl LogInspector {
+    /// Creates a new inspector of a case lasting `duration` simulated seconds
+    ///
+    /// Progress is computed from the time step count, or from the simulated time if `physical_time` is set
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(name: &str, duration: f64, physical_time: bool) -> Self {
+        let duration = if physical_time {
+            DurationSpec::PhysicalTime(duration)
+        } else {
+            DurationSpec::Steps((duration * RATE as f64) as usize)
+        };
+        Self {
+            case: Case::new(name, 0, "").duration_spec(duration),
+            duration,
+            read: 0,
+        }
+    }
+    /// Updates the progress from the whole content of the log,
+    /// `secs` being the wall-clock time elapsed since the previous update
+    ///
+    /// Only the complete lines appended since the previous update are parsed,
+    /// a log shorter than at the previous update, or not continuing where it was read up to,
+    /// is parsed again from the start.
+    /// The time steps are timed by the timestamps of their lines, `secs` being only shared
+    /// between the time steps of a log without timestamps
+    pub fn update(&mut self, content: &str, secs: f64) {
+        let appended = match content.get(self.read..) {
+            Some(appended) if self.read == 0 || content[..self.read].ends_with('\n') => appended,
+            _ => {
+                self.case = Case::new(self.case.name(), 0, "").duration_spec(self.duration);
+                self.read = 0;
+                content
+            }
+        };
+        let Some(end) = appended.rfind('\n') else {
+            return;
+        };
+        let lines: Vec<_> = appended[..end].lines().map(str::to_string).collect();
+        self.read += end + 1;
+        let error = self
+            .case
+            .update_from_lines(&lines, secs)
+            .err()
+            .map(|e| e.to_string());
+        self.case.set_error(error);
+    }
+    /// Returns the percent complete
+    pub fn percent_complete(&self) -> i64 {
+        self.case.percent_complete()
+    }
+    /// Returns the expected remaining wall-clock duration in seconds
+    pub fn eta_secs(&self) -> i64 {
+        self.case.eta_secs()
+    }
+    /// Returns the time step durations at the latest updates, from oldest to latest
+    pub fn step_times(&self) -> Vec<f64> {
+        self.case.step_times().iter().copied().collect()
+    }
+    /// Returns the status of the case as the JSON of the monitor snapshots
+    pub fn status(&self) -> String {
+        let cases = std::slice::from_ref(&self.case);
+        let snapshot = if self.case.is_complete() {
+            StatusSnapshot::new(&[], cases, 0)
+        } else {
+            StatusSnapshot::new(cases, &[], 0)
+        };
+        snapshot.to_json(&Redactor::default())
+    }
+}
+
+impl LogInspector {
+    /// Returns the inspected case
+    pub fn case(&self) -> &Case {
+        &self.case
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{History, StepParser};
+
+/// Number of the latest time steps of the inner iteration trend
+const ITERATION_STEPS: usize = 40;
+/// Number of the first time steps the inner iterations of the case are compared with
+const BASELINE_STEPS: usize = 10;
+/// Number of the latest time steps whose inner iterations are compared with the baseline
+const RECENT_STEPS: usize = 5;
+/// Factor of the inner iterations per time step over the baseline the iterations are climbing at
+pub const CLIMB_RATIO: f64 = 1.5;
+/// Minimum increase of the inner iterations per time step over the baseline,
+/// so a case needing 3 iterations instead of 2 is not flagged
+const MIN_CLIMB: f64 = 2.;
+
+/// Inner iterations per time step of a case, from the iteration numbers of the residual table
+/// counted between the time step lines of the log
+///
+/// ```text
+/// TimeStep     1: Time 5.000000e-02
+///   Iteration  Continuity  X-momentum  Y-momentum  Z-momentum      Energy
+///           1  1.0000e-03  2.0000e-04  2.0000e-04  3.0000e-04  1.0000e-05
+///           2  5.0000e-04  1.0000e-04  1.0000e-04  1.5000e-04  5.0000e-06
+/// TimeStep     2: Time 1.000000e-01
+/// ```
+#[derive(Debug, Clone)]
+pub struct InnerIterations {
+    /// Inner iterations of the latest complete time steps
+    history: History,
+    /// Inner iterations of the first complete time steps
+    baseline: Vec<usize>,
+    /// Set once a residual table header was found
+    table: bool,
+    /// Latest iteration number
+    iteration: Option<usize>,
+    /// Iteration number at the latest time step line
+    at_step: Option<usize>,
+}
+
+impl Default for InnerIterations {
+    fn default() -> Self {
+        Self {
+            history: History::new(ITERATION_STEPS),
+            baseline: vec![],
+            table: false,
+            iteration: None,
+            at_step: None,
+        }
+    }
+}
+
+impl InnerIterations {
+    /// Creates an empty trend
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Counts the inner iterations of the time steps completed in new lines of the log,
+    /// a time step being complete at the next time step line
+    pub fn scan<'a>(
+        &mut self,
+        lines: impl IntoIterator<Item = &'a str>,
+        parser: &dyn StepParser,
+    ) -> &mut Self {
+        for line in lines {
+            if parser.parse_line(line).is_some() {
+                if let Some((iteration, at_step)) = self.iteration.zip(self.at_step) {
+                    if iteration > at_step {
+                        self.push(iteration - at_step);
+                    }
+                }
+                self.at_step = self.iteration;
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("Iteration") => self.table = true,
+                Some(first) if self.table => {
+                    let Ok(iteration) = first.parse::<usize>() else {
+                        continue;
+                    };
+                    let mut values = fields.peekable();
+                    if values.peek().is_none() || values.any(|value| value.parse::<f64>().is_err())
+                    {
+                        continue;
+                    }
+                    // the iterations start over on a restart
+                    if self.iteration.is_some_and(|latest| iteration < latest) {
+                        self.at_step = None;
+                    }
+                    self.iteration = Some(iteration);
+                }
+                _ => (),
+            }
+        }
+        self
+    }
+    fn push(&mut self, iterations: usize) {
+        if self.baseline.len() < BASELINE_STEPS {
+            self.baseline.push(iterations);
+        }
+        self.history.push(iterations as f64);
+    }
+    /// Returns the inner iterations of the latest complete time steps, oldest first
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+    /// Returns the inner iterations of the latest complete time step
+    pub fn latest(&self) -> Option<usize> {
+        self.history.last().map(|iterations| iterations as usize)
+    }
+    /// Returns the mean inner iterations of the first time steps, `None` until they are complete
+    pub fn baseline(&self) -> Option<f64> {
+        (self.baseline.len() == BASELINE_STEPS)
+            .then(|| self.baseline.iter().sum::<usize>() as f64 / BASELINE_STEPS as f64)
+    }
+    /// Returns the mean inner iterations of the latest time steps
+    pub fn recent(&self) -> Option<f64> {
+        let n = self.history.len().min(RECENT_STEPS);
+        let skip = self.history.len() - n;
+        (n > 0).then(|| self.history.iter().skip(skip).sum::<f64>() / n as f64)
+    }
+    /// Returns true if the latest time steps need [CLIMB_RATIO] times the inner iterations
+    /// of the first ones
+    pub fn is_climbing(&self) -> bool {
+        self.baseline()
+            .zip(self.recent())
+            .filter(|_| self.history.len() >= BASELINE_STEPS + RECENT_STEPS)
+            .is_some_and(|(baseline, recent)| {
+                recent >= baseline * CLIMB_RATIO && recent - baseline >= MIN_CLIMB
+            })
+    }
+}
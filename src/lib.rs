@@ -1,28 +1,249 @@
 use std::{
+    collections::{HashMap, VecDeque},
     fmt::Display,
-    io,
+    fs, io,
     num::{ParseFloatError, ParseIntError},
     ops::Mul,
-    path::Path,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
     string::FromUtf8Error,
+    sync::{Arc, LazyLock, Mutex, MutexGuard, OnceLock},
 };
 
-use chrono::{Duration, Local};
+use chrono::{DateTime, Duration, Local};
 use regex::Regex;
 
+mod access;
+mod aggregate;
+mod alerts;
+mod alignment;
+mod badge;
+mod blackout;
+mod calendar;
+mod campaign;
+mod checkpoint;
+mod clock;
+mod columns;
+mod compare;
+mod config;
+mod contention;
+#[cfg(feature = "db")]
+mod db;
+mod deltas;
+#[cfg(feature = "monitor")]
+mod detail;
+mod diff;
+mod dirs;
+mod disk;
+mod distribution;
+mod downtime;
+mod duration;
+mod environment;
+mod eta;
+mod excerpt;
+mod export;
+mod failure;
+mod filter;
+mod forecast;
+mod health;
+mod heartbeat;
+mod heatmap;
+mod history;
+mod hooks;
+#[cfg(feature = "monitor")]
+mod http;
+mod init;
+mod inspect;
+mod iterations;
+mod liveness;
+mod maintenance;
+mod milestones;
+#[cfg(feature = "monitor")]
+mod monitor;
+mod notes;
+mod notify;
+#[cfg(feature = "otel")]
+mod otel;
+mod parser;
+mod plan;
+mod plot;
+#[cfg(feature = "plugins")]
+mod plugins;
+mod precursor;
+mod preflight;
+mod profile;
+mod query;
+mod queue;
+mod reconcile;
+mod record;
+mod redact;
+mod remote;
+mod rotated;
+#[cfg(feature = "s3")]
+mod s3;
+#[cfg(feature = "monitor")]
+mod screen;
+#[cfg(feature = "monitor")]
+mod session;
+mod snapshot;
+mod source;
+mod stages;
+mod stats;
+mod summary;
+mod tabs;
+mod tail;
+mod theme;
+mod throttle;
+mod trajectory;
+#[cfg(feature = "monitor")]
+mod transitions;
+mod triage;
+mod utilization;
+mod version;
+#[cfg(feature = "monitor")]
+mod watch;
+pub use access::{is_authorized, HttpConfig, TlsConfig};
+pub use aggregate::{Aggregate, AggregateError, AggregationConfig, Publisher};
+pub use alerts::{Alert, AlertKind, Alerts, Severity};
+pub use alignment::{AlignedCase, SimTimeAlignment, SimTimeline};
+pub use badge::{Badge, BadgeState};
+pub use blackout::{run_until, BlackoutError, BlackoutWindow};
+pub use calendar::{BusinessCalendar, CalendarError};
+pub use campaign::CampaignConfig;
+pub use checkpoint::{Checkpoint, CheckpointPattern, RestartAdvice};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use columns::{Column, ColumnError, ColumnSpec, SortKey, DEFAULT_COLUMNS, NAME_WIDTH};
+pub use compare::{CoreGroup, ThroughputComparison, TimedRun};
+pub use config::{CaseConfig, Config, ConfigDiff, ConfigError};
+pub use contention::{ContentionDetector, SlowInterval, OUTLIER_SIGMA};
+#[cfg(feature = "db")]
+pub use db::{CaseReport, DbError, RunDatabase};
+pub use deltas::{CaseDelta, Change, RefreshDeltas};
+#[cfg(feature = "monitor")]
+pub use detail::{CaseDetail, EventHistory, ResidualTrend, StepTimeHistogram, DETAIL_LINES};
+pub use diff::{CaseDiff, DiffError, SnapshotDiff};
+pub use dirs::{Dirs, CONFIG_TEMPLATE};
+pub use disk::{DiskConfig, DiskUsage};
+pub use distribution::{Percentiles, StepTimeDistribution};
+pub use downtime::{DowntimeHistory, OfflineInterval};
+pub use duration::{DurationError, SimSeconds, Steps, MAX_RATE_RATIO};
+pub use environment::Environment;
+pub use eta::{EtaConfig, EtaFormat, EtaStyle};
+pub use excerpt::{ExcerptConfig, LogExcerpt, EXCERPT_LINES, SNIPPET_LINES};
+#[cfg(feature = "parquet")]
+pub use export::write_parquet;
+pub use export::ExportConfig;
+pub use failure::{find_failure, ErrorPattern, LogFailure};
+pub use filter::{CaseFilter, FilterError, FilterTerm};
+pub use forecast::{BatchForecast, Forecast};
+pub use health::{HealthConfig, HealthIndex};
+pub use heartbeat::{Heartbeat, HeartbeatConfig};
+pub use heatmap::ThroughputCalendar;
+pub use history::{History, HISTORY_LEN};
+pub use hooks::{CaseEvent, Hooks, HooksConfig};
+#[cfg(feature = "monitor")]
+pub use http::{
+    BulkOperation, Control, ControlClient, ControlError, StatusServer, MAX_BODY, SNAPSHOT_HISTORY,
+};
+pub use init::{init_progress, InitPhase, InitProgress};
+pub use inspect::LogInspector;
+pub use iterations::{InnerIterations, CLIMB_RATIO};
+pub use liveness::Liveness;
+pub use maintenance::{active_window, MaintenanceError, MaintenanceWindow};
+pub use milestones::{find_milestones, Milestone, MilestonePattern};
+#[cfg(feature = "monitor")]
+pub use monitor::{Monitor, MonitorEvent};
+pub use notes::{Note, Notes};
+pub use notify::{Notification, Notifier, NotifyConfig};
+#[cfg(feature = "otel")]
+pub use otel::{OtlpExporter, OtlpLayer};
+pub use parser::{
+    parse_log, parse_log_with, Plugins, PluginsConfig, SnapshotSink, StepParser, StepRecord,
+    TimeStepPattern, TimeStepPatternError,
+};
+pub use plan::{BatchPlan, FinishOrder, PlannedRun, ProjectedFinish};
+#[cfg(feature = "plot")]
+pub use plot::PlotError;
+pub use plot::{PlotFormat, ProgressPlots};
+#[cfg(feature = "plugins")]
+pub use plugins::{Plugin, PluginError, PLUGIN_ABI};
+pub use precursor::{PrecursorConfig, Precursors, Seeding};
+pub use preflight::{Preflight, PreflightProblem};
+pub use profile::{DisplayProfile, PROFILES};
+pub use query::{Query, QueryError, QueryResult};
+pub use queue::{expected_start, START_FORMAT};
+pub use reconcile::{Discrepancy, Reconciliation};
+pub use record::CompletionRecord;
+pub use redact::{RedactionConfig, RedactionRule, Redactor};
+pub use remote::{RemoteSource, SSH_COMMAND};
+pub use rotated::{last_matching_line, open_log, rotated_logs};
+#[cfg(feature = "s3")]
+pub use s3::{S3Error, S3Source};
+#[cfg(feature = "monitor")]
+pub use screen::Screen;
+#[cfg(feature = "monitor")]
+pub use session::{
+    read_session, EtaEvolution, Incident, PostMortem, SessionEntry, SessionRecorder,
+};
+pub use snapshot::{GroupSummary, StatusSnapshot};
+pub use source::{
+    CommandSource, FileSource, GlobSource, LatestSource, LogRelocation, LogSource, PipeSource,
+    StringSource,
+};
+pub use stages::{Stage, StageConfig, StageState};
+pub use stats::{CaseUpdateStats, PollStats};
+pub use summary::{ArchivedCase, CampaignSummary, ConfigurationThroughput, StallIncident};
+pub use tabs::{Tab, TabConfig, Tabs};
+pub use tail::LogTail;
+pub use theme::{Theme, ThemeError};
+pub use throttle::{FilesystemConfig, ReadOptions, ReadPermit, ReadThrottle};
+pub use trajectory::{
+    DtChange, DtChanges, DtPattern, DtTrend, TimeStepping, Trajectory, TrajectoryPoint,
+    TRAJECTORY_LEN,
+};
+#[cfg(feature = "monitor")]
+pub use transitions::{Transition, TransitionKind, TransitionLog};
+pub use triage::{find_triage, Triage, TriageRule};
+pub use utilization::{JobUsage, ResourceCollector, Utilization, UTILIZATION_INTERVAL};
+pub use version::{version_mismatches, VersionPattern};
+#[cfg(feature = "monitor")]
+pub use watch::LogWatcher;
+
 /// Time in seconds between 2 status updates
 pub const UPDATE_TIME: usize = 180;
-// Path to CFD cases
+// Default path to CFD cases
 const ROOT: &str = "/shared";
-// Simulation sampling rate
-const RATE: usize = 20; //Hz
+// Simulation sampling rate in Hz
+const RATE: usize = 20;
+// Ratio of the latest time step duration to the mean above which a case is slowing down
+const SLOWING_RATIO: f64 = 1.25;
+// Default number of time steps the merged logs of a case may disagree by before a warning
+const MAX_LOG_SPREAD: usize = 10;
+// Default drift in percent of the mean time step duration from the expected one before a warning
+const STEP_TIME_TOLERANCE: f64 = 20.;
+// Number of time step duration samples before the mean is compared to the expected one
+const STEP_TIME_WARMUP: usize = 5;
+// Default number of the latest time step duration samples the ETA is computed from
+const ETA_WINDOW: usize = 50;
+// Factor of the sampling window the wall-clock time between two updates exceeds
+// once the monitor was offline in between
+const OFFLINE_RATIO: f64 = 2.;
+// Number of the latest monitor offline intervals kept per case
+const OFFLINE_INTERVALS: usize = 20;
+// Time step line of the solver log
+static TIME_STEP_LINE: LazyLock<TimeStepPattern> = LazyLock::new(TimeStepPattern::default);
 
 /// Elapsed time per simulation time step
+///
+/// Both the mean over the lifetime of the simulation and the mean over the latest samples
+/// are tracked, the ETA being computed from the latter so that the slow first time steps
+/// of the initialization do not bias it
 #[derive(Debug, Default, Clone)]
 pub struct ElapsedPerStep {
     value: f64,
     sample: usize,
+    since: Option<usize>,
+    window: Option<usize>,
+    recent: VecDeque<f64>,
 }
 
 impl ElapsedPerStep {
@@ -30,18 +251,67 @@ impl ElapsedPerStep {
     pub fn new() -> Self {
         Default::default()
     }
+    /// Creates a new object for the time steps from `step` on,
+    /// e.g. after a change of the simulated time per time step
+    pub fn since(step: usize) -> Self {
+        Self {
+            since: Some(step),
+            ..Default::default()
+        }
+    }
+    /// Sets the number of the latest samples the windowed mean is computed from, 50 by default,
+    /// the ETA using the lifetime mean if 0
+    pub fn window(mut self, samples: usize) -> Self {
+        self.window = Some(samples);
+        let excess = self.recent.len().saturating_sub(samples);
+        self.recent.drain(..excess);
+        self
+    }
+    /// Returns the number of the latest samples the windowed mean is computed from
+    pub fn window_len(&self) -> usize {
+        self.window.unwrap_or(ETA_WINDOW)
+    }
+    /// Returns the time step the mean is computed from, if not the start of the simulation
+    pub fn since_step(&self) -> Option<usize> {
+        self.since
+    }
     /// Updates the mean of a time step duration
     pub fn update(&mut self, value: f64) -> &mut Self {
         let n = self.sample as f64;
         self.sample += 1;
         self.value = (self.value * n + value) / self.sample as f64;
+        if self.window_len() > 0 {
+            if self.recent.len() == self.window_len() {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(value);
+        }
         self
     }
+    /// Returns the mean of a time step duration over the lifetime of the simulation
+    pub fn mean(&self) -> f64 {
+        self.value
+    }
+    /// Returns the mean of a time step duration over the latest samples,
+    /// `None` if the window is disabled
+    pub fn windowed(&self) -> Option<f64> {
+        (!self.recent.is_empty())
+            .then(|| self.recent.iter().sum::<f64>() / self.recent.len() as f64)
+    }
+    /// Returns the time step duration the ETA is computed from,
+    /// the windowed mean unless disabled
+    pub fn estimate(&self) -> f64 {
+        self.windowed().unwrap_or(self.value)
+    }
+    /// Returns the number of time step duration samples the mean is computed from
+    pub fn sample(&self) -> usize {
+        self.sample
+    }
 }
 
 impl Display for ElapsedPerStep {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:>8.2}", self.value)
+        write!(f, "{:>8.2}", self.estimate())
     }
 }
 
@@ -49,7 +319,22 @@ impl Mul<f64> for &ElapsedPerStep {
     type Output = f64;
 
     fn mul(self, rhs: f64) -> Self::Output {
-        self.value * rhs
+        self.estimate() * rhs
+    }
+}
+
+/// Length of a simulation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DurationSpec {
+    /// Total number of time steps
+    Steps(usize),
+    /// Simulated time in seconds, progress is computed from the parsed `Time` value
+    PhysicalTime(f64),
+}
+
+impl Default for DurationSpec {
+    fn default() -> Self {
+        Self::Steps(0)
     }
 }
 
@@ -57,11 +342,115 @@ impl Mul<f64> for &ElapsedPerStep {
 #[derive(Debug, Default, Clone)]
 pub struct Case {
     name: String,
-    duration: usize,
+    duration: DurationSpec,
     log: String,
+    root: Option<PathBuf>,
     step: Option<usize>,
     time: f64,
     elapsed_per_step: ElapsedPerStep,
+    first_seen: Option<(DateTime<Local>, usize)>,
+    created: Option<DateTime<Local>>,
+    completed: Option<DateTime<Local>>,
+    last_advance: Option<DateTime<Local>>,
+    stall_after: Option<u64>,
+    step_times: History,
+    step_time_distribution: StepTimeDistribution,
+    inner_iterations: InnerIterations,
+    sim_timeline: SimTimeline,
+    /// Wall-clock time of the latest update
+    updated: Option<DateTime<Local>>,
+    /// Latest intervals the monitor was offline in, oldest first
+    offline: VecDeque<OfflineInterval>,
+    etas: History,
+    trajectory: Trajectory,
+    contention: ContentionDetector,
+    dt_changes: DtChanges,
+    group: Option<String>,
+    disk: Option<DiskUsage>,
+    disk_at_risk: bool,
+    queue: Option<Queue>,
+    n_core: Option<usize>,
+    milestone_patterns: Vec<MilestonePattern>,
+    milestones: Vec<Milestone>,
+    source: OnceLock<SharedSource>,
+    scanned: bool,
+    init_phases: Vec<InitPhase>,
+    init: Option<InitProgress>,
+    triage_rules: Vec<TriageRule>,
+    triage: Option<Triage>,
+    error_patterns: Vec<ErrorPattern>,
+    log_failure: Option<LogFailure>,
+    log_relocations: Vec<LogRelocation>,
+    /// Bytes of the log read so far
+    bytes_read: u64,
+    blackouts: Vec<BlackoutWindow>,
+    stage_configs: Vec<StageConfig>,
+    stages: Vec<Stage>,
+    version_pattern: Option<VersionPattern>,
+    dt_pattern: Option<DtPattern>,
+    checkpoint_pattern: Option<CheckpointPattern>,
+    checkpoint: Option<Checkpoint>,
+    precursor: Option<PrecursorConfig>,
+    /// Cases the case can only start after
+    predecessors: Vec<String>,
+    reported_dt: Option<f64>,
+    initial_dt: Option<f64>,
+    solver_version: Option<String>,
+    version_mismatch: bool,
+    sampled_coarsely: bool,
+    /// Nominal time in seconds between two updates, set by the monitor
+    update_interval: Option<f64>,
+    max_log_spread: Option<usize>,
+    log_spread: Option<usize>,
+    expected_step_time: Option<f64>,
+    step_time_tolerance: Option<f64>,
+    severities: HashMap<AlertKind, Severity>,
+    liveness: Option<Liveness>,
+    solver_alive: Option<bool>,
+    resources: Option<utilization::ResourceSampler>,
+    follow_latest: bool,
+    paused: bool,
+    environment: Environment,
+    error: Option<String>,
+    note: Option<Note>,
+    deadline: Option<DateTime<Local>>,
+    priority: i32,
+    parser: Option<Arc<dyn StepParser>>,
+    clock: Option<Arc<dyn Clock>>,
+    excerpt_lines: Option<usize>,
+    downtime_factor: Option<f64>,
+    /// Latest log lines, shared by the clones of the case in the snapshots and the history
+    recent_lines: VecDeque<Arc<str>>,
+    read_options: ReadOptions,
+    /// Remote log the source reads, e.g. the log host or command of the configuration
+    log_origin: Option<String>,
+    /// Latest time step of the log with the wall-clock timestamp of its line
+    log_stamp: Option<(usize, DateTime<Local>)>,
+    /// Log timestamp of the time step of the latest advance
+    advance_stamp: Option<(usize, DateTime<Local>)>,
+    /// Time steps of the lines scanned at the latest update with the timestamps of their lines
+    step_stamps: Vec<(usize, DateTime<Local>)>,
+}
+
+/// Log source of a case, shared by its clones
+#[derive(Debug, Clone)]
+struct SharedSource(Arc<Mutex<dyn LogSource>>);
+
+impl SharedSource {
+    fn new<L: LogSource + 'static>(source: L) -> Self {
+        Self(Arc::new(Mutex::new(source)))
+    }
+    fn lock(&self) -> MutexGuard<'_, dyn LogSource + 'static> {
+        self.0.lock().unwrap()
+    }
+}
+
+/// Queue information of a case not started yet
+#[derive(Debug, Default, Clone)]
+struct Queue {
+    planned: Option<DateTime<Local>>,
+    job: Option<String>,
+    expected: Option<DateTime<Local>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -78,26 +467,333 @@ pub enum CaseError {
     Regex(#[from] regex::Error),
     #[error("No TimeStep/Time match found")]
     Capture,
-    #[error("grep TimeStep failed")]
+    #[error("failed to search the log for TimeStep")]
     Grep,
+    #[error("update panicked: {0}")]
+    Panic(String),
 }
 
 pub type Result<T> = std::result::Result<T, CaseError>;
 
+impl CaseError {
+    /// Returns true if the log was read but not understood
+    pub fn is_parse_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::UTF8(_) | Self::ParseFloat(_) | Self::ParseInt(_) | Self::Capture
+        )
+    }
+}
+
 impl Case {
     /// Creates a new case
     pub fn new<S: ToString>(name: S, duration: usize, log: S) -> Self {
         Self {
             name: name.to_string(),
-            duration,
+            duration: DurationSpec::Steps(duration * RATE),
             log: log.to_string(),
             ..Default::default()
         }
     }
+    /// Sets the throttle and the buffer size of the reads of the log files
+    pub fn read_options(mut self, options: ReadOptions) -> Self {
+        self.read_options = options;
+        self
+    }
+    /// Sets the source the log is read from, the log file by default
+    pub fn log_source<L: LogSource + 'static>(mut self, source: L) -> Self {
+        self.source = OnceLock::from(SharedSource::new(source));
+        self
+    }
+    /// Sets the source the log is read from and the remote log it reads,
+    /// cases reading different logs not having the same definition
+    pub(crate) fn remote_log_source<L: LogSource + 'static, S: ToString>(
+        mut self,
+        source: L,
+        origin: S,
+    ) -> Self {
+        self.log_origin = Some(origin.to_string());
+        self.log_source(source)
+    }
+    /// Sets the number of time steps the merged logs of a glob may disagree by before
+    /// a warning, 10 by default
+    pub fn max_log_spread(mut self, steps: usize) -> Self {
+        self.max_log_spread = Some(steps);
+        self
+    }
+    /// Sets the expected wall-clock duration of a time step in seconds, e.g. from sizing runs
+    pub fn expected_step_time(mut self, secs: f64) -> Self {
+        self.expected_step_time = Some(secs);
+        self
+    }
+    /// Sets the number of the latest time step duration samples the ETA is computed from,
+    /// 50 by default, the mean over the whole simulation being used if 0
+    pub fn eta_window(mut self, samples: usize) -> Self {
+        self.elapsed_per_step = self.elapsed_per_step.window(samples);
+        self
+    }
+    /// Sets the drift in percent of the mean time step duration from the expected one
+    /// before a warning, 20 by default
+    pub fn step_time_tolerance(mut self, percent: f64) -> Self {
+        self.step_time_tolerance = Some(percent);
+        self
+    }
+    /// Sets the directory the case directory is in, `/shared` by default
+    pub fn root_dir<P: AsRef<Path>>(mut self, root: P) -> Self {
+        self.root = Some(root.as_ref().to_path_buf());
+        self
+    }
+    /// Sets the length of the simulation, either in time steps or in simulated time
+    pub fn duration_spec(mut self, duration: DurationSpec) -> Self {
+        self.duration = duration;
+        self
+    }
+    /// Sets the time in seconds without a new time step before the case is deemed stalled
+    pub fn stall_after(mut self, secs: u64) -> Self {
+        self.stall_after = Some(secs);
+        self
+    }
+    /// Sets the number of standard deviations above the moving mean for a time step
+    /// to be an outlier, 3 by default
+    pub fn outlier_sigma(mut self, sigma: f64) -> Self {
+        self.contention = ContentionDetector::new(sigma);
+        self
+    }
+    /// Sets the severity of the alerts of the case, the default severity of the other kinds
+    pub fn severities(mut self, severities: HashMap<AlertKind, Severity>) -> Self {
+        self.severities = severities;
+        self
+    }
+    /// Returns the severity of the alerts of a kind for the case
+    pub fn alert_severity(&self, kind: AlertKind) -> Severity {
+        self.severities
+            .get(&kind)
+            .copied()
+            .unwrap_or_else(|| kind.severity())
+    }
+    /// Sets how the solver process of the case is checked once the case is stalled
+    pub fn liveness(mut self, liveness: Liveness) -> Self {
+        self.liveness = Some(liveness);
+        self
+    }
+    /// Sets the collector of the resource utilization of the solver job
+    pub fn resources(mut self, collector: ResourceCollector) -> Self {
+        self.resources = Some(utilization::ResourceSampler::new(collector));
+        self
+    }
+    /// Follows the most recently modified log matching the log name, a glob, instead of
+    /// merging them, e.g. `solve-672_*.out` with a new log at each restart of the solver
+    pub fn follow_latest(mut self) -> Self {
+        self.follow_latest = true;
+        self
+    }
+    /// Sets the group the case belongs to
+    pub fn group<S: ToString>(mut self, group: S) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+    /// Sets the planned start and the scheduler job of a case that may not have started yet
+    pub fn queued(mut self, planned: Option<DateTime<Local>>, job: Option<String>) -> Self {
+        self.queue = Some(Queue {
+            planned,
+            job,
+            expected: None,
+        });
+        self
+    }
+    /// Returns true if the case is queued and no time step has been found yet
+    pub fn is_pending(&self) -> bool {
+        self.queue.is_some() && self.step.is_none()
+    }
+    /// Returns the expected start of a pending case
+    ///
+    /// The scheduler estimate takes precedence over the planned start,
+    /// and a start in the past means the case is expected to start any time now
+    pub fn expected_start(&self) -> Option<DateTime<Local>> {
+        self.queue
+            .as_ref()
+            .and_then(|queue| queue.expected.or(queue.planned))
+            .map(|start| start.max(self.now()))
+    }
+    /// Returns the path to the case directory
+    pub fn case_dir(&self) -> PathBuf {
+        self.root
+            .as_deref()
+            .unwrap_or(Path::new(ROOT))
+            .join(&self.name)
+    }
+    /// Returns true if both cases have the same name, duration and log,
+    /// read from the same remote log and following the latest log alike
+    pub fn same_definition(&self, other: &Case) -> bool {
+        self.name == other.name
+            && self.duration == other.duration
+            && self.log == other.log
+            && self.follow_latest == other.follow_latest
+            && self.log_origin == other.log_origin
+    }
+    /// Takes the settings of `configured`, the same case as configured again,
+    /// keeping the progress and statistics of the case
+    pub fn reconfigure(&mut self, configured: Case) {
+        // every field is either a setting taken or a state kept
+        let Case {
+            name: _,
+            duration: _,
+            log: _,
+            root,
+            step: _,
+            time: _,
+            elapsed_per_step,
+            first_seen: _,
+            created: _,
+            completed: _,
+            last_advance: _,
+            stall_after,
+            step_times: _,
+            step_time_distribution: _,
+            inner_iterations: _,
+            sim_timeline: _,
+            updated: _,
+            offline: _,
+            etas: _,
+            trajectory: _,
+            contention,
+            dt_changes: _,
+            group,
+            disk: _,
+            disk_at_risk: _,
+            queue,
+            n_core,
+            milestone_patterns,
+            milestones: _,
+            source: _,
+            scanned: _,
+            init_phases,
+            init: _,
+            triage_rules,
+            triage: _,
+            error_patterns,
+            log_failure: _,
+            log_relocations: _,
+            bytes_read: _,
+            blackouts,
+            stage_configs,
+            stages: _,
+            version_pattern,
+            dt_pattern,
+            checkpoint_pattern,
+            checkpoint: _,
+            precursor,
+            predecessors,
+            reported_dt: _,
+            initial_dt: _,
+            solver_version: _,
+            version_mismatch: _,
+            sampled_coarsely: _,
+            update_interval: _,
+            max_log_spread,
+            log_spread: _,
+            expected_step_time,
+            step_time_tolerance,
+            severities,
+            liveness,
+            solver_alive: _,
+            resources,
+            follow_latest: _,
+            paused: _,
+            environment: _,
+            error: _,
+            note,
+            deadline,
+            priority,
+            parser,
+            clock,
+            excerpt_lines,
+            downtime_factor,
+            recent_lines: _,
+            read_options,
+            log_origin: _,
+            log_stamp: _,
+            advance_stamp: _,
+            step_stamps: _,
+        } = configured;
+        self.root = root;
+        self.elapsed_per_step =
+            std::mem::take(&mut self.elapsed_per_step).window(elapsed_per_step.window_len());
+        self.stall_after = stall_after;
+        self.contention.set_sigma(contention.sigma());
+        self.group = group;
+        // the scheduler estimate is kept for the same job
+        let expected = self
+            .queue
+            .take()
+            .filter(|previous| {
+                queue
+                    .as_ref()
+                    .is_some_and(|queue| queue.job == previous.job)
+            })
+            .and_then(|previous| previous.expected);
+        self.queue = queue.map(|queue| Queue { expected, ..queue });
+        self.n_core = n_core;
+        self.milestone_patterns = milestone_patterns;
+        self.init_phases = init_phases;
+        self.triage_rules = triage_rules;
+        self.error_patterns = error_patterns;
+        self.blackouts = blackouts;
+        self.stage_configs = stage_configs;
+        self.version_pattern = version_pattern;
+        self.dt_pattern = dt_pattern;
+        self.checkpoint_pattern = checkpoint_pattern;
+        self.precursor = precursor;
+        self.predecessors = predecessors;
+        self.max_log_spread = max_log_spread;
+        self.expected_step_time = expected_step_time;
+        self.step_time_tolerance = step_time_tolerance;
+        self.severities = severities;
+        self.liveness = liveness;
+        // the samples are kept for the same collector
+        let same = |sampler: &utilization::ResourceSampler| {
+            resources
+                .as_ref()
+                .is_some_and(|resources| resources.collector() == sampler.collector())
+        };
+        if !self.resources.as_ref().is_some_and(same) {
+            self.resources = resources;
+        }
+        self.note = note;
+        self.deadline = deadline;
+        self.priority = priority;
+        self.parser = parser;
+        if clock.is_some() {
+            self.clock = clock;
+        }
+        self.excerpt_lines = excerpt_lines;
+        self.downtime_factor = downtime_factor;
+        self.read_options = read_options;
+    }
+    /// Returns true if the logs matching the log name are merged, the log name being
+    /// a glob not followed for its latest log
+    fn merges_logs(&self) -> bool {
+        GlobSource::is_glob(&self.log) && !self.follow_latest
+    }
+    /// Returns the source the log is read from, the log file unless another one was set,
+    /// the logs matching the log name if it is a glob, or the latest of them if followed
+    fn source(&self) -> MutexGuard<'_, dyn LogSource + 'static> {
+        self.source
+            .get_or_init(|| {
+                let options = self.read_options.clone();
+                if self.follow_latest && GlobSource::is_glob(&self.log) {
+                    SharedSource::new(LatestSource::with_options(self.log_file(), options))
+                } else if GlobSource::is_glob(&self.log) {
+                    SharedSource::new(GlobSource::with_options(self.log_file(), options))
+                } else {
+                    SharedSource::new(FileSource::with_options(self.log_file(), options))
+                }
+            })
+            .lock()
+    }
     /// Returns the path to the log file
     pub fn log_file(&self) -> String {
-        Path::new(ROOT)
-            .join(&self.name)
+        self.case_dir()
             .join(&self.log)
             .to_str()
             .unwrap()
@@ -105,75 +801,1348 @@ impl Case {
     }
     /// Updates case status
     ///
-    /// A parser for the output of `grep TimeStep <log_file>| tail -n1`
+    /// Parses the last `TimeStep` line of the log
     pub fn update(&mut self) -> Result<&mut Self> {
-        let pattern = Regex::new(r"TimeStep\s+(\d+): Time\s+(\d+\.\d+e[+-]?\d+)")?;
-
-        let grep = Command::new("grep")
-            .arg("TimeStep")
-            .arg(&self.log_file())
-            .stdout(Stdio::piped())
-            .spawn()?;
-        let pipe = Command::new("tail")
-            .arg("-n1")
-            .stdin(Stdio::from(grep.stdout.unwrap()))
-            .stdout(Stdio::piped())
-            .spawn()?;
-        let output = pipe.wait_with_output()?;
-        if output.status.success() {
-            let time_step = String::from_utf8(output.stdout)?;
-
+        self.update_over(UPDATE_TIME as f64)
+    }
+    /// Updates case status, `secs` being the wall-clock time elapsed since the last time step change
+    ///
+    /// If the monitor was offline since the previous update, the wall-clock time since
+    /// the last time step change is used instead and the offline interval is recorded,
+    /// the gap being compared to the [update interval](Case::set_update_interval), `secs` if unset
+    #[tracing::instrument(level = "debug", skip(self), fields(case = %self.name))]
+    pub fn update_over(&mut self, secs: f64) -> Result<&mut Self> {
+        let secs = self.check_offline(secs);
+        let lines = self.scan_log()?;
+        self.scan_lines(&lines);
+        if self.is_pending() {
+            if let Some(queue) = self.queue.as_mut() {
+                queue.expected = queue.job.as_deref().and_then(expected_start);
+            }
+            if !self.source().exists() {
+                tracing::debug!(expected = ?self.expected_start(), "pending, no log yet");
+                return Ok(self);
+            }
+        }
+        // the log is polled for until the job starts
+        if self.is_waiting() {
+            tracing::debug!("waiting, no log yet");
+            return Ok(self);
+        }
+        if let Some(parser) = self.parser.clone() {
+            let time_step = if self.merges_logs() {
+                // the lines of the merged logs follow each other, the furthest log leads
+                lines
+                    .iter()
+                    .filter_map(|line| parser.parse_line(line))
+                    .max_by_key(|(time_step, _)| *time_step)
+            } else {
+                lines.iter().rev().find_map(|line| parser.parse_line(line))
+            };
+            match time_step {
+                Some((time_step, time_value)) => self.advance(time_step, time_value, secs),
+                None if self.step.is_some() || self.is_pending() || self.is_initializing() => {
+                    tracing::debug!("no new time step")
+                }
+                None => {
+                    tracing::warn!("no time step found by the parser");
+                    return Err(CaseError::Capture);
+                }
+            }
+            return Ok(self);
+        }
+        let pattern = Regex::new(parser::TIME_STEP)?;
+        let last_lines = self.source().last_matching_per_log("TimeStep");
+        let last_lines = match last_lines {
+            Ok(lines) => lines,
+            Err(e) if self.is_pending() || self.is_initializing() => {
+                tracing::debug!(error = %e, "no log yet");
+                vec![]
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "no time step found");
+                return Err(CaseError::Grep);
+            }
+        };
+        let mut time_steps = vec![];
+        for time_step in &last_lines {
             // Match the pattern against the input string
-            if let Some(captures) = pattern.captures(&time_step) {
+            if let Some(captures) = pattern.captures(time_step) {
                 // Extract the captured groups
                 let time_step = captures
                     .get(1)
                     .map_or("", |m| m.as_str())
                     .parse::<usize>()?;
                 let time_value = captures.get(2).map_or("", |m| m.as_str()).parse::<f64>()?;
-
-                let diff_step = time_step - self.step.unwrap_or_else(|| time_step);
-                self.step = Some(time_step);
-                self.time = time_value;
-                if diff_step > 0 {
-                    self.elapsed_per_step
-                        .update(UPDATE_TIME as f64 / diff_step as f64);
-                }
-            } else {
+                time_steps.push((time_step, time_value));
+            } else if !self.is_pending() && !self.is_initializing() {
+                tracing::warn!(line = time_step.trim(), "unexpected time step line");
                 return Err(CaseError::Capture);
             }
+        }
+        self.check_log_spread(&time_steps);
+        if let Some(&(time_step, time_value)) =
+            time_steps.iter().max_by_key(|(time_step, _)| *time_step)
+        {
+            self.advance(time_step, time_value, secs);
+        } else if last_lines.is_empty() && !self.is_pending() && !self.is_initializing() {
+            tracing::warn!("no time step found");
+            return Err(CaseError::Capture);
         } else {
-            return Err(CaseError::Grep);
+            tracing::debug!(init = ?self.init_progress().map(|p| p.to_string()), "no time step yet");
         }
 
         Ok(self)
     }
+    /// Updates case status from new lines of the log,
+    /// `secs` being the wall-clock time elapsed since the previous lines
+    ///
+    /// The log is not read from the filesystem, e.g. for a log inspected in a browser.
+    /// The case advances at every time step line, timed by the timestamps of the lines if they have one,
+    /// the time steps sharing `secs` otherwise
+    pub fn update_from_lines(&mut self, lines: &[String], secs: f64) -> Result<&mut Self> {
+        let pattern = Regex::new(parser::TIME_STEP)?;
+        let parser = self.parser.clone();
+        let time_steps: Vec<_> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let time_step = match &parser {
+                    Some(parser) => parser.parse_line(line),
+                    None => pattern.captures(line).and_then(|captures| {
+                        Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+                    }),
+                };
+                time_step.map(|time_step| (i, time_step))
+            })
+            .collect();
+        let secs = secs / time_steps.len().max(1) as f64;
+        // the lines up to each time step line are scanned before advancing to it
+        let mut start = 0;
+        for (i, (time_step, time_value)) in time_steps {
+            self.scan_lines(&lines[start..=i]);
+            self.advance(time_step, time_value, secs);
+            start = i + 1;
+        }
+        if start < lines.len() {
+            self.scan_lines(&lines[start..]);
+        }
+        Ok(self)
+    }
+    /// Records the interval since the previous update if the monitor was offline in it
+    /// and returns the wall-clock time since the last time step change in that case,
+    /// `secs` otherwise
+    fn check_offline(&mut self, secs: f64) -> f64 {
+        let now = self.now();
+        let Some(previous) = self.updated.replace(now) else {
+            return secs;
+        };
+        let gap = (now - previous).num_milliseconds() as f64 / 1e3;
+        if gap <= self.update_interval.unwrap_or(secs) * OFFLINE_RATIO {
+            return secs;
+        }
+        let interval = OfflineInterval {
+            from: previous,
+            to: now,
+        };
+        tracing::warn!(offline = %interval, "monitor was offline");
+        if self.offline.len() == OFFLINE_INTERVALS {
+            self.offline.pop_front();
+        }
+        self.offline.push_back(interval);
+        self.last_advance
+            .map(|at| (now - at).num_milliseconds() as f64 / 1e3)
+            .map_or(gap, |since| since.max(secs))
+    }
+    /// Returns the latest intervals the monitor was offline in, oldest first
+    pub fn offline_intervals(&self) -> impl Iterator<Item = &OfflineInterval> {
+        self.offline.iter()
+    }
+    /// Warns once the latest time steps of the merged logs disagree by more than the threshold
+    fn check_log_spread(&mut self, time_steps: &[(usize, f64)]) {
+        let steps = time_steps.iter().map(|(time_step, _)| *time_step);
+        let spread = steps
+            .clone()
+            .max()
+            .zip(steps.min())
+            .map(|(max, min)| max - min);
+        let spread =
+            spread.filter(|spread| *spread > self.max_log_spread.unwrap_or(MAX_LOG_SPREAD));
+        if spread.is_some() && self.log_spread.is_none() {
+            tracing::warn!(spread, logs = time_steps.len(), "merged logs disagree");
+        }
+        self.log_spread = spread;
+    }
+    /// Returns the number of time steps the merged logs disagree by, if above the threshold
+    pub fn log_spread(&self) -> Option<usize> {
+        self.log_spread
+    }
+    /// Records the latest time step, `secs` being the wall-clock time elapsed since the last time step change
+    fn advance(&mut self, time_step: usize, time_value: f64, secs: f64) {
+        let first = self.first_seen.is_none();
+        if first {
+            if let Some(job) = self.queue.as_ref().and_then(|queue| queue.job.as_deref()) {
+                self.environment.query_scheduler(job);
+            }
+            self.first_seen = Some((self.now(), time_step));
+            // the first rotated log was created when the simulation started
+            self.created = fs::metadata(rotated::oldest_log(self.log_file()))
+                .and_then(|meta| meta.created())
+                .ok()
+                .map(DateTime::from);
+            // the latest time step was written when the log was last modified,
+            // so a case found stalled by a new monitor is reported right away
+            self.last_advance = fs::metadata(self.log_file())
+                .and_then(|meta| meta.modified())
+                .ok()
+                .map(DateTime::from);
+        }
+        let previous = self.step.unwrap_or(time_step);
+        let previous_time = self.time;
+        let diff_step = time_step.saturating_sub(previous);
+        // the log timestamps tell the time between the time steps better than the polls
+        let secs = self.logged_secs(previous, time_step).unwrap_or(secs);
+        let step_durations = self.logged_step_durations(previous, time_step);
+        self.advance_stamp = self.log_stamp.filter(|(step, _)| *step == time_step);
+        self.step = Some(time_step);
+        self.time = time_value;
+        if diff_step > 0 || self.last_advance.is_none() {
+            self.last_advance = Some(self.now());
+        }
+        if diff_step > 0 || first {
+            self.trajectory.push(secs, time_step, time_value);
+            // the case is first seen at no elapsed time without its log creation time
+            if let Some(elapsed) = self.elapsed_secs().filter(|elapsed| *elapsed > 0) {
+                self.sim_timeline.push(time_value, elapsed as f64);
+            }
+        }
+        // the time step of an adaptive time stepping solver changes all the time
+        if diff_step > 0
+            && matches!(self.duration, DurationSpec::Steps(_))
+            && self
+                .dt_changes
+                .update(previous, time_step, previous_time, time_value)
+        {
+            // the time step durations before the change are not averaged with the ones after it
+            self.elapsed_per_step =
+                ElapsedPerStep::since(previous + 1).window(self.elapsed_per_step.window_len());
+            self.contention.rebase();
+        }
+        if diff_step > 0 {
+            self.solver_alive = None;
+            self.elapsed_per_step.update(secs / diff_step as f64);
+            // the time steps done while the monitor was offline are spread over the interval
+            if self
+                .offline
+                .back()
+                .is_some_and(|interval| interval.to == self.now())
+            {
+                self.step_times.push_offline(secs / diff_step as f64);
+            } else {
+                self.step_times.push(secs / diff_step as f64);
+            }
+            // without timestamps on every time step line, the time steps since the previous
+            // advance are taken as all lasting their mean
+            match step_durations {
+                Some(durations) => durations
+                    .into_iter()
+                    .for_each(|(secs, steps)| self.step_time_distribution.add(secs, steps)),
+                None => self
+                    .step_time_distribution
+                    .add(secs / diff_step as f64, diff_step),
+            }
+            self.contention
+                .update(previous, time_step, secs / diff_step as f64);
+            self.etas
+                .push((self.now() + Duration::seconds(self.eta_secs())).timestamp() as f64);
+        }
+        tracing::debug!(
+            step = time_step,
+            time = time_value,
+            diff_step,
+            "time step parsed"
+        );
+        if self.completed.is_none() && self.reached_end() {
+            tracing::info!(step = time_step, "case completed");
+            self.completed = Some(self.now());
+        }
+    }
+    /// Returns true if the last time step reached the end of the simulation
+    fn reached_end(&self) -> bool {
+        match self.duration {
+            DurationSpec::Steps(n_step) => match self.rescaled_end().zip(self.time_step_size()) {
+                Some((end, dt)) => self.step.is_some() && self.time >= end - dt / 2.,
+                None => self.step.is_some_and(|step| step >= n_step),
+            },
+            DurationSpec::PhysicalTime(end) => self.step.is_some() && self.time >= end,
+        }
+    }
+    /// Returns the mean simulated time per time step
+    ///
+    /// With a physical-time duration, it is the latest time step of the fitted trend,
+    /// or the average over the time steps so far until a trend is known,
+    /// otherwise the time step size reported by the log or found since the latest change
+    fn mean_time_step(&self) -> f64 {
+        match (self.duration, self.step, self.dt_trend()) {
+            (DurationSpec::PhysicalTime(_), _, Some(trend)) => trend.dt,
+            (DurationSpec::PhysicalTime(_), Some(step), None)
+                if self.reported_dt.is_none() && step > 0 && self.time > 0. =>
+            {
+                self.time / step as f64
+            }
+            _ => self.time_step_size().unwrap_or(1. / RATE as f64),
+        }
+    }
+    /// Returns the latest intervals of time steps much slower than usual, from oldest to latest
+    pub fn slow_intervals(&self) -> impl Iterator<Item = &SlowInterval> {
+        self.contention.intervals()
+    }
+    /// Returns the number of slow intervals found so far
+    pub fn slow_interval_count(&self) -> usize {
+        self.contention.count()
+    }
+    /// Returns the latest changes of the simulated time per time step, from oldest to latest
+    pub fn dt_changes(&self) -> impl Iterator<Item = &DtChange> {
+        self.dt_changes.changes()
+    }
+    /// Returns the number of changes of the simulated time per time step found so far
+    pub fn dt_change_count(&self) -> usize {
+        self.dt_changes.count()
+    }
+    /// Returns the time step size in simulated seconds, as reported by the log
+    /// or else as found from the simulated time of the time steps
+    pub fn time_step_size(&self) -> Option<f64> {
+        self.reported_dt.or(self.dt_changes.dt())
+    }
+    /// Returns the simulated time a duration in time steps ends at, once the time step size has changed
+    ///
+    /// The time steps of the duration are planned at the first time step size,
+    /// so the simulation ends at the same simulated time with more or fewer time steps
+    fn rescaled_end(&self) -> Option<f64> {
+        let DurationSpec::Steps(n_step) = self.duration else {
+            return None;
+        };
+        let initial = self.dt_changes.initial().or(self.initial_dt)?;
+        let dt = self.time_step_size()?;
+        ((dt - initial).abs() > trajectory::DT_CHANGE_RATIO * initial)
+            .then_some(n_step as f64 * initial)
+    }
+    /// Returns the trend of the time step, fitted over the latest time steps
+    pub fn dt_trend(&self) -> Option<DtTrend> {
+        self.trajectory.dt_trend()
+    }
+    /// Returns the latest time steps with the simulated and the wall-clock times they were seen at
+    pub fn trajectory(&self) -> &Trajectory {
+        &self.trajectory
+    }
+    /// Returns the expected number of time steps left
+    pub fn remaining_steps(&self) -> usize {
+        let step = self.step.unwrap_or_default();
+        match self.duration {
+            DurationSpec::Steps(n_step) => match self.rescaled_end().zip(self.time_step_size()) {
+                Some((end, dt)) => ((end - self.time).max(0.) / dt).round() as usize,
+                None => n_step.saturating_sub(step),
+            },
+            DurationSpec::PhysicalTime(end) => {
+                let remaining = (end - self.time).max(0.);
+                self.dt_trend()
+                    .map_or_else(
+                        || remaining / self.mean_time_step(),
+                        |trend| trend.steps_to(remaining),
+                    )
+                    .ceil() as usize
+            }
+        }
+    }
     /// Return the total number of time steps
+    ///
+    /// With a physical-time duration, it is projected from the trend of the time step,
+    /// and after a change of the time step size, from the current time step size
     pub fn total_step(&self) -> usize {
-        self.duration * RATE
+        match self.duration {
+            DurationSpec::Steps(_) if self.rescaled_end().is_some() => {
+                self.step.unwrap_or_default() + self.remaining_steps()
+            }
+            DurationSpec::Steps(n_step) => n_step,
+            DurationSpec::PhysicalTime(_) => self.step.unwrap_or_default() + self.remaining_steps(),
+        }
     }
-    /// Returns the expected duration of the simulation in seconds
+    /// Returns the simulated time left in seconds, `None` before the first time step
+    pub fn remaining_sim_secs(&self) -> Option<f64> {
+        if self.is_complete() {
+            return Some(0.);
+        }
+        self.step?;
+        Some(match (self.duration, self.rescaled_end()) {
+            (DurationSpec::PhysicalTime(end), _) | (_, Some(end)) => (end - self.time).max(0.),
+            (DurationSpec::Steps(_), None) => self.remaining_steps() as f64 * self.mean_time_step(),
+        })
+    }
+    /// Returns the expected time stepping left in seconds
+    fn run_secs(&self) -> i64 {
+        (&self.elapsed_per_step * self.remaining_steps() as f64) as i64
+    }
+    /// Returns the wall-clock duration in seconds that `secs` seconds of time stepping
+    /// take from now, the jobs being paused during the blackouts
+    fn with_blackouts(&self, secs: i64) -> i64 {
+        if self.blackouts.is_empty() {
+            return secs;
+        }
+        let now = self.now();
+        (run_until(&self.blackouts, now, secs) - now).num_seconds()
+    }
+    /// Returns the expected remaining duration of the simulation in seconds
     pub fn eta_secs(&self) -> i64 {
-        let n_step = self.total_step() - self.step.unwrap();
-        (&self.elapsed_per_step * n_step as f64) as i64
+        self.with_blackouts(self.run_secs())
+    }
+    /// Returns the conservative remaining duration of the simulation in seconds,
+    /// at the mean time step duration over the whole simulation scaled by the downtime factor
+    pub fn conservative_eta_secs(&self) -> i64 {
+        let factor = self.downtime_factor.unwrap_or(1.).max(1.);
+        self.with_blackouts(
+            (self.elapsed_per_step.mean() * self.remaining_steps() as f64 * factor) as i64,
+        )
+    }
+    /// Returns the conservative estimate of the completion of the simulation, the actual one
+    /// once complete, `None` before the first time step
+    pub fn conservative_eta(&self) -> Option<DateTime<Local>> {
+        self.completed.or_else(|| {
+            self.step
+                .map(|_| self.now() + Duration::seconds(self.conservative_eta_secs()))
+        })
+    }
+    /// Returns the remaining wall-clock time of the simulation, zero once complete,
+    /// `None` before the first time step
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.is_complete() {
+            Some(Duration::zero())
+        } else {
+            self.step.map(|_| Duration::seconds(self.eta_secs()))
+        }
+    }
+    /// Returns the estimated completion of the simulation, the actual one once complete,
+    /// `None` before the first time step
+    pub fn eta(&self) -> Option<DateTime<Local>> {
+        self.completed
+            .or_else(|| Some(self.now() + self.remaining()?))
+    }
+    /// Returns the estimated wall-clock start of the simulation
+    ///
+    /// The creation time of the log file is used if the filesystem records it,
+    /// otherwise the start is extrapolated back from the first observed time step
+    pub fn start(&self) -> Option<DateTime<Local>> {
+        self.created.or_else(|| {
+            self.first_seen.map(|(seen, step)| {
+                // the slow first time steps are part of the elapsed time
+                seen - Duration::seconds((self.elapsed_per_step.mean() * step as f64) as i64)
+            })
+        })
+    }
+    /// Returns the wall-clock time elapsed since the start of the simulation in seconds
+    pub fn elapsed_secs(&self) -> Option<i64> {
+        self.start()
+            .map(|start| (self.now() - start).num_seconds().max(0))
+    }
+    /// Returns the average number of time steps per hour since the start of the simulation
+    pub fn steps_per_hour(&self) -> Option<f64> {
+        match (self.step, self.elapsed_secs()) {
+            (Some(step), Some(elapsed)) if elapsed > 0 => {
+                Some(step as f64 * 3600. / elapsed as f64)
+            }
+            _ => None,
+        }
+    }
+    /// Returns the simulated seconds per wall-clock day
+    ///
+    /// The throughput is taken at the mean time step duration,
+    /// or averaged since the start of the simulation until it is known
+    pub fn sim_secs_per_day(&self) -> Option<f64> {
+        let step = self.step.filter(|step| *step > 0)?;
+        let mean = self.elapsed_per_step.mean();
+        let steps_per_day = if mean > 0. {
+            86400. / mean
+        } else {
+            self.steps_per_hour()? * 24.
+        };
+        Some(steps_per_day * self.time / step as f64)
+    }
+    /// Returns the state of the case: pending, initializing, running, paused, failed or completed
+    pub fn state(&self) -> &'static str {
+        if self.is_complete() {
+            "completed"
+        } else if self.paused {
+            "paused"
+        } else if self.log_failure.is_some() {
+            "failed"
+        } else if self.is_pending() {
+            "pending"
+        } else if self.is_waiting() {
+            "waiting"
+        } else if self.is_initializing() {
+            "initializing"
+        } else {
+            "running"
+        }
+    }
+    /// Returns true once the simulation has reached its last time step
+    pub fn is_complete(&self) -> bool {
+        self.completed.is_some()
+    }
+    /// Returns the wall-clock time at which the simulation was found complete
+    pub fn completion(&self) -> Option<DateTime<Local>> {
+        self.completed
+    }
+    /// Returns the total wall-clock duration of a completed simulation in seconds
+    pub fn runtime_secs(&self) -> Option<i64> {
+        self.completed
+            .zip(self.start())
+            .map(|(completed, start)| (completed - start).num_seconds().max(0))
+    }
+    /// Returns a copy of the case with the progress extrapolated by `secs` seconds of wall-clock time
+    pub fn interpolate(&self, secs: f64) -> Self {
+        let mut case = self.clone();
+        let mean = self.elapsed_per_step.estimate();
+        if let (Some(step), true) = (self.step, mean > 0.) {
+            let n_step = ((secs / mean) as usize).min(self.remaining_steps());
+            case.step = Some(step + n_step);
+            case.time += n_step as f64 * self.mean_time_step();
+        }
+        case
+    }
+    /// Returns the wall-clock time since the latest new time step in seconds
+    pub fn idle_secs(&self) -> Option<i64> {
+        self.last_advance
+            .map(|last| (self.now() - last).num_seconds().max(0))
+    }
+    /// Returns true if no new time step has been found for longer than the stall threshold
+    pub fn is_stalled(&self) -> bool {
+        match (self.stall_after, self.last_advance) {
+            (Some(after), Some(last)) if !self.is_complete() && !self.paused => {
+                (self.now() - last).num_seconds() > after as i64
+            }
+            _ => false,
+        }
+    }
+    /// Checks whether the solver process is still alive, if a liveness check is set
+    pub fn check_liveness(&mut self) {
+        let job = self.queue.as_ref().and_then(|queue| queue.job.as_deref());
+        self.solver_alive = self
+            .liveness
+            .as_ref()
+            .and_then(|liveness| liveness.check(&self.name, &self.case_dir(), job));
+    }
+    /// Samples the resource utilization of the solver job, if a collector is set,
+    /// at most every [UTILIZATION_INTERVAL] seconds
+    pub fn sample_resources(&mut self) {
+        let (now, case_dir, cores) = (self.now(), self.case_dir(), self.cores());
+        let job = self.queue.as_ref().and_then(|queue| queue.job.as_deref());
+        if let Some(resources) = self.resources.as_mut() {
+            resources.sample(now, &self.name, &case_dir, job, cores);
+        }
+    }
+    /// Returns the latest resource utilization of the solver job
+    pub fn utilization(&self) -> Option<Utilization> {
+        self.resources.as_ref()?.utilization()
+    }
+    /// Returns true if the last liveness check found the solver process dead
+    pub fn is_solver_dead(&self) -> bool {
+        self.solver_alive == Some(false) && !self.is_complete()
+    }
+    /// Pauses or resumes the updates of the case, the stall timer restarting on resume
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused && !paused && self.last_advance.is_some() {
+            self.last_advance = Some(self.now());
+        }
+        self.paused = paused;
+    }
+    /// Returns true if the updates of the case are paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /// Returns true if the latest time step duration is well above the mean
+    pub fn is_slowing(&self) -> bool {
+        self.step_times.len() > 1
+            && self
+                .step_times
+                .last()
+                .is_some_and(|last| last > SLOWING_RATIO * self.elapsed_per_step.mean())
+    }
+    /// Returns the expected wall-clock duration of a time step in seconds
+    pub fn expected_step_time_secs(&self) -> Option<f64> {
+        self.expected_step_time
+    }
+    /// Returns the drift in percent of the mean time step duration from the expected one,
+    /// once the mean is over enough time steps
+    pub fn step_time_drift(&self) -> Option<f64> {
+        let expected = self.expected_step_time.filter(|secs| *secs > 0.)?;
+        (self.elapsed_per_step.sample() >= STEP_TIME_WARMUP)
+            .then(|| 1e2 * (self.elapsed_per_step.mean() / expected - 1.))
+    }
+    /// Returns true if the mean time step duration drifts from the expected one
+    /// by more than the tolerance
+    pub fn is_off_step_budget(&self) -> bool {
+        self.step_time_drift().is_some_and(|drift| {
+            drift.abs() > self.step_time_tolerance.unwrap_or(STEP_TIME_TOLERANCE)
+        })
+    }
+    /// Returns the name of the case
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Sets the latest disk usage of the case and whether it crosses the configured thresholds
+    pub fn set_disk_usage(&mut self, usage: DiskUsage, at_risk: bool) -> &mut Self {
+        self.disk = Some(usage);
+        self.disk_at_risk = at_risk;
+        self
+    }
+    /// Returns the latest disk usage of the case
+    pub fn disk_usage(&self) -> Option<&DiskUsage> {
+        self.disk.as_ref()
+    }
+    /// Returns true if the case is at risk of running out of disk space
+    pub fn is_disk_at_risk(&self) -> bool {
+        self.disk_at_risk
+    }
+    /// Returns the group the case belongs to
+    pub fn group_name(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    /// Sets the patterns of the log lines marking the milestones of the simulation
+    pub fn milestones(mut self, patterns: Vec<MilestonePattern>) -> Self {
+        self.milestone_patterns = patterns;
+        self
+    }
+    /// Sets the failure signatures of the log lines mapped to triage hints
+    pub fn triage_rules(mut self, rules: Vec<TriageRule>) -> Self {
+        self.triage_rules = rules;
+        self
+    }
+    /// Sets the log line patterns of the fatal solver errors, the case failing once one is found
+    pub fn error_patterns(mut self, patterns: Vec<ErrorPattern>) -> Self {
+        self.error_patterns = patterns;
+        self
+    }
+    /// Sets the post-processing stages run once the simulation completes
+    pub fn stages(mut self, stages: Vec<StageConfig>) -> Self {
+        self.stage_configs = stages;
+        self
+    }
+    /// Returns the post-processing stages with their progress at the latest check
+    pub fn stage_progress(&self) -> &[Stage] {
+        &self.stages
+    }
+    /// Checks the progress of the post-processing stages, returning the stages that changed
+    pub fn check_stages(&mut self) -> Vec<Stage> {
+        let case_dir = self.case_dir();
+        let stages: Vec<_> = self
+            .stage_configs
+            .iter()
+            .map(|stage| stage.check(&case_dir))
+            .collect();
+        let changed = stages
+            .iter()
+            .filter(|stage| !self.stages.contains(stage))
+            .cloned()
+            .collect();
+        self.stages = stages;
+        changed
+    }
+    /// Returns the state of the case in its pipeline: `solving` until the simulation completes,
+    /// then the latest post-processing stage started, `solved` until the first one starts
+    pub fn pipeline_state(&self) -> &str {
+        if !self.is_complete() {
+            return "solving";
+        }
+        self.stages
+            .iter()
+            .rev()
+            .find(|stage| stage.has_started())
+            .map_or("solved", |stage| stage.name.as_str())
+    }
+    /// Returns true once the simulation and all the post-processing stages are done
+    pub fn is_pipeline_done(&self) -> bool {
+        self.is_complete()
+            && self.stages.len() == self.stage_configs.len()
+            && self.stages.iter().all(Stage::is_done)
+    }
+    /// Returns the fatal solver error found in the log since the latest time step
+    pub fn log_failure(&self) -> Option<&LogFailure> {
+        self.log_failure.as_ref()
+    }
+    /// Sets the recurring windows the jobs are paused in, the ETAs skipping over them
+    pub fn blackouts(mut self, blackouts: Vec<BlackoutWindow>) -> Self {
+        self.blackouts = blackouts;
+        self
+    }
+    /// Returns the moves, re-links and replacements of the log file found so far, from oldest to latest
+    pub fn log_relocations(&self) -> &[LogRelocation] {
+        &self.log_relocations
+    }
+    /// Returns the bytes of the new log lines read so far
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+    /// Sets the patterns of the log lines announcing the phases of the solver initialization
+    pub fn init_phases(mut self, phases: Vec<InitPhase>) -> Self {
+        self.init_phases = phases;
+        self
+    }
+    /// Sets the pattern of the log line giving the time step size
+    pub fn dt_pattern(mut self, pattern: DtPattern) -> Self {
+        self.dt_pattern = Some(pattern);
+        self
+    }
+    /// Sets the pattern of the log line of a checkpoint save
+    pub fn checkpoint_pattern(mut self, pattern: CheckpointPattern) -> Self {
+        self.checkpoint_pattern = Some(pattern);
+        self
+    }
+    /// Declares the case a precursor seeding a production case
+    pub fn precursor(mut self, config: PrecursorConfig) -> Self {
+        self.precursor = Some(config);
+        self
+    }
+    /// Sets the number of the latest log lines kept for the excerpts of the anomalies, 300 by default
+    pub fn excerpt_lines(mut self, lines: usize) -> Self {
+        self.excerpt_lines = Some(lines);
+        self
+    }
+    /// Returns the latest log lines, captured in the excerpts of the anomalies and shown in the detail view
+    pub fn recent_lines(&self) -> impl Iterator<Item = &str> {
+        self.recent_lines.iter().map(AsRef::as_ref)
+    }
+    /// Sets the ratio of the wall-clock runtime to the time stepping time of similar completed cases,
+    /// the conservative ETA accounting for their restarts and downtime
+    pub fn downtime_factor(mut self, factor: f64) -> Self {
+        self.downtime_factor = Some(factor);
+        self
+    }
+    /// Returns the production case seeded by the case, if a precursor
+    pub fn precursor_config(&self) -> Option<&PrecursorConfig> {
+        self.precursor.as_ref()
+    }
+    /// Sets the cases the case can only start after, e.g. the previous run of a restart chain
+    pub fn after<S: ToString>(mut self, predecessors: impl IntoIterator<Item = S>) -> Self {
+        self.predecessors = predecessors
+            .into_iter()
+            .map(|name| name.to_string())
+            .collect();
+        self
+    }
+    /// Returns the cases the case can only start after
+    pub fn predecessors(&self) -> &[String] {
+        &self.predecessors
+    }
+    /// Returns the latest checkpoint saved by the solver
+    pub fn last_checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+    /// Returns the checkpoint to restart a failed case from and the progress lost,
+    /// if the case has failed or stalled
+    pub fn restart_advice(&self) -> Option<RestartAdvice> {
+        let step = self.step.filter(|_| self.has_failed())?;
+        // a checkpoint past the latest time step was saved before a restart from an earlier one
+        let checkpoint = self
+            .checkpoint
+            .clone()
+            .filter(|checkpoint| checkpoint.step <= step);
+        let (from_step, from_time) = checkpoint
+            .as_ref()
+            .map_or((0, 0.), |checkpoint| (checkpoint.step, checkpoint.time));
+        Some(RestartAdvice {
+            checkpoint,
+            lost_steps: step - from_step,
+            lost_time: (self.time - from_time).max(0.),
+        })
+    }
+    /// Sets the pattern of the log line giving the solver version
+    pub fn version_pattern(mut self, pattern: VersionPattern) -> Self {
+        self.version_pattern = Some(pattern);
+        self
+    }
+    /// Returns the solver version found in the log
+    pub fn solver_version(&self) -> Option<&str> {
+        self.solver_version.as_deref()
+    }
+    /// Flags the case as run with a solver version different from other cases of its group
+    pub fn set_version_mismatch(&mut self, mismatch: bool) {
+        self.version_mismatch = mismatch;
+    }
+    /// Returns true if other cases of the group were run with a different solver version
+    pub fn has_version_mismatch(&self) -> bool {
+        self.version_mismatch
+    }
+    /// Sets the nominal time in seconds between two updates, e.g. the polling interval
+    /// of the monitor, the sampling windows spanning several updates
+    pub fn set_update_interval(&mut self, secs: f64) {
+        self.update_interval = Some(secs);
+    }
+    /// Flags the case as having time steps longer than the update interval
+    pub fn set_sampled_coarsely(&mut self, coarse: bool) {
+        self.sampled_coarsely = coarse;
+    }
+    /// Returns true if the time steps are longer than the update interval,
+    /// most updates then seeing no new time step
+    pub fn is_sampled_coarsely(&self) -> bool {
+        self.sampled_coarsely
+    }
+    /// Sets the expected completion of the simulation
+    pub fn deadline(mut self, deadline: DateTime<Local>) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+    /// Returns the expected completion of the simulation
+    pub fn deadline_time(&self) -> Option<DateTime<Local>> {
+        self.deadline
+    }
+    /// Returns the schedule slack in seconds, the deadline minus the ETA or the completion,
+    /// negative if the case is projected to miss its deadline
+    pub fn slack_secs(&self) -> Option<i64> {
+        let deadline = self.deadline?;
+        Some((deadline - self.eta()?).num_seconds())
+    }
+    /// Returns true if the case is projected to miss its deadline
+    pub fn is_late(&self) -> bool {
+        self.slack_secs().is_some_and(|slack| slack < 0)
+    }
+    /// Sets the priority of the case, higher first in the attention order, 0 by default
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+    /// Returns the priority of the case
+    pub fn priority_level(&self) -> i32 {
+        self.priority
+    }
+    /// Returns why the case needs attention: failed, stalled or late, `None` if it does not
+    pub fn attention(&self) -> Option<&'static str> {
+        if self.error.is_some() || self.log_failure.is_some() || self.is_solver_dead() {
+            Some("failed")
+        } else if self.is_stalled() {
+            Some("stalled")
+        } else if self.is_late() {
+            Some("late")
+        } else {
+            None
+        }
+    }
+    /// Sets the error of the latest update, `None` if it succeeded
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+    /// Returns the error of the latest update, if it failed
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    /// Sets the note of the operators on the case
+    pub fn set_note(&mut self, note: Option<Note>) {
+        self.note = note;
+    }
+    /// Returns the note of the operators on the case, if any
+    pub fn note(&self) -> Option<&Note> {
+        self.note.as_ref()
+    }
+    /// Returns the latest failure signature of the log with its triage hint,
+    /// if the case has failed or stalled
+    pub fn triage(&self) -> Option<&Triage> {
+        self.triage.as_ref().filter(|_| self.has_failed())
+    }
+    /// Returns true if the latest update failed, a solver error was found in the log
+    /// or the case has stalled
+    pub fn has_failed(&self) -> bool {
+        self.error.is_some()
+            || self.log_failure.is_some()
+            || self.is_stalled()
+            || self.is_solver_dead()
+    }
+    /// Returns the environment the case runs in
+    pub fn environment(&self) -> Environment {
+        Environment {
+            cores: self.cores(),
+            solver_version: self.solver_version.clone(),
+            ..self.environment.clone()
+        }
+    }
+    /// Sets the parser of the time steps, replacing the search of the last `TimeStep` line
+    pub fn parser(mut self, parser: Arc<dyn StepParser>) -> Self {
+        self.parser = Some(parser);
+        self
+    }
+    /// Sets the clock the ETAs and the stalls are computed from, the system clock by default
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+    /// Sets the clock of a case already created, see [Case::clock]
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+    /// Returns the current time of the clock of the case
+    pub fn now(&self) -> DateTime<Local> {
+        self.clock
+            .as_ref()
+            .map_or_else(Local::now, |clock| clock.now())
+    }
+    /// Returns true if the time steps are parsed by a parser set with [Case::parser]
+    pub fn has_parser(&self) -> bool {
+        self.parser.is_some()
+    }
+    /// Returns the wall-clock time in seconds between 2 time steps from the timestamps
+    /// of their log lines, if both have one
+    fn logged_secs(&self, previous: usize, time_step: usize) -> Option<f64> {
+        let ((from, start), (to, end)) = (self.advance_stamp?, self.log_stamp?);
+        (from == previous && to == time_step && to > from && end > start)
+            .then(|| (end - start).num_milliseconds() as f64 / 1e3)
+    }
+    /// Returns the durations in seconds of the time steps between 2 time steps,
+    /// with the number of time steps of each duration, from the timestamps
+    /// of the individual time step lines, if the latest advance and the lines scanned since have one
+    fn logged_step_durations(
+        &self,
+        previous: usize,
+        time_step: usize,
+    ) -> Option<Vec<(f64, usize)>> {
+        let (from, start) = self.advance_stamp?;
+        if from != previous {
+            return None;
+        }
+        let mut stamps = vec![(from, start)];
+        for &(step, at) in &self.step_stamps {
+            let (last, last_at) = stamps[stamps.len() - 1];
+            if step > last && step <= time_step && at >= last_at {
+                stamps.push((step, at));
+            }
+        }
+        (stamps.len() > 1 && stamps[stamps.len() - 1].0 == time_step).then(|| {
+            stamps
+                .windows(2)
+                .map(|pair| {
+                    let ((from, start), (to, end)) = (pair[0], pair[1]);
+                    let steps = to - from;
+                    (
+                        (end - start).num_milliseconds() as f64 / 1e3 / steps as f64,
+                        steps,
+                    )
+                })
+                .collect()
+        })
+    }
+    /// Returns the wall-clock time of the latest time step from the timestamp of its log line
+    pub fn logged_at(&self) -> Option<DateTime<Local>> {
+        self.advance_stamp
+            .filter(|(step, _)| Some(*step) == self.step)
+            .map(|(_, at)| at)
+    }
+    /// Returns the parser of the time step lines, the default pattern without a parser
+    fn step_parser(&self) -> &dyn StepParser {
+        match &self.parser {
+            Some(parser) => parser.as_ref(),
+            None => &*TIME_STEP_LINE,
+        }
+    }
+    /// Scans the lines appended to the log since the last update for milestones,
+    /// for the solver version and, until the first time step, for initialization phases
+    ///
+    /// The milestones already in the log at the first scan have no timestamp
+    fn scan_log(&mut self) -> Result<Vec<String>> {
+        let lines = match self.source().read_new() {
+            Ok(lines) => lines,
+            Err(e) if self.is_pending() => {
+                tracing::debug!(error = %e, "pending, log not readable yet");
+                vec![]
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to read the log");
+                return Err(CaseError::Grep);
+            }
+        };
+        // the line ends were trimmed
+        self.bytes_read += lines.iter().map(|line| line.len() as u64 + 1).sum::<u64>();
+        // the relocations are timed by the clock of the case, not by the sources
+        let now = self.now();
+        let relocations = self.source().relocations();
+        self.log_relocations
+            .extend(relocations.into_iter().map(|relocation| LogRelocation {
+                at: now,
+                ..relocation
+            }));
+        Ok(lines)
+    }
+    /// Looks for milestones, the solver version, the run environment
+    /// and the initialization progress in new lines of the log
+    fn scan_lines(&mut self, lines: &[String]) {
+        let time = self.scanned.then(|| self.now());
+        let mut stamp = None;
+        let mut log_stamp = None;
+        self.step_stamps.clear();
+        for line in lines {
+            stamp = parser::parse_timestamp(line).or(stamp);
+            if let Some((step, _)) = self.step_parser().parse_line(line) {
+                if let Some(at) = stamp.take() {
+                    self.step_stamps.push((step, at));
+                    log_stamp = Some((step, at));
+                }
+            }
+        }
+        if log_stamp.is_some() {
+            self.log_stamp = log_stamp;
+        }
+        let milestones = find_milestones(&self.milestone_patterns, &self.milestones, lines, time);
+        for milestone in &milestones {
+            tracing::info!(milestone = %milestone.name, line = %milestone.line, "milestone found");
+        }
+        self.milestones.extend(milestones);
+        if self.solver_version.is_none() {
+            self.solver_version = self
+                .version_pattern
+                .as_ref()
+                .and_then(|pattern| pattern.find(lines));
+            if let Some(version) = &self.solver_version {
+                tracing::info!(version, "solver version found");
+            }
+        }
+        if let Some(dt) = self
+            .dt_pattern
+            .as_ref()
+            .and_then(|pattern| pattern.find_last(lines))
+        {
+            if self.reported_dt != Some(dt) {
+                tracing::info!(dt, "time step size reported");
+            }
+            self.reported_dt = Some(dt);
+            self.initial_dt = self.initial_dt.or(Some(dt));
+        }
+        if let Some(checkpoint) = self.checkpoint_pattern.as_ref().and_then(|pattern| {
+            pattern.find_last(
+                lines,
+                self.step.map(|step| (step, self.time)),
+                self.step_parser(),
+            )
+        }) {
+            tracing::info!(step = checkpoint.step, file = ?checkpoint.file, "checkpoint saved");
+            self.checkpoint = Some(Checkpoint {
+                at: time,
+                ..checkpoint
+            });
+        }
+        let triage = find_triage(
+            &self.triage_rules,
+            self.triage.clone(),
+            lines,
+            self.step_parser(),
+        );
+        if let Some(triage) = triage
+            .as_ref()
+            .filter(|triage| self.triage.as_ref() != Some(triage))
+        {
+            tracing::warn!(signature = %triage.name, line = %triage.line, "failure signature found");
+        }
+        self.triage = triage;
+        let failure = find_failure(
+            &self.error_patterns,
+            self.log_failure.clone(),
+            lines,
+            self.step_parser(),
+        );
+        match (&failure, &self.log_failure) {
+            (Some(failure), found)
+                if found.as_ref().map(|found| &found.line) != Some(&failure.line) =>
+            {
+                tracing::error!(error = %failure.name, line = %failure.line, "solver error found in the log")
+            }
+            (None, Some(_)) => tracing::info!("time step after the solver error, restarted"),
+            _ => (),
+        }
+        self.log_failure = failure;
+        let mut iterations = std::mem::take(&mut self.inner_iterations);
+        iterations.scan(lines.iter().map(String::as_str), self.step_parser());
+        self.inner_iterations = iterations;
+        self.environment.scan(lines, self.case_dir());
+        let keep = self.excerpt_lines.unwrap_or(EXCERPT_LINES);
+        self.recent_lines.extend(
+            lines[lines.len().saturating_sub(keep)..]
+                .iter()
+                .map(|line| Arc::from(line.as_str())),
+        );
+        let excess = self.recent_lines.len().saturating_sub(keep);
+        self.recent_lines.drain(..excess);
+        if self.step.is_none() {
+            self.init = init_progress(&self.init_phases, self.init.take(), lines);
+        }
+        self.scanned = true;
+    }
+    /// Returns true if the solver has started but no time step has been found yet
+    pub fn is_initializing(&self) -> bool {
+        !self.is_pending() && self.step.is_none() && self.source().exists()
+    }
+    /// Returns true if the log does not exist yet, e.g. while the job is still queued,
+    /// without a scheduler queue tracking it
+    pub fn is_waiting(&self) -> bool {
+        !self.is_pending() && self.step.is_none() && self.source().is_missing()
+    }
+    /// Returns the progress through the initialization phases, if the solver is initializing
+    pub fn init_progress(&self) -> Option<&InitProgress> {
+        self.init.as_ref().filter(|_| self.step.is_none())
+    }
+    /// Returns the milestones found in the log
+    pub fn milestones_found(&self) -> &[Milestone] {
+        &self.milestones
+    }
+    /// Returns the timeline of the milestones,
+    /// repeated milestones being summarized by their count and latest occurrence
+    pub fn timeline(&self) -> String {
+        let mut names: Vec<&str> = vec![];
+        for milestone in &self.milestones {
+            if !names.contains(&milestone.name.as_str()) {
+                names.push(&milestone.name);
+            }
+        }
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let occurrences: Vec<_> =
+                    self.milestones.iter().filter(|m| m.name == name).collect();
+                let last = occurrences.last()?;
+                Some(if occurrences.len() > 1 {
+                    format!("{last} (x{})", occurrences.len())
+                } else {
+                    last.to_string()
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+    /// Sets the number of cores the solver runs on
+    pub fn n_core(mut self, n_core: usize) -> Self {
+        self.n_core = Some(n_core);
+        self
+    }
+    /// Returns the number of cores the solver runs on
+    ///
+    /// If not set, it is parsed from the log file name, logs being named `solve-<cores>_<job>.out`
+    pub fn cores(&self) -> Option<usize> {
+        self.n_core.or_else(|| cores_from_log(&self.log))
+    }
+    /// Returns the number of core-hours consumed since the start of the simulation
+    pub fn consumed_core_hours(&self) -> Option<f64> {
+        let secs = if self.is_complete() {
+            self.runtime_secs()?
+        } else {
+            self.elapsed_secs()?
+        };
+        Some(secs as f64 / 3600. * self.cores()? as f64)
+    }
+    /// Returns the estimated number of core-hours needed to complete the simulation
+    pub fn remaining_core_hours(&self) -> Option<f64> {
+        if self.is_complete() {
+            return Some(0.);
+        }
+        self.step?;
+        Some(self.run_secs() as f64 / 3600. * self.cores()? as f64)
+    }
+    /// Returns the simulation duration in seconds
+    pub fn duration(&self) -> usize {
+        match self.duration {
+            DurationSpec::Steps(n_step) => n_step / RATE,
+            DurationSpec::PhysicalTime(end) => end.round() as usize,
+        }
+    }
+    /// Returns the simulation duration in simulated seconds, at the nominal rate for a step count
+    pub fn sim_seconds(&self) -> SimSeconds {
+        self.duration.sim_seconds()
+    }
+    /// Returns the last time step
+    pub fn step(&self) -> Option<usize> {
+        self.step
+    }
+    /// Returns the mean elapsed time per time step
+    pub fn elapsed_per_step(&self) -> &ElapsedPerStep {
+        &self.elapsed_per_step
+    }
+    /// Returns the history of the time step durations at the latest updates
+    pub fn step_times(&self) -> &History {
+        &self.step_times
+    }
+    /// Returns the distribution of the time step durations over the whole simulation
+    pub fn step_time_distribution(&self) -> &StepTimeDistribution {
+        &self.step_time_distribution
+    }
+    /// Returns the median, the 90th and the 99th percentiles of the time step durations
+    pub fn step_time_percentiles(&self) -> Option<Percentiles> {
+        self.step_time_distribution.percentiles()
+    }
+    /// Returns the simulated time of the case against the wall-clock time since its start
+    pub fn sim_timeline(&self) -> &SimTimeline {
+        &self.sim_timeline
+    }
+    /// Returns the inner iterations per time step
+    pub fn inner_iterations(&self) -> &InnerIterations {
+        &self.inner_iterations
+    }
+    /// Returns true if the latest time steps need many more inner iterations than the first ones
+    pub fn is_iterating_more(&self) -> bool {
+        self.inner_iterations.is_climbing()
+    }
+    /// Returns the history of the ETAs, as Unix timestamps, at the latest updates
+    pub fn etas(&self) -> &History {
+        &self.etas
+    }
+    /// Returns the simulation time at the last time step
+    pub fn time(&self) -> f64 {
+        self.time
     }
     /// Returns the simulation percent complete
     pub fn percent_complete(&self) -> i64 {
-        (100f64 * self.step.unwrap() as f64 / self.total_step() as f64) as i64
+        (100f64 * self.fraction_complete()) as i64
+    }
+    /// Returns the fraction of the simulation complete, from 0 to 1,
+    /// `None` before the first time step
+    pub fn progress(&self) -> Option<f64> {
+        if self.is_complete() {
+            Some(1.)
+        } else {
+            self.step.map(|_| self.fraction_complete().clamp(0., 1.))
+        }
+    }
+    fn fraction_complete(&self) -> f64 {
+        match (self.duration, self.rescaled_end()) {
+            (DurationSpec::Steps(_), Some(end)) => self.time / end,
+            (DurationSpec::Steps(n_step), None) => {
+                self.step.unwrap_or_default() as f64 / n_step as f64
+            }
+            (DurationSpec::PhysicalTime(end), _) if self.step.is_some() => self.time / end,
+            (DurationSpec::PhysicalTime(_), _) => 0.,
+        }
     }
 }
 
 impl Display for Case {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let eta = Local::now() + Duration::seconds(self.eta_secs());
-        write!(
-            f,
-            "{:<20}{:>8}{:>10.2}{:}{:>20}",
-            self.name,
-            self.percent_complete(),
-            self.time,
-            self.elapsed_per_step,
-            eta.format("%Y-%m-%d %H:%M")
-        )
+        self.row(EtaStyle::default()).fmt(f)
     }
 }
+
+/// Status table row of a case
+struct Row<'a> {
+    case: &'a Case,
+    columns: &'a [Column],
+    /// Columns of the second line of the row
+    wrapped: &'a [Column],
+    eta: EtaStyle,
+    name_width: usize,
+}
+
+impl Display for Row<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let case = self.case;
+        case.fmt_row(f, self.columns, self.eta, self.name_width)?;
+        if !self.wrapped.is_empty() && !case.is_initializing() {
+            write!(f, "\n{:1$}", "", self.name_width)?;
+            case.fmt_row(f, self.wrapped, self.eta, self.name_width)?;
+        }
+        if let Some(error) = &case.error {
+            write!(f, "  ! {error}")?;
+        }
+        if let Some(failure) = &case.log_failure {
+            write!(f, "  ! {}: {}", failure.name, failure.line)?;
+        }
+        if let Some(triage) = case.triage() {
+            write!(f, "  ? {triage}")?;
+        }
+        if let Some(advice) = case.restart_advice() {
+            write!(f, "  -> {advice}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Case {
+    /// Returns the status table row of the case, with the ETA in the given format
+    pub fn row(&self, eta: EtaStyle) -> impl Display + '_ {
+        self.row_columns(DEFAULT_COLUMNS, eta)
+    }
+    /// Returns the status table row of the case with the given columns
+    pub fn row_columns<'a>(&'a self, columns: &'a [Column], eta: EtaStyle) -> impl Display + 'a {
+        self.row_fitted(columns, &[], eta, NAME_WIDTH)
+    }
+    /// Returns the status table row of the case with the given columns, the `wrapped` ones
+    /// on a second line, and with the case name truncated to `name_width`
+    pub fn row_fitted<'a>(
+        &'a self,
+        columns: &'a [Column],
+        wrapped: &'a [Column],
+        eta: EtaStyle,
+        name_width: usize,
+    ) -> impl Display + 'a {
+        Row {
+            case: self,
+            columns,
+            wrapped,
+            eta,
+            name_width,
+        }
+    }
+    /// Writes the status columns of the case
+    fn fmt_row(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        columns: &[Column],
+        eta_format: EtaStyle,
+        name_width: usize,
+    ) -> std::fmt::Result {
+        if self.is_initializing() {
+            return write!(
+                f,
+                "{:<name_width$}{:>8}  initializing: {}",
+                columns::truncate(&self.name, name_width),
+                "-",
+                self.init_progress()
+                    .map_or_else(|| "no phase reported yet".to_string(), |p| p.to_string())
+            );
+        }
+        let now = self.now();
+        for column in columns {
+            column.write(f, self, eta_format, now, name_width)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the number of cores from a log file name, logs being named `solve-<cores>_<job>.out`
+pub(crate) fn cores_from_log(log: &str) -> Option<usize> {
+    Regex::new(r"-(\d+)_")
+        .ok()?
+        .captures(log)?
+        .get(1)?
+        .as_str()
+        .parse()
+        .ok()
+}
+
+/// Formats a number of core-hours
+pub fn format_core_hours(core_hours: Option<f64>) -> String {
+    core_hours.map_or_else(|| "-".to_string(), |ch| format!("{ch:.0}"))
+}
+
+/// Formats a duration in seconds as `hours:minutes`
+pub fn format_hours_minutes(secs: i64) -> String {
+    format!("{}:{:02}", secs / 3600, (secs % 3600) / 60)
+}
+
+/// Returns the command running `command` in the shell of the platform, `sh` or `cmd`
+pub(crate) fn shell(command: &str) -> std::process::Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut cmd = std::process::Command::new(shell);
+    cmd.arg(flag).arg(command);
+    cmd
+}
+
+/// Formats a signed duration in seconds as `+hours:minutes` or `-hours:minutes`
+pub fn format_signed_hours_minutes(secs: i64) -> String {
+    let sign = if secs < 0 { '-' } else { '+' };
+    format!("{sign}{}", format_hours_minutes(secs.abs()))
+}
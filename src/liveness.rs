@@ -0,0 +1,98 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+/// Check of the solver process of a stalled case, telling a slow time step from a dead solver
+///
+/// ```toml
+/// [[cases]]
+/// name = "zen30az045_OS2"
+/// liveness = { pid_file = "solver.pid" }
+/// # or liveness = "pgrep" to look for a process with the case name in its command line,
+/// # or liveness = "slurm" to query the state of the `job` of the case
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liveness {
+    /// File holding the pid of the solver, relative to the case directory
+    PidFile(PathBuf),
+    /// Process with the case name in its command line, found with `pgrep -f`
+    Pgrep,
+    /// Scheduler job of the case, running as long as `squeue` reports it
+    Slurm,
+}
+
+impl Liveness {
+    /// Returns true if the solver process is alive, or `None` if it cannot be told
+    pub fn check(&self, name: &str, case_dir: &Path, job: Option<&str>) -> Option<bool> {
+        match self {
+            Self::PidFile(path) => {
+                let pid = fs::read_to_string(case_dir.join(path))
+                    .inspect_err(|e| tracing::debug!(case = name, error = %e, "no pid file"))
+                    .ok()?;
+                process_exists(pid.trim().parse().ok()?)
+            }
+            Self::Pgrep => {
+                let status = Command::new("pgrep")
+                    .args(["-f", name])
+                    .output()
+                    .inspect_err(
+                        |e| tracing::debug!(case = name, error = %e, "failed to run pgrep"),
+                    )
+                    .ok()?
+                    .status;
+                // pgrep exits with 1 if no process matched and 2 or 3 on errors
+                match status.code() {
+                    Some(0) => Some(true),
+                    Some(1) => Some(false),
+                    _ => None,
+                }
+            }
+            Self::Slurm => {
+                let job = job?;
+                let output = Command::new("squeue")
+                    .args(["-h", "-j", job, "-o", "%T"])
+                    .output()
+                    .inspect_err(|e| tracing::debug!(job, error = %e, "failed to run squeue"))
+                    .ok()?;
+                // squeue fails on a job id it has forgotten, i.e. a job finished for a while
+                let state = String::from_utf8_lossy(&output.stdout);
+                Some(
+                    output.status.success()
+                        && matches!(
+                            state.trim(),
+                            "RUNNING" | "COMPLETING" | "CONFIGURING" | "SUSPENDED"
+                        ),
+                )
+            }
+        }
+    }
+}
+
+/// Returns true if the process `pid` exists, or `None` if it cannot be told
+#[cfg(all(unix, feature = "monitor"))]
+fn process_exists(pid: u32) -> Option<bool> {
+    // 0 and the negative pids would signal process groups
+    let pid = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0)?;
+    // SAFETY: the null signal is not sent, only the existence of the process checked
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::ESRCH) => Some(false),
+        // the process of another user
+        Some(libc::EPERM) => Some(true),
+        _ => None,
+    }
+}
+
+/// Returns true if the process `pid` is listed in `/proc`, or `None` where there is no `/proc`
+#[cfg(not(all(unix, feature = "monitor")))]
+fn process_exists(pid: u32) -> Option<bool> {
+    let proc = Path::new("/proc");
+    proc.is_dir().then(|| proc.join(pid.to_string()).exists())
+}
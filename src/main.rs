@@ -1,42 +1,2114 @@
-use cfd_status::{Case, CaseError, UPDATE_TIME};
-use chrono::Local;
-use std::io::{stdout, Write};
-use std::thread::sleep;
-use std::time::Duration;
-
-fn main() -> anyhow::Result<()> {
-    let mut cases = vec![
-        Case::new("zen30az045_OS2", 1_200, "solve-672_14.out"),
-        Case::new("zen30az090_OS2", 1_200, "solve-672_16.out"),
-        Case::new("zen30az045_OS7", 900, "solve-672_15.out"),
-        Case::new("zen30az090_OS7", 900, "solve-672_17.out"),
-        Case::new("zen30az135_OS7", 900, "solve-672_18.out"),
-        Case::new("zen30az045_CD12", 900, "solve-672_19.out"),
-        Case::new("zen30az090_CD12", 900, "solve-672_20.out"),
-        Case::new("zen30az180_CD12", 900, "solve-672_21.out"),
-    ];
+use anyhow::Context;
+#[cfg(feature = "otel")]
+use cfd_status::OtlpExporter;
+#[cfg(feature = "plot")]
+use cfd_status::ProgressPlots;
+use cfd_status::{
+    active_window, parse_log_with, read_session, BulkOperation, Case, CaseConfig, CaseDetail,
+    CaseEvent, CaseFilter, Column, CompletionRecord, Config, Control, ControlClient, Dirs,
+    Discrepancy, DowntimeHistory, EventHistory, ExcerptConfig, FilterTerm, FinishOrder, GlobSource,
+    Heartbeat, Hooks, HttpConfig, LogExcerpt, LogSource, LogWatcher, MaintenanceWindow, Monitor,
+    MonitorEvent, Notes, Notifier, PipeSource, Plugins, PostMortem, Precursors, Preflight,
+    Publisher, Query, Reconciliation, Redactor, Screen, SessionEntry, SessionRecorder,
+    SimTimeAlignment, SnapshotDiff, SortKey, StatusServer, StatusSnapshot, Tabs,
+    ThroughputComparison, TimeStepPattern, TimedRun, TransitionLog, SNIPPET_LINES, START_FORMAT,
+};
+#[cfg(feature = "db")]
+use cfd_status::{CampaignSummary, CaseReport, RunDatabase};
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use clap::{
+    builder::{PossibleValuesParser, TypedValueParser},
+    Args, CommandFactory, Parser, Subcommand,
+};
+#[cfg(unix)]
+use signal_hook::consts::{SIGHUP, SIGUSR1};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{stdout, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tracing_subscriber::{
+    fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
+};
+
+/// Time in seconds between 2 display refreshes
+const REFRESH_TIME: u64 = 1;
+/// Time in milliseconds between 2 checks of the key presses
+const KEY_POLL: u64 = 50;
+/// Configuration file loaded by default if present in the current directory
+const CONFIG: &str = "cfd-status.toml";
+/// Default level of the monitor activity log
+const LOG_LEVEL: &str = "warn";
+/// Default address of the HTTP server in container mode
+const HTTP_ADDR: &str = "0.0.0.0:8080";
+/// Default address of the HTTP server of the monitor driven by `cfd-status ctl`
+const CTL_ADDR: &str = "127.0.0.1:8080";
+/// Number of events shown below the board when replaying a session
+const REPLAY_EVENTS: usize = 5;
+/// Exit code of `cfd-status check` if a case needs attention, 1 being a failure of the check itself
+const CHECK_FAILED: u8 = 2;
+
+/// Monitors the progress of the CFD cases of a campaign
+///
+/// Without a subcommand, the cases are monitored as with `cfd-status watch`
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    watch: WatchArgs,
+}
+
+/// Arguments shared by all the subcommands
+#[derive(Debug, Args)]
+struct GlobalArgs {
+    /// Path to the configuration file
+    #[arg(long, global = true)]
+    config: Option<String>,
+    /// Level, or `tracing` filter directives, of the monitor activity log
+    #[arg(long, global = true, default_value = LOG_LEVEL)]
+    log_level: String,
+    /// Writes the monitor activity log as JSON lines
+    #[arg(long, global = true)]
+    log_json: bool,
+    /// File the monitor activity log is appended to instead of stderr
+    #[arg(long, global = true)]
+    log_file: Option<String>,
+    /// Columns of the status table, separated by commas, instead of the configured ones
+    #[arg(long, global = true, value_delimiter = ',', value_name = "COLUMN")]
+    columns: Option<Vec<Column>>,
+    /// Column the rows of the status table are sorted by, `-column` for a decreasing order,
+    /// instead of the configured one
+    #[arg(long, global = true, value_name = "COLUMN", allow_hyphen_values = true)]
+    sort_by: Option<SortKey>,
+    /// Time zone the ETAs are shown in, e.g. `America/Santiago`, instead of the configured one
+    #[arg(long, global = true, value_name = "ZONE")]
+    timezone: Option<Tz>,
+    /// Follows the ETAs with their UTC time
+    #[arg(long, global = true)]
+    utc: bool,
+    /// Width of the status table in characters instead of the configured one
+    /// or the width of the terminal
+    #[arg(long, global = true)]
+    width: Option<usize>,
+    /// Wraps the columns not fitting the table width to a second line per case
+    #[arg(long, global = true)]
+    wrap: bool,
+    /// Display profile of the status board, e.g. `operator`, `summary` or `pi`,
+    /// instead of the configured one, switched with the `p` key
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+}
+
+/// Arguments of the monitoring
+#[derive(Debug, Clone, Default, PartialEq, Args)]
+struct WatchArgs {
+    /// Appends a compact block at each update instead of refreshing in place
+    #[arg(long)]
+    scroll: bool,
+    /// Prints each update as a JSON line, redacted as configured
+    #[arg(long)]
+    json: bool,
+    /// Updates the cases when their log files are modified instead of at a fixed interval
+    #[arg(long)]
+    watch: bool,
+    /// Address the HTTP server of the status listens on
+    #[arg(long)]
+    http: Option<String>,
+    /// Runs as a container entrypoint: configuration from the environment,
+    /// JSON logs to stdout and HTTP server on, authenticated with `CFD_STATUS_HTTP_TOKEN`
+    #[arg(long)]
+    container: bool,
+    /// File every snapshot and event of the session is recorded to
+    #[arg(long)]
+    record_session: Option<String>,
+    /// Renders the table without colors, also set by a non-empty `NO_COLOR`
+    #[arg(long)]
+    no_color: bool,
+    /// Renders the table without the changes since the previous update next to the columns
+    #[arg(long)]
+    no_deltas: bool,
+    /// Removes the completion records and run database entries of the cases
+    /// no longer configured at startup
+    #[arg(long)]
+    prune: bool,
+    /// Stops monitoring at this time, as `YYYY-MM-DD HH:MM` or `HH:MM`, or after this duration,
+    /// e.g. `90m` or `1h30m`, then prints the final status and exits with a non-zero code
+    /// if any case is stalled, failed or late
+    #[arg(long, value_name = "TIME", value_parser = deadline)]
+    until: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Monitors the cases until they are all complete, the default
+    Watch(WatchArgs),
+    /// Updates the cases once and prints their status
+    Snapshot {
+        /// Prints the status as JSON, redacted as configured
+        #[arg(long, conflicts_with = "compact")]
+        json: bool,
+        /// Prints the compact block instead of the table
+        #[arg(long)]
+        compact: bool,
+        /// Renders the table without colors, also set by a non-empty `NO_COLOR`
+        #[arg(long)]
+        no_color: bool,
+        /// Prints the result of a query instead of the status, e.g. `count(state=stalled) > 0`,
+        /// and exits with a non-zero code if it does not hold
+        #[arg(long, value_name = "QUERY", conflicts_with_all = ["json", "compact"])]
+        query: Option<Query>,
+    },
+    /// Updates the cases once and exits with a non-zero code if any case is stalled,
+    /// failed to update or is projected to miss its deadline, e.g. from cron
+    Check,
+    /// Appends a case to the configuration file, picked up by a running monitor on SIGHUP
+    AddCase {
+        /// Case name, also the name of the case directory
+        name: String,
+        /// Simulation duration in seconds
+        duration: usize,
+        /// Solver log file name within the case directory
+        log: String,
+        /// Campaign the case belongs to
+        #[arg(long)]
+        group: Option<String>,
+        /// Number of cores the solver runs on
+        #[arg(long)]
+        cores: Option<usize>,
+    },
+    /// Sets the note of the operators on a case, e.g. "waiting on license", picked up
+    /// by a running monitor, or prints the notes
+    Note {
+        /// Case name, the notes of all the cases being printed without it
+        name: Option<String>,
+        /// Text of the note, the note of the case being printed without it
+        #[arg(requires = "name")]
+        text: Option<String>,
+        /// Removes the note of the case
+        #[arg(long, requires = "name", conflicts_with = "text")]
+        clear: bool,
+    },
+    /// Prints the per-case summaries of the run database and a calendar heatmap
+    /// of the simulated seconds completed per day
+    Report,
+    /// Prints the report of the completed cases of a campaign archived in the run database:
+    /// total wall-clock, throughput per core count, stall incidents and timeline
+    Summary {
+        /// Campaign of the cases, all the archived cases without it
+        #[arg(long)]
+        campaign: Option<String>,
+        /// Prints the report as an HTML page instead of Markdown
+        #[arg(long)]
+        html: bool,
+    },
+    /// Projects the start and completion of the planned cases as the slots free up
+    Plan {
+        /// Prints the schedule as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Lists the running, pending and planned cases by projected completion with the gaps
+    /// between them, e.g. to schedule the post-processing jobs and the data transfers
+    FinishOrder {
+        /// Prints the finish order as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compares the mean time step duration of the cases and of the completion records
+    /// by number of cores, configured or parsed from the log file names
+    Compare {
+        /// Prints the comparison as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Aligns the cases on the latest simulated time they all reached and compares
+    /// the wall-clock time each took to reach it
+    Align {
+        /// Aligns the cases of this group only, e.g. the configurations of the same flow
+        #[arg(long)]
+        group: Option<String>,
+        /// Prints the alignment as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compares two snapshots printed by `snapshot --json`: the time steps advanced,
+    /// the throughput and the ETA drift of each case over the interval
+    Diff {
+        /// File of the older snapshot, the last line of a file of JSON lines
+        from: String,
+        /// File of the newer snapshot
+        to: String,
+        /// Prints the comparison as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Prints all the time steps of a log, e.g. of a completed run, as JSON lines
+    /// of the time step, the simulated time and the wall-clock timestamp if the log has any
+    Parse {
+        /// Path to the log, gzip-compressed or not
+        log: String,
+        /// Pattern of the time step line, the time step and the simulated time being
+        /// its first and second capture groups
+        #[arg(long)]
+        pattern: Option<String>,
+    },
+    /// Follows a log piped to stdin, e.g. `tail -f solve.out | cfd-status pipe --duration 900`,
+    /// printing the status of the case on a single line at each update until stdin is closed
+    Pipe {
+        /// Simulation duration in seconds
+        #[arg(long)]
+        duration: usize,
+        /// Case name shown in the status
+        #[arg(long, default_value = "stdin")]
+        name: String,
+        /// Seconds between the updates
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+        /// Pattern of the time step line, the time step and the simulated time being
+        /// its first and second capture groups
+        #[arg(long)]
+        pattern: Option<String>,
+    },
+    /// Pauses, resumes, acknowledges the alerts of or removes the matching cases
+    /// of a running monitor, through its HTTP server
+    Ctl {
+        /// Operation applied to every matching case
+        #[arg(value_parser = PossibleValuesParser::new(["pause", "resume", "ack", "remove"])
+            .map(|operation| operation.parse::<BulkOperation>().unwrap()))]
+        operation: BulkOperation,
+        #[command(flatten)]
+        selection: Selection,
+        /// Address of the HTTP server of the monitor, `https://host:port` over TLS
+        #[arg(long, default_value = CTL_ADDR)]
+        addr: String,
+        /// Bearer token of the monitor, its control token if set,
+        /// `CFD_STATUS_HTTP_TOKEN` by default
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Creates the configuration and state directories of the user with a configuration
+    /// to edit, `~/.config/cfd-status/config.toml`, loaded when no other configuration is found
+    Init {
+        /// Overwrites an existing configuration
+        #[arg(long)]
+        force: bool,
+    },
+    /// Checks every configured case before a long monitoring: the root and the case
+    /// directories exist, the logs are readable and have a time step line, and the durations
+    /// and the time stepping rate are sane, reporting all the problems at once
+    Preflight {
+        /// Checks the configuration of the container entrypoint, from the environment
+        #[arg(long)]
+        container: bool,
+    },
+    /// Checks the configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Re-renders a recorded session instead of monitoring the cases, or reviews it
+    #[command(alias = "replay-session")]
+    Replay {
+        /// Session file recorded with `--record-session`
+        path: String,
+        /// Replay speed, as a multiple of the recording speed
+        #[arg(long, default_value_t = 1., value_parser = positive)]
+        speed: f64,
+        /// Longest wait in seconds between 2 entries of the replay, skipping the quiet periods
+        #[arg(long, value_name = "SECS")]
+        max_gap: Option<f64>,
+        /// Replays the entries recorded from this time on, as `YYYY-MM-DD HH:MM`
+        #[arg(long, value_parser = local_time)]
+        from: Option<DateTime<Local>>,
+        /// Replays the entries recorded up to this time, as `YYYY-MM-DD HH:MM`
+        #[arg(long, value_parser = local_time)]
+        to: Option<DateTime<Local>>,
+        /// Appends the compact blocks and the events instead of refreshing in place
+        #[arg(long)]
+        scroll: bool,
+        /// Prints the incidents of the session and the evolution of the ETAs instead of replaying it
+        #[arg(long, conflicts_with_all = ["speed", "max_gap", "scroll"])]
+        post_mortem: bool,
+    },
+    /// Displays the merged status of the monitors of several clusters, from the snapshots
+    /// they push to this instance or write to a shared directory
+    Aggregate {
+        /// Shared directory the monitors write their snapshots to
+        #[arg(long, required_unless_present = "listen")]
+        dir: Option<String>,
+        /// Address the snapshots are pushed to, on `POST /push`
+        #[arg(long, num_args = 0..=1, default_missing_value = HTTP_ADDR)]
+        listen: Option<String>,
+        /// Time in seconds between 2 refreshes
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+        /// Time in seconds without a snapshot before a cluster is flagged as stale
+        #[arg(long, default_value_t = 600)]
+        stale_after: i64,
+        /// Prints the merged status as JSON lines
+        #[arg(long)]
+        json: bool,
+        /// Prints the merged status once and exits
+        #[arg(long, conflicts_with = "listen")]
+        once: bool,
+    },
+}
 
+/// Cases a bulk operation applies to
+#[derive(Debug, Args)]
+#[group(required = true, multiple = true)]
+struct Selection {
+    /// Condition on the cases, `name=`, `tag=`, `group=` or `state=`, all to be satisfied
+    #[arg(long = "filter", value_name = "KEY=VALUE")]
+    filters: Vec<FilterTerm>,
+    /// Selects only the stalled cases, dead solvers included
+    #[arg(long)]
+    all_stalled: bool,
+    /// Selects all the cases
+    #[arg(long, conflicts_with_all = ["filters", "all_stalled"])]
+    all: bool,
+}
+
+impl From<&Selection> for CaseFilter {
+    fn from(selection: &Selection) -> Self {
+        Self {
+            terms: selection.filters.clone(),
+            stalled: selection.all_stalled,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Loads the configuration and the plugins and reports the problems found
+    Validate,
+    /// Compares the configuration with the persisted state and the case directories
+    Reconcile {
+        /// Removes the completion records and run database entries of the cases
+        /// no longer configured
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+/// Parses a positive factor
+fn positive(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|value: &f64| *value > 0.)
+        .ok_or_else(|| "expected a positive factor".to_string())
+}
+
+/// Parses a local date and time formatted as [START_FORMAT]
+fn local_time(value: &str) -> Result<DateTime<Local>, String> {
+    NaiveDateTime::parse_from_str(value, START_FORMAT)
+        .ok()
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+        .ok_or_else(|| format!("expected a local time as {START_FORMAT:?}"))
+}
+
+/// Parses a deadline: a local time as [START_FORMAT], a time of day, the next one,
+/// or a duration from now with `d`, `h`, `m` and `s` units, e.g. `1h30m`
+fn deadline(value: &str) -> Result<DateTime<Local>, String> {
+    let now = Local::now();
+    if let Ok(time) = local_time(value) {
+        return Ok(time);
+    }
+    if let Ok(time) = NaiveTime::parse_from_str(value, "%H:%M") {
+        let today = now.date_naive().and_time(time);
+        let next = if today > now.naive_local() {
+            today
+        } else {
+            today + chrono::Duration::days(1)
+        };
+        return next
+            .and_local_timezone(Local)
+            .earliest()
+            .ok_or_else(|| format!("{value} does not exist in the local time zone"));
+    }
+    let invalid = || {
+        format!("expected a local time as {START_FORMAT:?} or \"HH:MM\", or a duration as e.g. \"1h30m\"")
+    };
+    let (mut secs, mut number) = (0i64, String::new());
+    for c in value.trim().chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'd' | 'h' | 'm' | 's' if !number.is_empty() => {
+                let unit = match c {
+                    'd' => 86400,
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                secs += number.parse::<i64>().map_err(|_| invalid())? * unit;
+                number.clear();
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    if !number.is_empty() || secs == 0 {
+        return Err(invalid());
+    }
+    Ok(now + chrono::Duration::seconds(secs))
+}
+
+impl Cli {
+    /// Parses the command line, completed by the `CFD_STATUS_*` and `NO_COLOR`
+    /// environment variables
+    fn from_env() -> Self {
+        let mut cli = Self::parse();
+        if cli.command.is_some() && cli.watch != WatchArgs::default() {
+            Self::command()
+                .error(
+                    clap::error::ErrorKind::ArgumentConflict,
+                    "the monitoring options go after `watch` when a subcommand is given",
+                )
+                .exit();
+        }
+        let no_color = std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+        let watch = match &mut cli.command {
+            Some(Command::Watch(watch)) => watch,
+            Some(Command::Snapshot { no_color: flag, .. }) => {
+                *flag |= no_color;
+                return cli;
+            }
+            Some(_) => return cli,
+            None => &mut cli.watch,
+        };
+        if std::env::var("CFD_STATUS_CONTAINER").is_ok_and(|v| !v.is_empty() && v != "0") {
+            watch.container = true;
+        }
+        watch.no_color |= no_color;
+        if watch.container {
+            cli.global.log_json = true;
+            if let Ok(level) = std::env::var("CFD_STATUS_LOG_LEVEL") {
+                cli.global.log_level = level;
+            } else if cli.global.log_level == LOG_LEVEL {
+                cli.global.log_level = "info".to_string();
+            }
+            if watch.http.is_none() {
+                watch.http = Some(
+                    std::env::var("CFD_STATUS_HTTP").unwrap_or_else(|_| HTTP_ADDR.to_string()),
+                );
+            }
+        }
+        cli
+    }
+    /// Returns the monitoring arguments, of `watch` or of the default invocation
+    fn watch_args(&self) -> &WatchArgs {
+        match &self.command {
+            Some(Command::Watch(watch)) => watch,
+            _ => &self.watch,
+        }
+    }
+}
+
+/// Installs the subscriber writing the monitor activity log to stderr or to `--log-file`,
+/// and exporting the spans of the monitor if an OpenTelemetry collector is set
+fn init_logging(args: &GlobalArgs, container: bool) -> anyhow::Result<()> {
+    let filter = EnvFilter::try_new(&args.log_level)
+        .with_context(|| format!("invalid log level {:?}", args.log_level))?;
+    let writer = match &args.log_file {
+        Some(path) => BoxMakeWriter::new(Arc::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("opening {path}"))?,
+        )),
+        None if container => BoxMakeWriter::new(std::io::stdout),
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(args.log_file.is_none());
+    let layer = if args.log_json {
+        layer.json().boxed()
+    } else {
+        layer.boxed()
+    };
+    let registry = tracing_subscriber::registry().with(layer.with_filter(filter));
+    // the spans are exported whatever the log level
+    #[cfg(feature = "otel")]
+    let registry = registry.with(OtlpExporter::from_env().map(|exporter| {
+        exporter.layer().with_filter(
+            tracing_subscriber::filter::Targets::new()
+                .with_target("cfd_status", tracing::Level::DEBUG),
+        )
+    }));
+    registry.init();
+    Ok(())
+}
+
+/// Appends a compact block of the status at each update until the collector hangs up
+fn scroll(rx: Receiver<StatusSnapshot>) {
+    for snapshot in rx {
+        println!("{}", snapshot.compact());
+    }
+}
+
+/// Prints each snapshot as a redacted JSON line until the collector hangs up
+fn json(rx: Receiver<StatusSnapshot>, redactor: Redactor) {
+    for snapshot in rx {
+        println!("{}", snapshot.to_json(&redactor));
+    }
+}
+
+/// Renders the tab shown of the latest snapshot at every refresh until the collector hangs up
+///
+/// The tabs are switched, the detail view of a case, with its latest `events`, opened,
+/// the notes of the cases edited, into the `notes` file, and the alerts of the cases
+/// acknowledged, sent to `acks`, with the keys pressed if stdin is a terminal,
+/// and the cells are annotated with the changes since the previous snapshot if `deltas` is set
+///
+/// The tables are fitted to the width of the terminal, as resized, without a configured `width`
+fn render(
+    rx: Receiver<StatusSnapshot>,
+    events: Receiver<SessionEntry>,
+    mut tabs: Tabs,
+    deltas: bool,
+    notes: Option<PathBuf>,
+    acks: Sender<String>,
+    width: Option<usize>,
+) {
+    let mut history = EventHistory::new();
     let mut stdout = stdout();
-    let error: Result<(), CaseError> = loop {
-        let status = match cases
-            .iter_mut()
-            .map(|case| case.update().map(|case| case.to_string()))
-            .collect::<Result<Vec<String>, CaseError>>()
+    let mut screen = Screen::new();
+    let mut snapshot: Option<StatusSnapshot> = None;
+    let terminal = RawTerminal::new();
+    let (key_tx, key_rx) = mpsc::channel();
+    if terminal.is_some() {
+        thread::spawn(move || keys(key_tx));
+    }
+    let mut drawn: Option<Instant> = None;
+    loop {
+        let (hung_up, mut changed) = match rx.recv_timeout(Duration::from_millis(KEY_POLL)) {
+            Ok(latest) => {
+                snapshot = Some(match snapshot.take() {
+                    Some(previous) if deltas => latest.with_deltas(&previous),
+                    _ => latest,
+                });
+                // a display profile refreshing less often shows the latest snapshot once due
+                (false, tabs.refresh().is_none())
+            }
+            Err(RecvTimeoutError::Timeout) => (false, false),
+            Err(RecvTimeoutError::Disconnected) => (true, false),
+        };
+        for key in key_rx.try_iter() {
+            changed |= tabs.handle_key(key, snapshot.as_ref());
+        }
+        // the monitor picks up the notes file once modified
+        if let Some(((case, text), path)) = tabs.take_note().zip(notes.as_ref()) {
+            let mut notes = Notes::read(path);
+            notes.set(&case, text, Local::now());
+            if let Err(e) = notes.write(path) {
+                tracing::error!(?path, error = %e, "failed to write the note");
+            }
+        }
+        if let Some(case) = tabs.take_acknowledged() {
+            let _ = acks.send(case);
+        }
+        for entry in events.try_iter() {
+            history.push(entry);
+        }
+        if width.is_none() {
+            let resized = terminal_width();
+            changed |= resized != tabs.active().columns.width;
+            tabs.set_width(resized);
+        }
+        let refresh = tabs.refresh().unwrap_or(Duration::from_secs(REFRESH_TIME));
+        if let Some(snapshot) = &snapshot {
+            if changed || drawn.is_none_or(|drawn| drawn.elapsed() >= refresh) {
+                let detail = tabs
+                    .detail()
+                    .and_then(|name| snapshot.cases().find(|case| case.name() == name));
+                let board = match detail {
+                    Some(case) => format!(
+                        "{} (Enter or Esc: back)\n{}",
+                        case.name(),
+                        CaseDetail::new(case, history.case(case.name()))
+                    ),
+                    None => tabs.render(snapshot),
+                };
+                screen
+                    .draw(&mut stdout, &board)
+                    .expect("failed to write to terminal");
+                drawn = Some(Instant::now());
+            }
+        }
+        if hung_up {
+            break;
+        }
+    }
+}
+
+/// Returns the width of the terminal of stdout, `None` if stdout is not a terminal
+#[cfg(unix)]
+fn terminal_width() -> Option<usize> {
+    // SAFETY: the window size is initialized by the ioctl before being read
+    unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        (libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0)
+            .then_some(size.ws_col as usize)
+    }
+}
+
+/// Returns the width of the terminal of stdout from `COLUMNS`, `None` if stdout is not a terminal
+#[cfg(not(unix))]
+fn terminal_width() -> Option<usize> {
+    use std::io::IsTerminal;
+    if !stdout().is_terminal() {
+        return None;
+    }
+    std::env::var("COLUMNS").ok()?.parse().ok()
+}
+
+/// Terminal reading single key presses without echoing them, restored when dropped
+#[cfg(unix)]
+struct RawTerminal(libc::termios);
+
+#[cfg(unix)]
+impl RawTerminal {
+    /// Switches the terminal of stdin to single key presses, `None` if stdin is not a terminal
+    fn new() -> Option<Self> {
+        // SAFETY: the termios structure is initialized by tcgetattr before being used
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0 {
+                return None;
+            }
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return None;
+            }
+            let original = termios;
+            // signals are still sent by Ctrl-C
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            (libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) == 0)
+                .then_some(Self(original))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        // SAFETY: the settings were read from the same terminal by tcgetattr
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
+/// Terminal without key presses, only read from Unix terminals
+#[cfg(not(unix))]
+struct RawTerminal;
+
+#[cfg(not(unix))]
+impl RawTerminal {
+    /// The keys are not read
+    fn new() -> Option<Self> {
+        None
+    }
+}
+
+/// Sends the keys pressed until stdin is closed or the renderer hangs up
+fn keys(tx: Sender<char>) {
+    let mut stdin = std::io::stdin().lock();
+    let mut buf = [0; 16];
+    while let Ok(n @ 1..) = stdin.read(&mut buf) {
+        // the up and down arrows select the previous and next case, like `k` and `j`
+        let keys = match &buf[..n] {
+            b"\x1b[A" => vec!['k'],
+            b"\x1b[B" => vec!['j'],
+            keys => keys.iter().map(|byte| *byte as char).collect(),
+        };
+        if keys.into_iter().any(|key| tx.send(key).is_err()) {
+            break;
+        }
+    }
+}
+
+/// Re-renders the entries of a recorded session, `speed` times faster than they were recorded
+/// and waiting at most `max_gap` seconds between 2 entries
+///
+/// The board of each snapshot is drawn with the latest events below it,
+/// or the compact blocks and events are appended with `--scroll`.
+/// Case updates are recorded but not replayed, the board shows the progress
+fn replay(
+    entries: impl Iterator<Item = SessionEntry>,
+    speed: f64,
+    max_gap: Option<f64>,
+    scroll: bool,
+) -> anyhow::Result<()> {
+    let mut stdout = stdout();
+    let mut screen = Screen::new();
+    let mut board = String::new();
+    let mut events = VecDeque::new();
+    let mut previous: Option<DateTime<Local>> = None;
+    for entry in entries {
+        if matches!(&entry, SessionEntry::Event { event, .. } if event == "updated") {
+            continue;
+        }
+        if let Some(previous) = previous {
+            let mut gap = (entry.at() - previous)
+                .to_std()
+                .unwrap_or_default()
+                .div_f64(speed);
+            if let Some(max_gap) = max_gap {
+                gap = gap.min(Duration::from_secs_f64(max_gap.max(0.)));
+            }
+            thread::sleep(gap);
+        }
+        previous = Some(entry.at());
+        match entry {
+            SessionEntry::Event {
+                at,
+                event,
+                case,
+                detail,
+            } => {
+                let mut line = format!("{} {event:<14}{case}", at.format("%Y-%m-%d %H:%M:%S"));
+                if let Some(detail) = detail {
+                    line.push_str(&format!(" {detail}"));
+                }
+                if scroll {
+                    println!("{line}");
+                    continue;
+                }
+                events.push_back(line);
+                if events.len() > REPLAY_EVENTS {
+                    events.pop_front();
+                }
+            }
+            SessionEntry::Snapshot {
+                board: latest,
+                compact,
+                ..
+            } => {
+                if scroll {
+                    println!("{compact}");
+                    continue;
+                }
+                board = latest;
+            }
+        }
+        let events: Vec<_> = events.iter().map(String::as_str).collect();
+        screen.draw(
+            &mut stdout,
+            &format!("{board}\nEvents:\n{}", events.join("\n")),
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the configuration with the table options of the command line applied
+fn config(args: &GlobalArgs, container: bool) -> anyhow::Result<Config> {
+    let mut config = load_config(args, container, true)?;
+    if args.columns.is_some() {
+        config.columns.clone_from(&args.columns);
+    }
+    if args.sort_by.is_some() {
+        config.sort_by = args.sort_by;
+    }
+    if args.timezone.is_some() {
+        config.eta.timezone = args.timezone;
+    }
+    config.eta.utc |= args.utc;
+    if args.width.is_some() {
+        config.table_width = args.width;
+    }
+    config.wrap_columns |= args.wrap;
+    if args.profile.is_some() {
+        config.profile.clone_from(&args.profile);
+        config.validate()?;
+    }
+    Ok(config)
+}
+
+/// Loads the configuration given with `--config <path>`, found in the current directory
+/// or in the configuration directory of the user or, in container mode, from the environment
+///
+/// The relative paths of the persisted state of the configuration of the user are
+/// in its state directory, created if missing, and the cases are validated if `checked` is set
+fn load_config(args: &GlobalArgs, container: bool, checked: bool) -> anyhow::Result<Config> {
+    if container && args.config.is_none() {
+        let config = match checked {
+            true => Config::from_env(),
+            false => Config::from_env_unchecked(),
+        };
+        return config.context("loading the configuration from the environment");
+    }
+    let from_path = |path: &Path| match checked {
+        true => Config::from_path(path),
+        false => Config::from_path_unchecked(path),
+    };
+    match args.config.as_deref() {
+        Some(path) => from_path(Path::new(path)).with_context(|| format!("loading {path}")),
+        None if Path::new(CONFIG).exists() => {
+            from_path(Path::new(CONFIG)).with_context(|| format!("loading {CONFIG}"))
+        }
+        None => match user_dirs() {
+            Some(dirs) => {
+                let path = dirs.config_file();
+                let mut config =
+                    from_path(&path).with_context(|| format!("loading {}", path.display()))?;
+                fs::create_dir_all(&dirs.state)
+                    .with_context(|| format!("creating {}", dirs.state.display()))?;
+                config.relocate_state(&dirs.state);
+                Ok(config)
+            }
+            None => Ok(Config {
+                cases: vec![
+                    CaseConfig::new("zen30az045_OS2", 1_200, "solve-672_14.out"),
+                    CaseConfig::new("zen30az090_OS2", 1_200, "solve-672_16.out"),
+                    CaseConfig::new("zen30az045_OS7", 900, "solve-672_15.out"),
+                    CaseConfig::new("zen30az090_OS7", 900, "solve-672_17.out"),
+                    CaseConfig::new("zen30az135_OS7", 900, "solve-672_18.out"),
+                    CaseConfig::new("zen30az045_CD12", 900, "solve-672_19.out"),
+                    CaseConfig::new("zen30az090_CD12", 900, "solve-672_20.out"),
+                    CaseConfig::new("zen30az180_CD12", 900, "solve-672_21.out"),
+                ],
+                ..Default::default()
+            }),
+        },
+    }
+}
+
+/// Returns the configuration file `--config` or, in container mode, `CFD_STATUS_CONFIG` gives,
+/// else the one found in the current directory or in the configuration directory of the user, if any
+fn config_file(args: &GlobalArgs, container: bool) -> Option<PathBuf> {
+    match args.config.as_deref() {
+        Some(path) => Some(path.into()),
+        None if container => std::env::var_os("CFD_STATUS_CONFIG").map(PathBuf::from),
+        None => Some(PathBuf::from(CONFIG))
+            .filter(|path| path.exists())
+            .or_else(|| user_dirs().map(|dirs| dirs.config_file())),
+    }
+}
+
+/// Returns the directories of the user if their configuration file exists
+fn user_dirs() -> Option<Dirs> {
+    Dirs::from_env().filter(|dirs| dirs.config_file().exists())
+}
+
+/// Creates the configuration and state directories of the user with the configuration template
+fn init(force: bool) -> anyhow::Result<()> {
+    let dirs = Dirs::from_env().context("no home directory")?;
+    let path = dirs
+        .init(force)
+        .with_context(|| format!("initializing {}", dirs.config.display()))?;
+    println!(
+        "created {}, state in {}",
+        path.display(),
+        dirs.state.display()
+    );
+    println!("add the cases to it, then run cfd-status");
+    Ok(())
+}
+
+/// File watched for modifications, e.g. the configuration reloaded once modified
+struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl WatchedFile {
+    fn new(path: PathBuf) -> Self {
+        let modified = Self::modified(&path);
+        Self { path, modified }
+    }
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
+    /// Returns true if the file was modified since the last call
+    fn changed(&mut self) -> bool {
+        let modified = Self::modified(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+}
+
+/// Sets the custom log parser of the plugins, if any, to the cases
+fn with_parser(cases: Vec<Case>, plugins: &Plugins) -> Vec<Case> {
+    match &plugins.parser {
+        Some(parser) => cases
+            .into_iter()
+            // the time step pattern of a case prevails
+            .map(|case| {
+                if case.has_parser() {
+                    case
+                } else {
+                    case.parser(parser.clone())
+                }
+            })
+            .collect(),
+        None => cases,
+    }
+}
+
+/// Sets the downtime factor of the completed cases of the same group, if any, to the cases
+fn with_history(cases: Vec<Case>, history: &DowntimeHistory) -> Vec<Case> {
+    cases
+        .into_iter()
+        .map(|case| match history.factor(case.group_name()) {
+            Some(factor) => case.downtime_factor(factor),
+            None => case,
+        })
+        .collect()
+}
+
+/// Prints the alignment of the cases, of a group if any, on simulated time
+fn align(config: &Config, group: Option<&str>, json: bool) -> anyhow::Result<()> {
+    let snapshot = poll_once(config, true)?;
+    let alignment = SimTimeAlignment::new(
+        snapshot
+            .cases()
+            .filter(|case| group.is_none() || case.group_name() == group),
+    );
+    if json {
+        println!("{}", serde_json::to_string(&alignment)?);
+    } else {
+        print!("{alignment}");
+    }
+    Ok(())
+}
+
+/// Updates the cases once and returns their status
+fn poll_once(config: &Config, no_color: bool) -> anyhow::Result<StatusSnapshot> {
+    let plugins = config.plugins.load().context("loading plugins")?;
+    let history = DowntimeHistory::read(&config.records);
+    let mut monitor = Monitor::new(
+        with_history(with_parser(config.cases(), &plugins), &history),
+        Duration::from_secs(config.interval),
+    )
+    .maintenance(config.maintenance.clone());
+    let snapshot = monitor
+        .poll()
+        .into_iter()
+        .find_map(|event| match event {
+            MonitorEvent::Snapshot(snapshot) => Some(snapshot),
+            _ => None,
+        })
+        .context("no snapshot after the update")?
+        .with_calendar(config.calendar.clone())
+        .with_theme((!no_color).then_some(config.theme))
+        .with_health(&config.health)
+        .with_eta(config.eta)
+        .with_columns(config.column_spec())
+        .with_plan(config.planned(), config.slots);
+    Ok(snapshot)
+}
+
+/// Updates the cases once and prints their status as a table, a compact block or JSON
+fn snapshot(config: &Config, json: bool, compact: bool, no_color: bool) -> anyhow::Result<()> {
+    let mut snapshot = poll_once(config, no_color)?.with_columns(config.column_spec().with_width(
+        config.table_width.or_else(terminal_width),
+        config.wrap_columns,
+    ));
+    if let Some(profile) = config
+        .profile
+        .as_deref()
+        .and_then(|name| config.display_profile(name))
+    {
+        snapshot = profile.apply(snapshot);
+    }
+    if json {
+        println!("{}", snapshot.to_json(&Redactor::from(&config.redaction)));
+    } else if compact {
+        println!("{}", snapshot.compact());
+    } else {
+        print!("{snapshot}");
+    }
+    Ok(())
+}
+
+/// Updates the cases once and prints a line per case needing attention and a summary line,
+/// returns false if any case needs attention
+///
+/// Stalls and update failures are expected during a maintenance window and not reported
+///
+/// ```text
+/// stalled case=zen30az045_OS2 step=1200 idle_secs=3600
+/// dead case=zen30az090_OS2 step=800 idle_secs=4200
+/// failed case=zen30az135_OS2 error="No TimeStep/Time match found"
+/// late case=zen30az180_OS2 slack_secs=-5400
+/// status=fail cases=6 stalled=2 failed=1 late=1
+/// ```
+fn check(config: &Config) -> anyhow::Result<bool> {
+    let snapshot = poll_once(config, true)?;
+    let maintenance = active_window(&config.maintenance, Local::now());
+    let (mut stalled, mut failed, mut late) = (0, 0, 0);
+    for case in snapshot.cases() {
+        let step = case
+            .step()
+            .map_or_else(|| "-".to_string(), |step| step.to_string());
+        let idle = case.idle_secs().unwrap_or_default();
+        if maintenance.is_none() && case.is_solver_dead() {
+            stalled += 1;
+            println!("dead case={} step={step} idle_secs={idle}", case.name());
+        } else if maintenance.is_none() && case.is_stalled() {
+            stalled += 1;
+            println!("stalled case={} step={step} idle_secs={idle}", case.name());
+        }
+        if let Some(error) = case.error().filter(|_| maintenance.is_none()) {
+            failed += 1;
+            println!("failed case={} error={error:?}", case.name());
+        }
+        if let Some(slack) = case
+            .slack_secs()
+            .filter(|_| case.is_late() && !case.is_complete())
         {
-            Ok(status) => status,
-            Err(e) => break Err(e),
+            late += 1;
+            println!("late case={} slack_secs={slack}", case.name());
+        }
+    }
+    let ok = stalled + failed + late == 0;
+    println!(
+        "status={} cases={} stalled={stalled} failed={failed} late={late}{}",
+        if ok { "ok" } else { "fail" },
+        snapshot.cases().count(),
+        maintenance.map_or_else(String::new, |window| format!(
+            " maintenance={:?}",
+            window.to_string()
+        ))
+    );
+    Ok(ok)
+}
+
+/// Updates the cases once and prints the schedule of the planned cases as a Gantt chart or JSON
+fn plan(config: &Config, json: bool) -> anyhow::Result<()> {
+    let plan = poll_once(config, true)?.plan();
+    if json {
+        println!("{}", serde_json::to_string(&plan)?);
+    } else {
+        println!("{plan}");
+    }
+    Ok(())
+}
+
+/// Updates the cases once and prints the cases by projected completion
+fn finish_order(config: &Config, json: bool) -> anyhow::Result<()> {
+    let order = FinishOrder::new(&poll_once(config, true)?.plan());
+    if json {
+        println!("{}", serde_json::to_string(&order)?);
+    } else {
+        print!("{order}");
+    }
+    Ok(())
+}
+
+/// Updates the cases once and prints the time step throughput by number of cores
+/// of the cases and of the completion records
+fn compare(config: &Config, json: bool) -> anyhow::Result<()> {
+    let snapshot = poll_once(config, true)?;
+    let runs = TimedRun::read_records(&config.records)
+        .into_iter()
+        .chain(snapshot.cases().filter_map(TimedRun::from_case));
+    let comparison = ThroughputComparison::new(runs);
+    if json {
+        println!("{}", serde_json::to_string(&comparison)?);
+    } else if comparison.is_empty() {
+        println!("no case with a number of cores and timed steps");
+    } else {
+        print!("{comparison}");
+    }
+    Ok(())
+}
+
+/// Prints the progress of the cases between two JSON snapshots
+fn diff(from: &str, to: &str, json: bool) -> anyhow::Result<()> {
+    let read = |path: &str| -> anyhow::Result<serde_json::Value> {
+        let content = fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+        // the output of `watch --json` is a snapshot per line
+        let value = serde_json::from_str(&content).or_else(|e| {
+            content
+                .lines()
+                .rfind(|line| !line.trim().is_empty())
+                .map_or(Err(e), serde_json::from_str)
+        });
+        value.with_context(|| format!("parsing {path}"))
+    };
+    let diff = SnapshotDiff::new(&read(from)?, &read(to)?)?;
+    if json {
+        println!("{}", serde_json::to_string(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+    Ok(())
+}
+
+/// Displays the merged status of the snapshots pushed to `listen`, with the `access`
+/// of the HTTP server, or written to `dir` at every refresh, until interrupted
+fn aggregate(
+    dir: Option<&str>,
+    listen: Option<&str>,
+    access: &HttpConfig,
+    interval: Duration,
+    stale_after: chrono::Duration,
+    json: bool,
+    once: bool,
+) -> anyhow::Result<()> {
+    let server = listen
+        .map(|addr| StatusServer::start(addr, Redactor::default(), Default::default(), access))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("starting the HTTP server: {e}"))?;
+    let mut stdout = stdout();
+    let mut screen = Screen::new();
+    loop {
+        let mut aggregate = server
+            .as_ref()
+            .map(StatusServer::aggregate)
+            .unwrap_or_default()
+            .stale_after(stale_after);
+        if let Some(dir) = dir {
+            aggregate
+                .read_dir(dir)
+                .with_context(|| format!("reading {dir}"))?;
+        }
+        if json {
+            println!("{}", aggregate.to_json());
+        } else if once {
+            print!("{aggregate}");
+        } else if aggregate.is_empty() {
+            screen.draw(&mut stdout, "waiting for the cluster snapshots")?;
+        } else {
+            screen.draw(&mut stdout, &aggregate.to_string())?;
+        }
+        if once {
+            return Ok(());
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Prints the time steps of a log as JSON lines
+fn parse(log: &str, pattern: Option<&str>) -> anyhow::Result<()> {
+    let parser = match pattern {
+        Some(pattern) => TimeStepPattern::new(pattern, 1, 2)
+            .with_context(|| format!("invalid time step pattern {pattern:?}"))?,
+        None => TimeStepPattern::default(),
+    };
+    let records = parse_log_with(log, &parser).with_context(|| format!("reading {log}"))?;
+    for record in records {
+        println!("{}", serde_json::to_string(&record)?);
+    }
+    Ok(())
+}
+
+/// Follows the log piped to stdin, printing the status of the case on a single line
+/// rewritten at each update if stdout is a terminal, on a new line otherwise
+fn pipe(name: &str, duration: usize, interval: u64, pattern: Option<&str>) -> anyhow::Result<()> {
+    use std::io::{IsTerminal, Write};
+
+    let source = PipeSource::stdin();
+    let mut case = Case::new(name, duration, "-").log_source(source.clone());
+    if let Some(pattern) = pattern {
+        let parser = TimeStepPattern::new(pattern, 1, 2)
+            .with_context(|| format!("invalid time step pattern {pattern:?}"))?;
+        case = case.parser(Arc::new(parser));
+    }
+    let live = stdout().is_terminal();
+    let interval = Duration::from_secs(interval.max(1));
+    let mut last = Instant::now();
+    loop {
+        // the last lines are read as soon as stdin is closed
+        while last.elapsed() < interval && !source.is_closed() {
+            thread::sleep(Duration::from_millis(100));
+        }
+        let closed = source.is_closed();
+        let secs = last.elapsed().as_secs_f64();
+        last = Instant::now();
+        let mut line = match case.update_over(secs).map(drop) {
+            Ok(()) if case.step().is_none() => format!("{name}: no time step yet"),
+            Ok(()) => case.to_string(),
+            Err(e) => format!("{name}: {e}"),
+        };
+        if live {
+            if let Some(width) = terminal_width() {
+                line = line.chars().take(width).collect();
+            }
+            print!("\r{line}\x1b[K");
+            stdout().flush()?;
+        } else {
+            println!("{line}");
+        }
+        if closed || case.is_complete() {
+            break;
+        }
+    }
+    if live {
+        println!();
+    }
+    Ok(())
+}
+
+/// Sets, removes or prints the note of a case, or prints the notes of all the cases
+fn note(
+    config: &Config,
+    name: Option<&str>,
+    text: Option<&str>,
+    clear: bool,
+) -> anyhow::Result<()> {
+    if config.notes.is_empty() {
+        anyhow::bail!("no notes file configured");
+    }
+    let mut notes = config.notes();
+    let Some(name) = name else {
+        for (case, note) in notes.iter() {
+            println!("{case:<20}{note}");
+        }
+        return Ok(());
+    };
+    if !config
+        .cases
+        .iter()
+        .chain(&config.planned)
+        .any(|case| case.name == name)
+    {
+        anyhow::bail!("no case {name} in the configuration");
+    }
+    match text {
+        Some(text) => notes.set(name, text, Local::now()),
+        None if clear => {
+            notes.remove(name);
+        }
+        None => {
+            if let Some(note) = notes.get(name) {
+                println!("{note}");
+            }
+            return Ok(());
+        }
+    }
+    notes
+        .write(&config.notes)
+        .with_context(|| format!("writing {}", config.notes))
+}
+
+/// Appends a case to the configuration file at `path`, created if needed
+fn add_case(file: &Path, case: &CaseConfig) -> anyhow::Result<()> {
+    let path = file.display();
+    let mut content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("reading {path}")),
+    };
+    let quoted = |value: &str| toml::Value::from(value).to_string();
+    content.push_str(&format!(
+        "\n[[cases]]\nname = {}\nduration = {}\nlog = {}\n",
+        quoted(&case.name),
+        case.duration,
+        quoted(&case.log)
+    ));
+    if let Some(group) = &case.group {
+        content.push_str(&format!("group = {}\n", quoted(group)));
+    }
+    if let Some(cores) = case.cores {
+        content.push_str(&format!("cores = {cores}\n"));
+    }
+    let config = Config::from_toml(&content)
+        .with_context(|| format!("{path} would be invalid with the new case"))?;
+    if config.cases.iter().filter(|c| c.name == case.name).count() > 1 {
+        anyhow::bail!("case {} is already in {path}", case.name);
+    }
+    fs::write(file, content).with_context(|| format!("writing {path}"))?;
+    println!(
+        "added {} to {path}, send SIGHUP to a running monitor to pick it up",
+        case.name
+    );
+    Ok(())
+}
+
+/// Applies a bulk operation through the HTTP server of a running monitor,
+/// authenticated with the bearer token if any
+fn ctl(
+    addr: &str,
+    token: Option<String>,
+    operation: BulkOperation,
+    filter: &CaseFilter,
+) -> anyhow::Result<()> {
+    let names = ControlClient::new(addr)
+        .token(token)
+        .apply(operation, filter)
+        .with_context(|| format!("{} through {addr}", operation.name()))?;
+    let done = match operation {
+        BulkOperation::Pause => "paused",
+        BulkOperation::Resume => "resumed",
+        BulkOperation::Ack => "acknowledged",
+        BulkOperation::Remove => "removed",
+    };
+    for name in &names {
+        println!("{done} {name}");
+    }
+    Ok(())
+}
+
+/// Loads the configuration and the plugins, and lists the cases without a log yet
+fn validate(global: &GlobalArgs) -> anyhow::Result<()> {
+    let config = config(global, false)?;
+    config.plugins.load().context("loading plugins")?;
+    for warning in config.warnings() {
+        println!("warning: {warning}");
+    }
+    for case in &config.cases {
+        if let Some(url) = &case.log_object {
+            #[cfg(feature = "s3")]
+            {
+                let mut source = cfd_status::S3Source::new(url)
+                    .with_context(|| format!("case {}", case.name))?;
+                match source.read_new() {
+                    Ok(_) if !source.exists() => println!("{}: no log yet at {url}", case.name),
+                    Ok(_) => (),
+                    Err(e) => println!("{}: failed to read {url}: {e}", case.name),
+                }
+                continue;
+            }
+            #[cfg(not(feature = "s3"))]
+            anyhow::bail!("case {}: {url} requires the `s3` feature", case.name);
+        }
+        let log = config.case(case).log_file();
+        let exists = if GlobSource::is_glob(&log) {
+            GlobSource::new(&log).exists()
+        } else {
+            Path::new(&log).exists()
         };
+        if case.log_command.is_none() && case.log_host.is_none() && !exists {
+            println!("{}: no log yet at {log}", case.name);
+        }
+    }
+    println!("configuration ok, cases: {}", config.cases.len());
+    Ok(())
+}
 
-        std::process::Command::new("clear").status().unwrap();
-        println!("{}", Local::now().format("%Y-%m-%d %H:%M:%S"));
+/// Checks the cases of the configuration, loaded unchecked, and prints the problems found,
+/// returns false if there are any
+fn preflight(global: &GlobalArgs, container: bool) -> anyhow::Result<bool> {
+    let preflight = Preflight::new(&load_config(global, container, false)?);
+    for problem in &preflight.problems {
+        println!("{problem}");
+    }
+    if preflight.is_ok() {
+        println!("preflight ok, cases: {}", preflight.cases);
+    } else {
         println!(
-            "{:20}{:>8}{:>10}{:>8}{:>20}",
-            "Case", "%", "P.[s]", "I.[s]", "ETA"
+            "{} problems found in {} cases",
+            preflight.problems.len(),
+            preflight.cases
+        );
+    }
+    Ok(preflight.is_ok())
+}
+
+/// Reconciles the configuration with the completion records, the run database
+/// and the case directories, removing the state of the cases no longer configured if `prune` is set
+fn reconcile(config: &Config, prune: bool) -> anyhow::Result<Reconciliation> {
+    let reconciliation = Reconciliation::new(config);
+    #[cfg(feature = "db")]
+    let database = config
+        .database
+        .as_ref()
+        .map(|path| RunDatabase::open(path).with_context(|| format!("opening {}", path.display())))
+        .transpose()?;
+    #[cfg(feature = "db")]
+    let reconciliation = match &database {
+        Some(database) => reconciliation
+            .with_database(config, database)
+            .context("reading the run database")?,
+        None => reconciliation,
+    };
+    if prune {
+        let removed = reconciliation
+            .prune_records(&config.records)
+            .with_context(|| format!("pruning {}", config.records))?;
+        tracing::info!(removed, "orphaned completion records pruned");
+        #[cfg(feature = "db")]
+        if let Some(database) = &database {
+            for case in reconciliation.orphans("database") {
+                database
+                    .remove_case(case)
+                    .with_context(|| format!("removing {case} from the run database"))?;
+            }
+        }
+    }
+    Ok(reconciliation)
+}
+
+/// Captures the latest log lines of a case at an anomaly, saved to the excerpts directory if any
+fn excerpt(case: &Case, anomaly: &str, config: Option<&ExcerptConfig>) -> LogExcerpt {
+    let excerpt = LogExcerpt::new(case, anomaly);
+    if let Some(config) = config {
+        match excerpt.save(&config.dir) {
+            Ok(path) => tracing::info!(case = case.name(), anomaly, ?path, "log excerpt saved"),
+            Err(e) => {
+                tracing::error!(case = case.name(), anomaly, error = %e, "failed to save the log excerpt")
+            }
+        }
+    }
+    excerpt
+}
+
+/// Prints the runtime, throughput and stalls of every case of the run database
+#[cfg(feature = "db")]
+fn report(config: &Config) -> anyhow::Result<()> {
+    let path = config
+        .database
+        .as_ref()
+        .context("no run database configured, set `database` in the configuration")?;
+    let (reports, calendar) = RunDatabase::open(path)
+        .and_then(|db| Ok((db.report()?, db.throughput_calendar()?)))
+        .with_context(|| format!("reading {}", path.display()))?;
+    println!("{}", CaseReport::header());
+    for report in reports {
+        println!("{report}");
+    }
+    println!("\nSimulated seconds per day\n{calendar}");
+    Ok(())
+}
+
+#[cfg(not(feature = "db"))]
+fn report(_config: &Config) -> anyhow::Result<()> {
+    anyhow::bail!("the report requires the `db` feature")
+}
+
+/// Prints the summary of the archived cases of a campaign as Markdown or HTML
+#[cfg(feature = "db")]
+fn summary(config: &Config, campaign: Option<&str>, html: bool) -> anyhow::Result<()> {
+    let path = config
+        .database
+        .as_ref()
+        .context("no run database configured, set `database` in the configuration")?;
+    let (cases, stalls) = RunDatabase::open(path)
+        .and_then(|db| Ok((db.archived()?, db.stall_incidents()?)))
+        .with_context(|| format!("reading {}", path.display()))?;
+    let summary = CampaignSummary::new(campaign, cases, stalls);
+    if summary.cases().is_empty() {
+        anyhow::bail!(
+            "no completed case{} in the run database",
+            campaign.map_or_else(String::new, |campaign| format!(" of campaign {campaign}"))
+        );
+    }
+    if html {
+        print!("{}", summary.to_html());
+    } else {
+        print!("{}", summary.to_markdown());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "db"))]
+fn summary(_config: &Config, _campaign: Option<&str>, _html: bool) -> anyhow::Result<()> {
+    anyhow::bail!("the summary requires the `db` feature")
+}
+
+fn main() -> anyhow::Result<ExitCode> {
+    let cli = Cli::from_env();
+    let container = cli.command.is_none() && cli.watch.container
+        || matches!(&cli.command, Some(Command::Watch(args)) if args.container);
+    init_logging(&cli.global, container)?;
+    let result = match &cli.command {
+        None | Some(Command::Watch(_)) => return watch(&cli.global, cli.watch_args()),
+        Some(Command::Snapshot {
+            query: Some(query), ..
+        }) => {
+            let result = query.evaluate(&poll_once(&config(&cli.global, false)?, true)?);
+            println!("{result}");
+            return Ok(if result.holds {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(CHECK_FAILED)
+            });
+        }
+        Some(Command::Snapshot {
+            json,
+            compact,
+            no_color,
+            ..
+        }) => snapshot(&config(&cli.global, false)?, *json, *compact, *no_color),
+        Some(Command::Check) => {
+            return Ok(if check(&config(&cli.global, false)?)? {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(CHECK_FAILED)
+            })
+        }
+        Some(Command::AddCase {
+            name,
+            duration,
+            log,
+            group,
+            cores,
+        }) => {
+            let mut case = CaseConfig::new(name, *duration, log);
+            case.group = group.clone();
+            case.cores = *cores;
+            add_case(
+                &config_file(&cli.global, false).unwrap_or(CONFIG.into()),
+                &case,
+            )
+        }
+        Some(Command::Note { name, text, clear }) => note(
+            &config(&cli.global, false)?,
+            name.as_deref(),
+            text.as_deref(),
+            *clear,
+        ),
+        Some(Command::Report) => report(&config(&cli.global, false)?),
+        Some(Command::Summary { campaign, html }) => {
+            summary(&config(&cli.global, false)?, campaign.as_deref(), *html)
+        }
+        Some(Command::Plan { json }) => plan(&config(&cli.global, false)?, *json),
+        Some(Command::FinishOrder { json }) => finish_order(&config(&cli.global, false)?, *json),
+        Some(Command::Compare { json }) => compare(&config(&cli.global, false)?, *json),
+        Some(Command::Align { group, json }) => {
+            align(&config(&cli.global, false)?, group.as_deref(), *json)
+        }
+        Some(Command::Diff { from, to, json }) => diff(from, to, *json),
+        Some(Command::Parse { log, pattern }) => parse(log, pattern.as_deref()),
+        Some(Command::Pipe {
+            duration,
+            name,
+            interval,
+            pattern,
+        }) => pipe(name, *duration, *interval, pattern.as_deref()),
+        Some(Command::Ctl {
+            operation,
+            selection,
+            addr,
+            token,
+        }) => ctl(
+            addr,
+            token
+                .clone()
+                .or_else(|| std::env::var("CFD_STATUS_HTTP_TOKEN").ok()),
+            *operation,
+            &selection.into(),
+        ),
+        Some(Command::Init { force }) => init(*force),
+        Some(Command::Preflight { container }) => {
+            return Ok(if preflight(&cli.global, *container)? {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(CHECK_FAILED)
+            })
+        }
+        Some(Command::Config {
+            command: ConfigCommand::Validate,
+        }) => validate(&cli.global),
+        Some(Command::Config {
+            command: ConfigCommand::Reconcile { prune },
+        }) => {
+            let reconciliation = reconcile(&config(&cli.global, false)?, *prune)?;
+            for discrepancy in &reconciliation.discrepancies {
+                match discrepancy {
+                    Discrepancy::Orphan { .. } if *prune => println!("{discrepancy} (pruned)"),
+                    _ => println!("{discrepancy}"),
+                }
+            }
+            if reconciliation.is_consistent() {
+                println!("configuration, state and case directories agree");
+            }
+            Ok(())
+        }
+        Some(Command::Replay {
+            path,
+            speed,
+            max_gap,
+            from,
+            to,
+            scroll,
+            post_mortem,
+        }) => {
+            let entries = read_session(path).with_context(|| format!("reading session {path}"))?;
+            let entries = entries.into_iter().filter(|entry| {
+                from.is_none_or(|from| entry.at() >= from) && to.is_none_or(|to| entry.at() <= to)
+            });
+            if *post_mortem {
+                print!("{}", PostMortem::new(&entries.collect::<Vec<_>>()));
+                Ok(())
+            } else {
+                replay(entries, *speed, *max_gap, *scroll)
+            }
+        }
+        Some(Command::Aggregate {
+            dir,
+            listen,
+            interval,
+            stale_after,
+            json,
+            once,
+        }) => aggregate(
+            dir.as_deref(),
+            listen.as_deref(),
+            &config(&cli.global, false)?.http,
+            Duration::from_secs(*interval),
+            chrono::Duration::seconds(*stale_after),
+            *json,
+            *once,
+        ),
+    };
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+/// Monitors the cases until they are all complete, the monitor is terminated or `--until`
+fn watch(global: &GlobalArgs, args: &WatchArgs) -> anyhow::Result<ExitCode> {
+    let mut config = config(global, args.container)?;
+    let mut config_file = config_file(global, args.container).map(WatchedFile::new);
+    let notes_file = |config: &Config| {
+        Some(PathBuf::from(&config.notes))
+            .filter(|path| !path.as_os_str().is_empty())
+            .map(WatchedFile::new)
+    };
+    let mut notes = notes_file(&config);
+    let plugins = config.plugins.load().context("loading plugins")?;
+    // the plugins are not reloaded with the configuration
+    let history = DowntimeHistory::read(&config.records);
+    tracing::debug!(runs = history.len(), "downtime history read");
+    let with_parser = |cases: Vec<Case>| with_history(with_parser(cases, &plugins), &history);
+    let cases = with_parser(config.cases());
+    let mut planned = config.planned();
+    let redactor = Redactor::from(&config.redaction);
+    let mut hooks = Hooks::new(config.hooks.clone());
+    if config.redaction.hooks {
+        hooks = hooks.redactor(redactor.clone());
+    }
+    let mut monitor = Monitor::new(cases, Duration::from_secs(config.interval))
+        .maintenance(config.maintenance.clone());
+    if let Some(disk) = config.disk.clone() {
+        monitor = monitor.disk(disk);
+    }
+    if args.watch {
+        match LogWatcher::new(
+            monitor.running().iter().map(|case| case.log_file()),
+            Duration::from_secs(config.debounce),
+        ) {
+            Ok(watcher) => monitor = monitor.watcher(watcher),
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to watch log files, falling back to polling")
+            }
+        }
+    }
+
+    // SIGHUP re-reads the configuration, SIGUSR1 pauses or resumes the updates
+    // and SIGTERM or SIGINT stops the monitor after the current poll
+    let reload = Arc::new(AtomicBool::new(false));
+    let pause = Arc::new(AtomicBool::new(false));
+    let terminate = Arc::new(AtomicBool::new(false));
+    let wake = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    let signals = [
+        (SIGHUP, &reload),
+        (SIGUSR1, &pause),
+        (SIGTERM, &terminate),
+        (SIGINT, &terminate),
+    ];
+    // elsewhere, the configuration is only reloaded once modified
+    #[cfg(not(unix))]
+    let signals = [(SIGTERM, &terminate), (SIGINT, &terminate)];
+    for (signal, flag) in signals {
+        signal_hook::flag::register(signal, flag.clone())?;
+        signal_hook::flag::register(signal, wake.clone())?;
+    }
+    let server = args
+        .http
+        .as_deref()
+        .map(|addr| StatusServer::start(addr, redactor.clone(), wake.clone(), &config.http))
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("starting the HTTP server: {e}"))?;
+    // the deadline wakes the monitor for a last update
+    let expired = Arc::new(AtomicBool::new(false));
+    if let Some(until) = args.until {
+        let (expired, wake) = (expired.clone(), wake.clone());
+        thread::spawn(move || {
+            if let Ok(wait) = (until - Local::now()).to_std() {
+                thread::sleep(wait);
+            }
+            expired.store(true, Ordering::Relaxed);
+            wake.store(true, Ordering::Relaxed);
+        });
+    }
+    monitor = monitor.wake_on(wake);
+    tracing::info!(
+        cases = monitor.running().len(),
+        watch = args.watch,
+        "monitor started"
+    );
+
+    let mut notifier = Notifier::new(config.notify.clone()).eta_format(config.eta.notifications());
+    let mut precursors = Precursors::new();
+
+    let mut recorder = args
+        .record_session
+        .as_deref()
+        .map(|path| SessionRecorder::create(path).with_context(|| format!("opening {path}")))
+        .transpose()?;
+    let mut transitions = (!config.events.is_empty())
+        .then(|| {
+            TransitionLog::create(&config.events, monitor.running().len())
+                .with_context(|| format!("opening {}", config.events))
+        })
+        .transpose()?;
+
+    let reconciliation = reconcile(&config, args.prune)?;
+    for discrepancy in &reconciliation.discrepancies {
+        tracing::warn!(%discrepancy, "configuration and state disagree");
+    }
+    if !args.prune
+        && reconciliation
+            .discrepancies
+            .iter()
+            .any(|discrepancy| matches!(discrepancy, Discrepancy::Orphan { .. }))
+    {
+        tracing::warn!(
+            "restart with --prune to remove the state of the cases no longer configured"
         );
-        print!("\r{:}", status.join("\n"));
-        stdout.flush().unwrap();
+    }
+    #[cfg(feature = "db")]
+    let database = config
+        .database
+        .as_ref()
+        .map(|path| RunDatabase::open(path).with_context(|| format!("opening {}", path.display())))
+        .transpose()?;
+    #[cfg(not(feature = "db"))]
+    if let Some(path) = &config.database {
+        tracing::warn!(?path, "the run database requires the `db` feature");
+    }
+    let mut heartbeat = config.heartbeat.clone().map(Heartbeat::from);
+    #[cfg(feature = "plot")]
+    let mut plots = config
+        .export
+        .plots
+        .as_ref()
+        .map(|dir| ProgressPlots::new(dir).format(config.export.plot_format));
+    #[cfg(not(feature = "plot"))]
+    if let Some(dir) = &config.export.plots {
+        tracing::warn!(?dir, "the progress charts require the `plot` feature");
+    }
+    let publisher = config.aggregation.clone().map(Publisher::from);
+    #[cfg(feature = "otel")]
+    let otel = OtlpExporter::from_env();
+    #[cfg(not(feature = "otel"))]
+    if let Ok(endpoint) = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        tracing::warn!(
+            endpoint,
+            "the OpenTelemetry export requires the `otel` feature"
+        );
+    }
+
+    let (tx, rx) = mpsc::channel();
+    // the events of the cases are shown in their detail view
+    let mut events = None;
+    // the cases acknowledged with a key press
+    let (acks_tx, acks) = mpsc::channel();
+    let renderer = if args.container {
+        // the status is only served over HTTP
+        thread::spawn(move || rx.into_iter().for_each(drop))
+    } else if args.json {
+        let redactor = redactor.clone();
+        thread::spawn(move || json(rx, redactor))
+    } else if args.scroll {
+        thread::spawn(move || scroll(rx))
+    } else {
+        let tabs = Tabs::new(&config);
+        let deltas = !args.no_deltas;
+        let notes = notes.as_ref().map(|notes| notes.path.clone());
+        let (events_tx, events_rx) = mpsc::channel();
+        events = Some(events_tx);
+        let width = config.table_width;
+        thread::spawn(move || render(rx, events_rx, tabs, deltas, notes, acks_tx, width))
+    };
+
+    let mut error = None;
+    let mut last = None;
+    // excerpts of the anomalies since the previous snapshot
+    let mut excerpts = vec![];
+    while let Some(event) = monitor.next() {
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record_event(&event);
+        }
+        if let Some(transitions) = transitions.as_mut() {
+            transitions.record(&event);
+        }
+        if let Some(events) = &events {
+            if let Some(entry) = SessionEntry::event(&event).filter(
+                |entry| !matches!(entry, SessionEntry::Event { event, .. } if event == "updated"),
+            ) {
+                let _ = events.send(entry);
+            }
+        }
+        let anomaly = match &event {
+            MonitorEvent::Stalled(case) => Some((case, "stalled")),
+            MonitorEvent::SolverDead(case) => Some((case, "dead")),
+            MonitorEvent::SlowInterval(case) => Some((case, "slow")),
+            MonitorEvent::Error { case, failures, .. } if *failures == config.max_failures => {
+                Some((case, "failed"))
+            }
+            _ => None,
+        };
+        if let Some((case, anomaly)) = anomaly {
+            excerpts.push(excerpt(case, anomaly, config.excerpts.as_ref()));
+        }
+        #[cfg(feature = "db")]
+        if let Some(database) = &database {
+            if let Err(e) = database.record(&event) {
+                tracing::error!(error = %e, "failed to store the event in the run database");
+            }
+        }
+        match event {
+            MonitorEvent::Updated(case) => {
+                tracing::trace!(case = case.name(), step = ?case.step(), "case updated")
+            }
+            MonitorEvent::Stalled(case) => hooks.trigger(CaseEvent::Stalled, &case, None),
+            MonitorEvent::SolverDead(case) => hooks.trigger(CaseEvent::SolverDead, &case, None),
+            // logged by the monitor and recorded in the session and the run database
+            MonitorEvent::SlowInterval(_)
+            | MonitorEvent::DtChanged(_)
+            | MonitorEvent::LogMoved(_) => (),
+            MonitorEvent::Completed(case) => {
+                if !config.records.is_empty() {
+                    if let Err(e) = CompletionRecord::from(&case).append(&config.records) {
+                        tracing::error!(case = case.name(), error = %e, "failed to write completion record");
+                    }
+                }
+                hooks.trigger(CaseEvent::Completed, &case, None);
+                precursors.seed(&case);
+            }
+            MonitorEvent::DiskAtRisk(case) => {
+                let detail = case.disk_usage().map(|usage| {
+                    format!(
+                        "case directory {:.1} GB, {:.1} GB free",
+                        usage.case_gb(),
+                        usage.free_gb()
+                    )
+                });
+                hooks.trigger(CaseEvent::DiskAtRisk, &case, detail.as_deref())
+            }
+            MonitorEvent::Error {
+                case,
+                error: e,
+                failures,
+            } => {
+                if failures >= config.max_failures {
+                    hooks.trigger(CaseEvent::Failed, &case, Some(&e.to_string()));
+                    error = Some(e);
+                }
+            }
+            MonitorEvent::Snapshot(snapshot) => {
+                let mut notifications =
+                    notifier.update(snapshot.running().chain(snapshot.completed()));
+                let snippet = config
+                    .excerpts
+                    .as_ref()
+                    .map_or(SNIPPET_LINES, |excerpts| excerpts.snippet);
+                notifications.extend(
+                    notifier
+                        .alerts_at(snapshot.alerts(), snapshot.taken())
+                        .into_iter()
+                        .map(|mut notification| {
+                            notification.excerpt = excerpts
+                                .iter()
+                                .rev()
+                                .find(|excerpt: &&LogExcerpt| excerpt.case == notification.case)
+                                .map(|excerpt| excerpt.snippet(snippet))
+                                .filter(|snippet| !snippet.is_empty());
+                            notification
+                        }),
+                );
+                excerpts.clear();
+                for notification in notifications {
+                    notifier.send(&notification);
+                }
+                let snapshot = snapshot
+                    .with_calendar(config.calendar.clone())
+                    .with_theme((!args.no_color).then_some(config.theme))
+                    .with_eta(config.eta)
+                    .with_columns(config.column_spec())
+                    .with_health(&config.health)
+                    .with_plan(planned.clone(), config.slots);
+                #[cfg(feature = "otel")]
+                if let Some(otel) = &otel {
+                    otel.record(&snapshot);
+                }
+                if let Some(heartbeat) = heartbeat.as_mut() {
+                    heartbeat.beat(&snapshot, monitor.restarts());
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record_snapshot(&snapshot);
+                }
+                config.export.export(&snapshot);
+                #[cfg(feature = "plot")]
+                if let Some(plots) = plots.as_mut() {
+                    if let Err(e) = plots.record(&snapshot).write() {
+                        tracing::error!(error = %e, "failed to render the progress charts");
+                    }
+                }
+                if let Some(publisher) = &publisher {
+                    publisher.publish(&snapshot, &redactor);
+                }
+                if let Some(server) = &server {
+                    server.update(&snapshot);
+                }
+                for sink in &plugins.sinks {
+                    sink.send(&snapshot, &redactor);
+                }
+                if args.until.is_some() {
+                    last = Some(snapshot.clone());
+                }
+                if error.is_some() || tx.send(snapshot).is_err() {
+                    break;
+                }
+                if terminate.load(Ordering::Relaxed) {
+                    tracing::info!("terminating");
+                    break;
+                }
+                if expired.load(Ordering::Relaxed) {
+                    tracing::info!(until = %args.until.unwrap_or_default(), "deadline reached");
+                    break;
+                }
+                if pause.swap(false, Ordering::Relaxed) {
+                    if monitor.is_paused() {
+                        tracing::info!("updates resumed");
+                        monitor.resume();
+                    } else {
+                        tracing::info!("updates paused");
+                        monitor.pause();
+                    }
+                }
+                // the configuration file is reloaded once modified, or on SIGHUP
+                let modified = config_file.as_mut().is_some_and(WatchedFile::changed);
+                if reload.swap(false, Ordering::Relaxed) || modified {
+                    // an invalid configuration is rejected, the previous one staying active
+                    match self::config(global, args.container) {
+                        Ok(reloaded) => {
+                            let diff = config.diff(&reloaded);
+                            tracing::info!(%diff, "configuration reloaded");
+                            config = reloaded;
+                            planned = config.planned();
+                            // the cases come with their notes, the notes file may have moved
+                            notes = notes_file(&config);
+                            // the cases kept keep their statistics
+                            monitor.set_cases(with_parser(config.cases()))
+                        }
+                        Err(e) => tracing::error!(
+                            "configuration rejected, the previous one stays active: {e:#}"
+                        ),
+                    }
+                }
+                // the notes written by the operators from another shell or the board
+                if notes.as_mut().is_some_and(WatchedFile::changed) {
+                    tracing::info!("notes reloaded");
+                    monitor.set_notes(&config.notes());
+                }
+                // with nothing else left to monitor, the production cases are awaited
+                for seeding in precursors.finished(monitor.is_done()) {
+                    let Some(e) = &seeding.error else {
+                        tracing::info!(
+                            case = seeding.precursor,
+                            production = seeding.production,
+                            "production case started"
+                        );
+                        // like the cases added over HTTP, superseded by the next reload
+                        if let Some(i) = planned
+                            .iter()
+                            .position(|case| case.name() == seeding.production)
+                        {
+                            with_parser(vec![planned.remove(i)])
+                                .into_iter()
+                                .for_each(|case| monitor.add_case(case));
+                        }
+                        continue;
+                    };
+                    tracing::error!(
+                        case = seeding.precursor,
+                        production = seeding.production,
+                        error = e,
+                        "precursor not seeding its production case"
+                    );
+                }
+                // cases added or removed over HTTP are superseded by the next reload
+                for control in server.iter().flat_map(StatusServer::controls) {
+                    match control {
+                        Control::Add(case) => with_parser(vec![config.case(&case)])
+                            .into_iter()
+                            .for_each(|case| monitor.add_case(case)),
+                        Control::Remove(name) => {
+                            monitor.remove_case(&name);
+                        }
+                        Control::Refresh => monitor.refresh_all(),
+                        Control::Pause(name) => {
+                            monitor.pause_case(&name);
+                        }
+                        Control::Resume(name) => {
+                            monitor.resume_case(&name);
+                        }
+                        Control::Acknowledge(name) => {
+                            monitor.acknowledge(&name);
+                        }
+                    }
+                }
+                for name in acks.try_iter() {
+                    if monitor.acknowledge(&name) {
+                        tracing::info!(case = name, "alerts acknowledged");
+                    }
+                }
+            }
+        }
+    }
+    drop(tx);
+    renderer.join().expect("renderer thread panicked");
+    hooks.wait();
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+    Ok(match last {
+        Some(snapshot) if !final_status(&snapshot, &config.maintenance, args) => {
+            ExitCode::from(CHECK_FAILED)
+        }
+        _ => ExitCode::SUCCESS,
+    })
+}
 
-        sleep(Duration::from_secs(UPDATE_TIME as u64));
+/// Prints the final status of a time-boxed monitoring, returns true if no case is stalled,
+/// failed or late, outside of a maintenance window
+fn final_status(
+    snapshot: &StatusSnapshot,
+    maintenance: &[MaintenanceWindow],
+    args: &WatchArgs,
+) -> bool {
+    let maintenance = active_window(maintenance, Local::now()).is_some();
+    let count = |f: fn(&Case) -> bool| snapshot.cases().filter(|case| f(case)).count();
+    let (stalled, failed) = if maintenance {
+        (0, 0)
+    } else {
+        (
+            count(|case| case.is_stalled() || case.is_solver_dead()),
+            count(|case| case.error().is_some()),
+        )
     };
-    Ok(error?)
+    let late = count(|case| case.is_late() && !case.is_complete());
+    let ok = stalled + failed + late == 0;
+    let status = if ok { "ok" } else { "fail" };
+    let cases = snapshot.cases().count();
+    if args.container {
+        tracing::info!(status, cases, stalled, failed, late, "final status");
+    } else if !args.json {
+        println!("{}", snapshot.compact());
+        println!("status={status} cases={cases} stalled={stalled} failed={failed} late={late}");
+    }
+    ok
 }
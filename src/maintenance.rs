@@ -0,0 +1,117 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Utc, Weekday};
+use serde::Deserialize;
+
+/// Recurring maintenance window
+///
+/// ```toml
+/// [[maintenance]]
+/// name = "EFS maintenance"
+/// weekday = "Sat"
+/// start = "02:00"
+/// end = "06:00"
+/// utc = true
+/// ```
+///
+/// The window repeats every day if `weekday` is omitted,
+/// and wraps past midnight if `end` is before `start`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "MaintenanceConfig")]
+pub struct MaintenanceWindow {
+    name: String,
+    weekday: Option<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+    utc: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct MaintenanceConfig {
+    name: String,
+    weekday: Option<String>,
+    start: String,
+    end: String,
+    #[serde(default)]
+    utc: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MaintenanceError {
+    #[error("invalid weekday {0:?}")]
+    Weekday(String),
+    #[error("invalid time {0:?}, expected HH:MM")]
+    Time(String),
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, MaintenanceError> {
+    NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| MaintenanceError::Time(time.to_string()))
+}
+
+impl TryFrom<MaintenanceConfig> for MaintenanceWindow {
+    type Error = MaintenanceError;
+
+    fn try_from(config: MaintenanceConfig) -> Result<Self, Self::Error> {
+        Ok(Self {
+            weekday: config
+                .weekday
+                .map(|day| day.parse().map_err(|_| MaintenanceError::Weekday(day)))
+                .transpose()?,
+            start: parse_time(&config.start)?,
+            end: parse_time(&config.end)?,
+            name: config.name,
+            utc: config.utc,
+        })
+    }
+}
+
+impl MaintenanceWindow {
+    /// Returns the window name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Returns true if `time` falls within the window
+    pub fn contains(&self, time: DateTime<Local>) -> bool {
+        if self.utc {
+            self.contains_in(time.with_timezone(&Utc))
+        } else {
+            self.contains_in(time)
+        }
+    }
+    fn contains_in<Tz: TimeZone>(&self, time: DateTime<Tz>) -> bool {
+        let clock = time.time();
+        let (day, within) = if self.start < self.end {
+            (time.weekday(), self.start <= clock && clock < self.end)
+        } else if clock >= self.start {
+            (time.weekday(), true)
+        } else {
+            // past midnight, the window opened the day before
+            ((time - Duration::days(1)).weekday(), clock < self.end)
+        };
+        within && self.weekday.is_none_or(|weekday| weekday == day)
+    }
+}
+
+impl Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (", self.name)?;
+        if let Some(weekday) = self.weekday {
+            write!(f, "{weekday} ")?;
+        }
+        write!(
+            f,
+            "{}-{}{})",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M"),
+            if self.utc { " UTC" } else { "" }
+        )
+    }
+}
+
+/// Returns the first of the maintenance windows that `time` falls within
+pub fn active_window(
+    windows: &[MaintenanceWindow],
+    time: DateTime<Local>,
+) -> Option<&MaintenanceWindow> {
+    windows.iter().find(|window| window.contains(time))
+}
@@ -0,0 +1,122 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::Deserialize;
+
+/// Pattern of a log line marking a milestone of the simulation
+///
+/// ```toml
+/// [[milestones]]
+/// name = "checkpoint"
+/// pattern = "Saving"
+/// repeat = true
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "MilestoneConfig")]
+pub struct MilestonePattern {
+    name: String,
+    pattern: Regex,
+    repeat: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct MilestoneConfig {
+    name: String,
+    pattern: String,
+    #[serde(default)]
+    repeat: bool,
+}
+
+impl TryFrom<MilestoneConfig> for MilestonePattern {
+    type Error = regex::Error;
+
+    fn try_from(config: MilestoneConfig) -> Result<Self, Self::Error> {
+        Self::new(config.name, &config.pattern, config.repeat)
+    }
+}
+
+impl MilestonePattern {
+    /// Creates a new milestone pattern, recorded at every match if `repeat` is true
+    /// or at the first match only otherwise
+    pub fn new<S: ToString>(name: S, pattern: &str, repeat: bool) -> Result<Self, regex::Error> {
+        Ok(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern)?,
+            repeat,
+        })
+    }
+    /// Returns the default milestones: mesh loaded, initialization complete,
+    /// first time step and checkpoints
+    pub fn defaults() -> Vec<Self> {
+        [
+            (
+                "mesh loaded",
+                r"(?i)\b(mesh|simulation)\b.*\b(loaded|read|imported)\b",
+                false,
+            ),
+            (
+                "initialized",
+                r"(?i)initiali[sz]ation (complete|done|finished)",
+                false,
+            ),
+            ("first time step", r"TimeStep\s+\d+:", false),
+            (
+                "checkpoint",
+                r"(?i)\b(saving|saved|checkpoint|autosave)\b",
+                true,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, pattern, repeat)| Self::new(name, pattern, repeat).unwrap())
+        .collect()
+    }
+    /// Returns the milestone name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Milestone found in a log
+#[derive(Debug, Clone)]
+pub struct Milestone {
+    pub name: String,
+    /// Time the milestone was observed, `None` if it was already in the log when monitoring started
+    pub time: Option<DateTime<Local>>,
+    pub line: String,
+}
+
+impl Display for Milestone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.time {
+            Some(time) => write!(f, "{} {}", self.name, time.format("%m-%d %H:%M")),
+            None => write!(f, "{} (before monitoring)", self.name),
+        }
+    }
+}
+
+/// Returns the new milestones in `lines`, given the milestones already found
+pub fn find_milestones(
+    patterns: &[MilestonePattern],
+    found: &[Milestone],
+    lines: &[String],
+    time: Option<DateTime<Local>>,
+) -> Vec<Milestone> {
+    let mut milestones: Vec<Milestone> = vec![];
+    for line in lines {
+        for pattern in patterns {
+            let seen = |milestones: &[Milestone]| milestones.iter().any(|m| m.name == pattern.name);
+            if !pattern.repeat && (seen(found) || seen(&milestones)) {
+                continue;
+            }
+            if pattern.pattern.is_match(line) {
+                milestones.push(Milestone {
+                    name: pattern.name.clone(),
+                    time,
+                    line: line.clone(),
+                });
+            }
+        }
+    }
+    milestones
+}
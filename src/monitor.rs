@@ -0,0 +1,645 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet, VecDeque},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    active_window, version_mismatches, Alerts, Case, CaseError, CaseUpdateStats, Clock, DiskConfig,
+    DiskUsage, LogWatcher, MaintenanceWindow, Notes, PollStats, StatusSnapshot, SystemClock,
+};
+
+/// Time between 2 checks of the wake-up flag while waiting for the next poll
+const WAKE_CHECK: Duration = Duration::from_secs(1);
+
+/// Maximum number of polls a failing case is skipped for, as a power of 2
+const MAX_BACKOFF: u32 = 4;
+
+/// Maximum number of consecutive panicking polls before the monitor gives up
+const MAX_RESTARTS: usize = 5;
+
+/// Consecutive update failures of a case
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: usize,
+    /// Poll the update is retried at
+    retry: usize,
+}
+
+/// Events emitted by the [Monitor]
+#[derive(Debug)]
+pub enum MonitorEvent {
+    /// A case has been updated
+    Updated(Case),
+    /// A case has not progressed for longer than its stall threshold
+    Stalled(Case),
+    /// A stalled case whose solver process is not running anymore
+    SolverDead(Case),
+    /// A case has reached its last time step and is not polled anymore
+    Completed(Case),
+    /// The latest time steps of a case are much slower than usual
+    SlowInterval(Case),
+    /// The simulated time per time step of a case with fixed time stepping has changed
+    DtChanged(Case),
+    /// The log file of a case was moved, re-linked or replaced
+    LogMoved(Case),
+    /// A case is at risk of running out of disk space
+    DiskAtRisk(Case),
+    /// A case failed to update `failures` consecutive times
+    Error {
+        case: Case,
+        error: CaseError,
+        failures: usize,
+    },
+    /// All the cases have been polled, the snapshots being numbered in sequence
+    Snapshot(StatusSnapshot),
+}
+
+/// CFD cases monitor
+///
+/// The monitor polls the cases every `interval`, or as soon as their log files are modified
+/// if a [LogWatcher] is set, and yields [MonitorEvent]s as an iterator.
+/// The iterator ends once all the cases are complete.
+pub struct Monitor {
+    running: Vec<Case>,
+    completed: Vec<Case>,
+    interval: Duration,
+    alerts: Alerts,
+    maintenance: Vec<MaintenanceWindow>,
+    stalled: HashSet<String>,
+    dead: HashSet<String>,
+    disk: Option<DiskConfig>,
+    polls: usize,
+    /// Sequence number of the latest snapshot event
+    sequence: u64,
+    watcher: Option<LogWatcher>,
+    /// Time of the last time step change of each case, only tracked with a watcher
+    advanced: HashMap<String, Instant>,
+    /// Polling time in seconds since the last time step change of the cases with
+    /// time steps longer than the interval, sampled over several polls
+    windows: HashMap<String, f64>,
+    /// Time of the previous poll of each case, the polling time being measured between polls
+    last_polls: HashMap<String, Instant>,
+    modified: Option<HashSet<PathBuf>>,
+    pending: VecDeque<MonitorEvent>,
+    polled: bool,
+    paused: bool,
+    wake: Option<Arc<AtomicBool>>,
+    failures: HashMap<String, Failures>,
+    /// Consecutive panicking polls
+    restarts: usize,
+    clock: Option<Arc<dyn Clock>>,
+    /// Cost of the latest poll
+    stats: PollStats,
+}
+
+impl Monitor {
+    /// Creates a new monitor polling the cases every `interval`
+    pub fn new(cases: Vec<Case>, interval: Duration) -> Self {
+        Self {
+            running: cases,
+            completed: vec![],
+            interval,
+            alerts: Alerts::new(),
+            maintenance: vec![],
+            stalled: HashSet::new(),
+            dead: HashSet::new(),
+            disk: None,
+            polls: 0,
+            sequence: 0,
+            watcher: None,
+            advanced: HashMap::new(),
+            windows: HashMap::new(),
+            last_polls: HashMap::new(),
+            modified: None,
+            pending: VecDeque::new(),
+            polled: false,
+            paused: false,
+            wake: None,
+            failures: HashMap::new(),
+            restarts: 0,
+            clock: None,
+            stats: PollStats::default(),
+        }
+    }
+    /// Sets the clock of the polls and of the cases, the system clock by default
+    ///
+    /// Without a watcher, the waits for the next poll are slept on the clock,
+    /// a mock clock being advanced by the interval instead
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        for case in self.running.iter_mut().chain(&mut self.completed) {
+            case.set_clock(clock.clone());
+        }
+        self.clock = Some(clock);
+        self
+    }
+    /// Returns the clock of the polls
+    fn time(&self) -> &dyn Clock {
+        self.clock.as_deref().unwrap_or(&SystemClock)
+    }
+    /// Sets the maintenance windows during which stall and I/O alerts are expected
+    pub fn maintenance(mut self, windows: Vec<MaintenanceWindow>) -> Self {
+        self.alerts = Alerts::new().maintenance(windows.clone());
+        self.maintenance = windows;
+        self
+    }
+    /// Tracks the disk usage of the case directories
+    pub fn disk(mut self, config: DiskConfig) -> Self {
+        self.disk = Some(config);
+        self
+    }
+    /// Updates the cases when their log files are modified,
+    /// all the cases are still polled after `interval` without any modification
+    pub fn watcher(mut self, watcher: LogWatcher) -> Self {
+        self.watcher = Some(watcher);
+        self
+    }
+    /// Stops waiting for the next poll as soon as `flag` is raised, the flag being lowered on wake-up
+    pub fn wake_on(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.wake = Some(flag);
+        self
+    }
+    /// Pauses the updates of the cases, snapshots are still emitted at every poll
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.refresh(HashSet::new());
+    }
+    /// Resumes the updates of the cases
+    pub fn resume(&mut self) {
+        self.paused = false;
+        // the pause is not accounted to the next time step
+        self.last_polls.clear();
+        self.refresh(HashSet::new());
+    }
+    /// Pauses the updates of a single case, returns false if there is no such running case
+    pub fn pause_case(&mut self, name: &str) -> bool {
+        let case = self.running.iter_mut().find(|case| case.name() == name);
+        case.map(|case| case.set_paused(true)).is_some()
+    }
+    /// Resumes the updates of a single case, returns false if there is no such running case
+    pub fn resume_case(&mut self, name: &str) -> bool {
+        let Some(case) = self.running.iter_mut().find(|case| case.name() == name) else {
+            return false;
+        };
+        case.set_paused(false);
+        // the pause is not accounted to the next time step
+        self.advanced.remove(name);
+        self.windows.remove(name);
+        self.last_polls.remove(name);
+        true
+    }
+    /// Sets the notes of the operators on the cases, removing the notes of the cases without one
+    pub fn set_notes(&mut self, notes: &Notes) {
+        for case in self.running.iter_mut().chain(&mut self.completed) {
+            case.set_note(notes.get(case.name()).cloned());
+        }
+    }
+    /// Acknowledges the alerts of a case until its state changes, returns false if it has none
+    pub fn acknowledge(&mut self, name: &str) -> bool {
+        self.alerts.acknowledge(name)
+    }
+    /// Polls the cases with the given logs without waiting
+    fn refresh(&mut self, logs: HashSet<PathBuf>) {
+        self.modified = Some(logs);
+        self.polled = false;
+    }
+    /// Returns true if the updates of the cases are paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /// Replaces the monitored cases, keeping the state of the cases whose definition is unchanged
+    ///
+    /// The cases missing from `cases` are removed, whether running or completed,
+    /// and the new cases are polled without waiting
+    pub fn set_cases(&mut self, cases: Vec<Case>) {
+        let mut added = HashSet::new();
+        let mut previous: Vec<Case> = self
+            .running
+            .drain(..)
+            .chain(self.completed.drain(..))
+            .collect();
+        for mut case in cases {
+            if let Some(clock) = &self.clock {
+                case.set_clock(clock.clone());
+            }
+            let case = match previous.iter().position(|p| p.same_definition(&case)) {
+                Some(i) => {
+                    let mut kept = previous.swap_remove(i);
+                    kept.reconfigure(case);
+                    kept
+                }
+                None => {
+                    if let Some(watcher) = self.watcher.as_mut() {
+                        // an unwatched log is still polled after `interval`
+                        let _ = watcher.watch(case.log_file());
+                    }
+                    added.insert(PathBuf::from(case.log_file()));
+                    case
+                }
+            };
+            if case.is_complete() {
+                self.completed.push(case);
+            } else {
+                self.running.push(case);
+            }
+        }
+        let names: HashSet<String> = self
+            .running
+            .iter()
+            .chain(&self.completed)
+            .map(|case| case.name().to_string())
+            .collect();
+        self.stalled.retain(|name| names.contains(name));
+        self.dead.retain(|name| names.contains(name));
+        self.failures.retain(|name, _| names.contains(name));
+        self.advanced.retain(|name, _| names.contains(name));
+        self.windows.retain(|name, _| names.contains(name));
+        self.last_polls.retain(|name, _| names.contains(name));
+        tracing::info!(cases = names.len(), added = added.len(), "cases replaced");
+        self.refresh(added);
+    }
+    /// Adds a case, replacing the case of the same name, and polls it without waiting
+    pub fn add_case(&mut self, case: Case) {
+        let mut cases: Vec<Case> = self
+            .running
+            .iter()
+            .chain(&self.completed)
+            .filter(|c| c.name() != case.name())
+            .cloned()
+            .collect();
+        cases.push(case);
+        self.set_cases(cases);
+    }
+    /// Removes the case of the given name, returns false if there is no such case
+    pub fn remove_case(&mut self, name: &str) -> bool {
+        let cases: Vec<Case> = self
+            .running
+            .iter()
+            .chain(&self.completed)
+            .filter(|case| case.name() != name)
+            .cloned()
+            .collect();
+        let removed = cases.len() < self.running.len() + self.completed.len();
+        if removed {
+            self.set_cases(cases);
+        }
+        removed
+    }
+    /// Polls all the cases without waiting for the next update
+    pub fn refresh_all(&mut self) {
+        self.modified = None;
+        self.polled = false;
+    }
+    /// Returns the cases still running
+    pub fn running(&self) -> &[Case] {
+        &self.running
+    }
+    /// Returns the completed cases
+    pub fn completed(&self) -> &[Case] {
+        &self.completed
+    }
+    /// Returns true once all the cases are complete, with their post-processing stages
+    pub fn is_done(&self) -> bool {
+        self.running.is_empty() && self.completed.iter().all(Case::is_pipeline_done)
+    }
+    /// Returns a snapshot of the cases
+    pub fn snapshot(&self) -> StatusSnapshot {
+        StatusSnapshot::new(
+            &self.running,
+            &self.completed,
+            self.interval.as_secs() as usize,
+        )
+        .taken_at(self.time().now())
+        .with_alerts(self.alerts.sorted())
+        .with_paused(self.paused)
+        .with_poll_stats(self.stats.clone())
+    }
+    /// Returns the cost of the latest poll
+    pub fn poll_stats(&self) -> &PollStats {
+        &self.stats
+    }
+    /// Returns the number of consecutive panicking polls
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+    /// Polls the cases once and returns the resulting events
+    ///
+    /// Only the cases with logs modified since the last poll are updated if a watcher is set.
+    /// A failing case is retried after a number of polls doubling with each consecutive failure,
+    /// a panicking update being a failure.
+    /// A panicking poll is restarted at the next poll, the panic resuming after 5 in a row
+    #[tracing::instrument(level = "debug", skip(self), fields(poll = self.polls))]
+    pub fn poll(&mut self) -> Vec<MonitorEvent> {
+        match panic::catch_unwind(AssertUnwindSafe(|| self.poll_cases())) {
+            Ok(events) => {
+                self.restarts = 0;
+                events
+            }
+            Err(panic) => {
+                self.restarts += 1;
+                if self.restarts > MAX_RESTARTS {
+                    panic::resume_unwind(panic);
+                }
+                tracing::error!(
+                    panic = panic_message(&*panic),
+                    restarts = self.restarts,
+                    "poll panicked, restarted at the next poll"
+                );
+                self.polled = true;
+                self.polls += 1;
+                vec![]
+            }
+        }
+    }
+    /// Polls the cases once, see [Monitor::poll]
+    fn poll_cases(&mut self) -> Vec<MonitorEvent> {
+        let mut events = vec![];
+        // the cost of the monitor is measured in real time, whatever its clock
+        let started = Instant::now();
+        let mut stats = vec![];
+        let modified = self.modified.take();
+        let maintenance = active_window(&self.maintenance, self.time().now()).is_some();
+        let disk = self
+            .disk
+            .as_ref()
+            .filter(|disk| self.polls.is_multiple_of(disk.every.max(1)));
+        for case in self.running.iter_mut() {
+            if self.paused {
+                break;
+            }
+            if case.is_paused() {
+                continue;
+            }
+            if modified
+                .as_ref()
+                .is_some_and(|modified| !modified.contains(Path::new(&case.log_file())))
+            {
+                continue;
+            }
+            let failures = self.failures.get(case.name()).copied();
+            if failures.is_some_and(|failures| self.polls < failures.retry) {
+                continue;
+            }
+            let (slow, dt_changes, relocations) = (
+                case.slow_interval_count(),
+                case.dt_change_count(),
+                case.log_relocations().len(),
+            );
+            let span = tracing::debug_span!(
+                "update",
+                case = case.name(),
+                step = tracing::field::Empty,
+                error = tracing::field::Empty
+            )
+            .entered();
+            let (updating, bytes) = (Instant::now(), case.bytes_read());
+            // the offline gaps are told from the polling interval, not from the sampling window
+            case.set_update_interval(self.interval.as_secs_f64());
+            let update = if self.watcher.is_some() {
+                // only the clock is borrowed, the cases being borrowed mutably
+                let now = self.clock.as_deref().unwrap_or(&SystemClock).instant();
+                let last = self.advanced.entry(case.name().to_string()).or_insert(now);
+                let step = case.step();
+                let update = update_case(case, now.duration_since(*last).as_secs_f64());
+                if case.step() != step {
+                    *last = now;
+                }
+                update
+            } else {
+                // the time since the previous poll is measured, so the time steps of the polls
+                // skipped after a failure and of the polls delayed by slow reads are accounted
+                // for whole, the interval being assumed at the first poll
+                let now = self.clock.as_deref().unwrap_or(&SystemClock).instant();
+                let secs = self
+                    .last_polls
+                    .insert(case.name().to_string(), now)
+                    .map_or(self.interval.as_secs_f64(), |last| {
+                        now.duration_since(last).as_secs_f64()
+                    });
+                // a poll without a new time step extends the sampling window to the next one,
+                // so time steps longer than the interval are not counted as one interval
+                let window = self.windows.entry(case.name().to_string()).or_default();
+                *window += secs;
+                let step = case.step();
+                let update = update_case(case, *window);
+                if case.step() != step {
+                    self.windows.remove(case.name());
+                }
+                let coarse = case.elapsed_per_step().mean() > self.interval.as_secs_f64();
+                if coarse && !case.is_sampled_coarsely() {
+                    tracing::warn!(
+                        case = case.name(),
+                        step_time = case.elapsed_per_step().mean(),
+                        interval = self.interval.as_secs(),
+                        "time steps longer than the update interval, consider raising it"
+                    );
+                }
+                case.set_sampled_coarsely(coarse);
+                update
+            };
+            stats.push(CaseUpdateStats {
+                case: case.name().to_string(),
+                secs: updating.elapsed().as_secs_f64(),
+                bytes: case.bytes_read() - bytes,
+                parse_failure: update.as_ref().is_err_and(CaseError::is_parse_failure),
+            });
+            match &update {
+                Ok(_) => span.record("step", case.step()),
+                Err(error) => span.record("error", tracing::field::display(error)),
+            };
+            match update {
+                // I/O errors are expected during maintenance, the case is polled again next time
+                Err(error) if maintenance => {
+                    tracing::debug!(case = case.name(), %error, "update failed during maintenance");
+                    continue;
+                }
+                Err(error) => {
+                    let count = failures.map_or(1, |failures| failures.count + 1);
+                    let backoff = 1 << (count as u32 - 1).min(MAX_BACKOFF);
+                    self.failures.insert(
+                        case.name().to_string(),
+                        Failures {
+                            count,
+                            retry: self.polls + backoff,
+                        },
+                    );
+                    tracing::error!(case = case.name(), %error, failures = count, "update failed");
+                    case.set_error(Some(format!(
+                        "{error} ({count}x, retry in {backoff} polls)"
+                    )));
+                    events.push(MonitorEvent::Error {
+                        case: case.clone(),
+                        error,
+                        failures: count,
+                    });
+                    continue;
+                }
+                Ok(_) => {
+                    if self.failures.remove(case.name()).is_some() {
+                        tracing::info!(case = case.name(), "update recovered");
+                    }
+                    case.set_error(None);
+                    if case.state() == "running" {
+                        case.sample_resources();
+                    }
+                    events.push(MonitorEvent::Updated(case.clone()));
+                    if case.slow_interval_count() > slow && !maintenance {
+                        if let Some(interval) = case.slow_intervals().last() {
+                            tracing::warn!(case = case.name(), %interval, "slow time steps");
+                        }
+                        events.push(MonitorEvent::SlowInterval(case.clone()));
+                    }
+                    if case.dt_change_count() > dt_changes {
+                        if let Some(change) = case.dt_changes().last() {
+                            tracing::info!(case = case.name(), %change, "time step changed");
+                        }
+                        events.push(MonitorEvent::DtChanged(case.clone()));
+                    }
+                    if case.log_relocations().len() > relocations {
+                        events.push(MonitorEvent::LogMoved(case.clone()));
+                    }
+                }
+            }
+            if let Some(disk) = disk {
+                // a failed measurement only keeps the previous usage
+                if let Ok(usage) = DiskUsage::measure(case.case_dir()) {
+                    let was_at_risk = case.is_disk_at_risk();
+                    case.set_disk_usage(usage, disk.is_at_risk(&usage));
+                    if case.is_disk_at_risk() && !was_at_risk {
+                        tracing::warn!(
+                            case = case.name(),
+                            case_gb = usage.case_gb(),
+                            free_gb = usage.free_gb(),
+                            "disk at risk"
+                        );
+                        events.push(MonitorEvent::DiskAtRisk(case.clone()));
+                    }
+                }
+            }
+            if case.is_stalled() {
+                case.check_liveness();
+                if case.is_solver_dead() {
+                    if self.dead.insert(case.name().to_string()) && !maintenance {
+                        tracing::error!(case = case.name(), step = ?case.step(), "solver dead");
+                        events.push(MonitorEvent::SolverDead(case.clone()));
+                    }
+                } else if self.stalled.insert(case.name().to_string()) && !maintenance {
+                    tracing::warn!(case = case.name(), step = ?case.step(), "case stalled");
+                    events.push(MonitorEvent::Stalled(case.clone()));
+                }
+            } else {
+                self.stalled.remove(case.name());
+                self.dead.remove(case.name());
+            }
+        }
+        // finished cases are retired and not polled anymore
+        let (done, running): (Vec<_>, Vec<_>) = self.running.drain(..).partition(Case::is_complete);
+        self.running = running;
+        events.extend(done.iter().cloned().map(MonitorEvent::Completed));
+        self.completed.extend(done);
+        for case in self.completed.iter_mut() {
+            for stage in case.check_stages() {
+                tracing::info!(case = case.name(), %stage, "post-processing stage");
+            }
+        }
+
+        let mismatches = version_mismatches(self.running.iter().chain(&self.completed));
+        for case in self.running.iter_mut() {
+            let mismatch = mismatches.contains_key(&case.group_name().map(str::to_string));
+            if mismatch && !case.has_version_mismatch() {
+                tracing::warn!(
+                    case = case.name(),
+                    version = case.solver_version(),
+                    "solver version differs within the group"
+                );
+            }
+            case.set_version_mismatch(mismatch);
+        }
+        let now = self.time().now();
+        self.alerts.update_at(&self.running, now);
+        self.stats = PollStats {
+            secs: started.elapsed().as_secs_f64(),
+            cases: stats,
+        };
+        self.sequence += 1;
+        events.push(MonitorEvent::Snapshot(
+            self.snapshot().with_sequence(self.sequence),
+        ));
+        self.polled = true;
+        self.polls += 1;
+        events
+    }
+    /// Waits for the next poll
+    ///
+    /// The wait is cut short if the wake-up flag is raised, without any case being updated
+    fn wait(&mut self) {
+        let deadline = self.time().instant() + self.interval;
+        let step = if self.wake.is_some() {
+            WAKE_CHECK
+        } else {
+            self.interval
+        };
+        self.modified = loop {
+            let timeout = deadline
+                .saturating_duration_since(self.time().instant())
+                .min(step);
+            let modified = match &self.watcher {
+                Some(watcher) => watcher.wait(timeout),
+                None => {
+                    self.time().sleep(timeout);
+                    None
+                }
+            };
+            let woken = self
+                .wake
+                .as_ref()
+                .is_some_and(|flag| flag.swap(false, Ordering::Relaxed));
+            if woken {
+                // no case is updated early, only a snapshot is emitted
+                break Some(HashSet::new());
+            }
+            if modified.is_some() || self.time().instant() >= deadline {
+                break modified;
+            }
+        };
+    }
+}
+
+impl Iterator for Monitor {
+    type Item = MonitorEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if self.polled {
+                if self.is_done() {
+                    return None;
+                }
+                self.wait();
+            }
+            let events = self.poll();
+            self.pending.extend(events);
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Updates a case over a sampling window, a panic of the update being an error
+fn update_case(case: &mut Case, secs: f64) -> Result<(), CaseError> {
+    panic::catch_unwind(AssertUnwindSafe(|| case.update_over(secs).map(|_| ())))
+        .unwrap_or_else(|panic| Err(CaseError::Panic(panic_message(&*panic))))
+}
+
+/// Returns the message of a panic payload
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
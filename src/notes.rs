@@ -0,0 +1,83 @@
+use std::{collections::BTreeMap, fmt::Display, fs, io, path::Path};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::export::write_atomically;
+
+/// Note of an operator on a case, e.g. "restarted from ckpt 42000 on 3/14"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Note {
+    pub text: String,
+    /// Time the note was written at
+    pub at: DateTime<Local>,
+}
+
+impl Display for Note {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.text, self.at.format("%Y-%m-%d %H:%M"))
+    }
+}
+
+/// Notes of the operators on the cases, kept in a TOML file so the context
+/// carries over the handovers between shifts and the restarts of the monitor
+///
+/// ```toml
+/// [zen30az045_OS2]
+/// text = "waiting on license"
+/// at = "2024-03-14T09:30:00+01:00"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Notes {
+    notes: BTreeMap<String, Note>,
+}
+
+impl Notes {
+    /// Reads the notes of a file, none if there is no file
+    pub fn read<P: AsRef<Path>>(path: P) -> Self {
+        let Ok(notes) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        toml::from_str(&notes).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "invalid notes file");
+            Self::default()
+        })
+    }
+    /// Writes the notes to a file, replacing it at once
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let notes = toml::to_string(self).map_err(io::Error::other)?;
+        write_atomically(path.as_ref(), &notes)
+    }
+    /// Sets the note of a case, written at `at`, an empty text removing it
+    pub fn set<S: ToString>(&mut self, case: &str, text: S, at: DateTime<Local>) {
+        let text = text.to_string();
+        if text.trim().is_empty() {
+            self.notes.remove(case);
+        } else {
+            self.notes.insert(
+                case.to_string(),
+                Note {
+                    text: text.trim().to_string(),
+                    at,
+                },
+            );
+        }
+    }
+    /// Removes the note of a case and returns it
+    pub fn remove(&mut self, case: &str) -> Option<Note> {
+        self.notes.remove(case)
+    }
+    /// Returns the note of a case
+    pub fn get(&self, case: &str) -> Option<&Note> {
+        self.notes.get(case)
+    }
+    /// Returns the cases with their note, in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Note)> {
+        self.notes.iter().map(|(case, note)| (case.as_str(), note))
+    }
+    /// Returns true if no case has a note
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
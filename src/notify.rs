@@ -0,0 +1,297 @@
+use std::{collections::HashMap, io::Write};
+
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+
+use crate::{Alert, AlertKind, Case, EtaStyle, Severity};
+
+/// Default time in seconds an alert must stay clear before being notified again once raised
+const CLEAR_AFTER: u64 = 300;
+
+/// Progress, ETA and alert notifications to the operator at the console
+///
+/// Progress notifications are informational, ETA slips are warnings
+/// and alerts have their configured severity
+///
+/// ```toml
+/// [notify]
+/// progress = [25, 50, 75, 100]
+/// eta_slip_hours = 2.0
+/// alerts = "warning"
+/// remind_after = 3600
+/// clear_after = 300
+/// bell = true
+/// bell_severity = "warning"
+/// desktop = true
+/// desktop_severity = "critical"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifyConfig {
+    /// Completion percentages a notification is sent at
+    #[serde(default = "default_progress")]
+    pub progress: Vec<i64>,
+    /// Delay in hours of the ETA a notification is sent at
+    pub eta_slip_hours: Option<f64>,
+    /// Lowest severity of the alerts notified when raised, none by default
+    pub alerts: Option<Severity>,
+    /// Time in seconds after which an alert still active, neither expected nor acknowledged,
+    /// is notified again, never by default
+    pub remind_after: Option<u64>,
+    /// Time in seconds an alert must stay clear before being notified again once raised,
+    /// so a flapping alert is notified once
+    #[serde(default = "default_clear_after")]
+    pub clear_after: u64,
+    /// Rings the terminal bell
+    #[serde(default = "default_bell")]
+    pub bell: bool,
+    /// Lowest severity of the notifications ringing the bell
+    #[serde(default = "default_severity")]
+    pub bell_severity: Severity,
+    /// Sends a desktop notification, requires the `desktop` feature
+    #[serde(default)]
+    pub desktop: bool,
+    /// Lowest severity of the notifications sent to the desktop
+    #[serde(default = "default_severity")]
+    pub desktop_severity: Severity,
+}
+
+fn default_progress() -> Vec<i64> {
+    vec![25, 50, 75, 100]
+}
+
+fn default_clear_after() -> u64 {
+    CLEAR_AFTER
+}
+
+fn default_bell() -> bool {
+    true
+}
+
+fn default_severity() -> Severity {
+    Severity::Info
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            progress: default_progress(),
+            eta_slip_hours: None,
+            alerts: None,
+            remind_after: None,
+            clear_after: CLEAR_AFTER,
+            bell: default_bell(),
+            bell_severity: default_severity(),
+            desktop: false,
+            desktop_severity: default_severity(),
+        }
+    }
+}
+
+/// Notification of a case transition
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub case: String,
+    pub message: String,
+    pub severity: Severity,
+    /// Last log lines of the case when the anomaly notified was detected
+    pub excerpt: Option<String>,
+}
+
+/// Progress of a case as last notified
+#[derive(Debug, Clone, Copy)]
+struct Notified {
+    percent: i64,
+    eta: Option<DateTime<Local>>,
+}
+
+/// Alert as last notified
+#[derive(Debug, Clone, Copy)]
+struct Alerted {
+    /// Time of the latest notification, or of the latest update the alert was silenced at
+    notified: DateTime<Local>,
+    /// Time the alert cleared at, if not active anymore
+    cleared: Option<DateTime<Local>>,
+}
+
+/// Tracker of the progress milestones, ETA slips and alerts of the cases
+#[derive(Debug, Default)]
+pub struct Notifier {
+    config: NotifyConfig,
+    notified: HashMap<String, Notified>,
+    alerted: HashMap<(String, AlertKind), Alerted>,
+    eta_format: EtaStyle,
+}
+
+impl Notifier {
+    /// Creates a new notifier
+    pub fn new(config: NotifyConfig) -> Self {
+        #[cfg(not(feature = "desktop"))]
+        if config.desktop {
+            tracing::warn!("desktop notifications require the `desktop` feature");
+        }
+        Self {
+            config,
+            notified: HashMap::new(),
+            alerted: HashMap::new(),
+            eta_format: EtaStyle::default(),
+        }
+    }
+    /// Sets the presentation of the ETAs in the notifications
+    pub fn eta_format(mut self, format: EtaStyle) -> Self {
+        self.eta_format = format;
+        self
+    }
+    /// Returns the notifications of the progress thresholds crossed and ETA slips since the last update
+    ///
+    /// The first update only records the state of the cases
+    pub fn update<'a>(&mut self, cases: impl IntoIterator<Item = &'a Case>) -> Vec<Notification> {
+        let mut notifications = vec![];
+        for case in cases.into_iter().filter(|case| case.step().is_some()) {
+            let percent = case.percent_complete();
+            let eta =
+                (!case.is_complete()).then(|| Local::now() + Duration::seconds(case.eta_secs()));
+            let Some(notified) = self.notified.get_mut(case.name()) else {
+                self.notified
+                    .insert(case.name().to_string(), Notified { percent, eta });
+                continue;
+            };
+            if let Some(threshold) = self
+                .config
+                .progress
+                .iter()
+                .filter(|&&threshold| notified.percent < threshold && percent >= threshold)
+                .max()
+            {
+                notifications.push(Notification {
+                    case: case.name().to_string(),
+                    message: format!("{}: {threshold}% complete", case.name()),
+                    severity: Severity::Info,
+                    excerpt: None,
+                });
+            }
+            notified.percent = percent;
+            match (self.config.eta_slip_hours, notified.eta, eta) {
+                (Some(hours), Some(baseline), Some(eta))
+                    if (eta - baseline).num_seconds() as f64 > hours * 3600. =>
+                {
+                    notifications.push(Notification {
+                        case: case.name().to_string(),
+                        message: format!(
+                            "{}: ETA slipped by {} to {}",
+                            case.name(),
+                            crate::format_hours_minutes((eta - baseline).num_seconds()),
+                            self.eta_format.format(eta, Local::now())
+                        ),
+                        severity: Severity::Warning,
+                        excerpt: None,
+                    });
+                    notified.eta = Some(eta);
+                }
+                (_, None, eta) => notified.eta = eta,
+                _ => (),
+            }
+        }
+        notifications
+    }
+    /// Returns the notifications of the alerts at `now`, at least as severe as configured:
+    /// the alerts raised since the last update, unless cleared for less than `clear_after`,
+    /// and the reminders of the ones still active after `remind_after`
+    ///
+    /// The expected and acknowledged alerts are silenced, their reminders starting over
+    /// once they are not anymore
+    pub fn alerts_at(&mut self, alerts: &[Alert], now: DateTime<Local>) -> Vec<Notification> {
+        let Some(lowest) = self.config.alerts else {
+            return vec![];
+        };
+        let mut notifications = vec![];
+        for alert in alerts {
+            let key = (alert.case.clone(), alert.kind);
+            let silenced = alert.expected.is_some() || alert.acknowledged;
+            let notify = !silenced && alert.severity() >= lowest;
+            let message = match self.alerted.get_mut(&key) {
+                Some(alerted) => {
+                    alerted.cleared = None;
+                    let remind = self
+                        .config
+                        .remind_after
+                        .is_some_and(|secs| (now - alerted.notified).num_seconds() >= secs as i64);
+                    if silenced {
+                        alerted.notified = now;
+                    }
+                    if !(notify && remind) {
+                        continue;
+                    }
+                    alerted.notified = now;
+                    format!(
+                        "{}: {} ({}) still active after {}",
+                        alert.case,
+                        alert.kind,
+                        alert.severity(),
+                        crate::format_hours_minutes((now - alert.since).num_seconds())
+                    )
+                }
+                None => {
+                    self.alerted.insert(
+                        key,
+                        Alerted {
+                            notified: now,
+                            cleared: None,
+                        },
+                    );
+                    if !notify {
+                        continue;
+                    }
+                    format!("{}: {} ({})", alert.case, alert.kind, alert.severity())
+                }
+            };
+            notifications.push(Notification {
+                case: alert.case.clone(),
+                message,
+                severity: alert.severity(),
+                excerpt: None,
+            });
+        }
+        let clear_after = self.config.clear_after as i64;
+        self.alerted.retain(|(case, kind), alerted| {
+            if alerts
+                .iter()
+                .any(|alert| &alert.case == case && alert.kind == *kind)
+            {
+                return true;
+            }
+            let cleared = *alerted.cleared.get_or_insert(now);
+            (now - cleared).num_seconds() < clear_after
+        });
+        notifications
+    }
+    /// Logs the notification at the level of its severity,
+    /// rings the terminal bell and sends a desktop notification as configured
+    pub fn send(&self, notification: &Notification) {
+        let (case, message) = (&notification.case, &notification.message);
+        let excerpt = notification.excerpt.as_deref();
+        match notification.severity {
+            Severity::Info => tracing::info!(case, excerpt, "{message}"),
+            Severity::Warning => tracing::warn!(case, excerpt, "{message}"),
+            Severity::Critical => tracing::error!(case, excerpt, "{message}"),
+        }
+        if self.config.bell && notification.severity >= self.config.bell_severity {
+            // on stderr, not to interfere with the status written to stdout
+            let mut stderr = std::io::stderr();
+            let _ = stderr.write_all(b"\x07").and_then(|_| stderr.flush());
+        }
+        #[cfg(feature = "desktop")]
+        if self.config.desktop && notification.severity >= self.config.desktop_severity {
+            let body = match excerpt {
+                Some(excerpt) => format!("{message}\n{excerpt}"),
+                None => message.clone(),
+            };
+            if let Err(e) = notify_rust::Notification::new()
+                .summary("cfd-status")
+                .body(&body)
+                .show()
+            {
+                tracing::warn!(error = %e, "failed to send desktop notification");
+            }
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use std::{
+    env,
+    fmt::Debug,
+    hash::{BuildHasher, RandomState},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+use crate::{Case, StatusSnapshot};
+
+/// Service name of the exported telemetry unless `OTEL_SERVICE_NAME` is set
+const SERVICE_NAME: &str = "cfd-status";
+/// Time between 2 exports of the finished spans
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Telemetry sent to the collector
+enum Export {
+    Span(Value),
+    Metrics(Value),
+}
+
+/// Exporter of the case progress metrics and of the update spans to an OpenTelemetry collector,
+/// with OTLP over HTTP in JSON
+///
+/// The collector endpoint, service name and request headers come from the
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`, `OTEL_SERVICE_NAME` and `OTEL_EXPORTER_OTLP_HEADERS`
+/// environment variables, e.g.
+///
+/// ```text
+/// OTEL_EXPORTER_OTLP_ENDPOINT=http://collector:4318
+/// OTEL_EXPORTER_OTLP_HEADERS=authorization=Bearer%20xyz
+/// ```
+///
+/// The requests are sent from a separate thread, failures being logged and the telemetry dropped
+#[derive(Clone)]
+pub struct OtlpExporter {
+    tx: Sender<Export>,
+    resource: Value,
+}
+
+impl Debug for OtlpExporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtlpExporter")
+            .field("resource", &self.resource)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OtlpExporter {
+    /// Creates a new exporter to the collector at `endpoint`, e.g. `http://collector:4318`
+    pub fn new(endpoint: &str, service_name: &str, headers: Vec<(String, String)>) -> Self {
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        let resource = json!({ "attributes": [attribute("service.name", service_name)] });
+        let (tx, rx) = mpsc::channel();
+        let spans_resource = resource.clone();
+        thread::spawn(move || {
+            let mut spans = vec![];
+            let mut flushed = Instant::now();
+            loop {
+                let disconnected = match rx.recv_timeout(FLUSH_INTERVAL) {
+                    Ok(Export::Span(span)) => {
+                        spans.push(span);
+                        false
+                    }
+                    Ok(Export::Metrics(metrics)) => {
+                        post(&endpoint, "metrics", &headers, &metrics);
+                        false
+                    }
+                    Err(RecvTimeoutError::Timeout) => false,
+                    Err(RecvTimeoutError::Disconnected) => true,
+                };
+                if disconnected || flushed.elapsed() >= FLUSH_INTERVAL {
+                    if !spans.is_empty() {
+                        let traces = traces(&spans_resource, std::mem::take(&mut spans));
+                        post(&endpoint, "traces", &headers, &traces);
+                    }
+                    flushed = Instant::now();
+                }
+                if disconnected {
+                    break;
+                }
+            }
+        });
+        Self { tx, resource }
+    }
+    /// Creates a new exporter from the environment, `None` unless `OTEL_EXPORTER_OTLP_ENDPOINT` is set
+    pub fn from_env() -> Option<Self> {
+        let endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+        let service_name =
+            env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| SERVICE_NAME.to_string());
+        let headers = env::var("OTEL_EXPORTER_OTLP_HEADERS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|header| header.split_once('='))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().replace("%20", " ")))
+            .collect();
+        Some(Self::new(&endpoint, &service_name, headers))
+    }
+    /// Returns the tracing layer exporting the spans once closed
+    pub fn layer(&self) -> OtlpLayer {
+        OtlpLayer {
+            exporter: self.clone(),
+            ids: RandomState::new(),
+        }
+    }
+    /// Exports the progress metrics of the cases of a snapshot
+    pub fn record(&self, snapshot: &StatusSnapshot) {
+        let _ = self.tx.send(Export::Metrics(
+            self.metrics(snapshot, unix_nanos(SystemTime::now())),
+        ));
+    }
+    /// Returns the OTLP metrics of the cases of a snapshot
+    ///
+    /// The gauges are the time step, the percent complete, the simulated time, the time step
+    /// duration the ETA is computed from and the remaining wall-clock time of each case
+    pub fn metrics(&self, snapshot: &StatusSnapshot, time: u128) -> Value {
+        let time = time.to_string();
+        let gauge = |name: &str, unit: &str, value: fn(&Case) -> Option<Value>| {
+            let points: Vec<_> = snapshot
+                .cases()
+                .filter_map(|case| {
+                    let mut attributes = vec![
+                        attribute("cfd.case", case.name()),
+                        attribute("cfd.state", case.state()),
+                    ];
+                    if let Some(group) = case.group_name() {
+                        attributes.push(attribute("cfd.group", group));
+                    }
+                    let mut point = json!({ "attributes": attributes, "timeUnixNano": time });
+                    match value(case)? {
+                        Value::Number(n) if n.is_f64() => point["asDouble"] = n.into(),
+                        value => point["asInt"] = value.to_string().into(),
+                    }
+                    Some(point)
+                })
+                .collect();
+            json!({ "name": name, "unit": unit, "gauge": { "dataPoints": points } })
+        };
+        let metrics = [
+            gauge("cfd.case.step", "{step}", |case| {
+                case.step().map(Value::from)
+            }),
+            gauge("cfd.case.progress", "%", |case| {
+                Some(case.percent_complete().into())
+            }),
+            gauge("cfd.case.simulated_time", "s", |case| {
+                case.step().map(|_| case.time().into())
+            }),
+            gauge("cfd.case.step_time", "s", |case| {
+                Some(case.elapsed_per_step().estimate())
+                    .filter(|secs| *secs > 0.)
+                    .map(Value::from)
+            }),
+            gauge("cfd.case.remaining", "s", |case| {
+                case.remaining()
+                    .map(|remaining| remaining.num_seconds().into())
+            }),
+        ];
+        json!({
+            "resourceMetrics": [{
+                "resource": self.resource,
+                "scopeMetrics": [{ "scope": scope(), "metrics": metrics }]
+            }]
+        })
+    }
+}
+
+/// Tracing layer exporting the spans of the monitor, such as the poll and case update spans,
+/// to the collector of an [OtlpExporter]
+pub struct OtlpLayer {
+    exporter: OtlpExporter,
+    ids: RandomState,
+}
+
+/// Span recorded by an [OtlpLayer] until it closes
+struct SpanData {
+    trace_id: String,
+    span_id: String,
+    parent_id: Option<String>,
+    start: u128,
+    attributes: Vec<Value>,
+}
+
+impl OtlpLayer {
+    /// Returns a random identifier of `n` 64 bits words in hexadecimal
+    fn random_id(&self, n: usize, seed: u64) -> String {
+        (0..n)
+            .map(|i| format!("{:016x}", self.ids.hash_one((seed, i, SystemTime::now()))))
+            .collect()
+    }
+}
+
+impl<S> Layer<S> for OtlpLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span.parent().and_then(|parent| {
+            parent
+                .extensions()
+                .get::<SpanData>()
+                .map(|data| (data.trace_id.clone(), data.span_id.clone()))
+        });
+        let (trace_id, parent_id) = match parent {
+            Some((trace_id, parent_id)) => (trace_id, Some(parent_id)),
+            None => (self.random_id(2, id.into_u64()), None),
+        };
+        let mut data = SpanData {
+            trace_id,
+            span_id: self.random_id(1, id.into_u64()),
+            parent_id,
+            start: unix_nanos(SystemTime::now()),
+            attributes: vec![],
+        };
+        attrs.record(&mut SpanFields(&mut data.attributes));
+        span.extensions_mut().insert(data);
+    }
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+                values.record(&mut SpanFields(&mut data.attributes));
+            }
+        }
+    }
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<SpanData>() else {
+            return;
+        };
+        let mut value = json!({
+            "traceId": data.trace_id,
+            "spanId": data.span_id,
+            "name": span.name(),
+            "kind": 1,
+            "startTimeUnixNano": data.start.to_string(),
+            "endTimeUnixNano": unix_nanos(SystemTime::now()).to_string(),
+            "attributes": data.attributes,
+        });
+        if let Some(parent_id) = data.parent_id {
+            value["parentSpanId"] = parent_id.into();
+        }
+        let _ = self.exporter.tx.send(Export::Span(value));
+    }
+}
+
+/// Visitor of the fields of a span, recorded as OTLP attributes
+struct SpanFields<'a>(&'a mut Vec<Value>);
+
+impl SpanFields<'_> {
+    fn push(&mut self, field: &Field, value: Value) {
+        self.0.retain(|attribute| attribute["key"] != field.name());
+        self.0.push(json!({ "key": field.name(), "value": value }));
+    }
+}
+
+impl Visit for SpanFields<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, json!({ "doubleValue": value }));
+    }
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, json!({ "intValue": value.to_string() }));
+    }
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, json!({ "intValue": value.to_string() }));
+    }
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, json!({ "boolValue": value }));
+    }
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, json!({ "stringValue": value }));
+    }
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.push(field, json!({ "stringValue": format!("{value:?}") }));
+    }
+}
+
+/// Returns the OTLP traces of finished spans
+fn traces(resource: &Value, spans: Vec<Value>) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": resource,
+            "scopeSpans": [{ "scope": scope(), "spans": spans }]
+        }]
+    })
+}
+
+/// Returns the instrumentation scope of the telemetry
+fn scope() -> Value {
+    json!({ "name": SERVICE_NAME, "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// Returns an OTLP string attribute
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// Returns the nanoseconds since the Unix epoch
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+}
+
+/// Sends telemetry to the `/v1/{signal}` path of the collector
+fn post(endpoint: &str, signal: &str, headers: &[(String, String)], body: &Value) {
+    let request = headers
+        .iter()
+        .fold(
+            ureq::post(&format!("{endpoint}/v1/{signal}")),
+            |request, (name, value)| request.set(name, value),
+        )
+        .set("content-type", "application/json")
+        .timeout(FLUSH_INTERVAL);
+    if let Err(e) = request.send_string(&body.to_string()) {
+        tracing::warn!(endpoint, signal, error = %e, "failed to export the telemetry");
+    }
+}
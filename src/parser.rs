@@ -0,0 +1,221 @@
+use std::{
+    fmt::Debug,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{rotated::open_log, Redactor, StatusSnapshot};
+
+/// Default pattern of the time step line of the solver log
+pub(crate) const TIME_STEP: &str = r"TimeStep\s+(\d+): Time\s+(\d+\.\d+e[+-]?\d+)";
+/// Wall-clock timestamp of a log line, e.g. `2024-06-10 14:03:27`
+static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(\d{4}-\d{2}-\d{2})[ T](\d{2}:\d{2}:\d{2}(?:\.\d+)?)\b").unwrap()
+});
+
+/// Parser of the time steps reported in a solver log
+pub trait StepParser: Send + Sync + Debug {
+    /// Returns the time step and the simulation time reported by a log line, if any
+    fn parse_line(&self, line: &str) -> Option<(usize, f64)>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TimeStepPatternError {
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+    #[error("the time step pattern has no capture group {0}")]
+    Group(usize),
+}
+
+/// Pattern of the time step line of a solver log, the time step and the simulated time
+/// being the `step` and `time` capture groups, the first and second by default
+///
+/// ```toml
+/// time_step = { pattern = 'Time Step\s+(\d+)\s+Time\s*=\s*(\S+)' }
+/// time_step = { pattern = 'T=(\S+) s, Time Step (\d+)', step = 2, time = 1 }
+/// ```
+///
+/// The simulated time may use a Fortran `D` exponent, e.g. `1.25D-02`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "TimeStepConfig")]
+pub struct TimeStepPattern {
+    regex: Regex,
+    step: usize,
+    time: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimeStepConfig {
+    pattern: String,
+    #[serde(default = "first")]
+    step: usize,
+    #[serde(default = "second")]
+    time: usize,
+}
+
+fn first() -> usize {
+    1
+}
+
+fn second() -> usize {
+    2
+}
+
+impl PartialEq for TimeStepPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.step == other.step
+            && self.time == other.time
+    }
+}
+
+impl TryFrom<TimeStepConfig> for TimeStepPattern {
+    type Error = TimeStepPatternError;
+
+    fn try_from(config: TimeStepConfig) -> Result<Self, Self::Error> {
+        Self::new(&config.pattern, config.step, config.time)
+    }
+}
+
+impl Default for TimeStepPattern {
+    fn default() -> Self {
+        Self::new(TIME_STEP, 1, 2).unwrap()
+    }
+}
+
+impl TimeStepPattern {
+    /// Creates a new time step pattern, `step` and `time` being the indices of the capture groups
+    /// of the time step and of the simulated time
+    pub fn new(pattern: &str, step: usize, time: usize) -> Result<Self, TimeStepPatternError> {
+        let regex = Regex::new(pattern)?;
+        if let Some(group) = [step, time]
+            .into_iter()
+            .find(|group| *group == 0 || *group >= regex.captures_len())
+        {
+            return Err(TimeStepPatternError::Group(group));
+        }
+        Ok(Self { regex, step, time })
+    }
+}
+
+impl StepParser for TimeStepPattern {
+    fn parse_line(&self, line: &str) -> Option<(usize, f64)> {
+        let captures = self.regex.captures(line)?;
+        let step = captures.get(self.step)?.as_str().parse().ok()?;
+        let time = captures.get(self.time)?.as_str().replace(['D', 'd'], "e");
+        Some((step, time.parse().ok()?))
+    }
+}
+
+/// Time step of a solver log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepRecord {
+    pub step: usize,
+    /// Simulated time in seconds
+    pub time: f64,
+    /// Latest wall-clock timestamp of the log since the previous time step, if the log has any
+    pub wall_clock: Option<DateTime<Local>>,
+}
+
+/// Returns all the time steps of a log, e.g. of a completed run, with the default time step pattern
+///
+/// The time steps are in the order of the log, a restart from a checkpoint going back
+/// to an earlier time step. A gzip-compressed log is decompressed on the fly
+pub fn parse_log<P: AsRef<Path>>(path: P) -> io::Result<Vec<StepRecord>> {
+    parse_log_with(path, &TimeStepPattern::default())
+}
+
+/// Returns all the time steps of a log parsed with `parser`
+pub fn parse_log_with<P: AsRef<Path>>(
+    path: P,
+    parser: &dyn StepParser,
+) -> io::Result<Vec<StepRecord>> {
+    let mut records = vec![];
+    let mut wall_clock = None;
+    for line in open_log(path)?.lines() {
+        let line = line?;
+        wall_clock = parse_timestamp(&line).or(wall_clock);
+        if let Some((step, time)) = parser.parse_line(&line) {
+            records.push(StepRecord {
+                step,
+                time,
+                wall_clock: wall_clock.take(),
+            });
+        }
+    }
+    Ok(records)
+}
+
+/// Returns the wall-clock timestamp of a log line, if any
+pub(crate) fn parse_timestamp(line: &str) -> Option<DateTime<Local>> {
+    let captures = TIMESTAMP.captures(line)?;
+    NaiveDateTime::parse_from_str(
+        &format!("{} {}", &captures[1], &captures[2]),
+        "%Y-%m-%d %H:%M:%S%.f",
+    )
+    .ok()?
+    .and_local_timezone(Local)
+    .earliest()
+}
+
+/// Receiver of the status snapshots
+pub trait SnapshotSink: Send + Debug {
+    /// Receives the snapshot of an update, to be redacted by `redactor` before leaving the monitor
+    fn send(&self, snapshot: &StatusSnapshot, redactor: &Redactor);
+}
+
+/// Shared libraries providing a log parser and snapshot sinks, requires the `plugins` feature
+///
+/// ```toml
+/// [plugins]
+/// parser = "/opt/partner/libsolver_parser.so"
+/// sinks = ["/opt/gmto/libim_sink.so"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PluginsConfig {
+    /// Library parsing the time steps of the logs instead of the search of the last `TimeStep` line
+    pub parser: Option<PathBuf>,
+    /// Libraries receiving the snapshots, redacted as the `[redaction]` section sets
+    #[serde(default)]
+    pub sinks: Vec<PathBuf>,
+}
+
+/// Parser and sinks loaded from the plugins
+#[derive(Debug, Default)]
+pub struct Plugins {
+    pub parser: Option<Arc<dyn StepParser>>,
+    pub sinks: Vec<Box<dyn SnapshotSink>>,
+}
+
+impl PluginsConfig {
+    /// Loads the plugins
+    #[cfg(feature = "plugins")]
+    pub fn load(&self) -> Result<Plugins, crate::PluginError> {
+        use crate::Plugin;
+        let parser = self
+            .parser
+            .as_ref()
+            .map(|path| Plugin::load(path)?.parser())
+            .transpose()?
+            .map(|parser| Arc::new(parser) as Arc<dyn StepParser>);
+        let sinks = self
+            .sinks
+            .iter()
+            .map(|path| Ok(Box::new(Plugin::load(path)?.sink()?) as Box<dyn SnapshotSink>))
+            .collect::<Result<_, crate::PluginError>>()?;
+        Ok(Plugins { parser, sinks })
+    }
+    /// Loads the plugins, none without the `plugins` feature
+    #[cfg(not(feature = "plugins"))]
+    pub fn load(&self) -> Result<Plugins, std::convert::Infallible> {
+        if self.parser.is_some() || !self.sinks.is_empty() {
+            tracing::warn!("plugins require the `plugins` feature, ignored");
+        }
+        Ok(Plugins::default())
+    }
+}
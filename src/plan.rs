@@ -0,0 +1,324 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Local};
+use serde::Serialize;
+
+use crate::{format_hours_minutes, Case, StatusSnapshot};
+
+/// Width of the bars of the schedule
+const GANTT_WIDTH: usize = 50;
+
+/// Projected run of a case in the schedule of the batch
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedRun {
+    pub case: String,
+    pub group: Option<String>,
+    /// `running`, `pending` for a queued case or `planned` for a case not submitted yet
+    pub state: &'static str,
+    pub start: Option<DateTime<Local>>,
+    pub finish: Option<DateTime<Local>>,
+    /// Predecessor the start of the run waits for, once it is later than a free slot
+    /// or not projected yet
+    pub after: Option<String>,
+}
+
+/// Schedule of the running, pending and planned cases
+///
+/// The planned cases start in order as soon as a slot frees up, the cases running
+/// at once being limited to `slots`, by default the number of cases running now.
+/// A planned case runs for its time steps at the mean time step duration
+/// of the running cases of its group, or of all the running cases
+///
+/// The pending and planned cases start after their predecessors in the schedule finish,
+/// the cases they are configured to run [after](crate::Case::after) and the precursors
+/// seeding them, a predecessor without a projected finish leaving the start unknown
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchPlan {
+    pub taken: DateTime<Local>,
+    pub slots: usize,
+    pub runs: Vec<PlannedRun>,
+    /// Completion of the last run, unknown while any run is
+    pub finish: Option<DateTime<Local>>,
+}
+
+impl BatchPlan {
+    /// Projects the schedule of the planned cases after the cases of the snapshot
+    pub fn new(snapshot: &StatusSnapshot, planned: &[Case], slots: Option<usize>) -> Self {
+        let taken = snapshot.taken();
+        let run = |case: &Case, state, start, finish| PlannedRun {
+            case: case.name().to_string(),
+            group: case.group_name().map(str::to_string),
+            state,
+            start,
+            finish,
+            after: None,
+        };
+        let cases: Vec<_> = snapshot
+            .running()
+            .chain(snapshot.pending())
+            .chain(planned)
+            .collect();
+        let predecessors = |case: &Case| -> Vec<String> {
+            let seeding = cases.iter().filter(|other| {
+                other
+                    .precursor_config()
+                    .is_some_and(|precursor| precursor.seeds == case.name())
+            });
+            case.predecessors()
+                .iter()
+                .cloned()
+                .chain(seeding.map(|other| other.name().to_string()))
+                .collect()
+        };
+        let mut runs: Vec<_> = snapshot
+            .running()
+            .map(|case| {
+                let finish = (case.elapsed_per_step().mean() > 0.)
+                    .then(|| taken + Duration::seconds(case.eta_secs()));
+                run(case, "running", case.start(), finish)
+            })
+            .collect();
+        // the slots free up as the running cases complete, a case without an ETA holds its slot
+        let mut free: Vec<_> = runs.iter().map(|run| run.finish).collect();
+        free.sort_by_key(|finish| (finish.is_none(), *finish));
+        let slots = slots.unwrap_or(free.len()).max(1);
+        let mut free: Vec<_> = free.split_off(free.len().saturating_sub(slots));
+        free.resize(slots, Some(taken));
+        let pending: Vec<_> = snapshot.pending().collect();
+        for case in chained(pending, &predecessors) {
+            let (start, finish) = snapshot.projection(case).unzip();
+            let mut pending = run(case, "pending", start.or(case.expected_start()), finish);
+            // the queued job is held until its predecessors finish
+            match last_finish(&runs, &predecessors(case)) {
+                // the projection already waits for the predecessors it knows of
+                Some((Some(ready), name)) if pending.start.is_none_or(|start| start <= ready) => {
+                    let shift = pending.start.map(|start| ready - start);
+                    pending.start = Some(ready);
+                    pending.finish = pending
+                        .finish
+                        .zip(shift)
+                        .map(|(finish, shift)| finish + shift);
+                    pending.after = Some(name);
+                }
+                Some((None, name)) => {
+                    (pending.start, pending.finish) = (None, None);
+                    pending.after = Some(name);
+                }
+                _ => (),
+            }
+            runs.push(pending);
+        }
+        for case in chained(planned.iter().collect(), &predecessors) {
+            let (slot, free_at) = free
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by_key(|(_, start)| (start.is_none(), *start))
+                .expect("no slot");
+            let (start, after) = match last_finish(&runs, &predecessors(case)) {
+                Some((Some(ready), name)) if free_at.is_some_and(|free_at| free_at < ready) => {
+                    (Some(ready), Some(name))
+                }
+                Some((None, name)) => (None, Some(name)),
+                _ => (free_at, None),
+            };
+            let finish = start
+                .zip(snapshot.reference_step_time(case.group_name()))
+                .map(|(start, step_time)| {
+                    start + Duration::seconds((case.total_step() as f64 * step_time) as i64)
+                });
+            free[slot] = finish;
+            runs.push(PlannedRun {
+                after,
+                ..run(case, "planned", start, finish)
+            });
+        }
+        let finish = runs
+            .iter()
+            .map(|run| run.finish)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|finishes| finishes.into_iter().max());
+        Self {
+            taken,
+            slots,
+            runs,
+            finish,
+        }
+    }
+}
+
+/// Orders the cases so each comes after its predecessors among them, in the given order
+/// otherwise, the cases of a dependency cycle being left in the given order
+fn chained<'a>(
+    mut cases: Vec<&'a Case>,
+    predecessors: &dyn Fn(&Case) -> Vec<String>,
+) -> Vec<&'a Case> {
+    let mut ordered: Vec<&Case> = Vec::with_capacity(cases.len());
+    while !cases.is_empty() {
+        let ready = cases.iter().position(|case| {
+            predecessors(case).iter().all(|name| {
+                cases.iter().all(|other| other.name() != name)
+                    || ordered.iter().any(|done| done.name() == name)
+            })
+        });
+        ordered.push(cases.remove(ready.unwrap_or(0)));
+    }
+    ordered
+}
+
+/// Returns the latest projected finish of the predecessors in the schedule with the name
+/// of the last one, `None` for the finish while any is not projected,
+/// `None` if no predecessor is in the schedule, e.g. all completed
+fn last_finish(
+    runs: &[PlannedRun],
+    predecessors: &[String],
+) -> Option<(Option<DateTime<Local>>, String)> {
+    let runs: Vec<_> = runs
+        .iter()
+        .filter(|run| predecessors.contains(&run.case))
+        .collect();
+    if let Some(unknown) = runs.iter().find(|run| run.finish.is_none()) {
+        return Some((None, unknown.case.clone()));
+    }
+    runs.into_iter()
+        .max_by_key(|run| run.finish)
+        .map(|run| (run.finish, run.case.clone()))
+}
+
+impl Display for BatchPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = |t: Option<DateTime<Local>>| {
+            t.map_or_else(|| "-".to_string(), |t| t.format("%m-%d %H:%M").to_string())
+        };
+        let end = self
+            .runs
+            .iter()
+            .filter_map(|run| run.finish)
+            .max()
+            .unwrap_or(self.taken);
+        let span = (end - self.taken).num_seconds().max(1) as f64;
+        let column = |t: DateTime<Local>| {
+            (((t - self.taken).num_seconds() as f64 / span * GANTT_WIDTH as f64).round() as usize)
+                .min(GANTT_WIDTH)
+        };
+        writeln!(
+            f,
+            "{:20}{:<width$}{:>14}{:>14}",
+            format!("Plan, {} slots", self.slots),
+            format!("{:<25}{:>25}", format(Some(self.taken)), format(Some(end))),
+            "Start",
+            "Finish",
+            width = GANTT_WIDTH
+        )?;
+        for run in &self.runs {
+            let bar = match run.finish {
+                Some(finish) => {
+                    let (from, to) = (
+                        column(run.start.unwrap_or(self.taken).max(self.taken)),
+                        column(finish),
+                    );
+                    let mark = if run.state == "running" { "=" } else { "#" };
+                    format!(
+                        "{}{}",
+                        " ".repeat(from),
+                        mark.repeat(to.saturating_sub(from).max(1))
+                    )
+                }
+                None => "?".to_string(),
+            };
+            write!(
+                f,
+                "{:<20}{:<width$}{:>14}{:>14}",
+                run.case,
+                bar,
+                format(run.start),
+                format(run.finish),
+                width = GANTT_WIDTH
+            )?;
+            match &run.after {
+                Some(after) => writeln!(f, "  after {after}")?,
+                None => writeln!(f)?,
+            }
+        }
+        write!(f, "Batch finish: {}", format(self.finish))
+    }
+}
+
+/// Projected completion of a case in the finish order
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectedFinish {
+    pub case: String,
+    pub group: Option<String>,
+    /// `running`, `pending` or `planned`, see [PlannedRun]
+    pub state: &'static str,
+    pub finish: DateTime<Local>,
+    /// Seconds since the previous completion, or since the projection for the first one
+    pub gap_secs: i64,
+}
+
+/// Cases of the schedule in their projected order of completion with the gaps between them,
+/// to schedule the post-processing jobs and the data transfers as the cases complete
+#[derive(Debug, Clone, Serialize)]
+pub struct FinishOrder {
+    pub taken: DateTime<Local>,
+    pub finishes: Vec<ProjectedFinish>,
+    /// Cases without a projected completion yet, e.g. before their first time steps
+    pub unknown: Vec<String>,
+}
+
+impl FinishOrder {
+    /// Sorts the runs of a schedule by projected completion
+    pub fn new(plan: &BatchPlan) -> Self {
+        let mut runs: Vec<_> = plan.runs.iter().collect();
+        runs.sort_by_key(|run| run.finish);
+        let (unknown, runs): (Vec<_>, Vec<_>) =
+            runs.into_iter().partition(|run| run.finish.is_none());
+        let mut previous = plan.taken;
+        let finishes = runs
+            .into_iter()
+            .filter_map(|run| {
+                let finish = run.finish?;
+                let gap_secs = (finish - previous).num_seconds().max(0);
+                previous = previous.max(finish);
+                Some(ProjectedFinish {
+                    case: run.case.clone(),
+                    group: run.group.clone(),
+                    state: run.state,
+                    finish,
+                    gap_secs,
+                })
+            })
+            .collect();
+        Self {
+            taken: plan.taken,
+            finishes,
+            unknown: unknown.into_iter().map(|run| run.case.clone()).collect(),
+        }
+    }
+}
+
+impl Display for FinishOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:<20}{:<10}{:>14}{:>10}{:>10}",
+            "#", "Case", "State", "Finish", "In", "Gap"
+        )?;
+        for (i, finish) in self.finishes.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<20}{:<10}{:>14}{:>10}{:>10}",
+                i + 1,
+                finish.case,
+                finish.state,
+                finish.finish.format("%m-%d %H:%M"),
+                format_hours_minutes((finish.finish - self.taken).num_seconds().max(0)),
+                format_hours_minutes(finish.gap_secs)
+            )?;
+        }
+        if !self.unknown.is_empty() {
+            writeln!(f, "No projected finish: {}", self.unknown.join(", "))?;
+        }
+        Ok(())
+    }
+}
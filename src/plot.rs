@@ -0,0 +1,350 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+#[cfg(feature = "plot")]
+use plotters::{coord::Shift, prelude::*, style::FontFamily};
+use serde::Deserialize;
+
+use crate::StatusSnapshot;
+
+/// Number of points kept in a series of the charts, every other one being dropped once full
+const PLOT_POINTS: usize = 512;
+/// Size in pixels of the charts of a case, the three charts stacked
+#[cfg(feature = "plot")]
+const CASE_SIZE: (u32, u32) = (800, 900);
+/// Size in pixels of the campaign overview
+#[cfg(feature = "plot")]
+const CAMPAIGN_SIZE: (u32, u32) = (800, 450);
+
+/// Image format of the charts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlotFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+impl PlotFormat {
+    /// Returns the file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// Error rendering the charts
+#[cfg(feature = "plot")]
+#[derive(Debug, thiserror::Error)]
+pub enum PlotError {
+    #[error("failed to write the chart")]
+    Io(#[from] std::io::Error),
+    #[error("failed to draw the chart: {0}")]
+    Draw(String),
+}
+
+/// Progress of a case over the updates
+#[derive(Debug, Clone, Default)]
+struct CaseSeries {
+    total_step: usize,
+    /// Wall-clock hours since the first update with the time step
+    steps: Vec<(f64, f64)>,
+    /// Time step with the mean duration in seconds of the time steps since the previous update
+    step_times: Vec<(f64, f64)>,
+    /// Wall-clock hours since the first update with the ETA
+    etas: Vec<(f64, DateTime<Local>)>,
+}
+
+impl CaseSeries {
+    /// Returns the drift in hours of the ETA from the first one, positive if it slipped
+    fn eta_drift(&self) -> Vec<(f64, f64)> {
+        let Some((_, first)) = self.etas.first() else {
+            return vec![];
+        };
+        self.etas
+            .iter()
+            .map(|(hours, eta)| (*hours, (*eta - *first).num_seconds() as f64 / 3600.))
+            .collect()
+    }
+    /// Returns the progress in percent of the time steps
+    fn progress(&self) -> Vec<(f64, f64)> {
+        let total = self.total_step.max(1) as f64;
+        self.steps
+            .iter()
+            .map(|(hours, step)| (*hours, 100. * step / total))
+            .collect()
+    }
+}
+
+/// Adds a point to a series, every other point being dropped once full
+fn push<T>(series: &mut Vec<T>, point: T) {
+    if series.len() == PLOT_POINTS {
+        let latest = series.pop();
+        let mut i = 0;
+        series.retain(|_| {
+            i += 1;
+            i % 2 == 1
+        });
+        series.extend(latest);
+    }
+    series.push(point);
+}
+
+/// Charts of the progress of the cases over the updates, rendered with the `plot` feature
+/// to an output directory, e.g. for the status emails and the web dashboard
+///
+/// Each case has a chart `<case>.svg` of its time step against the wall-clock time,
+/// of the time step duration against the time step and of the drift of its ETA,
+/// and `campaign.svg` has the progress of all the cases
+#[derive(Debug, Clone)]
+pub struct ProgressPlots {
+    dir: PathBuf,
+    format: PlotFormat,
+    /// Time of the first update
+    start: Option<DateTime<Local>>,
+    cases: BTreeMap<String, CaseSeries>,
+}
+
+impl ProgressPlots {
+    /// Creates the charts written to a directory
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            format: Default::default(),
+            start: None,
+            cases: BTreeMap::new(),
+        }
+    }
+    /// Sets the image format of the charts
+    pub fn format(mut self, format: PlotFormat) -> Self {
+        self.format = format;
+        self
+    }
+    /// Records the progress of the running cases of a snapshot
+    pub fn record(&mut self, snapshot: &StatusSnapshot) -> &mut Self {
+        let start = *self.start.get_or_insert(snapshot.taken());
+        let hours = (snapshot.taken() - start).num_seconds() as f64 / 3600.;
+        for case in snapshot.running() {
+            let Some(step) = case.step() else {
+                continue;
+            };
+            let series = self.cases.entry(case.name().to_string()).or_default();
+            series.total_step = case.total_step();
+            let step = step as f64;
+            let advanced = series
+                .steps
+                .last()
+                .is_none_or(|(_, latest)| step != *latest);
+            push(&mut series.steps, (hours, step));
+            // the mean duration of the time steps since the previous update
+            if let Some(secs) = case.step_times().last().filter(|_| advanced) {
+                push(&mut series.step_times, (step, secs));
+            }
+            if let Some(eta) = case.eta() {
+                push(&mut series.etas, (hours, eta));
+            }
+        }
+        self
+    }
+    /// Returns the names of the cases with a chart
+    pub fn cases(&self) -> impl Iterator<Item = &str> {
+        self.cases.keys().map(String::as_str)
+    }
+    /// Returns the wall-clock hours since the first update with the progress of a case
+    /// in percent of its time steps
+    pub fn progress(&self, case: &str) -> Vec<(f64, f64)> {
+        self.cases
+            .get(case)
+            .map(CaseSeries::progress)
+            .unwrap_or_default()
+    }
+    /// Returns the time steps of a case with the mean duration in seconds of the time steps
+    /// since the previous update
+    pub fn step_times(&self, case: &str) -> Vec<(f64, f64)> {
+        self.cases
+            .get(case)
+            .map(|series| series.step_times.clone())
+            .unwrap_or_default()
+    }
+    /// Returns the wall-clock hours since the first update with the drift in hours
+    /// of the ETA of a case from its first ETA, positive if it slipped
+    pub fn eta_drift(&self, case: &str) -> Vec<(f64, f64)> {
+        self.cases
+            .get(case)
+            .map(CaseSeries::eta_drift)
+            .unwrap_or_default()
+    }
+    /// Returns the path of the chart of a case, of the campaign if `None`
+    pub fn path(&self, case: Option<&str>) -> PathBuf {
+        let name = case.map_or_else(
+            || "campaign".to_string(),
+            |name| {
+                name.chars()
+                    .map(|c| match c {
+                        'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+                        _ => '_',
+                    })
+                    .collect()
+            },
+        );
+        self.dir.join(format!("{name}.{}", self.format.extension()))
+    }
+    /// Renders the charts of the cases and of the campaign, returning their paths
+    ///
+    /// The charts are rendered to hidden files renamed over the previous ones,
+    /// so a chart is never read partially written
+    #[cfg(feature = "plot")]
+    pub fn write(&self) -> Result<Vec<PathBuf>, PlotError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut paths = vec![];
+        for (name, series) in &self.cases {
+            let path = self.path(Some(name));
+            self.render(&path, Chart::Case(name, series))?;
+            paths.push(path);
+        }
+        let path = self.path(None);
+        self.render(&path, Chart::Campaign(&self.cases))?;
+        paths.push(path);
+        Ok(paths)
+    }
+    #[cfg(feature = "plot")]
+    fn render(&self, path: &Path, chart: Chart) -> Result<(), PlotError> {
+        let size = match chart {
+            Chart::Case(..) => CASE_SIZE,
+            Chart::Campaign(_) => CAMPAIGN_SIZE,
+        };
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = path.with_file_name(format!(".{file_name}"));
+        match self.format {
+            PlotFormat::Svg => chart.draw(&SVGBackend::new(&tmp, size).into_drawing_area())?,
+            PlotFormat::Png => chart.draw(&BitMapBackend::new(&tmp, size).into_drawing_area())?,
+        }
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Chart of a case or of the campaign
+#[cfg(feature = "plot")]
+enum Chart<'a> {
+    Case(&'a str, &'a CaseSeries),
+    Campaign(&'a BTreeMap<String, CaseSeries>),
+}
+
+#[cfg(feature = "plot")]
+impl Chart<'_> {
+    fn draw<DB: DrawingBackend>(&self, area: &DrawingArea<DB, Shift>) -> Result<(), PlotError> {
+        let error = |e: DrawingAreaErrorKind<DB::ErrorType>| PlotError::Draw(e.to_string());
+        area.fill(&WHITE).map_err(error)?;
+        match self {
+            Self::Case(name, series) => {
+                let panels = area.split_evenly((3, 1));
+                let charts = [
+                    (
+                        "time step",
+                        ("Wall-clock hours", "Time step"),
+                        series.steps.clone(),
+                    ),
+                    (
+                        "time step duration",
+                        ("Time step", "Duration [s]"),
+                        series.step_times.clone(),
+                    ),
+                    (
+                        "ETA drift",
+                        ("Wall-clock hours", "Drift [h]"),
+                        series.eta_drift(),
+                    ),
+                ];
+                for (panel, (caption, descs, points)) in panels.iter().zip(charts) {
+                    draw_lines(
+                        panel,
+                        &format!("{name}: {caption}"),
+                        descs,
+                        &[(name, points)],
+                    )?;
+                }
+            }
+            Self::Campaign(cases) => {
+                let progress: Vec<_> = cases
+                    .iter()
+                    .map(|(name, series)| (name.as_str(), series.progress()))
+                    .collect();
+                draw_lines(
+                    area,
+                    "Campaign progress",
+                    ("Wall-clock hours", "Progress [%]"),
+                    &progress,
+                )?;
+            }
+        }
+        area.present().map_err(error)
+    }
+}
+
+/// Returns the range of the values padded by 5%, around the value if single
+#[cfg(feature = "plot")]
+fn padded_range(values: impl Iterator<Item = f64>) -> std::ops::Range<f64> {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if !min.is_finite() {
+        return 0.0..1.;
+    }
+    let pad = if max > min { (max - min) * 0.05 } else { 1. };
+    min - pad..max + pad
+}
+
+/// Draws the lines of a chart, with a legend if more than one
+#[cfg(feature = "plot")]
+fn draw_lines<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    caption: &str,
+    (x_desc, y_desc): (&str, &str),
+    lines: &[(&str, Vec<(f64, f64)>)],
+) -> Result<(), PlotError> {
+    let error = |e: DrawingAreaErrorKind<DB::ErrorType>| PlotError::Draw(e.to_string());
+    let points = || lines.iter().flat_map(|(_, points)| points);
+    let mut chart = ChartBuilder::on(area)
+        .caption(caption, (FontFamily::SansSerif, 18))
+        .margin(10)
+        .x_label_area_size(35)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            padded_range(points().map(|(x, _)| *x)),
+            padded_range(points().map(|(_, y)| *y)),
+        )
+        .map_err(error)?;
+    chart
+        .configure_mesh()
+        .x_desc(x_desc)
+        .y_desc(y_desc)
+        .draw()
+        .map_err(error)?;
+    for (i, (name, points)) in lines.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart
+            .draw_series(LineSeries::new(
+                points.iter().copied(),
+                color.stroke_width(2),
+            ))
+            .map_err(error)?
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    }
+    if lines.len() > 1 {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .position(SeriesLabelPosition::UpperLeft)
+            .draw()
+            .map_err(error)?;
+    }
+    Ok(())
+}
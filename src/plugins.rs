@@ -0,0 +1,125 @@
+//! Plugins loaded from shared libraries through a C ABI
+//!
+//! A plugin exports `uint32_t cfd_status_plugin_abi(void)`, returning [PLUGIN_ABI], and either:
+//!  - a parser: `int cfd_status_parse_line(const char *line, uint64_t *step, double *time)`,
+//!    returning non-zero and setting `step` and `time` if the line reports a time step,
+//!  - a sink: `int cfd_status_send(const char *snapshot)`, receiving each snapshot as JSON
+//!    and returning 0 on success.
+
+use std::{
+    ffi::{c_char, c_int, CString},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use libloading::Library;
+
+use crate::{Redactor, SnapshotSink, StatusSnapshot, StepParser};
+
+/// Version of the plugin C ABI
+pub const PLUGIN_ABI: u32 = 1;
+
+type AbiFn = unsafe extern "C" fn() -> u32;
+type ParseFn = unsafe extern "C" fn(*const c_char, *mut u64, *mut f64) -> c_int;
+type SendFn = unsafe extern "C" fn(*const c_char) -> c_int;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    #[error("failed to load plugin {0:?}")]
+    Load(PathBuf, #[source] libloading::Error),
+    #[error("plugin {0:?} has ABI version {1}, expected {PLUGIN_ABI}")]
+    Abi(PathBuf, u32),
+    #[error("plugin {0:?} does not export {1}")]
+    Missing(PathBuf, &'static str),
+}
+
+/// Shared library plugin
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    path: PathBuf,
+    library: Arc<Library>,
+}
+
+impl Plugin {
+    /// Loads a plugin and checks its ABI version
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PluginError> {
+        let path = path.as_ref().to_path_buf();
+        // SAFETY: loading a plugin runs its initialization code, which is trusted as the monitor's own
+        let library =
+            unsafe { Library::new(&path) }.map_err(|e| PluginError::Load(path.clone(), e))?;
+        let plugin = Self {
+            path,
+            library: Arc::new(library),
+        };
+        // SAFETY: the symbol has the documented signature
+        let abi = unsafe { plugin.symbol::<AbiFn>("cfd_status_plugin_abi")?() };
+        if abi != PLUGIN_ABI {
+            return Err(PluginError::Abi(plugin.path, abi));
+        }
+        tracing::info!(path = ?plugin.path, "plugin loaded");
+        Ok(plugin)
+    }
+    /// Returns a function exported by the plugin
+    ///
+    /// # Safety
+    /// `T` must be the signature of the exported function
+    unsafe fn symbol<T: Copy>(&self, name: &'static str) -> Result<T, PluginError> {
+        self.library
+            .get::<T>(name.as_bytes())
+            .map(|symbol| *symbol)
+            .map_err(|_| PluginError::Missing(self.path.clone(), name))
+    }
+    /// Returns the log parser of the plugin
+    pub fn parser(self) -> Result<PluginParser, PluginError> {
+        // SAFETY: the symbol has the documented signature
+        let parse = unsafe { self.symbol::<ParseFn>("cfd_status_parse_line")? };
+        Ok(PluginParser {
+            _plugin: self,
+            parse,
+        })
+    }
+    /// Returns the snapshot sink of the plugin
+    pub fn sink(self) -> Result<PluginSink, PluginError> {
+        // SAFETY: the symbol has the documented signature
+        let send = unsafe { self.symbol::<SendFn>("cfd_status_send")? };
+        Ok(PluginSink { plugin: self, send })
+    }
+}
+
+/// Log parser of a plugin
+#[derive(Debug)]
+pub struct PluginParser {
+    // keeps the library loaded as long as `parse` may be called
+    _plugin: Plugin,
+    parse: ParseFn,
+}
+
+impl StepParser for PluginParser {
+    fn parse_line(&self, line: &str) -> Option<(usize, f64)> {
+        let line = CString::new(line).ok()?;
+        let (mut step, mut time) = (0u64, 0f64);
+        // SAFETY: the pointers are valid for the duration of the call
+        let parsed = unsafe { (self.parse)(line.as_ptr(), &mut step, &mut time) };
+        (parsed != 0).then_some((step as usize, time))
+    }
+}
+
+/// Snapshot sink of a plugin
+#[derive(Debug)]
+pub struct PluginSink {
+    plugin: Plugin,
+    send: SendFn,
+}
+
+impl SnapshotSink for PluginSink {
+    fn send(&self, snapshot: &StatusSnapshot, redactor: &Redactor) {
+        let Ok(json) = CString::new(snapshot.to_json(redactor)) else {
+            return;
+        };
+        // SAFETY: the pointer is valid for the duration of the call
+        let status = unsafe { (self.send)(json.as_ptr()) };
+        if status != 0 {
+            tracing::warn!(plugin = ?self.plugin.path, status, "plugin sink failed");
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::thread::{self, JoinHandle};
+
+use serde::Deserialize;
+
+use crate::{shell, Case};
+
+/// Production case seeded by a precursor, a short warm-up case
+///
+/// ```toml
+/// [[cases]]
+/// name = "zen30az045_OS2_warmup"
+/// duration = 50
+/// log = "solve-672_14.out"
+/// stall_after = 300
+///
+/// [cases.precursor]
+/// seeds = "zen30az045_OS2"
+/// validate = "check_warmup.sh $CFD_CASE_DIR"
+/// start = "cd /shared/$CFD_PRODUCTION && sbatch run.sh"
+/// ```
+///
+/// Once the precursor completes and its validation command exits successfully,
+/// the start command launches the production case, moved from the planned cases
+/// to the monitored ones
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PrecursorConfig {
+    /// Name of the production case
+    pub seeds: String,
+    /// Command checking the outputs of the precursor, passed if it exits successfully
+    pub validate: Option<String>,
+    /// Command starting the production case
+    pub start: Option<String>,
+}
+
+/// Validation of a completed precursor and start of its production case
+#[derive(Debug, Clone, PartialEq)]
+pub struct Seeding {
+    pub precursor: String,
+    pub production: String,
+    /// Failure of the validation or of the start command, `None` once the production case is started
+    pub error: Option<String>,
+}
+
+/// Runner of the validation and start commands of the completed precursors
+///
+/// The commands of each precursor are executed with `sh -c` in a separate thread,
+/// with the `CFD_CASE`, `CFD_CASE_DIR`, `CFD_LOG` and `CFD_PRODUCTION` environment variables
+#[derive(Debug, Default)]
+pub struct Precursors {
+    pending: Vec<JoinHandle<Seeding>>,
+}
+
+impl Precursors {
+    /// Creates a new runner
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Validates a completed case, if a precursor, then starts its production case
+    pub fn seed(&mut self, case: &Case) {
+        let Some(config) = case.precursor_config().cloned() else {
+            return;
+        };
+        let env = [
+            ("CFD_CASE", case.name().to_string()),
+            ("CFD_CASE_DIR", case.case_dir().display().to_string()),
+            ("CFD_LOG", case.log_file()),
+            ("CFD_PRODUCTION", config.seeds.clone()),
+        ];
+        let precursor = case.name().to_string();
+        tracing::info!(
+            case = precursor,
+            production = config.seeds,
+            "validating precursor"
+        );
+        self.pending.push(thread::spawn(move || {
+            let error = config
+                .validate
+                .iter()
+                .map(|command| ("validation", command))
+                .chain(config.start.iter().map(|command| ("start", command)))
+                .find_map(|(step, command)| run(step, command, &env).err());
+            Seeding {
+                precursor,
+                production: config.seeds,
+                error,
+            }
+        }));
+    }
+    /// Returns the seedings finished since the previous call, waiting for all of them if `wait`
+    pub fn finished(&mut self, wait: bool) -> Vec<Seeding> {
+        let (finished, pending) = self
+            .pending
+            .drain(..)
+            .partition(|handle| wait || handle.is_finished());
+        self.pending = pending;
+        finished
+            .into_iter()
+            .filter_map(|handle: JoinHandle<Seeding>| handle.join().ok())
+            .collect()
+    }
+}
+
+/// Executes a command of a precursor, failing unless it exits successfully
+fn run(step: &str, command: &str, env: &[(&str, String)]) -> Result<(), String> {
+    tracing::debug!(step, command, "running precursor command");
+    let output = shell(command)
+        .envs(env.iter().map(|(name, value)| (name, value)))
+        .output()
+        .map_err(|e| format!("{step} failed to execute: {e}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!(
+        "{step} exited with {}: {}",
+        output.status,
+        stderr.trim()
+    ))
+}
@@ -0,0 +1,180 @@
+use std::{fmt::Display, fs::File, path::PathBuf};
+
+use crate::{CaseError, Config, GlobSource, LogSource, RATE};
+
+/// Relative difference of the time step size of a log from the nominal one before it is reported
+const RATE_TOLERANCE: f64 = 0.01;
+
+/// Problem of the configuration or of the logs of a case, found before the monitoring starts
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreflightProblem {
+    /// Root directory of the cases missing
+    MissingRoot { root: PathBuf },
+    /// Case without a name, defined more than once, without a sane duration or running
+    /// after unknown cases, or display profile unknown
+    Invalid { reason: String },
+    /// Directory of a case missing
+    MissingDir { case: String, dir: PathBuf },
+    /// Log of a case missing, matched by no file or unreadable
+    UnreadableLog {
+        case: String,
+        log: String,
+        reason: String,
+    },
+    /// No line of the log of a case matching its time step pattern
+    NoTimeStep { case: String, log: String },
+    /// Time step size of the log of a case other than the one its time steps are counted at,
+    /// of the nominal time stepping rate or of its configured time step count
+    RateMismatch {
+        case: String,
+        dt: f64,
+        expected: f64,
+    },
+}
+
+impl Display for PreflightProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingRoot { root } => write!(f, "missing root: {}", root.display()),
+            Self::Invalid { reason } => write!(f, "invalid: {reason}"),
+            Self::MissingDir { case, dir } => {
+                write!(f, "missing directory: {case} has no {}", dir.display())
+            }
+            Self::UnreadableLog { case, log, reason } => {
+                write!(f, "unreadable log: {case} cannot read {log}: {reason}")
+            }
+            Self::NoTimeStep { case, log } => write!(
+                f,
+                "no time step: no line of {log} of {case} matches the time step pattern"
+            ),
+            Self::RateMismatch { case, dt, expected } => write!(
+                f,
+                "rate mismatch: {case} logs time steps of {dt}s instead of the {expected}s \
+                its time steps are counted at, set its `steps` or `time_stepping = \"adaptive\"`"
+            ),
+        }
+    }
+}
+
+/// Checks of the configured cases run before a long monitoring, all the problems
+/// being reported at once: the root and the case directories exist, the logs are readable
+/// and have a time step line, and the durations and the time stepping rate are sane
+#[derive(Debug, Clone, Default)]
+pub struct Preflight {
+    pub problems: Vec<PreflightProblem>,
+    /// Number of cases checked
+    pub cases: usize,
+}
+
+impl Preflight {
+    /// Checks the cases of a configuration, loaded unchecked so its invalid cases are reported
+    /// along with the others
+    pub fn new(config: &Config) -> Self {
+        let mut problems = vec![];
+        let root = config.root.is_dir();
+        if !root {
+            problems.push(PreflightProblem::MissingRoot {
+                root: config.root.clone(),
+            });
+        }
+        // the problems of the configuration are reported with the cases they are of, if any
+        let invalid = config.problems();
+        let reasons = |case: Option<usize>| {
+            invalid
+                .iter()
+                .filter(move |(i, _)| *i == case)
+                .map(|(_, e)| PreflightProblem::Invalid {
+                    reason: e.to_string(),
+                })
+        };
+        for (i, case_config) in config.cases.iter().enumerate() {
+            let len = problems.len();
+            problems.extend(reasons(Some(i)));
+            if problems.len() > len {
+                continue;
+            }
+            let name = case_config.name.clone();
+            let mut case = config.case(case_config);
+            let log = case.log_file();
+            // remote logs and log objects are only read
+            let local = case_config.log_command.is_none()
+                && case_config.log_host.is_none()
+                && case_config.log_object.is_none();
+            if local {
+                let dir = case.case_dir();
+                if !dir.is_dir() {
+                    // the cases of a missing root are not reported one by one
+                    if root || !dir.starts_with(&config.root) {
+                        problems.push(PreflightProblem::MissingDir { case: name, dir });
+                    }
+                    continue;
+                }
+                let readable = if GlobSource::is_glob(&log) {
+                    match GlobSource::new(&log).exists() {
+                        true => Ok(()),
+                        false => Err("no file matches".to_string()),
+                    }
+                } else {
+                    File::open(&log).map(drop).map_err(|e| e.to_string())
+                };
+                match readable {
+                    // a case waiting in the queue has no log yet
+                    Err(_) if case.is_pending() => continue,
+                    Err(reason) => {
+                        problems.push(PreflightProblem::UnreadableLog {
+                            case: name,
+                            log,
+                            reason,
+                        });
+                        continue;
+                    }
+                    Ok(()) => (),
+                }
+            }
+            match case.update().map(drop) {
+                Err(CaseError::Capture | CaseError::Grep) => {
+                    problems.push(PreflightProblem::NoTimeStep { case: name, log })
+                }
+                Err(e) => problems.push(PreflightProblem::UnreadableLog {
+                    case: name,
+                    log,
+                    reason: e.to_string(),
+                }),
+                Ok(()) if case.is_pending() => (),
+                Ok(()) => match case.step() {
+                    None => problems.push(PreflightProblem::NoTimeStep { case: name, log }),
+                    // the time steps of the duration are counted at the nominal rate,
+                    // or at the one of the configured time step count
+                    Some(step) if !case_config.is_adaptive() => {
+                        let dt = case.time_step_size().or_else(|| {
+                            (step > 0 && case.time() > 0.).then(|| case.time() / step as f64)
+                        });
+                        let expected = case_config
+                            .total_steps()
+                            .step_size(case_config.sim_seconds())
+                            .unwrap_or(1. / RATE as f64);
+                        if let Some(dt) =
+                            dt.filter(|dt| (dt - expected).abs() > RATE_TOLERANCE * expected)
+                        {
+                            problems.push(PreflightProblem::RateMismatch {
+                                case: name,
+                                dt,
+                                expected,
+                            });
+                        }
+                    }
+                    Some(_) => (),
+                },
+            }
+        }
+        problems.extend(reasons(None));
+        Self {
+            problems,
+            cases: config.cases.len(),
+        }
+    }
+    /// Returns true if no problem was found
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
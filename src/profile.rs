@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{Column, ColumnSpec, SortKey, StatusSnapshot, Theme};
+
+/// Names of the built-in display profiles, in the order they are switched through
+pub const PROFILES: [&str; 3] = ["operator", "summary", "pi"];
+
+/// Display profile of an audience, selecting the density of the status board: operators
+/// want everything, the principal investigators the progress and the ETAs only
+///
+/// ```toml
+/// [profiles.pi]
+/// columns = ["name", "percent", "eta"]
+/// refresh = 300
+/// grouped = true
+/// colors = false
+/// ```
+///
+/// The built-in `operator`, `summary` and `pi` profiles are replaced by the configured
+/// profiles of the same name
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DisplayProfile {
+    /// Columns of the status table, the configured columns by default
+    pub columns: Option<Vec<Column>>,
+    /// Column the rows are sorted by, the configured order by default
+    pub sort_by: Option<SortKey>,
+    /// Seconds between the refreshes of the board, at each update by default
+    pub refresh: Option<u64>,
+    /// Groups the rows by campaign with a summary per campaign, by default if any case
+    /// belongs to a campaign
+    pub grouped: Option<bool>,
+    /// Colors of the rows, the configured theme by default
+    pub theme: Option<Theme>,
+    /// Colors the rows, set to false for a plain table
+    pub colors: Option<bool>,
+}
+
+impl DisplayProfile {
+    /// Returns the built-in profile of the given name
+    pub fn builtin(name: &str) -> Option<Self> {
+        let columns = |columns: &[Column]| Some(columns.to_vec());
+        match name {
+            "operator" => Some(Self {
+                columns: columns(&[
+                    Column::Name,
+                    Column::State,
+                    Column::Percent,
+                    Column::Step,
+                    Column::Time,
+                    Column::StepTime,
+                    Column::Trend,
+                    Column::Iterations,
+                    Column::Elapsed,
+                    Column::StepsPerHour,
+                    Column::SimPerDay,
+                    Column::Remaining,
+                    Column::UsedCoreHours,
+                    Column::LeftCoreHours,
+                    Column::Utilization,
+                    Column::Eta,
+                    Column::ConservativeEta,
+                    Column::Slack,
+                    Column::Attention,
+                    Column::Note,
+                ]),
+                sort_by: Some(SortKey {
+                    column: Column::Attention,
+                    descending: false,
+                }),
+                ..Default::default()
+            }),
+            "summary" => Some(Self {
+                columns: columns(&[
+                    Column::Name,
+                    Column::Percent,
+                    Column::StepsPerHour,
+                    Column::Eta,
+                    Column::Slack,
+                ]),
+                ..Default::default()
+            }),
+            "pi" => Some(Self {
+                columns: columns(&[Column::Name, Column::Percent, Column::Eta]),
+                sort_by: Some(SortKey {
+                    column: Column::Eta,
+                    descending: false,
+                }),
+                refresh: Some(300),
+                grouped: Some(true),
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+    /// Returns the columns of the profile, the width of the table being kept
+    pub fn column_spec(&self, base: &ColumnSpec) -> ColumnSpec {
+        ColumnSpec::new(
+            self.columns.clone().unwrap_or(base.columns.clone()),
+            self.sort_by.unwrap_or(base.sort_by),
+        )
+        .with_width(base.width, base.wrap)
+    }
+    /// Returns the interval between the refreshes of the board, if set
+    pub fn refresh(&self) -> Option<Duration> {
+        self.refresh.map(Duration::from_secs)
+    }
+    /// Applies the grouping and the colors of the profile to a snapshot, a snapshot
+    /// without colors, e.g. with `--no-color`, staying so
+    pub fn style(&self, snapshot: StatusSnapshot) -> StatusSnapshot {
+        let theme = match self.colors {
+            Some(false) => None,
+            _ => snapshot.theme().map(|theme| self.theme.unwrap_or(theme)),
+        };
+        let grouped = self.grouped.or(snapshot.grouped());
+        snapshot.with_theme(theme).with_grouping(grouped)
+    }
+    /// Applies the columns, the grouping and the colors of the profile to a snapshot
+    pub fn apply(&self, snapshot: StatusSnapshot) -> StatusSnapshot {
+        let columns = self.column_spec(snapshot.columns());
+        self.style(snapshot.with_columns(columns))
+    }
+}
@@ -0,0 +1,324 @@
+use std::{fmt::Display, str::FromStr, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{Case, FilterError, FilterTerm, StatusSnapshot};
+
+/// Comparison of an aggregate of the cases to a number, e.g. `min(progress, group=zen30) < 0.5`
+static COMPARISON: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(\w+)\s*\(([^)]*)\)\s*(?:(<=|>=|==|!=|<|>|=)\s*([-+]?\d+(?:\.\d*)?(?:[eE][-+]?\d+)?))?\s*$")
+        .unwrap()
+});
+/// Separator of the comparisons of a query
+static CONNECTIVE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+(and|or)\s+").unwrap());
+
+#[derive(Debug, thiserror::Error)]
+pub enum QueryError {
+    #[error("invalid query {0:?}, expected e.g. `count(state=stalled) > 0`")]
+    Syntax(String),
+    #[error("unknown function {0:?}, expected count, min, max, mean or sum")]
+    Function(String),
+    #[error("unknown field {0:?}, expected one of {}", Field::NAMES.join(", "))]
+    Field(String),
+    #[error("{0}() needs a field, e.g. `{0}(progress)`")]
+    MissingField(String),
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+}
+
+/// Aggregate function over the selected cases
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Count,
+    Min,
+    Max,
+    Mean,
+    Sum,
+}
+
+/// Numeric field of a case
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    /// Fraction of the simulation complete, from 0 to 1
+    Progress,
+    Percent,
+    Step,
+    /// Mean time step duration in seconds
+    StepTime,
+    StepsPerHour,
+    RemainingHours,
+    SlackHours,
+    IdleSecs,
+    UsedCoreHours,
+    LeftCoreHours,
+}
+
+impl Field {
+    const NAMES: [&'static str; 10] = [
+        "progress",
+        "percent",
+        "step",
+        "step_time",
+        "steps_per_hour",
+        "remaining_hours",
+        "slack_hours",
+        "idle_secs",
+        "used_core_hours",
+        "left_core_hours",
+    ];
+    const ALL: [Self; 10] = [
+        Self::Progress,
+        Self::Percent,
+        Self::Step,
+        Self::StepTime,
+        Self::StepsPerHour,
+        Self::RemainingHours,
+        Self::SlackHours,
+        Self::IdleSecs,
+        Self::UsedCoreHours,
+        Self::LeftCoreHours,
+    ];
+    /// Returns the value of the field for a case, `None` if unknown
+    fn value(&self, case: &Case) -> Option<f64> {
+        match self {
+            Self::Progress => case.progress(),
+            Self::Percent => Some(case.percent_complete() as f64),
+            Self::Step => case.step().map(|step| step as f64),
+            Self::StepTime => case.step().map(|_| case.elapsed_per_step().mean()),
+            Self::StepsPerHour => case.steps_per_hour(),
+            Self::RemainingHours => case
+                .remaining()
+                .map(|remaining| remaining.num_seconds() as f64 / 3600.),
+            Self::SlackHours => case.slack_secs().map(|secs| secs as f64 / 3600.),
+            Self::IdleSecs => case.idle_secs().map(|secs| secs as f64),
+            Self::UsedCoreHours => case.consumed_core_hours(),
+            Self::LeftCoreHours => case.remaining_core_hours(),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = QueryError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .position(|name| *name == s)
+            .map(|i| Self::ALL[i])
+            .ok_or_else(|| QueryError::Field(s.to_string()))
+    }
+}
+
+/// Condition on the cases of an aggregate, `key=value` or `key!=value`
+///
+/// Besides the [FilterTerm]s, `state` may be `stalled`, `failed` or `late`
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Term(FilterTerm),
+    Stalled,
+    Failed,
+    Late,
+}
+
+impl Condition {
+    fn matches(&self, case: &Case) -> bool {
+        match self {
+            Self::Term(term) => term.matches(case),
+            Self::Stalled => case.is_stalled() || case.is_solver_dead(),
+            Self::Failed => case.error().is_some(),
+            Self::Late => case.is_late() && !case.is_complete(),
+        }
+    }
+}
+
+/// Aggregate of a field over the cases satisfying all the conditions
+#[derive(Debug, Clone, PartialEq)]
+struct Aggregate {
+    function: Function,
+    field: Option<Field>,
+    conditions: Vec<(Condition, bool)>,
+}
+
+impl Aggregate {
+    fn parse(name: &str, args: &str) -> Result<Self, QueryError> {
+        let function = match name {
+            "count" => Function::Count,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "mean" | "avg" => Function::Mean,
+            "sum" => Function::Sum,
+            name => return Err(QueryError::Function(name.to_string())),
+        };
+        let (mut field, mut conditions) = (None, vec![]);
+        for arg in args.split(',').map(str::trim).filter(|arg| !arg.is_empty()) {
+            if !arg.contains('=') {
+                field = Some(arg.parse()?);
+                continue;
+            }
+            let (term, negated) = match arg.split_once("!=") {
+                Some((key, value)) => (format!("{key}={value}"), true),
+                None => (arg.to_string(), false),
+            };
+            let condition = match term.replace(' ', "").as_str() {
+                "state=stalled" => Condition::Stalled,
+                "state=failed" => Condition::Failed,
+                "state=late" => Condition::Late,
+                _ => Condition::Term(term.parse()?),
+            };
+            conditions.push((condition, negated));
+        }
+        if field.is_none() && function != Function::Count {
+            return Err(QueryError::MissingField(name.to_string()));
+        }
+        Ok(Self {
+            function,
+            field,
+            conditions,
+        })
+    }
+    /// Returns the aggregate, `None` if no selected case has a value
+    fn evaluate(&self, snapshot: &StatusSnapshot) -> Option<f64> {
+        let cases = snapshot.cases().filter(|case| {
+            self.conditions
+                .iter()
+                .all(|(condition, negated)| condition.matches(case) != *negated)
+        });
+        let Some(field) = self.field else {
+            return Some(cases.count() as f64);
+        };
+        let values: Vec<_> = cases.filter_map(|case| field.value(case)).collect();
+        match self.function {
+            Function::Count => Some(values.len() as f64),
+            Function::Sum => Some(values.iter().sum()),
+            Function::Min => values.iter().copied().reduce(f64::min),
+            Function::Max => values.iter().copied().reduce(f64::max),
+            Function::Mean => {
+                (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+            }
+        }
+    }
+}
+
+/// Comparison of an aggregate to a number, a bare aggregate being true if it is not zero
+#[derive(Debug, Clone, PartialEq)]
+struct Comparison {
+    aggregate: Aggregate,
+    condition: Option<(String, f64)>,
+}
+
+impl Comparison {
+    fn holds(&self, value: Option<f64>) -> bool {
+        let Some(value) = value else {
+            return false;
+        };
+        match &self.condition {
+            None => value != 0.,
+            Some((op, rhs)) => match op.as_str() {
+                "<" => value < *rhs,
+                "<=" => value <= *rhs,
+                ">" => value > *rhs,
+                ">=" => value >= *rhs,
+                "!=" => value != *rhs,
+                _ => value == *rhs,
+            },
+        }
+    }
+}
+
+/// Question about the cases of a snapshot, for shell automation
+///
+/// A query is comparisons of aggregates of the cases, joined by `and`, binding first, and `or`:
+///
+/// ```text
+/// count(state=stalled) > 0
+/// min(progress, group=zen30) < 0.5 and count(state=failed) == 0
+/// max(slack_hours, state!=completed)
+/// ```
+///
+/// The functions are `count`, `min`, `max`, `mean` and `sum`, the conditions are [FilterTerm]s,
+/// or `state=stalled`, `state=failed` and `state=late`, and may be negated with `!=`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    /// Comparisons all holding for the query to hold, any of the groups holding
+    any: Vec<Vec<Comparison>>,
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut any = vec![vec![]];
+        let mut rest = s;
+        loop {
+            let (comparison, connective, next) = match CONNECTIVE.captures(rest) {
+                Some(captures) => {
+                    let (all, connective) = (captures.get(0).unwrap(), &captures[1]);
+                    (
+                        &rest[..all.start()],
+                        Some(connective == "or"),
+                        &rest[all.end()..],
+                    )
+                }
+                None => (rest, None, ""),
+            };
+            let captures = COMPARISON
+                .captures(comparison)
+                .ok_or_else(|| QueryError::Syntax(s.to_string()))?;
+            let condition = captures
+                .get(3)
+                .zip(captures.get(4))
+                .map(|(op, rhs)| (op.as_str().to_string(), rhs.as_str().parse().unwrap()));
+            any.last_mut().unwrap().push(Comparison {
+                aggregate: Aggregate::parse(&captures[1], &captures[2])?,
+                condition,
+            });
+            match connective {
+                Some(true) => any.push(vec![]),
+                Some(false) => (),
+                None => break,
+            }
+            rest = next;
+        }
+        Ok(Self { any })
+    }
+}
+
+impl Query {
+    /// Evaluates the query against a snapshot
+    pub fn evaluate(&self, snapshot: &StatusSnapshot) -> QueryResult {
+        let mut values = vec![];
+        let mut holds = false;
+        for all in &self.any {
+            let mut all_hold = true;
+            for comparison in all {
+                let value = comparison.aggregate.evaluate(snapshot);
+                all_hold &= comparison.holds(value);
+                values.push(value);
+            }
+            holds |= all_hold;
+        }
+        let bare = values.len() == 1 && self.any[0][0].condition.is_none();
+        QueryResult {
+            value: values[0].filter(|_| bare),
+            bare,
+            holds,
+        }
+    }
+}
+
+/// Result of a query, printed as the value of a single bare aggregate or as `true` or `false`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueryResult {
+    /// Value of the aggregate of a query without comparison, `None` if no case has a value
+    pub value: Option<f64>,
+    pub holds: bool,
+    bare: bool,
+}
+
+impl Display for QueryResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.value {
+            Some(value) => write!(f, "{value}"),
+            None if self.bare => write!(f, "-"),
+            None => write!(f, "{}", self.holds),
+        }
+    }
+}
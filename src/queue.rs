@@ -0,0 +1,45 @@
+use std::process::Command;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Deserializer};
+
+/// Date and time format of the planned start of a case
+pub const START_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Returns the expected start time of a queued SLURM job
+///
+/// Parses the output of `squeue -h -j <job> -o %S`, which is `N/A`
+/// when the scheduler has no estimate yet
+pub fn expected_start(job: &str) -> Option<DateTime<Local>> {
+    let output = Command::new("squeue")
+        .args(["-h", "-j", job, "-o", "%S"])
+        .output()
+        .inspect_err(|e| tracing::debug!(job, error = %e, "failed to run squeue"))
+        .ok()?;
+    if !output.status.success() {
+        tracing::debug!(job, status = %output.status, "squeue failed");
+        return None;
+    }
+    let start = String::from_utf8(output.stdout).ok()?;
+    let start = NaiveDateTime::parse_from_str(start.trim(), "%Y-%m-%dT%H:%M:%S").ok()?;
+    Local.from_local_datetime(&start).earliest()
+}
+
+/// Deserializes an optional local date and time formatted as [START_FORMAT]
+pub fn deserialize_start<'de, D>(deserializer: D) -> Result<Option<DateTime<Local>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|start| {
+            NaiveDateTime::parse_from_str(&start, START_FORMAT)
+                .ok()
+                .and_then(|start| Local.from_local_datetime(&start).earliest())
+                .ok_or_else(|| {
+                    serde::de::Error::custom(format!(
+                        "invalid start {start:?}, expected YYYY-MM-DD HH:MM"
+                    ))
+                })
+        })
+        .transpose()
+}
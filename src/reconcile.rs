@@ -0,0 +1,211 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Config, GlobSource};
+
+/// Extension of the solver logs looked for in the case directories
+const LOG_EXTENSION: &str = "out";
+
+/// Inconsistency between the configuration, the persisted state and the case directories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// Completion record or run database entry of a case no longer configured
+    Orphan { case: String, store: &'static str },
+    /// Directory of the root with solver logs that no case is configured for
+    NewCase { dir: PathBuf },
+    /// Log of a case missing while its directory has other solver logs
+    MovedLog {
+        case: String,
+        log: String,
+        found: Vec<PathBuf>,
+    },
+}
+
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Orphan { case, store } => write!(f, "orphan: {case} in the {store}"),
+            Self::NewCase { dir } => write!(f, "new case: {} is not configured", dir.display()),
+            Self::MovedLog { case, log, found } => write!(
+                f,
+                "moved log: {case} has no {log} but {}",
+                found
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// Comparison of the configured cases with the completion records, the run database
+/// and the directories of the root, run at startup so long-lived deployments
+/// do not accumulate stale state
+#[derive(Debug, Clone, Default)]
+pub struct Reconciliation {
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl Reconciliation {
+    /// Reconciles the configuration with the completion records and the case directories
+    pub fn new(config: &Config) -> Self {
+        let configured = configured(config);
+        let mut discrepancies: Vec<_> = recorded_cases(&config.records)
+            .into_iter()
+            .filter(|name| !configured.contains(name.as_str()))
+            .map(|case| Discrepancy::Orphan {
+                case,
+                store: "records",
+            })
+            .collect();
+        let mut dirs: Vec<_> = fs::read_dir(&config.root)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|dir| dir.is_dir() && !logs(dir).is_empty())
+            .filter(|dir| {
+                dir.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_none_or(|name| !configured.contains(name))
+            })
+            .collect();
+        dirs.sort();
+        discrepancies.extend(dirs.into_iter().map(|dir| Discrepancy::NewCase { dir }));
+        for case_config in &config.cases {
+            let case = config.case(case_config);
+            // remote logs, log objects, globs and cases waiting in the queue may have no log yet
+            if case_config.log_command.is_some()
+                || case_config.log_host.is_some()
+                || case_config.log_object.is_some()
+                || GlobSource::is_glob(&case_config.log)
+                || case.is_pending()
+                || Path::new(&case.log_file()).exists()
+            {
+                continue;
+            }
+            let found = logs(case.case_dir());
+            if !found.is_empty() {
+                discrepancies.push(Discrepancy::MovedLog {
+                    case: case_config.name.clone(),
+                    log: case_config.log.clone(),
+                    found,
+                });
+            }
+        }
+        Self { discrepancies }
+    }
+    /// Adds the run database entries of the cases no longer configured
+    #[cfg(feature = "db")]
+    pub fn with_database(
+        mut self,
+        config: &Config,
+        database: &crate::RunDatabase,
+    ) -> Result<Self, crate::DbError> {
+        let configured = configured(config);
+        self.discrepancies.extend(
+            database
+                .case_names()?
+                .into_iter()
+                .filter(|name| !configured.contains(name.as_str()))
+                .map(|case| Discrepancy::Orphan {
+                    case,
+                    store: "database",
+                }),
+        );
+        Ok(self)
+    }
+    /// Returns true if the configuration, the state and the filesystem agree
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+    /// Returns the names of the orphaned cases of a store
+    pub fn orphans(&self, store: &str) -> Vec<&str> {
+        self.discrepancies
+            .iter()
+            .filter_map(|discrepancy| match discrepancy {
+                Discrepancy::Orphan { case, store: s } if *s == store => Some(case.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+    /// Removes the orphaned records from the completion records file,
+    /// returns the number of records removed
+    pub fn prune_records<P: AsRef<Path>>(&self, path: P) -> io::Result<usize> {
+        let orphans = self.orphans("records");
+        if orphans.is_empty() {
+            return Ok(0);
+        }
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut records: toml::Table = toml::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e: toml::de::Error| invalid(e.to_string()))?;
+        let Some(toml::Value::Array(completed)) = records.get_mut("completed") else {
+            return Ok(0);
+        };
+        let before = completed.len();
+        completed.retain(|record| {
+            record
+                .get("name")
+                .and_then(|name| name.as_str())
+                .is_none_or(|name| !orphans.contains(&name))
+        });
+        let removed = before - completed.len();
+        fs::write(
+            path,
+            toml::to_string(&records).map_err(|e| invalid(e.to_string()))?,
+        )?;
+        Ok(removed)
+    }
+}
+
+/// Returns the names of the configured cases, monitored or planned
+fn configured(config: &Config) -> HashSet<&str> {
+    config
+        .cases
+        .iter()
+        .chain(&config.planned)
+        .map(|case| case.name.as_str())
+        .collect()
+}
+
+/// Returns the names of the cases of the completion records file, none if it does not exist
+fn recorded_cases<P: AsRef<Path>>(path: P) -> Vec<String> {
+    let Ok(records) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    let records: toml::Table = match toml::from_str(&records) {
+        Ok(records) => records,
+        Err(e) => {
+            tracing::warn!(error = %e, "invalid completion records file");
+            return vec![];
+        }
+    };
+    let mut names: Vec<String> = records
+        .get("completed")
+        .and_then(|completed| completed.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|record| record.get("name")?.as_str().map(str::to_string))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Returns the solver logs of a directory, in name order
+fn logs<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
+    let mut logs: Vec<_> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == LOG_EXTENSION))
+        .collect();
+    logs.sort();
+    logs
+}
@@ -0,0 +1,67 @@
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::{Case, Environment};
+
+/// Record of a completed case, appended to the completion records file
+///
+/// ```toml
+/// [[completed]]
+/// name = "zen30az045_OS2"
+/// duration = 1200
+/// ...
+/// [completed.environment]
+/// hosts = ["compute-[001-004]"]
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionRecord {
+    pub name: String,
+    pub group: Option<String>,
+    pub duration: usize,
+    pub log: String,
+    pub cores: Option<usize>,
+    pub steps: Option<usize>,
+    pub started: Option<DateTime<Local>>,
+    pub completed: Option<DateTime<Local>>,
+    pub runtime_secs: Option<i64>,
+    /// Mean time step duration in seconds
+    pub step_time: Option<f64>,
+    pub core_hours: Option<f64>,
+    pub environment: Environment,
+}
+
+impl From<&Case> for CompletionRecord {
+    fn from(case: &Case) -> Self {
+        Self {
+            name: case.name().to_string(),
+            group: case.group_name().map(str::to_string),
+            duration: case.duration(),
+            log: case.log_file(),
+            cores: case.cores(),
+            steps: case.step(),
+            started: case.start(),
+            completed: case.completion(),
+            runtime_secs: case.runtime_secs(),
+            step_time: Some(case.elapsed_per_step().mean()).filter(|secs| *secs > 0.),
+            core_hours: case.consumed_core_hours(),
+            environment: case.environment(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Records<'a> {
+    completed: [&'a CompletionRecord; 1],
+}
+
+impl CompletionRecord {
+    /// Appends the record to a TOML file as a `[[completed]]` table
+    pub fn append<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let record = toml::to_string(&Records { completed: [self] })
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{record}")
+    }
+}
@@ -0,0 +1,127 @@
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Host names and IP addresses, replaced by the `hostnames` rule
+const HOSTNAMES: &str = r"(?i)\b(?:\d{1,3}(?:\.\d{1,3}){3}|ip(?:-\d{1,3}){4}(?:\.[a-z0-9-]+)*|(?:node|compute|cn|gpu|login|head)[a-z0-9-]*\d+(?:\.[a-z0-9-]+)*|(?:[a-z0-9-]+\.)+(?:internal|local|lan|corp))\b";
+/// Absolute paths, replaced by the `paths` rule keeping the file name
+const PATHS: &str = r"(?:/[\w.+-]+)+/([\w.+-]+)";
+
+/// Rule replacing the matches of a pattern in shared outputs
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RedactionRuleConfig")]
+pub struct RedactionRule {
+    pattern: Regex,
+    replacement: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedactionRuleConfig {
+    pattern: String,
+    #[serde(default = "default_replacement")]
+    replacement: String,
+}
+
+fn default_replacement() -> String {
+    "<redacted>".to_string()
+}
+
+impl TryFrom<RedactionRuleConfig> for RedactionRule {
+    type Error = regex::Error;
+
+    fn try_from(config: RedactionRuleConfig) -> Result<Self, Self::Error> {
+        Self::new(&config.pattern, config.replacement)
+    }
+}
+
+impl RedactionRule {
+    /// Creates a new rule, `replacement` may refer to the capture groups of `pattern` as `$1`
+    pub fn new<S: ToString>(pattern: &str, replacement: S) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Regex::new(pattern)?,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+/// Redaction of the status shared outside of the project network
+///
+/// ```toml
+/// [redaction]
+/// hostnames = true
+/// paths = true
+/// hooks = true
+///
+/// [[redaction.rules]]
+/// pattern = 'gmto-\w+'
+/// replacement = "<project>"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RedactionConfig {
+    /// Replaces host names and IP addresses
+    #[serde(default)]
+    pub hostnames: bool,
+    /// Replaces the directories of absolute paths
+    #[serde(default)]
+    pub paths: bool,
+    /// Redacts the environment variables passed to the hooks too
+    #[serde(default)]
+    pub hooks: bool,
+    /// Additional rules, applied after the built-in ones
+    #[serde(default)]
+    pub rules: Vec<RedactionRule>,
+}
+
+/// Redactor applying the redaction rules in order
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    rules: Vec<RedactionRule>,
+}
+
+impl From<&RedactionConfig> for Redactor {
+    fn from(config: &RedactionConfig) -> Self {
+        let mut rules = vec![];
+        if config.hostnames {
+            rules.push(RedactionRule::new(HOSTNAMES, "<host>").unwrap());
+        }
+        if config.paths {
+            rules.push(RedactionRule::new(PATHS, "<path>/$1").unwrap());
+        }
+        rules.extend(config.rules.iter().cloned());
+        Self { rules }
+    }
+}
+
+impl Redactor {
+    /// Creates a new redactor from a list of rules
+    pub fn new(rules: Vec<RedactionRule>) -> Self {
+        Self { rules }
+    }
+    /// Returns true if the redactor has no rule
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+    /// Returns the text with all the rules applied
+    pub fn redact(&self, text: &str) -> String {
+        self.rules.iter().fold(text.to_string(), |text, rule| {
+            rule.pattern
+                .replace_all(&text, rule.replacement.as_str())
+                .into_owned()
+        })
+    }
+    /// Redacts all the strings of a JSON value, object keys excepted
+    pub fn redact_value(&self, value: Value) -> Value {
+        match value {
+            Value::String(text) => Value::String(self.redact(&text)),
+            Value::Array(values) => {
+                Value::Array(values.into_iter().map(|v| self.redact_value(v)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, v)| (key, self.redact_value(v)))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::{io, path::PathBuf};
+
+use chrono::Local;
+
+use crate::{shell, LogRelocation, LogSource};
+
+/// Command the remote logs are read through by default, failing instead of prompting for a password
+pub const SSH_COMMAND: &str = "ssh -o BatchMode=yes";
+/// Exit status of the remote scripts if the log is missing or unreadable
+const MISSING: i32 = 3;
+
+/// Log on a remote host read over SSH, e.g. `login01:/scratch/zen30/solve.out`
+///
+/// Only the bytes appended since the previous read are fetched, from the offset of the last
+/// complete line, and the log is read again from its beginning if it shrinks or is replaced,
+/// e.g. rotated. The last matching lines are searched for on the remote host,
+/// a multi-GB log going over the wire whole only at the first read and once replaced.
+/// An unreachable host or a failing remote script is an error, unlike a missing log
+///
+/// The remote host needs a POSIX shell and `head -c`, e.g. Linux or macOS, the last matching
+/// lines being searched for from the end of the log with `tac` where it is installed
+#[derive(Debug, Clone)]
+pub struct RemoteSource {
+    host: String,
+    path: String,
+    ssh: String,
+    /// Offset of the end of the last complete line read
+    offset: u64,
+    /// Inode of the log at the previous read
+    inode: Option<u64>,
+    exists: bool,
+    /// True until the log is found, and while the remote script finds it missing
+    missing: bool,
+    fetched: u64,
+    relocations: Vec<LogRelocation>,
+}
+
+impl RemoteSource {
+    /// Creates a new source reading the log at `path` on `host`, e.g. `login01` or `user@login01`
+    pub fn new<S: ToString>(host: S, path: S) -> Self {
+        Self {
+            host: host.to_string(),
+            path: path.to_string(),
+            ssh: SSH_COMMAND.to_string(),
+            offset: 0,
+            inode: None,
+            exists: false,
+            missing: true,
+            fetched: 0,
+            relocations: vec![],
+        }
+    }
+    /// Sets the command the log is read through, [SSH_COMMAND] by default,
+    /// called with `--`, the host and the remote script
+    pub fn ssh<S: ToString>(mut self, command: S) -> Self {
+        self.ssh = command.to_string();
+        self
+    }
+    /// Returns the number of bytes fetched from the remote host so far
+    pub fn fetched(&self) -> u64 {
+        self.fetched
+    }
+    /// Runs a script on the remote host with `$f` the path of the log and returns its output,
+    /// `None` if the log is missing, an error if the host is unreachable or the script fails
+    fn run(&mut self, script: &str) -> io::Result<Option<Vec<u8>>> {
+        let script = format!(
+            "f={}; [ -r \"$f\" ] || exit {MISSING}; {script}",
+            quote(&self.path)
+        );
+        // the host cannot be taken for an option of ssh
+        let command = format!("{} -- {} {}", self.ssh, quote(&self.host), quote(&script));
+        let output = shell(&command).output()?;
+        self.fetched += output.stdout.len() as u64;
+        self.exists = output.status.success();
+        self.missing = output.status.code() == Some(MISSING);
+        if self.exists {
+            Ok(Some(output.stdout))
+        } else if self.missing {
+            tracing::debug!(host = self.host, path = self.path, "no remote log");
+            Ok(None)
+        } else {
+            Err(io::Error::other(format!(
+                "remote log read of {}:{} failed with {}: {}",
+                self.host,
+                self.path,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+/// Returns a string quoted for a POSIX shell
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+impl LogSource for RemoteSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        // the log is read from its beginning if shrunk or replaced, the bytes read being
+        // prefixed with its size, its inode and the offset they start at
+        let script = format!(
+            "set -- $(wc -c < \"$f\") $(ls -iLd -- \"$f\"); o={}; \
+            [ \"$2\" = '{}' ] && [ \"$1\" -ge \"$o\" ] || o=0; echo \"$1 $2 $o\"; \
+            tail -c +$((o + 1)) -- \"$f\" | head -c $(($1 - o))",
+            self.offset,
+            self.inode.map_or(String::new(), |inode| inode.to_string())
+        );
+        let Some(output) = self.run(&script)? else {
+            return Ok(vec![]);
+        };
+        let (header, bytes) = output
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or((&output[..], &[][..]), |i| (&output[..i], &output[i + 1..]));
+        let header = String::from_utf8_lossy(header);
+        let mut fields = header.split_whitespace().skip(1).map(str::parse::<u64>);
+        let (Some(Ok(inode)), Some(Ok(start))) = (fields.next(), fields.next()) else {
+            return Err(io::Error::other(format!(
+                "unexpected remote log header {header:?} of {}:{}",
+                self.host, self.path
+            )));
+        };
+        if start < self.offset {
+            let replaced = self.inode.is_some_and(|previous| previous != inode);
+            tracing::warn!(
+                host = self.host,
+                path = self.path,
+                replaced,
+                "remote log shrunk or replaced, read again"
+            );
+            if replaced {
+                self.relocations.push(LogRelocation {
+                    at: Local::now(),
+                    from: PathBuf::from(&self.path),
+                    to: PathBuf::from(&self.path),
+                    replaced,
+                    switched: false,
+                });
+            }
+        }
+        self.inode = Some(inode);
+        // a partial last line is read again at the next call
+        let end = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        self.offset = start + end as u64;
+        Ok(String::from_utf8_lossy(&bytes[..end])
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect())
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        let pattern = quote(pattern);
+        let script = format!(
+            "if command -v tac > /dev/null; then tac -- \"$f\" | grep -m 1 -F -e {pattern}; \
+            else grep -F -e {pattern} -- \"$f\" | tail -n 1; fi; exit 0"
+        );
+        let output = self.run(&script)?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no log {}:{}", self.host, self.path),
+            )
+        })?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .next()
+            .map(|line| line.trim_end().to_string()))
+    }
+    fn exists(&self) -> bool {
+        self.exists
+    }
+    fn is_missing(&self) -> bool {
+        self.missing
+    }
+    fn relocations(&mut self) -> Vec<LogRelocation> {
+        std::mem::take(&mut self.relocations)
+    }
+}
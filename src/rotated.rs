@@ -0,0 +1,175 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use flate2::read::GzDecoder;
+
+/// Size in bytes of the blocks an uncompressed log is searched backwards by
+const SEARCH_BLOCK: u64 = 64 * 1024;
+
+/// Returns true if the file is gzip-compressed, according to its extension
+pub fn is_gzip<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().extension().is_some_and(|ext| ext == "gz")
+}
+
+/// Opens a log file, decompressing it on the fly if it is gzip-compressed
+pub fn open_log<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(&path)?;
+    Ok(if is_gzip(&path) {
+        Box::new(BufReader::new(GzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    })
+}
+
+/// Returns the log file followed by its rotated versions, from the newest to the oldest
+///
+/// The rotated versions of `solve.out` are `solve.out.1`, `solve.out.2.gz`, ...,
+/// `solve.out.gz` being the compressed log itself
+pub fn rotated_logs<P: AsRef<Path>>(log: P) -> Vec<PathBuf> {
+    let log = log.as_ref();
+    let (Some(dir), Some(name)) = (log.parent(), log.file_name().and_then(|n| n.to_str())) else {
+        return vec![log.to_path_buf()];
+    };
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    let mut rotated: Vec<(usize, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_str()?;
+            let suffix = file_name.strip_prefix(name)?;
+            let suffix = suffix.strip_suffix(".gz").unwrap_or(suffix);
+            let index = match suffix {
+                "" => 0,
+                suffix => suffix.strip_prefix('.')?.parse().ok()?,
+            };
+            Some((index, entry.path()))
+        })
+        .collect();
+    if rotated.is_empty() {
+        return vec![log.to_path_buf()];
+    }
+    rotated.sort();
+    rotated.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Returns the last line containing `pattern` in the log and its rotated versions,
+/// looking into older versions only if the newer ones have none
+pub fn last_matching_line<P: AsRef<Path>>(log: P, pattern: &str) -> io::Result<Option<String>> {
+    RotatedMatches::default().last_matching_line(log, pattern)
+}
+
+/// Last lines containing the patterns looked for in the versions of a log, by size and modification time,
+/// so the rotated versions, which do not change anymore, and the compressed ones in particular,
+/// are read only once
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RotatedMatches {
+    found: HashMap<(PathBuf, String), (Stat, Option<String>)>,
+}
+
+/// Size and modification time of a log file
+type Stat = (u64, SystemTime);
+
+impl RotatedMatches {
+    /// Returns the last line containing `pattern` in the log and its rotated versions,
+    /// looking into older versions only if the newer ones have none
+    pub(crate) fn last_matching_line<P: AsRef<Path>>(
+        &mut self,
+        log: P,
+        pattern: &str,
+    ) -> io::Result<Option<String>> {
+        let logs: Vec<_> = rotated_logs(log)
+            .into_iter()
+            .filter(|log| log.exists())
+            .collect();
+        if logs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "log file not found",
+            ));
+        }
+        self.found.retain(|(path, _), _| logs.contains(path));
+        for log in logs {
+            let metadata = fs::metadata(&log)?;
+            let stat = (metadata.len(), metadata.modified()?);
+            let key = (log, pattern.to_string());
+            let last = match self.found.get(&key) {
+                Some((found, last)) if *found == stat => last.clone(),
+                _ => {
+                    let last = last_line_in(&key.0, pattern)?;
+                    self.found.insert(key, (stat, last.clone()));
+                    last
+                }
+            };
+            if last.is_some() {
+                return Ok(last);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the last line of a log file containing `pattern`, decompressing it if it is gzip-compressed
+fn last_line_in(log: &Path, pattern: &str) -> io::Result<Option<String>> {
+    if !is_gzip(log) {
+        return last_line_containing(log, pattern);
+    }
+    let mut last = None;
+    for line in open_log(log)?.lines() {
+        let line = line?;
+        if line.contains(pattern) {
+            last = Some(line);
+        }
+    }
+    Ok(last)
+}
+
+/// Returns the last line of an uncompressed file containing `pattern`, reading the file
+/// backwards so only the end of a long log is read if the pattern is found there
+fn last_line_containing(path: &Path, pattern: &str) -> io::Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let mut end = file.metadata()?.len();
+    // beginning of the line cut by the start of the block read last
+    let mut cut = vec![];
+    while end > 0 {
+        let start = end.saturating_sub(SEARCH_BLOCK);
+        let mut block = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.append(&mut cut);
+        // the first line of the block is complete only at the start of the file
+        let first = match block.iter().position(|byte| *byte == b'\n') {
+            _ if start == 0 => 0,
+            Some(i) => i + 1,
+            None => {
+                cut = block;
+                end = start;
+                continue;
+            }
+        };
+        let line = block[first..]
+            .rsplit(|byte| *byte == b'\n')
+            .map(String::from_utf8_lossy)
+            .find(|line| line.contains(pattern));
+        if let Some(line) = line {
+            return Ok(Some(line.trim_end().to_string()));
+        }
+        block.truncate(first.saturating_sub(1));
+        cut = block;
+        end = start;
+    }
+    Ok(None)
+}
+
+/// Returns the oldest of the log and its rotated versions
+pub fn oldest_log<P: AsRef<Path>>(log: P) -> PathBuf {
+    rotated_logs(&log)
+        .pop()
+        .unwrap_or_else(|| log.as_ref().to_path_buf())
+}
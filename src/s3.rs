@@ -0,0 +1,323 @@
+use std::{
+    env,
+    fmt::Debug,
+    io::{self, Read},
+};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::LogSource;
+
+/// Size in bytes of the tail of the log read at the first read, the older lines being skipped
+const INITIAL_TAIL: u64 = 4 << 20;
+/// Size in bytes of the ranges read backwards from the end of the log looking for a line,
+/// small as the time step lines are close to the end
+const CHUNK: u64 = 64 << 10;
+/// Region of the buckets unless `AWS_REGION` is set
+const REGION: &str = "us-east-1";
+
+#[derive(Debug, thiserror::Error)]
+pub enum S3Error {
+    #[error("invalid S3 URL {0:?}, expected s3://bucket/key")]
+    Url(String),
+}
+
+/// Credentials of the requests, from the environment
+#[derive(Clone)]
+struct Credentials {
+    access_key: String,
+    secret_key: String,
+    token: Option<String>,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key", &self.access_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Credentials {
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            access_key: env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_key: env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            token: env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+/// Part of a log object
+struct Range {
+    bytes: Vec<u8>,
+    /// Offset of the first byte of the range
+    start: u64,
+    /// Size of the whole object
+    size: u64,
+}
+
+/// Log object on S3 or on an S3-compatible object storage, read with ranged GETs,
+/// e.g. `s3://cfd-runs/zen30az045_OS2/solve-672_14.out`
+///
+/// The credentials, region and endpoint come from the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`,
+/// `AWS_SESSION_TOKEN`, `AWS_REGION` and `AWS_ENDPOINT_URL` environment variables,
+/// the requests being anonymous without credentials.
+/// The first read starts at the last 4MiB of the log
+#[derive(Debug, Clone)]
+pub struct S3Source {
+    bucket: String,
+    key: String,
+    region: String,
+    endpoint: Option<String>,
+    credentials: Option<Credentials>,
+    offset: Option<u64>,
+    exists: bool,
+}
+
+impl S3Source {
+    /// Creates a new source reading the log object at the `s3://bucket/key` URL
+    pub fn new(url: &str) -> Result<Self, S3Error> {
+        let (bucket, key) = url
+            .strip_prefix("s3://")
+            .and_then(|path| path.split_once('/'))
+            .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+            .ok_or_else(|| S3Error::Url(url.to_string()))?;
+        Ok(Self {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            region: env::var("AWS_REGION")
+                .or_else(|_| env::var("AWS_DEFAULT_REGION"))
+                .unwrap_or_else(|_| REGION.to_string()),
+            endpoint: env::var("AWS_ENDPOINT_URL").ok(),
+            credentials: Credentials::from_env(),
+            offset: None,
+            exists: false,
+        })
+    }
+    /// Returns the URL, the host and the path of the object,
+    /// in the path style for a custom endpoint
+    fn location(&self) -> (String, String, String) {
+        let key = uri_encode(&self.key);
+        match &self.endpoint {
+            Some(endpoint) => {
+                let endpoint = endpoint.trim_end_matches('/');
+                let host = endpoint
+                    .split_once("://")
+                    .map_or(endpoint, |(_, host)| host)
+                    .to_string();
+                let path = format!("/{}/{key}", self.bucket);
+                (format!("{endpoint}{path}"), host, path)
+            }
+            None => {
+                let host = format!("{}.s3.{}.amazonaws.com", self.bucket, self.region);
+                let path = format!("/{key}");
+                (format!("https://{host}{path}"), host, path)
+            }
+        }
+    }
+    /// Returns the headers of a request signed with AWS Signature Version 4
+    fn signed_headers(
+        &self,
+        credentials: &Credentials,
+        host: &str,
+        path: &str,
+        range: &str,
+        now: DateTime<Utc>,
+    ) -> Vec<(&'static str, String)> {
+        let (date, amz_date) = (
+            now.format("%Y%m%d").to_string(),
+            now.format("%Y%m%dT%H%M%SZ").to_string(),
+        );
+        // the body of a GET is empty
+        let payload = hex(&Sha256::digest(b""));
+        let mut headers = vec![
+            ("host", host.to_string()),
+            ("range", range.to_string()),
+            ("x-amz-content-sha256", payload.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &credentials.token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let request = format!("GET\n{path}\n\n{canonical}\n{signed}\n{payload}");
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex(&Sha256::digest(request.as_bytes()))
+        );
+        let key = [date.as_str(), &self.region, "s3", "aws4_request"]
+            .into_iter()
+            .fold(
+                format!("AWS4{}", credentials.secret_key).into_bytes(),
+                |key, data| hmac(&key, data),
+            );
+        headers.push((
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed}, Signature={}",
+                credentials.access_key,
+                hex(&hmac(&key, &string_to_sign))
+            ),
+        ));
+        headers.retain(|(name, _)| *name != "host");
+        headers
+    }
+    /// Reads a range of the object, as in a `Range` header, `None` if the object does not exist
+    fn get(&mut self, range: &str) -> io::Result<Option<Range>> {
+        let (url, host, path) = self.location();
+        let request = ureq::get(&url);
+        let request = match &self.credentials {
+            Some(credentials) => self
+                .signed_headers(credentials, &host, &path, range, Utc::now())
+                .into_iter()
+                .fold(request, |request, (name, value)| request.set(name, &value)),
+            None => request.set("range", range),
+        };
+        let response = match request.call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => {
+                self.exists = false;
+                return Ok(None);
+            }
+            // nothing past the end of the object
+            Err(ureq::Error::Status(416, response)) => {
+                self.exists = true;
+                let size = content_range(&response).map_or(0, |(_, size)| size);
+                return Ok(Some(Range {
+                    bytes: vec![],
+                    start: size,
+                    size,
+                }));
+            }
+            Err(e) => {
+                tracing::debug!(bucket = self.bucket, key = self.key, error = %e, "log object request failed");
+                return Err(io::Error::other(e));
+            }
+        };
+        self.exists = true;
+        let content_range = content_range(&response);
+        let mut bytes = vec![];
+        response.into_reader().read_to_end(&mut bytes)?;
+        let (start, size) = content_range.unwrap_or((0, bytes.len() as u64));
+        Ok(Some(Range { bytes, start, size }))
+    }
+}
+
+impl LogSource for S3Source {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        let range = match self.offset {
+            Some(offset) => format!("bytes={offset}-"),
+            None => format!("bytes=-{INITIAL_TAIL}"),
+        };
+        let Some(range) = self.get(&range)? else {
+            return Ok(vec![]);
+        };
+        if self.offset.is_some_and(|offset| range.size < offset) {
+            // the object was replaced by a shorter one, e.g. on a restart
+            self.offset = Some(0);
+            return self.read_new();
+        }
+        let mut bytes = &range.bytes[..];
+        let mut offset = range.start;
+        if self.offset.is_none() && range.start > 0 {
+            // the tail of the log starts within a line
+            let skip = bytes
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(bytes.len(), |i| i + 1);
+            bytes = &bytes[skip..];
+            offset += skip as u64;
+        }
+        // a partial last line is read again at the next call
+        let end = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        self.offset = Some(offset + end as u64);
+        Ok(String::from_utf8_lossy(&bytes[..end])
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect())
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        let not_found = || io::Error::new(io::ErrorKind::NotFound, "no log object");
+        let mut range = self
+            .get(&format!("bytes=-{CHUNK}"))?
+            .ok_or_else(not_found)?;
+        // beginning of the first line of the previous range, ending in the current one
+        let mut carry = vec![];
+        loop {
+            let mut bytes = range.bytes;
+            bytes.extend(carry);
+            let split = if range.start > 0 {
+                bytes
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .map_or(bytes.len(), |i| i + 1)
+            } else {
+                0
+            };
+            let line = String::from_utf8_lossy(&bytes[split..])
+                .lines()
+                .rev()
+                .find(|line| line.contains(pattern))
+                .map(str::to_string);
+            if line.is_some() || range.start == 0 {
+                return Ok(line);
+            }
+            carry = bytes[..split].to_vec();
+            let end = range.start;
+            let start = end.saturating_sub(CHUNK);
+            range = self
+                .get(&format!("bytes={start}-{}", end - 1))?
+                .ok_or_else(not_found)?;
+        }
+    }
+    fn exists(&self) -> bool {
+        self.exists
+    }
+}
+
+/// Returns the offset of the first byte and the size of the object of a `Content-Range` header
+fn content_range(response: &ureq::Response) -> Option<(u64, u64)> {
+    let (range, size) = response
+        .header("content-range")?
+        .strip_prefix("bytes ")?
+        .split_once('/')?;
+    let start = match range {
+        "*" => size.parse().ok()?,
+        range => range.split_once('-')?.0.parse().ok()?,
+    };
+    Some((start, size.parse().ok()?))
+}
+
+/// Percent-encodes an object key, keeping the unreserved characters and the slashes
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            b => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::Print,
+    terminal::{Clear, ClearType},
+};
+
+/// Terminal screen updated by diffing successive frames
+///
+/// Only the lines that changed since the previous frame are rewritten,
+/// moving the cursor to them instead of clearing the whole terminal
+#[derive(Debug, Default)]
+pub struct Screen {
+    previous: Option<Vec<String>>,
+}
+
+impl Screen {
+    /// Creates a new screen
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Draws a frame, writing only the lines that differ from the previous frame
+    pub fn draw<W: Write>(&mut self, w: &mut W, frame: &str) -> io::Result<()> {
+        let lines: Vec<String> = frame.lines().map(String::from).collect();
+        let previous = match self.previous.take() {
+            Some(previous) => previous,
+            None => {
+                queue!(w, Clear(ClearType::All), MoveTo(0, 0))?;
+                vec![]
+            }
+        };
+        for (i, line) in lines.iter().enumerate() {
+            if previous.get(i) != Some(line) {
+                queue!(
+                    w,
+                    MoveTo(0, row(i)),
+                    Print(line),
+                    Clear(ClearType::UntilNewLine)
+                )?;
+            }
+        }
+        if previous.len() > lines.len() {
+            queue!(
+                w,
+                MoveTo(0, row(lines.len())),
+                Clear(ClearType::FromCursorDown)
+            )?;
+        }
+        queue!(w, MoveTo(0, row(lines.len())))?;
+        w.flush()?;
+        self.previous = Some(lines);
+        Ok(())
+    }
+    /// Forces a full redraw at the next frame
+    pub fn invalidate(&mut self) {
+        self.previous = None;
+    }
+}
+
+/// Returns the terminal row of the line at `index` of a frame
+fn row(index: usize) -> u16 {
+    index.try_into().unwrap_or(u16::MAX)
+}
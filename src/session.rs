@@ -0,0 +1,348 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{format_hours_minutes, format_signed_hours_minutes, MonitorEvent, StatusSnapshot};
+
+/// Entry of a recorded monitoring session, one JSON line per entry
+///
+/// ```json
+/// {"kind":"event","at":"2024-06-14T18:00:00+02:00","event":"stalled","case":"zen30az045_OS2","detail":null}
+/// {"kind":"snapshot","at":"2024-06-14T18:00:01+02:00","board":"...","compact":"...","status":{...}}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionEntry {
+    /// Monitor event
+    Event {
+        at: DateTime<Local>,
+        event: String,
+        case: String,
+        detail: Option<String>,
+    },
+    /// Status board as rendered at an update
+    Snapshot {
+        at: DateTime<Local>,
+        board: String,
+        compact: String,
+        status: serde_json::Value,
+    },
+}
+
+impl SessionEntry {
+    /// Returns the time the entry was recorded at
+    pub fn at(&self) -> DateTime<Local> {
+        match self {
+            Self::Event { at, .. } | Self::Snapshot { at, .. } => *at,
+        }
+    }
+    /// Returns the entry of a monitor event, `None` for a snapshot
+    pub fn event(event: &MonitorEvent) -> Option<Self> {
+        let (event, case, detail) = match event {
+            MonitorEvent::Updated(case) => ("updated", case, case.step().map(|s| s.to_string())),
+            MonitorEvent::Stalled(case) => ("stalled", case, None),
+            MonitorEvent::SolverDead(case) => ("solver_dead", case, None),
+            MonitorEvent::Completed(case) => ("completed", case, None),
+            MonitorEvent::SlowInterval(case) => (
+                "slow_interval",
+                case,
+                case.slow_intervals()
+                    .last()
+                    .map(|interval| interval.to_string()),
+            ),
+            MonitorEvent::DtChanged(case) => (
+                "dt_changed",
+                case,
+                case.dt_changes().last().map(|change| change.to_string()),
+            ),
+            MonitorEvent::LogMoved(case) => (
+                "log_moved",
+                case,
+                case.log_relocations()
+                    .last()
+                    .map(|relocation| relocation.to_string()),
+            ),
+            MonitorEvent::DiskAtRisk(case) => ("disk_at_risk", case, None),
+            MonitorEvent::Error {
+                case,
+                error,
+                failures,
+            } => ("error", case, Some(format!("{error} ({failures}x)"))),
+            MonitorEvent::Snapshot(_) => return None,
+        };
+        Some(Self::Event {
+            at: Local::now(),
+            event: event.to_string(),
+            case: case.name().to_string(),
+            detail,
+        })
+    }
+}
+
+/// Recorder appending every snapshot and event of a monitoring session to a file
+pub struct SessionRecorder {
+    file: File,
+}
+
+impl SessionRecorder {
+    /// Opens the session file at `path`, appending to it if it exists
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+    /// Records a monitor event, snapshots are recorded with [SessionRecorder::record_snapshot]
+    pub fn record_event(&mut self, event: &MonitorEvent) {
+        if let Some(entry) = SessionEntry::event(event) {
+            self.write(&entry);
+        }
+    }
+    /// Records the status board of a snapshot
+    pub fn record_snapshot(&mut self, snapshot: &StatusSnapshot) {
+        self.write(&SessionEntry::Snapshot {
+            at: snapshot.taken(),
+            board: snapshot.to_string(),
+            compact: snapshot.compact(),
+            status: serde_json::to_value(snapshot).expect("snapshot serialization failed"),
+        });
+    }
+    fn write(&mut self, entry: &SessionEntry) {
+        let line = serde_json::to_string(entry).expect("session entry serialization failed");
+        if let Err(e) = writeln!(self.file, "{line}") {
+            tracing::warn!(error = %e, "failed to record the session");
+        }
+    }
+}
+
+/// Reads the entries of a recorded session, in order
+pub fn read_session<P: AsRef<Path>>(path: P) -> io::Result<Vec<SessionEntry>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|line| {
+            serde_json::from_str(&line?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+/// Time format of the post-mortem report
+const REPORT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Event of a recorded session worth reviewing after the fact
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incident {
+    pub at: DateTime<Local>,
+    pub case: String,
+    pub event: String,
+    pub detail: Option<String>,
+    /// Time the case progressed or updated again, for stalls, dead solvers and update errors
+    pub resolved: Option<DateTime<Local>>,
+}
+
+impl Incident {
+    /// Returns true if the incident lasts until the case progresses or updates again
+    fn is_lasting(event: &str) -> bool {
+        matches!(event, "stalled" | "solver_dead" | "error")
+    }
+}
+
+/// ETAs of a case over a recorded session
+#[derive(Debug, Clone, PartialEq)]
+pub struct EtaEvolution {
+    pub case: String,
+    pub first: DateTime<Local>,
+    pub last: DateTime<Local>,
+    pub earliest: DateTime<Local>,
+    pub latest: DateTime<Local>,
+    pub completed: Option<DateTime<Local>>,
+}
+
+impl EtaEvolution {
+    /// Returns the drift of the ETA over the session in seconds, positive if it slipped
+    pub fn drift_secs(&self) -> i64 {
+        (self.completed.unwrap_or(self.last) - self.first).num_seconds()
+    }
+}
+
+/// Review of a recorded session: the incidents of the cases and how their ETAs evolved
+#[derive(Debug, Clone, Default)]
+pub struct PostMortem {
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+    pub snapshots: usize,
+    pub incidents: Vec<Incident>,
+    pub etas: Vec<EtaEvolution>,
+}
+
+impl PostMortem {
+    /// Reviews the entries of a recorded session, in order
+    pub fn new<'a>(entries: impl IntoIterator<Item = &'a SessionEntry>) -> Self {
+        let mut review = Self::default();
+        // latest time step of each case and its open incidents with the time step they started at
+        let mut steps: HashMap<&str, usize> = HashMap::new();
+        let mut open: HashMap<&str, Vec<(usize, Option<usize>)>> = HashMap::new();
+        for entry in entries {
+            review.from = review.from.or(Some(entry.at()));
+            review.to = Some(entry.at());
+            match entry {
+                SessionEntry::Event {
+                    at,
+                    event,
+                    case,
+                    detail,
+                } if event == "updated" => {
+                    let step = detail.as_deref().and_then(|step| step.parse().ok());
+                    if let Some(step) = step {
+                        steps.insert(case.as_str(), step);
+                    }
+                    // update errors end with a successful update, stalls with a new time step
+                    open.entry(case.as_str())
+                        .or_default()
+                        .retain(|&(i, since)| {
+                            let resolved = match review.incidents[i].event.as_str() {
+                                "error" => true,
+                                _ => step.zip(since).is_some_and(|(step, since)| step > since),
+                            };
+                            if resolved {
+                                review.incidents[i].resolved = Some(*at);
+                            }
+                            !resolved
+                        });
+                }
+                SessionEntry::Event {
+                    at,
+                    event,
+                    case,
+                    detail,
+                } => {
+                    if Incident::is_lasting(event) {
+                        open.entry(case.as_str())
+                            .or_default()
+                            .push((review.incidents.len(), steps.get(case.as_str()).copied()));
+                    }
+                    review.incidents.push(Incident {
+                        at: *at,
+                        case: case.clone(),
+                        event: event.clone(),
+                        detail: detail.clone(),
+                        resolved: None,
+                    });
+                }
+                SessionEntry::Snapshot { status, .. } => {
+                    review.snapshots += 1;
+                    review.update_etas(status);
+                }
+            }
+        }
+        review
+    }
+    fn update_etas(&mut self, status: &serde_json::Value) {
+        let time = |case: &serde_json::Value, key: &str| {
+            serde_json::from_value::<Option<DateTime<Local>>>(case.get(key)?.clone())
+                .ok()
+                .flatten()
+        };
+        for case in status["cases"].as_array().into_iter().flatten() {
+            let Some(name) = case["name"].as_str() else {
+                continue;
+            };
+            let position = self.etas.iter().position(|eta| eta.case == name);
+            let completed = time(case, "completed");
+            match (position, time(case, "eta")) {
+                (Some(i), eta) => {
+                    let evolution = &mut self.etas[i];
+                    if let Some(eta) = eta {
+                        evolution.last = eta;
+                        evolution.earliest = evolution.earliest.min(eta);
+                        evolution.latest = evolution.latest.max(eta);
+                    }
+                    evolution.completed = evolution.completed.or(completed);
+                }
+                (None, Some(eta)) => self.etas.push(EtaEvolution {
+                    case: name.to_string(),
+                    first: eta,
+                    last: eta,
+                    earliest: eta,
+                    latest: eta,
+                    completed,
+                }),
+                // cases already complete when the session started have no ETA to review
+                (None, None) => (),
+            }
+        }
+    }
+}
+
+impl Display for PostMortem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Some(from), Some(to)) = (self.from, self.to) else {
+            return writeln!(f, "empty session");
+        };
+        writeln!(
+            f,
+            "Session from {} to {} ({}), {} snapshots",
+            from.format(REPORT_FORMAT),
+            to.format(REPORT_FORMAT),
+            format_hours_minutes((to - from).num_seconds()),
+            self.snapshots
+        )?;
+        writeln!(f, "\nIncidents")?;
+        if self.incidents.is_empty() {
+            writeln!(f, "none")?;
+        }
+        for incident in &self.incidents {
+            write!(
+                f,
+                "{} {:<14}{:<20}",
+                incident.at.format(REPORT_FORMAT),
+                incident.event,
+                incident.case
+            )?;
+            if Incident::is_lasting(&incident.event) {
+                match incident.resolved {
+                    Some(resolved) => write!(
+                        f,
+                        " resolved after {}",
+                        format_hours_minutes((resolved - incident.at).num_seconds())
+                    )?,
+                    None => write!(f, " unresolved")?,
+                }
+            }
+            if let Some(detail) = &incident.detail {
+                write!(f, " {detail}")?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "\nETAs")?;
+        writeln!(
+            f,
+            "{:<20}{:>18}{:>18}{:>18}{:>18}{:>10}",
+            "Case", "First", "Earliest", "Latest", "Last", "Drift"
+        )?;
+        for eta in &self.etas {
+            let last = eta.completed.unwrap_or(eta.last);
+            writeln!(
+                f,
+                "{:<20}{:>18}{:>18}{:>18}{:>18}{:>10}{}",
+                eta.case,
+                eta.first.format(REPORT_FORMAT).to_string(),
+                eta.earliest.format(REPORT_FORMAT).to_string(),
+                eta.latest.format(REPORT_FORMAT).to_string(),
+                last.format(REPORT_FORMAT).to_string(),
+                format_signed_hours_minutes(eta.drift_secs()),
+                if eta.completed.is_some() {
+                    "  completed"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1004 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::{ser::SerializeStruct, Serialize};
+
+use crate::{
+    format_core_hours, format_hours_minutes, version_mismatches, Alert, AlertKind, BatchPlan,
+    BusinessCalendar, Case, CaseFilter, Checkpoint, ColumnSpec, DtChange, Environment, EtaConfig,
+    EtaStyle, HealthConfig, HealthIndex, LogFailure, LogRelocation, Note, OfflineInterval,
+    Percentiles, PollStats, Redactor, RefreshDeltas, RestartAdvice, SlowInterval, Stage, Theme,
+    Utilization,
+};
+
+/// State of all the cases at a given time
+#[derive(Debug, Clone)]
+pub struct StatusSnapshot {
+    taken: DateTime<Local>,
+    next_update: DateTime<Local>,
+    running: Vec<Case>,
+    completed: Vec<Case>,
+    alerts: Vec<Alert>,
+    calendar: Option<BusinessCalendar>,
+    paused: bool,
+    theme: Option<Theme>,
+    health: Option<HealthIndex>,
+    eta: EtaConfig,
+    planned: Vec<Case>,
+    slots: Option<usize>,
+    columns: ColumnSpec,
+    grouped: Option<bool>,
+    sequence: Option<u64>,
+    deltas: Option<RefreshDeltas>,
+    stats: Option<PollStats>,
+}
+
+impl StatusSnapshot {
+    /// Creates a new snapshot of the running and completed cases
+    pub fn new(running: &[Case], completed: &[Case], update_time: usize) -> Self {
+        let taken = Local::now();
+        Self {
+            taken,
+            next_update: taken + Duration::seconds(update_time as i64),
+            running: running.to_vec(),
+            completed: completed.to_vec(),
+            alerts: vec![],
+            calendar: None,
+            paused: false,
+            theme: None,
+            health: None,
+            eta: EtaConfig::default(),
+            planned: vec![],
+            slots: None,
+            columns: ColumnSpec::default(),
+            grouped: None,
+            sequence: None,
+            deltas: None,
+            stats: None,
+        }
+    }
+    /// Sets the time the snapshot is taken at, the time of the next update moving with it
+    pub fn taken_at(mut self, taken: DateTime<Local>) -> Self {
+        self.next_update = taken + (self.next_update - self.taken);
+        self.taken = taken;
+        self
+    }
+    /// Sets the business days calendar ETAs falling on non-business days are annotated with
+    pub fn with_calendar(mut self, calendar: Option<BusinessCalendar>) -> Self {
+        self.calendar = calendar;
+        self
+    }
+    /// Flags the snapshot as taken while the updates are paused
+    pub fn with_paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+    /// Sets the colors of the table rows, the table is not colored without a theme
+    pub fn with_theme(mut self, theme: Option<Theme>) -> Self {
+        self.theme = theme;
+        self
+    }
+    /// Returns the colors of the table rows, if colored
+    pub fn theme(&self) -> Option<Theme> {
+        self.theme
+    }
+    /// Sets the presentation of the ETAs of the table and of the compact blocks
+    pub fn with_eta(mut self, eta: EtaConfig) -> Self {
+        self.eta = eta;
+        self
+    }
+    /// Sets the columns of the table and the order of its rows
+    pub fn with_columns(mut self, columns: ColumnSpec) -> Self {
+        self.columns = columns;
+        self
+    }
+    /// Returns the columns of the table and the order of its rows
+    pub fn columns(&self) -> &ColumnSpec {
+        &self.columns
+    }
+    /// Groups the rows of the table by campaign, or not, by default if any case
+    /// belongs to a campaign
+    pub fn with_grouping(mut self, grouped: Option<bool>) -> Self {
+        self.grouped = grouped;
+        self
+    }
+    /// Returns the grouping of the rows of the table, if set
+    pub fn grouped(&self) -> Option<bool> {
+        self.grouped
+    }
+    /// Annotates the table with the changes of the cases since the previous snapshot
+    pub fn with_deltas(mut self, previous: &StatusSnapshot) -> Self {
+        self.deltas = Some(RefreshDeltas::new(previous, &self));
+        self
+    }
+    /// Returns the changes of the cases since the previous snapshot, if annotated
+    pub fn deltas(&self) -> Option<&RefreshDeltas> {
+        self.deltas.as_ref()
+    }
+    /// Sets the cost of the poll the snapshot is taken after, shown in the footer
+    pub fn with_poll_stats(mut self, stats: PollStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+    /// Returns the cost of the poll the snapshot is taken after, if taken by a monitor
+    pub fn poll_stats(&self) -> Option<&PollStats> {
+        self.stats.as_ref()
+    }
+    /// Returns the groups of the cases in the order of the table, none if the rows
+    /// are not grouped
+    fn groups(&self) -> Vec<Option<&str>> {
+        let mut groups: Vec<Option<&str>> = vec![];
+        for case in self.running.iter().chain(&self.completed) {
+            if !groups.contains(&case.group_name()) {
+                groups.push(case.group_name());
+            }
+        }
+        let grouped = self
+            .grouped
+            .unwrap_or_else(|| groups.iter().any(Option::is_some));
+        if !grouped {
+            groups.clear();
+        }
+        groups
+    }
+    /// Returns the running cases in the order of the rows of the table, grouped if any case is
+    pub fn rows(&self) -> Vec<&Case> {
+        let groups = self.groups();
+        if groups.is_empty() {
+            return self.columns.sort(self.running());
+        }
+        groups
+            .into_iter()
+            .flat_map(|group| {
+                self.columns
+                    .sort(self.running().filter(|case| case.group_name() == group))
+            })
+            .collect()
+    }
+    /// Returns the snapshot of the cases selected by a filter, with their alerts
+    pub fn filtered(&self, filter: &CaseFilter) -> Self {
+        let mut snapshot = self.clone();
+        snapshot.running.retain(|case| filter.matches(case));
+        snapshot.completed.retain(|case| filter.matches(case));
+        snapshot.planned.retain(|case| filter.matches(case));
+        let names: Vec<_> = snapshot
+            .cases()
+            .map(|case| case.name().to_string())
+            .collect();
+        snapshot.alerts.retain(|alert| names.contains(&alert.case));
+        snapshot
+    }
+    /// Sets the cases planned after the running ones and the number of cases run at once,
+    /// their schedule being appended to the table
+    pub fn with_plan(mut self, planned: Vec<Case>, slots: Option<usize>) -> Self {
+        self.planned = planned;
+        self.slots = slots;
+        self
+    }
+    /// Returns the schedule of the running, pending and planned cases
+    pub fn plan(&self) -> BatchPlan {
+        BatchPlan::new(self, &self.planned, self.slots)
+    }
+    /// Computes the campaign health index of the running cases and the alerts of the snapshot
+    pub fn with_health(mut self, config: &HealthConfig) -> Self {
+        self.health = Some(HealthIndex::new(self.running(), &self.alerts, config));
+        self
+    }
+    /// Returns the campaign health index, if computed
+    pub fn health(&self) -> Option<&HealthIndex> {
+        self.health.as_ref()
+    }
+    /// Returns true if the updates were paused when the snapshot was taken
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    /// Returns the business-day adjusted ETA of a case, if it falls on a non-business day
+    pub fn business_eta(&self, case: &Case) -> Option<DateTime<Local>> {
+        let eta = Local::now() + Duration::seconds(case.eta_secs());
+        self.calendar.as_ref()?.adjust(eta)
+    }
+    /// Sets the active alerts, expected sorted by decreasing severity
+    pub fn with_alerts(mut self, alerts: Vec<Alert>) -> Self {
+        self.alerts = alerts;
+        self
+    }
+    /// Returns the active alerts
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+    /// Returns the time the snapshot was taken
+    pub fn taken(&self) -> DateTime<Local> {
+        self.taken
+    }
+    /// Numbers the snapshot in the sequence published by a monitor, starting at 1
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+    /// Returns the sequence number of a published snapshot
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+    /// Returns the sequence number of the snapshot published before, `None` for the first one
+    ///
+    /// A consumer that last saw another snapshot missed updates
+    pub fn previous_sequence(&self) -> Option<u64> {
+        self.sequence
+            .filter(|&sequence| sequence > 1)
+            .map(|sequence| sequence - 1)
+    }
+    /// Returns all the cases, pending, running and completed
+    pub fn cases(&self) -> impl Iterator<Item = &Case> {
+        self.running.iter().chain(&self.completed)
+    }
+    /// Returns the cases running, i.e. neither pending nor completed
+    pub fn running(&self) -> impl Iterator<Item = &Case> {
+        self.running.iter().filter(|case| !case.is_pending())
+    }
+    /// Returns the cases pending in the queue
+    pub fn pending(&self) -> impl Iterator<Item = &Case> {
+        self.running.iter().filter(|case| case.is_pending())
+    }
+    /// Returns the completed cases
+    pub fn completed(&self) -> &[Case] {
+        &self.completed
+    }
+    /// Returns the mean time step duration of the running cases of a group,
+    /// or of all the running cases if none of the group has started
+    pub fn reference_step_time(&self, group: Option<&str>) -> Option<f64> {
+        let mean = |cases: Vec<f64>| {
+            (!cases.is_empty()).then(|| cases.iter().sum::<f64>() / cases.len() as f64)
+        };
+        let step_times = |same_group: bool| {
+            self.running()
+                .filter(|case| !same_group || case.group_name() == group)
+                .map(|case| case.elapsed_per_step().mean())
+                .filter(|mean| *mean > 0.)
+                .collect::<Vec<_>>()
+        };
+        mean(step_times(true)).or_else(|| mean(step_times(false)))
+    }
+    /// Returns the projected start and finish of a pending case
+    ///
+    /// The run time is estimated from the time step duration of the running cases.
+    /// The run starts once the running and pending cases it runs [after](Case::after)
+    /// are projected to finish, if later than the expected start, and is not projected
+    /// while any of them is not
+    pub fn projection(&self, case: &Case) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        self.projection_after(case, &mut vec![])
+    }
+    /// Returns the projected start and finish of a pending case,
+    /// `chain` being the cases projected after it, so a dependency cycle is not projected
+    fn projection_after<'a>(
+        &'a self,
+        case: &'a Case,
+        chain: &mut Vec<&'a str>,
+    ) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        if chain.contains(&case.name()) {
+            return None;
+        }
+        chain.push(case.name());
+        let mut ready = None;
+        for name in case.predecessors() {
+            let finish = if let Some(running) = self.running().find(|run| run.name() == name) {
+                (running.elapsed_per_step().mean() > 0.)
+                    .then(|| self.taken + Duration::seconds(running.eta_secs()))?
+            } else if let Some(pending) = self.pending().find(|pending| pending.name() == name) {
+                self.projection_after(pending, chain)?.1
+            } else {
+                // completed, or not monitored
+                continue;
+            };
+            ready = ready.max(Some(finish));
+        }
+        chain.pop();
+        let start = case.expected_start().max(ready)?;
+        let step_time = self.reference_step_time(case.group_name())?;
+        let run = Duration::seconds((case.total_step() as f64 * step_time) as i64);
+        Some((start, start + run))
+    }
+    /// Returns the cases sorted by increasing schedule slack, the cases without deadline last
+    pub fn by_slack<'a>(&self, cases: impl Iterator<Item = &'a Case>) -> Vec<&'a Case> {
+        let mut cases: Vec<_> = cases.collect();
+        cases.sort_by_key(|case| case.slack_secs().unwrap_or(i64::MAX));
+        cases
+    }
+    /// Returns true if there is no case left running
+    pub fn is_done(&self) -> bool {
+        self.running.is_empty()
+    }
+    /// Returns the snapshot as a JSON line, redacted for sharing
+    pub fn to_json(&self, redactor: &Redactor) -> String {
+        let value = serde_json::to_value(self).expect("snapshot serialization failed");
+        redactor.redact_value(value).to_string()
+    }
+    /// Returns the status of the cases as a JSON array, redacted for sharing
+    pub fn cases_json(&self, redactor: &Redactor) -> String {
+        let cases: Vec<_> = self.cases().map(CaseStatus::from).collect();
+        let value = serde_json::to_value(cases).expect("case serialization failed");
+        redactor.redact_value(value).to_string()
+    }
+    /// Returns the status of the case of the given name as JSON, redacted for sharing
+    pub fn case_json(&self, name: &str, redactor: &Redactor) -> Option<String> {
+        let case = self.cases().find(|case| case.name() == name)?;
+        let value =
+            serde_json::to_value(CaseStatus::from(case)).expect("case serialization failed");
+        Some(redactor.redact_value(value).to_string())
+    }
+    /// Returns a compact block with one line per case, meant to be appended to a log
+    pub fn compact(&self) -> String {
+        let mut header = format!(
+            "# {}",
+            self.eta.compact().zoned(self.taken, "%Y-%m-%d %H:%M:%S")
+        );
+        if let Some(health) = &self.health {
+            header.push_str(&format!(" health {:.0}/100", health.score));
+        }
+        let mut block = vec![header];
+        for case in self.by_slack(self.running()) {
+            if case.is_initializing() {
+                block.push(format!(
+                    "{:<20}initializing {}",
+                    case.name(),
+                    case.init_progress()
+                        .map_or_else(|| "-".to_string(), |p| p.to_string())
+                ));
+                continue;
+            }
+            let eta = self.taken + Duration::seconds(case.eta_secs());
+            let conservative = self.taken + Duration::seconds(case.conservative_eta_secs());
+            let mut line = format!(
+                "{:<20}{:>4}% step {:>8} I.[s]{} ETA {} (cons. {})",
+                case.name(),
+                case.percent_complete(),
+                case.step().unwrap_or_default(),
+                case.elapsed_per_step(),
+                self.eta.compact().format(eta, self.taken),
+                self.eta.compact().format(conservative, self.taken)
+            );
+            if let Some(usable) = self.business_eta(case) {
+                line.push_str(&format!(
+                    " (usable {})",
+                    self.eta.compact().zoned(usable, "%a %Y-%m-%d %H:%M")
+                ));
+            }
+            if case.is_late() {
+                let late = -case.slack_secs().unwrap_or_default();
+                line.push_str(&format!(" LATE by {}", format_hours_minutes(late)));
+            }
+            if let Some(error) = case.error() {
+                line.push_str(&format!(" ! {error}"));
+            }
+            if let Some(failure) = case.log_failure() {
+                line.push_str(&format!(" ! {}: {}", failure.name, failure.line));
+            }
+            if let Some(triage) = case.triage() {
+                line.push_str(&format!(" ? {triage}"));
+            }
+            if let Some(advice) = case.restart_advice() {
+                line.push_str(&format!(" -> {advice}"));
+            }
+            block.push(line);
+        }
+        for case in self.pending() {
+            let (start, finish) = self.projection(case).unzip();
+            block.push(format!(
+                "{:<20}pending, start {} finish {}",
+                case.name(),
+                start.map_or_else(
+                    || "-".to_string(),
+                    |t| t.format("%Y-%m-%d %H:%M").to_string()
+                ),
+                finish.map_or_else(
+                    || "-".to_string(),
+                    |t| t.format("%Y-%m-%d %H:%M").to_string()
+                )
+            ));
+        }
+        for case in &self.completed {
+            let mut line = format!(
+                "{:<20}done in {}",
+                case.name(),
+                case.runtime_secs()
+                    .map_or_else(|| "-".to_string(), format_hours_minutes)
+            );
+            if let Some(stage) = case
+                .stage_progress()
+                .iter()
+                .rev()
+                .find(|stage| stage.has_started())
+            {
+                line.push_str(&format!(", {stage}"));
+            }
+            block.push(line);
+        }
+        for alert in &self.alerts {
+            block.push(format!("! {alert}"));
+        }
+        block.join("\n")
+    }
+    /// Returns an iCalendar with an event per case at its projected completion,
+    /// or at its completion once completed
+    ///
+    /// The events keep the same UID from one snapshot to the next,
+    /// so the calendar clients move them as the ETAs shift
+    pub fn to_ics(&self) -> String {
+        let stamp = ics_time(self.taken);
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//GMTO//cfd-status//EN".to_string(),
+            "X-WR-CALNAME:CFD completions".to_string(),
+        ];
+        let mut event = |case: &Case, at: DateTime<Local>, summary: String, description: String| {
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:{}@cfd-status", ics_text(case.name())),
+                format!("DTSTAMP:{stamp}"),
+                format!("DTSTART:{}", ics_time(at)),
+                "DURATION:PT30M".to_string(),
+                format!("SUMMARY:{}", ics_text(&summary)),
+                format!("DESCRIPTION:{}", ics_text(&description)),
+                "END:VEVENT".to_string(),
+            ]);
+        };
+        for case in self.running().filter(|case| case.step().is_some()) {
+            let eta = self.taken + Duration::seconds(case.eta_secs());
+            let conservative = self.taken + Duration::seconds(case.conservative_eta_secs());
+            let mut description = format!(
+                "{}% at step {} of {}, conservative ETA {}",
+                case.percent_complete(),
+                case.step().unwrap_or_default(),
+                case.total_step(),
+                conservative.format("%Y-%m-%d %H:%M")
+            );
+            if let Some(error) = case.error() {
+                description.push_str(&format!(", failed: {error}"));
+            }
+            if let Some(note) = case.note() {
+                description.push_str(&format!(", note: {}", note.text));
+            }
+            event(case, eta, format!("{} completes", case.name()), description);
+        }
+        for case in self.pending() {
+            if let Some((start, finish)) = self.projection(case) {
+                let description =
+                    format!("pending, expected start {}", start.format("%Y-%m-%d %H:%M"));
+                event(
+                    case,
+                    finish,
+                    format!("{} completes (projected)", case.name()),
+                    description,
+                );
+            }
+        }
+        for case in &self.completed {
+            if let Some(completed) = case.completion() {
+                let description = case.runtime_secs().map_or_else(
+                    || "completed".to_string(),
+                    |secs| format!("completed in {}", format_hours_minutes(secs)),
+                );
+                event(
+                    case,
+                    completed,
+                    format!("{} completed", case.name()),
+                    description,
+                );
+            }
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.iter().map(|line| ics_fold(line) + "\r\n").collect()
+    }
+}
+
+/// Returns a time as an iCalendar UTC date-time
+fn ics_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Escapes the special characters of an iCalendar text value
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds an iCalendar content line longer than 75 octets,
+/// the continuation lines starting with a space
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+impl Display for StatusSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let now = Local::now();
+        let since = (now - self.taken).num_milliseconds() as f64 * 1e-3;
+        write!(f, "{}", self.eta.table().zoned(now, "%Y-%m-%d %H:%M:%S"))?;
+        if self.paused {
+            write!(f, "{:>30}", "updates paused")?;
+        } else if !self.is_done() {
+            write!(
+                f,
+                "{:>30}",
+                format!(
+                    "next update in {}s",
+                    (self.next_update - now).num_seconds().max(0)
+                )
+            )?;
+        }
+        writeln!(f)?;
+        if let Some(health) = &self.health {
+            writeln!(f, "Campaign {health}")?;
+        }
+        if !self.alerts.is_empty() {
+            writeln!(f, "Alerts")?;
+            for alert in &self.alerts {
+                match &self.theme {
+                    Some(theme) => writeln!(f, "{}", theme.paint_alert(alert))?,
+                    None => writeln!(f, "{alert}")?,
+                }
+            }
+            if self
+                .alerts
+                .iter()
+                .any(|alert| alert.kind == AlertKind::CoarseSampling)
+            {
+                writeln!(
+                    f,
+                    "hint: raise the update interval, now {}s, above the time step duration",
+                    (self.next_update - self.taken).num_seconds()
+                )?;
+            }
+            writeln!(f)?;
+        }
+        let columns = self.columns.fit(
+            self.running().map(Case::name),
+            self.eta.table(),
+            self.deltas.is_some(),
+        );
+        writeln!(
+            f,
+            "{}",
+            columns.header_with_deltas(self.eta.table(), self.deltas.is_some())
+        )?;
+        let paint = |case: &Case, row: String| match &self.theme {
+            Some(theme) => theme.paint(case, row),
+            None => row,
+        };
+        let row = |case: &Case| {
+            let case = case.interpolate(since);
+            let usable = self.business_eta(&case).map(|usable| {
+                format!(
+                    "  usable {}",
+                    self.eta.table().zoned(usable, "%a %Y-%m-%d %H:%M")
+                )
+            });
+            match &self.deltas {
+                // the cells and the changes are painted apart, each in its style
+                Some(deltas) if !case.is_initializing() => {
+                    columns.row_with_delta(
+                        &case,
+                        deltas.get(case.name()),
+                        self.eta.table(),
+                        self.theme.as_ref(),
+                    ) + &usable.map_or_else(String::new, |usable| paint(&case, usable))
+                }
+                _ => paint(
+                    &case,
+                    format!(
+                        "{}{}",
+                        columns.row(&case, self.eta.table()),
+                        usable.unwrap_or_default()
+                    ),
+                ),
+            }
+        };
+        let groups = self.groups();
+        if groups.is_empty() {
+            for case in self.columns.sort(self.running()) {
+                writeln!(f, "{}", row(case))?;
+            }
+        } else {
+            for group in groups {
+                writeln!(f, "[{}]", group.unwrap_or("ungrouped"))?;
+                for case in self
+                    .columns
+                    .sort(self.running().filter(|case| case.group_name() == group))
+                {
+                    writeln!(f, "{}", row(case))?;
+                }
+                let summary = GroupSummary::new(
+                    self.running
+                        .iter()
+                        .chain(&self.completed)
+                        .filter(|case| case.group_name() == group),
+                )
+                .with_eta_format(self.eta.table());
+                writeln!(f, "{summary}")?;
+            }
+        }
+        for (group, versions) in version_mismatches(self.running.iter().chain(&self.completed)) {
+            let versions: Vec<_> = versions
+                .iter()
+                .map(|(version, cases)| {
+                    format!(
+                        "{version} ({})",
+                        cases.iter().cloned().collect::<Vec<_>>().join(", ")
+                    )
+                })
+                .collect();
+            writeln!(
+                f,
+                "Solver versions differ in [{}]: {}",
+                group.as_deref().unwrap_or("ungrouped"),
+                versions.join(", ")
+            )?;
+        }
+        if self
+            .running()
+            .any(|case| !case.milestones_found().is_empty())
+        {
+            writeln!(f, "\nMilestones")?;
+            for case in self
+                .running()
+                .filter(|case| !case.milestones_found().is_empty())
+            {
+                writeln!(f, "{:<20}{}", case.name(), case.timeline())?;
+            }
+        }
+        if self.pending().next().is_some() {
+            writeln!(f, "\nPending")?;
+            writeln!(
+                f,
+                "{:20}{:>12}{:>20}{:>10}{:>20}",
+                "Case", "Queue wait", "Start", "Run", "Finish"
+            )?;
+            let format = |t: Option<DateTime<Local>>| {
+                t.map_or_else(
+                    || "-".to_string(),
+                    |t| t.format("%Y-%m-%d %H:%M").to_string(),
+                )
+            };
+            for case in self.pending() {
+                let (start, finish) = self.projection(case).unzip();
+                let start = start.or(case.expected_start());
+                writeln!(
+                    f,
+                    "{:<20}{:>12}{:>20}{:>10}{:>20}",
+                    case.name(),
+                    start.map_or_else(
+                        || "-".to_string(),
+                        |start| format_hours_minutes((start - now).num_seconds().max(0))
+                    ),
+                    format(start),
+                    start.zip(finish).map_or_else(
+                        || "-".to_string(),
+                        |(start, finish)| format_hours_minutes((finish - start).num_seconds())
+                    ),
+                    format(finish)
+                )?;
+            }
+        }
+        if !self.planned.is_empty() {
+            writeln!(f, "\n{}", self.plan())?;
+        }
+        if !self.completed.is_empty() {
+            writeln!(f, "\nCompleted")?;
+            writeln!(
+                f,
+                "{:20}{:>10}{:>10}{:>20}",
+                "Case", "Runtime", "Used[ch]", "Finished"
+            )?;
+            for case in &self.completed {
+                let row = format!(
+                    "{:<20}{:>10}{:>10}{:>20}",
+                    case.name(),
+                    case.runtime_secs()
+                        .map_or_else(|| "-".to_string(), format_hours_minutes),
+                    format_core_hours(case.consumed_core_hours()),
+                    case.completion().map_or_else(
+                        || "-".to_string(),
+                        |t| t.format("%Y-%m-%d %H:%M").to_string()
+                    )
+                );
+                writeln!(f, "{}", paint(case, row))?;
+            }
+        }
+        writeln!(
+            f,
+            "\nTotal: {}",
+            GroupSummary::new(self.running.iter().chain(&self.completed))
+                .with_eta_format(self.eta.table())
+        )?;
+        if let Some(stats) = &self.stats {
+            writeln!(f, "{stats}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Aggregate statistics of a group of cases
+#[derive(Debug, Default, Serialize)]
+pub struct GroupSummary {
+    pub pending: usize,
+    pub running: usize,
+    pub stalled: usize,
+    pub done: usize,
+    pub consumed_core_hours: f64,
+    pub remaining_core_hours: f64,
+    pub latest_eta: Option<DateTime<Local>>,
+    /// Presentation of the latest ETA
+    #[serde(skip)]
+    pub eta_format: EtaStyle,
+}
+
+impl GroupSummary {
+    /// Computes the statistics of the given cases
+    pub fn new<'a>(cases: impl Iterator<Item = &'a Case>) -> Self {
+        let now = Local::now();
+        cases.fold(Default::default(), |mut summary: Self, case| {
+            summary.consumed_core_hours += case.consumed_core_hours().unwrap_or_default();
+            if case.is_complete() {
+                summary.done += 1;
+                return summary;
+            }
+            if case.is_pending() {
+                summary.pending += 1;
+                return summary;
+            }
+            summary.running += 1;
+            if case.is_stalled() {
+                summary.stalled += 1;
+            }
+            summary.remaining_core_hours += case.remaining_core_hours().unwrap_or_default();
+            if case.step().is_some() {
+                let eta = now + Duration::seconds(case.eta_secs());
+                summary.latest_eta = summary.latest_eta.max(Some(eta));
+            }
+            summary
+        })
+    }
+    /// Sets the presentation of the latest ETA
+    pub fn with_eta_format(mut self, format: EtaStyle) -> Self {
+        self.eta_format = format;
+        self
+    }
+}
+
+impl Display for GroupSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} running, {} stalled, {} done, {:.0} core-hours used, {:.0} left",
+            self.running,
+            self.stalled,
+            self.done,
+            self.consumed_core_hours,
+            self.remaining_core_hours
+        )?;
+        if self.pending > 0 {
+            write!(f, ", {} pending", self.pending)?;
+        }
+        if let Some(eta) = self.latest_eta {
+            write!(
+                f,
+                ", last ETA {}",
+                self.eta_format.format(eta, Local::now())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized status of a case
+#[derive(Serialize)]
+struct CaseStatus<'a> {
+    name: &'a str,
+    group: Option<&'a str>,
+    state: &'static str,
+    log: String,
+    step: Option<usize>,
+    total_step: usize,
+    percent: i64,
+    time: f64,
+    step_time: f64,
+    /// Mean time step duration over the latest samples, the ETA is computed from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recent_step_time: Option<f64>,
+    /// Time step the step time is averaged from, after a change of the simulated time per time step
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_time_since: Option<usize>,
+    /// Simulated seconds per time step, as reported by the log or found from the simulated time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_step_size: Option<f64>,
+    /// Expected time step duration in seconds and drift in percent of the mean from it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected_step_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_time_drift: Option<f64>,
+    /// Wall-clock time of the latest time step from the log timestamps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    logged_at: Option<DateTime<Local>>,
+    steps_per_hour: Option<f64>,
+    sim_secs_per_day: Option<f64>,
+    /// Simulated seconds left
+    remaining_sim_secs: Option<f64>,
+    eta: Option<DateTime<Local>>,
+    /// ETA at the mean time step duration of the simulation, with the downtime of similar cases
+    conservative_eta: Option<DateTime<Local>>,
+    expected_start: Option<DateTime<Local>>,
+    completed: Option<DateTime<Local>>,
+    deadline: Option<DateTime<Local>>,
+    slack_secs: Option<i64>,
+    priority: i32,
+    /// Reason the case needs attention: failed, stalled or late
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attention: Option<&'static str>,
+    error: Option<&'a str>,
+    /// Percentiles of the time step durations over the whole simulation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_time_percentiles: Option<Percentiles>,
+    /// Inner iterations of the latest time step
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inner_iterations: Option<usize>,
+    /// Set if the inner iterations per time step are climbing
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    iterations_climbing: bool,
+    /// Note of the operators
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<&'a Note>,
+    /// CPU and GPU utilization of the solver job
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utilization: Option<Utilization>,
+    /// Fatal solver error found in the log
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log_failure: Option<&'a LogFailure>,
+    /// Moves, re-links and replacements of the log file
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    log_relocations: &'a [LogRelocation],
+    /// Triage hint of the failure signature found in the log of a failed case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    triage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_checkpoint: Option<&'a Checkpoint>,
+    /// Checkpoint to restart a failed case from and the progress lost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    restart: Option<RestartAdvice>,
+    /// State of the case in its pipeline, if it has post-processing stages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pipeline: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    stages: &'a [Stage],
+    /// Production case seeded by a precursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seeds: Option<&'a str>,
+    environment: Environment,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    slow_intervals: Vec<&'a SlowInterval>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    dt_changes: Vec<&'a DtChange>,
+    /// Intervals the monitor was offline in, the time steps done in them being spread evenly
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    offline: Vec<&'a OfflineInterval>,
+}
+
+impl<'a> From<&'a Case> for CaseStatus<'a> {
+    fn from(case: &'a Case) -> Self {
+        let state = case.state();
+        Self {
+            name: case.name(),
+            group: case.group_name(),
+            state,
+            log: case.log_file(),
+            step: case.step(),
+            total_step: case.total_step(),
+            percent: case.percent_complete(),
+            time: case.time(),
+            step_time: case.elapsed_per_step().mean(),
+            recent_step_time: case.elapsed_per_step().windowed(),
+            step_time_since: case.elapsed_per_step().since_step(),
+            time_step_size: case.time_step_size(),
+            expected_step_time: case.expected_step_time_secs(),
+            step_time_drift: case.step_time_drift(),
+            logged_at: case.logged_at(),
+            steps_per_hour: case.steps_per_hour(),
+            sim_secs_per_day: case.sim_secs_per_day(),
+            remaining_sim_secs: case.remaining_sim_secs(),
+            eta: case.eta().filter(|_| state == "running"),
+            conservative_eta: case.conservative_eta().filter(|_| state == "running"),
+            expected_start: case.expected_start(),
+            completed: case.completion(),
+            deadline: case.deadline_time(),
+            slack_secs: case.slack_secs(),
+            priority: case.priority_level(),
+            attention: case.attention(),
+            error: case.error(),
+            step_time_percentiles: case.step_time_percentiles(),
+            inner_iterations: case.inner_iterations().latest(),
+            iterations_climbing: case.is_iterating_more(),
+            note: case.note(),
+            utilization: case.utilization(),
+            log_failure: case.log_failure(),
+            log_relocations: case.log_relocations(),
+            triage: case.triage().map(ToString::to_string),
+            last_checkpoint: case.last_checkpoint(),
+            restart: case.restart_advice(),
+            pipeline: (!case.stage_progress().is_empty()).then(|| case.pipeline_state()),
+            stages: case.stage_progress(),
+            seeds: case
+                .precursor_config()
+                .map(|precursor| precursor.seeds.as_str()),
+            environment: case.environment(),
+            slow_intervals: case.slow_intervals().collect(),
+            dt_changes: case.dt_changes().collect(),
+            offline: case.offline_intervals().collect(),
+        }
+    }
+}
+
+/// Serialized alert
+#[derive(Serialize)]
+struct AlertStatus<'a> {
+    case: &'a str,
+    kind: String,
+    severity: String,
+    since: DateTime<Local>,
+    expected: Option<&'a str>,
+    acknowledged: bool,
+}
+
+impl Serialize for StatusSnapshot {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut snapshot = serializer.serialize_struct("StatusSnapshot", 8)?;
+        // only the snapshots published by a monitor are numbered
+        if let Some(sequence) = self.sequence {
+            snapshot.serialize_field("seq", &sequence)?;
+            snapshot.serialize_field("previous_seq", &self.previous_sequence())?;
+        }
+        snapshot.serialize_field("taken", &self.taken)?;
+        snapshot.serialize_field("paused", &self.paused)?;
+        snapshot.serialize_field("health", &self.health)?;
+        snapshot.serialize_field(
+            "cases",
+            &self
+                .running
+                .iter()
+                .chain(&self.completed)
+                .map(CaseStatus::from)
+                .collect::<Vec<_>>(),
+        )?;
+        snapshot.serialize_field(
+            "alerts",
+            &self
+                .alerts
+                .iter()
+                .map(|alert| AlertStatus {
+                    case: &alert.case,
+                    kind: alert.kind.to_string(),
+                    severity: alert.severity().to_string(),
+                    since: alert.since,
+                    expected: alert.expected.as_deref(),
+                    acknowledged: alert.acknowledged,
+                })
+                .collect::<Vec<_>>(),
+        )?;
+        snapshot.serialize_field(
+            "total",
+            &GroupSummary::new(self.running.iter().chain(&self.completed)),
+        )?;
+        snapshot.end()
+    }
+}
@@ -0,0 +1,718 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::{Debug, Display},
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::{
+    rotated::{self, RotatedMatches},
+    shell, LogTail, ReadOptions,
+};
+
+/// Number of the latest lines of a piped log searched for the last line matching a pattern
+const PIPE_LINES: usize = 1000;
+
+/// Source of the lines of a solver log
+///
+/// [Case](crate::Case)s read their log through a source, the log file by default,
+/// so the parsing can be fed from a string in tests or from a command for remote logs
+pub trait LogSource: Debug + Send {
+    /// Returns the complete lines appended to the log since the previous call
+    ///
+    /// A log that does not exist yet has no line and is [LogSource::is_missing],
+    /// while a log that cannot be read, e.g. its command failing, is an error
+    fn read_new(&mut self) -> io::Result<Vec<String>>;
+    /// Returns the last line of the whole log containing `pattern`
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>>;
+    /// Returns the last line containing `pattern` of each of the logs merged by the source,
+    /// the source being a single log by default
+    fn last_matching_per_log(&mut self, pattern: &str) -> io::Result<Vec<String>> {
+        Ok(self.last_matching(pattern)?.into_iter().collect())
+    }
+    /// Returns true once the log exists, i.e. the solver has started
+    fn exists(&self) -> bool;
+    /// Returns true if the log was not found, as opposed to a log failing to be read,
+    /// a log not existing being missing by default
+    fn is_missing(&self) -> bool {
+        !self.exists()
+    }
+    /// Returns the relocations of the log found since the previous call, none by default
+    fn relocations(&mut self) -> Vec<LogRelocation> {
+        vec![]
+    }
+}
+
+/// Change of the file a log path leads to, the log or its case directory having been moved,
+/// re-linked or replaced while the case runs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogRelocation {
+    /// Time the relocation was found, by the clock of the case
+    pub at: DateTime<Local>,
+    /// File the log was read from, symbolic links resolved
+    pub from: PathBuf,
+    /// File the log is read from now
+    pub to: PathBuf,
+    /// True if the log is a different file, read again from its beginning,
+    /// false if the same file was moved or re-linked
+    pub replaced: bool,
+    /// True if a newer log matching the glob of the case is followed instead, e.g. after a restart
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub switched: bool,
+}
+
+impl Display for LogRelocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.switched {
+            write!(
+                f,
+                "log switched: {} -> {}",
+                self.from.display(),
+                self.to.display()
+            )
+        } else if self.replaced {
+            write!(
+                f,
+                "log replaced: {} -> {}, reopened",
+                self.from.display(),
+                self.to.display()
+            )
+        } else {
+            write!(
+                f,
+                "log moved: {} -> {}",
+                self.from.display(),
+                self.to.display()
+            )
+        }
+    }
+}
+
+/// Size and modification time of a log file
+type Stat = (u64, SystemTime);
+
+/// File a log path leads to, its path with the symbolic links resolved and its device and inode
+#[derive(Debug, Clone, PartialEq)]
+struct FileId {
+    path: PathBuf,
+    inode: Option<(u64, u64)>,
+}
+
+#[cfg(unix)]
+fn inode(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Log file, including its rotated and gzip-compressed predecessors
+///
+/// The file is stat'ed before being read, an unchanged file is not read again.
+/// Symbolic links are resolved at every read: a log moved or re-linked is read on
+/// from where it was, and a different file replacing the log is read from its beginning,
+/// after the end of the replaced log if it was rotated uncompressed, e.g. to `solve.out.1`
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    path: PathBuf,
+    tail: LogTail,
+    options: ReadOptions,
+    /// Stat of the file at the latest read of the new lines
+    read: Option<Stat>,
+    /// Stat of the file the last matching lines were found in
+    matched_stat: Option<Stat>,
+    matched: HashMap<String, Option<String>>,
+    /// Last matching lines of the rotated versions of the log
+    rotated: RotatedMatches,
+    /// File read at the latest read of the new lines
+    file: Option<FileId>,
+    /// Lines appended to the log before it was rotated and not read yet
+    stitched: Vec<String>,
+    relocations: Vec<LogRelocation>,
+}
+
+impl FileSource {
+    /// Creates a new source reading the log file at `path`
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self::with_options(path, ReadOptions::default())
+    }
+    /// Creates a new source reading the log file at `path`, throttled and chunked as set by `options`
+    pub fn with_options<P: AsRef<Path>>(path: P, options: ReadOptions) -> Self {
+        let tail = match options.chunk_size {
+            Some(bytes) => LogTail::new().chunk_size(bytes),
+            None => LogTail::new(),
+        };
+        Self {
+            path: path.as_ref().to_path_buf(),
+            tail,
+            options,
+            read: None,
+            matched_stat: None,
+            matched: HashMap::new(),
+            rotated: RotatedMatches::default(),
+            file: None,
+            stitched: vec![],
+            relocations: vec![],
+        }
+    }
+    /// Returns the size and modification time of the file, `None` if it does not exist
+    fn stat(&self) -> Option<Stat> {
+        let metadata = fs::metadata(&self.path).ok()?;
+        Some((metadata.len(), metadata.modified().ok()?))
+    }
+    /// Returns the file the log path leads to, `None` if it does not exist
+    fn identify(&self) -> Option<FileId> {
+        let metadata = fs::metadata(&self.path).ok()?;
+        Some(FileId {
+            path: fs::canonicalize(&self.path).ok()?,
+            inode: inode(&metadata),
+        })
+    }
+    /// Reads the end of a replaced log rotated to one of the versions of the log,
+    /// from where it was read up to
+    fn stitch(&mut self, previous: &FileId) {
+        if previous.inode.is_none() {
+            return;
+        }
+        let Some(rotated) = rotated::rotated_logs(&self.path)
+            .into_iter()
+            .skip(1)
+            .find(|log| {
+                !rotated::is_gzip(log)
+                    && fs::metadata(log).is_ok_and(|metadata| inode(&metadata) == previous.inode)
+            })
+        else {
+            return;
+        };
+        match self.tail.read_new(&rotated) {
+            Ok(lines) => self.stitched.extend(lines),
+            Err(e) => {
+                tracing::warn!(log = %rotated.display(), error = %e, "failed to read the rotated log")
+            }
+        }
+    }
+    /// Compares the file the log path leads to with the file read before,
+    /// starting over from the beginning of a different file
+    fn follow(&mut self) {
+        let Some(file) = self.identify() else {
+            return;
+        };
+        let Some(previous) = self
+            .file
+            .replace(file.clone())
+            .filter(|previous| *previous != file)
+        else {
+            return;
+        };
+        let replaced = file.inode.is_none() || file.inode != previous.inode;
+        if replaced {
+            self.stitch(&previous);
+            self.tail.rewind();
+            self.read = None;
+            self.matched_stat = None;
+            self.matched.clear();
+        }
+        tracing::warn!(
+            from = %previous.path.display(),
+            to = %file.path.display(),
+            replaced,
+            "log file moved"
+        );
+        self.relocations.push(LogRelocation {
+            at: Local::now(),
+            from: previous.path,
+            to: file.path,
+            replaced,
+            switched: false,
+        });
+    }
+}
+
+impl LogSource for FileSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        self.follow();
+        let mut lines = std::mem::take(&mut self.stitched);
+        let Some(stat) = self.stat() else {
+            return Ok(lines);
+        };
+        if self.read == Some(stat) {
+            return Ok(lines);
+        }
+        let _permit = self.options.acquire();
+        lines.extend(self.tail.read_new(&self.path)?);
+        self.read = Some(stat);
+        Ok(lines)
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        let stat = self.stat();
+        if stat.is_none() || stat != self.matched_stat {
+            self.matched.clear();
+            self.matched_stat = stat;
+        }
+        if let Some(line) = self.matched.get(pattern) {
+            return Ok(line.clone());
+        }
+        let _permit = self.options.acquire();
+        let line = self.rotated.last_matching_line(&self.path, pattern)?;
+        if stat.is_some() {
+            self.matched.insert(pattern.to_string(), line.clone());
+        }
+        Ok(line)
+    }
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+    fn relocations(&mut self) -> Vec<LogRelocation> {
+        std::mem::take(&mut self.relocations)
+    }
+}
+
+/// Logs matching a glob merged into one, e.g. one log per MPI rank or per restart segment
+///
+/// Only the file name may have `*` and `?` wildcards, the directory is listed
+/// at every read so the logs created later are picked up
+#[derive(Debug, Clone)]
+pub struct GlobSource {
+    pattern: PathBuf,
+    logs: BTreeMap<PathBuf, FileSource>,
+    options: ReadOptions,
+}
+
+impl GlobSource {
+    /// Creates a new source reading the log files matching `pattern`
+    pub fn new<P: AsRef<Path>>(pattern: P) -> Self {
+        Self::with_options(pattern, ReadOptions::default())
+    }
+    /// Creates a new source reading the log files matching `pattern`,
+    /// throttled and chunked as set by `options`
+    pub fn with_options<P: AsRef<Path>>(pattern: P, options: ReadOptions) -> Self {
+        Self {
+            pattern: pattern.as_ref().to_path_buf(),
+            logs: BTreeMap::new(),
+            options,
+        }
+    }
+    /// Returns true if the log name has wildcards, i.e. stands for several logs
+    pub fn is_glob(log: &str) -> bool {
+        log.contains(['*', '?'])
+    }
+    /// Returns the paths to the log files matching the pattern, in name order
+    fn matching(&self) -> Vec<PathBuf> {
+        let (Some(dir), Some(pattern)) = (
+            self.pattern.parent(),
+            self.pattern.file_name().and_then(|n| n.to_str()),
+        ) else {
+            return vec![];
+        };
+        let mut logs: Vec<_> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| wildcard_match(pattern, name))
+            })
+            .map(|entry| entry.path())
+            .collect();
+        logs.sort();
+        logs
+    }
+    /// Adds the sources of the log files created since the previous call
+    fn refresh(&mut self) {
+        for path in self.matching() {
+            self.logs
+                .entry(path.clone())
+                .or_insert_with(|| FileSource::with_options(path, self.options.clone()));
+        }
+    }
+}
+
+impl LogSource for GlobSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        self.refresh();
+        let mut lines = vec![];
+        for log in self.logs.values_mut() {
+            lines.extend(log.read_new()?);
+        }
+        Ok(lines)
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        Ok(self.last_matching_per_log(pattern)?.pop())
+    }
+    fn last_matching_per_log(&mut self, pattern: &str) -> io::Result<Vec<String>> {
+        self.refresh();
+        if self.logs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no log file matching the pattern",
+            ));
+        }
+        // the segments of a restarted solver last written before a newer one was created
+        // are superseded, the creation times being ignored where unavailable
+        let created: Vec<_> = self
+            .logs
+            .keys()
+            .filter_map(|path| Some((path.clone(), fs::metadata(path).ok()?.created().ok()?)))
+            .collect();
+        let mut lines = vec![];
+        for log in self.logs.values_mut().filter(|log| log.exists()) {
+            let superseded = fs::metadata(&log.path)
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| {
+                    created
+                        .iter()
+                        .any(|(path, created)| *path != log.path && *created > modified)
+                });
+            if !superseded {
+                lines.extend(log.last_matching(pattern)?);
+            }
+        }
+        Ok(lines)
+    }
+    fn exists(&self) -> bool {
+        !self.matching().is_empty()
+    }
+    fn relocations(&mut self) -> Vec<LogRelocation> {
+        self.logs
+            .values_mut()
+            .flat_map(|log| log.relocations())
+            .collect()
+    }
+}
+
+/// Most recently modified of the logs matching a glob, e.g. `solve-672_*.out` with the number
+/// of the log incremented at each restart of the solver
+///
+/// The directory is listed at every read, a newer log being read from its beginning
+/// and the switch recorded as a relocation of the log
+#[derive(Debug, Clone)]
+pub struct LatestSource {
+    glob: GlobSource,
+    log: Option<FileSource>,
+    relocations: Vec<LogRelocation>,
+}
+
+impl LatestSource {
+    /// Creates a new source following the latest log file matching `pattern`
+    pub fn new<P: AsRef<Path>>(pattern: P) -> Self {
+        Self::with_options(pattern, ReadOptions::default())
+    }
+    /// Creates a new source following the latest log file matching `pattern`,
+    /// throttled and chunked as set by `options`
+    pub fn with_options<P: AsRef<Path>>(pattern: P, options: ReadOptions) -> Self {
+        Self {
+            glob: GlobSource::with_options(pattern, options),
+            log: None,
+            relocations: vec![],
+        }
+    }
+    /// Returns the path to the log followed, `None` before a log matches
+    pub fn current(&self) -> Option<&Path> {
+        self.log.as_ref().map(|log| log.path.as_path())
+    }
+    /// Switches to the most recently modified log, the last one by name on a tie
+    fn refresh(&mut self) {
+        let Some(latest) = self
+            .glob
+            .matching()
+            .into_iter()
+            .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+            .max()
+            .map(|(_, path)| path)
+        else {
+            return;
+        };
+        if self.current() == Some(latest.as_path()) {
+            return;
+        }
+        let log = FileSource::with_options(&latest, self.glob.options.clone());
+        if let Some(previous) = self.log.replace(log) {
+            tracing::info!(
+                from = %previous.path.display(),
+                to = %latest.display(),
+                "following a newer log"
+            );
+            self.relocations.push(LogRelocation {
+                at: Local::now(),
+                from: previous.path,
+                to: latest,
+                replaced: true,
+                switched: true,
+            });
+        }
+    }
+}
+
+impl LogSource for LatestSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        self.refresh();
+        match self.log.as_mut() {
+            Some(log) => log.read_new(),
+            None => Ok(vec![]),
+        }
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        self.refresh();
+        match self.log.as_mut() {
+            Some(log) => log.last_matching(pattern),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no log file matching the pattern",
+            )),
+        }
+    }
+    fn exists(&self) -> bool {
+        self.glob.exists()
+    }
+    fn relocations(&mut self) -> Vec<LogRelocation> {
+        let mut relocations = std::mem::take(&mut self.relocations);
+        relocations.extend(self.log.iter_mut().flat_map(|log| log.relocations()));
+        relocations
+    }
+}
+
+/// Returns true if `name` matches `pattern`, `*` standing for any sequence of characters
+/// and `?` for any single character
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // position of the last `*` in the pattern and of the name where it was matched
+    let (mut p, mut n, mut star) = (0, 0, None);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// In-memory log, shared by its clones so it can be appended to after it is given to a case
+#[derive(Debug, Clone, Default)]
+pub struct StringSource {
+    content: Arc<Mutex<String>>,
+    /// True until appended to if created missing
+    missing: Arc<AtomicBool>,
+    read: usize,
+}
+
+impl StringSource {
+    /// Creates a new source from the content of a log
+    pub fn new<S: ToString>(content: S) -> Self {
+        Self {
+            content: Arc::new(Mutex::new(content.to_string())),
+            ..Default::default()
+        }
+    }
+    /// Creates a new source of a log not created yet, until appended to
+    pub fn missing() -> Self {
+        Self {
+            missing: Arc::new(AtomicBool::new(true)),
+            ..Default::default()
+        }
+    }
+    /// Appends some content to the log
+    pub fn append(&self, content: &str) {
+        self.content.lock().unwrap().push_str(content);
+        self.missing.store(false, Ordering::Relaxed);
+    }
+}
+
+impl LogSource for StringSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        let content = self.content.lock().unwrap();
+        if content.len() < self.read {
+            self.read = 0;
+        }
+        // a partial last line is read again at the next call
+        let Some(end) = content[self.read..].rfind('\n') else {
+            return Ok(vec![]);
+        };
+        let lines = content[self.read..self.read + end]
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect();
+        self.read += end + 1;
+        Ok(lines)
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        let content = self.content.lock().unwrap();
+        Ok(content
+            .lines()
+            .rev()
+            .find(|line| line.contains(pattern))
+            .map(str::to_string))
+    }
+    fn exists(&self) -> bool {
+        !self.content.lock().unwrap().is_empty()
+    }
+    fn is_missing(&self) -> bool {
+        self.missing.load(Ordering::Relaxed)
+    }
+}
+
+/// Log read from a stream on a background thread, e.g. stdin fed by `tail -f solve.out`,
+/// the lines being buffered until read
+#[derive(Debug, Clone, Default)]
+pub struct PipeSource {
+    buffer: Arc<Mutex<PipeBuffer>>,
+}
+
+#[derive(Debug, Default)]
+struct PipeBuffer {
+    /// Lines not read yet
+    new: Vec<String>,
+    /// Latest lines
+    recent: VecDeque<String>,
+    closed: bool,
+}
+
+impl PipeSource {
+    /// Creates a new source reading the lines of `reader` until its end
+    pub fn new<R: BufRead + Send + 'static>(mut reader: R) -> Self {
+        let source = Self::default();
+        let buffer = source.buffer.clone();
+        thread::spawn(move || {
+            let mut line = vec![];
+            // the lines are read as bytes so a line of invalid UTF-8 does not end the stream
+            while reader.read_until(b'\n', &mut line).is_ok_and(|n| n > 0) {
+                let text = String::from_utf8_lossy(&line).trim_end().to_string();
+                let mut buffer = buffer.lock().unwrap();
+                if buffer.recent.len() == PIPE_LINES {
+                    buffer.recent.pop_front();
+                }
+                buffer.recent.push_back(text.clone());
+                buffer.new.push(text);
+                line.clear();
+            }
+            buffer.lock().unwrap().closed = true;
+        });
+        source
+    }
+    /// Creates a new source reading the lines of stdin
+    pub fn stdin() -> Self {
+        Self::new(io::BufReader::new(io::stdin()))
+    }
+    /// Returns true once the end of the stream is reached, e.g. the writer of the pipe exited
+    pub fn is_closed(&self) -> bool {
+        self.buffer.lock().unwrap().closed
+    }
+}
+
+impl LogSource for PipeSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        Ok(std::mem::take(&mut self.buffer.lock().unwrap().new))
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        let buffer = self.buffer.lock().unwrap();
+        Ok(buffer
+            .recent
+            .iter()
+            .rev()
+            .find(|line| line.contains(pattern))
+            .cloned())
+    }
+    fn exists(&self) -> bool {
+        !self.buffer.lock().unwrap().recent.is_empty()
+    }
+}
+
+/// Log printed by a shell command, e.g. `ssh login01 cat /scratch/zen30/solve.out`
+///
+/// The command is run at every read and the lines already returned are skipped.
+/// The log does not exist until the command first succeeds, a failing command being an error
+#[derive(Debug, Clone)]
+pub struct CommandSource {
+    command: String,
+    read: usize,
+    exists: bool,
+}
+
+impl CommandSource {
+    /// Creates a new source running `command` with `sh -c`
+    pub fn new<S: ToString>(command: S) -> Self {
+        Self {
+            command: command.to_string(),
+            read: 0,
+            exists: false,
+        }
+    }
+    /// Runs the command and returns its output, an error if the command failed
+    fn output(&mut self) -> io::Result<String> {
+        let output = shell(&self.command).stderr(Stdio::null()).output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with {}",
+                self.command, output.status
+            )));
+        }
+        self.exists = true;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+impl LogSource for CommandSource {
+    fn read_new(&mut self) -> io::Result<Vec<String>> {
+        let output = self.output()?;
+        let lines: Vec<_> = output.lines().map(|line| line.trim_end()).collect();
+        // a partial last line is read again at the next call
+        let complete = if output.ends_with('\n') {
+            lines.len()
+        } else {
+            lines.len().saturating_sub(1)
+        };
+        if complete < self.read {
+            self.read = 0;
+        }
+        let new = lines[self.read..complete]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        self.read = complete;
+        Ok(new)
+    }
+    fn last_matching(&mut self, pattern: &str) -> io::Result<Option<String>> {
+        let output = self.output()?;
+        Ok(output
+            .lines()
+            .rev()
+            .find(|line| line.contains(pattern))
+            .map(str::to_string))
+    }
+    fn exists(&self) -> bool {
+        self.exists
+    }
+    fn is_missing(&self) -> bool {
+        false
+    }
+}
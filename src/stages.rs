@@ -0,0 +1,116 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Post-processing stage run once the simulation of a case completes,
+/// e.g. the extraction of the optical path differences
+///
+/// ```toml
+/// [[stages]]
+/// name = "extracting"
+/// log = "optical_path/extract.log"
+/// marker = "optical_path/DONE"
+/// duration = 7200
+///
+/// [[stages]]
+/// name = "archived"
+/// marker = "ARCHIVED"
+/// ```
+///
+/// The log and marker files are relative to the case directory: a stage is running once
+/// its log exists, and done once its marker exists
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StageConfig {
+    pub name: String,
+    /// Log file of the stage, created when the stage starts
+    pub log: Option<String>,
+    /// File created when the stage is done
+    pub marker: Option<String>,
+    /// Expected wall-clock duration in seconds, the stage being overdue once exceeded
+    pub duration: Option<u64>,
+}
+
+/// Progress of a post-processing stage
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "state", content = "since")]
+pub enum StageState {
+    Waiting,
+    Running(DateTime<Local>),
+    /// Running for longer than the expected duration
+    Overdue(DateTime<Local>),
+    Done(DateTime<Local>),
+}
+
+/// Post-processing stage of a case with its progress
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stage {
+    pub name: String,
+    #[serde(flatten)]
+    pub state: StageState,
+}
+
+impl StageConfig {
+    /// Returns the progress of the stage of the case in `case_dir`
+    pub fn check(&self, case_dir: &Path) -> Stage {
+        let modified = |file: &Option<String>| {
+            file.as_ref()
+                .and_then(|file| fs::metadata(case_dir.join(file)).ok())
+                .and_then(|meta| meta.created().or_else(|_| meta.modified()).ok())
+        };
+        let state = match (modified(&self.log), modified(&self.marker)) {
+            (_, Some(done)) => StageState::Done(done.into()),
+            (Some(started), None) => {
+                let overdue = self.duration.is_some_and(|secs| {
+                    SystemTime::now()
+                        .duration_since(started)
+                        .is_ok_and(|elapsed| elapsed > Duration::from_secs(secs))
+                });
+                if overdue {
+                    StageState::Overdue(started.into())
+                } else {
+                    StageState::Running(started.into())
+                }
+            }
+            (None, None) => StageState::Waiting,
+        };
+        Stage {
+            name: self.name.clone(),
+            state,
+        }
+    }
+}
+
+impl Stage {
+    /// Returns true once the stage is done
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, StageState::Done(_))
+    }
+    /// Returns true if the stage has started
+    pub fn has_started(&self) -> bool {
+        self.state != StageState::Waiting
+    }
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.state {
+            StageState::Waiting => write!(f, "{} waiting", self.name),
+            StageState::Running(since) => {
+                write!(f, "{} since {}", self.name, since.format("%m-%d %H:%M"))
+            }
+            StageState::Overdue(since) => write!(
+                f,
+                "{} since {} (overdue)",
+                self.name,
+                since.format("%m-%d %H:%M")
+            ),
+            StageState::Done(at) => write!(f, "{} done {}", self.name, at.format("%m-%d %H:%M")),
+        }
+    }
+}
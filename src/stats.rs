@@ -0,0 +1,60 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+/// Bytes per kB
+const KB: f64 = 1e3;
+
+/// Cost of the update of a case during a poll
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaseUpdateStats {
+    pub case: String,
+    /// Wall-clock duration of the update in seconds
+    pub secs: f64,
+    /// Bytes of the log read
+    pub bytes: u64,
+    /// Whether the update failed parsing the log
+    pub parse_failure: bool,
+}
+
+/// Cost of a poll of the monitor, to tell when slow log reads, e.g. on NFS,
+/// make the monitor itself the bottleneck
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PollStats {
+    /// Wall-clock duration of the poll in seconds
+    pub secs: f64,
+    /// Updates of the cases polled, in polling order
+    pub cases: Vec<CaseUpdateStats>,
+}
+
+impl PollStats {
+    /// Returns the bytes of the logs read
+    pub fn bytes(&self) -> u64 {
+        self.cases.iter().map(|case| case.bytes).sum()
+    }
+    /// Returns the number of updates failing to parse the log
+    pub fn parse_failures(&self) -> usize {
+        self.cases.iter().filter(|case| case.parse_failure).count()
+    }
+    /// Returns the slowest update
+    pub fn slowest(&self) -> Option<&CaseUpdateStats> {
+        self.cases.iter().max_by(|a, b| a.secs.total_cmp(&b.secs))
+    }
+}
+
+impl Display for PollStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Poll: {:.3}s, {} cases updated, {:.1} kB read, {} parse failures",
+            self.secs,
+            self.cases.len(),
+            self.bytes() as f64 / KB,
+            self.parse_failures()
+        )?;
+        if let Some(slowest) = self.slowest() {
+            write!(f, ", slowest {} {:.3}s", slowest.case, slowest.secs)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::format_hours_minutes;
+
+/// Width of the bars of the timeline
+const TIMELINE_WIDTH: usize = 40;
+/// Format of the timestamps of the summary
+const AT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Record of a completed case, moved to the archive of the run database on completion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArchivedCase {
+    pub name: String,
+    pub campaign: Option<String>,
+    /// Number of cores the solver ran on
+    pub cores: Option<usize>,
+    pub total_step: usize,
+    /// First time the case was seen running
+    pub first_seen: DateTime<Local>,
+    pub completed: DateTime<Local>,
+    /// Time steps advanced while monitored
+    pub steps: usize,
+    /// Number of stall incidents
+    pub stalls: usize,
+}
+
+impl ArchivedCase {
+    /// Returns the wall-clock time the case was monitored for in seconds
+    pub fn runtime_secs(&self) -> i64 {
+        (self.completed - self.first_seen).num_seconds().max(0)
+    }
+    /// Returns the average throughput in time steps per hour
+    pub fn steps_per_hour(&self) -> Option<f64> {
+        let secs = self.runtime_secs();
+        (secs > 0).then(|| self.steps as f64 * 3600. / secs as f64)
+    }
+}
+
+/// Stall of a case, from the events of the run database
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StallIncident {
+    pub case: String,
+    pub at: DateTime<Local>,
+}
+
+/// Average throughput of the completed cases running on the same number of cores
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigurationThroughput {
+    pub cores: Option<usize>,
+    pub cases: usize,
+    /// Time steps per hour over the cumulated runtime of the cases
+    pub steps_per_hour: Option<f64>,
+}
+
+/// Report of the completed cases of a campaign, for the weekly meeting:
+/// total wall-clock, throughput per configuration, stall incidents and timeline
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CampaignSummary {
+    campaign: Option<String>,
+    cases: Vec<ArchivedCase>,
+    stalls: Vec<StallIncident>,
+}
+
+impl CampaignSummary {
+    /// Creates the summary of the archived cases of a campaign, of all of them without a campaign,
+    /// with their stall incidents
+    pub fn new(
+        campaign: Option<&str>,
+        cases: impl IntoIterator<Item = ArchivedCase>,
+        stalls: impl IntoIterator<Item = StallIncident>,
+    ) -> Self {
+        let mut cases: Vec<_> = cases
+            .into_iter()
+            .filter(|case| campaign.is_none() || case.campaign.as_deref() == campaign)
+            .collect();
+        cases.sort_by_key(|case| case.first_seen);
+        let mut stalls: Vec<_> = stalls
+            .into_iter()
+            .filter(|stall| cases.iter().any(|case| case.name == stall.case))
+            .collect();
+        stalls.sort_by_key(|stall| stall.at);
+        Self {
+            campaign: campaign.map(str::to_string),
+            cases,
+            stalls,
+        }
+    }
+    /// Returns the archived cases, by first sighting
+    pub fn cases(&self) -> &[ArchivedCase] {
+        &self.cases
+    }
+    /// Returns the stall incidents of the cases, oldest first
+    pub fn stalls(&self) -> &[StallIncident] {
+        &self.stalls
+    }
+    /// Returns the first sighting of the first case and the completion of the last one
+    pub fn span(&self) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        let start = self.cases.iter().map(|case| case.first_seen).min()?;
+        let end = self.cases.iter().map(|case| case.completed).max()?;
+        Some((start, end))
+    }
+    /// Returns the wall-clock time from the first sighting of the first case to the completion
+    /// of the last one in seconds
+    pub fn wall_clock_secs(&self) -> i64 {
+        self.span()
+            .map_or(0, |(start, end)| (end - start).num_seconds().max(0))
+    }
+    /// Returns the average throughput of the cases by number of cores
+    pub fn throughput(&self) -> Vec<ConfigurationThroughput> {
+        let mut configurations: BTreeMap<Option<usize>, (usize, usize, i64)> = BTreeMap::new();
+        for case in &self.cases {
+            let (cases, steps, secs) = configurations.entry(case.cores).or_default();
+            *cases += 1;
+            *steps += case.steps;
+            *secs += case.runtime_secs();
+        }
+        configurations
+            .into_iter()
+            .map(|(cores, (cases, steps, secs))| ConfigurationThroughput {
+                cores,
+                cases,
+                steps_per_hour: (secs > 0).then(|| steps as f64 * 3600. / secs as f64),
+            })
+            .collect()
+    }
+    /// Returns the title of the summary
+    fn title(&self) -> String {
+        match &self.campaign {
+            Some(campaign) => format!("Campaign {campaign} summary"),
+            None => "Campaign summary".to_string(),
+        }
+    }
+    /// Returns the overview lines of the summary
+    fn overview(&self) -> Vec<String> {
+        let mut lines = vec![format!("Completed cases: {}", self.cases.len())];
+        if let Some((start, end)) = self.span() {
+            lines.push(format!(
+                "Wall-clock: {} ({} to {})",
+                format_hours_minutes(self.wall_clock_secs()),
+                start.format(AT_FORMAT),
+                end.format(AT_FORMAT)
+            ));
+        }
+        lines.push(format!("Stall incidents: {}", self.stalls.len()));
+        lines
+    }
+    /// Returns the table of the throughput by configuration
+    fn throughput_table(&self) -> Table {
+        Table {
+            header: &["Cores", "Cases", "Step/h"],
+            rows: self
+                .throughput()
+                .into_iter()
+                .map(|configuration| {
+                    vec![
+                        or_dash(configuration.cores),
+                        configuration.cases.to_string(),
+                        or_dash(
+                            configuration
+                                .steps_per_hour
+                                .map(|rate| format!("{rate:.0}")),
+                        ),
+                    ]
+                })
+                .collect(),
+        }
+    }
+    /// Returns the table of the cases
+    fn case_table(&self) -> Table {
+        Table {
+            header: &[
+                "Case",
+                "Cores",
+                "First seen",
+                "Completed",
+                "Runtime",
+                "Steps",
+                "Step/h",
+                "Stalls",
+            ],
+            rows: self
+                .cases
+                .iter()
+                .map(|case| {
+                    vec![
+                        case.name.clone(),
+                        or_dash(case.cores),
+                        case.first_seen.format(AT_FORMAT).to_string(),
+                        case.completed.format(AT_FORMAT).to_string(),
+                        format_hours_minutes(case.runtime_secs()),
+                        case.steps.to_string(),
+                        or_dash(case.steps_per_hour().map(|rate| format!("{rate:.0}"))),
+                        case.stalls.to_string(),
+                    ]
+                })
+                .collect(),
+        }
+    }
+    /// Returns the table of the stall incidents
+    fn stall_table(&self) -> Table {
+        Table {
+            header: &["Case", "Stalled at"],
+            rows: self
+                .stalls
+                .iter()
+                .map(|stall| vec![stall.case.clone(), stall.at.format(AT_FORMAT).to_string()])
+                .collect(),
+        }
+    }
+    /// Returns the offset and the length of the bar of each case on the timeline,
+    /// at least a character long
+    fn timeline(&self) -> Vec<(&str, usize, usize)> {
+        let Some((start, _)) = self.span() else {
+            return vec![];
+        };
+        let span = self.wall_clock_secs().max(1) as f64;
+        let column = |at: DateTime<Local>| {
+            ((at - start).num_seconds() as f64 / span * TIMELINE_WIDTH as f64).round() as usize
+        };
+        self.cases
+            .iter()
+            .map(|case| {
+                let offset = column(case.first_seen).min(TIMELINE_WIDTH - 1);
+                let length = column(case.completed).saturating_sub(offset).max(1);
+                (
+                    case.name.as_str(),
+                    offset,
+                    length.min(TIMELINE_WIDTH - offset),
+                )
+            })
+            .collect()
+    }
+    /// Renders the summary as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("# {}\n\n", self.title());
+        for line in self.overview() {
+            writeln!(markdown, "- {line}").unwrap();
+        }
+        write!(
+            markdown,
+            "\n## Throughput per configuration\n\n{}",
+            self.throughput_table().to_markdown()
+        )
+        .unwrap();
+        write!(
+            markdown,
+            "\n## Cases\n\n{}",
+            self.case_table().to_markdown()
+        )
+        .unwrap();
+        if !self.stalls.is_empty() {
+            write!(
+                markdown,
+                "\n## Stall incidents\n\n{}",
+                self.stall_table().to_markdown()
+            )
+            .unwrap();
+        }
+        let timeline = self.timeline();
+        if !timeline.is_empty() {
+            let width = timeline
+                .iter()
+                .map(|(name, ..)| name.len())
+                .max()
+                .unwrap_or(0);
+            markdown.push_str("\n## Timeline\n\n```text\n");
+            for (name, offset, length) in timeline {
+                writeln!(
+                    markdown,
+                    "{name:width$}  |{}{}{}|",
+                    " ".repeat(offset),
+                    "█".repeat(length),
+                    " ".repeat(TIMELINE_WIDTH - offset - length)
+                )
+                .unwrap();
+            }
+            markdown.push_str("```\n");
+        }
+        markdown
+    }
+    /// Renders the summary as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let title = escape(&self.title());
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\n\
+             body {{ font-family: sans-serif; }}\n\
+             table {{ border-collapse: collapse; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 2px 8px; }}\n\
+             .timeline {{ width: 40em; background: #eee; }}\n\
+             .bar {{ height: 1em; background: #369; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n<ul>\n"
+        );
+        for line in self.overview() {
+            writeln!(html, "<li>{}</li>", escape(&line)).unwrap();
+        }
+        html.push_str("</ul>\n");
+        write!(
+            html,
+            "<h2>Throughput per configuration</h2>\n{}",
+            self.throughput_table().to_html()
+        )
+        .unwrap();
+        write!(html, "<h2>Cases</h2>\n{}", self.case_table().to_html()).unwrap();
+        if !self.stalls.is_empty() {
+            write!(
+                html,
+                "<h2>Stall incidents</h2>\n{}",
+                self.stall_table().to_html()
+            )
+            .unwrap();
+        }
+        let timeline = self.timeline();
+        if !timeline.is_empty() {
+            html.push_str("<h2>Timeline</h2>\n<table>\n");
+            let percent = |columns: usize| columns as f64 * 100. / TIMELINE_WIDTH as f64;
+            for (name, offset, length) in timeline {
+                writeln!(
+                    html,
+                    "<tr><td>{}</td><td class=\"timeline\"><div class=\"bar\" \
+                     style=\"margin-left: {:.1}%; width: {:.1}%\"></div></td></tr>",
+                    escape(name),
+                    percent(offset),
+                    percent(length)
+                )
+                .unwrap();
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Table of the summary, rendered as Markdown or HTML
+struct Table {
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn to_markdown(&self) -> String {
+        let mut markdown = format!("| {} |\n", self.header.join(" | "));
+        writeln!(markdown, "|{}", "---|".repeat(self.header.len())).unwrap();
+        for row in &self.rows {
+            writeln!(markdown, "| {} |", row.join(" | ")).unwrap();
+        }
+        markdown
+    }
+    fn to_html(&self) -> String {
+        let cells = |tag: &str, cells: &mut dyn Iterator<Item = &str>| {
+            cells
+                .map(|cell| format!("<{tag}>{}</{tag}>", escape(cell)))
+                .collect::<String>()
+        };
+        let mut html = format!(
+            "<table>\n<tr>{}</tr>\n",
+            cells("th", &mut self.header.iter().copied())
+        );
+        for row in &self.rows {
+            writeln!(
+                html,
+                "<tr>{}</tr>",
+                cells("td", &mut row.iter().map(String::as_str))
+            )
+            .unwrap();
+        }
+        html.push_str("</table>\n");
+        html
+    }
+}
+
+/// Returns the value or a dash without it
+fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+/// Escapes the HTML special characters of a text
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,380 @@
+use std::{fmt::Write, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    CaseFilter, Column, ColumnSpec, Config, DisplayProfile, FilterTerm, SimTimeAlignment, SortKey,
+    StatusSnapshot,
+};
+
+/// Maximum number of tabs, selected with the keys 1 to 9
+const MAX_TABS: usize = 9;
+
+/// Tab of the status board showing the cases of a campaign or profile
+///
+/// ```toml
+/// [[tabs]]
+/// name = "zen30"
+/// filter = ["group=zen30"]
+/// columns = ["name", "percent", "steps_per_hour", "eta"]
+/// sort_by = "eta"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct TabConfig {
+    pub name: String,
+    /// Conditions all satisfied by the cases of the tab, all the cases by default
+    #[serde(default)]
+    pub filter: Vec<FilterTerm>,
+    /// Shows only the stalled cases
+    #[serde(default)]
+    pub stalled: bool,
+    /// Columns of the table of the tab, the configured columns by default
+    pub columns: Option<Vec<Column>>,
+    /// Column the rows of the tab are sorted by, the configured order by default
+    pub sort_by: Option<SortKey>,
+}
+
+/// Tab of the status board with its own filter, columns and sort order
+#[derive(Debug, Clone)]
+pub struct Tab {
+    pub name: String,
+    pub filter: CaseFilter,
+    pub columns: ColumnSpec,
+}
+
+impl Tab {
+    /// Sorts the rows by the next column of the table, in increasing order
+    pub fn sort_next(&mut self) -> &mut Self {
+        let columns = &self.columns.columns;
+        let i = columns
+            .iter()
+            .position(|column| *column == self.columns.sort_by.column)
+            .map_or(0, |i| (i + 1) % columns.len());
+        self.columns.sort_by = SortKey {
+            column: columns[i],
+            descending: false,
+        };
+        self
+    }
+    /// Reverses the order of the rows
+    pub fn reverse(&mut self) -> &mut Self {
+        self.columns.sort_by.descending = !self.columns.sort_by.descending;
+        self
+    }
+}
+
+/// Tabs of the status board, all drawn from the snapshots of the same monitor
+///
+/// Without configured tabs, there is a tab with all the cases
+/// followed by a tab per campaign
+///
+/// A [display profile](DisplayProfile) replaces the columns of all the tabs
+#[derive(Debug, Clone)]
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+    /// Columns of the tabs without a display profile
+    configured: Vec<ColumnSpec>,
+    profiles: Vec<(String, DisplayProfile)>,
+    /// Display profile applied, if any
+    profile: Option<usize>,
+    /// Row of the case selected in the tab shown
+    selected: Option<usize>,
+    /// Case whose detail view is open
+    detail: Option<String>,
+    /// Case whose note is being edited, with the text typed so far
+    editing: Option<(String, String)>,
+    /// Case whose note was edited, with its new text
+    edited: Option<(String, String)>,
+    /// Case whose alerts were acknowledged
+    acknowledged: Option<String>,
+    /// Set while the cases are aligned on simulated time instead of tabulated
+    aligned: bool,
+}
+
+impl Tabs {
+    /// Creates the tabs of the configuration
+    pub fn new(config: &Config) -> Self {
+        let columns = config.column_spec();
+        let mut tabs: Vec<_> = config
+            .tabs
+            .iter()
+            .map(|tab| Tab {
+                name: tab.name.clone(),
+                filter: CaseFilter {
+                    terms: tab.filter.clone(),
+                    stalled: tab.stalled,
+                },
+                columns: ColumnSpec::new(
+                    tab.columns.clone().unwrap_or(columns.columns.clone()),
+                    tab.sort_by.unwrap_or(columns.sort_by),
+                )
+                .with_width(columns.width, columns.wrap),
+            })
+            .collect();
+        if tabs.is_empty() {
+            let mut groups: Vec<&str> = vec![];
+            for group in config.cases.iter().filter_map(|case| case.group.as_deref()) {
+                if !groups.contains(&group) {
+                    groups.push(group);
+                }
+            }
+            let tab = |name: &str, terms| Tab {
+                name: name.to_string(),
+                filter: CaseFilter {
+                    terms,
+                    stalled: false,
+                },
+                columns: columns.clone(),
+            };
+            tabs.push(tab("all", vec![]));
+            tabs.extend(
+                groups
+                    .into_iter()
+                    .map(|group| tab(group, vec![FilterTerm::Group(group.to_string())])),
+            );
+        }
+        tabs.truncate(MAX_TABS);
+        let mut tabs = Self {
+            configured: tabs.iter().map(|tab| tab.columns.clone()).collect(),
+            tabs,
+            active: 0,
+            profiles: config.display_profiles(),
+            profile: None,
+            selected: None,
+            detail: None,
+            editing: None,
+            edited: None,
+            acknowledged: None,
+            aligned: false,
+        };
+        tabs.set_profile(config.profile.as_deref());
+        tabs
+    }
+    /// Applies the display profile of the given name to the tabs, none for the configured
+    /// columns, returns false if there is no such profile
+    pub fn set_profile(&mut self, name: Option<&str>) -> bool {
+        let profile = match name {
+            Some(name) => match self
+                .profiles
+                .iter()
+                .position(|(profile, _)| profile == name)
+            {
+                Some(i) => Some(i),
+                None => return false,
+            },
+            None => None,
+        };
+        self.profile = profile;
+        for (tab, configured) in self.tabs.iter_mut().zip(&self.configured) {
+            let width = tab.columns.width;
+            tab.columns = match profile {
+                Some(i) => self.profiles[i].1.column_spec(configured),
+                None => configured.clone(),
+            };
+            tab.columns.width = width;
+        }
+        true
+    }
+    /// Returns the display profile applied with its name, if any
+    pub fn profile(&self) -> Option<(&str, &DisplayProfile)> {
+        self.profile
+            .map(|i| (self.profiles[i].0.as_str(), &self.profiles[i].1))
+    }
+    /// Returns the interval between the refreshes of the board of the display profile, if set
+    pub fn refresh(&self) -> Option<Duration> {
+        self.profile()?.1.refresh()
+    }
+    /// Sets the width the tables of the tabs are fitted to, e.g. on a terminal resize
+    pub fn set_width(&mut self, width: Option<usize>) -> &mut Self {
+        for tab in &mut self.tabs {
+            tab.columns.width = width;
+        }
+        self
+    }
+    /// Returns the tab shown
+    pub fn active(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+    /// Returns the case whose detail view is open, if any
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+    /// Returns the case whose note was edited with its new text, empty to remove the note,
+    /// once after the edit
+    pub fn take_note(&mut self) -> Option<(String, String)> {
+        self.edited.take()
+    }
+    /// Returns the case whose alerts were acknowledged, once after the key press
+    pub fn take_acknowledged(&mut self) -> Option<String> {
+        self.acknowledged.take()
+    }
+    /// Returns the snapshot of the cases of the tab shown, with its columns
+    /// and the grouping and colors of the display profile
+    fn board(&self, snapshot: &StatusSnapshot) -> StatusSnapshot {
+        let tab = self.active();
+        let board = snapshot
+            .filtered(&tab.filter)
+            .with_columns(tab.columns.clone());
+        match self.profile() {
+            Some((_, profile)) => profile.style(board),
+            None => board,
+        }
+    }
+    /// Returns the names of the cases of the tab shown in the order of its rows
+    fn rows(&self, snapshot: &StatusSnapshot) -> Vec<String> {
+        self.board(snapshot)
+            .rows()
+            .into_iter()
+            .map(|case| case.name().to_string())
+            .collect()
+    }
+    /// Returns the case selected in the tab shown, if any
+    fn selected(&self, snapshot: &StatusSnapshot) -> Option<String> {
+        let rows = self.rows(snapshot);
+        let i = self.selected?.min(rows.len().checked_sub(1)?);
+        rows.into_iter().nth(i)
+    }
+    /// Handles a key press on the board of the latest snapshot, returns true if the board changed
+    ///
+    /// The keys 1 to 9 select a tab, `s` sorts by the next column and `r` reverses the order,
+    /// `j` and `k` select the next and previous case, Enter opens or closes the detail view
+    /// of the case selected and Escape closes it, `n` edits the note of the case selected,
+    /// saved with Enter and dropped with Escape, and `p` switches to the next display profile
+    pub fn handle_key(&mut self, key: char, snapshot: Option<&StatusSnapshot>) -> bool {
+        if let Some((_, text)) = self.editing.as_mut() {
+            match key {
+                '\n' | '\r' => self.edited = self.editing.take(),
+                '\x1b' => self.editing = None,
+                '\x7f' | '\x08' => {
+                    text.pop();
+                }
+                key if !key.is_control() => text.push(key),
+                _ => return false,
+            }
+            return true;
+        }
+        match key {
+            '1'..='9' => {
+                let i = key as usize - '1' as usize;
+                let changed = i < self.tabs.len() && i != self.active;
+                if changed {
+                    self.active = i;
+                    self.selected = None;
+                }
+                changed
+            }
+            'j' | 'k' => {
+                let rows = snapshot.map_or(0, |snapshot| self.rows(snapshot).len());
+                let Some(last) = rows.checked_sub(1) else {
+                    return false;
+                };
+                self.selected = Some(match (self.selected, key) {
+                    (None, _) => 0,
+                    (Some(i), 'j') => (i + 1).min(last),
+                    (Some(i), _) => i.min(last).saturating_sub(1),
+                });
+                true
+            }
+            '\n' | '\r' if self.detail.is_some() => {
+                self.detail = None;
+                true
+            }
+            '\n' | '\r' => {
+                self.detail = snapshot.and_then(|snapshot| self.selected(snapshot));
+                self.detail.is_some()
+            }
+            '\x1b' => self.detail.take().is_some(),
+            'n' => {
+                let Some(snapshot) = snapshot else {
+                    return false;
+                };
+                self.editing = self.selected(snapshot).map(|name| {
+                    let text = snapshot
+                        .cases()
+                        .find(|case| case.name() == name)
+                        .and_then(|case| case.note())
+                        .map_or_else(String::new, |note| note.text.clone());
+                    (name, text)
+                });
+                self.editing.is_some()
+            }
+            'a' => {
+                self.acknowledged = snapshot.and_then(|snapshot| self.selected(snapshot));
+                self.acknowledged.is_some()
+            }
+            'c' => {
+                self.aligned = !self.aligned;
+                true
+            }
+            's' => {
+                self.tabs[self.active].sort_next();
+                true
+            }
+            'r' => {
+                self.tabs[self.active].reverse();
+                true
+            }
+            'p' if !self.profiles.is_empty() => {
+                // the configured columns come after the last profile
+                let next = match self.profile {
+                    None => Some(0),
+                    Some(i) => self.profiles.get(i + 1).map(|_| i + 1),
+                };
+                let name = next.map(|i| self.profiles[i].0.clone());
+                self.set_profile(name.as_deref())
+            }
+            _ => false,
+        }
+    }
+    /// Returns the status board of the tab shown, below the tab bar if there are several tabs
+    /// and the case selected if any, the cases of the tab being aligned on simulated time
+    /// once toggled
+    pub fn render(&self, snapshot: &StatusSnapshot) -> String {
+        let tab = self.active();
+        let filtered = self.board(snapshot);
+        let mut board = if self.aligned {
+            format!(
+                "{}(c: back to the table)\n",
+                SimTimeAlignment::new(filtered.cases())
+            )
+        } else {
+            filtered.to_string()
+        };
+        if let Some((case, text)) = &self.editing {
+            board = format!("note of {case}: {text}_ (Enter: save, Esc: cancel)\n{board}");
+        } else if let Some(selected) = self.selected(snapshot) {
+            board = format!(
+                "selected: {selected} (j/k: select, Enter: details, n: note, a: ack, c: align)\n{board}"
+            );
+        }
+        let profile = self
+            .profile()
+            .map(|(name, _)| format!("  profile: {name} (p: next)"));
+        if self.tabs.len() < 2 {
+            return match profile {
+                Some(profile) => format!("{}\n{board}", profile.trim_start()),
+                None => board,
+            };
+        }
+        let mut bar = String::new();
+        for (i, tab) in self.tabs.iter().enumerate() {
+            let label = format!("{} {}", i + 1, tab.name);
+            if i == self.active {
+                write!(bar, "[{label}] ").unwrap();
+            } else {
+                write!(bar, " {label}  ").unwrap();
+            }
+        }
+        let sort_by = tab.columns.sort_by;
+        writeln!(
+            bar,
+            "  sort: {}{} (s: next, r: reverse){}",
+            if sort_by.descending { "-" } else { "" },
+            sort_by.column,
+            profile.unwrap_or_default()
+        )
+        .unwrap();
+        bar + &board
+    }
+}
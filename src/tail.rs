@@ -0,0 +1,68 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::rotated::{is_gzip, open_log};
+
+/// Incremental reader of a growing log file
+///
+/// Remembers the offset of the last complete line read,
+/// and starts over from the beginning if the file shrinks.
+/// A gzip-compressed file is read whole again only if its size changes
+#[derive(Debug, Default, Clone)]
+pub struct LogTail {
+    offset: u64,
+    chunk_size: Option<usize>,
+}
+
+impl LogTail {
+    /// Creates a new reader starting at the beginning of the file
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Sets the size in bytes of the read buffer, 8 KiB by default,
+    /// larger buffers making fewer requests to networked filesystems
+    pub fn chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = Some(bytes.max(1));
+        self
+    }
+    /// Starts over from the beginning of the file at the next read
+    pub fn rewind(&mut self) {
+        self.offset = 0;
+    }
+    /// Returns the complete lines appended to the file since the last read
+    pub fn read_new<P: AsRef<Path>>(&mut self, path: P) -> io::Result<Vec<String>> {
+        if is_gzip(&path) {
+            let len = path.as_ref().metadata()?.len();
+            if len == self.offset {
+                return Ok(vec![]);
+            }
+            self.offset = len;
+            return open_log(path)?.lines().collect();
+        }
+        let mut file = File::open(path)?;
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut reader = match self.chunk_size {
+            Some(bytes) => BufReader::with_capacity(bytes, file),
+            None => BufReader::new(file),
+        };
+        let mut lines = vec![];
+        let mut buffer = vec![];
+        loop {
+            buffer.clear();
+            let n = reader.read_until(b'\n', &mut buffer)?;
+            // a partial last line is read again at the next call
+            if n == 0 || buffer.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += n as u64;
+            lines.push(String::from_utf8_lossy(&buffer).trim_end().to_string());
+        }
+        Ok(lines)
+    }
+}
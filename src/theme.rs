@@ -0,0 +1,169 @@
+use std::fmt::Display;
+
+use owo_colors::{DynColors, OwoColorize, Style};
+use serde::Deserialize;
+
+use crate::{Alert, Case, Change, Severity};
+
+#[derive(Debug, thiserror::Error)]
+#[error("invalid theme color {0:?}, expected a color name, `#rrggbb`, `dim` or `none`")]
+pub struct ThemeError(String);
+
+/// Theme configuration, each color is a name (`"red"`, `"bright black"`), `#rrggbb`,
+/// `dim` for a dimmed default foreground or `none`
+///
+/// ```toml
+/// [theme]
+/// on_track = "green"
+/// slowing = "yellow"
+/// failing = "red"
+/// late = "red"
+/// completed = "dim"
+/// info = "cyan"
+/// warning = "yellow"
+/// critical = "bright red"
+/// improved = "green"
+/// regressed = "red"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct ThemeConfig {
+    on_track: String,
+    slowing: String,
+    failing: String,
+    late: String,
+    completed: String,
+    info: String,
+    warning: String,
+    critical: String,
+    improved: String,
+    regressed: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            on_track: "green".to_string(),
+            slowing: "yellow".to_string(),
+            failing: "red".to_string(),
+            late: "red".to_string(),
+            completed: "dim".to_string(),
+            info: "cyan".to_string(),
+            warning: "yellow".to_string(),
+            critical: "bright red".to_string(),
+            improved: "green".to_string(),
+            regressed: "red".to_string(),
+        }
+    }
+}
+
+/// Colors of the status table rows
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "ThemeConfig")]
+pub struct Theme {
+    /// Running cases progressing normally
+    pub on_track: Style,
+    /// Cases whose latest time step duration is well above the mean
+    pub slowing: Style,
+    /// Stalled cases and cases failing to update
+    pub failing: Style,
+    /// Cases projected to miss their deadline
+    pub late: Style,
+    /// Completed cases
+    pub completed: Style,
+    /// Informational alerts
+    pub info: Style,
+    /// Warning alerts
+    pub warning: Style,
+    /// Critical alerts
+    pub critical: Style,
+    /// Changes for the better between refreshes, e.g. an earlier ETA
+    pub improved: Style,
+    /// Changes for the worse between refreshes, e.g. a later ETA
+    pub regressed: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::try_from(ThemeConfig::default()).expect("invalid default theme")
+    }
+}
+
+impl TryFrom<ThemeConfig> for Theme {
+    type Error = ThemeError;
+
+    fn try_from(config: ThemeConfig) -> Result<Self, Self::Error> {
+        Ok(Self {
+            on_track: style(&config.on_track)?,
+            slowing: style(&config.slowing)?,
+            failing: style(&config.failing)?,
+            late: style(&config.late)?,
+            completed: style(&config.completed)?,
+            info: style(&config.info)?,
+            warning: style(&config.warning)?,
+            critical: style(&config.critical)?,
+            improved: style(&config.improved)?,
+            regressed: style(&config.regressed)?,
+        })
+    }
+}
+
+fn style(color: &str) -> Result<Style, ThemeError> {
+    match color.trim() {
+        "none" | "" => Ok(Style::new()),
+        "dim" => Ok(Style::new().dimmed()),
+        color => color
+            .parse::<DynColors>()
+            .map(|color| Style::new().color(color))
+            .map_err(|_| ThemeError(color.to_string())),
+    }
+}
+
+impl Theme {
+    /// Returns the style of the row of a case
+    ///
+    /// Failing takes precedence over late, then slowing;
+    /// pending, waiting and initializing cases are not colored
+    pub fn case_style(&self, case: &Case) -> Style {
+        if case.is_complete() {
+            self.completed
+        } else if case.is_stalled() || case.error().is_some() || case.log_failure().is_some() {
+            self.failing
+        } else if case.is_late() {
+            self.late
+        } else if case.is_pending() || case.is_waiting() || case.is_initializing() {
+            Style::new()
+        } else if case.is_slowing() {
+            self.slowing
+        } else {
+            self.on_track
+        }
+    }
+    /// Returns the row of a case in its style
+    pub fn paint<T: Display>(&self, case: &Case, row: T) -> String {
+        row.style(self.case_style(case)).to_string()
+    }
+    /// Returns the style of an alert, dimmed once expected or acknowledged
+    pub fn alert_style(&self, alert: &Alert) -> Style {
+        if alert.expected.is_some() || alert.acknowledged {
+            return Style::new().dimmed();
+        }
+        match alert.severity() {
+            Severity::Info => self.info,
+            Severity::Warning => self.warning,
+            Severity::Critical => self.critical,
+        }
+    }
+    /// Returns the line of an alert in its style
+    pub fn paint_alert(&self, alert: &Alert) -> String {
+        alert.style(self.alert_style(alert)).to_string()
+    }
+    /// Returns the annotation of a change between refreshes in its style
+    pub fn paint_change<T: Display>(&self, change: Change, annotation: T) -> String {
+        let style = match change {
+            Change::Improved => self.improved,
+            Change::Regressed => self.regressed,
+        };
+        annotation.style(style).to_string()
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    sync::{Arc, Condvar, Mutex, OnceLock},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+/// Limits of the log reads, so polling many cases on a parallel or networked filesystem
+/// (Lustre, NFS, EFS) does not cause metadata storms
+///
+/// ```toml
+/// [filesystem]
+/// max_concurrent_reads = 4
+/// reads_per_sec = 20
+/// chunk_size = 1048576
+/// ```
+///
+/// The logs are stat'ed before being read, the unchanged ones being skipped
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FilesystemConfig {
+    /// Maximum number of logs read at the same time
+    pub max_concurrent_reads: Option<usize>,
+    /// Maximum number of log reads per second
+    pub reads_per_sec: Option<f64>,
+    /// Size in bytes of the buffer the logs are read with, 8 KiB by default
+    pub chunk_size: Option<usize>,
+    #[serde(skip)]
+    throttle: OnceLock<Option<Arc<ReadThrottle>>>,
+}
+
+impl FilesystemConfig {
+    /// Returns the options of the log reads, the throttle being shared by all the cases
+    pub fn read_options(&self) -> ReadOptions {
+        let throttle = self.throttle.get_or_init(|| {
+            (self.max_concurrent_reads.is_some() || self.reads_per_sec.is_some()).then(|| {
+                Arc::new(ReadThrottle::new(
+                    self.max_concurrent_reads,
+                    self.reads_per_sec,
+                ))
+            })
+        });
+        ReadOptions {
+            throttle: throttle.clone(),
+            chunk_size: self.chunk_size,
+        }
+    }
+}
+
+/// Options of the reads of a log file
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Throttle every read waits for
+    pub throttle: Option<Arc<ReadThrottle>>,
+    /// Size in bytes of the read buffer
+    pub chunk_size: Option<usize>,
+}
+
+impl ReadOptions {
+    /// Waits for the throttle, if any, returning the permit to hold while reading
+    pub fn acquire(&self) -> Option<ReadPermit<'_>> {
+        self.throttle.as_deref().map(ReadThrottle::acquire)
+    }
+}
+
+#[derive(Debug)]
+struct ThrottleState {
+    active: usize,
+    next: Option<Instant>,
+}
+
+/// Concurrency cap and rate limiter of the log reads
+#[derive(Debug)]
+pub struct ReadThrottle {
+    max_concurrent: Option<usize>,
+    interval: Option<Duration>,
+    state: Mutex<ThrottleState>,
+    released: Condvar,
+}
+
+impl ReadThrottle {
+    /// Creates a new throttle allowing at most `max_concurrent` reads at the same time
+    /// and `per_sec` reads per second
+    pub fn new(max_concurrent: Option<usize>, per_sec: Option<f64>) -> Self {
+        Self {
+            max_concurrent: max_concurrent.map(|max| max.max(1)),
+            interval: per_sec
+                .filter(|rate| *rate > 0.)
+                .map(|rate| Duration::from_secs_f64(1. / rate)),
+            state: Mutex::new(ThrottleState {
+                active: 0,
+                next: None,
+            }),
+            released: Condvar::new(),
+        }
+    }
+    /// Waits for a read to be allowed, the read ending when the permit is dropped
+    pub fn acquire(&self) -> ReadPermit<'_> {
+        let mut state = self.state.lock().unwrap();
+        while self.max_concurrent.is_some_and(|max| state.active >= max) {
+            state = self.released.wait(state).unwrap();
+        }
+        state.active += 1;
+        let wait = self.interval.and_then(|interval| {
+            let now = Instant::now();
+            let slot = state.next.map_or(now, |next| next.max(now));
+            state.next = Some(slot + interval);
+            slot.checked_duration_since(now)
+        });
+        drop(state);
+        if let Some(wait) = wait.filter(|wait| !wait.is_zero()) {
+            sleep(wait);
+        }
+        ReadPermit(self)
+    }
+    /// Returns the number of reads in progress
+    pub fn active(&self) -> usize {
+        self.state.lock().unwrap().active
+    }
+}
+
+/// Read allowed by a [ReadThrottle], until dropped
+#[derive(Debug)]
+pub struct ReadPermit<'a>(&'a ReadThrottle);
+
+impl Drop for ReadPermit<'_> {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().active -= 1;
+        self.0.released.notify_one();
+    }
+}
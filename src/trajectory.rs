@@ -0,0 +1,293 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Number of time steps kept in a trajectory
+pub const TRAJECTORY_LEN: usize = 32;
+
+/// How the solver advances the simulated time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeStepping {
+    /// Constant time step, progress is the time step count
+    #[default]
+    Fixed,
+    /// Variable time step, progress is the simulated time and the remaining
+    /// time steps are projected from the trend of the time step
+    Adaptive,
+}
+
+/// Time step of a case seen at `wall` seconds after the first sample of its trajectory
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TrajectoryPoint {
+    pub wall: f64,
+    pub step: usize,
+    pub time: f64,
+}
+
+/// Latest time steps of a case with the simulated and the wall-clock times they were seen at
+#[derive(Debug, Clone, Default)]
+pub struct Trajectory {
+    points: VecDeque<TrajectoryPoint>,
+    wall: f64,
+}
+
+impl Trajectory {
+    /// Adds a time step seen `secs` seconds after the previous one
+    ///
+    /// The trajectory starts over if the time step goes back, e.g. on a restart
+    pub fn push(&mut self, secs: f64, step: usize, time: f64) -> &mut Self {
+        if self.points.back().is_some_and(|point| step < point.step) {
+            self.points.clear();
+        }
+        if self.points.len() == TRAJECTORY_LEN {
+            self.points.pop_front();
+        }
+        self.wall += secs;
+        self.points.push_back(TrajectoryPoint {
+            wall: self.wall,
+            step,
+            time,
+        });
+        self
+    }
+    /// Returns an iterator over the time steps, from oldest to latest
+    pub fn points(&self) -> impl Iterator<Item = &TrajectoryPoint> {
+        self.points.iter()
+    }
+    /// Returns the simulated seconds per wall-clock second over the trajectory
+    pub fn sim_rate(&self) -> Option<f64> {
+        let (first, last) = self.points.front().zip(self.points.back())?;
+        let wall = last.wall - first.wall;
+        (wall > 0.).then(|| (last.time - first.time) / wall)
+    }
+    /// Returns the least-squares fit of the time step against the time step count
+    pub fn dt_trend(&self) -> Option<DtTrend> {
+        let samples: Vec<_> = self
+            .points
+            .iter()
+            .zip(self.points.iter().skip(1))
+            .filter(|(a, b)| b.step > a.step && b.time > a.time)
+            .map(|(a, b)| {
+                (
+                    (a.step + b.step) as f64 / 2.,
+                    (b.time - a.time) / (b.step - a.step) as f64,
+                )
+            })
+            .collect();
+        let (&(_, latest), n) = (samples.last()?, samples.len() as f64);
+        let min = samples
+            .iter()
+            .map(|(_, dt)| *dt)
+            .fold(f64::INFINITY, f64::min);
+        let step = self.points.back()?.step as f64;
+        let (mean_x, mean_y) = samples
+            .iter()
+            .fold((0., 0.), |(x, y), (sx, sy)| (x + sx / n, y + sy / n));
+        let (sxy, sxx) = samples.iter().fold((0., 0.), |(sxy, sxx), (x, y)| {
+            (
+                sxy + (x - mean_x) * (y - mean_y),
+                sxx + (x - mean_x).powi(2),
+            )
+        });
+        let slope = if sxx > 0. { sxy / sxx } else { 0. };
+        let dt = mean_y + slope * (step - mean_x);
+        Some(if dt > 0. {
+            DtTrend { dt, slope, min }
+        } else {
+            DtTrend {
+                dt: latest,
+                slope: 0.,
+                min,
+            }
+        })
+    }
+}
+
+/// Linear trend of the time step of an adaptive time stepping solver
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DtTrend {
+    /// Time step at the latest time step count in simulated seconds
+    pub dt: f64,
+    /// Change of the time step per time step
+    pub slope: f64,
+    /// Smallest time step seen, a shrinking time step is not extrapolated below it
+    pub min: f64,
+}
+
+impl DtTrend {
+    /// Returns the number of time steps needed to simulate `secs` more seconds
+    pub fn steps_to(&self, secs: f64) -> f64 {
+        let covered = |n: f64| self.dt * n + self.slope * n * n / 2.;
+        let solve = |slope: f64| {
+            if slope.abs() * secs < 1e-9 * self.dt * self.dt {
+                secs / self.dt
+            } else {
+                (-self.dt + (self.dt * self.dt + 2. * slope * secs).max(0.).sqrt()) / slope
+            }
+        };
+        if self.slope >= 0. {
+            return solve(self.slope);
+        }
+        if self.dt <= self.min {
+            return secs / self.dt;
+        }
+        // the time step shrinks down to the smallest time step seen and holds there
+        let floor = (self.min - self.dt) / self.slope;
+        if covered(floor) >= secs {
+            solve(self.slope)
+        } else {
+            floor + (secs - covered(floor)) / self.min
+        }
+    }
+}
+
+/// Relative change of the time step above which a fixed time step is deemed changed
+pub(crate) const DT_CHANGE_RATIO: f64 = 0.01;
+/// Number of time step changes kept per case
+const DT_CHANGE_LEN: usize = 100;
+
+/// Change of the time step of a fixed time stepping solver, deliberate or not
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DtChange {
+    /// First time step with the new time step
+    pub step: usize,
+    pub from: f64,
+    pub to: f64,
+    pub at: DateTime<Local>,
+}
+
+impl Display for DtChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "time step {:.3e}s -> {:.3e}s at step {}",
+            self.from, self.to, self.step
+        )
+    }
+}
+
+/// Default pattern of the log line giving the time step size
+const DT_PATTERN: &str = r"(?i)\btime[ -]?step(?:\s+size)?\s*[:=]\s*(\d+(?:\.\d*)?(?:e[+-]?\d+)?)";
+
+/// Pattern of the log line giving the time step size in seconds, the size being the first capture group
+///
+/// ```toml
+/// dt_pattern = 'Time-step size: (\S+)'
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct DtPattern(Regex);
+
+impl TryFrom<String> for DtPattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::new(&pattern)
+    }
+}
+
+impl Default for DtPattern {
+    fn default() -> Self {
+        Self::new(DT_PATTERN).unwrap()
+    }
+}
+
+impl DtPattern {
+    /// Creates a new time step size pattern
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self(Regex::new(pattern)?))
+    }
+    /// Returns the latest positive time step size reported by the log lines
+    pub fn find_last(&self, lines: &[String]) -> Option<f64> {
+        lines.iter().rev().find_map(|line| {
+            self.0
+                .captures(line)?
+                .get(1)?
+                .as_str()
+                .parse()
+                .ok()
+                .filter(|dt: &f64| *dt > 0.)
+        })
+    }
+}
+
+/// Changes of the simulated time per time step of a case
+#[derive(Debug, Clone, Default)]
+pub struct DtChanges {
+    dt: Option<f64>,
+    initial: Option<f64>,
+    changes: VecDeque<DtChange>,
+    count: usize,
+    /// Last time step of the interval the latest change was found in
+    changed_at: Option<usize>,
+}
+
+impl DtChanges {
+    /// Adds the time steps from `from_step` to `to_step`, simulated from `from_time` to `to_time`,
+    /// returns true if the time step has changed
+    ///
+    /// The tolerance accounts for the 7 significant digits of the simulated time in the logs.
+    /// An interval spanning a change averages both time steps, so a change found
+    /// right after another one amends it
+    pub fn update(
+        &mut self,
+        from_step: usize,
+        to_step: usize,
+        from_time: f64,
+        to_time: f64,
+    ) -> bool {
+        let (n_step, span) = (to_step.saturating_sub(from_step), to_time - from_time);
+        if n_step == 0 || span <= 0. {
+            return false;
+        }
+        let dt = span / n_step as f64;
+        let resolution = 10f64.powf(to_time.log10().floor() - 6.);
+        let tolerance = DT_CHANGE_RATIO.max(4. * resolution / span);
+        let changed = self
+            .dt
+            .is_some_and(|previous| (dt - previous).abs() > tolerance * previous);
+        match self.changes.back_mut() {
+            Some(change) if changed && self.changed_at == Some(from_step) => change.to = dt,
+            _ if changed => {
+                if self.changes.len() == DT_CHANGE_LEN {
+                    self.changes.pop_front();
+                }
+                self.changes.push_back(DtChange {
+                    step: from_step + 1,
+                    from: self.dt.unwrap_or_default(),
+                    to: dt,
+                    at: Local::now(),
+                });
+                self.count += 1;
+            }
+            _ => (),
+        }
+        if changed {
+            self.changed_at = Some(to_step);
+        }
+        if changed || self.dt.is_none() {
+            self.dt = Some(dt);
+        }
+        self.initial = self.initial.or(self.dt);
+        changed
+    }
+    /// Returns the current time step in simulated seconds
+    pub fn dt(&self) -> Option<f64> {
+        self.dt
+    }
+    /// Returns the first time step found in simulated seconds
+    pub fn initial(&self) -> Option<f64> {
+        self.initial
+    }
+    /// Returns the latest time step changes, from oldest to latest
+    pub fn changes(&self) -> impl Iterator<Item = &DtChange> {
+        self.changes.iter()
+    }
+    /// Returns the number of time step changes found so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
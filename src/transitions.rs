@@ -0,0 +1,334 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::{Case, Clock, MonitorEvent, SystemClock};
+
+/// Kind of a state transition of a case or of the monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionKind {
+    /// The monitor started with an empty event log
+    MonitorStarted,
+    /// The monitor started again, appending to the event log of a previous run
+    MonitorRestarted,
+    /// The first time step of a case was found
+    Started,
+    Stalled,
+    SolverDead,
+    /// A stalled or failing case progresses or updates again
+    Resumed,
+    Completed,
+    /// A solver error was found in the log of a case, or its update failed
+    Failed,
+    /// The log of a case was replaced by a different file, e.g. rotated or after a restart
+    LogRotated,
+    /// The log of a case was moved or re-linked
+    LogMoved,
+}
+
+/// State transition of a case or of the monitor, one JSON line of the event log
+///
+/// ```json
+/// {"at":"2024-06-14T18:00:00+02:00","event":"stalled","case":"zen30az045_OS2","group":"baseline","log":"/shared/zen30az045_OS2/solve.out","step":120,"time":6.0,"percent":5}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub at: DateTime<Local>,
+    pub event: TransitionKind,
+    /// Case of the transition, none for the transitions of the monitor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+    /// Latest time step of the case
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<usize>,
+    /// Simulated time of the case in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<f64>,
+    /// Progress of the case in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Transition {
+    /// Creates a transition of the monitor
+    fn monitor(event: TransitionKind, clock: &dyn Clock, detail: String) -> Self {
+        Self {
+            at: clock.now(),
+            event,
+            case: None,
+            group: None,
+            log: None,
+            step: None,
+            time: None,
+            percent: None,
+            detail: Some(detail),
+        }
+    }
+    /// Creates a transition of a case
+    fn case(event: TransitionKind, case: &Case, detail: Option<String>) -> Self {
+        Self {
+            at: case.now(),
+            event,
+            case: Some(case.name().to_string()),
+            group: case.group_name().map(str::to_string),
+            log: Some(case.log_file()),
+            step: case.step(),
+            time: case.step().map(|_| case.time()),
+            percent: case.step().map(|_| case.percent_complete()),
+            detail,
+        }
+    }
+}
+
+/// Append-only event log of the state transitions of the cases and of the monitor,
+/// one JSON line per transition, e.g. `grep zen30az045_OS2 cfd-status-events.jsonl`
+/// or `tail -f` by other tools
+///
+/// The states of the cases are restored from the log of a previous run,
+/// so a case is not started or completed again when the monitor restarts.
+/// The state of a case is forgotten when its log is replaced, when it goes back
+/// to an earlier time step and when it is removed from the configuration
+pub struct TransitionLog {
+    file: File,
+    started: HashSet<String>,
+    stalled: HashSet<String>,
+    dead: HashSet<String>,
+    failed: HashSet<String>,
+    completed: HashSet<String>,
+    /// Latest time step of each case, a restart going back to an earlier one
+    steps: HashMap<String, usize>,
+}
+
+impl TransitionLog {
+    /// Opens the event log at `path`, appending to it if it exists,
+    /// and records the start of the monitor with its number of cases
+    pub fn create<P: AsRef<Path>>(path: P, cases: usize) -> io::Result<Self> {
+        Self::with_clock(path, cases, &SystemClock)
+    }
+    /// Opens the event log at `path` as [TransitionLog::create] does,
+    /// the start of the monitor being timed by `clock`
+    pub fn with_clock<P: AsRef<Path>>(
+        path: P,
+        cases: usize,
+        clock: &dyn Clock,
+    ) -> io::Result<Self> {
+        let previous = match Self::read(&path) {
+            Ok(transitions) => transitions,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        // a last line truncated by a crash is ended, not continued by the next transition
+        if file.metadata()?.len() > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+        let mut log = Self {
+            file,
+            started: HashSet::new(),
+            stalled: HashSet::new(),
+            dead: HashSet::new(),
+            failed: HashSet::new(),
+            completed: HashSet::new(),
+            steps: HashMap::new(),
+        };
+        for transition in &previous {
+            if let Some(case) = &transition.case {
+                log.restore(transition.event, case);
+                if let Some(step) = transition.step {
+                    log.steps.insert(case.clone(), step);
+                }
+            }
+        }
+        let event = if previous.is_empty() {
+            TransitionKind::MonitorStarted
+        } else {
+            TransitionKind::MonitorRestarted
+        };
+        log.write(&Transition::monitor(event, clock, format!("{cases} cases")));
+        Ok(log)
+    }
+    /// Reads the transitions of an event log, in order
+    ///
+    /// The lines that do not parse, e.g. truncated by a crash while written
+    /// or of a transition unknown to this version, are skipped with a warning
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<Transition>> {
+        let path = path.as_ref();
+        let mut transitions = vec![];
+        for (i, line) in BufReader::new(File::open(path)?).split(b'\n').enumerate() {
+            let line = line?;
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            match serde_json::from_slice(&line) {
+                Ok(transition) => transitions.push(transition),
+                Err(e) => tracing::warn!(
+                    path = %path.display(),
+                    line = i + 1,
+                    error = %e,
+                    "skipping an unreadable line of the event log"
+                ),
+            }
+        }
+        Ok(transitions)
+    }
+    /// Updates the state of a case with a transition
+    fn restore(&mut self, event: TransitionKind, case: &str) {
+        let case = case.to_string();
+        match event {
+            TransitionKind::Started => {
+                self.started.insert(case);
+            }
+            TransitionKind::Stalled => {
+                self.stalled.insert(case);
+            }
+            TransitionKind::SolverDead => {
+                self.stalled.insert(case.clone());
+                self.dead.insert(case);
+            }
+            TransitionKind::Failed => {
+                self.failed.insert(case);
+            }
+            TransitionKind::Resumed => {
+                self.stalled.remove(&case);
+                self.dead.remove(&case);
+                self.failed.remove(&case);
+            }
+            TransitionKind::Completed => {
+                self.completed.insert(case);
+            }
+            // a new log, e.g. of a restart, starts and completes again
+            TransitionKind::LogRotated => self.forget(&case),
+            _ => (),
+        }
+    }
+    /// Forgets the state of a case
+    fn forget(&mut self, case: &str) {
+        for states in [
+            &mut self.started,
+            &mut self.stalled,
+            &mut self.dead,
+            &mut self.failed,
+            &mut self.completed,
+        ] {
+            states.remove(case);
+        }
+        self.steps.remove(case);
+    }
+    /// Records the state transitions of a monitor event
+    pub fn record(&mut self, event: &MonitorEvent) {
+        match event {
+            MonitorEvent::Updated(case) => {
+                if let Some(step) = case.step() {
+                    let previous = self.steps.insert(case.name().to_string(), step);
+                    if previous.is_some_and(|previous| step < previous) {
+                        self.forget(case.name());
+                        self.steps.insert(case.name().to_string(), step);
+                    }
+                }
+            }
+            MonitorEvent::Snapshot(snapshot) => {
+                let names: HashSet<_> = snapshot.cases().map(Case::name).collect();
+                let removed: Vec<_> = self
+                    .started
+                    .iter()
+                    .chain(&self.stalled)
+                    .chain(&self.dead)
+                    .chain(&self.failed)
+                    .chain(&self.completed)
+                    .chain(self.steps.keys())
+                    .filter(|case| !names.contains(case.as_str()))
+                    .cloned()
+                    .collect();
+                for case in removed {
+                    self.forget(&case);
+                }
+            }
+            _ => (),
+        }
+        for (kind, case, detail) in self.transitions(event) {
+            self.restore(kind, case.name());
+            self.write(&Transition::case(kind, case, detail));
+        }
+    }
+    /// Returns the state transitions of a monitor event, with their case and detail
+    fn transitions<'a>(
+        &self,
+        event: &'a MonitorEvent,
+    ) -> Vec<(TransitionKind, &'a Case, Option<String>)> {
+        let mut transitions = vec![];
+        match event {
+            MonitorEvent::Updated(case) => {
+                let name = case.name();
+                if case.step().is_some() && !self.started.contains(name) {
+                    transitions.push((TransitionKind::Started, case, None));
+                }
+                if let Some(failure) = case.log_failure() {
+                    if !self.failed.contains(name) {
+                        let detail = format!("{}: {}", failure.name, failure.line);
+                        transitions.push((TransitionKind::Failed, case, Some(detail)));
+                    }
+                } else if self.failed.contains(name) {
+                    transitions.push((TransitionKind::Resumed, case, Some("updated".to_string())));
+                } else if self.stalled.contains(name) && !case.is_stalled() {
+                    transitions.push((
+                        TransitionKind::Resumed,
+                        case,
+                        Some("progressing".to_string()),
+                    ));
+                }
+            }
+            MonitorEvent::Stalled(case) if !self.stalled.contains(case.name()) => {
+                transitions.push((TransitionKind::Stalled, case, None))
+            }
+            MonitorEvent::SolverDead(case) if !self.dead.contains(case.name()) => {
+                transitions.push((TransitionKind::SolverDead, case, None))
+            }
+            MonitorEvent::Completed(case) if !self.completed.contains(case.name()) => {
+                transitions.push((TransitionKind::Completed, case, None))
+            }
+            MonitorEvent::Error { case, error, .. } if !self.failed.contains(case.name()) => {
+                transitions.push((TransitionKind::Failed, case, Some(error.to_string())))
+            }
+            MonitorEvent::LogMoved(case) => {
+                if let Some(relocation) = case.log_relocations().last() {
+                    let kind = if relocation.replaced || relocation.switched {
+                        TransitionKind::LogRotated
+                    } else {
+                        TransitionKind::LogMoved
+                    };
+                    transitions.push((kind, case, Some(relocation.to_string())));
+                }
+            }
+            _ => (),
+        }
+        transitions
+    }
+    fn write(&mut self, transition: &Transition) {
+        let line = serde_json::to_string(transition).expect("transition serialization failed");
+        if let Err(e) = writeln!(self.file, "{line}") {
+            tracing::warn!(error = %e, "failed to append to the event log");
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt::Display;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::StepParser;
+
+/// Log failure signature with the triage hint shown next to a failed case
+///
+/// ```toml
+/// [[triage]]
+/// name = "license lost"
+/// pattern = "(?i)license.*lost"
+/// hint = "check the license server, then resume from the last checkpoint"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "TriageConfig")]
+pub struct TriageRule {
+    name: String,
+    pattern: Regex,
+    hint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TriageConfig {
+    name: String,
+    pattern: String,
+    hint: String,
+}
+
+impl TryFrom<TriageConfig> for TriageRule {
+    type Error = regex::Error;
+
+    fn try_from(config: TriageConfig) -> Result<Self, Self::Error> {
+        Self::new(config.name, &config.pattern, config.hint)
+    }
+}
+
+impl TriageRule {
+    /// Creates a new triage rule
+    pub fn new<S: ToString>(name: S, pattern: &str, hint: S) -> Result<Self, regex::Error> {
+        Ok(Self {
+            name: name.to_string(),
+            pattern: Regex::new(pattern)?,
+            hint: hint.to_string(),
+        })
+    }
+    /// Returns the default rules: license lost, MPI rank failure, out-of-memory,
+    /// NaN residuals and EFS I/O errors
+    pub fn defaults() -> Vec<Self> {
+        [
+            (
+                "license lost",
+                r"(?i)licen[cs]e.*\b(lost|expired|unavailable|denied|checkout failed)\b",
+                "check the license server, then resume from the last checkpoint",
+            ),
+            (
+                "MPI rank failure",
+                r"(?i)\bmpi_abort\b|\b(rank|process)\s+\d+\b.*\b(died|killed|terminated|exited)\b|connection reset by peer",
+                "likely spot node loss — resubmit from last checkpoint",
+            ),
+            (
+                "out of memory",
+                r"(?i)out of memory|\boom[- ]?kill|cannot allocate memory|std::bad_alloc",
+                "resubmit on more nodes or on instances with more memory",
+            ),
+            (
+                "NaN residuals",
+                r"(?i)\b(nan|inf)\b.*\bresidual|\bresidual.*\b(nan|inf)\b|floating point exception",
+                "diverged — restart from an earlier checkpoint with a smaller time step",
+            ),
+            (
+                "EFS I/O error",
+                r"(?i)input/output error|stale file handle|no space left on device|nfs: server .* not responding",
+                "check the EFS mount and its throughput, then resume from the last checkpoint",
+            ),
+        ]
+        .into_iter()
+        .map(|(name, pattern, hint)| Self::new(name, pattern, hint).unwrap())
+        .collect()
+    }
+    /// Returns the failure signature name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Failure signature found in a log
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triage {
+    pub name: String,
+    pub hint: String,
+    pub line: String,
+}
+
+impl Display for Triage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.hint)
+    }
+}
+
+/// Returns the latest failure signature in `lines`, given the one found before
+/// and the parser of the time step lines
+///
+/// A time step after a signature clears it, the solver having recovered
+pub fn find_triage(
+    rules: &[TriageRule],
+    found: Option<Triage>,
+    lines: &[String],
+    steps: &dyn StepParser,
+) -> Option<Triage> {
+    lines.iter().fold(found, |found, line| {
+        if steps.parse_line(line).is_some() {
+            return None;
+        }
+        rules
+            .iter()
+            .find(|rule| rule.pattern.is_match(line))
+            .map(|rule| Triage {
+                name: rule.name.clone(),
+                hint: rule.hint.clone(),
+                line: line.clone(),
+            })
+            .or(found)
+    })
+}
@@ -0,0 +1,216 @@
+use std::{fmt::Display, path::Path, process::Command};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::shell;
+
+/// Minimum time in seconds between two samples of the resource utilization of a case
+pub const UTILIZATION_INTERVAL: i64 = 60;
+
+/// Utilization of the resources of the solver job in percent, CPU and GPU
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Utilization {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpu: Option<f64>,
+}
+
+impl Utilization {
+    /// Parses the output of a collector command, `cpu=<percent> gpu=<percent>` with either
+    /// being optional, or a single CPU percentage
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut utilization = Self::default();
+        for field in output.split_whitespace() {
+            let (key, value) = field.split_once('=').unwrap_or(("cpu", field));
+            let Ok(value) = value.trim_end_matches('%').parse() else {
+                continue;
+            };
+            match key {
+                "cpu" => utilization.cpu = Some(value),
+                "gpu" => utilization.gpu = Some(value),
+                _ => (),
+            }
+        }
+        (utilization.cpu.is_some() || utilization.gpu.is_some()).then_some(utilization)
+    }
+    /// Returns the lowest utilization, of the most starved resource
+    pub fn lowest(&self) -> Option<f64> {
+        match (self.cpu, self.gpu) {
+            (Some(cpu), Some(gpu)) => Some(cpu.min(gpu)),
+            (cpu, gpu) => cpu.or(gpu),
+        }
+    }
+}
+
+impl Display for Utilization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent =
+            |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.0}%"));
+        match self.gpu {
+            Some(_) => write!(f, "{}/{}", percent(self.cpu), percent(self.gpu)),
+            None => write!(f, "{}", percent(self.cpu)),
+        }
+    }
+}
+
+/// Collector of the resource utilization of the solver job of a case, sampled at most every
+/// [UTILIZATION_INTERVAL] seconds while the case is running
+///
+/// ```toml
+/// [[cases]]
+/// name = "zen30az045_OS2"
+/// job = "4242"
+/// resources = "slurm"
+/// # or resources = { command = "ssh node12 gpu-usage" } printing `cpu=<percent> gpu=<percent>`
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceCollector {
+    /// `sstat` usage of the running steps of the `job` of the case, the CPU utilization
+    /// being the CPU time between two samples over the cores of the case
+    Slurm,
+    /// Shell command printing the utilization, with the case name, directory and job
+    /// in `CFD_CASE`, `CFD_CASE_DIR` and `CFD_JOB`
+    Command(String),
+}
+
+/// Usage of a job from `sstat`: the CPU time of its steps in seconds and their mean GPU utilization
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JobUsage {
+    pub cpu_secs: f64,
+    pub gpu: Option<f64>,
+}
+
+impl JobUsage {
+    /// Parses the output of `sstat -n -P -a -j <job> --format=TRESUsageInTot`, a line per step
+    /// as `cpu=00:10:23,mem=1G,gres/gpuutil=85`
+    pub fn parse(output: &str) -> Option<Self> {
+        let mut usage = Self::default();
+        let (mut steps, mut gpus) = (0, vec![]);
+        for line in output.lines() {
+            for tres in line.trim().split(',') {
+                match tres.split_once('=') {
+                    Some(("cpu", time)) => {
+                        usage.cpu_secs += parse_cpu_time(time)?;
+                        steps += 1;
+                    }
+                    Some(("gres/gpuutil", percent)) => gpus.extend(percent.parse::<f64>().ok()),
+                    _ => (),
+                }
+            }
+        }
+        if !gpus.is_empty() {
+            usage.gpu = Some(gpus.iter().sum::<f64>() / gpus.len() as f64);
+        }
+        (steps > 0).then_some(usage)
+    }
+}
+
+/// Parses a CPU time of Slurm, `[DD-][HH:]MM:SS[.mmm]`, in seconds
+fn parse_cpu_time(time: &str) -> Option<f64> {
+    let (days, time) = match time.split_once('-') {
+        Some((days, time)) => (days.parse::<f64>().ok()?, time),
+        None => (0., time),
+    };
+    let mut secs = 0.;
+    for (i, field) in time.rsplit(':').enumerate() {
+        let scale = match i {
+            0 => 1.,
+            1 => 60.,
+            2 => 3600.,
+            _ => return None,
+        };
+        secs += field.parse::<f64>().ok()? * scale;
+    }
+    Some(days * 86400. + secs)
+}
+
+/// Resource collector of a case with its latest samples
+#[derive(Debug, Clone)]
+pub(crate) struct ResourceSampler {
+    collector: ResourceCollector,
+    /// Time of the latest sample
+    sampled: Option<DateTime<Local>>,
+    /// Latest job usage from `sstat`, the CPU utilization being computed between two samples
+    usage: Option<(DateTime<Local>, JobUsage)>,
+    utilization: Option<Utilization>,
+}
+
+impl ResourceSampler {
+    pub(crate) fn new(collector: ResourceCollector) -> Self {
+        Self {
+            collector,
+            sampled: None,
+            usage: None,
+            utilization: None,
+        }
+    }
+    pub(crate) fn collector(&self) -> &ResourceCollector {
+        &self.collector
+    }
+    /// Samples the utilization at `now` if the latest sample is older than [UTILIZATION_INTERVAL]
+    pub(crate) fn sample(
+        &mut self,
+        now: DateTime<Local>,
+        name: &str,
+        case_dir: &Path,
+        job: Option<&str>,
+        cores: Option<usize>,
+    ) {
+        if self
+            .sampled
+            .is_some_and(|at| (now - at).num_seconds() < UTILIZATION_INTERVAL)
+        {
+            return;
+        }
+        self.sampled = Some(now);
+        self.utilization = match &self.collector {
+            ResourceCollector::Command(command) => {
+                let output = shell(command)
+                    .env("CFD_CASE", name)
+                    .env("CFD_CASE_DIR", case_dir)
+                    .env("CFD_JOB", job.unwrap_or_default())
+                    .output()
+                    .inspect_err(|e| {
+                        tracing::debug!(case = name, error = %e, "failed to run the resource collector")
+                    })
+                    .ok()
+                    .filter(|output| output.status.success());
+                output
+                    .and_then(|output| Utilization::parse(&String::from_utf8_lossy(&output.stdout)))
+            }
+            ResourceCollector::Slurm => {
+                let usage = job.and_then(|job| {
+                    let output = Command::new("sstat")
+                        .args(["-n", "-P", "-a", "-j", job, "--format=TRESUsageInTot"])
+                        .output()
+                        .inspect_err(|e| tracing::debug!(job, error = %e, "failed to run sstat"))
+                        .ok()?;
+                    JobUsage::parse(&String::from_utf8_lossy(&output.stdout))
+                });
+                let previous = self.usage.take();
+                self.usage = usage.map(|usage| (now, usage));
+                usage
+                    .map(|usage| Utilization {
+                        cpu: previous.zip(cores).and_then(|((at, before), cores)| {
+                            let secs = (now - at).num_milliseconds() as f64 / 1e3;
+                            (secs > 0. && cores > 0 && usage.cpu_secs >= before.cpu_secs).then(
+                                || {
+                                    100. * (usage.cpu_secs - before.cpu_secs)
+                                        / (secs * cores as f64)
+                                },
+                            )
+                        }),
+                        gpu: usage.gpu,
+                    })
+                    .filter(|utilization| utilization.lowest().is_some())
+            }
+        };
+    }
+    /// Returns the latest utilization
+    pub(crate) fn utilization(&self) -> Option<Utilization> {
+        self.utilization
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::Case;
+
+/// Default pattern of the log line giving the solver version
+const VERSION_PATTERN: &str = r"(?i)\b(?:STAR-CCM\+|version)\s*:?\s*v?(\d+(?:\.\d+)+)";
+
+/// Pattern of the log line giving the solver version, the version being the first capture group
+///
+/// ```toml
+/// version_pattern = 'STAR-CCM\+ (\d+\.\d+)'
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct VersionPattern(Regex);
+
+impl TryFrom<String> for VersionPattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::new(&pattern)
+    }
+}
+
+impl Default for VersionPattern {
+    fn default() -> Self {
+        Self::new(VERSION_PATTERN).unwrap()
+    }
+}
+
+impl VersionPattern {
+    /// Creates a new solver version pattern
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(Self(Regex::new(pattern)?))
+    }
+    /// Returns the first solver version found in the log lines
+    pub fn find(&self, lines: &[String]) -> Option<String> {
+        lines.iter().find_map(|line| {
+            self.0
+                .captures(line)
+                .and_then(|captures| captures.get(1))
+                .map(|version| version.as_str().to_string())
+        })
+    }
+}
+
+/// Returns the cases of each solver version, for the groups whose cases were run with different versions
+pub fn version_mismatches<'a>(
+    cases: impl IntoIterator<Item = &'a Case>,
+) -> BTreeMap<Option<String>, BTreeMap<String, BTreeSet<String>>> {
+    let mut groups: BTreeMap<Option<String>, BTreeMap<String, BTreeSet<String>>> = BTreeMap::new();
+    for case in cases {
+        if let Some(version) = case.solver_version() {
+            groups
+                .entry(case.group_name().map(str::to_string))
+                .or_default()
+                .entry(version.to_string())
+                .or_default()
+                .insert(case.name().to_string());
+        }
+    }
+    groups.retain(|_, versions| versions.len() > 1);
+    groups
+}
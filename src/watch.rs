@@ -0,0 +1,119 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use notify::{Event, RecursiveMode, Watcher};
+
+use crate::{source::wildcard_match, GlobSource};
+
+/// Log files modification watcher
+///
+/// The directories of the log files are watched rather than the files themselves
+/// so that logs created or replaced after the watcher has started are caught too,
+/// a log name with wildcards standing for all the logs of its directory matching it
+pub struct LogWatcher {
+    watcher: notify::RecommendedWatcher,
+    rx: Receiver<notify::Result<Event>>,
+    logs: HashSet<PathBuf>,
+    debounce: Duration,
+}
+
+impl LogWatcher {
+    /// Creates a new watcher for the given log files
+    ///
+    /// Modification events are batched over the `debounce` period
+    pub fn new<I, P>(logs: I, debounce: Duration) -> notify::Result<Self>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        let logs: HashSet<PathBuf> = logs.into_iter().map(|log| log.as_ref().into()).collect();
+        let dirs: HashSet<&Path> = logs.iter().filter_map(|log| log.parent()).collect();
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        Ok(Self {
+            watcher,
+            rx,
+            logs,
+            debounce,
+        })
+    }
+    /// Adds a log file to the watched files
+    pub fn watch<P: AsRef<Path>>(&mut self, log: P) -> notify::Result<()> {
+        let log = log.as_ref().to_path_buf();
+        if let Some(dir) = log.parent() {
+            if !self
+                .logs
+                .iter()
+                .any(|watched| watched.parent() == Some(dir))
+            {
+                self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            }
+        }
+        self.logs.insert(log);
+        Ok(())
+    }
+    /// Returns the watched log a modified file is, either the log itself
+    /// or a glob of its directory matching the file name
+    fn watched(&self, path: &Path) -> Option<PathBuf> {
+        if self.logs.contains(path) {
+            return Some(path.to_path_buf());
+        }
+        let name = path.file_name()?.to_str()?;
+        self.logs
+            .iter()
+            .find(|log| {
+                log.parent() == path.parent()
+                    && log
+                        .file_name()
+                        .and_then(|pattern| pattern.to_str())
+                        .is_some_and(|pattern| {
+                            GlobSource::is_glob(pattern) && wildcard_match(pattern, name)
+                        })
+            })
+            .cloned()
+    }
+    /// Waits up to `timeout` for some log files to be modified
+    ///
+    /// Returns the modified log files, or `None` if the timeout has elapsed
+    /// or the watcher has failed, in which case all the logs should be polled
+    pub fn wait(&self, timeout: Duration) -> Option<HashSet<PathBuf>> {
+        let deadline = Instant::now() + timeout;
+        let mut modified = HashSet::new();
+        let mut settle: Option<Instant> = None;
+        loop {
+            let until = settle.map_or(deadline, |settle| settle.min(deadline));
+            match self
+                .rx
+                .recv_timeout(until.saturating_duration_since(Instant::now()))
+            {
+                Ok(Ok(event)) if event.kind.is_modify() || event.kind.is_create() => {
+                    modified.extend(
+                        event
+                            .paths
+                            .into_iter()
+                            .filter_map(|path| self.watched(&path)),
+                    );
+                    if !modified.is_empty() && settle.is_none() {
+                        settle = Some(Instant::now() + self.debounce);
+                    }
+                }
+                Ok(Ok(_)) => (),
+                Ok(Err(_)) | Err(RecvTimeoutError::Disconnected) => return None,
+                Err(RecvTimeoutError::Timeout) => {
+                    return if modified.is_empty() {
+                        None
+                    } else {
+                        Some(modified)
+                    }
+                }
+            }
+        }
+    }
+}
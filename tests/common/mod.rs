@@ -0,0 +1,77 @@
+//! Fixtures and helpers shared by the tests, each test file using some of them
+#![allow(dead_code)]
+
+use std::{
+    fmt::{self, Debug},
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use cfd_status::{Case, InitPhase, MilestonePattern, StringSource, VersionPattern};
+
+pub const INITIALIZING: &str = include_str!("../fixtures/initializing.out");
+pub const RUNNING: &str = include_str!("../fixtures/running.out");
+pub const COMPLETED: &str = include_str!("../fixtures/completed.out");
+
+/// Time a background condition is waited for before failing the test
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns a 10s (200 time steps) case reading `source` with the default log patterns
+pub fn case(source: StringSource) -> Case {
+    Case::new("zen30az045_OS2", 10, "solve.out")
+        .log_source(source)
+        .milestones(MilestonePattern::defaults())
+        .init_phases(InitPhase::defaults())
+        .version_pattern(VersionPattern::default())
+}
+
+/// Temporary directory, unique to a test and to the process, removed when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory for the test `name`
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("cfd-status-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Debug for TempDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Polls `done` until it holds, failing the test after 10s
+pub fn wait_until(what: &str, mut done: impl FnMut() -> bool) {
+    let start = Instant::now();
+    while !done() {
+        assert!(start.elapsed() < TIMEOUT, "timed out waiting for {what}");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
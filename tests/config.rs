@@ -0,0 +1,651 @@
+//! Tests of the configuration, of the campaigns, of the chained runs and of the preflight
+
+mod common;
+
+use cfd_status::{
+    Case, Column, Config, ConfigError, Dirs, DurationError, DurationSpec, FinishOrder,
+    PrecursorConfig, Precursors, Preflight, PreflightProblem, SimSeconds, StageConfig, StageState,
+    StatusSnapshot, Steps, StringSource, Tabs, CONFIG_TEMPLATE, DEFAULT_COLUMNS,
+};
+use chrono::{Duration, Local};
+
+use common::{case, TempDir, COMPLETED, INITIALIZING, RUNNING};
+
+#[test]
+fn precursor_seeding() {
+    let precursor = |validate: &str| {
+        case(StringSource::new(COMPLETED)).precursor(PrecursorConfig {
+            seeds: "zen30az045_OS2_prod".to_string(),
+            validate: Some(validate.to_string()),
+            start: Some(r#"test "$CFD_PRODUCTION" = zen30az045_OS2_prod"#.to_string()),
+        })
+    };
+    let mut precursors = Precursors::new();
+    precursors.seed(&case(StringSource::new(COMPLETED)));
+    precursors.seed(&precursor(r#"test "$CFD_CASE" = zen30az045_OS2"#));
+    precursors.seed(&precursor("echo diverged >&2; false"));
+    let seedings = precursors.finished(true);
+    assert_eq!(seedings.len(), 2);
+    assert_eq!(seedings[0].production, "zen30az045_OS2_prod");
+    assert_eq!(seedings[0].error, None);
+    let error = seedings[1].error.as_deref().unwrap();
+    assert!(error.starts_with("validation exited") && error.ends_with("diverged"));
+}
+
+#[test]
+fn campaign_expansion() {
+    let config = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+
+        [[campaigns]]
+        group = "baseline"
+        zenith = [0, 30, 60]
+        azimuth = [0, 45, 90, 135, 180]
+        wind = ["OS2", "OS7", "CD12", "CD17"]
+        log = "solve-{category}.out"
+        duration = 400
+        cores = 672
+
+        [campaigns.durations]
+        CD = 340
+        OS7 = 500
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.cases.len(), 60);
+    let case = |name: &str| config.cases.iter().find(|case| case.name == name).unwrap();
+    assert_eq!(case("zen30az045_OS2").duration, SimSeconds(1200.));
+    assert_eq!(case("zen30az045_OS2").group, None);
+    assert_eq!(case("zen00az000_OS2").duration, SimSeconds(400.));
+    assert_eq!(case("zen00az000_OS2").group.as_deref(), Some("baseline"));
+    assert_eq!(case("zen60az180_OS7").duration, SimSeconds(500.));
+    assert_eq!(case("zen60az135_CD12").duration, SimSeconds(340.));
+    assert_eq!(case("zen60az135_CD12").log, "solve-CD.out");
+    assert_eq!(case("zen60az135_CD12").cores, Some(672));
+}
+
+#[test]
+fn post_processing_stages() {
+    let root = TempDir::new("stages");
+    let dir = root.join("zen30az045_OS2");
+    std::fs::create_dir_all(dir.join("optical_path")).unwrap();
+    let stages = vec![
+        StageConfig {
+            name: "extracting".to_string(),
+            log: Some("optical_path/extract.log".to_string()),
+            marker: Some("optical_path/DONE".to_string()),
+            duration: Some(7200),
+        },
+        StageConfig {
+            name: "archived".to_string(),
+            log: None,
+            marker: Some("ARCHIVED".to_string()),
+            duration: None,
+        },
+    ];
+    let mut case = case(StringSource::new(COMPLETED))
+        .root_dir(&root)
+        .stages(stages);
+    assert_eq!(case.pipeline_state(), "solving");
+    case.update().unwrap();
+    assert!(case.check_stages().len() == 2);
+    assert_eq!(case.pipeline_state(), "solved");
+    assert!(!case.is_pipeline_done());
+    std::fs::write(dir.join("optical_path/extract.log"), "").unwrap();
+    let changed = case.check_stages();
+    assert_eq!(changed.len(), 1);
+    assert!(matches!(changed[0].state, StageState::Running(_)));
+    assert_eq!(case.pipeline_state(), "extracting");
+    assert!(case.check_stages().is_empty());
+    std::fs::write(dir.join("optical_path/DONE"), "").unwrap();
+    std::fs::write(dir.join("ARCHIVED"), "").unwrap();
+    assert_eq!(case.check_stages().len(), 2);
+    assert_eq!(case.pipeline_state(), "archived");
+    assert!(case.is_pipeline_done());
+    let status =
+        StatusSnapshot::new(&[], std::slice::from_ref(&case), 180).to_json(&Default::default());
+    let status: serde_json::Value = serde_json::from_str(&status).unwrap();
+    let case = &status["cases"][0];
+    assert_eq!(case["pipeline"], "archived");
+    assert_eq!(case["stages"][0]["name"], "extracting");
+    assert_eq!(case["stages"][0]["state"], "done");
+}
+
+#[test]
+fn config_reload() {
+    let config = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+
+        [[cases]]
+        name = "zen30az090_OS2"
+        duration = 1200
+        log = "solve-672_16.out"
+        "#,
+    )
+    .unwrap();
+    let reloaded = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1800
+        log = "solve-672_14.out"
+
+        [[cases]]
+        name = "zen30az135_OS2"
+        duration = 1200
+        log = "solve-672_18.out"
+        "#,
+    )
+    .unwrap();
+    let diff = config.diff(&reloaded);
+    assert_eq!(diff.added, ["zen30az135_OS2"]);
+    assert_eq!(diff.removed, ["zen30az090_OS2"]);
+    assert_eq!(diff.changed, ["zen30az045_OS2"]);
+    assert_eq!(
+        diff.to_string(),
+        "added zen30az135_OS2; removed zen30az090_OS2; changed zen30az045_OS2"
+    );
+    assert!(config.diff(&config).is_empty());
+    // any setting of a case changes it, a remote log changing its definition
+    let remote = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+        stall_after = 600
+
+        [[cases]]
+        name = "zen30az090_OS2"
+        duration = 1200
+        log = "solve-672_16.out"
+        log_host = "login01"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.diff(&remote).changed,
+        ["zen30az045_OS2", "zen30az090_OS2"]
+    );
+    assert!(config
+        .case(&config.cases[0])
+        .same_definition(&remote.case(&remote.cases[0])));
+    assert!(!config
+        .case(&config.cases[1])
+        .same_definition(&remote.case(&remote.cases[1])));
+    let duplicate = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 900
+        log = "solve-672_15.out"
+        "#,
+    );
+    assert!(matches!(duplicate, Err(ConfigError::Duplicate(name)) if name == "zen30az045_OS2"));
+    let no_duration = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 0
+        log = "solve-672_14.out"
+        "#,
+    );
+    assert!(matches!(no_duration, Err(ConfigError::NoDuration(_))));
+    let two_sources = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+        log_command = "cat solve-672_14.out"
+        log_host = "login01"
+        "#,
+    );
+    assert!(matches!(
+        two_sources,
+        Err(ConfigError::ConflictingSources(_))
+    ));
+}
+
+#[test]
+fn typed_durations() {
+    assert_eq!(SimSeconds(1.5).to_steps(), Steps(30));
+    assert_eq!(Steps(30).to_sim_seconds(), SimSeconds(1.5));
+    assert_eq!(Steps(100).to_sim_seconds_at(50.), SimSeconds(2.));
+    assert_eq!(DurationSpec::PhysicalTime(2.).steps(), Steps(40));
+    assert_eq!("20min".parse::<SimSeconds>().unwrap(), SimSeconds(1200.));
+    assert!(matches!(
+        "24000 steps".parse::<SimSeconds>(),
+        Err(DurationError::Steps(_))
+    ));
+    let config = |case: &str| Config::from_toml(&format!("[[cases]]\n{case}"));
+    let case = config(
+        r#"
+        name = "zen30az045_OS2"
+        duration = "2h"
+        log = "solve-672_14.out"
+        steps = 72000
+        "#,
+    )
+    .unwrap();
+    assert_eq!(case.cases[0].duration, SimSeconds(7200.));
+    assert_eq!(case.cases[0].total_steps(), Steps(72000));
+    assert!(config(
+        r#"
+        name = "zen30az045_OS2"
+        duration = "24000 steps"
+        log = "solve-672_14.out"
+        "#,
+    )
+    .is_err());
+    let typed_steps = config(
+        r#"
+        name = "zen30az045_OS2"
+        duration = 24000
+        log = "solve-672_14.out"
+        steps = 24000
+        "#,
+    );
+    // some solvers run at other rates, the inconsistency is only a warning
+    let warnings = typed_steps.unwrap().warnings();
+    let [error] = warnings.as_slice() else {
+        panic!("steps typed as duration not warned about: {warnings:?}");
+    };
+    assert!(matches!(
+        error,
+        ConfigError::InconsistentSteps {
+            steps: Steps(24000),
+            ..
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "case zen30az045_OS2: 24000 steps over 24000s is inconsistent with the 20 Hz time stepping, \
+        is the duration a time step count instead of simulated seconds?"
+    );
+    let no_steps = config(
+        r#"
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+        steps = 0
+        "#,
+    );
+    assert!(matches!(no_steps, Err(ConfigError::NoSteps(_))));
+    let adaptive = config(
+        r#"
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+        steps = 1200
+        time_stepping = "adaptive"
+        "#,
+    );
+    assert!(adaptive.unwrap().warnings().is_empty());
+    // a duration typed as a time step count is far above the ones of the other cases
+    let config = Config::from_toml(
+        r#"
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+        [[cases]]
+        name = "zen30az090_OS2"
+        duration = 24000
+        log = "solve-672_16.out"
+        [[cases]]
+        name = "zen30az135_OS2"
+        duration = "20min"
+        log = "solve-672_18.out"
+        [[campaigns]]
+        zenith = [0]
+        azimuth = [0]
+        wind = ["OS2", "CD12"]
+        log = "solve.out"
+        duration = "20min"
+        [campaigns.durations]
+        CD = "1h"
+        "#,
+    )
+    .unwrap();
+    let warnings: Vec<_> = config.warnings().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        [
+            "case zen30az090_OS2: 24000s is over 10 times the median 1200s of the cases, \
+        is the duration a time step count instead of simulated seconds?"
+        ]
+    );
+    let case = |name: &str| config.cases.iter().find(|case| case.name == name).unwrap();
+    assert_eq!(case("zen00az000_CD12").duration, SimSeconds(3600.));
+    assert_eq!(case("zen00az000_CD12").total_steps(), Steps(72000));
+}
+
+#[test]
+fn display_profiles() {
+    let config = Config::from_toml(
+        r#"
+        profile = "pi"
+
+        [profiles.lead]
+        columns = ["name", "percent", "steps_per_hour"]
+        colors = false
+        "#,
+    )
+    .unwrap();
+    let names: Vec<_> = config
+        .display_profiles()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, ["operator", "summary", "pi", "lead"]);
+    let mut running = case(StringSource::new(RUNNING));
+    running.update().unwrap();
+    let snapshot =
+        StatusSnapshot::new(std::slice::from_ref(&running), &[], 60).with_theme(Some(config.theme));
+    let mut tabs = Tabs::new(&config);
+    assert_eq!(tabs.profile().map(|(name, _)| name), Some("pi"));
+    assert_eq!(
+        tabs.active().columns.columns,
+        [Column::Name, Column::Percent, Column::Eta]
+    );
+    assert_eq!(tabs.refresh(), Some(std::time::Duration::from_secs(300)));
+    // the rows are grouped even without campaigns
+    let board = tabs.render(&snapshot);
+    assert!(board.starts_with("profile: pi (p: next)\n"), "{board}");
+    assert!(board.contains("[ungrouped]"), "{board}");
+    // the configured profiles follow the built-in ones, then the configured columns
+    assert!(tabs.handle_key('p', Some(&snapshot)));
+    assert_eq!(tabs.profile().map(|(name, _)| name), Some("lead"));
+    let (_, lead) = tabs.profile().unwrap();
+    assert!(lead.style(snapshot.clone()).theme().is_none());
+    assert!(tabs.handle_key('p', Some(&snapshot)));
+    assert!(tabs.profile().is_none());
+    assert_eq!(tabs.active().columns.columns, DEFAULT_COLUMNS);
+    assert!(!tabs.render(&snapshot).contains("[ungrouped]"));
+    assert!(!tabs.set_profile(Some("board")));
+    assert!(matches!(
+        Config::from_toml(r#"profile = "board""#),
+        Err(ConfigError::UnknownProfile(name)) if name == "board"
+    ));
+}
+
+#[test]
+fn chained_runs() {
+    let running = |name: &str, duration: usize| {
+        let source = StringSource::new(RUNNING);
+        let mut case = Case::new(name, duration, "solve.out").log_source(source.clone());
+        case.update().unwrap();
+        source.append("TimeStep   121: Time 6.050000e+00\n");
+        case.update_over(300.).unwrap();
+        case
+    };
+    let initializing =
+        Case::new("zen30az135_OS2", 10, "solve.out").log_source(StringSource::new(INITIALIZING));
+    let cases = [
+        running("zen30az090_OS2", 20),
+        initializing,
+        running("zen30az045_OS2", 10),
+    ];
+    // listed before its predecessor, the second planned case still starts after it
+    let planned = vec![
+        Case::new("zen30az225_OS2", 10, "solve.out").after(["zen30az180_OS2"]),
+        Case::new("zen30az180_OS2", 10, "solve.out").after(["zen30az090_OS2"]),
+        Case::new("zen30az270_OS2", 10, "solve.out").after(["zen30az135_OS2"]),
+    ];
+    let snapshot = StatusSnapshot::new(&cases, &[], 60).with_plan(planned, None);
+    let plan = snapshot.plan();
+    let run = |name: &str| plan.runs.iter().find(|run| run.case == name).unwrap();
+    let taken = snapshot.taken();
+    // a slot frees up at the finish of zen30az045_OS2 but zen30az090_OS2 is waited for
+    assert_eq!(
+        run("zen30az180_OS2").start,
+        Some(taken + Duration::seconds(279 * 300))
+    );
+    assert_eq!(
+        run("zen30az180_OS2").after.as_deref(),
+        Some("zen30az090_OS2")
+    );
+    assert_eq!(run("zen30az225_OS2").start, run("zen30az180_OS2").finish);
+    assert_eq!(
+        run("zen30az225_OS2").after.as_deref(),
+        Some("zen30az180_OS2")
+    );
+    // the initializing predecessor has no projected finish
+    assert_eq!(run("zen30az270_OS2").start, None);
+    assert_eq!(
+        run("zen30az270_OS2").after.as_deref(),
+        Some("zen30az135_OS2")
+    );
+    assert_eq!(plan.finish, None);
+    assert!(
+        plan.to_string()
+            .lines()
+            .any(|line| line.starts_with("zen30az225_OS2")
+                && line.ends_with("  after zen30az180_OS2"))
+    );
+    // without declared predecessors the planned case takes the first free slot
+    let planned = Case::new("zen30az180_OS2", 10, "solve.out");
+    let plan = StatusSnapshot::new(&cases, &[], 60)
+        .with_plan(vec![planned], None)
+        .plan();
+    assert_eq!(plan.runs[3].after, None);
+    // a queued case is projected to start once the case it runs after is projected to finish
+    let queued = |name: &str, after: &str| {
+        Case::new(name, 10, "solve.out")
+            .queued(Some(Local::now()), None)
+            .after([after])
+    };
+    let cases = [
+        running("zen30az090_OS2", 20),
+        queued("zen30az315_OS2", "zen30az090_OS2"),
+        queued("zen30az000_OS2", "zen30az315_OS2"),
+        queued("zen30az135_OS2", "zen30az045_OS2"),
+    ];
+    let snapshot = StatusSnapshot::new(&cases, &[], 60);
+    let taken = snapshot.taken();
+    let (start, finish) = snapshot.projection(&cases[1]).unwrap();
+    assert_eq!(start, taken + Duration::seconds(279 * 300));
+    assert_eq!(finish, start + Duration::seconds(200 * 300));
+    assert_eq!(snapshot.projection(&cases[2]).unwrap().0, finish);
+    // a predecessor not monitored does not hold the case
+    assert!(snapshot.projection(&cases[3]).unwrap().0 < start);
+    let order = FinishOrder::new(&snapshot.plan());
+    let names: Vec<_> = order
+        .finishes
+        .iter()
+        .map(|finish| finish.case.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "zen30az135_OS2",
+            "zen30az090_OS2",
+            "zen30az315_OS2",
+            "zen30az000_OS2"
+        ]
+    );
+    let plan = snapshot.plan();
+    assert_eq!(plan.runs[2].after.as_deref(), Some("zen30az315_OS2"));
+}
+
+#[test]
+fn case_dependencies() {
+    let config = |after: &str| {
+        Config::from_toml(&format!(
+            r#"
+            [[cases]]
+            name = "zen30az045_OS2"
+            duration = 10
+            log = "solve.out"
+            after = [{after}]
+
+            [[cases]]
+            name = "zen30az090_OS2"
+            duration = 10
+            log = "solve.out"
+            after = ["zen30az045_OS2"]
+
+            [[planned]]
+            name = "zen30az135_OS2"
+            duration = 10
+            log = "solve.out"
+            after = ["zen30az090_OS2"]
+            "#
+        ))
+    };
+    assert!(config("").is_ok());
+    assert!(matches!(
+        config(r#""zen30az180_OS2""#),
+        Err(ConfigError::UnknownPredecessor { case, after })
+            if case == "zen30az045_OS2" && after == "zen30az180_OS2"
+    ));
+    assert!(matches!(
+        config(r#""zen30az045_OS2""#),
+        Err(ConfigError::DependencyCycle(case)) if case == "zen30az045_OS2"
+    ));
+    assert!(matches!(
+        config(r#""zen30az135_OS2""#),
+        Err(ConfigError::DependencyCycle(_))
+    ));
+}
+
+#[test]
+fn user_directories() {
+    let home = TempDir::new("dirs");
+    let dirs = Dirs::new(home.join(".config"), home.join(".local/state"));
+    assert_eq!(
+        dirs.config_file(),
+        home.join(".config/cfd-status/config.toml")
+    );
+    let path = dirs.init(false).unwrap();
+    assert!(dirs.state.is_dir());
+    assert_eq!(
+        dirs.init(false).unwrap_err().kind(),
+        std::io::ErrorKind::AlreadyExists
+    );
+    std::fs::write(&path, "").unwrap();
+    dirs.init(true).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), CONFIG_TEMPLATE);
+
+    let mut config = Config::from_path(&path).unwrap();
+    assert!(config.cases.is_empty());
+    config.database = Some("runs.db".into());
+    config.notes = "/shared/notes.toml".into();
+    config.relocate_state(&dirs.state);
+    assert_eq!(
+        std::path::Path::new(&config.records),
+        dirs.state.join("records.toml")
+    );
+    assert_eq!(config.notes, "/shared/notes.toml");
+    assert_eq!(config.database, Some(dirs.state.join("runs.db")));
+}
+
+#[test]
+fn preflight() {
+    let root = TempDir::new("preflight");
+    for (case, log) in [
+        ("running", Some(RUNNING)),
+        ("initializing", Some(INITIALIZING)),
+        ("no_log", None),
+        (
+            "fast",
+            Some("TimeStep     1: Time 1.000000e-02\nTimeStep     2: Time 2.000000e-02\n"),
+        ),
+        (
+            "fast_steps",
+            Some("TimeStep     1: Time 1.000000e-02\nTimeStep     2: Time 2.000000e-02\n"),
+        ),
+    ] {
+        std::fs::create_dir_all(root.join(case)).unwrap();
+        if let Some(log) = log {
+            std::fs::write(root.join(case).join("solve.out"), log).unwrap();
+        }
+    }
+    let config = Config::from_toml_unchecked(&format!(
+        r#"
+            root = {root:?}
+            profile = "wide"
+            [[cases]]
+            name = "running"
+            duration = 10
+            log = "solve.out"
+            [[cases]]
+            name = "running"
+            duration = 10
+            log = "solve.out"
+            [[cases]]
+            name = "initializing"
+            duration = 10
+            log = "solve.out"
+            [[cases]]
+            name = "no_log"
+            duration = 10
+            log = "solve.out"
+            [[cases]]
+            name = "missing"
+            duration = 10
+            log = "solve.out"
+            [[cases]]
+            name = "fast"
+            duration = 10
+            log = "solve.out"
+            [[cases]]
+            name = "fast_steps"
+            duration = 10
+            log = "solve.out"
+            steps = 1000
+            [[cases]]
+            name = "no_duration"
+            duration = 0
+            log = "solve.out"
+        "#
+    ))
+    .unwrap();
+    assert!(Config::from_toml(&format!(
+        "root = {root:?}\n[[cases]]\nname = \"a\"\nduration = 0\nlog = \"a.out\""
+    ))
+    .is_err());
+    let preflight = Preflight::new(&config);
+    let problems: Vec<_> = preflight.problems.iter().map(ToString::to_string).collect();
+    assert_eq!(preflight.cases, 8);
+    assert_eq!(problems.len(), 7, "{problems:#?}");
+    assert_eq!(
+        problems[0],
+        "invalid: case running is defined more than once"
+    );
+    assert!(
+        problems[1].starts_with("no time step: no line of"),
+        "{}",
+        problems[1]
+    );
+    assert!(problems[2].starts_with("unreadable log: no_log cannot read"));
+    assert!(problems[3].starts_with("missing directory: missing has no"));
+    assert!(matches!(
+        preflight.problems[4],
+        PreflightProblem::RateMismatch { dt, .. } if (dt - 0.01).abs() < 1e-9
+    ));
+    assert_eq!(
+        problems[5],
+        "invalid: case no_duration has no simulation duration"
+    );
+    assert_eq!(problems[6], "invalid: unknown display profile wide");
+    std::fs::remove_dir_all(&root).unwrap();
+    assert!(matches!(
+        Preflight::new(&config).problems[0],
+        PreflightProblem::MissingRoot { .. }
+    ));
+}
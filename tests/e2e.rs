@@ -0,0 +1,232 @@
+//! Runs the daemon against fake cases with the scripted solvers of the [harness]
+#![cfg(feature = "monitor")]
+
+mod common;
+mod harness;
+
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use harness::{case, Action, Daemon, FakeRoot};
+
+const STEP: Duration = Duration::from_millis(100);
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[test]
+fn steady_cases_complete() {
+    let root = FakeRoot::new("steady");
+    let config = root.config(
+        r#"
+[[cases]]
+name = "zen30az045_OS2"
+duration = 1
+log = "solve-672_14.out"
+steps = 20
+
+[[cases]]
+name = "zen30az090_OS2"
+duration = 1
+log = "solve-672_16.out"
+steps = 20
+"#,
+    );
+    let fast = root.solver(
+        "zen30az045_OS2",
+        "solve-672_14.out",
+        vec![Action::Steps {
+            count: 20,
+            every: STEP,
+        }],
+    );
+    let slow = root.solver(
+        "zen30az090_OS2",
+        "solve-672_16.out",
+        vec![Action::Steps {
+            count: 20,
+            every: STEP * 2,
+        }],
+    );
+    let mut daemon = Daemon::start(&root, &config);
+
+    let running = daemon.wait_for(TIMEOUT, |snapshot| {
+        case(snapshot, "zen30az090_OS2")["state"] == "running"
+    });
+    assert_eq!(case(&running, "zen30az090_OS2")["total_step"], 20);
+    let served: serde_json::Value = serde_json::from_str(&daemon.get("/status")).unwrap();
+    assert_eq!(served["cases"].as_array().unwrap().len(), 2);
+    let seq = served["seq"].as_u64().unwrap();
+    let kept: serde_json::Value =
+        serde_json::from_str(&daemon.get(&format!("/snapshots/{seq}"))).unwrap();
+    assert_eq!(kept["taken"], served["taken"]);
+
+    fast.join();
+    slow.join();
+    let snapshots = daemon.finish(TIMEOUT);
+    let events = daemon.events();
+    let last = snapshots.last().unwrap();
+    // the snapshots are numbered without gaps
+    for (previous, snapshot) in snapshots.iter().zip(&snapshots[1..]) {
+        assert_eq!(snapshot["previous_seq"], previous["seq"]);
+    }
+    for name in ["zen30az045_OS2", "zen30az090_OS2"] {
+        assert_eq!(case(last, name)["state"], "completed");
+        assert_eq!(case(last, name)["step"], 20);
+        assert!(events.contains(&("completed".to_string(), name.to_string())));
+        assert!(fs::read_to_string(root.file("records.toml"))
+            .unwrap()
+            .contains(name));
+    }
+    assert!(!events.iter().any(|(event, _)| event == "error"));
+}
+
+#[test]
+fn stalled_case_is_reported() {
+    let root = FakeRoot::new("stalled");
+    let hook = root.file("stalled.txt");
+    let config = root.config(&format!(
+        r#"
+stall_after = 2
+
+[hooks]
+stalled = "echo $CFD_CASE >> {}"
+log = {:?}
+
+[[cases]]
+name = "zen30az045_OS7"
+duration = 1
+log = "solve-672_15.out"
+steps = 20
+"#,
+        hook.display(),
+        root.file("hooks.log"),
+    ));
+    let solver = root.solver(
+        "zen30az045_OS7",
+        "solve-672_15.out",
+        vec![
+            Action::Steps {
+                count: 5,
+                every: STEP,
+            },
+            Action::Stall(Duration::from_secs(5)),
+            Action::Steps {
+                count: 15,
+                every: STEP,
+            },
+        ],
+    );
+    let mut daemon = Daemon::start(&root, &config);
+
+    solver.join();
+    daemon.finish(TIMEOUT);
+    let events = daemon.events();
+    let stalled = events
+        .iter()
+        .position(|event| *event == ("stalled".to_string(), "zen30az045_OS7".to_string()))
+        .expect("no stalled event");
+    let completed = events
+        .iter()
+        .position(|event| *event == ("completed".to_string(), "zen30az045_OS7".to_string()))
+        .expect("no completed event");
+    assert!(stalled < completed);
+    assert_eq!(fs::read_to_string(hook).unwrap().trim(), "zen30az045_OS7");
+}
+
+#[test]
+fn restarted_case_completes() {
+    let root = FakeRoot::new("restarted");
+    let config = root.config(
+        r#"
+[[cases]]
+name = "zen30az135_OS7"
+duration = 1
+log = "solve-672_18.out"
+steps = 20
+"#,
+    );
+    let solver = root.solver(
+        "zen30az135_OS7",
+        "solve-672_18.out",
+        vec![
+            Action::Steps {
+                count: 15,
+                every: STEP,
+            },
+            Action::Stall(Duration::from_millis(1500)),
+            Action::Restart { from: 2 },
+            Action::Steps {
+                count: 18,
+                every: STEP,
+            },
+        ],
+    );
+    let mut daemon = Daemon::start(&root, &config);
+
+    solver.join();
+    let snapshots = daemon.finish(TIMEOUT);
+    let events = daemon.events();
+    let steps: Vec<_> = snapshots
+        .iter()
+        .filter_map(|snapshot| case(snapshot, "zen30az135_OS7")["step"].as_u64())
+        .collect();
+    assert!(
+        steps.windows(2).any(|pair| pair[1] < pair[0]),
+        "the restart was not seen: {steps:?}"
+    );
+    assert_eq!(steps.last(), Some(&20));
+    assert!(!events.iter().any(|(event, _)| event == "error"));
+}
+
+#[test]
+fn piped_log_is_followed() {
+    let mut pipe = Command::new(env!("CARGO_BIN_EXE_cfd-status"))
+        .args([
+            "pipe",
+            "--duration",
+            "1",
+            "--interval",
+            "1",
+            "--name",
+            "zen30az045_OS2",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = pipe.stdin.take().unwrap();
+    for step in 1..=20 {
+        writeln!(stdin, "TimeStep {step:>5}: Time {:.6e}", step as f64 * 0.05).unwrap();
+        thread::sleep(STEP);
+    }
+    drop(stdin);
+    let output = pipe.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // one status line per update, stdout not being a terminal
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines.len() > 1, "{stdout}");
+    assert!(lines.iter().all(|line| line.starts_with("zen30az045_OS2")));
+    assert!(lines.last().unwrap().contains(" 100 "), "{stdout}");
+}
+
+#[test]
+fn container_preflight_checks_the_environment() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cfd-status"))
+        .args(["preflight", "--container"])
+        .env("CFD_STATUS_CASES", "zen30az045_OS2:0:solve.out")
+        .env("CFD_STATUS_ROOT", std::env::temp_dir())
+        .env_remove("CFD_STATUS_CONFIG")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("invalid: case zen30az045_OS2 has no simulation duration"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,352 @@
+//! Tests of the time step durations, the ETAs and the remaining simulated time
+
+mod common;
+
+use cfd_status::{
+    run_until, AlertKind, Alerts, BlackoutError, BlackoutWindow, Case, Column, Config,
+    DowntimeHistory, DurationSpec, ElapsedPerStep, FinishOrder, Severity, StatusSnapshot,
+    StringSource,
+};
+#[cfg(feature = "monitor")]
+use cfd_status::{CaseDetail, StepTimeDistribution};
+use chrono::{Duration, Local, TimeZone};
+
+use common::{case, TempDir, COMPLETED, INITIALIZING, RUNNING};
+
+#[test]
+fn conservative_eta() {
+    let dir = TempDir::new("downtime");
+    let path = dir.join("records.toml");
+    std::fs::write(
+        &path,
+        r#"
+[[completed]]
+name = "zen30az045_OS2"
+group = "OS"
+steps = 100
+runtime_secs = 300
+step_time = 2.0
+
+[[completed]]
+name = "zen30az045_CD12"
+group = "CD"
+steps = 100
+runtime_secs = 100
+step_time = 2.0
+
+[[completed]]
+name = "zen30az045_OS7"
+steps = 100
+runtime_secs = 400
+"#,
+    )
+    .unwrap();
+    let history = DowntimeHistory::read(&path);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.factor(Some("OS")), Some(1.5));
+    assert_eq!(history.factor(Some("CD")), Some(1.));
+    assert_eq!(history.factor(Some("CFD")), Some(1.25));
+
+    let split = RUNNING.find("TimeStep    61:").unwrap();
+    let source = StringSource::new(&RUNNING[..split]);
+    let mut case = case(source.clone()).eta_window(1);
+    case.update_over(0.).unwrap();
+    source.append(&RUNNING[split..]);
+    case.update_over(120.).unwrap();
+    assert_eq!(case.conservative_eta_secs(), case.eta_secs());
+    let case = case.downtime_factor(history.factor(Some("OS")).unwrap());
+    assert_eq!(case.conservative_eta_secs(), 240);
+}
+
+#[test]
+fn windowed_step_time() {
+    let mut lifetime = ElapsedPerStep::new().window(0);
+    let mut windowed = ElapsedPerStep::new().window(3);
+    for secs in [20., 20., 2., 2., 2.] {
+        lifetime.update(secs);
+        windowed.update(secs);
+    }
+    assert!((windowed.mean() - 9.2).abs() < 1e-9);
+    assert_eq!(windowed.windowed(), Some(2.));
+    assert_eq!(&windowed * 10., 20.);
+    assert_eq!(lifetime.windowed(), None);
+    assert!((&lifetime * 10. - 92.).abs() < 1e-9);
+}
+
+#[test]
+fn adaptive_time_step_trend() {
+    let source = StringSource::default();
+    let mut case = case(source.clone()).duration_spec(DurationSpec::PhysicalTime(2.));
+    // the time step grows from 0.01s by 1e-4s per time step
+    for step in [10, 20, 30, 40] {
+        let time = 0.01 * step as f64 + 5e-5 * (step * step) as f64;
+        source.append(&format!("TimeStep {step}: Time {time:e}\n"));
+        case.update_over(20.).unwrap();
+    }
+    let trend = case.dt_trend().unwrap();
+    assert!((trend.dt - 0.014).abs() < 1e-9);
+    assert!((trend.slope - 1e-4).abs() < 1e-9);
+    assert_eq!(case.total_step(), 124);
+    assert_eq!(case.eta_secs(), 168);
+}
+
+#[test]
+fn slow_intervals() {
+    let source = StringSource::default();
+    let mut case = case(source.clone());
+    // 2s time steps, slowed down to 20s then 10s between the time steps 70 and 90
+    for (step, secs) in (10..=110)
+        .step_by(10)
+        .zip([20., 20., 20., 20., 20., 20., 20., 200., 100., 20., 20.])
+    {
+        source.append(&format!(
+            "TimeStep {step}: Time {:.6e}\n",
+            step as f64 * 0.05
+        ));
+        case.update_over(secs).unwrap();
+    }
+    assert_eq!(case.slow_interval_count(), 1);
+    let interval = case.slow_intervals().next().unwrap();
+    assert_eq!((interval.from_step, interval.to_step), (70, 90));
+    assert!((interval.expected - 2.).abs() < 1e-9);
+}
+
+#[test]
+fn time_step_change() {
+    let source = StringSource::default();
+    let mut case = case(source.clone());
+    // the time step is halved from the time step 61 on
+    let mut time = 0.;
+    for step in (10..=120).step_by(10) {
+        time += if step > 60 { 0.25 } else { 0.5 };
+        source.append(&format!("TimeStep {step}: Time {time:.6e}\n"));
+        case.update_over(if step > 60 { 40. } else { 20. }).unwrap();
+    }
+    assert_eq!(case.dt_change_count(), 1);
+    let change = case.dt_changes().next().unwrap();
+    assert_eq!(change.step, 61);
+    assert!((change.from - 0.05).abs() < 1e-9 && (change.to - 0.025).abs() < 1e-9);
+    assert_eq!(case.elapsed_per_step().since_step(), Some(61));
+    assert!((case.elapsed_per_step().mean() - 4.).abs() < 1e-9);
+    assert_eq!(case.slow_interval_count(), 0);
+}
+
+#[test]
+fn step_time_budget() {
+    let source = StringSource::default();
+    let mut case = case(source.clone())
+        .expected_step_time(1.5)
+        .step_time_tolerance(10.);
+    for step in (10..=60).step_by(10) {
+        source.append(&format!(
+            "TimeStep {step}: Time {:.6e}\n",
+            step as f64 * 0.05
+        ));
+        case.update_over(20.).unwrap();
+        // the drift is only known after a few time step duration samples
+        assert_eq!(case.step_time_drift().is_some(), step > 50);
+    }
+    assert_eq!(case.step_time_drift().map(f64::round), Some(33.));
+    assert!(case.is_off_step_budget());
+    let mut alerts = Alerts::new();
+    alerts.update(std::slice::from_ref(&case));
+    let alert = &alerts.sorted()[0];
+    assert_eq!(alert.kind, AlertKind::StepTimeBudget);
+    assert_eq!(alert.severity(), Severity::Warning);
+}
+
+#[test]
+fn eta_api() {
+    let mut case = self::case(StringSource::new(INITIALIZING));
+    case.update().unwrap();
+    assert_eq!(
+        (case.eta(), case.remaining(), case.progress()),
+        (None, None, None)
+    );
+    let mut case = self::case(StringSource::new(RUNNING));
+    case.update().unwrap();
+    let remaining = case.remaining().unwrap();
+    assert_eq!(remaining.num_seconds(), case.eta_secs());
+    let eta = case.eta().unwrap();
+    assert!((eta - Local::now() - remaining).num_seconds().abs() <= 1);
+    let progress = case.progress().unwrap();
+    assert_eq!((100. * progress) as i64, case.percent_complete());
+    let mut case = self::case(StringSource::new(COMPLETED));
+    case.update().unwrap();
+    assert_eq!(case.remaining(), Some(Duration::zero()));
+    assert_eq!(case.eta(), case.completion());
+    assert_eq!(case.progress(), Some(1.));
+}
+
+#[test]
+fn remaining_sim_time() {
+    let mut case = case(StringSource::new(RUNNING));
+    assert_eq!(case.remaining_sim_secs(), None);
+    case.update().unwrap();
+    // 80 time steps of 0.05s left
+    assert!((case.remaining_sim_secs().unwrap() - 4.).abs() < 1e-9);
+    let now = Local::now();
+    assert_eq!(
+        Column::SimRemaining.cell(&case, Default::default(), now),
+        "4.00"
+    );
+    assert!(Column::Remaining
+        .cell(&case, Default::default(), now)
+        .starts_with("4.0s/"));
+    assert_eq!("remaining".parse::<Column>().unwrap(), Column::Remaining);
+    let mut case = case
+        .duration_spec(DurationSpec::PhysicalTime(8.))
+        .log_source(StringSource::new(RUNNING));
+    case.update().unwrap();
+    assert!((case.remaining_sim_secs().unwrap() - 2.).abs() < 1e-9);
+}
+
+#[test]
+fn blackout_windows() {
+    let at = |day: u32, hour: u32, minute: u32| {
+        Local
+            .with_ymd_and_hms(2024, 6, day, hour, minute, 0)
+            .unwrap()
+    };
+    let nightly = BlackoutWindow::new("nightly backup", "30 1 * * *", 3600).unwrap();
+    assert_eq!(
+        nightly.window_at(at(10, 22, 0)),
+        Some((at(11, 1, 30), at(11, 2, 30)))
+    );
+    // the job is paused during the window on its way
+    assert_eq!(
+        run_until(std::slice::from_ref(&nightly), at(10, 22, 0), 5 * 3600),
+        at(11, 4, 0)
+    );
+    // within the window, the job resumes at its end
+    assert_eq!(
+        run_until(std::slice::from_ref(&nightly), at(11, 1, 45), 3600),
+        at(11, 3, 30)
+    );
+    // 2 nights in a row
+    assert_eq!(
+        run_until(std::slice::from_ref(&nightly), at(10, 22, 0), 29 * 3600),
+        at(12, 5, 0)
+    );
+    // Saturday 2024-06-15 and Sunday written as 0 or 7
+    let weekend = BlackoutWindow::new("weekend", "0 2 * * 6,7", 7200).unwrap();
+    assert_eq!(
+        weekend.window_at(at(10, 0, 0)),
+        Some((at(15, 2, 0), at(15, 4, 0)))
+    );
+    assert_eq!(
+        weekend.window_at(at(15, 5, 0)).map(|(start, _)| start),
+        Some(at(16, 2, 0))
+    );
+    for cron in ["30 1 * *", "61 1 * * *", "*/0 * * * *", "5-1 * * * *"] {
+        assert!(
+            BlackoutWindow::new("invalid", cron, 60).is_err(),
+            "{cron} accepted"
+        );
+    }
+    assert!(matches!(
+        BlackoutWindow::new("empty", "0 1 * * *", 0),
+        Err(BlackoutError::Duration)
+    ));
+    // a job paused half of every minute takes twice as long
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone());
+    case.update().unwrap();
+    source.append("TimeStep   121: Time 6.050000e+00\n");
+    case.update_over(10.).unwrap();
+    let eta = case.eta_secs() as f64;
+    assert!(eta > 600.);
+    let case = case.blackouts(vec![BlackoutWindow::new("half", "* * * * *", 30).unwrap()]);
+    let ratio = case.eta_secs() as f64 / eta;
+    assert!((1.8..2.2).contains(&ratio), "ETA ratio {ratio}");
+    let config = Config::from_toml(
+        r#"
+        [[blackouts]]
+        name = "nightly backup"
+        cron = "30 1 * * *"
+        duration = 3600
+        utc = true
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.blackouts[0].to_string(),
+        "nightly backup (30 1 * * *, 1:00 UTC)"
+    );
+}
+
+#[test]
+fn finish_order() {
+    let running = |name: &str, duration: usize| {
+        let source = StringSource::new(RUNNING);
+        let mut case = Case::new(name, duration, "solve.out").log_source(source.clone());
+        case.update().unwrap();
+        source.append("TimeStep   121: Time 6.050000e+00\n");
+        case.update_over(300.).unwrap();
+        case
+    };
+    let initializing =
+        Case::new("zen30az135_OS2", 10, "solve.out").log_source(StringSource::new(INITIALIZING));
+    let cases = [
+        running("zen30az090_OS2", 20),
+        initializing,
+        running("zen30az045_OS2", 10),
+    ];
+    let planned = Case::new("zen30az180_OS2", 10, "solve.out");
+    let snapshot = StatusSnapshot::new(&cases, &[], 60).with_plan(vec![planned], None);
+    let order = FinishOrder::new(&snapshot.plan());
+    let names: Vec<_> = order
+        .finishes
+        .iter()
+        .map(|finish| finish.case.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["zen30az045_OS2", "zen30az090_OS2", "zen30az180_OS2"]
+    );
+    assert_eq!(order.unknown, ["zen30az135_OS2"]);
+    // 79 then 279 time steps of 5 minutes left, the planned case starting after the first
+    let gaps: Vec<_> = order
+        .finishes
+        .iter()
+        .map(|finish| finish.gap_secs)
+        .collect();
+    assert_eq!(gaps, [79 * 300, 200 * 300, 0]);
+    let table = order.to_string();
+    let lines: Vec<_> = table.lines().collect();
+    assert!(lines[1].starts_with("   1  zen30az045_OS2      running "));
+    assert!(lines[1].ends_with("      6:35      6:35"));
+    assert!(lines[3].ends_with("     23:15      0:00"));
+    assert_eq!(lines[4], "No projected finish: zen30az135_OS2");
+}
+
+#[cfg(feature = "monitor")]
+#[test]
+fn step_time_percentiles() {
+    let mut distribution = StepTimeDistribution::new();
+    assert_eq!(distribution.percentiles(), None);
+    distribution.add(2., 890);
+    distribution.add(3., 100);
+    // checkpoint writes
+    distribution.add(40., 10);
+    distribution.add(f64::NAN, 10);
+    assert_eq!(distribution.steps(), 1000);
+    assert_eq!(distribution.max(), 40.);
+    let percentiles = distribution.percentiles().unwrap();
+    let close = |secs: f64, expected: f64| (secs / expected - 1.).abs() < 0.06;
+    assert!(close(percentiles.p50, 2.), "{percentiles}");
+    assert!(close(percentiles.p90, 3.), "{percentiles}");
+    assert!(close(percentiles.p99, 3.), "{percentiles}");
+    assert!(close(distribution.percentile(99.5).unwrap(), 40.));
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone());
+    case.update().unwrap();
+    assert_eq!(case.step_time_percentiles(), None);
+    source.append("TimeStep   130: Time 6.500000e+00\n");
+    case.update_over(30.).unwrap();
+    let percentiles = case.step_time_percentiles().unwrap();
+    assert!(close(percentiles.p99, 3.), "{percentiles}");
+    assert_eq!(case.step_time_distribution().steps(), 10);
+    let detail = CaseDetail::new(&case, []).to_string();
+    assert!(detail.contains(&format!("  {percentiles}, max ")));
+}
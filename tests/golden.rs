@@ -0,0 +1,266 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+use cfd_status::{
+    parse_log_with, ArchivedCase, CampaignSummary, Case, Config, HealthConfig, InitPhase,
+    MilestonePattern, Redactor, StallIncident, StatusSnapshot, StepParser, StringSource, Theme,
+    VersionPattern,
+};
+use chrono::{Local, TimeZone};
+use regex::Regex;
+
+const INITIALIZING: &str = include_str!("fixtures/initializing.out");
+const RUNNING: &str = include_str!("fixtures/running.out");
+const COMPLETED: &str = include_str!("fixtures/completed.out");
+/// Time in seconds between 2 updates of the cases
+const UPDATE_TIME: usize = 180;
+
+/// Returns a 10s (200 time steps) case reading `source` with the default log patterns
+fn case(name: &str, source: StringSource) -> Case {
+    Case::new(name, 10, "solve.out")
+        .log_source(source)
+        .milestones(MilestonePattern::defaults())
+        .init_phases(InitPhase::defaults())
+        .version_pattern(VersionPattern::default())
+}
+
+/// Returns a case for each fixture, the running one updated twice for a time step duration
+fn snapshot() -> StatusSnapshot {
+    let (start, rest) = RUNNING.split_at(RUNNING.find("TimeStep    61:").unwrap());
+    let source = StringSource::new(start);
+    let mut running = case("zen30az045_OS2", source.clone());
+    running.update_over(UPDATE_TIME as f64).unwrap();
+    source.append(rest);
+    running.update_over(UPDATE_TIME as f64).unwrap();
+    let mut initializing = case("zen30az090_OS2", StringSource::new(INITIALIZING));
+    initializing.update().unwrap();
+    let mut completed = case("zen30az045_OS7", StringSource::new(COMPLETED));
+    completed.update().unwrap();
+    StatusSnapshot::new(&[running, initializing], &[completed], UPDATE_TIME)
+        .with_health(&HealthConfig::default())
+}
+
+/// Returns the summary of a campaign of 3 completed cases on 2 core counts, one having stalled
+fn summary() -> CampaignSummary {
+    let at = |hour: u32| Local.with_ymd_and_hms(2024, 3, 4, hour, 0, 0).unwrap();
+    let archived =
+        |name: &str, cores: usize, hours: (u32, u32), steps: usize, stalls: usize| ArchivedCase {
+            name: name.to_string(),
+            campaign: Some("OS".to_string()),
+            cores: Some(cores),
+            total_step: steps,
+            first_seen: at(hours.0),
+            completed: at(hours.1),
+            steps,
+            stalls,
+        };
+    let cases = [
+        archived("zen30az090_OS2", 336, (10, 18), 4000, 1),
+        archived("zen30az045_OS2", 672, (8, 12), 8000, 0),
+        archived("zen30az135_OS2", 672, (12, 14), 2000, 0),
+    ];
+    let stalls = [StallIncident {
+        case: "zen30az090_OS2".to_string(),
+        at: at(15),
+    }];
+    CampaignSummary::new(Some("OS"), cases, stalls)
+}
+
+/// Returns the time steps parsed from a solver log with the status of its case,
+/// updated with the first half of the time steps then with the rest of the log
+fn solver_status(log: &Path, config: &Config) -> String {
+    let case_config = &config.cases[0];
+    let parser = case_config.time_step.clone().unwrap_or_default();
+    let mut rendering = String::new();
+    writeln!(rendering, "Time steps").unwrap();
+    let records = parse_log_with(log, &parser).unwrap();
+    for record in &records {
+        let wall_clock = or_dash(record.wall_clock.map(|at| at.format("%H:%M:%S")));
+        writeln!(
+            rendering,
+            "  {:>6} {:>12.4e}  {wall_clock}",
+            record.step, record.time
+        )
+        .unwrap();
+    }
+    // the log is split before the first time step line past the middle of the time steps
+    let text = fs::read_to_string(log).unwrap();
+    let mut offset = 0;
+    let middle = text
+        .split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line)
+        })
+        .filter(|(_, line)| {
+            parser
+                .parse_line(line.trim_end_matches(['\n', '\r']))
+                .is_some()
+        })
+        .nth(records.len() / 2)
+        .map_or(text.len(), |(start, _)| start);
+    let (start, rest) = text.split_at(middle);
+    let source = StringSource::new(start);
+    let mut case = config.case(case_config).log_source(source.clone());
+    case.update_over(UPDATE_TIME as f64).unwrap();
+    write_status(&mut rendering, "first half of the log", &case);
+    source.append(rest);
+    case.update_over(UPDATE_TIME as f64).unwrap();
+    write_status(&mut rendering, "whole log", &case);
+    rendering
+}
+
+/// Writes the progress and the ETA of a case
+fn write_status(rendering: &mut String, update: &str, case: &Case) {
+    // no time step duration is known before the second time step
+    let timed = case.elapsed_per_step().sample() > 0;
+    writeln!(rendering, "\nStatus after the {update}").unwrap();
+    writeln!(
+        rendering,
+        "  {}: time step {} of {}, {}%, simulated time {:.4e}s",
+        case.state(),
+        or_dash(case.step()),
+        case.total_step(),
+        case.percent_complete(),
+        case.time()
+    )
+    .unwrap();
+    writeln!(
+        rendering,
+        "  time step size {}, {} changes, {} per time step, {} time steps per hour",
+        or_dash(case.time_step_size().map(|dt| format!("{dt:.4}s"))),
+        case.dt_change_count(),
+        or_dash(timed.then(|| format!("{:.2}s", case.elapsed_per_step().mean()))),
+        or_dash(
+            case.steps_per_hour()
+                .filter(|_| timed)
+                .map(|rate| format!("{rate:.1}"))
+        )
+    )
+    .unwrap();
+    writeln!(
+        rendering,
+        "  {} time steps and {:.4}s simulated left, ETA in {}, conservatively {}",
+        case.remaining_steps(),
+        case.remaining_sim_secs().unwrap_or_default(),
+        or_dash(timed.then(|| format!("{}s", case.eta_secs()))),
+        or_dash(timed.then(|| format!("{}s", case.conservative_eta_secs())))
+    )
+    .unwrap();
+    writeln!(
+        rendering,
+        "  solver {}, last checkpoint {}",
+        or_dash(case.solver_version()),
+        or_dash(case.last_checkpoint())
+    )
+    .unwrap();
+}
+
+/// Returns the value, `-` if none
+fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+/// Replaces the wall-clock dependent parts of a rendering
+fn scrub(rendering: &str) -> String {
+    let timestamp =
+        Regex::new(r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(:\d{2}(\.\d+)?)?([+-]\d{2}:\d{2}|Z)?")
+            .unwrap();
+    let short = Regex::new(r"\d{2}-\d{2} \d{2}:\d{2}").unwrap();
+    let ics = Regex::new(r"\d{8}T\d{6}Z").unwrap();
+    let next_update = Regex::new(r"next update in \d+s").unwrap();
+    let rendering = timestamp.replace_all(rendering, "<timestamp>");
+    let rendering = short.replace_all(&rendering, "<timestamp>");
+    let rendering = ics.replace_all(&rendering, "<timestamp>");
+    next_update
+        .replace_all(&rendering, "next update in <secs>s")
+        .to_string()
+}
+
+/// Compares a rendering to the expected output in `tests/golden`,
+/// or overwrites the expected output if `UPDATE_GOLDEN` is set
+fn assert_golden(name: &str, rendering: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+    let rendering = scrub(rendering);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &rendering).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("reading {}: {e}, run with UPDATE_GOLDEN=1", path.display()));
+    assert!(
+        rendering == expected,
+        "{name} differs from the expected output, run with UPDATE_GOLDEN=1 \
+         and review the diff if the change is deliberate\n--- expected\n{expected}\n--- actual\n{rendering}"
+    );
+}
+
+#[test]
+fn table() {
+    assert_golden("table.txt", &snapshot().to_string());
+}
+
+#[test]
+fn themed_table() {
+    let snapshot = snapshot().with_theme(Some(Theme::default()));
+    assert_golden("table-themed.txt", &snapshot.to_string());
+}
+
+#[test]
+fn compact() {
+    assert_golden("compact.txt", &snapshot().compact());
+}
+
+#[test]
+fn json() {
+    let value: serde_json::Value =
+        serde_json::from_str(&snapshot().to_json(&Redactor::new(vec![]))).unwrap();
+    assert_golden(
+        "snapshot.json",
+        &serde_json::to_string_pretty(&value).unwrap(),
+    );
+}
+
+#[test]
+fn summary_markdown() {
+    assert_golden("summary.md", &summary().to_markdown());
+}
+
+#[test]
+fn summary_html() {
+    assert_golden("summary.html", &summary().to_html());
+}
+
+#[test]
+fn completion_calendar() {
+    assert_golden("completions.ics", &snapshot().to_ics());
+}
+
+/// Each anonymized solver log excerpt `tests/fixtures/solvers/<format>.out` is parsed
+/// with the case of `<format>.toml` against `tests/golden/solvers/<format>.txt`
+///
+/// A new solver version is added with an excerpt of its log and its case, the expected output
+/// being written with `UPDATE_GOLDEN=1` and reviewed
+#[test]
+fn solver_logs() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/solvers");
+    let mut logs: Vec<_> = fs::read_dir(&fixtures)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "out"))
+        .collect();
+    logs.sort();
+    assert!(!logs.is_empty());
+    for log in logs {
+        let format = log.file_stem().unwrap().to_string_lossy().to_string();
+        let config = fs::read_to_string(log.with_extension("toml"))
+            .unwrap_or_else(|e| panic!("reading the case of {format}: {e}"));
+        let config = Config::from_toml(&config).unwrap();
+        assert_golden(
+            &format!("solvers/{format}.txt"),
+            &solver_status(&log, &config),
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! End-to-end harness: fake cases in a temporary root, written by scripted solvers
+//! and monitored by the `cfd-status` binary
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::common::TempDir;
+
+/// Simulated time of a time step, matching the 20Hz sampling rate
+const TIME_STEP: f64 = 0.05;
+
+/// Temporary directory standing for `/shared`, removed when dropped
+pub struct FakeRoot {
+    dir: TempDir,
+}
+
+impl FakeRoot {
+    /// Creates an empty root, unique to the test `name`
+    pub fn new(name: &str) -> Self {
+        Self {
+            dir: TempDir::new(&format!("e2e-{name}")),
+        }
+    }
+    /// Returns the path to a file of the root, outside of the case directories
+    pub fn file(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+    /// Writes the configuration file of the daemon, `cases` being TOML appended to the
+    /// settings pointing the daemon to the root with a 1s update interval
+    pub fn config(&self, cases: &str) -> PathBuf {
+        let path = self.file("cfd-status.toml");
+        let config = format!(
+            "root = {:?}\ninterval = 1\nrecords = {:?}\nevents = {:?}\n{cases}",
+            &*self.dir,
+            self.file("records.toml"),
+            self.file("events.jsonl"),
+        );
+        fs::write(&path, config).unwrap();
+        path
+    }
+    /// Starts a solver writing the log of `case` as scripted
+    pub fn solver(&self, case: &str, log: &str, script: Vec<Action>) -> FakeSolver {
+        let dir = self.dir.join(case);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(log);
+        FakeSolver(thread::spawn(move || {
+            let mut step = 0;
+            let mut log = start(&path);
+            for action in script {
+                match action {
+                    Action::Steps { count, every } => {
+                        for _ in 0..count {
+                            thread::sleep(every);
+                            step += 1;
+                            writeln!(
+                                log,
+                                "TimeStep {step:>5}: Time {:.6e}",
+                                step as f64 * TIME_STEP
+                            )
+                            .unwrap();
+                            writeln!(log, "          {step}  1.0000e-03  2.0000e-04").unwrap();
+                        }
+                    }
+                    Action::Stall(duration) => thread::sleep(duration),
+                    Action::Restart { from } => {
+                        // the solver is restarted from a checkpoint with a new log
+                        step = from;
+                        log = start(&path);
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Creates the log at `path`, with the solver banner and initialization
+fn start(path: &Path) -> File {
+    let mut log = File::create(path).unwrap();
+    writeln!(log, "Starting STAR-CCM+ parallel server").unwrap();
+    writeln!(log, "STAR-CCM+ 17.06.007 (linux-x86_64-2.28/gnu11.2)").unwrap();
+    writeln!(log, "Initialization complete").unwrap();
+    log
+}
+
+/// Step of the script of a fake solver
+pub enum Action {
+    /// Writes `count` time steps, one every `every`
+    Steps { count: usize, every: Duration },
+    /// Writes nothing for a while
+    Stall(Duration),
+    /// Restarts the solver from the time step `from` with a new log
+    Restart { from: usize },
+}
+
+/// Solver thread writing a fake log
+pub struct FakeSolver(JoinHandle<()>);
+
+impl FakeSolver {
+    /// Waits for the end of the script
+    pub fn join(self) {
+        self.0.join().unwrap();
+    }
+}
+
+/// The `cfd-status` binary monitoring a fake root
+pub struct Daemon {
+    child: Child,
+    port: u16,
+    snapshots: Arc<Mutex<Vec<Value>>>,
+    reader: Option<JoinHandle<()>>,
+    session: PathBuf,
+}
+
+impl Daemon {
+    /// Starts the daemon with the configuration of the root, printing the snapshots
+    /// as JSON lines, serving the status over HTTP and recording the session
+    pub fn start(root: &FakeRoot, config: &Path) -> Self {
+        let port = free_port();
+        let session = root.file("session.jsonl");
+        let mut child = Command::new(env!("CARGO_BIN_EXE_cfd-status"))
+            .arg("--config")
+            .arg(config)
+            .arg("--json")
+            .args(["--http", &format!("127.0.0.1:{port}")])
+            .arg("--record-session")
+            .arg(&session)
+            .args(["--log-file", root.file("activity.log").to_str().unwrap()])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start cfd-status");
+        let stdout = child.stdout.take().unwrap();
+        let snapshots = Arc::new(Mutex::new(vec![]));
+        let reader = {
+            let snapshots = snapshots.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let snapshot = serde_json::from_str(&line.unwrap()).expect("invalid JSON line");
+                    snapshots.lock().unwrap().push(snapshot);
+                }
+            })
+        };
+        Self {
+            child,
+            port,
+            snapshots,
+            reader: Some(reader),
+            session,
+        }
+    }
+    /// Returns the body of a GET request to the HTTP server, retried until the server is up
+    pub fn get(&self, path: &str) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            match TcpStream::connect(("127.0.0.1", self.port)) {
+                Ok(mut stream) => {
+                    write!(
+                        stream,
+                        "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response).unwrap();
+                    let (_, body) = response.split_once("\r\n\r\n").unwrap_or_default();
+                    return body.to_string();
+                }
+                Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(100)),
+                Err(e) => panic!("HTTP server not reachable: {e}"),
+            }
+        }
+    }
+    /// Waits until the latest snapshot satisfies `predicate` and returns it
+    pub fn wait_for<F: Fn(&Value) -> bool>(&self, timeout: Duration, predicate: F) -> Value {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(snapshot) = self
+                .snapshots
+                .lock()
+                .unwrap()
+                .last()
+                .filter(|s| predicate(s))
+            {
+                return snapshot.clone();
+            }
+            assert!(
+                Instant::now() < deadline,
+                "no matching snapshot before the timeout"
+            );
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+    /// Waits for the daemon to exit once all the cases are complete,
+    /// and returns the snapshots it printed
+    pub fn finish(&mut self, timeout: Duration) -> Vec<Value> {
+        let deadline = Instant::now() + timeout;
+        while self.child.try_wait().unwrap().is_none() {
+            if Instant::now() > deadline {
+                let _ = self.child.kill();
+                panic!("daemon still running after {timeout:?}");
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert!(self.child.wait().unwrap().success(), "daemon failed");
+        self.reader.take().unwrap().join().unwrap();
+        self.snapshots.lock().unwrap().clone()
+    }
+    /// Returns the `(event, case)` pairs recorded in the session, without the case updates
+    pub fn events(&self) -> Vec<(String, String)> {
+        let session = OpenOptions::new().read(true).open(&self.session).unwrap();
+        BufReader::new(session)
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap())
+            .filter(|entry| entry["kind"] == "event" && entry["event"] != "updated")
+            .map(|entry| {
+                (
+                    entry["event"].as_str().unwrap().to_string(),
+                    entry["case"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+/// Returns a local port nothing listens on
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Returns the status of the case `name` in a snapshot
+pub fn case<'a>(snapshot: &'a Value, name: &str) -> &'a Value {
+    snapshot["cases"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|case| case["name"] == name)
+        .unwrap_or_else(|| panic!("no case {name} in the snapshot"))
+}
@@ -0,0 +1,228 @@
+//! Tests of the HTTP server of the status and of the pushes to an aggregator
+#![cfg(feature = "monitor")]
+
+mod common;
+
+use cfd_status::{
+    is_authorized, AggregationConfig, BulkOperation, Case, CaseFilter, Config, ControlClient,
+    ControlError, HttpConfig, Monitor, MonitorEvent, Publisher, StatusServer, StatusSnapshot,
+    StringSource,
+};
+use serde_json::json;
+
+use common::{case, wait_until, RUNNING};
+
+#[test]
+fn authenticated_push() {
+    let mut running = case(StringSource::new(RUNNING));
+    running.update().unwrap();
+    let snapshot = StatusSnapshot::new(&[running], &[], 180);
+    let access = HttpConfig {
+        token: Some("s3cr3t".to_string()),
+        ..Default::default()
+    };
+    let server = StatusServer::start(
+        "127.0.0.1:0",
+        Default::default(),
+        Default::default(),
+        &access,
+    )
+    .unwrap();
+    let push = format!("http://{}/push", server.addr().unwrap());
+    // the monitors need the token of the central server
+    let status: serde_json::Value =
+        serde_json::from_str(&snapshot.to_json(&Default::default())).unwrap();
+    let body = json!({"cluster": "hpc-west", "status": status});
+    let rejected = ureq::post(&push)
+        .set("Authorization", "Bearer hpc-west")
+        .send_string(&body.to_string());
+    assert!(
+        matches!(rejected, Err(ureq::Error::Status(401, _))),
+        "{rejected:?}"
+    );
+    Publisher::from(AggregationConfig {
+        cluster: "hpc-east".to_string(),
+        push: Some(push),
+        token: access.token,
+        dir: None,
+    })
+    .publish(&snapshot, &Default::default());
+    // the push is sent from a separate thread
+    wait_until("the push", || !server.aggregate().is_empty());
+    assert_eq!(
+        server.aggregate().clusters().collect::<Vec<_>>(),
+        ["hpc-east"]
+    );
+}
+
+#[test]
+fn http_access() {
+    assert!(is_authorized(Some("Bearer s3cr3t"), "s3cr3t"));
+    assert!(!is_authorized(Some("Bearer s3cr3"), "s3cr3t"));
+    assert!(!is_authorized(Some("Basic s3cr3t"), "s3cr3t"));
+    assert!(!is_authorized(None, "s3cr3t"));
+    let config = Config::from_toml(
+        r#"
+            [http]
+            token = "s3cr3t"
+            tls = { certificate = "cert.pem", private_key = "key.pem" }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.http.token().as_deref(), Some("s3cr3t"));
+    assert_eq!(
+        config.http.tls.as_ref().unwrap().certificate,
+        std::path::Path::new("cert.pem")
+    );
+    let access = HttpConfig {
+        token_env: Some("CFD_STATUS_TEST_HTTP_TOKEN".to_string()),
+        ..config.http.clone()
+    };
+    std::env::set_var("CFD_STATUS_TEST_HTTP_TOKEN", "from-env");
+    assert_eq!(access.token().as_deref(), Some("from-env"));
+
+    let access = HttpConfig {
+        tls: None,
+        ..config.http
+    };
+    let server = StatusServer::start(
+        "127.0.0.1:0",
+        Default::default(),
+        Default::default(),
+        &access,
+    )
+    .unwrap();
+    let addr = server.addr().unwrap().to_string();
+    let addr = addr.as_str();
+    let filter = CaseFilter::default();
+    let rejected = ControlClient::new(addr).apply(BulkOperation::Pause, &filter);
+    assert!(
+        matches!(rejected, Err(ControlError::Rejected(401, _))),
+        "{rejected:?}"
+    );
+    // authenticated, the request is served, without a snapshot yet
+    let served = ControlClient::new(format!("http://{addr}/"))
+        .token(Some("s3cr3t".to_string()))
+        .apply(BulkOperation::Pause, &filter);
+    assert!(
+        matches!(served, Err(ControlError::Rejected(status, _)) if status != 401),
+        "{served:?}"
+    );
+    // a body without a length is read up to the limit only
+    let oversized = std::io::Read::take(std::io::repeat(b' '), cfd_status::MAX_BODY + 1);
+    let pushed = ureq::post(&format!("http://{addr}/push"))
+        .set("Authorization", "Bearer s3cr3t")
+        .send(oversized);
+    assert!(
+        matches!(pushed, Err(ureq::Error::Status(413, _))),
+        "{pushed:?}"
+    );
+    // the cases running commands cannot be added over HTTP
+    let add = |case: &str| {
+        ureq::post(&format!("http://{addr}/cases"))
+            .set("Authorization", "Bearer s3cr3t")
+            .send_string(case)
+            .map_or_else(
+                |e| match e {
+                    ureq::Error::Status(status, _) => status,
+                    e => panic!("{e}"),
+                },
+                |response| response.status(),
+            )
+    };
+    let added = add(r#"{"name": "OS2", "duration": 10, "log": "solve.out"}"#);
+    assert_eq!(added, 202);
+    for field in [
+        r#""log_command": "cat solve.out""#,
+        r#""log_host": "-oProxyCommand=id""#,
+        r#""resources": {"command": "nvidia-smi"}"#,
+        r#""precursor": {"seeds": "OS7", "start": "sbatch OS7.sh"}"#,
+    ] {
+        let added = add(&format!(
+            r#"{{"name": "OS2", "duration": 10, "log": "solve.out", {field}}}"#
+        ));
+        assert_eq!(added, 403, "{field}");
+    }
+    // with a control token, the token only reads
+    let access = HttpConfig {
+        control_token: Some("c0ntr0l".to_string()),
+        ..access
+    };
+    let server = StatusServer::start(
+        "127.0.0.1:0",
+        Default::default(),
+        Default::default(),
+        &access,
+    )
+    .unwrap();
+    let addr = server.addr().unwrap();
+    let status = |method: &str, path: &str, token: &str| {
+        ureq::request(method, &format!("http://{addr}{path}"))
+            .set("Authorization", &format!("Bearer {token}"))
+            .call()
+            .map_or_else(
+                |e| match e {
+                    ureq::Error::Status(status, _) => status,
+                    e => panic!("{e}"),
+                },
+                |response| response.status(),
+            )
+    };
+    assert_eq!(status("POST", "/refresh", "s3cr3t"), 401);
+    assert_eq!(status("POST", "/refresh", "c0ntr0l"), 202);
+    assert_eq!(status("GET", "/aggregate", "s3cr3t"), 200);
+    assert_eq!(status("GET", "/aggregate", "c0ntr0l"), 200);
+    assert_eq!(status("GET", "/aggregate", "none"), 401);
+    // and without a token, the server only listens on the loopback interface
+    let unauthenticated = HttpConfig::default();
+    assert!(StatusServer::start(
+        "0.0.0.0:0",
+        Default::default(),
+        Default::default(),
+        &unauthenticated
+    )
+    .is_err());
+}
+
+#[test]
+fn metrics_labels() {
+    let name = "node017 \"OS2\"\\\nrerun";
+    let source = StringSource::new(RUNNING);
+    let case = Case::new(name, 10, "solve.out").log_source(source.clone());
+    let mut monitor = Monitor::new(vec![case], std::time::Duration::from_secs(30));
+    monitor.poll();
+    source.append("TimeStep   130: Time 6.500000e+00\n");
+    let Some(MonitorEvent::Snapshot(snapshot)) = monitor.poll().pop() else {
+        panic!("no snapshot after the poll");
+    };
+    let redactor =
+        cfd_status::Redactor::new(vec![
+            cfd_status::RedactionRule::new(r"node\d+", "<host>").unwrap()
+        ]);
+    let server = StatusServer::start(
+        "127.0.0.1:0",
+        redactor,
+        Default::default(),
+        &HttpConfig::default(),
+    )
+    .unwrap();
+    let addr = server.addr().unwrap();
+    server.update(&snapshot);
+    let metrics = ureq::get(&format!("http://{addr}/metrics"))
+        .call()
+        .unwrap()
+        .into_string()
+        .unwrap();
+    assert!(!metrics.contains("node017"), "{metrics}");
+    let case = r#"case="<host> \"OS2\"\\\nrerun""#;
+    for series in [
+        "cfd_status_case_step_time_seconds_count",
+        "cfd_status_case_update_seconds",
+        "cfd_status_case_bytes_read",
+    ] {
+        assert!(
+            metrics.contains(&format!("{series}{{{case}}} ")),
+            "{series} in {metrics}"
+        );
+    }
+}
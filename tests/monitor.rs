@@ -0,0 +1,430 @@
+//! Tests of the monitor, of its clock, heartbeat and event log
+#![cfg(feature = "monitor")]
+
+mod common;
+
+use std::{io::Write, sync::Arc};
+
+use cfd_status::{
+    Case, CaseDetail, CaseError, Change, Column, ColumnSpec, Heartbeat, HeartbeatConfig, MockClock,
+    Monitor, MonitorEvent, SessionEntry, StatusSnapshot, StepParser, StringSource, TransitionKind,
+    TransitionLog,
+};
+use chrono::{Duration, Local, TimeZone};
+use serde_json::json;
+
+use common::{case, TempDir, RUNNING};
+
+/// Time step parser panicking on every line
+#[derive(Debug)]
+struct PanickingParser;
+
+impl StepParser for PanickingParser {
+    fn parse_line(&self, _line: &str) -> Option<(usize, f64)> {
+        panic!("corrupted line")
+    }
+}
+
+#[test]
+fn monitor_heartbeat() {
+    let case = case(StringSource::new(RUNNING)).parser(Arc::new(PanickingParser));
+    let mut monitor = Monitor::new(vec![case], std::time::Duration::from_millis(10));
+    let events = monitor.poll();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        MonitorEvent::Error { error: CaseError::Panic(message), .. } if message == "corrupted line"
+    )));
+    let Some(MonitorEvent::Snapshot(snapshot)) = events.last() else {
+        panic!("no snapshot after the poll");
+    };
+    assert_eq!(monitor.restarts(), 0);
+    let dir = TempDir::new("heartbeat");
+    let file = dir.join("heartbeat.json");
+    let mut heartbeat = Heartbeat::from(HeartbeatConfig {
+        file: Some(file.clone()),
+        url: None,
+    });
+    heartbeat.beat(snapshot, monitor.restarts());
+    heartbeat.beat(snapshot, monitor.restarts());
+    let status: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(status["beats"], 2);
+    assert_eq!(status["running"], 1);
+    assert_eq!(status["restarts"], 0);
+}
+
+#[test]
+fn refresh_deltas() {
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone());
+    case.update().unwrap();
+    let previous = StatusSnapshot::new(std::slice::from_ref(&case), &[], 180);
+    source.append(
+        &(121..=140)
+            .map(|step| format!("TimeStep   {step}: Time {:.6e}\n", step as f64 * 0.05))
+            .collect::<String>(),
+    );
+    case.update_over(10.).unwrap();
+    let snapshot =
+        StatusSnapshot::new(std::slice::from_ref(&case), &[], 180).with_deltas(&previous);
+    let delta = snapshot.deltas().unwrap().get("zen30az045_OS2").unwrap();
+    assert_eq!(delta.steps, Some(20));
+    assert_eq!(delta.percent, Some(10));
+    assert_eq!(
+        delta.annotation(Column::Step),
+        Some(("+20".to_string(), Change::Improved))
+    );
+    assert_eq!(delta.annotation(Column::Name), None);
+    let table = snapshot
+        .with_columns(ColumnSpec::new(
+            vec![Column::Percent, Column::Step],
+            Default::default(),
+        ))
+        .to_string();
+    assert!(table.contains("+10"), "{table}");
+    assert!(table.contains("+20"), "{table}");
+}
+
+#[test]
+fn mock_clock() {
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    let source = StringSource::new(RUNNING);
+    let case = case(source.clone()).stall_after(600);
+    let mut monitor = Monitor::new(vec![case], std::time::Duration::from_secs(300))
+        .clock(Arc::new(clock.clone()));
+    let mut snapshot = || loop {
+        if let MonitorEvent::Snapshot(snapshot) = monitor.next().unwrap() {
+            break snapshot;
+        }
+    };
+    let first = snapshot();
+    assert_eq!(first.taken(), start);
+    assert_eq!(clock.elapsed(), std::time::Duration::ZERO);
+    // the next poll waits for the interval on the clock, in no time
+    source.append("TimeStep   121: Time 6.050000e+00\n");
+    let second = snapshot();
+    assert_eq!(clock.elapsed().as_secs(), 300);
+    assert_eq!(second.taken(), start + Duration::minutes(5));
+    let case = second.running().next().unwrap();
+    assert_eq!(case.now(), second.taken());
+    // 79 time steps of 5 minutes left
+    assert_eq!(case.eta_secs(), 79 * 300);
+    assert_eq!(
+        case.eta(),
+        Some(start + Duration::minutes(5) + Duration::seconds(79 * 300))
+    );
+    // the case stalls at the first poll over 10 minutes after its last time step
+    let stalled = monitor
+        .find(|event| matches!(event, MonitorEvent::Stalled(_)))
+        .unwrap();
+    assert_eq!(clock.elapsed().as_secs(), 20 * 60);
+    assert!(matches!(stalled, MonitorEvent::Stalled(case) if case.is_stalled()));
+    // the alerts are raised at the time of the clock
+    let Some(MonitorEvent::Snapshot(snapshot)) =
+        monitor.find(|event| matches!(event, MonitorEvent::Snapshot(_)))
+    else {
+        panic!("no snapshot after the stall");
+    };
+    assert_eq!(snapshot.alerts()[0].since, start + Duration::minutes(20));
+}
+
+#[test]
+fn delayed_polls() {
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    let source = StringSource::new(RUNNING);
+    let mut monitor = Monitor::new(
+        vec![case(source.clone())],
+        std::time::Duration::from_secs(300),
+    )
+    .clock(Arc::new(clock.clone()));
+    let mut snapshot = || loop {
+        if let MonitorEvent::Snapshot(snapshot) = monitor.next().unwrap() {
+            break snapshot;
+        }
+    };
+    snapshot();
+    // a poll delayed by 5 minutes, e.g. by a slow filesystem
+    source.append("TimeStep   121: Time 6.050000e+00\nTimeStep   122: Time 6.100000e+00\n");
+    clock.advance(std::time::Duration::from_secs(300));
+    let delayed = snapshot();
+    assert_eq!(clock.elapsed().as_secs(), 600);
+    let case = delayed.running().next().unwrap();
+    // the 2 time steps took the 10 minutes since the previous poll, not the interval
+    assert_eq!(case.elapsed_per_step().mean(), 300.);
+    assert_eq!(case.eta_secs(), 78 * 300);
+}
+
+#[test]
+fn transition_log() {
+    let dir = TempDir::new("events");
+    let path = dir.join("events.jsonl");
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    let source = StringSource::new(RUNNING);
+    let case = case(source.clone()).stall_after(600).group("baseline");
+    let mut monitor = Monitor::new(vec![case], std::time::Duration::from_secs(300))
+        .clock(Arc::new(clock.clone()));
+    let mut transitions = TransitionLog::with_clock(&path, 1, &clock).unwrap();
+    let mut until = |done: &dyn Fn(&MonitorEvent) -> bool| {
+        for event in monitor.by_ref() {
+            transitions.record(&event);
+            if done(&event) {
+                break;
+            }
+        }
+    };
+    until(&|event| matches!(event, MonitorEvent::Stalled(_)));
+    source.append("TimeStep   121: Time 6.050000e+00\n");
+    until(&|event| matches!(event, MonitorEvent::Snapshot(_)));
+    source.append("TimeStep   200: Time 1.000000e+01\n");
+    until(&|event| matches!(event, MonitorEvent::Completed(_)));
+    let kinds = |path: &std::path::Path| -> Vec<_> {
+        TransitionLog::read(path)
+            .unwrap()
+            .into_iter()
+            .map(|transition| transition.event)
+            .collect()
+    };
+    assert_eq!(
+        kinds(&path),
+        [
+            TransitionKind::MonitorStarted,
+            TransitionKind::Started,
+            TransitionKind::Stalled,
+            TransitionKind::Resumed,
+            TransitionKind::Completed
+        ]
+    );
+    let stalled = &TransitionLog::read(&path).unwrap()[2];
+    assert_eq!(stalled.case.as_deref(), Some("zen30az045_OS2"));
+    assert_eq!(stalled.group.as_deref(), Some("baseline"));
+    assert_eq!((stalled.step, stalled.percent), (Some(120), Some(60)));
+    assert_eq!(stalled.at, start + Duration::minutes(15));
+    assert_eq!(TransitionLog::read(&path).unwrap()[0].at, start);
+    // the states of the cases are restored when the monitor restarts,
+    // skipping a transition of a later version and a line truncated by a crash
+    let mut log = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    writeln!(
+        log,
+        r#"{{"at":"2024-06-14T19:00:00+02:00","event":"paused"}}"#
+    )
+    .unwrap();
+    write!(log, r#"{{"at":"2024-06-14T19:00:00+02:00","event":"sta"#).unwrap();
+    drop(log);
+    let completed = monitor.completed()[0].clone();
+    let mut transitions = TransitionLog::create(&path, 1).unwrap();
+    transitions.record(&MonitorEvent::Updated(completed.clone()));
+    transitions.record(&MonitorEvent::Completed(completed.clone()));
+    transitions.record(&MonitorEvent::Error {
+        case: completed.clone(),
+        error: CaseError::Grep,
+        failures: 1,
+    });
+    transitions.record(&MonitorEvent::Error {
+        case: completed.clone(),
+        error: CaseError::Grep,
+        failures: 2,
+    });
+    transitions.record(&MonitorEvent::Updated(completed));
+    assert_eq!(
+        kinds(&path)[5..],
+        [
+            TransitionKind::MonitorRestarted,
+            TransitionKind::Failed,
+            TransitionKind::Resumed
+        ]
+    );
+}
+
+#[test]
+fn transition_log_forgets() {
+    let dir = TempDir::new("forgotten-events");
+    let path = dir.join("forgotten-events.jsonl");
+    let updated = |log: &str| {
+        let mut case = case(StringSource::new(log));
+        case.update().unwrap();
+        case
+    };
+    let (running, restarted) = (
+        updated(RUNNING),
+        updated("TimeStep   10: Time 5.000000e-01\n"),
+    );
+    let kinds = |path: &std::path::Path| -> Vec<_> {
+        TransitionLog::read(path)
+            .unwrap()
+            .into_iter()
+            .map(|transition| transition.event)
+            .collect()
+    };
+    let mut transitions = TransitionLog::create(&path, 1).unwrap();
+    transitions.record(&MonitorEvent::Updated(running.clone()));
+    transitions.record(&MonitorEvent::Completed(running.clone()));
+    // going back to an earlier time step
+    transitions.record(&MonitorEvent::Updated(restarted.clone()));
+    transitions.record(&MonitorEvent::Completed(restarted.clone()));
+    // removed from the configuration
+    transitions.record(&MonitorEvent::Snapshot(StatusSnapshot::new(&[], &[], 180)));
+    transitions.record(&MonitorEvent::Updated(running.clone()));
+    assert_eq!(
+        kinds(&path)[1..],
+        [
+            TransitionKind::Started,
+            TransitionKind::Completed,
+            TransitionKind::Started,
+            TransitionKind::Completed,
+            TransitionKind::Started
+        ]
+    );
+    // a replaced log, restored when the monitor restarts
+    drop(transitions);
+    let mut log = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    writeln!(
+        log,
+        r#"{{"at":"2024-06-14T19:00:00+02:00","event":"log_rotated","case":"zen30az045_OS2"}}"#
+    )
+    .unwrap();
+    drop(log);
+    let mut transitions = TransitionLog::create(&path, 1).unwrap();
+    transitions.record(&MonitorEvent::Updated(running));
+    assert_eq!(
+        kinds(&path)[7..],
+        [TransitionKind::MonitorRestarted, TransitionKind::Started]
+    );
+}
+
+#[test]
+fn poll_stats() {
+    // a time step overflowing its type
+    let garbled = "TimeStep   99999999999999999999999: Time 1.000000e+00\n";
+    let garbled = Case::new("garbled", 10, "garbled.out").log_source(StringSource::new(garbled));
+    let source = StringSource::new(RUNNING);
+    let mut monitor = Monitor::new(
+        vec![case(source.clone()), garbled],
+        std::time::Duration::from_secs(300),
+    );
+    let snapshot = monitor
+        .poll()
+        .into_iter()
+        .find_map(|event| match event {
+            MonitorEvent::Snapshot(snapshot) => Some(snapshot),
+            _ => None,
+        })
+        .unwrap();
+    let stats = snapshot.poll_stats().unwrap();
+    assert_eq!(stats, monitor.poll_stats());
+    assert_eq!(stats.cases.len(), 2);
+    assert_eq!(stats.cases[0].case, "zen30az045_OS2");
+    assert_eq!(stats.cases[0].bytes, RUNNING.len() as u64);
+    assert!(!stats.cases[0].parse_failure);
+    assert!(stats.cases[1].parse_failure);
+    assert_eq!(stats.bytes(), RUNNING.len() as u64 + 54);
+    assert_eq!(stats.parse_failures(), 1);
+    assert!(stats.secs >= stats.cases.iter().map(|case| case.secs).sum::<f64>());
+    let footer = snapshot.to_string();
+    let footer = footer.lines().last().unwrap();
+    assert!(footer.starts_with("Poll: "), "{footer}");
+    assert!(footer.contains("2 cases updated, "));
+    assert!(footer.contains(" 1 parse failures, slowest "));
+    // only the new lines are read at the next poll
+    source.append("TimeStep   121: Time 6.050000e+00\n");
+    monitor.poll();
+    assert_eq!(monitor.poll_stats().cases[0].bytes, 34);
+    assert_eq!(monitor.poll_stats().cases[1].bytes, 0);
+}
+
+#[test]
+fn monitor_reconfigured() {
+    let source = StringSource::new(RUNNING);
+    let mut monitor = Monitor::new(
+        vec![case(source.clone()).group("OS2")],
+        std::time::Duration::from_secs(30),
+    );
+    monitor.poll();
+    // the settings of a case configured again are taken, its progress kept
+    monitor.set_cases(vec![case(StringSource::default()).group("OS7").priority(2)]);
+    let case = &monitor.running()[0];
+    assert_eq!(case.step(), Some(120));
+    assert_eq!(case.group_name(), Some("OS7"));
+    assert_eq!(case.priority_level(), 2);
+}
+
+#[test]
+fn monitor_offline() {
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    let source = StringSource::new(RUNNING);
+    let mut running = case(source.clone()).clock(Arc::new(clock.clone()));
+    running.update_over(30.).unwrap();
+    let steps = |from: usize| {
+        (from..from + 10)
+            .map(|step| format!("TimeStep   {step}: Time {:.6e}\n", step as f64 * 0.05))
+            .collect::<String>()
+    };
+    source.append(&steps(121));
+    clock.advance(std::time::Duration::from_secs(30));
+    running.update_over(30.).unwrap();
+    assert_eq!(running.step_times().last(), Some(3.));
+    assert_eq!(running.offline_intervals().count(), 0);
+    // the monitor is suspended for 2 hours, the case running on
+    source.append(&steps(131));
+    clock.advance(std::time::Duration::from_secs(7200));
+    running.update_over(30.).unwrap();
+    assert_eq!(running.step_times().last(), Some(720.));
+    let offline: Vec<_> = running.offline_intervals().collect();
+    assert_eq!(offline.len(), 1);
+    assert_eq!(offline[0].secs(), 7200);
+    assert_eq!(offline[0].to, start + Duration::seconds(7230));
+    let detail = CaseDetail::new(&running, []).to_string();
+    assert!(
+        detail.contains("Monitor offline\n  2024-06-14 18:00:30 to"),
+        "{detail}"
+    );
+    // under a monitor, the gaps are told from its interval, the sampling windows spanning polls
+    let clock = MockClock::new(start);
+    let source = StringSource::new(RUNNING);
+    let mut monitor = Monitor::new(
+        vec![case(source.clone())],
+        std::time::Duration::from_secs(30),
+    )
+    .clock(Arc::new(clock.clone()));
+    let mut snapshot = || loop {
+        if let MonitorEvent::Snapshot(snapshot) = monitor.next().unwrap() {
+            break snapshot;
+        }
+    };
+    snapshot();
+    source.append(&steps(121));
+    snapshot();
+    // a poll without a new time step, then the monitor suspended for 2 hours,
+    // the 10 time steps spanning the 7260 s since the last one
+    snapshot();
+    source.append(&steps(131));
+    clock.advance(std::time::Duration::from_secs(7200));
+    let resumed = snapshot();
+    let case = resumed.running().next().unwrap();
+    let offline: Vec<_> = case.offline_intervals().collect();
+    assert_eq!(offline.len(), 1);
+    assert_eq!(offline[0].secs(), 7230);
+    assert_eq!(case.step_times().last(), Some(726.));
+    // the samples of the interval are marked in the history, the snapshot and the session
+    assert_eq!(case.step_times().offline().last(), Some(true));
+    assert!(case.step_times().sparkline().ends_with('┆'));
+    let dir = TempDir::new("offline");
+    let path = dir.join("offline.jsonl");
+    cfd_status::SessionRecorder::create(&path)
+        .unwrap()
+        .record_snapshot(&resumed);
+    let entries = cfd_status::read_session(&path).unwrap();
+    let [SessionEntry::Snapshot { status, .. }] = entries.as_slice() else {
+        panic!("no recorded snapshot in {entries:?}");
+    };
+    assert_eq!(status["cases"][0]["offline"][0]["to"], json!(offline[0].to));
+}
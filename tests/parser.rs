@@ -0,0 +1,328 @@
+mod common;
+
+use std::sync::Arc;
+
+use cfd_status::{
+    parse_log, parse_log_with, AlertKind, Alerts, BadgeState, Case, CaseError, CommandSource,
+    DtPattern, DurationSpec, ErrorPattern, Severity, StepParser, StepRecord, StringSource,
+    TimeStepPattern,
+};
+#[cfg(feature = "monitor")]
+use cfd_status::{CaseDetail, Column};
+#[cfg(feature = "monitor")]
+use chrono::Local;
+
+use common::{case, TempDir, COMPLETED, INITIALIZING, RUNNING};
+
+#[test]
+fn running_time_step() {
+    let mut case = case(StringSource::new(RUNNING));
+    case.update().unwrap();
+    assert_eq!(case.step(), Some(120));
+    assert!((case.time() - 6.).abs() < 1e-9);
+    assert_eq!(case.total_step(), 200);
+    assert_eq!(case.percent_complete(), 60);
+    assert_eq!(case.state(), "running");
+}
+
+#[test]
+fn running_environment() {
+    let mut case = case(StringSource::new(RUNNING));
+    case.update().unwrap();
+    assert_eq!(case.solver_version(), Some("17.06.007"));
+    let environment = case.environment();
+    assert_eq!(environment.hosts, ["compute-001"]);
+    assert_eq!(environment.instance_type.as_deref(), Some("c6i.32xlarge"));
+    assert_eq!(environment.mesh.as_deref(), Some("zen30az045_OS2.sim"));
+}
+
+#[test]
+fn running_milestones() {
+    let mut case = case(StringSource::new(RUNNING));
+    case.update().unwrap();
+    let names: Vec<_> = case
+        .milestones_found()
+        .iter()
+        .map(|milestone| milestone.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "mesh loaded",
+            "initialized",
+            "first time step",
+            "checkpoint",
+            "checkpoint"
+        ]
+    );
+}
+
+#[test]
+fn initializing() {
+    let mut case = case(StringSource::new(INITIALIZING));
+    case.update().unwrap();
+    assert_eq!(case.step(), None);
+    assert!(case.is_initializing());
+    assert_eq!(case.state(), "initializing");
+    let progress = case.init_progress().unwrap();
+    assert_eq!(progress.phase, "AMG setup");
+}
+
+#[test]
+fn completed() {
+    let mut case = case(StringSource::new(COMPLETED));
+    case.update().unwrap();
+    assert_eq!(case.step(), Some(200));
+    assert!(case.is_complete());
+    assert_eq!(case.state(), "completed");
+    assert_eq!(case.eta_secs(), 0);
+}
+
+#[test]
+fn offline_log_parsing() {
+    let records = parse_log("tests/fixtures/completed.out").unwrap();
+    assert_eq!(records.len(), 200);
+    assert_eq!(records[0].step, 1);
+    assert!((records[199].time - 10.).abs() < 1e-9);
+    assert!(records.iter().all(|record| record.wall_clock.is_none()));
+
+    let dir = TempDir::new("parse");
+    let path = dir.join("solve.out");
+    std::fs::write(
+        &path,
+        "2024-06-10 14:03:27 solver started\nT=1.0D-01 s, Time Step 1\nT=2.0D-01 s, Time Step 2\n\
+         [2024-06-10T14:05:00.5] autosave\nT=3.0D-01 s, Time Step 3\n",
+    )
+    .unwrap();
+    let parser = TimeStepPattern::new(r"T=(\S+) s, Time Step (\d+)", 2, 1).unwrap();
+    let records = parse_log_with(&path, &parser).unwrap();
+    let wall_clock = |record: &StepRecord| {
+        record
+            .wall_clock
+            .map(|time| time.format("%H:%M:%S%.3f").to_string())
+    };
+    assert_eq!(
+        records.iter().map(|record| record.step).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert_eq!(wall_clock(&records[0]).as_deref(), Some("14:03:27.000"));
+    assert_eq!(wall_clock(&records[1]), None);
+    assert_eq!(wall_clock(&records[2]).as_deref(), Some("14:05:00.500"));
+}
+
+#[test]
+fn incremental_step_time() {
+    let split = RUNNING.find("TimeStep    61:").unwrap();
+    let source = StringSource::new(&RUNNING[..split]);
+    let mut case = case(source.clone());
+    case.update_over(0.).unwrap();
+    assert_eq!(case.step(), Some(60));
+    source.append(&RUNNING[split..]);
+    case.update_over(120.).unwrap();
+    assert_eq!(case.step(), Some(120));
+    assert!((case.elapsed_per_step().mean() - 2.).abs() < 1e-9);
+    assert_eq!(case.eta_secs(), 160);
+}
+
+#[test]
+fn physical_time_progress() {
+    let mut case = case(StringSource::new(RUNNING)).duration_spec(DurationSpec::PhysicalTime(8.));
+    case.update().unwrap();
+    assert_eq!(case.percent_complete(), 75);
+    assert_eq!(case.total_step(), 160);
+}
+
+#[test]
+fn reported_time_step_size() {
+    let source = StringSource::default();
+    let mut case = case(source.clone()).dt_pattern(DtPattern::default());
+    // the time step is halved from the time step 61 on, the 10s end needing 340 time steps
+    let mut time = 0.;
+    for step in (10..=120).step_by(10) {
+        if step == 70 {
+            source.append("Time step size: 2.5e-02\n");
+        }
+        time += if step > 60 { 0.25 } else { 0.5 };
+        source.append(&format!("TimeStep {step}: Time {time:.6e}\n"));
+        case.update_over(if step > 60 { 40. } else { 20. }).unwrap();
+    }
+    assert_eq!(case.time_step_size(), Some(0.025));
+    assert_eq!(case.total_step(), 340);
+    assert_eq!(case.percent_complete(), 45);
+    assert_eq!(case.eta_secs(), 880);
+}
+
+#[test]
+fn time_step_pattern() {
+    let pattern = TimeStepPattern::new(r"Time Step\s+(\d+)\s+Time\s*=\s*(\S+)", 1, 2).unwrap();
+    let log = "Time Step 41 Time = 2.05D+00\nsaving zen30az045_OS2@00041.sim\nTime Step 42 Time = 2.1D+00\n";
+    let mut case = case(StringSource::new(log)).parser(Arc::new(pattern));
+    case.update().unwrap();
+    assert_eq!(case.step(), Some(42));
+    assert!((case.time() - 2.1).abs() < 1e-9);
+    let swapped = TimeStepPattern::new(r"T=(\S+) s, step (\d+)", 2, 1).unwrap();
+    assert_eq!(swapped.parse_line("T=0.5 s, step 10"), Some((10, 0.5)));
+    assert!(TimeStepPattern::new(r"Time Step (\d+)", 1, 2).is_err());
+    assert!(TimeStepPattern::new(r"Time Step (\d+", 1, 2).is_err());
+}
+
+#[test]
+fn logged_step_time() {
+    let source = StringSource::new(
+        "2024-06-10 14:00:00 TimeStep     1: Time 5.000000e-02\n\
+         2024-06-10 14:00:10 TimeStep     2: Time 1.000000e-01\n",
+    );
+    let mut case = case(source.clone());
+    case.update_over(1000.).unwrap();
+    assert_eq!(case.step(), Some(2));
+    source.append("2024-06-10 14:00:20 writing restart\nTimeStep     3: Time 1.500000e-01\n");
+    case.update_over(1000.).unwrap();
+    // the timestamp before the time step line stands for it
+    assert!((case.elapsed_per_step().mean() - 10.).abs() < 1e-9);
+    assert_eq!(
+        case.logged_at().map(|at| at.format("%H:%M:%S").to_string()),
+        Some("14:00:20".to_string())
+    );
+    // without timestamps the time between the updates is used
+    source.append("TimeStep     4: Time 2.000000e-01\n");
+    case.update_over(1000.).unwrap();
+    assert!((case.elapsed_per_step().mean() - 505.).abs() < 1e-9);
+    assert_eq!(case.logged_at(), None);
+}
+
+#[test]
+fn logged_step_durations() {
+    // the time steps read at once are each timed by their lines
+    let source = StringSource::new("2024-06-10 14:00:00 TimeStep     1: Time 5.000000e-02\n");
+    let mut case = case(source.clone());
+    case.update_over(1000.).unwrap();
+    source.append(
+        "2024-06-10 14:00:02 TimeStep     2: Time 1.000000e-01\n\
+         2024-06-10 14:00:04 TimeStep     3: Time 1.500000e-01\n\
+         2024-06-10 14:00:44 TimeStep     4: Time 2.000000e-01\n",
+    );
+    case.update_over(1000.).unwrap();
+    let distribution = case.step_time_distribution();
+    assert_eq!(distribution.steps(), 3);
+    assert_eq!(distribution.max(), 40.);
+    assert!((distribution.sum() - 44.).abs() < 1e-9);
+    // without timestamps the time steps all last their mean
+    source.append("TimeStep     5: Time 2.500000e-01\nTimeStep     6: Time 3.000000e-01\n");
+    case.update_over(1000.).unwrap();
+    assert_eq!(case.step_time_distribution().steps(), 5);
+    assert_eq!(case.step_time_distribution().max(), 500.);
+}
+
+#[test]
+fn solver_error() {
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone()).error_patterns(ErrorPattern::defaults());
+    case.update().unwrap();
+    assert_eq!(case.state(), "running");
+    source.append("Floating point exception (core dumped)\n");
+    case.update().unwrap();
+    assert_eq!(case.state(), "failed");
+    assert_eq!(case.attention(), Some("failed"));
+    let failure = case.log_failure().unwrap();
+    assert_eq!(failure.name, "floating point exception");
+    assert_eq!(failure.line, "Floating point exception (core dumped)");
+    let row = case.to_string();
+    assert!(
+        row.contains("! floating point exception: Floating point exception (core dumped)"),
+        "{row}"
+    );
+    let mut alerts = Alerts::new();
+    let alert = alerts
+        .update(std::slice::from_ref(&case))
+        .sorted()
+        .remove(0);
+    assert_eq!(alert.kind, AlertKind::LogError);
+    assert_eq!(alert.severity(), Severity::Critical);
+    // a time step after the error is a restart
+    let step = case.step().unwrap() + 1;
+    source.append(&format!(
+        "TimeStep {step}: Time {:.6e}\n",
+        step as f64 * 0.05
+    ));
+    case.update().unwrap();
+    assert_eq!(case.state(), "running");
+    assert!(alerts.update(&[case]).sorted().is_empty());
+}
+
+#[test]
+fn no_time_step_yet() {
+    let mut case = case(StringSource::new("Segmentation fault (core dumped)\n"));
+    case.update().unwrap();
+    assert_eq!(case.step(), None);
+    assert_eq!(case.state(), "initializing");
+    assert!(case.init_progress().is_none());
+}
+
+#[test]
+fn missing_log() {
+    let mut case = case(StringSource::default());
+    assert!(!case.is_initializing());
+    assert!(matches!(case.update(), Err(CaseError::Capture)));
+    // a log not created yet is polled for until it is
+    let source = StringSource::missing();
+    let mut case = self::case(source.clone());
+    assert!(case.is_waiting());
+    case.update().unwrap();
+    assert_eq!(case.state(), "waiting");
+    assert_eq!(BadgeState::of(&case), BadgeState::Waiting);
+    source.append("Loading simulation: zen30az045_OS2.sim\n");
+    case.update().unwrap();
+    assert_eq!(case.state(), "initializing");
+    source.append("TimeStep     1: Time 5.000000e-02\n");
+    case.update().unwrap();
+    assert_eq!(case.state(), "running");
+    assert_eq!(case.step(), Some(1));
+}
+
+#[test]
+fn command_source() {
+    let mut case = Case::new("remote", 10, "solve.out")
+        .log_source(CommandSource::new("printf 'TimeStep 5: Time 2.5e-01\\n'"));
+    case.update().unwrap();
+    assert_eq!(case.step(), Some(5));
+    let mut case = Case::new("remote", 10, "solve.out").log_source(CommandSource::new("exit 1"));
+    assert!(matches!(case.update(), Err(CaseError::Grep)));
+    assert!(!case.is_waiting());
+}
+
+#[cfg(feature = "monitor")]
+#[test]
+fn inner_iterations() {
+    let mut running = case(StringSource::new(RUNNING));
+    running.update().unwrap();
+    assert_eq!(running.inner_iterations().latest(), Some(2));
+    assert!(!running.is_iterating_more());
+
+    let mut log = String::new();
+    let mut iteration = 0;
+    for (step, iterations) in (1..=20).zip([2; 12].into_iter().chain([7; 8])) {
+        log.push_str(&format!(
+            "TimeStep {step}: Time {:.6e}\n  Iteration  Continuity  X-momentum\n",
+            step as f64 * 0.05
+        ));
+        for _ in 0..iterations {
+            iteration += 1;
+            log.push_str(&format!("  {iteration:>9}  1.0000e-03  2.0000e-04\n"));
+        }
+    }
+    let source = StringSource::new(&log);
+    let mut case = case(source.clone());
+    case.update().unwrap();
+    assert_eq!(case.inner_iterations().baseline(), Some(2.));
+    assert_eq!(case.inner_iterations().latest(), Some(7));
+    assert!(case.is_iterating_more());
+    assert_eq!(
+        Column::Iterations.cell(&case, Default::default(), Local::now()),
+        "7↑"
+    );
+    let mut alerts = Alerts::new();
+    alerts.update(std::slice::from_ref(&case));
+    assert_eq!(alerts.sorted()[0].kind, AlertKind::IterationsClimbing);
+    let detail = CaseDetail::new(&case, []).to_string();
+    assert!(detail.contains("climbing from 2.0"), "{detail}");
+}
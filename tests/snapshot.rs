@@ -0,0 +1,783 @@
+//! Tests of the status snapshots and of their renderings and reports
+
+mod common;
+
+use std::sync::Arc;
+
+use cfd_status::{
+    Aggregate, AggregateError, AggregationConfig, Alerts, ArchivedCase, Badge, BadgeState,
+    CampaignSummary, Case, CheckpointPattern, Column, ColumnSpec, Config, ErrorPattern,
+    HealthConfig, HealthIndex, JobUsage, LogExcerpt, MockClock, Notes, Notifier, NotifyConfig,
+    ProgressPlots, Publisher, Query, ResourceCollector, Severity, SimTimeAlignment, SimTimeline,
+    SnapshotDiff, StallIncident, StatusSnapshot, StringSource, Tabs, ThroughputCalendar,
+    ThroughputComparison, TimedRun, TriageRule, Utilization,
+};
+#[cfg(feature = "monitor")]
+use cfd_status::{CaseDetail, EventHistory, ResidualTrend, SessionEntry, StepTimeHistogram};
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use serde_json::json;
+
+use common::{case, TempDir, COMPLETED, RUNNING};
+
+#[test]
+fn status_badge() {
+    let mut case = case(StringSource::new(RUNNING));
+    case.update().unwrap();
+    let badge = Badge::case(&case);
+    assert_eq!(badge.state, BadgeState::Running);
+    assert_eq!(badge.message(), "60%");
+    let svg = badge.with_label("a&b".to_string()).to_svg();
+    assert!(svg.contains("<text x=\"20\" y=\"14\">a&amp;b</text>"));
+    assert!(svg.contains(BadgeState::Running.color()));
+}
+
+#[test]
+fn health_without_cores() {
+    let mut case =
+        Case::new("zen30az045_OS2", 10, "solve-0_14.out").log_source(StringSource::new(RUNNING));
+    case.update().unwrap();
+    let health = HealthIndex::new([&case].into_iter(), &[], &HealthConfig::default());
+    assert_eq!(health.idle, 0.);
+    assert!(health.score.is_finite());
+}
+
+#[test]
+fn log_excerpt() {
+    let mut case = case(StringSource::new(RUNNING)).excerpt_lines(3);
+    case.update().unwrap();
+    let excerpt = LogExcerpt::new(&case, "stalled");
+    let last: Vec<_> = RUNNING.lines().rev().take(3).collect();
+    assert_eq!(
+        excerpt.lines,
+        last.iter().rev().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(excerpt.snippet(1), last[0]);
+    assert_eq!(excerpt.snippet(10), excerpt.lines.join("\n"));
+    let text = excerpt.to_string();
+    assert!(text.starts_with("# zen30az045_OS2 stalled at "));
+    assert!(text
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with(", step 120, last 3 log lines"));
+    let dir = TempDir::new("excerpt");
+    let path = excerpt.save(&dir).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+    assert!(path.starts_with(dir.join("zen30az045_OS2")));
+}
+
+#[test]
+fn failure_triage() {
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone()).triage_rules(TriageRule::defaults());
+    case.update().unwrap();
+    source.append(
+        "[ip-10-0-3-17] MPI_ABORT was invoked on rank 412 in communicator MPI_COMM_WORLD\n",
+    );
+    case.update().unwrap();
+    // the hint is only shown once the case has failed
+    assert!(case.triage().is_none());
+    case.set_error(Some("log not updated".to_string()));
+    let triage = case.triage().unwrap();
+    assert_eq!(triage.name, "MPI rank failure");
+    assert_eq!(
+        triage.hint,
+        "likely spot node loss — resubmit from last checkpoint"
+    );
+    // a time step after the signature is a recovery
+    let step = case.step().unwrap() + 1;
+    source.append(&format!(
+        "TimeStep {step}: Time {:.6e}\n",
+        step as f64 * 0.05
+    ));
+    case.update().unwrap();
+    case.set_error(Some("log not updated".to_string()));
+    assert!(case.triage().is_none());
+}
+
+#[cfg(feature = "otel")]
+#[test]
+fn otlp_metrics() {
+    let mut running = case(StringSource::new(RUNNING));
+    running.update().unwrap();
+    let snapshot = StatusSnapshot::new(&[running], &[], 180);
+    let exporter = cfd_status::OtlpExporter::new("http://127.0.0.1:4318", "cfd-status", vec![]);
+    let metrics = exporter.metrics(&snapshot, 1_700_000_000_000_000_000);
+    let resource = &metrics["resourceMetrics"][0];
+    assert_eq!(
+        resource["resource"]["attributes"][0]["value"]["stringValue"],
+        "cfd-status"
+    );
+    let metrics = &resource["scopeMetrics"][0]["metrics"];
+    assert_eq!(metrics[0]["name"], "cfd.case.step");
+    let point = &metrics[0]["gauge"]["dataPoints"][0];
+    assert_eq!(point["asInt"], "120");
+    assert_eq!(point["timeUnixNano"], "1700000000000000000");
+    assert_eq!(
+        point["attributes"][0]["value"]["stringValue"],
+        "zen30az045_OS2"
+    );
+    assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["asInt"], "60");
+}
+
+#[test]
+fn status_query() {
+    let mut running = case(StringSource::new(RUNNING));
+    running.update().unwrap();
+    let mut completed = self::case(StringSource::new(COMPLETED));
+    completed.update().unwrap();
+    let snapshot = StatusSnapshot::new(&[running], &[completed], 180);
+    let query = |query: &str| query.parse::<Query>().unwrap().evaluate(&snapshot);
+    assert!(query("count(state=stalled) == 0").holds);
+    assert!(query("min(progress) < 0.7 and max(progress) == 1").holds);
+    assert!(!query("count(state=failed) > 0 or mean(percent) > 90").holds);
+    assert_eq!(query("mean(percent)").to_string(), "80");
+    assert_eq!(query("count(state!=completed)").value, Some(1.));
+    assert_eq!(query("min(step, name=none)").to_string(), "-");
+    assert!("max(foo) > 1".parse::<Query>().is_err());
+    assert!("count(state=stalled) >".parse::<Query>().is_err());
+}
+
+#[test]
+fn snapshot_diff() {
+    let from = json!({
+        "taken": "2024-06-14T18:00:00+02:00",
+        "cases": [
+            {"name": "a", "state": "running", "step": 100, "time": 5.0, "eta": "2024-06-17T06:00:00+02:00"},
+            {"name": "b", "state": "running", "step": 40, "time": 2.0, "eta": "2024-06-15T12:00:00+02:00"},
+            {"name": "c", "state": "running", "step": 10, "time": 0.5},
+        ]
+    });
+    let to = json!({
+        "taken": "2024-06-17T08:00:00+02:00",
+        "cases": [
+            {"name": "a", "state": "running", "step": 1540, "time": 77.0, "eta": "2024-06-18T09:30:00+02:00"},
+            {"name": "b", "state": "completed", "step": 200, "time": 10.0, "completed": "2024-06-15T10:00:00+02:00"},
+            {"name": "d", "state": "pending"},
+        ]
+    });
+    let diff = SnapshotDiff::new(&from, &to).unwrap();
+    let names: Vec<_> = diff.cases.iter().map(|case| case.case.as_str()).collect();
+    assert_eq!(names, ["a", "b", "d", "c"]);
+    let a = &diff.cases[0];
+    assert_eq!(a.steps, Some(1440));
+    assert_eq!(a.steps_per_hour, Some(1440. / 62.));
+    assert_eq!(a.eta_drift_secs, Some(27 * 3600 + 1800));
+    let b = &diff.cases[1];
+    assert_eq!(b.eta_drift_secs, Some(-2 * 3600));
+    assert_eq!(b.to_state.as_deref(), Some("completed"));
+    assert_eq!((diff.cases[2].steps, diff.cases[3].steps), (None, None));
+    let report = diff.to_string();
+    // the times are shown in the local time zone
+    assert!(report.lines().next().unwrap().ends_with("(62:00)"));
+    assert!(report.contains("running -> completed"));
+    assert!(report.contains("removed"));
+    assert!(SnapshotDiff::new(&to, &from).is_err());
+    assert!(SnapshotDiff::new(&json!({"cases": []}), &to).is_err());
+}
+
+#[test]
+fn throughput_calendar() {
+    let day = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+    // a weekend slowdown and an outage on the Tuesday
+    let calendar = ThroughputCalendar::new([
+        (day(13), 800.),
+        (day(14), 600.),
+        (day(15), 150.),
+        (day(16), 100.),
+        (day(16), 100.),
+        (day(18), 0.),
+        (day(19), 400.),
+    ]);
+    assert_eq!(calendar.max(), 800.);
+    let rendered = calendar.to_string();
+    let lines: Vec<_> = rendered.lines().collect();
+    assert_eq!(lines[0].trim(), "Mo Tu We Th Fr Sa Su");
+    assert_eq!(lines[1], "2024-06-10           ██ ▓▓ ░░ ░░    1750.00s");
+    assert_eq!(lines[2], "2024-06-17     ·· ▒▒                 400.00s");
+    assert!(lines[3].ends_with("of 800.00s"));
+    assert_eq!(ThroughputCalendar::default().to_string(), "no history\n");
+}
+
+#[test]
+fn attention_order() {
+    let running = |name: &str| {
+        let mut case = Case::new(name, 10, "solve.out").log_source(StringSource::new(RUNNING));
+        case.update().unwrap();
+        case
+    };
+    let mut failed = running("failed");
+    failed.set_error(Some("log file missing".to_string()));
+    let late = running("late").deadline(Local::now() - Duration::hours(1));
+    let urgent = running("urgent").priority(10);
+    let cases = [running("routine"), urgent, late, failed];
+    let spec = ColumnSpec::new(
+        vec![Column::Priority, Column::Attention],
+        "attention".parse().unwrap(),
+    );
+    let names: Vec<_> = spec
+        .sort(cases.iter())
+        .iter()
+        .map(|case| case.name())
+        .collect();
+    assert_eq!(names, ["failed", "late", "urgent", "routine"]);
+    assert_eq!(cases[3].attention(), Some("failed"));
+    assert_eq!(cases[0].attention(), None);
+    let now = Local::now();
+    assert_eq!(
+        Column::Attention.cell(&cases[2], Default::default(), now),
+        "late"
+    );
+    assert_eq!(
+        Column::Priority.cell(&cases[1], Default::default(), now),
+        "10"
+    );
+}
+
+#[test]
+fn restart_advice() {
+    let mut case =
+        case(StringSource::new(RUNNING)).checkpoint_pattern(CheckpointPattern::default());
+    case.update().unwrap();
+    let checkpoint = case.last_checkpoint().unwrap();
+    assert_eq!(checkpoint.step, 100);
+    assert_eq!(checkpoint.file.as_deref(), Some("zen30az045_OS2@00100.sim"));
+    // the restart is only advised once the case has failed
+    assert!(case.restart_advice().is_none());
+    case.set_error(Some("log not updated".to_string()));
+    let advice = case.restart_advice().unwrap();
+    assert_eq!(advice.lost_steps, 20);
+    assert_eq!(
+        advice.to_string(),
+        "restart from zen30az045_OS2@00100.sim (step 100) losing 1.00s simulated (20 steps)"
+    );
+}
+
+#[test]
+fn cluster_aggregation() {
+    let mut running = case(StringSource::new(RUNNING));
+    running.update().unwrap();
+    let snapshot = StatusSnapshot::new(&[running], &[], 180);
+    let dir = TempDir::new("clusters");
+    for cluster in ["hpc-west", "hpc-east"] {
+        Publisher::from(AggregationConfig {
+            cluster: cluster.to_string(),
+            push: None,
+            token: None,
+            dir: Some(dir.to_path_buf()),
+        })
+        .publish(&snapshot, &Default::default());
+    }
+    let mut aggregate = Aggregate::new();
+    aggregate.read_dir(&dir).unwrap();
+    assert_eq!(
+        aggregate.clusters().collect::<Vec<_>>(),
+        ["hpc-east", "hpc-west"]
+    );
+    let merged: serde_json::Value = serde_json::from_str(&aggregate.to_json()).unwrap();
+    assert_eq!(merged["cases"][0]["cluster"], "hpc-east");
+    assert_eq!(merged["cases"][1]["cluster"], "hpc-west");
+    assert_eq!(merged["cases"][1]["name"], "zen30az045_OS2");
+    let board = aggregate.to_string();
+    assert!(board.contains("hpc-west: 1 cases"));
+    // an older snapshot of a cluster does not replace the latest one
+    let mut older: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("hpc-east.json")).unwrap()).unwrap();
+    older["status"]["taken"] = json!((Local::now() - Duration::hours(1)).to_rfc3339());
+    older["status"]["cases"] = json!([]);
+    assert_eq!(aggregate.insert(&older).unwrap(), "hpc-east");
+    assert!(aggregate.to_string().contains("hpc-east: 1 cases"));
+    assert!(matches!(
+        aggregate.insert(&json!({"status": {}})),
+        Err(AggregateError::NoCluster)
+    ));
+    let mut stale = Aggregate::new().stale_after(Duration::minutes(10));
+    stale.insert(&older).unwrap();
+    assert!(stale.is_stale("hpc-east"));
+}
+
+#[test]
+fn core_count_comparison() {
+    let dir = TempDir::new("compare");
+    let path = dir.join("compare.toml");
+    std::fs::write(
+        &path,
+        r#"
+[[completed]]
+name = "zen30az045_OS2"
+log = "solve-336_101.out"
+steps = 100
+step_time = 20.0
+
+[[completed]]
+name = "zen30az090_OS2"
+log = "solve.out"
+cores = 336
+steps = 300
+step_time = 24.0
+
+[[completed]]
+name = "zen30az135_OS2"
+log = "solve.out"
+steps = 100
+step_time = 20.0
+"#,
+    )
+    .unwrap();
+    let records = TimedRun::read_records(&path);
+    // the core count of the last record is unknown
+    assert_eq!(records.len(), 2);
+    let source = StringSource::new(
+        "2024-06-10 14:00:00 TimeStep     1: Time 5.000000e-02\n\
+         2024-06-10 14:00:12 TimeStep     2: Time 1.000000e-01\n",
+    );
+    let mut running =
+        Case::new("zen30az180_OS2", 10, "solve-672_102.out").log_source(source.clone());
+    running.update().unwrap();
+    source.append("2024-06-10 14:00:24 TimeStep     3: Time 1.500000e-01\n");
+    running.update().unwrap();
+    let run = TimedRun::from_case(&running).unwrap();
+    assert_eq!((run.cores, run.steps), (672, 1));
+    let comparison = ThroughputComparison::new(records.into_iter().chain([run]));
+    let [a, b] = comparison.groups.as_slice() else {
+        panic!("expected 2 groups, got {:?}", comparison.groups);
+    };
+    assert_eq!((a.cores, a.cases, a.steps), (336, 2, 400));
+    assert!((a.secs_per_step - 23.).abs() < 1e-9);
+    assert_eq!(b.cores, 672);
+    assert!((b.speedup - 23. / 12.).abs() < 1e-9);
+    assert!((b.efficiency - 23. / 24.).abs() < 1e-9);
+    assert!(comparison.to_string().contains("96%"));
+    assert!(TimedRun::from_case(&Case::new("pending", 10, "solve-672_103.out")).is_none());
+}
+
+#[cfg(feature = "monitor")]
+#[test]
+fn case_detail() {
+    let residuals = ResidualTrend::from_lines(RUNNING.lines());
+    let latest: Vec<_> = residuals.latest().collect();
+    assert_eq!(latest.len(), 5);
+    assert_eq!(latest[0], ("Continuity", 5e-4));
+    assert_eq!(latest[4], ("Energy", 5e-6));
+    assert!(ResidualTrend::from_lines(COMPLETED.lines().take(3)).is_empty());
+    let histogram = StepTimeHistogram::new([1., 1., 2., 3.]);
+    assert_eq!(histogram.bins.len(), 8);
+    assert_eq!(histogram.bins.iter().map(|(_, _, n)| n).sum::<usize>(), 4);
+    assert_eq!(histogram.bins[0].2, 2);
+    assert_eq!(histogram.bins[7].2, 1);
+    assert_eq!(StepTimeHistogram::new([2., 2.]).bins, [(2., 2., 2)]);
+
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone());
+    case.update().unwrap();
+    source.append("TimeStep   121: Time 6.050000e+00\n");
+    case.update_over(10.).unwrap();
+    let mut events = EventHistory::new();
+    events.push(SessionEntry::Event {
+        at: Local::now(),
+        event: "stalled".to_string(),
+        case: case.name().to_string(),
+        detail: None,
+    });
+    let detail = CaseDetail::new(&case, events.case(case.name()))
+        .lines(3)
+        .to_string();
+    assert!(detail.contains("Continuity"), "{detail}");
+    assert!(detail.contains("Time step durations"), "{detail}");
+    assert!(detail.contains(" stalled"), "{detail}");
+    assert!(detail.contains("Log, last 3 lines"), "{detail}");
+
+    let snapshot = StatusSnapshot::new(std::slice::from_ref(&case), &[], 180);
+    let mut tabs = Tabs::new(&Config::default());
+    assert!(!tabs.handle_key('\n', Some(&snapshot)));
+    assert!(tabs.handle_key('j', Some(&snapshot)));
+    assert!(tabs
+        .render(&snapshot)
+        .starts_with("selected: zen30az045_OS2"));
+    assert!(tabs.handle_key('\n', Some(&snapshot)));
+    assert_eq!(tabs.detail(), Some("zen30az045_OS2"));
+    assert!(tabs.handle_key('\x1b', Some(&snapshot)));
+    assert_eq!(tabs.detail(), None);
+}
+
+#[test]
+fn operator_notes() {
+    let dir = TempDir::new("notes");
+    let path = dir.join("notes.toml");
+    let at = Local.with_ymd_and_hms(2024, 3, 14, 9, 30, 0).unwrap();
+    let mut notes = Notes::default();
+    notes.set("zen30az045_OS2", " restarted from ckpt 42000 on 3/14 ", at);
+    notes.set("zen30az090_OS2", "waiting on license", at);
+    notes.set("zen30az090_OS2", "", at);
+    notes.write(&path).unwrap();
+    let notes = Notes::read(&path);
+    assert_eq!(notes.iter().count(), 1);
+    let note = notes.get("zen30az045_OS2").unwrap();
+    assert_eq!(note.text, "restarted from ckpt 42000 on 3/14");
+    assert_eq!(note.at, at);
+    assert_eq!(
+        note.to_string(),
+        "restarted from ckpt 42000 on 3/14 (2024-03-14 09:30)"
+    );
+    let config = Config::from_toml(&format!(
+        r#"
+        notes = {:?}
+        columns = ["name", "note"]
+
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 1200
+        log = "solve-672_14.out"
+
+        [[cases]]
+        name = "zen30az090_OS2"
+        duration = 1200
+        log = "solve-672_16.out"
+        "#,
+        path.display().to_string()
+    ))
+    .unwrap();
+    let cases = config.cases();
+    assert_eq!(cases[0].note(), Some(note));
+    assert_eq!(cases[1].note(), None);
+    // the notes are truncated in their column but exported in full
+    let snapshot = StatusSnapshot::new(&cases, &[], 60).with_columns(config.column_spec());
+    let cell = Column::Note.cell(&cases[0], Default::default(), Local::now());
+    assert_eq!(cell, "restarted from ckpt 42000 on 3…");
+    assert_eq!(cell.chars().count(), 31);
+    assert!(snapshot
+        .to_string()
+        .contains(&format!("zen30az045_OS2       {cell}")));
+    let json = serde_json::to_value(&snapshot).unwrap();
+    assert_eq!(
+        json["cases"][0]["note"]["text"],
+        "restarted from ckpt 42000 on 3/14"
+    );
+    assert!(json["cases"][1].get("note").is_none());
+    // the board edits the note of the case selected
+    let mut tabs = Tabs::new(&config);
+    assert!(!tabs.handle_key('n', Some(&snapshot)));
+    assert!(tabs.handle_key('j', Some(&snapshot)));
+    assert!(tabs.handle_key('n', Some(&snapshot)));
+    assert!(tabs
+        .render(&snapshot)
+        .starts_with("note of zen30az045_OS2: restarted from ckpt 42000 on 3/14_"));
+    for _ in 0.."on 3/14".len() {
+        assert!(tabs.handle_key('\x7f', Some(&snapshot)));
+    }
+    for key in "yesterday\n".chars() {
+        assert!(tabs.handle_key(key, Some(&snapshot)));
+    }
+    assert_eq!(
+        tabs.take_note(),
+        Some((
+            "zen30az045_OS2".to_string(),
+            "restarted from ckpt 42000 yesterday".to_string()
+        ))
+    );
+    assert_eq!(tabs.take_note(), None);
+    // the keys are not taken by the editor once the note is saved
+    assert!(tabs.handle_key('\n', Some(&snapshot)));
+    assert_eq!(tabs.detail(), Some("zen30az045_OS2"));
+}
+
+#[test]
+fn campaign_summary() {
+    let at = |hour: u32| Local.with_ymd_and_hms(2024, 3, 4, hour, 0, 0).unwrap();
+    let archived =
+        |name: &str, campaign: &str, cores: usize, hours: (u32, u32), steps: usize| ArchivedCase {
+            name: name.to_string(),
+            campaign: Some(campaign.to_string()),
+            cores: Some(cores),
+            total_step: steps,
+            first_seen: at(hours.0),
+            completed: at(hours.1),
+            steps,
+            stalls: 0,
+        };
+    let cases = [
+        archived("zen30az090_OS2", "OS", 336, (10, 18), 4000),
+        archived("zen30az045_OS2", "OS", 672, (8, 12), 8000),
+        archived("zen30az135_OS2", "OS", 672, (12, 14), 2000),
+        archived("zen30az045_CD12", "CD", 672, (0, 2), 2000),
+    ];
+    let stalls = [
+        StallIncident {
+            case: "zen30az090_OS2".to_string(),
+            at: at(15),
+        },
+        StallIncident {
+            case: "zen30az045_CD12".to_string(),
+            at: at(1),
+        },
+    ];
+    let summary = CampaignSummary::new(Some("OS"), cases, stalls);
+    let names: Vec<_> = summary
+        .cases()
+        .iter()
+        .map(|case| case.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["zen30az045_OS2", "zen30az090_OS2", "zen30az135_OS2"]
+    );
+    assert_eq!(summary.stalls().len(), 1);
+    assert_eq!(summary.wall_clock_secs(), 10 * 3600);
+    let throughput: Vec<_> = summary
+        .throughput()
+        .into_iter()
+        .map(|configuration| {
+            (
+                configuration.cores,
+                configuration.cases,
+                configuration.steps_per_hour,
+            )
+        })
+        .collect();
+    assert_eq!(
+        throughput,
+        [
+            (Some(336), 1, Some(500.)),
+            (Some(672), 2, Some(10000. / 6.))
+        ]
+    );
+    let markdown = summary.to_markdown();
+    assert!(markdown.starts_with("# Campaign OS summary\n\n- Completed cases: 3\n"));
+    assert!(markdown.contains("- Wall-clock: 10:00 (2024-03-04 08:00 to 2024-03-04 18:00)\n"));
+    assert!(markdown.contains("| 672 | 2 | 1667 |\n"));
+    assert!(markdown.contains("| zen30az090_OS2 | 2024-03-04 15:00 |\n"));
+    let bar = format!("zen30az090_OS2  |{}{}|", " ".repeat(8), "█".repeat(32));
+    assert!(markdown.contains(&bar));
+    let html = summary.to_html();
+    assert!(html.contains("<h1>Campaign OS summary</h1>"));
+    assert!(html.contains("<tr><td>672</td><td>2</td><td>1667</td></tr>"));
+    assert!(html.contains("margin-left: 20.0%; width: 80.0%"));
+    let all = CampaignSummary::new(None, summary.cases().to_vec(), []);
+    assert!(all.to_markdown().starts_with("# Campaign summary\n"));
+    assert!(!all.to_markdown().contains("## Stall incidents"));
+}
+
+#[test]
+fn resource_utilization() {
+    let utilization = Utilization::parse("cpu=85.2 gpu=40%\n").unwrap();
+    assert_eq!((utilization.cpu, utilization.gpu), (Some(85.2), Some(40.)));
+    assert_eq!(utilization.lowest(), Some(40.));
+    assert_eq!(utilization.to_string(), "85%/40%");
+    assert_eq!(Utilization::parse("97").unwrap().to_string(), "97%");
+    assert_eq!(Utilization::parse("n/a"), None);
+    let usage =
+        JobUsage::parse("cpu=1-00:00:10,mem=12G,gres/gpuutil=80\ncpu=10:05.500,gres/gpuutil=60\n")
+            .unwrap();
+    assert_eq!(usage.cpu_secs, 86400. + 10. + 605.5);
+    assert_eq!(usage.gpu, Some(70.));
+    assert_eq!(JobUsage::parse(""), None);
+
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    let dir = TempDir::new("samples");
+    let samples = dir.join("samples");
+    let command = format!(
+        "echo $CFD_CASE >> {0}; echo \"cpu=$(wc -l < {0}) gpu=12\"",
+        samples.display()
+    );
+    let mut case = case(StringSource::new(RUNNING))
+        .clock(Arc::new(clock.clone()))
+        .resources(ResourceCollector::Command(command));
+    case.update().unwrap();
+    assert_eq!(case.utilization(), None);
+    case.sample_resources();
+    assert_eq!(case.utilization().unwrap().to_string(), "1%/12%");
+    clock.advance(std::time::Duration::from_secs(30));
+    case.sample_resources();
+    assert_eq!(case.utilization().unwrap().cpu, Some(1.));
+    clock.advance(std::time::Duration::from_secs(30));
+    case.sample_resources();
+    assert_eq!(case.utilization().unwrap().cpu, Some(2.));
+    assert_eq!(
+        std::fs::read_to_string(&samples).unwrap(),
+        "zen30az045_OS2\nzen30az045_OS2\n"
+    );
+    let columns = ColumnSpec::new(
+        vec![Column::Name, "utilization".parse().unwrap()],
+        "utilization".parse().unwrap(),
+    );
+    assert!(columns
+        .row(&case, Default::default())
+        .to_string()
+        .ends_with("     2%/12%"));
+}
+
+#[test]
+fn sim_time_alignment() {
+    let mut timeline = SimTimeline::new();
+    timeline.push(2., 100.).push(4., 300.);
+    assert_eq!(timeline.elapsed_at(1.), Some(50.));
+    assert_eq!(timeline.elapsed_at(3.), Some(200.));
+    assert_eq!(timeline.elapsed_at(5.), None);
+
+    let clock = MockClock::new(Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap());
+    let (fast_log, slow_log) = (StringSource::new(RUNNING), StringSource::new(RUNNING));
+    let mut fast = Case::new("zen30az045_OS2", 10, "solve.out")
+        .log_source(fast_log.clone())
+        .clock(Arc::new(clock.clone()));
+    let mut slow = Case::new("zen30az090_OS2", 10, "solve.out")
+        .log_source(slow_log.clone())
+        .clock(Arc::new(clock.clone()));
+    fast.update().unwrap();
+    slow.update().unwrap();
+    for i in 1..=4 {
+        clock.advance(std::time::Duration::from_secs(600));
+        for (source, step) in [(&fast_log, 120 + 2 * i), (&slow_log, 120 + i)] {
+            source.append(&format!(
+                "TimeStep {step}: Time {:.6e}\n",
+                step as f64 * 0.05
+            ));
+        }
+        fast.update_over(600.).unwrap();
+        slow.update_over(600.).unwrap();
+    }
+    let alignment = SimTimeAlignment::new([&fast, &slow]);
+    assert_eq!(alignment.time, Some(slow.time()));
+    let standings: Vec<_> = alignment
+        .cases
+        .iter()
+        .map(|case| (case.name.as_str(), case.standing(alignment.median)))
+        .collect();
+    assert_eq!(
+        standings,
+        [("zen30az045_OS2", "ahead"), ("zen30az090_OS2", "behind")]
+    );
+    let table = alignment.to_string();
+    assert!(table.starts_with("Aligned at 6.20 simulated s"), "{table}");
+}
+
+#[test]
+fn narrow_table() {
+    let mut case = Case::new("zen30az045_OS2_restart_from_checkpoint", 10, "solve.out")
+        .log_source(StringSource::new(RUNNING));
+    case.update().unwrap();
+    let names = [case.name()];
+    let columns = ColumnSpec::default().with_width(Some(60), false);
+    let fitted = columns.fit(names, Default::default(), false);
+    assert_eq!(fitted.name_width, 12);
+    assert!(fitted.wrapped.is_empty());
+    assert!(fitted.columns.len() < columns.columns.len());
+    let row = fitted.row(&case, Default::default()).to_string();
+    assert!(row.starts_with("zen30az045_…"), "{row}");
+    assert!(fitted.header(Default::default()).chars().count() <= 60);
+    let wrapped = columns
+        .clone()
+        .with_width(Some(60), true)
+        .fit(names, Default::default(), false);
+    assert_eq!(wrapped.columns, fitted.columns);
+    assert!(!wrapped.wrapped.is_empty());
+    let header = wrapped.header(Default::default());
+    assert_eq!(header.lines().count(), 2);
+    assert!(
+        header.lines().all(|line| line.chars().count() <= 60),
+        "{header}"
+    );
+    let row = wrapped.row(&case, Default::default()).to_string();
+    assert_eq!(row.lines().count(), 2);
+    assert!(row.lines().nth(1).unwrap().starts_with(&" ".repeat(12)));
+    let wide = columns
+        .with_width(Some(400), false)
+        .fit(names, Default::default(), false);
+    assert_eq!(wide.name_width, case.name().len() + 1);
+    assert_eq!(wide.columns, ColumnSpec::default().columns);
+}
+
+#[test]
+fn progress_plots() {
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    let source = StringSource::new(RUNNING);
+    let mut running = case(source.clone()).clock(Arc::new(clock.clone()));
+    running.update_over(30.).unwrap();
+    let dir = TempDir::new("plots");
+    let mut plots = ProgressPlots::new(&dir);
+    plots.record(&StatusSnapshot::new(std::slice::from_ref(&running), &[], 60).taken_at(start));
+    let steps: String = (121..131)
+        .map(|step| format!("TimeStep   {step}: Time {:.6e}\n", step as f64 * 0.05))
+        .collect();
+    source.append(&steps);
+    clock.advance(std::time::Duration::from_secs(1800));
+    running.update_over(1800.).unwrap();
+    plots.record(
+        &StatusSnapshot::new(std::slice::from_ref(&running), &[], 60)
+            .taken_at(start + Duration::seconds(1800)),
+    );
+    assert_eq!(plots.cases().collect::<Vec<_>>(), ["zen30az045_OS2"]);
+    assert_eq!(plots.progress("zen30az045_OS2"), [(0., 60.), (0.5, 65.)]);
+    assert_eq!(
+        plots.step_times("zen30az045_OS2").last(),
+        Some(&(130., 180.))
+    );
+    let drift = plots.eta_drift("zen30az045_OS2");
+    assert_eq!(drift.len(), 2);
+    assert_eq!(drift[0], (0., 0.));
+    // the ETA slips at 180 s per time step
+    assert!(drift[1].1 > 0., "{drift:?}");
+    assert_eq!(plots.path(None), dir.join("campaign.svg"));
+    #[cfg(feature = "plot")]
+    {
+        let paths = plots.write().unwrap();
+        assert_eq!(
+            paths,
+            [dir.join("zen30az045_OS2.svg"), dir.join("campaign.svg")]
+        );
+        for path in paths {
+            assert!(std::fs::read_to_string(path).unwrap().starts_with("<svg"));
+        }
+    }
+}
+
+#[test]
+fn alert_hysteresis() {
+    let source = StringSource::new(RUNNING);
+    let mut case = case(source.clone()).error_patterns(ErrorPattern::defaults());
+    case.update().unwrap();
+    let mut alerts = Alerts::new();
+    alerts.update(std::slice::from_ref(&case));
+    assert!(!alerts.acknowledge(case.name()));
+    source.append("Floating point exception (core dumped)\n");
+    case.update().unwrap();
+    alerts.update(std::slice::from_ref(&case));
+    assert!(alerts.acknowledge(case.name()));
+    alerts.update(std::slice::from_ref(&case));
+    assert!(alerts.sorted()[0].acknowledged);
+    // the acknowledgement holds until the state of the case changes
+    case.set_paused(true);
+    alerts.update(std::slice::from_ref(&case));
+    assert!(!alerts.sorted()[0].acknowledged);
+
+    let mut notifier = Notifier::new(NotifyConfig {
+        alerts: Some(Severity::Warning),
+        remind_after: Some(3600),
+        ..Default::default()
+    });
+    let t0 = Local::now();
+    let at = |secs| t0 + Duration::seconds(secs);
+    let mut alert = alerts.sorted().remove(0);
+    alert.since = t0;
+    let raised = std::slice::from_ref(&alert);
+    assert_eq!(notifier.alerts_at(raised, t0).len(), 1);
+    assert!(notifier.alerts_at(raised, at(180)).is_empty());
+    let reminder = notifier.alerts_at(raised, at(3600)).remove(0);
+    assert!(
+        reminder.message.ends_with("still active after 1:00"),
+        "{}",
+        reminder.message
+    );
+    // an alert cleared for a moment is not notified again
+    assert!(notifier.alerts_at(&[], at(3700)).is_empty());
+    assert!(notifier.alerts_at(raised, at(3800)).is_empty());
+    assert!(notifier.alerts_at(&[], at(3900)).is_empty());
+    assert!(notifier.alerts_at(&[], at(4200)).is_empty());
+    assert_eq!(notifier.alerts_at(raised, at(4300)).len(), 1);
+    // nor reminded once acknowledged
+    alert.acknowledged = true;
+    let acknowledged = std::slice::from_ref(&alert);
+    assert!(notifier.alerts_at(acknowledged, at(8000)).is_empty());
+    assert!(notifier.alerts_at(acknowledged, at(12000)).is_empty());
+}
@@ -0,0 +1,399 @@
+//! Tests of the log sources, of the moved, rotated and replaced logs and of the log inspection
+
+mod common;
+
+use std::{io::Write, sync::Arc};
+
+use cfd_status::{
+    Case, CaseError, Config, FileSource, Liveness, LogInspector, LogSource, MockClock, PipeSource,
+    ReadOptions, ReadThrottle, RemoteSource, StringSource,
+};
+#[cfg(feature = "monitor")]
+use cfd_status::{LogWatcher, Monitor, MonitorEvent};
+use chrono::{Local, TimeZone};
+
+use common::{case, wait_until, TempDir, RUNNING};
+
+#[test]
+fn recent_lines_shared_by_clones() {
+    let mut case = case(StringSource::new(RUNNING));
+    case.update().unwrap();
+    let clone = case.clone();
+    assert_eq!(
+        case.recent_lines().last().map(str::as_ptr),
+        clone.recent_lines().last().map(str::as_ptr)
+    );
+}
+
+#[test]
+fn throttled_reads() {
+    let throttle = Arc::new(ReadThrottle::new(Some(1), Some(20.)));
+    let start = std::time::Instant::now();
+    for _ in 0..3 {
+        let _permit = throttle.acquire();
+        assert_eq!(throttle.active(), 1);
+    }
+    assert!(start.elapsed() >= std::time::Duration::from_millis(100));
+    assert_eq!(throttle.active(), 0);
+    let dir = TempDir::new("throttle");
+    let path = dir.join("throttle.out");
+    std::fs::write(&path, RUNNING).unwrap();
+    let options = ReadOptions {
+        throttle: Some(throttle),
+        chunk_size: Some(64),
+    };
+    let mut source = FileSource::with_options(&path, options);
+    assert_eq!(source.read_new().unwrap().len(), RUNNING.lines().count());
+    // an unchanged log is not read again
+    assert!(source.read_new().unwrap().is_empty());
+    let line = source.last_matching("TimeStep").unwrap();
+    assert!(line
+        .as_deref()
+        .is_some_and(|line| line.contains("TimeStep   120:")));
+    assert_eq!(source.last_matching("TimeStep").unwrap(), line);
+    std::fs::write(
+        &path,
+        format!("{RUNNING}TimeStep   121: Time 6.050000e+00\n"),
+    )
+    .unwrap();
+    assert_eq!(source.read_new().unwrap().len(), 1);
+    let line = source.last_matching("TimeStep").unwrap();
+    assert!(line.is_some_and(|line| line.contains("TimeStep   121:")));
+}
+
+#[test]
+fn log_inspector() {
+    let log = "2024-06-10 14:00:00 TimeStep     1: Time 5.000000e-02\n\
+               2024-06-10 14:00:10 TimeStep     2: Time 1.000000e-01\n\
+               2024-06-10 14:00:20 TimeStep     3: Time 1.500000e-01\n\
+               2024-06-10 14:00:30 TimeStep     4: Ti";
+    let mut inspector = LogInspector::new("zen30az045_OS2", 10., false);
+    // the whole log is read at once, the step times come from its timestamps
+    inspector.update(log, 0.);
+    assert_eq!(inspector.case().step(), Some(3));
+    assert_eq!(inspector.step_times(), vec![10., 10.]);
+    assert_eq!(inspector.eta_secs(), 1970);
+    // the log was replaced by a shorter one
+    inspector.update("TimeStep     1: Time 5.000000e-02\n", 30.);
+    assert_eq!(inspector.case().step(), Some(1));
+    // the read offset falls within a character of another log
+    let replaced = format!("{}\nTimeStep     2: Time 1.000000e-01\n", "é".repeat(20));
+    inspector.update(&replaced, 30.);
+    assert_eq!(inspector.case().step(), Some(2));
+    assert!(inspector.step_times().is_empty());
+}
+
+#[test]
+fn remote_tail() {
+    let dir = TempDir::new("remote");
+    std::fs::create_dir_all(dir.join("zen30az045_OS2")).unwrap();
+    // stands for ssh, running the remote script locally
+    let ssh = dir.join("ssh");
+    std::fs::write(&ssh, "shift 2\nexec sh -c \"$1\"\n").unwrap();
+    let ssh = format!("sh {}", ssh.display());
+    let log = dir.join("zen30az045_OS2/solve.out");
+    let mut source = RemoteSource::new("login01", log.to_str().unwrap()).ssh(&ssh);
+    assert!(source.read_new().unwrap().is_empty());
+    assert!(!source.exists());
+    assert!(source.is_missing());
+    std::fs::write(&log, "a\n".repeat(100) + "b\npart").unwrap();
+    assert_eq!(source.read_new().unwrap().len(), 101);
+    assert!(source.exists());
+    // only the bytes from the partial line on are fetched again
+    let fetched = source.fetched();
+    let mut file = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+    file.write_all(b"ial\nc\n").unwrap();
+    assert_eq!(source.read_new().unwrap(), ["partial", "c"]);
+    assert!(source.fetched() - fetched < std::fs::metadata(&log).unwrap().len());
+    assert!(source.read_new().unwrap().is_empty());
+    assert_eq!(source.last_matching("b").unwrap().as_deref(), Some("b"));
+    assert_eq!(source.last_matching("it's").unwrap(), None);
+    // a shrunk log is read again from its beginning
+    std::fs::write(&log, "x\n").unwrap();
+    assert_eq!(source.read_new().unwrap(), ["x"]);
+    assert!(source.relocations().is_empty());
+    // and so is a rotated one
+    let rotated = dir.join("zen30az045_OS2/solve.out.new");
+    std::fs::write(&rotated, "TimeStep     1: Time 5.000000e-02\nb\n").unwrap();
+    std::fs::rename(&rotated, &log).unwrap();
+    assert_eq!(
+        source.read_new().unwrap(),
+        ["TimeStep     1: Time 5.000000e-02", "b"]
+    );
+    let relocations = source.relocations();
+    assert_eq!(relocations.len(), 1);
+    assert!(relocations[0].replaced);
+    // the cases with a log host read their log through ssh
+    std::fs::write(&log, RUNNING).unwrap();
+    let config = Config::from_toml(&format!(
+        r#"
+        root = "{}"
+        ssh = "{ssh}"
+
+        [[cases]]
+        name = "zen30az045_OS2"
+        duration = 10
+        log = "solve.out"
+        log_host = "login01"
+        "#,
+        dir.display()
+    ))
+    .unwrap();
+    let start = Local.with_ymd_and_hms(2024, 6, 14, 18, 0, 0).unwrap();
+    let mut case = config
+        .case(&config.cases[0])
+        .clock(Arc::new(MockClock::new(start)));
+    case.update().unwrap();
+    assert_eq!(case.step(), Some(120));
+    // the replacements of the log are timed by the clock of the case
+    std::fs::write(&rotated, RUNNING).unwrap();
+    std::fs::rename(&rotated, &log).unwrap();
+    case.update().unwrap();
+    assert_eq!(case.log_relocations()[0].at, start);
+    // an unreachable host is an error, not a missing log
+    let mut source = RemoteSource::new("login01", log.to_str().unwrap()).ssh("exit 255;");
+    assert!(source.read_new().is_err());
+    assert!(!source.exists());
+    assert!(!source.is_missing());
+    let mut case = Case::new("remote", 10, "solve.out").log_source(source);
+    assert!(matches!(case.update(), Err(CaseError::Grep)));
+    assert!(!case.is_waiting());
+}
+
+#[test]
+fn pipe_source() {
+    let (reader, mut writer) = std::io::pipe().unwrap();
+    let source = PipeSource::new(std::io::BufReader::new(reader));
+    let mut case = Case::new("stdin", 10, "-").log_source(source.clone());
+    assert!(!source.exists());
+    writer.write_all(RUNNING.as_bytes()).unwrap();
+    // the lines are read on a background thread
+    wait_until("the piped lines", || {
+        case.update().map(drop).ok();
+        case.step() == Some(120)
+    });
+    assert!(source.exists());
+    writer
+        .write_all(b"TimeStep   121: Time 6.050000e+00\n")
+        .unwrap();
+    drop(writer);
+    wait_until("the end of the pipe", || source.is_closed());
+    case.update_over(30.).unwrap();
+    assert_eq!(case.step(), Some(121));
+    assert_eq!(case.step_times().last(), Some(30.));
+}
+
+#[cfg(feature = "monitor")]
+#[cfg(unix)]
+#[test]
+fn moved_log() {
+    use std::os::unix::fs::symlink;
+    let root = TempDir::new("moved");
+    let (run_a, run_b) = (root.join("run-a"), root.join("run-b"));
+    std::fs::create_dir_all(&run_a).unwrap();
+    std::fs::write(run_a.join("solve.out"), RUNNING).unwrap();
+    let link = root.join("zen30az045_OS2");
+    symlink(&run_a, &link).unwrap();
+    let case = Case::new("zen30az045_OS2", 10, "solve.out").root_dir(&root);
+    let mut monitor = Monitor::new(vec![case], std::time::Duration::from_millis(10));
+    let moved = |events: &[MonitorEvent]| {
+        events.iter().find_map(|event| match event {
+            MonitorEvent::LogMoved(case) => case.log_relocations().last().cloned(),
+            _ => None,
+        })
+    };
+    assert!(moved(&monitor.poll()).is_none());
+    // the case directory is moved and re-linked, the log is read on from where it was
+    std::fs::rename(&run_a, &run_b).unwrap();
+    std::fs::remove_file(&link).unwrap();
+    symlink(&run_b, &link).unwrap();
+    let mut log = std::fs::OpenOptions::new()
+        .append(true)
+        .open(run_b.join("solve.out"))
+        .unwrap();
+    std::io::Write::write_all(&mut log, b"TimeStep   121: Time 6.050000e+00\n").unwrap();
+    let events = monitor.poll();
+    let relocation = moved(&events).expect("no log moved event");
+    assert!(!relocation.replaced);
+    assert!(relocation.to.ends_with("run-b/solve.out"));
+    assert_eq!(monitor.running()[0].step(), Some(121));
+    // a different file replacing the log is read from its beginning
+    std::fs::write(
+        root.join("solve.tmp"),
+        "TimeStep     1: Time 5.000000e-02\n",
+    )
+    .unwrap();
+    std::fs::rename(root.join("solve.tmp"), run_b.join("solve.out")).unwrap();
+    let relocation = moved(&monitor.poll()).expect("no log replaced event");
+    assert!(relocation.replaced);
+    assert!(relocation.to_string().starts_with("log replaced"));
+    assert_eq!(monitor.running()[0].step(), Some(1));
+    assert!(moved(&monitor.poll()).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn rotated_log() {
+    let dir = TempDir::new("rotated");
+    let log = dir.join("solve.out");
+    std::fs::write(&log, "TimeStep     1: Time 5.000000e-02\n").unwrap();
+    let mut source = FileSource::new(&log);
+    assert_eq!(source.read_new().unwrap().len(), 1);
+    // the lines written before the rotation are read before the new log
+    let mut file = std::fs::OpenOptions::new().append(true).open(&log).unwrap();
+    file.write_all(b"TimeStep     2: Time 1.000000e-01\n")
+        .unwrap();
+    std::fs::rename(&log, dir.join("solve.out.1")).unwrap();
+    std::fs::write(&log, "TimeStep     3: Time 1.500000e-01\n").unwrap();
+    assert_eq!(
+        source.read_new().unwrap(),
+        [
+            "TimeStep     2: Time 1.000000e-01",
+            "TimeStep     3: Time 1.500000e-01"
+        ]
+    );
+    // the compressed versions are decompressed once
+    let gz = dir.join("solve.out.2.gz");
+    let mut encoder =
+        flate2::write::GzEncoder::new(std::fs::File::create(&gz).unwrap(), Default::default());
+    encoder.write_all(b"Courant number max 0.4\n").unwrap();
+    encoder.finish().unwrap();
+    assert_eq!(
+        source.last_matching("Courant").unwrap().as_deref(),
+        Some("Courant number max 0.4")
+    );
+    let modified = std::fs::metadata(&gz).unwrap().modified().unwrap();
+    let size = std::fs::metadata(&gz).unwrap().len() as usize;
+    std::fs::write(&gz, vec![0; size]).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&gz)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    std::fs::write(&log, "TimeStep     4: Time 2.000000e-01\n").unwrap();
+    assert_eq!(
+        source.last_matching("Courant").unwrap().as_deref(),
+        Some("Courant number max 0.4")
+    );
+}
+
+#[cfg(feature = "monitor")]
+#[test]
+fn latest_log() {
+    let root = TempDir::new("latest");
+    let dir = root.join("zen30az045_OS2");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("solve-672_14.out"), RUNNING).unwrap();
+    let log = std::fs::File::options()
+        .append(true)
+        .open(dir.join("solve-672_14.out"))
+        .unwrap();
+    log.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(60))
+        .unwrap();
+    let case = Case::new("zen30az045_OS2", 10, "solve-672_*.out")
+        .root_dir(&root)
+        .follow_latest();
+    let mut monitor = Monitor::new(vec![case], std::time::Duration::from_millis(10));
+    let switched = |events: &[MonitorEvent]| {
+        events.iter().find_map(|event| match event {
+            MonitorEvent::LogMoved(case) => case.log_relocations().last().cloned(),
+            _ => None,
+        })
+    };
+    assert!(switched(&monitor.poll()).is_none());
+    assert_eq!(monitor.running()[0].step(), Some(120));
+    // the solver restarts from a checkpoint into a new log, only the newest log is read
+    std::fs::write(
+        dir.join("solve-672_22.out"),
+        "TimeStep   101: Time 5.050000e+00\n",
+    )
+    .unwrap();
+    let relocation = switched(&monitor.poll()).expect("no log switched event");
+    assert!(relocation.switched && relocation.replaced);
+    assert!(relocation.from.ends_with("solve-672_14.out"));
+    assert!(relocation.to.ends_with("solve-672_22.out"));
+    assert!(relocation.to_string().starts_with("log switched: "));
+    assert_eq!(monitor.running()[0].step(), Some(101));
+    assert!(switched(&monitor.poll()).is_none());
+}
+
+#[test]
+fn restart_segments_spread() {
+    let root = TempDir::new("segments");
+    let dir = root.join("zen30az045_OS2");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("solve-672_14.out"), RUNNING).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    // the solver restarts from a checkpoint into a new segment, the older one is left as is
+    std::fs::write(
+        dir.join("solve-672_15.out"),
+        "TimeStep   101: Time 5.050000e+00\n",
+    )
+    .unwrap();
+    let mut case = Case::new("zen30az045_OS2", 10, "solve-672_*.out").root_dir(&root);
+    case.update().unwrap();
+    assert_eq!(case.log_spread(), None);
+    // both logs written after the last one was created disagree
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(dir.join("solve-672_14.out"), RUNNING).unwrap();
+    case.update().unwrap();
+    assert_eq!(case.log_spread(), Some(19));
+}
+
+#[cfg(feature = "monitor")]
+#[test]
+fn glob_watcher() {
+    let root = TempDir::new("watch");
+    let glob = root.join("solve-672_*.out");
+    let watcher = LogWatcher::new([&glob], std::time::Duration::from_millis(10)).unwrap();
+    std::fs::write(root.join("solve-672_15.out"), RUNNING).unwrap();
+    std::fs::write(root.join("other.out"), RUNNING).unwrap();
+    let modified = watcher
+        .wait(std::time::Duration::from_secs(5))
+        .expect("no log modified");
+    assert_eq!(modified, std::collections::HashSet::from([glob]));
+}
+
+#[test]
+fn backward_search() {
+    let dir = TempDir::new("search");
+    let path = dir.join("search.out");
+    // the last time step line straddles the first 64 KiB block read from the end
+    let filler = format!(
+        "{}{}\n",
+        format!("{}\n", "x".repeat(99)).repeat(655),
+        "y".repeat(25)
+    );
+    assert_eq!(filler.len(), 65526);
+    let log = format!("{RUNNING}TimeStep   121: Time 6.050000e+00\n{filler}");
+    std::fs::write(&path, &log).unwrap();
+    let mut source = FileSource::new(&path);
+    assert_eq!(
+        source.last_matching("TimeStep").unwrap().as_deref(),
+        Some("TimeStep   121: Time 6.050000e+00")
+    );
+    assert_eq!(
+        source.last_matching("yyy").unwrap().as_deref(),
+        Some("y".repeat(25).as_str())
+    );
+    // the first line of the file is complete
+    assert!(source
+        .last_matching(RUNNING.lines().next().unwrap())
+        .unwrap()
+        .is_some());
+    assert_eq!(source.last_matching("no such line").unwrap(), None);
+    std::fs::remove_file(&path).unwrap();
+    assert!(source.last_matching("TimeStep").is_err());
+}
+
+#[test]
+fn pid_liveness() {
+    let dir = TempDir::new("liveness");
+    let liveness = Liveness::PidFile("solver.pid".into());
+    assert_eq!(liveness.check("zen30az045_OS2", &dir, None), None);
+    std::fs::write(dir.join("solver.pid"), format!("{}\n", std::process::id())).unwrap();
+    assert_eq!(liveness.check("zen30az045_OS2", &dir, None), Some(true));
+    std::fs::write(dir.join("solver.pid"), i32::MAX.to_string()).unwrap();
+    assert_eq!(liveness.check("zen30az045_OS2", &dir, None), Some(false));
+}